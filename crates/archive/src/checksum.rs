@@ -0,0 +1,51 @@
+//! Provides a reader that computes the SHA-256 digest of an archive while it is read, so that
+//! downloads can be verified without a separate pass over the data.
+
+use std::io::{self, Read};
+
+use super::ArchiveError;
+use sha2::{Digest, Sha256};
+
+pub(crate) struct Sha256Read<R> {
+    source: R,
+    hasher: Sha256,
+}
+
+impl<R: Read> Read for Sha256Read<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.source.read(buf)?;
+        self.hasher.update(&buf[..len]);
+        Ok(len)
+    }
+}
+
+impl<R: Read> Sha256Read<R> {
+    pub(crate) fn new(source: R) -> Self {
+        Sha256Read {
+            source,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Reads any data that is left, and returns the (hex-encoded) digest of all of the data
+    pub(crate) fn finish(mut self) -> Result<String, ArchiveError> {
+        io::copy(&mut self, &mut io::sink())?;
+        Ok(hex::encode(self.hasher.finalize()))
+    }
+
+    /// Reads any data that is left, and checks the digest of all of the data against the
+    /// expected (hex-encoded) digest, if there is one
+    pub(crate) fn verify(self, expected: Option<&str>) -> Result<String, ArchiveError> {
+        let actual = self.finish()?;
+
+        match expected {
+            Some(expected) if !actual.eq_ignore_ascii_case(expected) => {
+                Err(ArchiveError::ChecksumMismatch {
+                    expected: expected.to_string(),
+                    actual,
+                })
+            }
+            _ => Ok(actual),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+//! Provides the client that archives are downloaded with, which can be replaced so that
+//! downloads go through some other HTTP stack.
+
+use std::io::{self, Read};
+
+pub use attohttpc::header::{HeaderMap, HeaderName};
+pub use attohttpc::StatusCode;
+
+/// The response to a request, with its body still to be read.
+pub struct HttpResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Box<dyn Read + Send>,
+}
+
+/// Sends the requests for downloads.
+///
+/// Implementations only need to send a `GET` request with the given headers and return whatever
+/// the server responded with, including unsuccessful statuses, which the caller checks.
+pub trait HttpClient: Send + Sync {
+    fn get(&self, url: &str, headers: &[(HeaderName, String)]) -> io::Result<HttpResponse>;
+}
+
+/// The client that is used unless another one is provided, which sends requests with `attohttpc`.
+pub struct DefaultClient;
+
+impl HttpClient for DefaultClient {
+    fn get(&self, url: &str, headers: &[(HeaderName, String)]) -> io::Result<HttpResponse> {
+        let mut request = attohttpc::get(url);
+        for (name, value) in headers {
+            request = request.header(name.clone(), value.clone());
+        }
+
+        let (status, headers, body) = request
+            .send()
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?
+            .split();
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: Box::new(body),
+        })
+    }
+}
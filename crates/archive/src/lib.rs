@@ -1,13 +1,17 @@
 //! This crate provides types for fetching and unpacking compressed
 //! archives in tarball or zip format.
-use std::fs::File;
+use std::fs::{File, OpenOptions};
+use std::io;
 use std::path::Path;
 
 use thiserror::Error;
 
+mod checksum;
+mod http;
 mod tarball;
 mod zip;
 
+pub use crate::http::{DefaultClient, HeaderMap, HeaderName, HttpClient, HttpResponse, StatusCode};
 pub use crate::tarball::Tarball;
 pub use crate::zip::Zip;
 
@@ -31,6 +35,9 @@ pub enum ArchiveError {
 
     #[error("{0}")]
     ZipError(#[from] zip_rs::result::ZipError),
+
+    #[error("checksum mismatch (expected SHA-256 {expected}, found {actual})")]
+    ChecksumMismatch { expected: String, actual: String },
 }
 
 /// Metadata describing whether an archive comes from a local or remote origin.
@@ -40,18 +47,40 @@ pub enum Origin {
     Remote,
 }
 
+/// Creates the file that a download is written to, which only the current user can read
+///
+/// On Unix, a symlink in place of the file is refused rather than followed, so that a download
+/// can't be redirected to overwrite some other file.
+pub(crate) fn create_download_file(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600).custom_flags(libc::O_NOFOLLOW);
+    }
+
+    options.open(path)
+}
+
 pub trait Archive {
     fn compressed_size(&self) -> u64;
     fn uncompressed_size(&self) -> Option<u64>;
 
-    /// Unpacks the zip archive to the specified destination folder.
+    /// Unpacks the archive to the specified destination folder, returning the (hex-encoded)
+    /// SHA-256 digest of the archive.
     fn unpack(
         self: Box<Self>,
         dest: &Path,
         progress: &mut dyn FnMut(&(), usize),
-    ) -> Result<(), ArchiveError>;
+    ) -> Result<String, ArchiveError>;
 
     fn origin(&self) -> Origin;
+
+    /// Requires the archive to have the given (hex-encoded) SHA-256 digest, which is checked as
+    /// the archive is unpacked. Unpacking fails if the digest doesn't match.
+    fn expect_sha256(&mut self, digest: String);
 }
 
 cfg_if::cfg_if! {
@@ -65,12 +94,16 @@ cfg_if::cfg_if! {
         }
 
         /// Fetch a remote archive in the native OS-preferred format from the specified
-        /// URL and store its results at the specified file path.
+        /// URL with the given client, and store its results at the specified file path.
         ///
         /// On Windows, the preferred format is zip. On Unixes, the preferred format
         /// is tarball.
-        pub fn fetch_native(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-            Tarball::fetch(url, cache_file)
+        pub fn fetch_native(
+            client: &dyn HttpClient,
+            url: &str,
+            cache_file: &Path,
+        ) -> Result<Box<dyn Archive>, ArchiveError> {
+            Tarball::fetch(client, url, cache_file)
         }
     } else if #[cfg(windows)] {
         /// Load an archive in the native OS-preferred format from the specified file.
@@ -82,14 +115,40 @@ cfg_if::cfg_if! {
         }
 
         /// Fetch a remote archive in the native OS-preferred format from the specified
-        /// URL and store its results at the specified file path.
+        /// URL with the given client, and store its results at the specified file path.
         ///
         /// On Windows, the preferred format is zip. On Unixes, the preferred format
         /// is tarball.
-        pub fn fetch_native(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-            Zip::fetch(url, cache_file)
+        pub fn fetch_native(
+            client: &dyn HttpClient,
+            url: &str,
+            cache_file: &Path,
+        ) -> Result<Box<dyn Archive>, ArchiveError> {
+            Zip::fetch(client, url, cache_file)
         }
     } else {
         compile_error!("Unsupported OS (expected 'unix' or 'windows').");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::create_download_file;
+
+    #[cfg(unix)]
+    #[test]
+    fn download_file_is_private_and_not_a_symlink() {
+        use std::os::unix::fs::{symlink, PermissionsExt};
+
+        let dir = tempfile::tempdir().unwrap();
+        let download = dir.path().join("download");
+        let file = create_download_file(&download).unwrap();
+        assert_eq!(file.metadata().unwrap().permissions().mode() & 0o777, 0o600);
+
+        let target = dir.path().join("target");
+        let link = dir.path().join("link");
+        symlink(&target, &link).unwrap();
+        assert!(create_download_file(&link).is_err());
+        assert!(!target.exists());
+    }
+}
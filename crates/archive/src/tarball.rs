@@ -5,13 +5,13 @@ use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
-use super::{Archive, ArchiveError, Origin};
-use attohttpc::header::HeaderMap;
+use super::checksum::Sha256Read;
+use super::http::{HttpClient, HttpResponse};
+use super::{create_download_file, Archive, ArchiveError, Origin};
+use attohttpc::header::{HeaderMap, HeaderName, AUTHORIZATION, RANGE};
 use flate2::read::GzDecoder;
 use fs_utils::ensure_containing_dir_exists;
-use hyperx::header::{
-    AcceptRanges, ByteRangeSpec, ContentLength, Header, Range, RangeUnit, TypedHeaders,
-};
+use hyperx::header::{AcceptRanges, ByteRangeSpec, ContentLength, Range, RangeUnit, TypedHeaders};
 use progress_read::ProgressRead;
 use tee::TeeReader;
 
@@ -23,8 +23,9 @@ pub struct Tarball {
     // If the uncompressed size is not available, the compressed size will be
     // used for the download/unpack progress indicator, so that will be slightly off.
     uncompressed_size: Option<u64>,
-    data: Box<dyn Read>,
+    data: Box<dyn Read + Send>,
     origin: Origin,
+    expected_sha256: Option<String>,
 }
 
 /// Determines the length of an HTTP response's content in bytes, using
@@ -47,14 +48,34 @@ impl Tarball {
             compressed_size,
             data: Box::new(source),
             origin: Origin::Local,
+            expected_sha256: None,
         }))
     }
 
-    /// Initiate fetching of a tarball from the given URL, returning a
-    /// tarball that can be streamed (and that tees its data to a local
-    /// file as it streams).
-    pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-        let (status, headers, response) = attohttpc::get(url).send()?.split();
+    /// Initiate fetching of a tarball from the given URL with the given
+    /// client, returning a tarball that can be streamed (and that tees its
+    /// data to a local file as it streams).
+    pub fn fetch(
+        client: &dyn HttpClient,
+        url: &str,
+        cache_file: &Path,
+    ) -> Result<Box<dyn Archive>, ArchiveError> {
+        Tarball::fetch_authorized(client, url, None, cache_file)
+    }
+
+    /// Initiate fetching of a tarball from the given URL, like `fetch`, sending the given
+    /// `Authorization` header with each request, for registries that require credentials.
+    pub fn fetch_authorized(
+        client: &dyn HttpClient,
+        url: &str,
+        authorization: Option<&str>,
+        cache_file: &Path,
+    ) -> Result<Box<dyn Archive>, ArchiveError> {
+        let HttpResponse {
+            status,
+            headers,
+            body,
+        } = client.get(url, &request_headers(authorization))?;
 
         if !status.is_success() {
             return Err(ArchiveError::HttpError(status));
@@ -62,20 +83,21 @@ impl Tarball {
 
         let compressed_size = content_length(&headers)?;
         let uncompressed_size = if accepts_byte_ranges(&headers) {
-            fetch_uncompressed_size(url, compressed_size)
+            fetch_uncompressed_size(client, url, authorization, compressed_size)
         } else {
             None
         };
 
         ensure_containing_dir_exists(&cache_file)?;
-        let file = File::create(cache_file)?;
-        let data = Box::new(TeeReader::new(response, file));
+        let file = create_download_file(cache_file)?;
+        let data = Box::new(TeeReader::new(body, file));
 
         Ok(Box::new(Tarball {
             uncompressed_size,
             compressed_size,
             data,
             origin: Origin::Remote,
+            expected_sha256: None,
         }))
     }
 }
@@ -91,15 +113,26 @@ impl Archive for Tarball {
         self: Box<Self>,
         dest: &Path,
         progress: &mut dyn FnMut(&(), usize),
-    ) -> Result<(), ArchiveError> {
-        let decoded = GzDecoder::new(self.data);
-        let mut tarball = tar::Archive::new(ProgressRead::new(decoded, (), progress));
-        tarball.unpack(dest)?;
-        Ok(())
+    ) -> Result<String, ArchiveError> {
+        // The archive is unpacked as it streams in, so the digest is computed along the way
+        let mut data = Sha256Read::new(self.data);
+        {
+            let decoded = GzDecoder::new(&mut data);
+            let mut tarball = tar::Archive::new(ProgressRead::new(decoded, (), progress));
+            tarball.unpack(dest)?;
+        }
+
+        // Unpacking stops at the end of the tar entries, so reading whatever is left (padding and
+        // the gzip trailer) ensures the digest covers the full archive, and that a download is
+        // completely written to its cache file
+        data.verify(self.expected_sha256.as_deref())
     }
     fn origin(&self) -> Origin {
         self.origin
     }
+    fn expect_sha256(&mut self, digest: String) {
+        self.expected_sha256 = Some(digest);
+    }
 }
 
 // From http://www.gzip.org/zlib/rfc-gzip.html#member-format
@@ -126,12 +159,20 @@ fn unpack_isize(packed: [u8; 4]) -> u64 {
 /// of a gzip file from a URL. This makes two round-trips to the server but avoids
 /// downloading the entire gzip file. For very small files it's unlikely to be
 /// more efficient than simply downloading the entire file up front.
-fn fetch_isize(url: &str, len: u64) -> Result<[u8; 4], ArchiveError> {
+fn fetch_isize(
+    client: &dyn HttpClient,
+    url: &str,
+    authorization: Option<&str>,
+    len: u64,
+) -> Result<[u8; 4], ArchiveError> {
     let range_header = Range::Bytes(vec![ByteRangeSpec::FromTo(len - 4, len - 1)]);
-    let (status, headers, mut response) = attohttpc::get(url)
-        .header(Range::header_name(), range_header.to_string())
-        .send()?
-        .split();
+    let mut headers = request_headers(authorization);
+    headers.push((RANGE, range_header.to_string()));
+    let HttpResponse {
+        status,
+        headers,
+        body: mut response,
+    } = client.get(url, &headers)?;
 
     if !status.is_success() {
         return Err(ArchiveError::HttpError(status));
@@ -171,9 +212,24 @@ fn accepts_byte_ranges(headers: &HeaderMap) -> bool {
 /// an extra round-trip to the server, so it's only more efficient than just
 /// downloading the file if the file is large enough that downloading it is
 /// slower than the extra round trips.
-fn fetch_uncompressed_size(url: &str, len: u64) -> Option<u64> {
+fn fetch_uncompressed_size(
+    client: &dyn HttpClient,
+    url: &str,
+    authorization: Option<&str>,
+    len: u64,
+) -> Option<u64> {
     // if there is an error, we ignore it and return None, instead of failing
-    fetch_isize(url, len).ok().map(unpack_isize)
+    fetch_isize(client, url, authorization, len)
+        .ok()
+        .map(unpack_isize)
+}
+
+/// Builds the headers of a request, with an `Authorization` header if one is given.
+fn request_headers(authorization: Option<&str>) -> Vec<(HeaderName, String)> {
+    authorization
+        .map(|authorization| (AUTHORIZATION, authorization.to_string()))
+        .into_iter()
+        .collect()
 }
 
 /// Determines the uncompressed size of the specified gzip file on disk.
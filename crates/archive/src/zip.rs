@@ -2,14 +2,16 @@
 //! zip file in Windows operating systems.
 
 use std::fs::{create_dir_all, File};
-use std::io::copy;
+use std::io::{copy, Seek, SeekFrom};
 use std::path::Path;
 
-use crate::ArchiveError;
+use crate::{create_download_file, ArchiveError};
 use progress_read::ProgressRead;
 use verbatim::PathExt;
 use zip_rs::ZipArchive;
 
+use super::checksum::Sha256Read;
+use super::http::{HttpClient, HttpResponse};
 use super::Archive;
 use super::Origin;
 
@@ -17,6 +19,7 @@ pub struct Zip {
     compressed_size: u64,
     data: File,
     origin: Origin,
+    expected_sha256: Option<String>,
 }
 
 impl Zip {
@@ -28,30 +31,39 @@ impl Zip {
             compressed_size,
             data: source,
             origin: Origin::Local,
+            expected_sha256: None,
         }))
     }
 
-    /// Initiate fetching of a Node zip archive from the given URL, returning
-    /// a `Remote` data source.
-    pub fn fetch(url: &str, cache_file: &Path) -> Result<Box<dyn Archive>, ArchiveError> {
-        let (status, _, mut response) = attohttpc::get(url).send()?.split();
+    /// Initiate fetching of a Node zip archive from the given URL with the
+    /// given client, returning a `Remote` data source.
+    pub fn fetch(
+        client: &dyn HttpClient,
+        url: &str,
+        cache_file: &Path,
+    ) -> Result<Box<dyn Archive>, ArchiveError> {
+        let HttpResponse {
+            status,
+            body: mut response,
+            ..
+        } = client.get(url, &[])?;
 
         if !status.is_success() {
             return Err(ArchiveError::HttpError(status));
         }
 
-        {
-            let mut file = File::create(cache_file)?;
-            copy(&mut response, &mut file)?;
-        }
+        // Read the download back through the same handle, rather than opening its path again
+        let mut file = create_download_file(cache_file)?;
+        copy(&mut response, &mut file)?;
+        file.seek(SeekFrom::Start(0))?;
 
-        let file = File::open(cache_file)?;
         let compressed_size = file.metadata()?.len();
 
         Ok(Box::new(Zip {
             compressed_size,
             data: file,
             origin: Origin::Remote,
+            expected_sha256: None,
         }))
     }
 }
@@ -67,11 +79,17 @@ impl Archive for Zip {
         self: Box<Self>,
         dest: &Path,
         progress: &mut dyn FnMut(&(), usize),
-    ) -> Result<(), ArchiveError> {
+    ) -> Result<String, ArchiveError> {
         // Use a verbatim path to avoid the legacy Windows 260 byte path limit.
         let dest: &Path = &dest.to_verbatim();
 
-        let mut zip = ZipArchive::new(ProgressRead::new(self.data, (), progress))?;
+        // Zip files can't be unpacked until they are complete, since the index of entries is at
+        // the end, so the digest is computed before unpacking anything
+        let mut data = self.data;
+        let digest = Sha256Read::new(&mut data).verify(self.expected_sha256.as_deref())?;
+        data.seek(SeekFrom::Start(0))?;
+
+        let mut zip = ZipArchive::new(ProgressRead::new(data, (), progress))?;
         for i in 0..zip.len() {
             let mut entry = zip.by_index(i)?;
 
@@ -97,11 +115,14 @@ impl Archive for Zip {
                 copy(&mut entry, &mut file)?;
             }
         }
-        Ok(())
+        Ok(digest)
     }
     fn origin(&self) -> Origin {
         self.origin
     }
+    fn expect_sha256(&mut self, digest: String) {
+        self.expected_sha256 = Some(digest);
+    }
 }
 
 #[cfg(test)]
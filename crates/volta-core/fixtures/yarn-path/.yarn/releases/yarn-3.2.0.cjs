@@ -0,0 +1 @@
+// Vendored Yarn release
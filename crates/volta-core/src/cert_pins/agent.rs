@@ -0,0 +1,232 @@
+//! Sends the requests for pinned hosts, checking the certificate of each connection during its
+//! TLS handshake
+//!
+//! The default client doesn't expose the certificates of its connections, so requests for a
+//! pinned host are sent with a `ureq` agent instead, whose TLS connector checks the certificate
+//! that the host presents before the request is written. The agents are built once and keep their
+//! connections open, and a connection is only kept once it has been checked. Redirects are
+//! followed by the agent, so the host of every hop is checked against its own pins, and redirects
+//! to plain HTTP are refused. Proxies are read from `HTTPS_PROXY` and `NO_PROXY`, as they are by
+//! the default client.
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::{host_pins, Pin};
+use crate::environment;
+use crate::error::ErrorKind;
+use crate::http::{HeaderMap, HeaderName, HttpResponse, StatusCode};
+use attohttpc::header::HeaderValue;
+use log::debug;
+use native_tls::TlsStream;
+use ureq::{Agent, AgentBuilder, Proxy, ReadWrite, TlsConnector};
+use url::Url;
+
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The agents that requests for pinned hosts are sent with
+pub(super) struct Agents {
+    direct: Agent,
+    /// The agent for hosts that aren't listed in `NO_PROXY`, if a proxy is set
+    proxied: Option<Agent>,
+}
+
+impl Agents {
+    pub(super) fn new(pins: &'static HashMap<String, Vec<Pin>>) -> io::Result<Self> {
+        let connector = Arc::new(PinningConnector {
+            connector: native_tls::TlsConnector::new().map_err(other)?,
+            pins,
+        });
+        let builder = || {
+            AgentBuilder::new()
+                .https_only(true)
+                .timeout_connect(TIMEOUT)
+                .timeout_read(TIMEOUT)
+                .tls_connector(connector.clone())
+        };
+
+        let proxied = match https_proxy() {
+            Some(proxy) => Some(builder().proxy(Proxy::new(proxy).map_err(other)?).build()),
+            None => None,
+        };
+
+        Ok(Agents {
+            direct: builder().build(),
+            proxied,
+        })
+    }
+
+    /// Sends a `GET` request, following any redirects
+    pub(super) fn get(
+        &self,
+        url: &Url,
+        headers: &[(HeaderName, String)],
+    ) -> io::Result<HttpResponse> {
+        let agent = match &self.proxied {
+            Some(agent) if !bypasses_proxy(&no_proxy(), url.host_str().unwrap_or_default()) => {
+                agent
+            }
+            _ => &self.direct,
+        };
+
+        let mut request = agent.request_url("GET", url);
+        for (name, value) in headers {
+            request = request.set(name.as_str(), value);
+        }
+
+        // Unsuccessful statuses are returned like any other response, for the caller to check
+        let response = match request.call() {
+            Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+            Err(error) => return Err(other(error)),
+        };
+
+        let status = StatusCode::from_u16(response.status()).map_err(other)?;
+        let mut headers = HeaderMap::new();
+        for name in response.headers_names() {
+            let header = HeaderName::from_bytes(name.as_bytes()).map_err(other)?;
+            for value in response.all(&name) {
+                headers.append(header.clone(), HeaderValue::from_str(value).map_err(other)?);
+            }
+        }
+
+        Ok(HttpResponse {
+            status,
+            headers,
+            body: Box::new(response.into_reader()),
+        })
+    }
+}
+
+/// Makes TLS connections, checking the certificate of every pinned host after its handshake
+///
+/// The handshake still validates the certificate against the system trust store, so a pin adds
+/// to the usual checks rather than replacing them.
+struct PinningConnector {
+    connector: native_tls::TlsConnector,
+    pins: &'static HashMap<String, Vec<Pin>>,
+}
+
+impl TlsConnector for PinningConnector {
+    fn connect(
+        &self,
+        dns_name: &str,
+        io: Box<dyn ReadWrite>,
+    ) -> Result<Box<dyn ReadWrite>, ureq::Error> {
+        let stream = self
+            .connector
+            .connect(dns_name, io)
+            .map_err(|error| other(error.to_string()))?;
+
+        if let Some(pins) = host_pins(self.pins, dns_name) {
+            debug!("Checking the certificate of {} against its pins", dns_name);
+            let certificate = stream
+                .peer_certificate()
+                .map_err(other)?
+                .ok_or_else(|| {
+                    other(
+                        ErrorKind::CertificateCheckError {
+                            host: dns_name.into(),
+                        }
+                        .to_string(),
+                    )
+                })?
+                .to_der()
+                .map_err(other)?;
+
+            if !pins.iter().any(|pin| pin.matches(&certificate)) {
+                return Err(other(
+                    ErrorKind::CertificatePinMismatch {
+                        host: dns_name.into(),
+                    }
+                    .to_string(),
+                )
+                .into());
+            }
+        }
+
+        Ok(Box::new(CheckedStream(stream)))
+    }
+}
+
+/// A TLS connection whose certificate has been checked
+struct CheckedStream(TlsStream<Box<dyn ReadWrite>>);
+
+impl ReadWrite for CheckedStream {
+    fn socket(&self) -> Option<&TcpStream> {
+        self.0.get_ref().socket()
+    }
+}
+
+impl Read for CheckedStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl Write for CheckedStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl fmt::Debug for CheckedStream {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CheckedStream({:?})", self.0.get_ref())
+    }
+}
+
+fn https_proxy() -> Option<String> {
+    ["https_proxy", "HTTPS_PROXY"].iter().find_map(|name| {
+        environment::var(name)
+            .ok()
+            .filter(|proxy| !proxy.is_empty())
+    })
+}
+
+fn no_proxy() -> String {
+    environment::var("no_proxy")
+        .or_else(|_| environment::var("NO_PROXY"))
+        .unwrap_or_default()
+}
+
+/// Whether a host is one of the hosts, or in one of the domains, in a `NO_PROXY` list
+fn bypasses_proxy(no_proxy: &str, host: &str) -> bool {
+    no_proxy
+        .split(',')
+        .map(|entry| entry.trim().trim_start_matches('.'))
+        .filter(|entry| !entry.is_empty())
+        .any(|entry| entry == "*" || host == entry || host.ends_with(&format!(".{}", entry)))
+}
+
+fn other<E>(error: E) -> io::Error
+where
+    E: Into<Box<dyn Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::Other, error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::bypasses_proxy;
+
+    #[test]
+    fn matches_no_proxy_entries() {
+        assert!(bypasses_proxy("localhost, .example.com", "example.com"));
+        assert!(bypasses_proxy(
+            "localhost, .example.com",
+            "nodejs.example.com"
+        ));
+        assert!(bypasses_proxy("*", "nodejs.org"));
+        assert!(!bypasses_proxy("localhost,example.com", "notexample.com"));
+        assert!(!bypasses_proxy("", "nodejs.org"));
+    }
+}
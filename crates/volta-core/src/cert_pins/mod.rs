@@ -0,0 +1,221 @@
+//! Provides optional certificate pinning for the hosts that tools are downloaded from
+//!
+//! When the `certificatePins` setting lists digests for a host, the certificate that the host
+//! presents is checked on the connection that is used to download from it, before the request is
+//! sent. The download only goes ahead if the certificate, or its public key, matches one of the
+//! pins, so an intercepted connection is caught even when the system trust store accepts the
+//! interceptor's certificate.
+//!
+//! Only requests for a pinned host are checked. They are sent with an agent that checks the
+//! certificate during the TLS handshake of each connection (see `agent`), while requests for any
+//! other host are sent with the installed client as usual.
+//!
+//! Pins are only read from the user settings, so that a project can't turn them off.
+
+use std::collections::HashMap;
+use std::io;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::error::{ErrorKind, Fallible, VoltaError};
+use crate::http::{HeaderName, HttpClient, HttpResponse};
+use crate::settings::Settings;
+use double_checked_cell::DoubleCheckedCell;
+use lazy_static::lazy_static;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+mod agent;
+
+lazy_static! {
+    static ref PINS: DoubleCheckedCell<HashMap<String, Vec<Pin>>> = DoubleCheckedCell::new();
+    static ref AGENTS: DoubleCheckedCell<agent::Agents> = DoubleCheckedCell::new();
+}
+
+/// A pinned SHA-256 digest, of either a whole certificate or only its public key
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Pin {
+    Certificate(Vec<u8>),
+    /// The digest of the certificate's `SubjectPublicKeyInfo`, which stays the same when a
+    /// certificate is renewed with the same key
+    PublicKey(Vec<u8>),
+}
+
+impl Pin {
+    fn matches(&self, certificate: &[u8]) -> bool {
+        match self {
+            Pin::Certificate(digest) => Sha256::digest(certificate).as_slice() == digest.as_slice(),
+            Pin::PublicKey(digest) => public_key_info(certificate).map_or(false, |key| {
+                Sha256::digest(key).as_slice() == digest.as_slice()
+            }),
+        }
+    }
+}
+
+impl FromStr for Pin {
+    type Err = VoltaError;
+
+    /// Parses a pin in hexadecimal, optionally separated with colons as `openssl` prints them
+    ///
+    /// Digests of a public key are prefixed with `spki:`.
+    fn from_str(s: &str) -> Fallible<Self> {
+        let (public_key, digest) = match s.strip_prefix("spki:") {
+            Some(digest) => (true, digest),
+            None => (false, s),
+        };
+        let digest: String = digest.chars().filter(|c| *c != ':').collect();
+
+        match hex::decode(digest) {
+            Ok(digest) if digest.len() == 32 && public_key => Ok(Pin::PublicKey(digest)),
+            Ok(digest) if digest.len() == 32 => Ok(Pin::Certificate(digest)),
+            _ => Err(ErrorKind::InvalidCertificatePin { pin: s.into() }.into()),
+        }
+    }
+}
+
+/// Checks that a URL may be downloaded from, given the pins for its host
+///
+/// This loads the pins, so that invalid settings are reported before anything is downloaded, and
+/// refuses plain HTTP for a pinned host. The certificate itself is checked by `PinnedClient` on
+/// the connection that the download is made over.
+pub(crate) fn verify(url: &str) -> Fallible<()> {
+    let pins = pins()?;
+    match Url::parse(url) {
+        Ok(parsed) if parsed.scheme() != "https" && url_pins(pins, &parsed).is_some() => {
+            Err(ErrorKind::InsecurePinnedUrl { url: url.into() }.into())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A client that checks the certificate of pinned hosts
+///
+/// Requests for a host without any pins are passed to the installed client unchanged.
+pub(crate) struct PinnedClient(pub(crate) Arc<dyn HttpClient>);
+
+impl HttpClient for PinnedClient {
+    fn get(&self, url: &str, headers: &[(HeaderName, String)]) -> io::Result<HttpResponse> {
+        let pins =
+            pins().map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()))?;
+        match Url::parse(url) {
+            Ok(parsed) if url_pins(pins, &parsed).is_some() => AGENTS
+                .get_or_try_init(|| agent::Agents::new(pins))?
+                .get(&parsed, headers),
+            _ => self.0.get(url, headers),
+        }
+    }
+}
+
+/// Loads the pins from the user settings, which are only read once
+fn pins<'a>() -> Fallible<&'a HashMap<String, Vec<Pin>>> {
+    PINS.get_or_try_init(|| Ok(Settings::user()?.certificate_pins().clone()))
+}
+
+/// Finds the pins for the host of a URL, if it has any
+fn url_pins<'a>(pins: &'a HashMap<String, Vec<Pin>>, url: &Url) -> Option<&'a [Pin]> {
+    host_pins(pins, url.host_str()?)
+}
+
+/// Finds the pins for a host, if it has any
+///
+/// IPv6 addresses are looked up both with and without the brackets that surround them in a URL.
+fn host_pins<'a>(pins: &'a HashMap<String, Vec<Pin>>, host: &str) -> Option<&'a [Pin]> {
+    pins.get(host)
+        .or_else(|| pins.get(host.trim_start_matches('[').trim_end_matches(']')))
+        .map(Vec::as_slice)
+        .filter(|pins| !pins.is_empty())
+}
+
+/// Finds the DER encoding of the `SubjectPublicKeyInfo` in a certificate
+fn public_key_info(certificate: &[u8]) -> Option<&[u8]> {
+    let (_, certificate, _) = der_element(certificate)?;
+    let (_, mut fields, _) = der_element(certificate)?;
+
+    // Skip the optional version, then the serial number, signature algorithm, issuer, validity,
+    // and subject that come before the public key
+    if fields.first() == Some(&0xa0) {
+        fields = der_element(fields)?.2;
+    }
+    for _ in 0..5 {
+        fields = der_element(fields)?.2;
+    }
+
+    der_element(fields).map(|(element, _, _)| element)
+}
+
+/// Splits the first DER element from some data, returning the whole element, its contents, and
+/// the data that follows it
+fn der_element(data: &[u8]) -> Option<(&[u8], &[u8], &[u8])> {
+    let length = *data.get(1)?;
+    let (header, length) = if length < 0x80 {
+        (2, length as usize)
+    } else {
+        let count = (length & 0x7f) as usize;
+        if count == 0 || count > 4 {
+            return None;
+        }
+        let length = data
+            .get(2..2 + count)?
+            .iter()
+            .fold(0, |acc, byte| (acc << 8) | *byte as usize);
+        (2 + count, length)
+    };
+
+    let end = header + length;
+    let element = data.get(..end)?;
+    Some((element, &element[header..], &data[end..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{der_element, url_pins, Pin};
+    use std::collections::HashMap;
+    use url::Url;
+
+    #[test]
+    fn parses_pins() {
+        let digest = vec![0xab; 32];
+        let colons = ["AB"; 32].join(":");
+
+        assert_eq!(
+            colons.parse::<Pin>().unwrap(),
+            Pin::Certificate(digest.clone())
+        );
+        assert_eq!(
+            format!("spki:{}", "ab".repeat(32)).parse::<Pin>().unwrap(),
+            Pin::PublicKey(digest)
+        );
+        assert!("abcd".parse::<Pin>().is_err());
+        assert!("sha256/q83vEjRWeJA=".parse::<Pin>().is_err());
+    }
+
+    #[test]
+    fn finds_host_pins() {
+        let pin = Pin::Certificate(vec![0xab; 32]);
+        let mut pins = HashMap::new();
+        pins.insert("nodejs.org".to_string(), vec![pin.clone()]);
+        pins.insert("::1".to_string(), vec![pin.clone()]);
+        pins.insert("example.com".to_string(), vec![]);
+
+        let find = |url: &str| url_pins(&pins, &Url::parse(url).unwrap()).map(<[Pin]>::to_vec);
+
+        assert_eq!(
+            find("https://nodejs.org/dist/index.json"),
+            Some(vec![pin.clone()])
+        );
+        assert_eq!(find("https://[::1]:8443/dist/index.json"), Some(vec![pin]));
+        assert_eq!(find("https://user@127.0.0.1:1234/yarn.tgz"), None);
+        assert_eq!(find("https://example.com/yarn.tgz"), None);
+    }
+
+    #[test]
+    fn splits_der_elements() {
+        let data = [0x30, 0x03, 0x02, 0x01, 0x05, 0xff];
+        let (element, contents, rest) = der_element(&data).unwrap();
+        assert_eq!(element, &data[..5]);
+        assert_eq!(contents, &[0x02, 0x01, 0x05]);
+        assert_eq!(rest, &[0xff]);
+
+        assert!(der_element(&[0x30, 0x05, 0x00]).is_none());
+    }
+}
@@ -0,0 +1,195 @@
+//! Provides a trust-on-first-use record of the checksums of downloaded tools
+//!
+//! The first time an archive is downloaded from a URL, its SHA-256 digest is recorded in the
+//! Volta directory. Later downloads from the same URL have to produce the same bytes, so that a
+//! mirror that is compromised after the first download can't slip in a different tool. Whether a
+//! mismatch is refused or only warned about is controlled by the `checksumMismatch` setting.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::Path;
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{create_staging_file, persist_staging_file, read_file};
+use crate::layout::volta_home;
+use crate::settings::{ChecksumPolicy, Settings};
+use crate::sync::VoltaLock;
+use crate::tool::verify::{self, Published};
+use archive::{Archive, ArchiveError, Origin};
+use log::warn;
+use serde::{Deserialize, Serialize};
+
+/// What was recorded about a download the first time its URL was fetched
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Download {
+    pub sha256: String,
+    pub verification: Verification,
+}
+
+/// How the first download from a URL was checked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Verification {
+    /// The download matched the checksum that its server publishes
+    PublishedChecksum,
+    /// There was nothing to check the download against, so it was trusted as it was
+    FirstUse,
+}
+
+/// Finds the URL that an archive with the given digest was downloaded from, along with what was
+/// recorded about that download
+pub fn find_download(sha256: &str) -> Fallible<Option<(String, Download)>> {
+    let file = volta_home()?.checksums_file();
+    Ok(load(file)?
+        .into_iter()
+        .find(|(_, download)| download.sha256.eq_ignore_ascii_case(sha256)))
+}
+
+/// Prepares an archive downloaded from a URL to be checked against the digest recorded the first
+/// time that URL was downloaded, or to record its digest if this is the first time
+///
+/// `published` is the digest that the server lists for the archive, if any, which the archive has
+/// to match as well. Digests other than SHA-256 are checked against the file the archive was
+/// downloaded to, once it has been unpacked.
+pub(crate) fn guard(
+    url: &str,
+    archive: Box<dyn Archive>,
+    download: &Path,
+    published: Option<Published>,
+) -> Fallible<Box<dyn Archive>> {
+    let file = volta_home()?.checksums_file();
+    let recorded = load(file)?.remove(url).map(|download| download.sha256);
+    let policy = Settings::user()?.checksum_policy();
+    let verification = match published {
+        Some(_) => Verification::PublishedChecksum,
+        None => Verification::FirstUse,
+    };
+    let (mut archive, published) = match published {
+        Some(Published::Sha256(digest)) => (archive, Some(digest)),
+        Some(published) => (verify::check_download(archive, download, published), None),
+        None => (archive, None),
+    };
+
+    let expected = match policy {
+        ChecksumPolicy::Refuse => match (published, &recorded) {
+            (Some(published), Some(recorded)) if !published.eq_ignore_ascii_case(recorded) => {
+                return Err(ErrorKind::ChecksumChanged {
+                    url: url.into(),
+                    file: file.to_owned(),
+                }
+                .into());
+            }
+            (published, recorded) => published.or_else(|| recorded.clone()),
+        },
+        // Mismatches with the recorded digest are only reported once the archive is unpacked
+        ChecksumPolicy::Warn => published,
+    };
+    if let Some(digest) = expected {
+        archive.expect_sha256(digest);
+    }
+
+    Ok(Box::new(FirstUseArchive {
+        inner: archive,
+        url: url.into(),
+        recorded,
+        verification,
+    }))
+}
+
+/// An archive that records its digest once it has been unpacked, if none was recorded before
+struct FirstUseArchive {
+    inner: Box<dyn Archive>,
+    url: String,
+    recorded: Option<String>,
+    verification: Verification,
+}
+
+impl Archive for FirstUseArchive {
+    fn compressed_size(&self) -> u64 {
+        self.inner.compressed_size()
+    }
+
+    fn uncompressed_size(&self) -> Option<u64> {
+        self.inner.uncompressed_size()
+    }
+
+    fn unpack(
+        self: Box<Self>,
+        dest: &Path,
+        progress: &mut dyn FnMut(&(), usize),
+    ) -> Result<String, ArchiveError> {
+        let FirstUseArchive {
+            inner,
+            url,
+            recorded,
+            verification,
+        } = *self;
+        let digest = inner.unpack(dest, progress)?;
+
+        match recorded {
+            Some(recorded) if !recorded.eq_ignore_ascii_case(&digest) => warn!(
+                "{} has changed since it was first downloaded (expected SHA-256 {}, found {})",
+                url, recorded, digest
+            ),
+            Some(_) => {}
+            // Failing to record the digest only loses protection for later downloads, so it
+            // shouldn't stop this one from being used
+            None => {
+                let download = Download {
+                    sha256: digest.clone(),
+                    verification,
+                };
+                if let Err(error) = record(&url, download) {
+                    warn!("Could not record the checksum of {}: {}", url, error);
+                }
+            }
+        }
+
+        Ok(digest)
+    }
+
+    fn origin(&self) -> Origin {
+        self.inner.origin()
+    }
+
+    fn expect_sha256(&mut self, digest: String) {
+        self.inner.expect_sha256(digest);
+    }
+}
+
+/// Reads the recorded downloads, keyed by their URL
+fn load(file: &Path) -> Fallible<BTreeMap<String, Download>> {
+    let contents = read_file(file).with_context(|| ErrorKind::ReadChecksumsError {
+        file: file.to_owned(),
+    })?;
+
+    match contents {
+        Some(contents) => {
+            serde_json::from_str(&contents).with_context(|| ErrorKind::ReadChecksumsError {
+                file: file.to_owned(),
+            })
+        }
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+fn record(url: &str, download: Download) -> Fallible<()> {
+    // Other processes may be recording their downloads at the same time
+    let _lock = VoltaLock::acquire_if_supported()?;
+    let file = volta_home()?.checksums_file();
+    let mut checksums = load(file)?;
+    checksums.insert(url.into(), download);
+
+    let mut staging = create_staging_file()?;
+    // Serializing a map of strings and enums can't fail
+    let json = serde_json::to_string_pretty(&checksums).expect("Checksums are serializable");
+    staging
+        .write_all(json.as_bytes())
+        .with_context(|| ErrorKind::WriteChecksumsError {
+            file: file.to_owned(),
+        })?;
+    persist_staging_file(staging, file).with_context(|| ErrorKind::WriteChecksumsError {
+        file: file.to_owned(),
+    })?;
+    Ok(())
+}
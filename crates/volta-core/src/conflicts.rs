@@ -0,0 +1,208 @@
+//! Provides detection of other version managers that take precedence over Volta
+//!
+//! When a directory with its own `node` (or `npm`, `yarn`, etc.) comes before Volta's shims on
+//! the `PATH`, commands silently run that version instead of the one Volta would choose, so
+//! pins appear to be ignored. This finds those directories and identifies what put them there.
+
+use std::env;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::layout::volta_home;
+
+/// The tools that Volta provides shims for, which other managers also provide
+const TOOLS: &[&str] = &["node", "npm", "npx", "yarn", "pnpm"];
+
+/// The version manager (or other source) that provides tools in a directory
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Manager {
+    Nvm,
+    N,
+    Nodenv,
+    Asdf,
+    Fnm,
+    /// The package manager shims of Node's Corepack
+    Corepack,
+    /// An installation of Node that isn't managed by any known version manager
+    Other,
+}
+
+impl fmt::Display for Manager {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Manager::Nvm => "nvm",
+            Manager::N => "n",
+            Manager::Nodenv => "nodenv",
+            Manager::Asdf => "asdf",
+            Manager::Fnm => "fnm",
+            Manager::Corepack => "Corepack",
+            Manager::Other => "another installation of Node",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A directory earlier on the `PATH` than Volta's shims that provides some of the same tools
+pub struct Conflict {
+    pub manager: Manager,
+    pub dir: PathBuf,
+    pub tools: Vec<&'static str>,
+}
+
+impl Conflict {
+    /// Describes how to let Volta's shims take precedence again
+    pub fn fix(&self) -> String {
+        match self.manager {
+            Manager::Corepack => {
+                "Run `corepack disable` so that Volta manages the package managers.".into()
+            }
+            Manager::Other => format!(
+                "Remove {} from your PATH, or move Volta's shims before it.",
+                self.dir.display()
+            ),
+            manager => format!(
+                "Remove the lines that load {} from your shell profile, or run `volta setup` to \
+                 move Volta's lines after them.",
+                manager
+            ),
+        }
+    }
+}
+
+/// Finds the directories on the `PATH` before Volta's shims that provide any of the same tools
+///
+/// If the shims aren't on the `PATH` at all, every such directory is a conflict.
+pub fn path_conflicts() -> Fallible<Vec<Conflict>> {
+    let shim_dir = volta_home()?.shim_dir().to_owned();
+    let path = env::var_os("PATH").unwrap_or_default();
+    let mut conflicts = Vec::new();
+
+    for dir in env::split_paths(&path) {
+        if same_dir(&dir, &shim_dir) {
+            break;
+        }
+        conflicts.extend(conflicts_in(dir));
+    }
+
+    Ok(conflicts)
+}
+
+/// Determines whether Volta's shim directory is on the `PATH`
+pub fn shims_on_path() -> Fallible<bool> {
+    let shim_dir = volta_home()?.shim_dir().to_owned();
+    let path = env::var_os("PATH").unwrap_or_default();
+    Ok(env::split_paths(&path).any(|dir| same_dir(&dir, &shim_dir)))
+}
+
+/// Builds a `PATH` without Volta's shim directory, so that tools are found as if Volta wasn't
+/// installed
+pub fn path_without_shims() -> Fallible<OsString> {
+    let shim_dir = volta_home()?.shim_dir().to_owned();
+    let path = env::var_os("PATH").unwrap_or_default();
+    let dirs = env::split_paths(&path).filter(|dir| !same_dir(dir, &shim_dir));
+    env::join_paths(dirs).with_context(|| ErrorKind::BuildPathError)
+}
+
+/// Builds a `PATH` with Volta's shim directory at the front, unless it is already on the `PATH`
+pub fn path_with_shims() -> Fallible<OsString> {
+    let shim_dir = volta_home()?.shim_dir().to_owned();
+    let path = env::var_os("PATH").unwrap_or_default();
+    if env::split_paths(&path).any(|dir| same_dir(&dir, &shim_dir)) {
+        return Ok(path);
+    }
+    let dirs = std::iter::once(shim_dir).chain(env::split_paths(&path));
+    env::join_paths(dirs).with_context(|| ErrorKind::BuildPathError)
+}
+
+/// Finds the tools in a directory, reporting the Corepack shims separately from the installation
+/// of Node that they were enabled in
+fn conflicts_in(dir: PathBuf) -> Vec<Conflict> {
+    let (corepack, tools): (Vec<&'static str>, Vec<&'static str>) = TOOLS
+        .iter()
+        .copied()
+        .filter(|tool| find_executable(&dir, tool).is_some())
+        .partition(|tool| is_corepack_shim(&dir, tool));
+
+    let mut conflicts = Vec::new();
+    if !tools.is_empty() {
+        conflicts.push(Conflict {
+            manager: identify(&dir),
+            dir: dir.clone(),
+            tools,
+        });
+    }
+    if !corepack.is_empty() {
+        conflicts.push(Conflict {
+            manager: Manager::Corepack,
+            dir,
+            tools: corepack,
+        });
+    }
+    conflicts
+}
+
+/// Identifies the manager of a directory from its location
+fn identify(dir: &Path) -> Manager {
+    let path = dir.to_string_lossy().replace('\\', "/");
+    let under_env = |var: &str| {
+        env::var_os(var)
+            .filter(|root| !root.is_empty())
+            .map_or(false, |root| dir.starts_with(root))
+    };
+
+    if under_env("NVM_DIR") || path.contains("/.nvm/") {
+        Manager::Nvm
+    } else if under_env("NODENV_ROOT") || path.contains("/.nodenv/") {
+        Manager::Nodenv
+    } else if under_env("ASDF_DATA_DIR") || path.contains("/.asdf/") {
+        Manager::Asdf
+    } else if under_env("FNM_DIR") || path.contains("fnm") {
+        Manager::Fnm
+    } else if dir
+        .parent()
+        .map_or(false, |prefix| prefix.join("n").join("versions").is_dir())
+    {
+        // n copies the active version into its prefix, next to its cache of versions
+        Manager::N
+    } else {
+        Manager::Other
+    }
+}
+
+/// Determines whether a tool in the directory is one of the shims created by `corepack enable`
+fn is_corepack_shim(dir: &Path, tool: &str) -> bool {
+    let exe = match find_executable(dir, tool) {
+        Some(exe) => exe,
+        None => return false,
+    };
+    if let Ok(target) = fs::read_link(&exe) {
+        return target.to_string_lossy().contains("corepack");
+    }
+
+    // On Windows, the shims are scripts that run Corepack
+    exe.extension().map_or(false, |ext| ext == "cmd")
+        && fs::read_to_string(&exe).map_or(false, |script| script.contains("corepack"))
+}
+
+fn find_executable(dir: &Path, tool: &str) -> Option<PathBuf> {
+    #[cfg(unix)]
+    let candidates = [tool.to_string()];
+    #[cfg(windows)]
+    let candidates = [format!("{}.exe", tool), format!("{}.cmd", tool)];
+
+    candidates
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|file| file.is_file())
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    a == b
+        || match (fs::canonicalize(a), fs::canonicalize(b)) {
+            (Ok(a), Ok(b)) => a == b,
+            _ => false,
+        }
+}
@@ -0,0 +1,118 @@
+//! Runs several downloads at the same time, with a limit on how many are running at once
+//!
+//! Each download spends most of its time waiting on the network, so running them in parallel
+//! means that a batch takes about as long as its largest download. Every download is sent with
+//! the client from `http`, which is shared by the whole process. While a batch is running, the
+//! progress bars of the individual downloads are hidden in favor of a single spinner, since they
+//! would draw over each other.
+
+use std::collections::VecDeque;
+use std::panic::resume_unwind;
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+
+use crate::error::{Fallible, SendableError, VoltaError};
+use crate::style::{progress_spinner, set_parallel_downloads};
+
+/// The most downloads that run at the same time
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+/// A download, which can borrow from its caller, since it finishes before `run` returns
+pub(crate) type Task<'a> = Box<dyn FnOnce() -> Fallible<()> + Send + 'a>;
+
+/// Runs a batch of downloads, showing a spinner with the given message until all of them finish
+///
+/// A failed download doesn't stop the others. The error of the first task that failed, in the
+/// order the tasks were given, is returned.
+pub(crate) fn run(message: String, tasks: Vec<Task<'_>>) -> Fallible<()> {
+    let count = tasks.len();
+    let queue = Mutex::new(tasks.into_iter().enumerate().collect::<VecDeque<_>>());
+    let results: Mutex<Vec<Option<Result<(), SendableError>>>> =
+        Mutex::new((0..count).map(|_| None).collect());
+
+    let spinner = progress_spinner(message);
+    set_parallel_downloads(true);
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..count.min(MAX_CONCURRENT_DOWNLOADS))
+            .map(|_| {
+                scope.spawn(|| {
+                    while let Some((index, task)) = next_task(&queue) {
+                        let result = task().map_err(SendableError::from);
+                        results.lock().unwrap_or_else(PoisonError::into_inner)[index] =
+                            Some(result);
+                    }
+                })
+            })
+            .collect();
+
+        for worker in workers {
+            worker.join().unwrap_or_else(|panic| resume_unwind(panic));
+        }
+    });
+
+    set_parallel_downloads(false);
+    spinner.finish_and_clear();
+
+    results
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner)
+        .into_iter()
+        .flatten()
+        .map(|result| result.map_err(VoltaError::from))
+        .collect()
+}
+
+/// Takes the next task from the queue, releasing the queue before the task runs
+fn next_task<'a>(queue: &Mutex<VecDeque<(usize, Task<'a>)>>) -> Option<(usize, Task<'a>)> {
+    queue
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .pop_front()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Task, MAX_CONCURRENT_DOWNLOADS};
+    use crate::error::ErrorKind;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    #[test]
+    fn limits_concurrent_downloads() {
+        let running = &AtomicUsize::new(0);
+        let most = &AtomicUsize::new(0);
+
+        let tasks = (0..MAX_CONCURRENT_DOWNLOADS * 2)
+            .map(|_| {
+                Box::new(move || {
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most.fetch_max(now, Ordering::SeqCst);
+                    sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                }) as Task
+            })
+            .collect();
+
+        run("Fetching".into(), tasks).unwrap();
+        assert!(most.load(Ordering::SeqCst) <= MAX_CONCURRENT_DOWNLOADS);
+    }
+
+    #[test]
+    fn finishes_every_download_after_a_failure() {
+        let finished = AtomicUsize::new(0);
+        let tasks: Vec<Task> = vec![
+            Box::new(|| Err(ErrorKind::Interrupted.into())),
+            Box::new(|| {
+                finished.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            }),
+        ];
+
+        let error = run("Fetching".into(), tasks).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Interrupted));
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+    }
+}
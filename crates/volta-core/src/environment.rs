@@ -0,0 +1,40 @@
+//! Provides the environment variables that Volta reads its options from.
+//!
+//! These are the variables of the process, unless a snapshot was given with
+//! `SessionBuilder::env`, in which case the variables of the process are ignored. The snapshot is
+//! shared by every session in the process, like the HTTP client.
+
+use std::collections::HashMap;
+use std::env::{self, VarError};
+use std::ffi::{OsStr, OsString};
+use std::sync::{PoisonError, RwLock};
+
+use lazy_static::lazy_static;
+
+lazy_static! {
+    static ref VARS: RwLock<Option<HashMap<OsString, OsString>>> = RwLock::new(None);
+}
+
+/// Replaces the environment of the process with a snapshot, for every session in the process
+///
+/// Options that are read once, like the Volta directory, keep the value that they were first read
+/// with.
+pub(crate) fn set_vars(vars: HashMap<OsString, OsString>) {
+    *VARS.write().unwrap_or_else(PoisonError::into_inner) = Some(vars);
+}
+
+/// Fetches an environment variable, like `std::env::var_os`
+pub(crate) fn var_os(name: &str) -> Option<OsString> {
+    match &*VARS.read().unwrap_or_else(PoisonError::into_inner) {
+        Some(vars) => vars.get(OsStr::new(name)).cloned(),
+        None => env::var_os(name),
+    }
+}
+
+/// Fetches an environment variable as a string, like `std::env::var`
+pub(crate) fn var(name: &str) -> Result<String, VarError> {
+    match var_os(name) {
+        Some(value) => value.into_string().map_err(VarError::NotUnicode),
+        None => Err(VarError::NotPresent),
+    }
+}
@@ -0,0 +1,103 @@
+use super::{ErrorKind, ExitCode};
+
+/// The broad category of an error, for programs that need to react to failures
+///
+/// `ErrorKind` has a variant for every failure, and those change as Volta does, so matching on
+/// them is fragile. Categories are stable: new ones may be added, but an error won't move from
+/// one category to another without a major release.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorCategory {
+    /// A request to a server failed, or returned something unexpected
+    NetworkError,
+
+    /// No version of a tool matches the requested version
+    VersionNotFound,
+
+    /// The command needs a project, but it wasn't run inside one
+    NoProject,
+
+    /// A hook failed to run, or returned something Volta couldn't use
+    HookFailed,
+
+    /// The arguments to the command were invalid
+    InvalidArguments,
+
+    /// A project, hook, or settings file is missing or invalid
+    ConfigurationError,
+
+    /// A file or directory could not be read or written
+    FileSystemError,
+
+    /// The environment Volta runs in is missing something, or is set up incorrectly
+    EnvironmentError,
+
+    /// A tool or binary that was needed isn't available
+    ExecutableNotFound,
+
+    /// A tool or binary could not be run
+    ExecutionFailure,
+
+    /// The process was interrupted before the command finished
+    Interrupted,
+
+    /// Any other failure, usually a bug in Volta
+    Other,
+}
+
+impl ErrorKind {
+    /// The category of this error
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            ErrorKind::NotInPackage | ErrorKind::NotInWorkspace => ErrorCategory::NoProject,
+            ErrorKind::ExecuteHookError { .. }
+            | ErrorKind::HookCommandFailed { .. }
+            | ErrorKind::InvalidHookCommand { .. }
+            | ErrorKind::InvalidHookOutput { .. }
+            | ErrorKind::WasmPluginError { .. } => ErrorCategory::HookFailed,
+            _ => match self.exit_code() {
+                ExitCode::NetworkError => ErrorCategory::NetworkError,
+                ExitCode::NoVersionMatch => ErrorCategory::VersionNotFound,
+                ExitCode::InvalidArguments => ErrorCategory::InvalidArguments,
+                ExitCode::ConfigurationError => ErrorCategory::ConfigurationError,
+                ExitCode::FileSystemError => ErrorCategory::FileSystemError,
+                ExitCode::EnvironmentError => ErrorCategory::EnvironmentError,
+                ExitCode::ExecutableNotFound => ErrorCategory::ExecutableNotFound,
+                ExitCode::ExecutionFailure => ErrorCategory::ExecutionFailure,
+                ExitCode::Interrupted => ErrorCategory::Interrupted,
+                ExitCode::Success | ExitCode::UnknownError | ExitCode::NotYetImplemented => {
+                    ErrorCategory::Other
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ErrorCategory;
+    use crate::error::{ErrorKind, VoltaError};
+
+    #[test]
+    fn errors_are_categorized() {
+        let error: VoltaError = ErrorKind::NotInPackage.into();
+        assert_eq!(error.category(), ErrorCategory::NoProject);
+
+        let error: VoltaError = ErrorKind::HookCommandFailed {
+            command: "get-url".into(),
+        }
+        .into();
+        assert_eq!(error.category(), ErrorCategory::HookFailed);
+
+        let error: VoltaError = ErrorKind::NodeVersionNotFound {
+            matching: "99".into(),
+        }
+        .into();
+        assert_eq!(error.category(), ErrorCategory::VersionNotFound);
+
+        assert_eq!(
+            ErrorKind::Interrupted.category(),
+            ErrorCategory::Interrupted
+        );
+    }
+}
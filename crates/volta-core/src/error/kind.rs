@@ -1,5 +1,6 @@
 use std::fmt;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use super::ExitCode;
 use crate::style::{text_width, tool_version};
@@ -14,9 +15,19 @@ https://github.com/volta-cli/volta/issues with the details!";
 
 const PERMISSIONS_CTA: &str = "Please ensure you have correct permissions to the Volta directory.";
 
+/// The detailed kind of an error
+///
+/// Variants are added and changed as Volta does, so programs that need to react to failures should
+/// match on `ErrorCategory` instead.
 #[derive(Debug)]
 #[cfg_attr(test, derive(PartialEq))]
+#[non_exhaustive]
 pub enum ErrorKind {
+    /// Thrown when a shim needs a tool that isn't fetched, and automatic downloads are disabled
+    AutoDownloadDisabled {
+        tool: String,
+    },
+
     /// Thrown when package tries to install a binary that is already installed.
     BinaryAlreadyInstalled {
         bin_name: String,
@@ -27,6 +38,14 @@ pub enum ErrorKind {
     /// Thrown when executing an external binary fails
     BinaryExecError,
 
+    /// Thrown when a bin's name only differs by case from another bin, on a case-insensitive
+    /// file system
+    BinaryNameCaseConflict {
+        bin_name: String,
+        existing_bin: String,
+        existing_package: String,
+    },
+
     /// Thrown when a binary could not be found in the local inventory
     BinaryNotFound {
         name: String,
@@ -50,6 +69,28 @@ pub enum ErrorKind {
         package: String,
     },
 
+    /// Thrown when attempting to unpin a package, rather than node, npm, or Yarn
+    CannotUnpinPackage {
+        package: String,
+    },
+
+    /// Thrown when the certificate of a host with pinned certificates could not be retrieved
+    CertificateCheckError {
+        host: String,
+    },
+
+    /// Thrown when a host presents a certificate that doesn't match any of its pins
+    CertificatePinMismatch {
+        host: String,
+    },
+
+    /// Thrown when the published checksum of a download differs from the one recorded the first
+    /// time it was downloaded
+    ChecksumChanged {
+        url: String,
+        file: PathBuf,
+    },
+
     /// Thrown when the Completions out-dir is not a directory
     CompletionsOutFileError {
         path: PathBuf,
@@ -62,9 +103,6 @@ pub enum ErrorKind {
 
     CouldNotDetermineTool,
 
-    /// Thrown when unable to start the migration executable
-    CouldNotStartMigration,
-
     CreateDirError {
         dir: PathBuf,
     },
@@ -106,11 +144,35 @@ pub enum ErrorKind {
         advice: String,
     },
 
+    /// Thrown when a directory given on the command line doesn't exist
+    DirectoryNotFound {
+        dir: PathBuf,
+    },
+
     DownloadToolNetworkError {
         tool: tool::Spec,
         from_url: String,
     },
 
+    /// Thrown when npm is configured with `engine-strict` and the platform doesn't satisfy `engines`
+    EngineStrictConflict {
+        tool: String,
+        version: String,
+        range: String,
+        manifest: PathBuf,
+    },
+
+    /// Thrown when `volta exec` can't start the command it was given
+    ExecCommandError {
+        command: String,
+    },
+
+    /// Thrown when the executable to run from an ephemeral package install can't be determined
+    ExecutableNotFound {
+        package: String,
+        bins: Vec<String>,
+    },
+
     /// Thrown when unable to execute a hook command
     ExecuteHookError {
         command: String,
@@ -132,10 +194,10 @@ pub enum ErrorKind {
         command: String,
     },
 
-    /// Thrown when a hook contains multiple fields (prefix, template, or bin)
+    /// Thrown when a hook contains multiple fields (prefix, template, bin, or wasm)
     HookMultipleFieldsSpecified,
 
-    /// Thrown when a hook doesn't contain any of the known fields (prefix, template, or bin)
+    /// Thrown when a hook doesn't contain any of the known fields (prefix, template, bin, or wasm)
     HookNoFieldsSpecified,
 
     /// Thrown when determining the path to a hook fails
@@ -143,9 +205,45 @@ pub enum ErrorKind {
         command: String,
     },
 
+    /// Thrown when a hook command doesn't finish within its timeout
+    HookTimedOut {
+        command: String,
+        timeout: Duration,
+    },
+
+    /// Thrown when a Node version installed by another version manager could not be imported
+    ImportNodeError {
+        version: String,
+        dir: PathBuf,
+    },
+
+    /// Thrown when the directory of a version manager to import from doesn't exist
+    ImportSourceNotFound {
+        manager: String,
+        dir: PathBuf,
+    },
+
+    /// Thrown when a URL for a host with pinned certificates doesn't use HTTPS
+    InsecurePinnedUrl {
+        url: String,
+    },
+
     /// Thrown when determining the name of a newly-installed package fails
     InstalledPackageNameError,
 
+    /// Thrown when a task stops because the process was interrupted, e.g. with Ctrl+C
+    Interrupted,
+
+    /// Thrown when a certificate pin in the settings isn't a SHA-256 digest
+    InvalidCertificatePin {
+        pin: String,
+    },
+
+    /// Thrown when the checksumMismatch setting is neither "refuse" nor "warn"
+    InvalidChecksumPolicy {
+        policy: String,
+    },
+
     InvalidHookCommand {
         command: String,
     },
@@ -155,6 +253,11 @@ pub enum ErrorKind {
         command: String,
     },
 
+    /// Thrown when a tool given to `volta inventory export` isn't Node, npm, or Yarn
+    InvalidInventorySelection {
+        tool: String,
+    },
+
     /// Thrown when a user does e.g. `volta install node 12` instead of
     /// `volta install node@12`.
     InvalidInvocation {
@@ -170,20 +273,73 @@ pub enum ErrorKind {
         version: String,
     },
 
+    /// Thrown when a format other than "dist" or "npm" is given for node.index in the hooks
+    InvalidNodeIndexFormat {
+        format: String,
+    },
+
+    /// Thrown when the pin location in a settings file isn't recognized
+    InvalidPinLocation {
+        location: String,
+    },
+
     /// Thrown when a format other than "npm" or "github" is given for yarn.index in the hooks
     InvalidRegistryFormat {
         format: String,
     },
 
+    /// Thrown when the resolution preference in a settings file is not recognized
+    InvalidResolutionPreference {
+        preference: String,
+    },
+
     /// Thrown when a tool name is invalid per npm's rules.
     InvalidToolName {
         name: String,
         errors: Vec<String>,
     },
 
+    /// Thrown when an inventory archive has Node binaries for a different platform
+    InventoryArchivePlatformMismatch {
+        file: PathBuf,
+        archive: String,
+        current: String,
+    },
+
+    /// Thrown when a file in an inventory archive doesn't match the checksum in its manifest
+    InventoryChecksumMismatch {
+        file: PathBuf,
+        entry: String,
+    },
+
+    /// Thrown when an image to export is missing some of its files, or some of them changed
+    InventoryImageDamaged {
+        tool: String,
+        version: String,
+    },
+
     /// Thrown when unable to acquire a lock on the Volta directory
     LockAcquireError,
 
+    /// Thrown when another Volta process holds the lock on the Volta directory for too long
+    LockTimeout {
+        seconds: u64,
+    },
+
+    /// Thrown when a floating pin has no up-to-date entry in `volta.lock` while running frozen
+    LockfileOutOfDate {
+        tool: String,
+        spec: String,
+    },
+
+    /// Thrown when `volta migrate` can't ask for confirmation
+    MigrateNotInteractive,
+
+    /// Thrown when the Volta directory was migrated to a layout by a newer version of Volta
+    NewerLayoutError {
+        version: u32,
+    },
+
     /// Thrown when pinning or installing npm@bundled and couldn't detect the bundled version
     NoBundledNpm {
         command: String,
@@ -192,11 +348,44 @@ pub enum ErrorKind {
     /// Thrown when Yarn is not set at the command-line
     NoCommandLineYarn,
 
+    /// Thrown when the 'current' alias is used but no version of the tool is active
+    NoCurrentVersion {
+        tool: String,
+    },
+
     /// Thrown when a user tries to install a Yarn or npm version before installing a Node version.
     NoDefaultNodeVersion {
         tool: String,
     },
 
+    /// Thrown when the 'installed' alias is used but no matching version has been fetched
+    NoInstalledVersion {
+        tool: String,
+    },
+
+    /// Thrown when an nvm command without a version can't find an `.nvmrc` file
+    NoNvmrcFile,
+
+    /// Thrown when running in strict mode inside a project that doesn't pin a platform
+    NoPinnedPlatform {
+        manifest: PathBuf,
+    },
+
+    /// Thrown when Yarn is run in a shell session that selected Node with `volta use`, and no Yarn
+    /// version is available
+    NoShellYarn,
+
+    /// Thrown when the command that builds Node from source fails
+    NodeBuildFailed {
+        version: String,
+        command: String,
+    },
+
+    /// Thrown when Node has to be built from source, but no build command is configured
+    NodeBuildNotConfigured {
+        version: String,
+    },
+
     /// Thrown when there is no Node version matching a requested semver specifier.
     NodeVersionNotFound {
         matching: String,
@@ -235,6 +424,9 @@ pub enum ErrorKind {
     /// Thrown when default Yarn is not set
     NoDefaultYarn,
 
+    /// Thrown when pinning for a workspace from a project that isn't part of one
+    NotInWorkspace,
+
     /// Thrown when `npm link` is called with a package that isn't available
     NpmLinkMissingPackage {
         package: String,
@@ -254,6 +446,16 @@ pub enum ErrorKind {
         version: String,
     },
 
+    /// Thrown when the nvm compatibility shim is run with a command it doesn't support
+    NvmCommandUnsupported {
+        command: String,
+    },
+
+    /// Thrown when `nvm use` is given a version that isn't installed
+    NvmVersionNotInstalled {
+        version: String,
+    },
+
     /// Thrown when the command to install a global package is not successful
     PackageInstallFailed {
         package: String,
@@ -274,6 +476,11 @@ pub enum ErrorKind {
         package: String,
     },
 
+    /// Thrown when upgrading a package that hasn't been installed with Volta
+    PackageNotInstalled {
+        package: String,
+    },
+
     /// Thrown when parsing a package manifest fails
     PackageParseError {
         file: PathBuf,
@@ -300,6 +507,16 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when an inventory archive has no manifest, or one that could not be parsed
+    ParseInventoryArchiveError {
+        file: PathBuf,
+    },
+
+    /// Thrown when unable to parse a project's `volta.lock`
+    ParseLockfileError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to parse the node index cache
     ParseNodeIndexCacheError,
 
@@ -320,6 +537,16 @@ pub enum ErrorKind {
     /// Thrown when unable to parse the platform.json file
     ParsePlatformError,
 
+    /// Thrown when a settings bundle could not be parsed
+    ParseSettingsBundleError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the settings file could not be parsed
+    ParseSettingsError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to parse a tool spec (`<tool>[@<version>]`)
     ParseToolSpecError {
         tool_spec: String,
@@ -330,6 +557,16 @@ pub enum ErrorKind {
         tool: String,
     },
 
+    /// Thrown when `volta install --dest` is used with a tool other than Node
+    PrefixInstallUnsupported {
+        tool: String,
+    },
+
+    /// Thrown when a project's hooks run commands that the user hasn't allowed
+    ProjectHooksNotAllowed {
+        file: PathBuf,
+    },
+
     /// Thrown when executing a project-local binary fails
     ProjectLocalBinaryExecError {
         command: String,
@@ -356,6 +593,11 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when the recorded checksums of downloaded tools could not be read
+    ReadChecksumsError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to read the default npm version file
     ReadDefaultNpmError {
         file: PathBuf,
@@ -366,11 +608,32 @@ pub enum ErrorKind {
         dir: PathBuf,
     },
 
+    /// Thrown when the record of allowed project hooks could not be read
+    ReadHookApprovalsError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error opening a hooks.json file
     ReadHooksError {
         file: PathBuf,
     },
 
+    /// Thrown when an inventory archive could not be read or unpacked
+    ReadInventoryArchiveError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the files of an image could not be read
+    ReadInventoryImageError {
+        tool: String,
+        version: String,
+    },
+
+    /// Thrown when unable to read a project's `volta.lock`
+    ReadLockfileError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error reading the Node Index Cache
     ReadNodeIndexCacheError {
         file: PathBuf,
@@ -394,16 +657,41 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when a settings bundle could not be read
+    ReadSettingsBundleError {
+        file: PathBuf,
+    },
+
+    /// Thrown when reading the settings file fails
+    ReadSettingsError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the record of the shim executable could not be read
+    ReadShimRecordError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to read the user Path environment variable from the registry
     #[cfg(windows)]
     ReadUserPathError,
 
+    /// Thrown when the module of a WebAssembly plugin could not be read
+    ReadWasmPluginError {
+        module: PathBuf,
+    },
+
     /// Thrown when the public registry for Node or Yarn could not be downloaded.
     RegistryFetchError {
         tool: String,
         from_url: String,
     },
 
+    /// Thrown when listing remote versions of a tool other than Node
+    RemoteListUnsupported {
+        tool: String,
+    },
+
     /// Thrown when the shim binary is called directly, not through a symlink
     RunShimDirectly,
 
@@ -461,25 +749,106 @@ pub enum ErrorKind {
         manager: PackageManager,
     },
 
+    /// Thrown when `volta list --usage` is used with a package
+    UsageListUnsupported {
+        tool: String,
+    },
+
+    /// Thrown when `volta use` is given a package instead of Node, npm, or Yarn
+    UseUnsupportedTool {
+        tool: String,
+    },
+
+    /// Thrown when `volta use` is run without any tools, or without clearing the session
+    UseWithoutTools,
+
+    /// Thrown when an explicitly requested version is in the blocklist
+    VersionBlocklisted {
+        tool: String,
+        version: String,
+        reason: Option<String>,
+    },
+
     VersionParseError {
         version: String,
     },
 
+    /// Thrown when the Volta directory or its shims are owned by another user
+    VoltaDirOwnedByOtherUser {
+        dir: PathBuf,
+    },
+
+    /// Thrown when the Volta directory or its shims can be written by any user
+    VoltaDirWorldWritable {
+        dir: PathBuf,
+    },
+
+    /// Thrown when a session is built with a Volta directory after another one is already in use
+    VoltaHomeAlreadySet {
+        home: PathBuf,
+    },
+
+    /// Thrown when a WebAssembly plugin could not be loaded, or failed while it ran
+    WasmPluginError {
+        module: PathBuf,
+        reason: String,
+    },
+
+    /// Thrown when a hook uses a WebAssembly plugin, but Volta was built without support for them
+    WasmPluginsNotEnabled {
+        module: PathBuf,
+    },
+
     /// Thrown when there was an error writing a bin config file
     WriteBinConfigError {
         file: PathBuf,
     },
 
+    /// Thrown when the checksum of a downloaded tool could not be recorded
+    WriteChecksumsError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error writing the default npm to file
     WriteDefaultNpmError {
         file: PathBuf,
     },
 
+    /// Thrown when a file provided by the GitHub Actions runner could not be written
+    WriteGitHubFileError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the record of allowed project hooks could not be written
+    WriteHookApprovalsError {
+        file: PathBuf,
+    },
+
+    /// Thrown when there was an error recording how Volta was installed
+    WriteInstallSourceError {
+        file: PathBuf,
+    },
+
+    /// Thrown when there was an error writing the manifest of installed tool versions
+    WriteInstalledVersionsError {
+        file: PathBuf,
+    },
+
+    /// Thrown when an inventory archive could not be written
+    WriteInventoryArchiveError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error writing the npm launcher
     WriteLauncherError {
         tool: String,
     },
 
+    /// Thrown when unable to write a project's `volta.lock`
+    WriteLockfileError {
+        file: PathBuf,
+    },
+
     /// Thrown when there was an error writing the node index cache
     WriteNodeIndexCacheError {
         file: PathBuf,
@@ -500,10 +869,26 @@ pub enum ErrorKind {
         file: PathBuf,
     },
 
+    /// Thrown when a settings bundle, or one of the files in it, could not be written
+    WriteSettingsBundleError {
+        file: PathBuf,
+    },
+
+    /// Thrown when the record of the shim executable could not be written
+    WriteShimRecordError {
+        file: PathBuf,
+    },
+
     /// Thrown when unable to write the user PATH environment variable
     #[cfg(windows)]
     WriteUserPathError,
 
+    /// Thrown when Volta runs inside WSL with the home directory of the Windows installation
+    #[cfg(target_os = "linux")]
+    WslWindowsVoltaHome {
+        home: PathBuf,
+    },
+
     /// Thrown when a user attempts to install a version of Yarn2
     Yarn2NotSupported,
 
@@ -521,6 +906,13 @@ pub enum ErrorKind {
 impl fmt::Display for ErrorKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
+            ErrorKind::AutoDownloadDisabled { tool } => write!(
+                f,
+                "Could not use {}, because it hasn't been fetched and automatic downloads are disabled.
+
+Please fetch it first with `volta fetch {}`, or set `autoDownload` to `true` in your settings.",
+                tool, tool
+            ),
             ErrorKind::BinaryAlreadyInstalled {
                 bin_name,
                 existing_package,
@@ -529,7 +921,7 @@ impl fmt::Display for ErrorKind {
                 f,
                 "Executable '{}' is already installed by {}
 
-Please remove {} before installing {}",
+Please remove {} before installing {}, or use `volta install --force {3}` to replace it",
                 bin_name, existing_package, existing_package, new_package
             ),
             ErrorKind::BinaryExecError => write!(
@@ -538,6 +930,17 @@ Please remove {} before installing {}",
 
 See `volta help install` and `volta help pin` for info about making tools available."
             ),
+            ErrorKind::BinaryNameCaseConflict {
+                bin_name,
+                existing_bin,
+                existing_package,
+            } => write!(
+                f,
+                "Executable '{}' would replace '{}' from {}, because file names on this file system are not case-sensitive.
+
+Please remove {} before installing '{}'",
+                bin_name, existing_bin, existing_package, existing_package, bin_name
+            ),
             ErrorKind::BinaryNotFound { name } => write!(
                 f,
                 r#"Could not find executable "{}"
@@ -572,6 +975,38 @@ Use `volta install {}` to update the default version.",
 Use `npm install` or `yarn add` to select a version of {} for this project.",
                 package
             ),
+            ErrorKind::CannotUnpinPackage { package } => write!(
+                f,
+                "Only node, npm, and yarn can be unpinned from a project
+
+Use `npm uninstall` or `yarn remove` to remove {} from this project.",
+                package
+            ),
+            ErrorKind::CertificateCheckError { host } => write!(
+                f,
+                "Could not check the certificate of {}
+
+Please verify your internet connection.",
+                host
+            ),
+            ErrorKind::CertificatePinMismatch { host } => write!(
+                f,
+                "The certificate presented by {} does not match any of its pinned fingerprints.
+
+The connection may have been intercepted. If the host has changed its certificate, please update
+`certificatePins` in your Volta settings.",
+                host
+            ),
+            ErrorKind::ChecksumChanged { url, file } => write!(
+                f,
+                "The checksum published for {}
+has changed since it was first downloaded.
+
+The server may have been compromised. If the file was intentionally replaced, please remove its
+entry from {} to trust the new file.",
+                url,
+                file.display()
+            ),
             ErrorKind::CompletionsOutFileError { path } => write!(
                 f,
                 "Completions file `{}` already exists.
@@ -594,12 +1029,6 @@ Please remove the file or pass `-f` or `--force` to override.",
 {}",
                 REPORT_BUG_CTA
             ),
-            ErrorKind::CouldNotStartMigration => write!(
-                f,
-                "Could not start migration process to upgrade your Volta directory.
-
-Please ensure you have 'volta-migrate' on your PATH and run it directly."
-            ),
             ErrorKind::CreateDirError { dir } => write!(
                 f,
                 "Could not create directory {}
@@ -666,6 +1095,13 @@ at {}
             ErrorKind::DeprecatedCommandError { command, advice } => {
                 write!(f, "The subcommand `{}` is deprecated.\n{}", command, advice)
             }
+            ErrorKind::DirectoryNotFound { dir } => write!(
+                f,
+                "Directory not found: {}
+
+Please ensure that the directory exists.",
+                dir.display()
+            ),
             ErrorKind::DownloadToolNetworkError { tool, from_url } => write!(
                 f,
                 "Could not download {}
@@ -674,6 +1110,43 @@ from {}
 Please verify your internet connection and ensure the correct version is specified.",
                 tool, from_url
             ),
+            ErrorKind::EngineStrictConflict {
+                tool,
+                version,
+                range,
+                manifest,
+            } => write!(
+                f,
+                "{} does not satisfy the engines range '{}' in {}, and npm is configured with engine-strict.
+
+Use `volta pin {}@<version>` to select a version in that range, or disable engine-strict in your .npmrc",
+                tool_version(tool, version),
+                range,
+                manifest.display(),
+                tool
+            ),
+            ErrorKind::ExecCommandError { command } => write!(
+                f,
+                "Could not execute command '{}'
+
+Please ensure that the command exists on your system.",
+                command,
+            ),
+            ErrorKind::ExecutableNotFound { package, bins } if bins.is_empty() => write!(
+                f,
+                "Package '{}' does not provide any executables.",
+                package
+            ),
+            ErrorKind::ExecutableNotFound { package, bins } => write!(
+                f,
+                "Could not determine which executable of '{}' to run.
+
+Available executables: {}
+
+Use `volta x --bin <name> {0}` to choose one.",
+                package,
+                bins.join(", ")
+            ),
             ErrorKind::ExecuteHookError { command } => write!(
                 f,
                 "Could not execute hook command: '{}'
@@ -724,13 +1197,13 @@ Please verify the requested tool and version.",
                 f,
                 "Hook configuration includes multiple hook types.
 
-Please include only one of 'bin', 'prefix', or 'template'"
+Please include only one of 'bin', 'prefix', 'template', or 'wasm'"
             ),
             ErrorKind::HookNoFieldsSpecified => write!(
                 f,
                 "Hook configuration includes no hook types.
 
-Please include one of 'bin', 'prefix', or 'template'"
+Please include one of 'bin', 'prefix', 'template', or 'wasm'"
             ),
             ErrorKind::HookPathError { command } => write!(
                 f,
@@ -739,13 +1212,65 @@ Please include one of 'bin', 'prefix', or 'template'"
 Please ensure that the correct command is specified.",
                 command
             ),
-            ErrorKind::InstalledPackageNameError => write!(
+            ErrorKind::HookTimedOut { command, timeout } => write!(
                 f,
-                "Could not determine the name of the package that was just installed.
+                "Hook command '{}' did not finish within {} seconds.
+
+Please check that the command works, or increase `timeoutSeconds` for the hook.",
+                command,
+                timeout.as_secs_f64()
+            ),
+            ErrorKind::ImportNodeError { version, dir } => write!(
+                f,
+                "Could not import Node {} from {}
+
+{}",
+                version,
+                dir.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ImportSourceNotFound { manager, dir } => write!(
+                f,
+                "Could not find the {} directory at {}
+
+Please ensure that {} is installed, or set the environment variable that points to its directory.",
+                manager,
+                dir.display(),
+                manager
+            ),
+            ErrorKind::InsecurePinnedUrl { url } => write!(
+                f,
+                "Could not download from {}
+
+Its host has pinned certificates, so it can only be downloaded from over HTTPS.",
+                url
+            ),
+            ErrorKind::InstalledPackageNameError => write!(
+                f,
+                "Could not determine the name of the package that was just installed.
 
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::Interrupted => write!(
+                f,
+                "Interrupted, so the tools that were being fetched were not installed."
+            ),
+            ErrorKind::InvalidCertificatePin { pin } => write!(
+                f,
+                "Invalid certificate pin: '{}'
+
+Please use the SHA-256 fingerprint of a certificate, or 'spki:' followed by the SHA-256 digest of
+its public key, in hexadecimal.",
+                pin
+            ),
+            ErrorKind::InvalidChecksumPolicy { policy } => write!(
+                f,
+                "Unrecognized checksum mismatch policy: '{}'
+
+Please use either 'refuse' or 'warn'.",
+                policy
+            ),
             ErrorKind::InvalidHookCommand { command } => write!(
                 f,
                 "Invalid hook command: '{}'
@@ -761,6 +1286,13 @@ Please ensure that the command output is valid UTF-8 text.",
                 command
             ),
 
+            ErrorKind::InvalidInventorySelection { tool } => write!(
+                f,
+                "Could not export {} to an inventory archive.
+
+Only Node, npm, and Yarn can be exported, optionally with a version or range, like `node@18`.",
+                tool
+            ),
             ErrorKind::InvalidInvocation {
                 action,
                 name,
@@ -816,6 +1348,20 @@ To {action} the package '{version}', please use an explicit version such as '{ve
                 write!(f, "{}\n\n{}", error, wrapped_cta)
             }
 
+            ErrorKind::InvalidNodeIndexFormat { format } => write!(
+                f,
+                "Unrecognized Node index format: '{}'
+
+Please specify either 'dist' or 'npm' for the format.",
+                format
+            ),
+            ErrorKind::InvalidPinLocation { location } => write!(
+                f,
+                "Unrecognized pin location: '{}'
+
+Please use either 'package.json' or 'volta.json'.",
+                location
+            ),
             ErrorKind::InvalidRegistryFormat { format } => write!(
                 f,
                 "Unrecognized index registry format: '{}'
@@ -824,6 +1370,13 @@ Please specify either 'npm' or 'github' for the format.",
 format
             ),
 
+            ErrorKind::InvalidResolutionPreference { preference } => write!(
+                f,
+                "Unrecognized resolution preference: '{}'
+
+Please use either 'newest' or 'installed-first'.",
+                preference
+            ),
             ErrorKind::InvalidToolName { name, errors } => {
                 let indentation = "    ";
                 let wrapped = match text_width() {
@@ -844,11 +1397,72 @@ format
                     name, call_to_action, formatted_errs
                 )
             }
+            ErrorKind::InventoryArchivePlatformMismatch {
+                file,
+                archive,
+                current,
+            } => write!(
+                f,
+                "Could not import inventory archive
+from {}
+
+The Node versions in it are for {}, but this machine needs {}. Please export them
+on a machine with the same platform.",
+                file.display(),
+                archive,
+                current
+            ),
+            ErrorKind::InventoryChecksumMismatch { file, entry } => write!(
+                f,
+                "Could not import inventory archive
+from {}
+
+The contents of '{}' don't match the manifest. The archive may have been damaged
+while it was copied; please export it again.",
+                file.display(),
+                entry
+            ),
+            ErrorKind::InventoryImageDamaged { tool, version } => write!(
+                f,
+                "Could not export {tool}@{version}, because some of its files are missing or changed.
+
+Please run `volta fetch {tool}@{version}` to repair it, then try again.",
+                tool = tool,
+                version = version
+            ),
             // Note: No CTA as this error is purely informational and shouldn't be exposed to the user
             ErrorKind::LockAcquireError => write!(
                 f,
                 "Unable to acquire lock on Volta directory"
             ),
+            ErrorKind::LockTimeout { seconds } => write!(
+                f,
+                "Timed out after {} seconds waiting for another Volta process to finish.
+
+If another process is still fetching a tool, set VOLTA_LOCK_TIMEOUT to a longer
+number of seconds. Otherwise, please make sure no other Volta process is stuck.",
+                seconds
+            ),
+            ErrorKind::LockfileOutOfDate { tool, spec } => write!(
+                f,
+                "The project pins {}@{}, but volta.lock has no matching entry.
+
+Please run `volta pin --lock {}@{}` to update the lockfile.",
+                tool, spec, tool, spec
+            ),
+            ErrorKind::MigrateNotInteractive => write!(
+                f,
+                "Could not ask before making changes, since there is no terminal to answer in.
+
+Use `volta migrate --yes` to make every change without asking."
+            ),
+            ErrorKind::NewerLayoutError { version } => write!(
+                f,
+                "Your Volta directory has been upgraded by a newer version of Volta (layout v{}).
+
+Please update Volta to the latest version.",
+                version
+            ),
             ErrorKind::NoBundledNpm { command } => write!(
                 f,
                 "Could not detect bundled npm version.
@@ -862,6 +1476,14 @@ Please ensure you have a Node version selected with `volta {} node` (see `volta
 
 Use `volta run --yarn` to select a version (see `volta help run` for more info)."
             ),
+            ErrorKind::NoCurrentVersion { tool } => write!(
+                f,
+                "Could not find a current version of {}.
+
+Use `volta install {}` to select a default version first, or specify a version explicitly.",
+                tool,
+                tool.to_lowercase()
+            ),
             ErrorKind::NoDefaultNodeVersion { tool } => write!(
                 f,
                 "Cannot install {} because the default Node version is not set.
@@ -869,6 +1491,50 @@ Use `volta run --yarn` to select a version (see `volta help run` for more info).
 Use `volta install node` to select a default Node first, then install a {0} version.",
                                 tool
             ),
+            ErrorKind::NoInstalledVersion { tool } => write!(
+                f,
+                "Could not find an installed version of {}.
+
+Use `volta fetch {}` to fetch a version first, or specify a version explicitly.",
+                tool,
+                tool.to_lowercase()
+            ),
+            ErrorKind::NoNvmrcFile => write!(
+                f,
+                "No .nvmrc file found in this directory or its parents.
+
+Please specify a version of Node, e.g. `nvm use 16`."
+            ),
+            ErrorKind::NoPinnedPlatform { manifest } => write!(
+                f,
+                "No pinned Node version found in {}
+
+Strict mode is enabled, so the default platform will not be used in this project.
+Use `volta pin node` to select a version (see `volta help pin` for more info).",
+                manifest.display()
+            ),
+            ErrorKind::NoShellYarn => write!(
+                f,
+                "No Yarn version found for this shell session.
+
+Use `volta use yarn` to select a version, or `volta install yarn` to set a default."
+            ),
+            ErrorKind::NodeBuildFailed { version, command } => write!(
+                f,
+                "Could not build node@{} from source with `{}`
+
+Please check the output of the build command for details.",
+                version, command
+            ),
+            ErrorKind::NodeBuildNotConfigured { version } => write!(
+                f,
+                "Node does not publish pre-built binaries for this platform, so node@{} has to be built from source.
+
+To build Node from source, set `nodeBuildCommand` in your Volta settings, for example:
+
+    \"nodeBuildCommand\": \"./configure --prefix=\\\"$VOLTA_NODE_PREFIX\\\" && make -j4 install\"",
+                version
+            ),
             ErrorKind::NodeVersionNotFound { matching } => write!(
                 f,
                 r#"Could not find Node version matching "{}" in the version registry.
@@ -938,6 +1604,12 @@ Use `volta install` to select a default version of a tool."
                 "Yarn is not available.
 
 Use `volta install yarn` to select a default version (see `volta help install` for more info)."
+            ),
+            ErrorKind::NotInWorkspace => write!(
+                f,
+                "Not in a workspace.
+
+To pin only the current project, run `volta pin` without `--workspace`."
             ),
             ErrorKind::NpmLinkMissingPackage { package } => write!(
                 f,
@@ -967,6 +1639,22 @@ Please verify that the version is correct."#,
 This project is configured to use version {} of npm.",
                 version
             ),
+            ErrorKind::NvmCommandUnsupported { command } => write!(
+                f,
+                "'nvm {}' is not supported by Volta.
+
+Volta's nvm command supports `nvm install`, `nvm use`, and `nvm current`.
+Use `volta help` to see how to manage Node with Volta.",
+                command
+            ),
+            ErrorKind::NvmVersionNotInstalled { version } => write!(
+                f,
+                "No installed version of Node matches '{}'.
+
+Use `nvm install {}` to install it first.",
+                version,
+                version
+            ),
             ErrorKind::PackageInstallFailed { package } => write!(
                 f,
                 "Could not install package '{}'
@@ -995,6 +1683,13 @@ Please ensure the package includes a valid manifest file.",
 Please verify the requested package is correct.",
                 package
             ),
+            ErrorKind::PackageNotInstalled { package } => write!(
+                f,
+                "Package '{}' is not installed.
+
+Use `volta install {0}` to install it.",
+                package
+            ),
             ErrorKind::PackageParseError { file } => write!(
                 f,
                 "Could not parse project manifest
@@ -1040,6 +1735,22 @@ from {}
 Please ensure the file is correctly formatted.",
                 file.display()
             ),
+            ErrorKind::ParseInventoryArchiveError { file } => write!(
+                f,
+                "Could not parse inventory archive
+from {}
+
+Please ensure the file was created by `volta inventory export`.",
+                file.display()
+            ),
+            ErrorKind::ParseLockfileError { file } => write!(
+                f,
+                "Could not parse lockfile
+from {}
+
+Please ensure the file is correctly formatted, or delete it and re-run `volta pin --lock`.",
+                file.display()
+            ),
             ErrorKind::ParseNodeIndexCacheError => write!(
                 f,
                 "Could not parse Node index cache file.
@@ -1082,6 +1793,22 @@ Please ensure the version of Node is correct."
 {}",
                 REPORT_BUG_CTA
             ),
+            ErrorKind::ParseSettingsBundleError { file } => write!(
+                f,
+                "Could not parse settings bundle
+from {}
+
+Please ensure the file was created by `volta export-settings`.",
+                file.display()
+            ),
+            ErrorKind::ParseSettingsError { file } => write!(
+                f,
+                "Could not parse settings file
+from {}
+
+Please ensure the file is correctly formatted.",
+                file.display()
+            ),
             ErrorKind::ParseToolSpecError { tool_spec } => write!(
                 f,
                 "Could not parse tool spec `{}`
@@ -1096,6 +1823,22 @@ Please supply a spec in the format `<tool name>[@<version>]`.",
 {}",
                 tool, PERMISSIONS_CTA
             ),
+            ErrorKind::PrefixInstallUnsupported { tool } => write!(
+                f,
+                "Cannot install {} into a prefix.
+
+Only Node can be installed with `--dest`.",
+                tool
+            ),
+            ErrorKind::ProjectHooksNotAllowed { file } => write!(
+                f,
+                "The project hooks in {} run commands that haven't been allowed.
+
+Hooks that come with a project can run any command, so they have to be reviewed first.
+To review and allow them, run a Volta command (such as `volta list`) in the project from a terminal.
+To allow project hooks without reviewing them, e.g. in CI, set VOLTA_ALLOW_PROJECT_HOOKS.",
+                file.display()
+            ),
             ErrorKind::ProjectLocalBinaryExecError { command } => write!(
                 f,
                 "Could not execute `{}`
@@ -1136,6 +1879,14 @@ at {}
                 "Could not read executable configuration
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadChecksumsError { file } => write!(
+                f,
+                "Could not read the recorded checksums from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1156,11 +1907,43 @@ from {}
 {}",
                 dir.display(), PERMISSIONS_CTA
             ),
+            ErrorKind::ReadHookApprovalsError { file } => write!(
+                f,
+                "Could not read the allowed project hooks from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::ReadHooksError { file } => write!(
                 f,
                 "Could not read hooks file
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadInventoryArchiveError { file } => write!(
+                f,
+                "Could not read inventory archive
+from {}
+
+Please ensure the file exists and is readable.",
+                file.display()
+            ),
+            ErrorKind::ReadInventoryImageError { tool, version } => write!(
+                f,
+                "Could not read the files of {}@{}
+
+{}",
+                tool, version, PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadLockfileError { file } => write!(
+                f,
+                "Could not read lockfile
+from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1203,6 +1986,31 @@ from {}
                 "Could not read default platform file
 from {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadSettingsBundleError { file } => write!(
+                f,
+                "Could not read settings bundle
+from {}
+
+Please ensure the file exists and is readable.",
+                file.display()
+            ),
+            ErrorKind::ReadSettingsError { file } => write!(
+                f,
+                "Could not read settings file
+from {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::ReadShimRecordError { file } => write!(
+                f,
+                "Could not read the record of the shim executable from {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1214,6 +2022,14 @@ from {}
 
 Please ensure you have access to the your environment variables."
             ),
+            ErrorKind::ReadWasmPluginError { module } => write!(
+                f,
+                "Could not read plugin module {}
+
+{}",
+                module.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::RegistryFetchError { tool, from_url } => write!(
                 f,
                 "Could not download {} version registry
@@ -1222,6 +2038,13 @@ from {}
 Please verify your internet connection.",
                 tool, from_url
             ),
+            ErrorKind::RemoteListUnsupported { tool } => write!(
+                f,
+                "Listing remote versions is only supported for Node, not '{}'.
+
+Please use `volta list node --remote`, optionally with a version range like `node@18`.",
+                tool
+            ),
             ErrorKind::RunShimDirectly => write!(
                 f,
                 "'volta-shim' should not be called directly.
@@ -1315,6 +2138,45 @@ To upgrade it, please use the command `{} {0}`"#,
                     package, name, command
                 )
             }
+            ErrorKind::UsageListUnsupported { tool } => write!(
+                f,
+                "Usage is only recorded for Node, npm, and Yarn, not '{}'.
+
+Please use `volta list --usage`, optionally followed by `node`, `npm`, or `yarn`.",
+                tool
+            ),
+            ErrorKind::UseUnsupportedTool { tool } => write!(
+                f,
+                "Could not select {} for this shell session.
+
+Only Node, npm, and Yarn versions can be selected with `volta use`.",
+                tool
+            ),
+            ErrorKind::UseWithoutTools => write!(
+                f,
+                "No tools specified.
+
+Use `volta use node@<version>` to select a version for this shell session, or
+`volta use --clear` to go back to the versions of the project or your default."
+            ),
+            ErrorKind::VersionBlocklisted {
+                tool,
+                version,
+                reason,
+            } => {
+                let reason = match reason {
+                    Some(reason) => format!("\n\nReason: {}", reason),
+                    None => String::new(),
+                };
+                write!(
+                    f,
+                    "{} is blocklisted in your Volta settings.{}
+
+Please choose a different version.",
+                    tool_version(tool, version),
+                    reason
+                )
+            }
             ErrorKind::VersionParseError { version } => write!(
                 f,
                 r#"Could not parse version "{}"
@@ -1322,11 +2184,59 @@ To upgrade it, please use the command `{} {0}`"#,
 Please verify the intended version."#,
                 version
             ),
+            ErrorKind::VoltaDirOwnedByOtherUser { dir } => write!(
+                f,
+                "{} is owned by another user, who could replace the tools that Volta runs.
+
+Please take ownership of it with `sudo chown -R \"$(id -un)\" {}`, or set VOLTA_HOME to a
+directory of your own. To only show a warning, set VOLTA_UNSAFE_PERMISSIONS=warn.",
+                dir.display(),
+                dir.display()
+            ),
+            ErrorKind::VoltaDirWorldWritable { dir } => write!(
+                f,
+                "{} is writable by every user, who could replace the tools that Volta runs.
+
+Please remove their access with `chmod o-w {}`. To only show a warning, set
+VOLTA_UNSAFE_PERMISSIONS=warn.",
+                dir.display(),
+                dir.display()
+            ),
+            ErrorKind::VoltaHomeAlreadySet { home } => write!(
+                f,
+                "Could not use {} as the Volta directory, since another one is already in use.
+
+The Volta directory can only be set before anything uses it.",
+                home.display()
+            ),
+            ErrorKind::WasmPluginError { module, reason } => write!(
+                f,
+                "Plugin {} failed: {}
+
+Please ensure that the plugin exports the functions that Volta calls.",
+                module.display(),
+                reason
+            ),
+            ErrorKind::WasmPluginsNotEnabled { module } => write!(
+                f,
+                "Could not run plugin {}
+
+This build of Volta does not support WebAssembly plugins, which are experimental. Please use a build with the `wasm-plugins` feature.",
+                module.display()
+            ),
             ErrorKind::WriteBinConfigError { file } => write!(
                 f,
                 "Could not write executable configuration
 to {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteChecksumsError { file } => write!(
+                f,
+                "Could not record the checksum of a download in {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1336,6 +2246,47 @@ to {}
                 "Could not write bundled npm version
 to {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteGitHubFileError { file } => write!(
+                f,
+                "Could not write to the GitHub Actions file {}
+
+Please ensure that the job can write to the files provided by the runner.",
+                file.display()
+            ),
+            ErrorKind::WriteHookApprovalsError { file } => write!(
+                f,
+                "Could not record the allowed project hooks in {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteInstallSourceError { file } => write!(
+                f,
+                "Could not record how Volta was installed to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteInstalledVersionsError { file } => write!(
+                f,
+                "Could not write the manifest of installed versions
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteInventoryArchiveError { file } => write!(
+                f,
+                "Could not write inventory archive
+to {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1347,6 +2298,15 @@ to {}
 This is most likely an intermittent failure, please try again.",
                 tool
             ),
+            ErrorKind::WriteLockfileError { file } => write!(
+                f,
+                "Could not write lockfile
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
             ErrorKind::WriteNodeIndexCacheError { file } => write!(
                 f,
                 "Could not write Node index cache
@@ -1379,6 +2339,23 @@ to {}
                 "Could not save platform settings
 to {}
 
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteSettingsBundleError { file } => write!(
+                f,
+                "Could not write settings
+to {}
+
+{}",
+                file.display(),
+                PERMISSIONS_CTA
+            ),
+            ErrorKind::WriteShimRecordError { file } => write!(
+                f,
+                "Could not record the shim executable in {}
+
 {}",
                 file.display(),
                 PERMISSIONS_CTA
@@ -1390,6 +2367,15 @@ to {}
 
 Please ensure you have permissions to edit your environment variables."
             ),
+            #[cfg(target_os = "linux")]
+            ErrorKind::WslWindowsVoltaHome { home } => write!(
+                f,
+                "VOLTA_HOME ({}) belongs to the Windows installation of Volta.
+
+Volta in WSL installs Linux versions of your tools, which can't share a directory with the Windows versions.
+Please set VOLTA_HOME to a directory inside WSL, such as `~/.volta`.",
+                home.display()
+            ),
             ErrorKind::Yarn2NotSupported => write!(
                 f,
                 "Yarn version 2 is not recommended for use, and not supported by Volta.
@@ -1418,17 +2404,22 @@ Please verify that the version is correct."#,
 impl ErrorKind {
     pub fn exit_code(&self) -> ExitCode {
         match self {
+            ErrorKind::AutoDownloadDisabled { .. } => ExitCode::ExecutableNotFound,
             ErrorKind::BinaryAlreadyInstalled { .. } => ExitCode::FileSystemError,
             ErrorKind::BinaryExecError => ExitCode::ExecutionFailure,
+            ErrorKind::BinaryNameCaseConflict { .. } => ExitCode::FileSystemError,
             ErrorKind::BinaryNotFound { .. } => ExitCode::ExecutableNotFound,
             ErrorKind::BuildPathError => ExitCode::EnvironmentError,
             ErrorKind::BypassError { .. } => ExitCode::ExecutionFailure,
             ErrorKind::CannotFetchPackage { .. } => ExitCode::InvalidArguments,
             ErrorKind::CannotPinPackage { .. } => ExitCode::InvalidArguments,
+            ErrorKind::CannotUnpinPackage { .. } => ExitCode::InvalidArguments,
+            ErrorKind::CertificateCheckError { .. } => ExitCode::NetworkError,
+            ErrorKind::CertificatePinMismatch { .. } => ExitCode::NetworkError,
+            ErrorKind::ChecksumChanged { .. } => ExitCode::NetworkError,
             ErrorKind::CompletionsOutFileError { .. } => ExitCode::InvalidArguments,
             ErrorKind::ContainingDirError { .. } => ExitCode::FileSystemError,
             ErrorKind::CouldNotDetermineTool => ExitCode::UnknownError,
-            ErrorKind::CouldNotStartMigration => ExitCode::EnvironmentError,
             ErrorKind::CreateDirError { .. } => ExitCode::FileSystemError,
             ErrorKind::CreateLayoutFileError { .. } => ExitCode::FileSystemError,
             ErrorKind::CreateSharedLinkError { .. } => ExitCode::FileSystemError,
@@ -1438,7 +2429,11 @@ impl ErrorKind {
             ErrorKind::DeleteDirectoryError { .. } => ExitCode::FileSystemError,
             ErrorKind::DeleteFileError { .. } => ExitCode::FileSystemError,
             ErrorKind::DeprecatedCommandError { .. } => ExitCode::InvalidArguments,
+            ErrorKind::DirectoryNotFound { .. } => ExitCode::InvalidArguments,
             ErrorKind::DownloadToolNetworkError { .. } => ExitCode::NetworkError,
+            ErrorKind::EngineStrictConflict { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ExecCommandError { .. } => ExitCode::ExecutionFailure,
+            ErrorKind::ExecutableNotFound { .. } => ExitCode::ExecutableNotFound,
             ErrorKind::ExecuteHookError { .. } => ExitCode::ExecutionFailure,
             ErrorKind::ExtensionCycleError { .. } => ExitCode::ConfigurationError,
             ErrorKind::ExtensionPathError { .. } => ExitCode::FileSystemError,
@@ -1446,17 +2441,42 @@ impl ErrorKind {
             ErrorKind::HookMultipleFieldsSpecified => ExitCode::ConfigurationError,
             ErrorKind::HookNoFieldsSpecified => ExitCode::ConfigurationError,
             ErrorKind::HookPathError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::HookTimedOut { .. } => ExitCode::ExecutionFailure,
+            ErrorKind::ImportNodeError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ImportSourceNotFound { .. } => ExitCode::ConfigurationError,
+            ErrorKind::InsecurePinnedUrl { .. } => ExitCode::ConfigurationError,
             ErrorKind::InstalledPackageNameError => ExitCode::UnknownError,
+            ErrorKind::Interrupted => ExitCode::Interrupted,
+            ErrorKind::InvalidCertificatePin { .. } => ExitCode::ConfigurationError,
+            ErrorKind::InvalidChecksumPolicy { .. } => ExitCode::ConfigurationError,
             ErrorKind::InvalidHookCommand { .. } => ExitCode::ExecutableNotFound,
             ErrorKind::InvalidHookOutput { .. } => ExitCode::ExecutionFailure,
+            ErrorKind::InvalidInventorySelection { .. } => ExitCode::InvalidArguments,
             ErrorKind::InvalidInvocation { .. } => ExitCode::InvalidArguments,
             ErrorKind::InvalidInvocationOfBareVersion { .. } => ExitCode::InvalidArguments,
+            ErrorKind::InvalidNodeIndexFormat { .. } => ExitCode::ConfigurationError,
+            ErrorKind::InvalidPinLocation { .. } => ExitCode::ConfigurationError,
             ErrorKind::InvalidRegistryFormat { .. } => ExitCode::ConfigurationError,
+            ErrorKind::InvalidResolutionPreference { .. } => ExitCode::ConfigurationError,
             ErrorKind::InvalidToolName { .. } => ExitCode::InvalidArguments,
+            ErrorKind::InventoryArchivePlatformMismatch { .. } => ExitCode::EnvironmentError,
+            ErrorKind::InventoryChecksumMismatch { .. } => ExitCode::FileSystemError,
+            ErrorKind::InventoryImageDamaged { .. } => ExitCode::FileSystemError,
             ErrorKind::LockAcquireError => ExitCode::FileSystemError,
+            ErrorKind::LockTimeout { .. } => ExitCode::FileSystemError,
+            ErrorKind::LockfileOutOfDate { .. } => ExitCode::ConfigurationError,
+            ErrorKind::MigrateNotInteractive => ExitCode::InvalidArguments,
+            ErrorKind::NewerLayoutError { .. } => ExitCode::EnvironmentError,
             ErrorKind::NoBundledNpm { .. } => ExitCode::ConfigurationError,
             ErrorKind::NoCommandLineYarn => ExitCode::ConfigurationError,
+            ErrorKind::NoCurrentVersion { .. } => ExitCode::NoVersionMatch,
             ErrorKind::NoDefaultNodeVersion { .. } => ExitCode::ConfigurationError,
+            ErrorKind::NoInstalledVersion { .. } => ExitCode::NoVersionMatch,
+            ErrorKind::NoNvmrcFile => ExitCode::ConfigurationError,
+            ErrorKind::NoPinnedPlatform { .. } => ExitCode::ConfigurationError,
+            ErrorKind::NoShellYarn => ExitCode::ConfigurationError,
+            ErrorKind::NodeBuildFailed { .. } => ExitCode::UnknownError,
+            ErrorKind::NodeBuildNotConfigured { .. } => ExitCode::ConfigurationError,
             ErrorKind::NodeVersionNotFound { .. } => ExitCode::NoVersionMatch,
             ErrorKind::NoHomeEnvironmentVar => ExitCode::EnvironmentError,
             ErrorKind::NoInstallDir => ExitCode::EnvironmentError,
@@ -1468,20 +2488,28 @@ impl ErrorKind {
             ErrorKind::NoShellProfile { .. } => ExitCode::EnvironmentError,
             ErrorKind::NotInPackage => ExitCode::ConfigurationError,
             ErrorKind::NoDefaultYarn => ExitCode::ConfigurationError,
+            ErrorKind::NotInWorkspace => ExitCode::ConfigurationError,
             ErrorKind::NpmLinkMissingPackage { .. } => ExitCode::ConfigurationError,
             ErrorKind::NpmLinkWrongManager { .. } => ExitCode::ConfigurationError,
             ErrorKind::NpmVersionNotFound { .. } => ExitCode::NoVersionMatch,
             ErrorKind::NpxNotAvailable { .. } => ExitCode::ExecutableNotFound,
+            ErrorKind::NvmCommandUnsupported { .. } => ExitCode::InvalidArguments,
+            ErrorKind::NvmVersionNotInstalled { .. } => ExitCode::NoVersionMatch,
             ErrorKind::PackageInstallFailed { .. } => ExitCode::UnknownError,
             ErrorKind::PackageManifestParseError { .. } => ExitCode::ConfigurationError,
             ErrorKind::PackageManifestReadError { .. } => ExitCode::FileSystemError,
             ErrorKind::PackageNotFound { .. } => ExitCode::InvalidArguments,
+            ErrorKind::PackageNotInstalled { .. } => ExitCode::ConfigurationError,
             ErrorKind::PackageParseError { .. } => ExitCode::ConfigurationError,
             ErrorKind::PackageReadError { .. } => ExitCode::FileSystemError,
             ErrorKind::PackageUnpackError => ExitCode::ConfigurationError,
             ErrorKind::PackageWriteError { .. } => ExitCode::FileSystemError,
             ErrorKind::ParseBinConfigError => ExitCode::UnknownError,
             ErrorKind::ParseHooksError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParseInventoryArchiveError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParseLockfileError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParseSettingsBundleError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::ParseSettingsError { .. } => ExitCode::ConfigurationError,
             ErrorKind::ParseToolSpecError { .. } => ExitCode::InvalidArguments,
             ErrorKind::ParseNodeIndexCacheError => ExitCode::UnknownError,
             ErrorKind::ParseNodeIndexError { .. } => ExitCode::NetworkError,
@@ -1490,23 +2518,35 @@ impl ErrorKind {
             ErrorKind::ParsePackageConfigError => ExitCode::UnknownError,
             ErrorKind::ParsePlatformError => ExitCode::ConfigurationError,
             ErrorKind::PersistInventoryError { .. } => ExitCode::FileSystemError,
+            ErrorKind::PrefixInstallUnsupported { .. } => ExitCode::InvalidArguments,
+            ErrorKind::ProjectHooksNotAllowed { .. } => ExitCode::ConfigurationError,
             ErrorKind::ProjectLocalBinaryExecError { .. } => ExitCode::ExecutionFailure,
             ErrorKind::ProjectLocalBinaryNotFound { .. } => ExitCode::FileSystemError,
             ErrorKind::PublishHookBothUrlAndBin => ExitCode::ConfigurationError,
             ErrorKind::PublishHookNeitherUrlNorBin => ExitCode::ConfigurationError,
             ErrorKind::ReadBinConfigDirError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadBinConfigError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadChecksumsError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadDefaultNpmError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadDirError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadHookApprovalsError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadHooksError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadInventoryArchiveError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadInventoryImageError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadLockfileError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadNodeIndexCacheError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadNodeIndexExpiryError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadNpmManifestError => ExitCode::UnknownError,
             ErrorKind::ReadPackageConfigError { .. } => ExitCode::FileSystemError,
             ErrorKind::ReadPlatformError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadSettingsBundleError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadSettingsError { .. } => ExitCode::FileSystemError,
+            ErrorKind::ReadShimRecordError { .. } => ExitCode::FileSystemError,
             #[cfg(windows)]
             ErrorKind::ReadUserPathError => ExitCode::EnvironmentError,
+            ErrorKind::ReadWasmPluginError { .. } => ExitCode::FileSystemError,
             ErrorKind::RegistryFetchError { .. } => ExitCode::NetworkError,
+            ErrorKind::RemoteListUnsupported { .. } => ExitCode::InvalidArguments,
             ErrorKind::RunShimDirectly => ExitCode::InvalidArguments,
             ErrorKind::SetupToolImageError { .. } => ExitCode::FileSystemError,
             ErrorKind::SetToolExecutable { .. } => ExitCode::FileSystemError,
@@ -1519,16 +2559,36 @@ impl ErrorKind {
             ErrorKind::UnpackArchiveError { .. } => ExitCode::UnknownError,
             ErrorKind::UpgradePackageNotFound { .. } => ExitCode::ConfigurationError,
             ErrorKind::UpgradePackageWrongManager { .. } => ExitCode::ConfigurationError,
+            ErrorKind::UsageListUnsupported { .. } => ExitCode::InvalidArguments,
+            ErrorKind::UseUnsupportedTool { .. } => ExitCode::InvalidArguments,
+            ErrorKind::UseWithoutTools => ExitCode::InvalidArguments,
+            ErrorKind::VersionBlocklisted { .. } => ExitCode::NoVersionMatch,
             ErrorKind::VersionParseError { .. } => ExitCode::NoVersionMatch,
+            ErrorKind::VoltaDirOwnedByOtherUser { .. } => ExitCode::EnvironmentError,
+            ErrorKind::VoltaDirWorldWritable { .. } => ExitCode::EnvironmentError,
+            ErrorKind::VoltaHomeAlreadySet { .. } => ExitCode::EnvironmentError,
+            ErrorKind::WasmPluginError { .. } => ExitCode::ConfigurationError,
+            ErrorKind::WasmPluginsNotEnabled { .. } => ExitCode::ConfigurationError,
             ErrorKind::WriteBinConfigError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteChecksumsError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteDefaultNpmError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteGitHubFileError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteHookApprovalsError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteInstallSourceError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteInstalledVersionsError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteInventoryArchiveError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteLauncherError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteLockfileError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteNodeIndexCacheError { .. } => ExitCode::FileSystemError,
             ErrorKind::WriteNodeIndexExpiryError { .. } => ExitCode::FileSystemError,
             ErrorKind::WritePackageConfigError { .. } => ExitCode::FileSystemError,
             ErrorKind::WritePlatformError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteSettingsBundleError { .. } => ExitCode::FileSystemError,
+            ErrorKind::WriteShimRecordError { .. } => ExitCode::FileSystemError,
             #[cfg(windows)]
             ErrorKind::WriteUserPathError => ExitCode::EnvironmentError,
+            #[cfg(target_os = "linux")]
+            ErrorKind::WslWindowsVoltaHome { .. } => ExitCode::EnvironmentError,
             ErrorKind::Yarn2NotSupported => ExitCode::NoVersionMatch,
             ErrorKind::YarnLatestFetchError { .. } => ExitCode::NetworkError,
             ErrorKind::YarnVersionNotFound { .. } => ExitCode::NoVersionMatch,
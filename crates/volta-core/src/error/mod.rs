@@ -2,9 +2,11 @@ use std::error::Error;
 use std::fmt;
 use std::process::exit;
 
+mod category;
 mod kind;
 mod reporter;
 
+pub use category::ErrorCategory;
 pub use kind::ErrorKind;
 pub use reporter::report_error;
 
@@ -45,6 +47,11 @@ impl VoltaError {
     pub fn kind(&self) -> &ErrorKind {
         &self.inner.kind
     }
+
+    /// Get the category of this error, which is stable, unlike its `ErrorKind`
+    pub fn category(&self) -> ErrorCategory {
+        self.inner.kind.category()
+    }
 }
 
 impl fmt::Display for VoltaError {
@@ -67,6 +74,34 @@ impl From<ErrorKind> for VoltaError {
     }
 }
 
+/// A `VoltaError` that can be moved between threads
+///
+/// Source errors aren't required to be `Send`, so only the message of the source is kept
+pub(crate) struct SendableError {
+    kind: ErrorKind,
+    source: Option<String>,
+}
+
+impl From<VoltaError> for SendableError {
+    fn from(error: VoltaError) -> Self {
+        let Inner { kind, source } = *error.inner;
+
+        SendableError {
+            kind,
+            source: source.map(|source| source.to_string()),
+        }
+    }
+}
+
+impl From<SendableError> for VoltaError {
+    fn from(error: SendableError) -> Self {
+        match error.source {
+            Some(source) => VoltaError::from_source(source, error.kind),
+            None => error.kind.into(),
+        }
+    }
+}
+
 /// Trait providing the with_context method to easily convert any Result error into a VoltaError
 pub trait Context<T> {
     fn with_context<F>(self, f: F) -> Fallible<T>
@@ -121,6 +156,10 @@ pub enum ExitCode {
 
     /// The requested executable is not available.
     ExecutableNotFound = 127,
+
+    /// The process was interrupted (e.g. with Ctrl+C), following the shell convention of 128 plus
+    /// the number of `SIGINT`.
+    Interrupted = 130,
 }
 
 impl ExitCode {
@@ -5,6 +5,7 @@ use std::io::Write;
 use std::path::PathBuf;
 
 use super::VoltaError;
+use crate::github::{error_annotation, is_github_actions};
 use crate::layout::volta_home;
 use crate::style::format_error_cause;
 use chrono::Local;
@@ -18,6 +19,11 @@ pub fn report_error(volta_version: &str, err: &VoltaError) {
     let message = err.to_string();
     error!("{}", message);
 
+    if is_github_actions() {
+        // Workflow commands are read from stdout, and shown as annotations on the run
+        println!("{}", error_annotation(&strip_ansi_codes(&message)));
+    }
+
     if let Some(details) = compose_error_details(err) {
         if is_ci() {
             // In CI, we write the error details to the log so that they are available in the CI logs
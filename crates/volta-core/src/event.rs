@@ -47,6 +47,13 @@ pub enum EventKind {
     },
 }
 
+impl Event {
+    /// The number of milliseconds since the epoch when the event was recorded
+    pub fn timestamp(&self) -> u64 {
+        self.timestamp
+    }
+}
+
 impl EventKind {
     pub fn into_event(self, activity_kind: ActivityKind) -> Event {
         Event {
@@ -91,14 +98,40 @@ fn get_error_env() -> ErrorEnv {
     }
 }
 
+/// Receives each event as soon as it is recorded, rather than only when the session ends
+///
+/// This lets programs that embed Volta follow along in the same process, e.g. to show a live
+/// status, while the `publish` hook still receives the whole log at the end.
+pub trait EventSubscriber: Send {
+    fn on_event(&mut self, event: &Event);
+}
+
+impl<F> EventSubscriber for F
+where
+    F: FnMut(&Event) + Send,
+{
+    fn on_event(&mut self, event: &Event) {
+        self(event)
+    }
+}
+
 pub struct EventLog {
     events: Vec<Event>,
+    subscribers: Vec<Box<dyn EventSubscriber>>,
 }
 
 impl EventLog {
     /// Constructs a new 'EventLog'
     pub fn init() -> Self {
-        EventLog { events: Vec::new() }
+        EventLog {
+            events: Vec::new(),
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Adds a subscriber, which receives the events recorded from now on
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber>) {
+        self.subscribers.push(subscriber);
     }
 
     pub fn add_event_start(&mut self, activity_kind: ActivityKind) {
@@ -140,6 +173,9 @@ impl EventLog {
 
     fn add_event(&mut self, event_kind: EventKind, activity_kind: ActivityKind) {
         let event = event_kind.into_event(activity_kind);
+        for subscriber in &mut self.subscribers {
+            subscriber.on_event(&event);
+        }
         self.events.push(event);
     }
 
@@ -158,10 +194,11 @@ impl EventLog {
 #[cfg(test)]
 pub mod tests {
 
-    use super::{EventKind, EventLog};
+    use super::{Event, EventKind, EventLog};
     use crate::error::{ErrorKind, ExitCode};
     use crate::session::ActivityKind;
     use regex::Regex;
+    use std::sync::{Arc, Mutex};
 
     #[test]
     fn test_adding_events() {
@@ -208,4 +245,22 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_subscribers_receive_events_as_recorded() {
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let mut event_log = EventLog::init();
+        event_log.add_event_start(ActivityKind::Current);
+
+        let sink = Arc::clone(&received);
+        event_log.subscribe(Box::new(move |event: &Event| {
+            sink.lock().unwrap().push(event.name.clone());
+        }));
+        assert!(received.lock().unwrap().is_empty());
+
+        event_log.add_event_start(ActivityKind::Install);
+        event_log.add_event_end(ActivityKind::Install, ExitCode::Success);
+        assert_eq!(*received.lock().unwrap(), vec!["install", "install"]);
+        assert_eq!(event_log.events.len(), 3);
+    }
 }
@@ -0,0 +1,120 @@
+//! Provides the removal of Node, npm, and Yarn versions that haven't been used in a while, so
+//! that versions which were only needed once don't take up disk space forever.
+//!
+//! Versions are only ever removed by `volta evict-unused --yes`, never in the background. Volta
+//! only knows the pins of the projects it has run in, and only while they are in the project
+//! cache, so a version that looks unused can still be needed by a project. Listing the versions
+//! first lets the user check them before anything is removed.
+
+use crate::error::Fallible;
+use crate::inventory::{image_usage, package_configs, remove_image, ImageTool};
+use crate::project::{known_pins, KnownPin};
+use crate::session::Session;
+use crate::settings::Settings;
+use crate::sync::VoltaLock;
+use crate::version::{satisfies, VersionSpec};
+use chrono::Utc;
+use log::debug;
+use semver::Version;
+
+/// A version of Node, npm, or Yarn that hasn't been used for longer than the user allows
+pub struct UnusedVersion {
+    pub tool: ImageTool,
+    pub version: Version,
+    /// How many whole days it has been since the version was last used, or installed if it has
+    /// never been run
+    pub idle_days: u64,
+}
+
+/// Finds the Node, npm, and Yarn versions that haven't been used for longer than the
+/// `evictUnusedAfterDays` setting allows
+///
+/// A version is kept if the default toolchain, an installed package, or any project in the
+/// project cache uses it, or if Volta doesn't know when it was installed or last used. Only the
+/// user settings are checked, so that a project can't cause versions to be removed. Without the
+/// setting, no version is unused.
+pub fn unused_versions(session: &mut Session) -> Fallible<Vec<UnusedVersion>> {
+    let max_age = match Settings::user()?.evict_unused_after() {
+        Some(max_age) => max_age,
+        None => return Ok(Vec::new()),
+    };
+    let pins = pinned_versions(session)?;
+
+    let now = Utc::now();
+    let mut unused = Vec::new();
+    for &tool in &ImageTool::ALL {
+        let name = tool.to_string();
+        for version in tool.versions()? {
+            let usage = image_usage(&name, &version);
+            // Versions that were never run are measured from when they were installed
+            let idle = match usage.last_used.or(usage.installed) {
+                Some(time) => now.signed_duration_since(time).to_std().unwrap_or_default(),
+                None => continue,
+            };
+            let pinned = pins
+                .iter()
+                .any(|pin| pin.tool == name && matches(&pin.version, &version));
+            if idle <= max_age || pinned {
+                continue;
+            }
+
+            unused.push(UnusedVersion {
+                tool,
+                version,
+                idle_days: idle.as_secs() / (24 * 60 * 60),
+            });
+        }
+    }
+
+    Ok(unused)
+}
+
+/// Removes the given versions, which were found by `unused_versions`
+pub fn evict(versions: &[UnusedVersion]) -> Fallible<()> {
+    let _lock = VoltaLock::acquire();
+    for unused in versions {
+        debug!(
+            "Removing {}@{}, which hasn't been used in {} days",
+            unused.tool, unused.version, unused.idle_days
+        );
+        remove_image(unused.tool, &unused.version)?;
+    }
+    Ok(())
+}
+
+/// Collects the versions used by the default toolchain, the installed packages, and the projects
+/// that Volta has run in
+fn pinned_versions(session: &mut Session) -> Fallible<Vec<KnownPin>> {
+    let mut pins = known_pins();
+
+    let platforms = session
+        .default_platform()?
+        .cloned()
+        .into_iter()
+        .chain(package_configs()?.into_iter().map(|config| config.platform));
+    for platform in platforms {
+        let versions = vec![
+            ("node", Some(platform.node)),
+            ("npm", platform.npm),
+            ("yarn", platform.yarn),
+        ];
+        for (tool, version) in versions {
+            if let Some(version) = version {
+                pins.push(KnownPin {
+                    tool: tool.into(),
+                    version: VersionSpec::Exact(version),
+                });
+            }
+        }
+    }
+
+    Ok(pins)
+}
+
+fn matches(spec: &VersionSpec, version: &Version) -> bool {
+    match spec {
+        VersionSpec::Exact(exact) => exact == version,
+        VersionSpec::Semver(requirement) => satisfies(requirement, version, true),
+        VersionSpec::None | VersionSpec::Tag(_) => false,
+    }
+}
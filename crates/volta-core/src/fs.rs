@@ -1,16 +1,22 @@
 //! Provides utilities for operating on the filesystem.
 
+use std::borrow::Cow;
 use std::fs::{self, create_dir_all, read_dir, DirEntry, File, Metadata};
-use std::io;
+use std::io::{self, BufRead, BufReader};
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
+use log::debug;
 use retry::delay::Fibonacci;
 use retry::{retry, Error as RetryError, OperationResult};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tempfile::{tempdir_in, NamedTempFile, TempDir};
+use walkdir::WalkDir;
 
 /// Opens a file, creating it if it doesn't exist
 pub fn touch(path: &Path) -> io::Result<File> {
@@ -23,10 +29,103 @@ pub fn touch(path: &Path) -> io::Result<File> {
     File::open(path)
 }
 
+/// Files modified this close to when they were read might have changed while they were being
+/// read, without the modification time showing it, so caches shouldn't rely on their stamps
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// The state of a file (its size and modification time) when it was read, or `None` if the file
+/// didn't exist, so that caches of data read from files can tell when the files change
+#[derive(Serialize, Deserialize, PartialEq)]
+pub(crate) struct FileStamp {
+    path: PathBuf,
+    state: Option<(u64, Duration)>,
+}
+
+impl FileStamp {
+    pub(crate) fn current(path: &Path) -> io::Result<Self> {
+        let state = match fs::metadata(path) {
+            Ok(meta) => {
+                let modified = meta
+                    .modified()?
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default();
+                Some((meta.len(), modified))
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => None,
+            Err(error) => return Err(error),
+        };
+
+        Ok(FileStamp {
+            path: path.to_owned(),
+            state,
+        })
+    }
+
+    /// Determines whether the file exists now, whatever state it was in when it was read
+    pub(crate) fn exists(&self) -> bool {
+        self.path.exists()
+    }
+
+    /// Determines whether the file is still in the same state
+    pub(crate) fn is_current(&self) -> bool {
+        FileStamp::current(&self.path).ok().as_ref() == Some(self)
+    }
+
+    /// Determines whether the file was modified too close to `loaded` (the time at which reading
+    /// it started) for the stamp to be trusted
+    pub(crate) fn is_racy(&self, loaded: SystemTime) -> bool {
+        match self.state {
+            Some((_, modified)) => UNIX_EPOCH + modified + RACY_WINDOW >= loaded,
+            None => false,
+        }
+    }
+}
+
+/// Converts an absolute path into an extended-length (`\\?\`) path on Windows
+///
+/// Extended-length paths aren't limited to `MAX_PATH` (260 characters), which the deeply nested
+/// `node_modules` directories in tool images can easily exceed. Any path derived from the result
+/// (e.g. by joining or walking the directory) is an extended-length path as well. Relative paths,
+/// and all paths on other platforms, are returned unchanged.
+#[cfg(windows)]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    use std::ffi::OsString;
+    use std::path::{Component, Prefix};
+
+    if !path.is_absolute() {
+        return Cow::Borrowed(path);
+    }
+
+    // Extended-length paths are passed to the filesystem as-is, so they can only use backslashes,
+    // which rebuilding the path from its components ensures
+    let normalized: PathBuf = path.components().collect();
+    let mut extended = OsString::from(r"\\?\");
+    match path.components().next() {
+        Some(Component::Prefix(prefix)) => match prefix.kind() {
+            Prefix::Disk(_) => extended.push(normalized.as_os_str()),
+            Prefix::UNC(..) => {
+                // `\\server\share` becomes `\\?\UNC\server\share`
+                extended.push("UNC");
+                extended.push(&normalized.to_string_lossy()[1..]);
+            }
+            _ => return Cow::Borrowed(path),
+        },
+        _ => return Cow::Borrowed(path),
+    }
+
+    Cow::Owned(PathBuf::from(extended))
+}
+
+/// Converts an absolute path into an extended-length path on Windows, which is a no-op here
+#[cfg(unix)]
+pub fn long_path(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
 /// Removes the target directory, if it exists. If the directory doesn't exist, that is treated as
 /// success.
 pub fn remove_dir_if_exists<P: AsRef<Path>>(path: P) -> Fallible<()> {
-    fs::remove_dir_all(&path)
+    fs::remove_dir_all(long_path(path.as_ref()))
         .or_else(ok_if_not_found)
         .with_context(|| ErrorKind::DeleteDirectoryError {
             directory: path.as_ref().to_owned(),
@@ -35,7 +134,7 @@ pub fn remove_dir_if_exists<P: AsRef<Path>>(path: P) -> Fallible<()> {
 
 /// Removes the target file, if it exists. If the file doesn't exist, that is treated as success.
 pub fn remove_file_if_exists<P: AsRef<Path>>(path: P) -> Fallible<()> {
-    fs::remove_file(&path)
+    fs::remove_file(long_path(path.as_ref()))
         .or_else(ok_if_not_found)
         .with_context(|| ErrorKind::DeleteFileError {
             file: path.as_ref().to_owned(),
@@ -66,6 +165,23 @@ pub fn read_file<P: AsRef<Path>>(path: P) -> io::Result<Option<String>> {
     }
 }
 
+/// Returns the SHA-256 digest of a file, as a hex string
+pub fn file_sha256(file: &Path) -> io::Result<String> {
+    let mut hasher = Sha256::new();
+    io::copy(&mut File::open(file)?, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Returns the key for a cache entry that depends on the given values, as the hex SHA-256 digest
+/// of their `bincode` encoding
+///
+/// Unlike `DefaultHasher`, whose output can change between Rust releases, this gives the same key
+/// in every build of Volta, so an entry that one build writes is found by the next.
+pub(crate) fn cache_key<T: Serialize>(values: &T) -> String {
+    let encoded = bincode::serialize(values).expect("Cache keys are made of plain data");
+    hex::encode(Sha256::digest(encoded))
+}
+
 /// Reads the full contents of a directory, eagerly extracting each directory entry
 /// and its metadata and returning an iterator over them. Returns `Error` if any of
 /// these steps fails.
@@ -103,19 +219,53 @@ where
 }
 
 /// Creates a NamedTempFile in the Volta tmp directory
+///
+/// The file gets an unpredictable name and can only be read by the current user. Downloads are
+/// staged here, rather than in the system temp directory, which any user can write to.
 pub fn create_staging_file() -> Fallible<NamedTempFile> {
     let tmp_dir = volta_home()?.tmp_dir();
-    NamedTempFile::new_in(&tmp_dir).with_context(|| ErrorKind::CreateTempFileError {
-        in_dir: tmp_dir.to_owned(),
-    })
+    restrict_to_owner(tmp_dir)
+        .and_then(|()| NamedTempFile::new_in(&tmp_dir))
+        .with_context(|| ErrorKind::CreateTempFileError {
+            in_dir: tmp_dir.to_owned(),
+        })
 }
 
 /// Creates a staging directory in the Volta tmp directory
 pub fn create_staging_dir() -> Fallible<TempDir> {
     let tmp_root = volta_home()?.tmp_dir();
-    tempdir_in(&tmp_root).with_context(|| ErrorKind::CreateTempDirError {
-        in_dir: tmp_root.to_owned(),
-    })
+    restrict_to_owner(tmp_root)
+        .and_then(|()| tempdir_in(&tmp_root))
+        .with_context(|| ErrorKind::CreateTempDirError {
+            in_dir: tmp_root.to_owned(),
+        })
+}
+
+/// Flushes a staging file to disk, then atomically renames it to its destination
+///
+/// Since the tmp directory is inside the Volta directory, the rename never crosses filesystems,
+/// so the destination is never left partially written, even if Volta is interrupted.
+pub fn persist_staging_file(staging: NamedTempFile, dest: &Path) -> io::Result<()> {
+    staging.as_file().sync_all()?;
+    staging.persist(dest)?;
+    Ok(())
+}
+
+/// Removes any access to a directory by other users
+#[cfg(unix)]
+fn restrict_to_owner(dir: &Path) -> io::Result<()> {
+    let mut permissions = fs::metadata(dir)?.permissions();
+    if permissions.mode() & 0o077 != 0 {
+        permissions.set_mode(0o700);
+        fs::set_permissions(dir, permissions)?;
+    }
+    Ok(())
+}
+
+/// Directories in the user's profile are already private to them on Windows
+#[cfg(windows)]
+fn restrict_to_owner(_dir: &Path) -> io::Result<()> {
+    Ok(())
 }
 
 /// Create a file symlink. The `dst` path will be a symbolic link pointing to the `src` path.
@@ -166,6 +316,119 @@ pub fn set_executable(_bin: &Path) -> io::Result<()> {
     Ok(())
 }
 
+/// Replaces files in `dir` with hard links to identical files at the same relative paths in
+/// `reference`, returning the number of files that were linked
+///
+/// Tool images are never modified after they are installed, so images that share files (like the
+/// npm bundled with different Node versions) can share the disk space as well. This is purely an
+/// optimization: if the filesystem doesn't support hard links between the two directories, the
+/// files are left as they are.
+pub fn link_duplicate_files(dir: &Path, reference: &Path) -> u64 {
+    let (dir, reference) = (long_path(dir), long_path(reference));
+    let mut linked = 0;
+
+    for entry in WalkDir::new(&dir).into_iter().filter_map(Result::ok) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        let path = entry.path();
+        let original = match path.strip_prefix(&dir) {
+            Ok(relative) => reference.join(relative),
+            Err(_) => continue,
+        };
+        if !same_contents(path, &original).unwrap_or(false) {
+            continue;
+        }
+
+        let mut link = path.as_os_str().to_owned();
+        link.push(".volta-link");
+        if let Err(error) = fs::hard_link(&original, &link) {
+            debug!("Not linking duplicate files: {}", error);
+            break;
+        }
+        if fs::rename(&link, path).is_err() {
+            let _ = fs::remove_file(&link);
+            continue;
+        }
+        linked += 1;
+    }
+
+    linked
+}
+
+/// Copies a directory tree, skipping the entries (and their contents) for which `include` returns
+/// false, and returns the number of files that were copied
+///
+/// With `link`, files are hard linked rather than copied where the filesystem allows it. Symbolic
+/// links are recreated as they are, so links within the tree keep pointing into the copy.
+pub fn copy_tree<F>(source: &Path, dest: &Path, link: bool, include: F) -> io::Result<u64>
+where
+    F: Fn(&Path) -> bool,
+{
+    let mut copied = 0;
+    let walker = WalkDir::new(source).into_iter().filter_entry(|entry| {
+        entry
+            .path()
+            .strip_prefix(source)
+            .map_or(false, |relative| include(relative))
+    });
+
+    for entry in walker {
+        let entry = entry?;
+        let relative = entry
+            .path()
+            .strip_prefix(source)
+            .expect("Entries are inside the source directory");
+        let target = dest.join(relative);
+        let file_type = entry.file_type();
+
+        if file_type.is_dir() {
+            create_dir_all(&target)?;
+        } else if file_type.is_symlink() {
+            let original = fs::read_link(entry.path())?;
+            if fs::metadata(entry.path()).map_or(false, |meta| meta.is_dir()) {
+                symlink_dir(original, &target)?;
+            } else {
+                symlink_file(original, &target)?;
+            }
+        } else {
+            if !link || fs::hard_link(entry.path(), &target).is_err() {
+                fs::copy(entry.path(), &target)?;
+            }
+            copied += 1;
+        }
+    }
+
+    Ok(copied)
+}
+
+/// Determines whether two regular files have the same contents
+fn same_contents(a: &Path, b: &Path) -> io::Result<bool> {
+    let (meta_a, meta_b) = (fs::symlink_metadata(a)?, fs::symlink_metadata(b)?);
+    if !meta_b.is_file() || meta_a.len() != meta_b.len() {
+        return Ok(false);
+    }
+    if meta_a.permissions() != meta_b.permissions() {
+        return Ok(false);
+    }
+
+    let mut file_a = BufReader::new(File::open(a)?);
+    let mut file_b = BufReader::new(File::open(b)?);
+    loop {
+        let chunk_a = file_a.fill_buf()?;
+        if chunk_a.is_empty() {
+            return Ok(true);
+        }
+        let len = chunk_a.len().min(file_b.fill_buf()?.len());
+        if len == 0 || chunk_a[..len] != file_b.buffer()[..len] {
+            return Ok(false);
+        }
+        file_a.consume(len);
+        file_b.consume(len);
+    }
+}
+
 /// Rename a file or directory to a new name, retrying if the operation fails because of permissions
 ///
 /// Will retry for ~30 seconds with longer and longer delays between each, to allow for virus scan
@@ -178,11 +441,11 @@ where
     // 21 Fibonacci steps starting at 1 ms is ~28 seconds total
     // See https://github.com/rust-lang/rustup/pull/1873 where this was used by Rustup to work around
     // virus scanning file locks
-    let from = from.as_ref();
-    let to = to.as_ref();
+    let from = long_path(from.as_ref());
+    let to = long_path(to.as_ref());
 
     retry(Fibonacci::from_millis(1).take(21), || {
-        match fs::rename(from, to) {
+        match fs::rename(&from, &to) {
             Ok(_) => OperationResult::Ok(()),
             Err(e) => match e.kind() {
                 io::ErrorKind::PermissionDenied => OperationResult::Retry(e),
@@ -195,3 +458,39 @@ where
         RetryError::Internal(message) => io::Error::new(io::ErrorKind::Other, message),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::cache_key;
+
+    #[test]
+    fn cache_keys_are_stable() {
+        // The SHA-256 digest of the `bincode` encoding, which doesn't depend on the Rust release
+        assert_eq!(
+            cache_key(&("a", 1u32)),
+            "f6769f5a1d28801ce6504b3b28b5c81c68347adb92e18866022abec24f6e9f57"
+        );
+        assert_ne!(cache_key(&("a", 1u32)), cache_key(&("a", 2u32)));
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn converts_absolute_paths_to_extended_length() {
+        use super::long_path;
+        use std::path::Path;
+
+        assert_eq!(
+            long_path(Path::new("C:/Users/me/.volta")),
+            Path::new(r"\\?\C:\Users\me\.volta")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\server\share\volta")),
+            Path::new(r"\\?\UNC\server\share\volta")
+        );
+        assert_eq!(
+            long_path(Path::new(r"\\?\C:\already")),
+            Path::new(r"\\?\C:\already")
+        );
+        assert_eq!(long_path(Path::new("relative")), Path::new("relative"));
+    }
+}
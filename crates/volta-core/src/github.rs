@@ -0,0 +1,136 @@
+//! Provides support for running inside GitHub Actions
+//!
+//! Each step of a workflow runs in a new shell that doesn't read any profile scripts, so changes
+//! to the environment of later steps are made by appending to the files that the runner provides
+//! in `GITHUB_PATH`, `GITHUB_ENV`, and `GITHUB_OUTPUT`. Messages printed as workflow commands
+//! (like `::error::`) are shown as annotations on the run.
+
+use std::fs::{read_to_string, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::environment;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::layout::volta_home;
+use crate::platform::Platform;
+use crate::session::Session;
+use crate::tool::{node_distro_arch, NODE_DISTRO_OS};
+use log::{debug, info};
+
+/// Determines whether Volta is running in a GitHub Actions workflow
+pub fn is_github_actions() -> bool {
+    environment::var_os("GITHUB_ACTIONS").map_or(false, |value| value == "true")
+}
+
+/// Prepares the later steps of the job to use Volta, and reports the cache key of the platform
+///
+/// This is called at the end of `volta setup` and `volta install` in a workflow.
+pub fn prepare_job(session: &mut Session) -> Fallible<()> {
+    export_environment()?;
+
+    if let Some(platform) = Platform::current(session)? {
+        let key = set_cache_key(&platform)?;
+        info!("Cache key for actions/cache: {}", key);
+    }
+
+    Ok(())
+}
+
+/// Makes the shims and `VOLTA_HOME` available to the later steps of the job
+fn export_environment() -> Fallible<()> {
+    let home = volta_home()?;
+
+    if let Some(path_file) = runner_file("GITHUB_PATH") {
+        let shim_dir = home.shim_dir().to_string_lossy().into_owned();
+        // The file only grows during a job, so the directory is only added once
+        let existing = read_to_string(&path_file).unwrap_or_default();
+        if !existing.lines().any(|line| line == shim_dir) {
+            append_line(&path_file, &shim_dir)?;
+        }
+    }
+
+    if let Some(env_file) = runner_file("GITHUB_ENV") {
+        append_line(
+            &env_file,
+            &format!("VOLTA_HOME={}", home.root().to_string_lossy()),
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Sets the `cache-key` output of the step to a key that identifies the platform, so that it can
+/// be passed to `actions/cache` to restore the tools of earlier runs
+///
+/// Returns the key, which is also set in the output if the runner provides one.
+fn set_cache_key(platform: &Platform) -> Fallible<String> {
+    let key = cache_key(platform);
+    if let Some(output_file) = runner_file("GITHUB_OUTPUT") {
+        append_line(&output_file, &format!("cache-key={}", key))?;
+    }
+    Ok(key)
+}
+
+/// Formats a message as an error annotation
+pub fn error_annotation(message: &str) -> String {
+    format!("::error title=Volta::{}", escape_data(message))
+}
+
+fn cache_key(platform: &Platform) -> String {
+    let npm = match &platform.npm {
+        Some(npm) => npm.value.to_string(),
+        None => "bundled".into(),
+    };
+    let yarn = match &platform.yarn {
+        Some(yarn) => yarn.value.to_string(),
+        None => "none".into(),
+    };
+
+    format!(
+        "volta-{}-{}-node-{}-npm-{}-yarn-{}",
+        NODE_DISTRO_OS,
+        node_distro_arch(),
+        platform.node.value,
+        npm,
+        yarn
+    )
+}
+
+/// Returns the path of one of the files provided by the runner, if it is set
+fn runner_file(name: &str) -> Option<PathBuf> {
+    let file = environment::var_os(name).filter(|file| !file.is_empty())?;
+    Some(PathBuf::from(file))
+}
+
+fn append_line(file: &Path, line: &str) -> Fallible<()> {
+    debug!("Appending '{}' to {}", line, file.display());
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file)
+        .and_then(|mut file| writeln!(file, "{}", line))
+        .with_context(|| ErrorKind::WriteGitHubFileError {
+            file: file.to_owned(),
+        })
+}
+
+/// Escapes the characters that have a meaning in the data of a workflow command
+fn escape_data(message: &str) -> String {
+    message
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_multiline_annotations() {
+        assert_eq!(
+            error_annotation("Could not find 100% of it.\n\nTry again."),
+            "::error title=Volta::Could not find 100%25 of it.%0A%0ATry again."
+        );
+    }
+}
@@ -0,0 +1,132 @@
+//! Provides a cache of parsed hooks files, so that running a tool doesn't need to read and parse
+//! every hooks file that applies to it each time.
+//!
+//! Each entry records the size and modification time of its hooks file, and is only used while
+//! the file is unchanged, so editing a hooks file takes effect immediately.
+//!
+//! It also holds the output of `bin` hook commands that set `cacheSeconds`, which is reused until
+//! it is older than that.
+
+use std::fs::{create_dir_all, metadata, read_to_string, File};
+use std::io::{self, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use super::serial::RawHookConfig;
+use crate::fs::{cache_key, FileStamp};
+use crate::layout::volta_home;
+use log::debug;
+use tempfile::NamedTempFile;
+
+/// The version of the cached format, which has to change whenever `RawHookConfig` does, since
+/// entries written in an older format can't be told apart from current ones
+const CACHE_FORMAT: u32 = 3;
+
+/// Returns the path to the cache file for the given hooks file
+fn cache_file(hooks_file: &Path) -> Option<PathBuf> {
+    let key = cache_key(&(CACHE_FORMAT, hooks_file.to_string_lossy()));
+
+    let home = volta_home().ok()?;
+    Some(home.hooks_cache_dir().join(key))
+}
+
+/// Reads the parsed hooks file from the cache, if it is still up to date
+pub(super) fn load(hooks_file: &Path) -> Option<RawHookConfig> {
+    let file = File::open(cache_file(hooks_file)?).ok()?;
+    let (source, raw): (FileStamp, RawHookConfig) =
+        match bincode::deserialize_from(BufReader::new(file)) {
+            Ok(entry) => entry,
+            Err(error) => {
+                debug!("Ignoring invalid hooks cache: {}", error);
+                return None;
+            }
+        };
+
+    if source.is_current() {
+        Some(raw)
+    } else {
+        None
+    }
+}
+
+/// Writes a parsed hooks file to the cache
+///
+/// `loaded` is the time at which reading the file started. Failures are only logged, since the
+/// cache is purely an optimization.
+pub(super) fn store(hooks_file: &Path, raw: &RawHookConfig, loaded: SystemTime) {
+    if let Err(error) = try_store(hooks_file, raw, loaded) {
+        debug!(
+            "Unable to write hooks cache for {}: {}",
+            hooks_file.display(),
+            error
+        );
+    }
+}
+
+fn try_store(hooks_file: &Path, raw: &RawHookConfig, loaded: SystemTime) -> io::Result<()> {
+    let source = FileStamp::current(hooks_file)?;
+    if source.is_racy(loaded) {
+        return Ok(());
+    }
+
+    let cache_file = match cache_file(hooks_file) {
+        Some(cache_file) => cache_file,
+        None => return Ok(()),
+    };
+    let dir = cache_file
+        .parent()
+        .expect("File paths always have a parent");
+    create_dir_all(dir)?;
+
+    let contents = bincode::serialize(&(&source, raw))
+        .map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+    let mut staging = NamedTempFile::new_in(dir)?;
+    staging.write_all(&contents)?;
+    staging.persist(cache_file).map_err(|error| error.error)?;
+
+    Ok(())
+}
+
+/// Returns the key under which the output of a `bin` hook command is cached
+pub(super) fn output_key(bin: &str, base_path: &Path, extra_arg: Option<&str>) -> String {
+    cache_key(&(bin, base_path.to_string_lossy(), extra_arg))
+}
+
+fn output_file(key: &str) -> Option<PathBuf> {
+    Some(volta_home().ok()?.hook_output_cache_dir().join(key))
+}
+
+/// Reads the cached output of a `bin` hook command, if it is no older than `ttl`
+pub(super) fn load_output(key: &str, ttl: Duration) -> Option<String> {
+    let file = output_file(key)?;
+    let age = metadata(&file).ok()?.modified().ok()?.elapsed().ok()?;
+    if age > ttl {
+        return None;
+    }
+
+    read_to_string(file).ok()
+}
+
+/// Writes the output of a `bin` hook command to the cache
+///
+/// As with the parsed hooks files, failures are only logged.
+pub(super) fn store_output(key: &str, output: &str) {
+    if let Err(error) = try_store_output(key, output) {
+        debug!("Unable to write hook output cache: {}", error);
+    }
+}
+
+fn try_store_output(key: &str, output: &str) -> io::Result<()> {
+    let file = match output_file(key) {
+        Some(file) => file,
+        None => return Ok(()),
+    };
+    let dir = file.parent().expect("File paths always have a parent");
+    create_dir_all(dir)?;
+
+    let mut staging = NamedTempFile::new_in(dir)?;
+    staging.write_all(output.as_bytes())?;
+    staging.persist(file).map_err(|error| error.error)?;
+
+    Ok(())
+}
@@ -0,0 +1,146 @@
+//! Provides the approval that project hooks need before they can run commands
+//!
+//! A `bin` hook runs a command, and a `wasm` hook runs a WebAssembly module, so a hooks file that
+//! comes with a project, rather than from the user's own Volta directory, could run anything as
+//! soon as Volta is used in that project. As with `direnv allow`, such a file has to be allowed
+//! once, and then again whenever its contents (or the modules it loads) change. Approvals are
+//! recorded by the digest of the file and its modules, keyed by its path.
+//!
+//! The user is only asked from a terminal, and never from a shim, since the terminal belongs to
+//! the tool being run. Where no one can be asked, e.g. in CI, `VOLTA_ALLOW_PROJECT_HOOKS` allows
+//! project hooks without reviewing them.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use super::serial::RawHookConfig;
+use crate::environment;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{create_staging_file, persist_staging_file, read_file};
+use crate::layout::volta_home;
+use atty::Stream;
+use log::debug;
+use sha2::{Digest, Sha256};
+
+/// Set to allow project hooks to run without being reviewed
+const ALLOW_ENV_VAR: &str = "VOLTA_ALLOW_PROJECT_HOOKS";
+
+/// Ensures that a project hooks file doesn't run any commands or modules that the user hasn't
+/// allowed, asking the user to review them if `prompt` is set and there is a terminal to ask from
+pub(super) fn ensure_allowed(hooks_file: &Path, prompt: bool) -> Fallible<()> {
+    let contents = match read_file(hooks_file).with_context(|| ErrorKind::ReadHooksError {
+        file: hooks_file.to_owned(),
+    })? {
+        Some(contents) => contents,
+        None => return Ok(()),
+    };
+    // A file that can't be parsed is reported when the hooks are loaded, and can't run anything
+    let raw: RawHookConfig = match serde_json::from_str(&contents) {
+        Ok(raw) => raw,
+        Err(_) => return Ok(()),
+    };
+    let bins = raw.bins();
+    let modules = raw.wasm_modules();
+    if bins.is_empty() && modules.is_empty() {
+        return Ok(());
+    }
+
+    if environment::var_os(ALLOW_ENV_VAR).is_some() {
+        debug!(
+            "Allowing the project hooks in {} since {} is set",
+            hooks_file.display(),
+            ALLOW_ENV_VAR
+        );
+        return Ok(());
+    }
+
+    let approvals_file = volta_home()?.hook_approvals_file();
+    let mut approvals = load(approvals_file)?;
+    let key = hooks_file.to_string_lossy().into_owned();
+    let digest = digest(hooks_file, &contents, &modules);
+    if approvals.get(&key) == Some(&digest) {
+        return Ok(());
+    }
+
+    // Without a terminal there is no one to ask, so the hooks stay blocked
+    let can_prompt = prompt && atty::is(Stream::Stdin) && atty::is(Stream::Stderr);
+    if !can_prompt || !confirm(hooks_file, &bins, &modules) {
+        return Err(ErrorKind::ProjectHooksNotAllowed {
+            file: hooks_file.to_owned(),
+        }
+        .into());
+    }
+
+    approvals.insert(key, digest);
+    save(approvals_file, &approvals)
+}
+
+/// Digests a hooks file along with the WebAssembly modules that it loads, so that changing one of
+/// the modules has to be allowed again
+fn digest(hooks_file: &Path, contents: &str, modules: &[&str]) -> String {
+    let base_dir = hooks_file
+        .parent()
+        .expect("File paths always have a parent");
+    let mut hasher = Sha256::new();
+    hasher.update(contents.as_bytes());
+    for module in modules {
+        // A module that can't be read can't run either, which is reported when the hook is used
+        if let Ok(bytes) = fs::read(base_dir.join(module)) {
+            hasher.update(&bytes);
+        }
+    }
+    hex::encode(hasher.finalize())
+}
+
+/// Shows the commands and modules that a hooks file runs and asks whether to allow them
+///
+/// The question is written to stderr, since stdout may belong to the tool being run.
+fn confirm(hooks_file: &Path, bins: &[&str], modules: &[&str]) -> bool {
+    eprintln!("The project hooks in {} run:", hooks_file.display());
+    for bin in bins {
+        eprintln!("    {}", bin.trim());
+    }
+    for module in modules {
+        eprintln!("    {} (WebAssembly module)", module);
+    }
+    eprint!("Allow them to run? [y/N] ");
+    let _ = io::stderr().flush();
+
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"),
+        Err(_) => false,
+    }
+}
+
+/// Reads the digests of the allowed hooks files, keyed by their path
+fn load(file: &Path) -> Fallible<BTreeMap<String, String>> {
+    let contents = read_file(file).with_context(|| ErrorKind::ReadHookApprovalsError {
+        file: file.to_owned(),
+    })?;
+
+    match contents {
+        Some(contents) => {
+            serde_json::from_str(&contents).with_context(|| ErrorKind::ReadHookApprovalsError {
+                file: file.to_owned(),
+            })
+        }
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+fn save(file: &Path, approvals: &BTreeMap<String, String>) -> Fallible<()> {
+    let mut staging = create_staging_file()?;
+    // Serializing a map of strings can't fail
+    let json = serde_json::to_string_pretty(approvals).expect("Approvals are serializable");
+    staging
+        .write_all(json.as_bytes())
+        .with_context(|| ErrorKind::WriteHookApprovalsError {
+            file: file.to_owned(),
+        })?;
+    persist_staging_file(staging, file).with_context(|| ErrorKind::WriteHookApprovalsError {
+        file: file.to_owned(),
+    })
+}
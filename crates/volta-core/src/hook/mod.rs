@@ -5,14 +5,18 @@ use std::fs::File;
 use std::iter::once;
 use std::marker::PhantomData;
 use std::path::Path;
+use std::time::SystemTime;
 
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
 use crate::project::Project;
-use crate::tool::{Node, Npm, Tool};
+use crate::tool::{Npm, Tool};
 use lazycell::LazyCell;
 use log::debug;
 
+mod cache;
+mod consent;
+mod plugin;
 pub(crate) mod serial;
 pub mod tool;
 
@@ -40,15 +44,18 @@ impl LazyHookConfig {
     }
 
     /// Forces the loading of the hook configuration from both project-local and user-default hooks
-    pub fn get(&self, project: Option<&Project>) -> Fallible<&HookConfig> {
+    ///
+    /// Project hooks that haven't been allowed yet are only offered to the user for review if
+    /// `prompt` is set.
+    pub fn get(&self, project: Option<&Project>, prompt: bool) -> Fallible<&HookConfig> {
         self.settings
-            .try_borrow_with(|| HookConfig::current(project))
+            .try_borrow_with(|| HookConfig::current(project, prompt))
     }
 }
 
 /// Volta hook configuration
 pub struct HookConfig {
-    node: Option<ToolHooks<Node>>,
+    node: Option<NodeHooks>,
     npm: Option<ToolHooks<Npm>>,
     yarn: Option<YarnHooks>,
     events: Option<EventHooks>,
@@ -66,6 +73,16 @@ pub struct ToolHooks<T: Tool> {
     phantom: PhantomData<T>,
 }
 
+/// Volta hooks for Node
+pub struct NodeHooks {
+    /// The hook for resolving the URL for a distro version
+    pub distro: Option<tool::DistroHook>,
+    /// The hook for resolving the URL for the latest version
+    pub latest: Option<tool::MetadataHook>,
+    /// The hook for resolving the Tool Index URL
+    pub index: Option<tool::NodeIndexHook>,
+}
+
 /// Volta hooks for Yarn
 pub struct YarnHooks {
     /// The hook for resolving the URL for a distro version
@@ -88,6 +105,17 @@ impl<T: Tool> ToolHooks<T> {
     }
 }
 
+impl NodeHooks {
+    /// Extends this NodeHooks with another, giving precendence to the current instance
+    fn merge(self, other: Self) -> Self {
+        Self {
+            distro: self.distro.or(other.distro),
+            latest: self.latest.or(other.latest),
+            index: self.index.or(other.index),
+        }
+    }
+}
+
 impl YarnHooks {
     /// Extends this YarnHooks with another, giving precendence to the current instance
     fn merge(self, other: Self) -> Self {
@@ -110,7 +138,7 @@ macro_rules! merge_hooks {
 }
 
 impl HookConfig {
-    pub fn node(&self) -> Option<&ToolHooks<Node>> {
+    pub fn node(&self) -> Option<&NodeHooks> {
         self.node.as_ref()
     }
 
@@ -128,7 +156,7 @@ impl HookConfig {
 
     /// Returns the current hooks, which are a merge between the user hooks and
     /// the project hooks (if any).
-    fn current(project: Option<&Project>) -> Fallible<Self> {
+    fn current(project: Option<&Project>, prompt: bool) -> Fallible<Self> {
         let default_hooks_file = volta_home()?.default_hooks_file();
 
         // Since `from_paths` expects the paths to be sorted in descending precedence order, we
@@ -136,14 +164,25 @@ impl HookConfig {
         // precedence order)
         // See the per-project configuration RFC for more details on the configuration precedence:
         // https://github.com/volta-cli/rfcs/blob/main/text/0033-per-project-config.md#configuration-precedence
-        let paths = project
+        let project_paths: Vec<_> = project
             .into_iter()
             .flat_map(Project::workspace_roots)
             .map(|root| {
                 let mut path = root.join(".volta");
                 path.push("hooks.json");
-                Cow::Owned(path)
+                path
             })
+            .collect();
+
+        // Unlike the user's own hooks, project hooks come from whoever wrote the project, so
+        // the commands they run have to be allowed first
+        for path in &project_paths {
+            consent::ensure_allowed(path, prompt)?;
+        }
+
+        let paths = project_paths
+            .into_iter()
+            .map(Cow::Owned)
             .chain(once(Cow::Borrowed(default_hooks_file)));
 
         Self::from_paths(paths)
@@ -194,14 +233,24 @@ impl HookConfig {
             return Ok(None);
         }
 
-        let file = File::open(file_path).with_context(|| ErrorKind::ReadHooksError {
-            file: file_path.to_path_buf(),
-        })?;
-
-        let raw: serial::RawHookConfig =
-            serde_json::de::from_reader(file).with_context(|| ErrorKind::ParseHooksError {
-                file: file_path.to_path_buf(),
-            })?;
+        let raw = match cache::load(file_path) {
+            Some(raw) => raw,
+            None => {
+                let loaded = SystemTime::now();
+                let file = File::open(file_path).with_context(|| ErrorKind::ReadHooksError {
+                    file: file_path.to_path_buf(),
+                })?;
+
+                let raw: serial::RawHookConfig =
+                    serde_json::de::from_reader(file).with_context(|| {
+                        ErrorKind::ParseHooksError {
+                            file: file_path.to_path_buf(),
+                        }
+                    })?;
+                cache::store(file_path, &raw, loaded);
+                raw
+            }
+        };
 
         // Invariant: Since we successfully loaded it, we know we have a valid file path
         let hooks_path = file_path.parent().expect("File paths always have a parent");
@@ -240,6 +289,29 @@ impl RegistryFormat {
     }
 }
 
+/// Format of the index used for Node (Dist or Npm)
+///
+/// `Dist` is the format of the `index.json` on nodejs.org, and `Npm` is the format of the npm
+/// Registry, for mirrors that only serve Node as a registry package
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum NodeIndexFormat {
+    Dist,
+    Npm,
+}
+
+impl NodeIndexFormat {
+    pub fn from_str(raw_format: &str) -> Fallible<NodeIndexFormat> {
+        match raw_format {
+            "dist" => Ok(NodeIndexFormat::Dist),
+            "npm" => Ok(NodeIndexFormat::Npm),
+            other => Err(ErrorKind::InvalidNodeIndexFormat {
+                format: String::from(other),
+            }
+            .into()),
+        }
+    }
+}
+
 /// Volta hooks related to events.
 pub struct EventHooks {
     /// The hook for publishing events, if any.
@@ -258,8 +330,9 @@ impl EventHooks {
 #[cfg(test)]
 pub mod tests {
 
-    use super::{tool, HookConfig, Publish, RegistryFormat};
+    use super::{serial, tool, HookConfig, NodeIndexFormat, Publish, RegistryFormat};
     use std::path::PathBuf;
+    use std::time::Duration;
 
     fn fixture_path(fixture_dir: &str) -> PathBuf {
         let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -293,6 +366,7 @@ pub mod tests {
             Some(tool::DistroHook::Bin {
                 bin: "/some/bin/for/node/distro".to_string(),
                 base_path: fixture_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
@@ -300,13 +374,18 @@ pub mod tests {
             Some(tool::MetadataHook::Bin {
                 bin: "/some/bin/for/node/latest".to_string(),
                 base_path: fixture_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Bin {
-                bin: "/some/bin/for/node/index".to_string(),
-                base_path: fixture_dir.clone(),
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Bin {
+                    bin: "/some/bin/for/node/index".to_string(),
+                    base_path: fixture_dir.clone(),
+                    options: tool::BinOptions::default(),
+                },
             })
         );
         assert_eq!(
@@ -314,6 +393,7 @@ pub mod tests {
             Some(tool::DistroHook::Bin {
                 bin: "/bin/to/yarn/distro".to_string(),
                 base_path: fixture_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
@@ -321,6 +401,7 @@ pub mod tests {
             Some(tool::MetadataHook::Bin {
                 bin: "/bin/to/yarn/latest".to_string(),
                 base_path: fixture_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
@@ -330,6 +411,7 @@ pub mod tests {
                 metadata: tool::MetadataHook::Bin {
                     bin: "/bin/to/yarn/index".to_string(),
                     base_path: fixture_dir,
+                    options: tool::BinOptions::default(),
                 },
             })
         );
@@ -339,6 +421,86 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_from_str_bin_options() {
+        let fixture_dir = fixture_path("hooks");
+        let options_file = fixture_dir.join("bin_options.json");
+        let hooks = HookConfig::from_file(&options_file).unwrap().unwrap();
+        let node = hooks.node.unwrap();
+
+        assert_eq!(
+            node.distro,
+            Some(tool::DistroHook::Bin {
+                bin: "/some/bin/for/node/distro".to_string(),
+                base_path: fixture_dir.clone(),
+                options: tool::BinOptions {
+                    timeout: Some(Duration::from_secs(30)),
+                    cache_ttl: Some(Duration::from_secs(3600)),
+                },
+            })
+        );
+        assert_eq!(
+            node.index,
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Bin {
+                    bin: "/some/bin/for/node/index".to_string(),
+                    base_path: fixture_dir,
+                    options: tool::BinOptions::default(),
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_raw_bins() {
+        let bin_file = fixture_path("hooks").join("bins.json");
+        let contents = std::fs::read_to_string(bin_file).unwrap();
+        let raw: serial::RawHookConfig = serde_json::from_str(&contents).unwrap();
+
+        assert_eq!(
+            raw.bins(),
+            vec![
+                "/some/bin/for/node/distro",
+                "/some/bin/for/node/latest",
+                "/some/bin/for/node/index",
+                "/bin/to/yarn/distro",
+                "/bin/to/yarn/latest",
+                "/bin/to/yarn/index",
+                "/events/bin",
+            ]
+        );
+
+        let template_file = fixture_path("hooks").join("templates.json");
+        let contents = std::fs::read_to_string(template_file).unwrap();
+        let raw: serial::RawHookConfig = serde_json::from_str(&contents).unwrap();
+        assert!(raw.bins().is_empty());
+    }
+
+    #[test]
+    fn test_from_str_wasm_plugins() {
+        let fixture_dir = fixture_path("hooks");
+        let wasm_file = fixture_dir.join("wasm.json");
+        let hooks = HookConfig::from_file(&wasm_file).unwrap().unwrap();
+        let node = hooks.node.unwrap();
+        let module = fixture_dir.join("./plugins/mirror.wasm");
+
+        assert_eq!(
+            node.distro,
+            Some(tool::DistroHook::Wasm {
+                module: module.clone()
+            })
+        );
+        assert_eq!(node.latest, None);
+        assert_eq!(
+            node.index,
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Wasm { module },
+            })
+        );
+    }
+
     #[test]
     fn test_from_str_prefixes() {
         let fixture_dir = fixture_path("hooks");
@@ -361,9 +523,10 @@ pub mod tests {
         );
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Prefix(
-                "http://localhost/node/index/".to_string()
-            ))
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Prefix("http://localhost/node/index/".to_string()),
+            })
         );
         assert_eq!(
             yarn.distro,
@@ -407,9 +570,12 @@ pub mod tests {
         );
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Template(
-                "http://localhost/node/index/{{version}}/".to_string()
-            ))
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Template(
+                    "http://localhost/node/index/{{version}}/".to_string()
+                ),
+            })
         );
         assert_eq!(
             yarn.distro,
@@ -434,6 +600,32 @@ pub mod tests {
         );
     }
 
+    #[test]
+    fn test_platform_name_overrides() {
+        let raw: serial::RawHookConfig = serde_json::from_str(&format!(
+            r#"{{
+                "node": {{
+                    "distro": {{ "template": "http://localhost/{{{{os}}}}/{{{{arch}}}}/{{{{libc}}}}/{{{{filename}}}}" }}
+                }},
+                "platforms": {{
+                    "{}": {{ "os": "custom-os", "arch": "custom-arch" }},
+                    "other-platform": {{ "os": "ignored" }}
+                }}
+            }}"#,
+            tool::platform_key()
+        ))
+        .unwrap();
+        let hooks = raw.into_hook_config(&PathBuf::from("/")).unwrap();
+
+        // Only the overrides for the current platform are used, the rest are left as they were
+        assert_eq!(
+            hooks.node.unwrap().distro,
+            Some(tool::DistroHook::Template(
+                "http://localhost/custom-os/custom-arch/{{libc}}/{{filename}}".to_string()
+            ))
+        );
+    }
+
     #[test]
     fn test_from_str_format_npm() {
         let fixture_dir = fixture_path("hooks");
@@ -449,12 +641,13 @@ pub mod tests {
                 metadata: tool::MetadataHook::Prefix("http://localhost/yarn/index/".to_string())
             })
         );
-        // node and npm don't have format
+        // npm doesn't have a format, since its index is always the registry
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Prefix(
-                "http://localhost/node/index/".to_string()
-            ))
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Npm,
+                metadata: tool::MetadataHook::Prefix("http://localhost/node/index/".to_string()),
+            })
         );
         assert_eq!(
             npm.index,
@@ -479,12 +672,13 @@ pub mod tests {
                 metadata: tool::MetadataHook::Prefix("http://localhost/yarn/index/".to_string())
             })
         );
-        // node and npm don't have format
+        // node doesn't have a github format, and npm doesn't have a format at all
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Prefix(
-                "http://localhost/node/index/".to_string()
-            ))
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Prefix("http://localhost/node/index/".to_string()),
+            })
         );
         assert_eq!(
             npm.index,
@@ -514,6 +708,7 @@ pub mod tests {
             Some(tool::DistroHook::Bin {
                 bin: "/some/bin/for/node/distro".to_string(),
                 base_path: project_hooks_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
@@ -521,13 +716,18 @@ pub mod tests {
             Some(tool::MetadataHook::Bin {
                 bin: "/some/bin/for/node/latest".to_string(),
                 base_path: project_hooks_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Bin {
-                bin: "/some/bin/for/node/index".to_string(),
-                base_path: project_hooks_dir,
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Bin {
+                    bin: "/some/bin/for/node/index".to_string(),
+                    base_path: project_hooks_dir,
+                    options: tool::BinOptions::default(),
+                },
             })
         );
         assert_eq!(
@@ -573,6 +773,7 @@ pub mod tests {
             Some(tool::DistroHook::Bin {
                 bin: "/some/bin/for/node/distro".to_string(),
                 base_path: project_hooks_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
@@ -580,13 +781,18 @@ pub mod tests {
             Some(tool::MetadataHook::Bin {
                 bin: "/some/bin/for/node/latest".to_string(),
                 base_path: project_hooks_dir.clone(),
+                options: tool::BinOptions::default(),
             })
         );
         assert_eq!(
             node.index,
-            Some(tool::MetadataHook::Bin {
-                bin: "/some/bin/for/node/index".to_string(),
-                base_path: project_hooks_dir,
+            Some(tool::NodeIndexHook {
+                format: NodeIndexFormat::Dist,
+                metadata: tool::MetadataHook::Bin {
+                    bin: "/some/bin/for/node/index".to_string(),
+                    base_path: project_hooks_dir,
+                    options: tool::BinOptions::default(),
+                },
             })
         );
         assert_eq!(
@@ -0,0 +1,124 @@
+//! Provides hooks that are implemented by WebAssembly modules
+//!
+//! This is experimental, and only available when Volta is built with the `wasm-plugins` feature.
+//! A plugin computes the same URLs as a `bin` hook, but it runs in a sandbox instead of as a
+//! native process: it is given no imports, so it can't read files, use the network, or run
+//! commands, and it is stopped if it runs for too long.
+//!
+//! A plugin exports its `memory` and the following functions. Strings are passed as UTF-8, and a
+//! string that is returned is packed into an `i64` as `(ptr << 32) | len`.
+//!
+//! - `alloc(len: i32) -> i32`, which reserves memory for each argument before a call
+//! - `distro(version_ptr, version_len, filename_ptr, filename_len) -> i64`, for `distro` hooks
+//! - `metadata(filename_ptr, filename_len) -> i64`, for `latest` and `index` hooks
+
+use std::path::Path;
+
+use crate::error::{ErrorKind, Fallible};
+
+/// Resolves the URL of a distro by calling the `distro` function of a plugin
+pub(super) fn resolve_distro(module: &Path, version: &str, filename: &str) -> Fallible<String> {
+    call(module, "distro", &[version, filename])
+}
+
+/// Resolves the URL of a metadata file by calling the `metadata` function of a plugin
+pub(super) fn resolve_metadata(module: &Path, filename: &str) -> Fallible<String> {
+    call(module, "metadata", &[filename])
+}
+
+#[cfg(feature = "wasm-plugins")]
+fn call(module: &Path, function: &str, args: &[&str]) -> Fallible<String> {
+    use crate::error::Context;
+    use log::debug;
+
+    let bytes = std::fs::read(module).with_context(|| ErrorKind::ReadWasmPluginError {
+        module: module.to_owned(),
+    })?;
+
+    debug!("Calling '{}' in plugin {}", function, module.display());
+    let url =
+        sandbox::call(&bytes, function, args).map_err(|reason| ErrorKind::WasmPluginError {
+            module: module.to_owned(),
+            reason,
+        })?;
+
+    Ok(url.trim().to_string())
+}
+
+#[cfg(not(feature = "wasm-plugins"))]
+fn call(module: &Path, _function: &str, _args: &[&str]) -> Fallible<String> {
+    Err(ErrorKind::WasmPluginsNotEnabled {
+        module: module.to_owned(),
+    }
+    .into())
+}
+
+#[cfg(feature = "wasm-plugins")]
+mod sandbox {
+    use std::convert::TryFrom;
+
+    use wasmi::core::Value;
+    use wasmi::{Config, Engine, Linker, Module, Store};
+
+    /// The number of instructions (roughly) that a call may run, which is far more than building
+    /// a URL needs, but stops a plugin that loops forever
+    const FUEL: u64 = 100_000_000;
+
+    pub(super) fn call(bytes: &[u8], function: &str, args: &[&str]) -> Result<String, String> {
+        let mut config = Config::default();
+        config.consume_fuel(true);
+        let engine = Engine::new(&config);
+        let module = Module::new(&engine, bytes).map_err(|error| error.to_string())?;
+
+        let mut store = Store::new(&engine, ());
+        store.add_fuel(FUEL).map_err(|error| error.to_string())?;
+
+        // Nothing is linked, so a plugin that imports any functions can't be instantiated
+        let linker = Linker::<()>::new();
+        let instance = linker
+            .instantiate(&mut store, &module)
+            .and_then(|instance| instance.start(&mut store))
+            .map_err(|error| error.to_string())?;
+
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or_else(|| "the plugin does not export its 'memory'".to_string())?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|error| format!("'alloc': {}", error))?;
+        let func = instance
+            .get_func(&store, function)
+            .ok_or_else(|| format!("the plugin does not export '{}'", function))?;
+
+        let mut params = Vec::new();
+        for arg in args {
+            let len =
+                i32::try_from(arg.len()).map_err(|_| "an argument is too long".to_string())?;
+            let ptr = alloc
+                .call(&mut store, len)
+                .map_err(|error| format!("'alloc': {}", error))?;
+            memory
+                .write(&mut store, ptr as usize, arg.as_bytes())
+                .map_err(|error| error.to_string())?;
+            params.push(Value::I32(ptr));
+            params.push(Value::I32(len));
+        }
+
+        let mut results = [Value::I64(0)];
+        func.call(&mut store, &params, &mut results)
+            .map_err(|error| format!("'{}': {}", function, error))?;
+        let packed = match results[0] {
+            Value::I64(packed) => packed as u64,
+            _ => return Err(format!("'{}' did not return an i64", function)),
+        };
+
+        let ptr = (packed >> 32) as usize;
+        let len = (packed & 0xffff_ffff) as usize;
+        let mut output = vec![0; len];
+        memory
+            .read(&store, ptr, &mut output)
+            .map_err(|error| error.to_string())?;
+
+        String::from_utf8(output).map_err(|_| format!("'{}' did not return UTF-8 text", function))
+    }
+}
@@ -1,11 +1,13 @@
+use std::collections::BTreeMap;
 use std::convert::{TryFrom, TryInto};
 use std::marker::PhantomData;
 use std::path::Path;
+use std::time::Duration;
 
 use super::tool;
-use super::RegistryFormat;
+use super::{NodeIndexFormat, RegistryFormat};
 use crate::error::{ErrorKind, Fallible, VoltaError};
-use crate::tool::{Node, Npm, Tool};
+use crate::tool::{Npm, Tool};
 use serde::{Deserialize, Serialize};
 
 #[derive(Serialize, Deserialize)]
@@ -13,6 +15,14 @@ pub struct RawResolveHook {
     prefix: Option<String>,
     template: Option<String>,
     bin: Option<String>,
+    /// The path of a WebAssembly plugin, relative to the hooks file
+    wasm: Option<String>,
+    /// How long a `bin` command may run, which other kinds of hooks ignore
+    #[serde(rename = "timeoutSeconds")]
+    timeout_seconds: Option<u64>,
+    /// How long the output of a `bin` command is reused, which other kinds of hooks ignore
+    #[serde(rename = "cacheSeconds")]
+    cache_seconds: Option<u64>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -20,7 +30,39 @@ pub struct RawIndexHook {
     prefix: Option<String>,
     template: Option<String>,
     bin: Option<String>,
+    wasm: Option<String>,
     format: Option<String>,
+    #[serde(rename = "timeoutSeconds")]
+    timeout_seconds: Option<u64>,
+    #[serde(rename = "cacheSeconds")]
+    cache_seconds: Option<u64>,
+}
+
+/// Names to substitute for the platform placeholders in templates, for mirrors that don't use
+/// the same platform names as nodejs.org
+#[derive(Default, Serialize, Deserialize)]
+pub struct RawPlatformNames {
+    os: Option<String>,
+    arch: Option<String>,
+    libc: Option<String>,
+}
+
+impl RawPlatformNames {
+    /// Replaces the overridden placeholders, leaving the rest to be resolved with the defaults
+    fn apply(&self, template: String) -> String {
+        let overrides = [
+            (tool::OS_TEMPLATE, &self.os),
+            (tool::ARCH_TEMPLATE, &self.arch),
+            (tool::LIBC_TEMPLATE, &self.libc),
+        ];
+
+        overrides
+            .iter()
+            .fold(template, |template, (placeholder, name)| match name {
+                Some(name) => template.replace(placeholder, name),
+                None => template,
+            })
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -30,75 +72,151 @@ pub struct RawPublishHook {
 }
 
 impl RawResolveHook {
-    fn into_hook<H, P, T, B>(self, to_prefix: P, to_template: T, to_bin: B) -> Fallible<H>
+    fn bin_options(&self) -> tool::BinOptions {
+        tool::BinOptions {
+            timeout: self.timeout_seconds.map(Duration::from_secs),
+            cache_ttl: self.cache_seconds.map(Duration::from_secs),
+        }
+    }
+
+    fn into_hook<H, P, T, B, W>(
+        self,
+        to_prefix: P,
+        to_template: T,
+        to_bin: B,
+        to_wasm: W,
+    ) -> Fallible<H>
     where
         P: FnOnce(String) -> H,
         T: FnOnce(String) -> H,
-        B: FnOnce(String) -> H,
+        B: FnOnce(String, tool::BinOptions) -> H,
+        W: FnOnce(String) -> H,
     {
+        let options = self.bin_options();
         match self {
             RawResolveHook {
                 prefix: Some(prefix),
                 template: None,
                 bin: None,
+                wasm: None,
+                ..
             } => Ok(to_prefix(prefix)),
             RawResolveHook {
                 prefix: None,
                 template: Some(template),
                 bin: None,
+                wasm: None,
+                ..
             } => Ok(to_template(template)),
             RawResolveHook {
                 prefix: None,
                 template: None,
                 bin: Some(bin),
-            } => Ok(to_bin(bin)),
+                wasm: None,
+                ..
+            } => Ok(to_bin(bin, options)),
             RawResolveHook {
                 prefix: None,
                 template: None,
                 bin: None,
+                wasm: Some(wasm),
+                ..
+            } => Ok(to_wasm(wasm)),
+            RawResolveHook {
+                prefix: None,
+                template: None,
+                bin: None,
+                wasm: None,
+                ..
             } => Err(ErrorKind::HookNoFieldsSpecified.into()),
             _ => Err(ErrorKind::HookMultipleFieldsSpecified.into()),
         }
     }
 
-    pub fn into_distro_hook(self, base_dir: &Path) -> Fallible<tool::DistroHook> {
+    pub fn into_distro_hook(
+        self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<tool::DistroHook> {
         self.into_hook(
             tool::DistroHook::Prefix,
-            tool::DistroHook::Template,
-            |bin| tool::DistroHook::Bin {
+            |template| tool::DistroHook::Template(names.apply(template)),
+            |bin, options| tool::DistroHook::Bin {
                 bin,
                 base_path: base_dir.to_owned(),
+                options,
+            },
+            |wasm| tool::DistroHook::Wasm {
+                module: base_dir.join(wasm),
             },
         )
     }
 
-    pub fn into_metadata_hook(self, base_dir: &Path) -> Fallible<tool::MetadataHook> {
+    pub fn into_metadata_hook(
+        self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<tool::MetadataHook> {
         self.into_hook(
             tool::MetadataHook::Prefix,
-            tool::MetadataHook::Template,
-            |bin| tool::MetadataHook::Bin {
+            |template| tool::MetadataHook::Template(names.apply(template)),
+            |bin, options| tool::MetadataHook::Bin {
                 bin,
                 base_path: base_dir.to_owned(),
+                options,
+            },
+            |wasm| tool::MetadataHook::Wasm {
+                module: base_dir.join(wasm),
             },
         )
     }
 }
 
 impl RawIndexHook {
-    pub fn into_index_hook(self, base_dir: &Path) -> Fallible<tool::YarnIndexHook> {
+    fn into_resolve_hook(self) -> RawResolveHook {
+        RawResolveHook {
+            prefix: self.prefix,
+            template: self.template,
+            bin: self.bin,
+            wasm: self.wasm,
+            timeout_seconds: self.timeout_seconds,
+            cache_seconds: self.cache_seconds,
+        }
+    }
+
+    pub fn into_node_index_hook(
+        mut self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<tool::NodeIndexHook> {
+        // use user-specified format, or default to the nodejs.org index
+        let format = match self.format.take() {
+            Some(format_str) => NodeIndexFormat::from_str(&format_str)?,
+            None => NodeIndexFormat::Dist,
+        };
+        Ok(tool::NodeIndexHook {
+            format,
+            metadata: self
+                .into_resolve_hook()
+                .into_metadata_hook(base_dir, names)?,
+        })
+    }
+
+    pub fn into_index_hook(
+        mut self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<tool::YarnIndexHook> {
         // use user-specified format, or default to Github (legacy)
-        let format = match self.format {
+        let format = match self.format.take() {
             Some(format_str) => RegistryFormat::from_str(&format_str)?,
             None => RegistryFormat::Github,
         };
         Ok(tool::YarnIndexHook {
             format,
-            metadata: RawResolveHook {
-                prefix: self.prefix,
-                template: self.template,
-                bin: self.bin,
-            }
-            .into_metadata_hook(base_dir)?,
+            metadata: self
+                .into_resolve_hook()
+                .into_metadata_hook(base_dir, names)?,
         })
     }
 }
@@ -127,10 +245,12 @@ impl TryFrom<RawPublishHook> for super::Publish {
 
 #[derive(Serialize, Deserialize)]
 pub struct RawHookConfig {
-    pub node: Option<RawToolHooks<Node>>,
+    pub node: Option<RawNodeHooks>,
     pub npm: Option<RawToolHooks<Npm>>,
     pub yarn: Option<RawYarnHooks>,
     pub events: Option<RawEventHooks>,
+    /// Overrides for the platform names in templates, keyed by the platform they apply to
+    pub platforms: Option<BTreeMap<String, RawPlatformNames>>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -160,6 +280,14 @@ pub struct RawToolHooks<T: Tool> {
     phantom: PhantomData<T>,
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "node")]
+pub struct RawNodeHooks {
+    pub distro: Option<RawResolveHook>,
+    pub latest: Option<RawResolveHook>,
+    pub index: Option<RawIndexHook>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename = "yarn")]
 pub struct RawYarnHooks {
@@ -169,10 +297,85 @@ pub struct RawYarnHooks {
 }
 
 impl RawHookConfig {
+    /// Lists the commands that the `bin` hooks in this configuration run
+    pub fn bins(&self) -> Vec<&str> {
+        fn bin(hook: &Option<RawResolveHook>) -> Option<&str> {
+            hook.as_ref()?.bin.as_deref()
+        }
+        fn index_bin(hook: &Option<RawIndexHook>) -> Option<&str> {
+            hook.as_ref()?.bin.as_deref()
+        }
+
+        let mut bins = Vec::new();
+        if let Some(node) = &self.node {
+            bins.extend([bin(&node.distro), bin(&node.latest), index_bin(&node.index)]);
+        }
+        if let Some(npm) = &self.npm {
+            bins.extend([bin(&npm.distro), bin(&npm.latest), bin(&npm.index)]);
+        }
+        if let Some(yarn) = &self.yarn {
+            bins.extend([bin(&yarn.distro), bin(&yarn.latest), index_bin(&yarn.index)]);
+        }
+
+        let publish = self
+            .events
+            .as_ref()
+            .and_then(|events| events.publish.as_ref());
+        bins.push(publish.and_then(|publish| publish.bin.as_deref()));
+
+        bins.into_iter().flatten().collect()
+    }
+
+    /// Lists the WebAssembly modules that the `wasm` hooks in this configuration run, relative to
+    /// the hooks file
+    pub fn wasm_modules(&self) -> Vec<&str> {
+        fn wasm(hook: &Option<RawResolveHook>) -> Option<&str> {
+            hook.as_ref()?.wasm.as_deref()
+        }
+        fn index_wasm(hook: &Option<RawIndexHook>) -> Option<&str> {
+            hook.as_ref()?.wasm.as_deref()
+        }
+
+        let mut modules = Vec::new();
+        if let Some(node) = &self.node {
+            modules.extend([
+                wasm(&node.distro),
+                wasm(&node.latest),
+                index_wasm(&node.index),
+            ]);
+        }
+        if let Some(npm) = &self.npm {
+            modules.extend([wasm(&npm.distro), wasm(&npm.latest), wasm(&npm.index)]);
+        }
+        if let Some(yarn) = &self.yarn {
+            modules.extend([
+                wasm(&yarn.distro),
+                wasm(&yarn.latest),
+                index_wasm(&yarn.index),
+            ]);
+        }
+
+        modules.into_iter().flatten().collect()
+    }
+
     pub fn into_hook_config(self, base_dir: &Path) -> Fallible<super::HookConfig> {
-        let node = self.node.map(|n| n.into_tool_hooks(base_dir)).transpose()?;
-        let npm = self.npm.map(|n| n.into_tool_hooks(base_dir)).transpose()?;
-        let yarn = self.yarn.map(|y| y.into_yarn_hooks(base_dir)).transpose()?;
+        let names = self
+            .platforms
+            .and_then(|mut platforms| platforms.remove(&tool::platform_key()))
+            .unwrap_or_default();
+
+        let node = self
+            .node
+            .map(|n| n.into_node_hooks(base_dir, &names))
+            .transpose()?;
+        let npm = self
+            .npm
+            .map(|n| n.into_tool_hooks(base_dir, &names))
+            .transpose()?;
+        let yarn = self
+            .yarn
+            .map(|y| y.into_yarn_hooks(base_dir, &names))
+            .transpose()?;
         let events = self.events.map(|e| e.try_into()).transpose()?;
         Ok(super::HookConfig {
             node,
@@ -184,18 +387,22 @@ impl RawHookConfig {
 }
 
 impl<T: Tool> RawToolHooks<T> {
-    pub fn into_tool_hooks(self, base_dir: &Path) -> Fallible<super::ToolHooks<T>> {
+    pub fn into_tool_hooks(
+        self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<super::ToolHooks<T>> {
         let distro = self
             .distro
-            .map(|d| d.into_distro_hook(base_dir))
+            .map(|d| d.into_distro_hook(base_dir, names))
             .transpose()?;
         let latest = self
             .latest
-            .map(|d| d.into_metadata_hook(base_dir))
+            .map(|d| d.into_metadata_hook(base_dir, names))
             .transpose()?;
         let index = self
             .index
-            .map(|d| d.into_metadata_hook(base_dir))
+            .map(|d| d.into_metadata_hook(base_dir, names))
             .transpose()?;
 
         Ok(super::ToolHooks {
@@ -207,19 +414,50 @@ impl<T: Tool> RawToolHooks<T> {
     }
 }
 
+impl RawNodeHooks {
+    pub fn into_node_hooks(
+        self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<super::NodeHooks> {
+        let distro = self
+            .distro
+            .map(|d| d.into_distro_hook(base_dir, names))
+            .transpose()?;
+        let latest = self
+            .latest
+            .map(|d| d.into_metadata_hook(base_dir, names))
+            .transpose()?;
+        let index = self
+            .index
+            .map(|d| d.into_node_index_hook(base_dir, names))
+            .transpose()?;
+
+        Ok(super::NodeHooks {
+            distro,
+            latest,
+            index,
+        })
+    }
+}
+
 impl RawYarnHooks {
-    pub fn into_yarn_hooks(self, base_dir: &Path) -> Fallible<super::YarnHooks> {
+    pub fn into_yarn_hooks(
+        self,
+        base_dir: &Path,
+        names: &RawPlatformNames,
+    ) -> Fallible<super::YarnHooks> {
         let distro = self
             .distro
-            .map(|d| d.into_distro_hook(base_dir))
+            .map(|d| d.into_distro_hook(base_dir, names))
             .transpose()?;
         let latest = self
             .latest
-            .map(|d| d.into_metadata_hook(base_dir))
+            .map(|d| d.into_metadata_hook(base_dir, names))
             .transpose()?;
         let index = self
             .index
-            .map(|d| d.into_index_hook(base_dir))
+            .map(|d| d.into_index_hook(base_dir, names))
             .transpose()?;
 
         Ok(super::YarnHooks {
@@ -1,36 +1,79 @@
 //! Types representing Volta Tool Hooks.
 
 use std::ffi::OsString;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Stdio;
+use std::process::{Command, Output, Stdio};
+use std::sync::mpsc::{channel, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use crate::command::create_command;
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::hook::RegistryFormat;
-use crate::tool::{NODE_DISTRO_ARCH, NODE_DISTRO_OS};
+use crate::hook::{cache, plugin, NodeIndexFormat, RegistryFormat};
+use crate::tool::{is_musl, node_distro_arch, NODE_DISTRO_OS};
 use cmdline_words_parser::parse_posix;
 use dunce::canonicalize;
 use lazy_static::lazy_static;
 use log::debug;
 use semver::Version;
 
-const ARCH_TEMPLATE: &str = "{{arch}}";
-const OS_TEMPLATE: &str = "{{os}}";
+pub(super) const ARCH_TEMPLATE: &str = "{{arch}}";
+pub(super) const OS_TEMPLATE: &str = "{{os}}";
+pub(super) const LIBC_TEMPLATE: &str = "{{libc}}";
 const VERSION_TEMPLATE: &str = "{{version}}";
 const EXTENSION_TEMPLATE: &str = "{{ext}}";
 const FILENAME_TEMPLATE: &str = "{{filename}}";
 
+/// How often a hook command with a timeout is checked for having finished
+const TIMEOUT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
 lazy_static! {
     static ref REL_PATH: String = format!(".{}", std::path::MAIN_SEPARATOR);
     static ref REL_PATH_PARENT: String = format!("..{}", std::path::MAIN_SEPARATOR);
 }
 
+/// The key for the current platform in the `platforms` overrides of a hooks file, which uses the
+/// nodejs.org names of its OS and architecture (e.g. `linux-x64` or `darwin-arm64`)
+pub(super) fn platform_key() -> String {
+    format!("{}-{}", NODE_DISTRO_OS, node_distro_arch())
+}
+
+/// The C library of the current platform, substituted for `{{libc}}` in templates
+///
+/// This is only meaningful on Linux, where it is either `glibc` or `musl`.
+fn libc() -> &'static str {
+    if is_musl() {
+        "musl"
+    } else if cfg!(target_os = "linux") {
+        "glibc"
+    } else {
+        ""
+    }
+}
+
+/// Settings for running the command of a `bin` hook
+#[derive(PartialEq, Eq, Debug, Default, Clone, Copy)]
+pub struct BinOptions {
+    /// How long the command may run before it is stopped, from `timeoutSeconds`
+    pub timeout: Option<Duration>,
+    /// How long the output of the command is reused for, from `cacheSeconds`
+    pub cache_ttl: Option<Duration>,
+}
+
 /// A hook for resolving the distro URL for a given tool version
 #[derive(PartialEq, Debug)]
 pub enum DistroHook {
     Prefix(String),
     Template(String),
-    Bin { bin: String, base_path: PathBuf },
+    Bin {
+        bin: String,
+        base_path: PathBuf,
+        options: BinOptions,
+    },
+    Wasm {
+        module: PathBuf,
+    },
 }
 
 impl DistroHook {
@@ -41,13 +84,19 @@ impl DistroHook {
         match &self {
             DistroHook::Prefix(prefix) => Ok(format!("{}{}", prefix, filename)),
             DistroHook::Template(template) => Ok(template
-                .replace(ARCH_TEMPLATE, NODE_DISTRO_ARCH)
+                .replace(ARCH_TEMPLATE, node_distro_arch())
                 .replace(OS_TEMPLATE, NODE_DISTRO_OS)
+                .replace(LIBC_TEMPLATE, libc())
                 .replace(EXTENSION_TEMPLATE, extension)
                 .replace(FILENAME_TEMPLATE, filename)
                 .replace(VERSION_TEMPLATE, &version.to_string())),
-            DistroHook::Bin { bin, base_path } => {
-                execute_binary(bin, base_path, Some(version.to_string()))
+            DistroHook::Bin {
+                bin,
+                base_path,
+                options,
+            } => execute_binary(bin, base_path, options, Some(version.to_string())),
+            DistroHook::Wasm { module } => {
+                plugin::resolve_distro(module, &version.to_string(), filename)
             }
         }
     }
@@ -83,7 +132,14 @@ fn calculate_extension(filename: &str) -> Option<&str> {
 pub enum MetadataHook {
     Prefix(String),
     Template(String),
-    Bin { bin: String, base_path: PathBuf },
+    Bin {
+        bin: String,
+        base_path: PathBuf,
+        options: BinOptions,
+    },
+    Wasm {
+        module: PathBuf,
+    },
 }
 
 impl MetadataHook {
@@ -92,14 +148,34 @@ impl MetadataHook {
         match &self {
             MetadataHook::Prefix(prefix) => Ok(format!("{}{}", prefix, filename)),
             MetadataHook::Template(template) => Ok(template
-                .replace(ARCH_TEMPLATE, NODE_DISTRO_ARCH)
+                .replace(ARCH_TEMPLATE, node_distro_arch())
                 .replace(OS_TEMPLATE, NODE_DISTRO_OS)
+                .replace(LIBC_TEMPLATE, libc())
                 .replace(FILENAME_TEMPLATE, filename)),
-            MetadataHook::Bin { bin, base_path } => execute_binary(bin, base_path, None),
+            MetadataHook::Bin {
+                bin,
+                base_path,
+                options,
+            } => execute_binary(bin, base_path, options, None),
+            MetadataHook::Wasm { module } => plugin::resolve_metadata(module, filename),
         }
     }
 }
 
+/// A hook for resolving the URL for the Node index
+#[derive(PartialEq, Debug)]
+pub struct NodeIndexHook {
+    pub format: NodeIndexFormat,
+    pub metadata: MetadataHook,
+}
+
+impl NodeIndexHook {
+    /// Performs resolution of the metadata URL based on the given default file name
+    pub fn resolve(&self, filename: &str) -> Fallible<String> {
+        self.metadata.resolve(filename)
+    }
+}
+
 /// A hook for resolving the URL for the Yarn index
 #[derive(PartialEq, Debug)]
 pub struct YarnIndexHook {
@@ -113,16 +189,56 @@ impl YarnIndexHook {
         match &self.metadata {
             MetadataHook::Prefix(prefix) => Ok(format!("{}{}", prefix, filename)),
             MetadataHook::Template(template) => Ok(template
-                .replace(ARCH_TEMPLATE, NODE_DISTRO_ARCH)
+                .replace(ARCH_TEMPLATE, node_distro_arch())
                 .replace(OS_TEMPLATE, NODE_DISTRO_OS)
+                .replace(LIBC_TEMPLATE, libc())
                 .replace(FILENAME_TEMPLATE, filename)),
-            MetadataHook::Bin { bin, base_path } => execute_binary(bin, base_path, None),
+            MetadataHook::Bin {
+                bin,
+                base_path,
+                options,
+            } => execute_binary(bin, base_path, options, None),
+            MetadataHook::Wasm { module } => plugin::resolve_metadata(module, filename),
         }
     }
 }
 
 /// Execute a shell command and return the trimmed stdout from that command
-fn execute_binary(bin: &str, base_path: &Path, extra_arg: Option<String>) -> Fallible<String> {
+///
+/// If the hook sets `cacheSeconds`, the output is reused until it is that old. It is keyed by the
+/// command and its arguments, so each tool and version that the command is run for is cached
+/// separately.
+fn execute_binary(
+    bin: &str,
+    base_path: &Path,
+    options: &BinOptions,
+    extra_arg: Option<String>,
+) -> Fallible<String> {
+    let cached = options.cache_ttl.map(|ttl| {
+        let key = cache::output_key(bin.trim(), base_path, extra_arg.as_deref());
+        (key, ttl)
+    });
+    if let Some((key, ttl)) = &cached {
+        if let Some(output) = cache::load_output(key, *ttl) {
+            debug!("Using cached output of hook command: {}", bin.trim());
+            return Ok(output);
+        }
+    }
+
+    let output = run_binary(bin, base_path, options.timeout, extra_arg)?;
+    if let Some((key, _)) = &cached {
+        cache::store_output(key, &output);
+    }
+
+    Ok(output)
+}
+
+fn run_binary(
+    bin: &str,
+    base_path: &Path,
+    timeout: Option<Duration>,
+    extra_arg: Option<String>,
+) -> Fallible<String> {
     let mut trimmed = bin.trim().to_string();
     let mut words = parse_posix(&mut trimmed);
     let cmd = match words.next() {
@@ -158,11 +274,14 @@ fn execute_binary(bin: &str, base_path: &Path, extra_arg: Option<String>) -> Fal
         .stderr(Stdio::inherit());
 
     debug!("Running hook command: {:?}", command);
-    let output = command
-        .output()
-        .with_context(|| ErrorKind::ExecuteHookError {
-            command: String::from(bin.trim()),
-        })?;
+    let output = match timeout {
+        Some(timeout) => output_with_timeout(command, timeout, bin.trim())?,
+        None => command
+            .output()
+            .with_context(|| ErrorKind::ExecuteHookError {
+                command: String::from(bin.trim()),
+            })?,
+    };
 
     if !output.status.success() {
         return Err(ErrorKind::HookCommandFailed {
@@ -178,11 +297,74 @@ fn execute_binary(bin: &str, base_path: &Path, extra_arg: Option<String>) -> Fal
     Ok(url.trim().to_string())
 }
 
+/// Runs a hook command, stopping it if it hasn't finished when the timeout runs out
+fn output_with_timeout(mut command: Command, timeout: Duration, bin: &str) -> Fallible<Output> {
+    let mut child = command
+        .spawn()
+        .with_context(|| ErrorKind::ExecuteHookError {
+            command: String::from(bin),
+        })?;
+
+    // The output is read on another thread, so that a command which writes more than fits in the
+    // pipe isn't blocked until it times out
+    let mut stdout = child.stdout.take().expect("Hook command stdout is piped");
+    let (sender, receiver) = channel();
+    thread::spawn(move || {
+        let mut buffer = Vec::new();
+        let _ = sender.send(stdout.read_to_end(&mut buffer).map(|_| buffer));
+    });
+
+    let timed_out = || -> Fallible<Output> {
+        Err(ErrorKind::HookTimedOut {
+            command: String::from(bin),
+            timeout,
+        }
+        .into())
+    };
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        let status = child
+            .try_wait()
+            .with_context(|| ErrorKind::ExecuteHookError {
+                command: String::from(bin),
+            })?;
+        match status {
+            Some(status) => break status,
+            None if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return timed_out();
+            }
+            None => thread::sleep(TIMEOUT_POLL_INTERVAL),
+        }
+    };
+
+    // A process that the command started in the background can keep the pipe open after the
+    // command exits, so the output is only waited for until the same deadline. The reader is left
+    // to finish on its own after that.
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    match receiver.recv_timeout(remaining) {
+        Ok(Ok(stdout)) => Ok(Output {
+            status,
+            stdout,
+            stderr: Vec::new(),
+        }),
+        Err(RecvTimeoutError::Timeout) => timed_out(),
+        _ => Err(ErrorKind::InvalidHookOutput {
+            command: String::from(bin),
+        }
+        .into()),
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
-    use super::{calculate_extension, DistroHook, MetadataHook};
-    use crate::tool::{NODE_DISTRO_ARCH, NODE_DISTRO_OS};
+    use super::{calculate_extension, BinOptions, DistroHook, MetadataHook};
+    use crate::error::ErrorKind;
+    use crate::tool::{node_distro_arch, NODE_DISTRO_OS};
     use semver::Version;
+    use std::time::{Duration, Instant};
 
     #[test]
     fn test_distro_prefix_resolve() {
@@ -208,7 +390,9 @@ pub mod tests {
         // tar.gz format has extra handling, to support a multi-part extension
         let expected = format!(
             "http://localhost/node/{}/{}/{}/tar.gz/node-v1.0.0.tar.gz",
-            NODE_DISTRO_OS, NODE_DISTRO_ARCH, version
+            NODE_DISTRO_OS,
+            node_distro_arch(),
+            version
         );
         assert_eq!(
             hook.resolve(&version, "node-v1.0.0.tar.gz")
@@ -219,7 +403,9 @@ pub mod tests {
         // zip is a standard extension
         let expected = format!(
             "http://localhost/node/{}/{}/{}/zip/node-v1.0.0.zip",
-            NODE_DISTRO_OS, NODE_DISTRO_ARCH, version
+            NODE_DISTRO_OS,
+            node_distro_arch(),
+            version
         );
         assert_eq!(
             hook.resolve(&version, "node-v1.0.0.zip")
@@ -247,7 +433,8 @@ pub mod tests {
         );
         let expected = format!(
             "http://localhost/node/{}/{}/index.json",
-            NODE_DISTRO_OS, NODE_DISTRO_ARCH
+            NODE_DISTRO_OS,
+            node_distro_arch()
         );
 
         assert_eq!(
@@ -256,6 +443,46 @@ pub mod tests {
         );
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_bin_resolve_times_out() {
+        let hook = MetadataHook::Bin {
+            bin: "sleep 5".to_string(),
+            base_path: std::env::temp_dir(),
+            options: BinOptions {
+                timeout: Some(Duration::from_millis(100)),
+                cache_ttl: None,
+            },
+        };
+
+        let error = hook
+            .resolve("index.json")
+            .expect_err("Hook should time out");
+        assert!(matches!(error.kind(), ErrorKind::HookTimedOut { .. }));
+        assert!(error.to_string().contains("within 0.1 seconds"));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_bin_resolve_times_out_with_background_process() {
+        // The background `sleep` keeps the output pipe open after the command itself exits
+        let hook = MetadataHook::Bin {
+            bin: "sh -c 'sleep 5 & echo http://localhost/index.json'".to_string(),
+            base_path: std::env::temp_dir(),
+            options: BinOptions {
+                timeout: Some(Duration::from_millis(500)),
+                cache_ttl: None,
+            },
+        };
+
+        let started = Instant::now();
+        let error = hook
+            .resolve("index.json")
+            .expect_err("Hook should time out");
+        assert!(matches!(error.kind(), ErrorKind::HookTimedOut { .. }));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+
     #[test]
     fn test_calculate_extension() {
         // Handles .tar.* files
@@ -0,0 +1,162 @@
+//! Provides the HTTP client that Volta makes all of its requests with
+//!
+//! Requests are sent with `attohttpc` by default. Programs that embed Volta can provide their own
+//! client with `SessionBuilder::http_client`, e.g. to add the authentication that a corporate
+//! proxy requires, and tests can answer requests from fixtures without running a server. The
+//! client is shared by the whole process, since tools are also fetched from other threads.
+
+use std::io::{self, Read};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::cert_pins::PinnedClient;
+use crate::signal::interrupted;
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+
+pub use archive::{DefaultClient, HeaderMap, HeaderName, HttpClient, HttpResponse, StatusCode};
+
+lazy_static! {
+    static ref CLIENT: RwLock<Arc<dyn HttpClient>> = RwLock::new(Arc::new(DefaultClient));
+}
+
+/// Replaces the client that requests are sent with, for every session in the process
+pub fn set_client(client: Arc<dyn HttpClient>) {
+    *CLIENT.write().unwrap_or_else(PoisonError::into_inner) = client;
+}
+
+/// Returns the client that requests are sent with
+///
+/// Reading the body of a response fails once the process is interrupted, so that downloads are
+/// cancelled (see `signal`). Requests for a host with pinned certificates are sent without the
+/// installed client, so that the certificate of each connection can be checked (see `cert_pins`).
+pub fn client() -> Arc<dyn HttpClient> {
+    let installed = CLIENT
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone();
+    Arc::new(Interruptible(Arc::new(PinnedClient(installed))))
+}
+
+struct Interruptible(Arc<dyn HttpClient>);
+
+impl HttpClient for Interruptible {
+    fn get(&self, url: &str, headers: &[(HeaderName, String)]) -> io::Result<HttpResponse> {
+        let mut response = self.0.get(url, headers)?;
+        response.body = Box::new(InterruptibleBody(response.body));
+        Ok(response)
+    }
+}
+
+struct InterruptibleBody(Box<dyn Read + Send>);
+
+impl Read for InterruptibleBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if interrupted() {
+            return Err(io::Error::new(io::ErrorKind::Other, "interrupted"));
+        }
+        self.0.read(buf)
+    }
+}
+
+/// Sends a `GET` request, treating a response with an unsuccessful status as an error
+pub(crate) fn get(url: &str, headers: &[(HeaderName, String)]) -> io::Result<HttpResponse> {
+    let response = client().get(url, headers)?;
+    if response.status.is_success() {
+        Ok(response)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("HTTP status {} from {}", response.status, url),
+        ))
+    }
+}
+
+/// Sends a `GET` request and reads the body of a successful response as text
+pub(crate) fn get_text(url: &str, headers: &[(HeaderName, String)]) -> io::Result<String> {
+    let mut text = String::new();
+    get(url, headers)?.body.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Sends a `GET` request and parses the body of a successful response as JSON
+pub(crate) fn get_json<T: DeserializeOwned>(
+    url: &str,
+    headers: &[(HeaderName, String)],
+) -> io::Result<T> {
+    let text = get_text(url, headers)?;
+    serde_json::from_str(&text).map_err(io::Error::from)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{
+        get_text, set_client, HeaderMap, HeaderName, HttpClient, HttpResponse, StatusCode, CLIENT,
+    };
+    use std::io;
+    use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+    use lazy_static::lazy_static;
+
+    lazy_static! {
+        static ref CLIENT_LOCK: Mutex<()> = Mutex::new(());
+    }
+
+    /// Replaces the client for the duration of a test, putting the previous one back when dropped
+    ///
+    /// The client is shared by the whole process, so tests that replace it, or that send requests,
+    /// hold this to run one at a time.
+    pub(crate) struct TestClient {
+        previous: Arc<dyn HttpClient>,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl TestClient {
+        pub(crate) fn install(client: Arc<dyn HttpClient>) -> Self {
+            let lock = CLIENT_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            let previous = CLIENT
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone();
+            set_client(client);
+            TestClient {
+                previous,
+                _lock: lock,
+            }
+        }
+
+        pub(crate) fn replace(&self, client: Arc<dyn HttpClient>) {
+            set_client(client);
+        }
+    }
+
+    impl Drop for TestClient {
+        fn drop(&mut self) {
+            set_client(self.previous.clone());
+        }
+    }
+
+    /// Answers every request from memory, with the URL as the body
+    struct EchoClient(StatusCode);
+
+    impl HttpClient for EchoClient {
+        fn get(&self, url: &str, _headers: &[(HeaderName, String)]) -> io::Result<HttpResponse> {
+            Ok(HttpResponse {
+                status: self.0,
+                headers: HeaderMap::new(),
+                body: Box::new(io::Cursor::new(url.as_bytes().to_vec())),
+            })
+        }
+    }
+
+    #[test]
+    fn requests_use_the_installed_client() {
+        let client = TestClient::install(Arc::new(EchoClient(StatusCode::OK)));
+        assert_eq!(
+            get_text("https://example.com/index.json", &[]).unwrap(),
+            "https://example.com/index.json"
+        );
+
+        client.replace(Arc::new(EchoClient(StatusCode::NOT_FOUND)));
+        assert!(get_text("https://example.com/index.json", &[]).is_err());
+    }
+}
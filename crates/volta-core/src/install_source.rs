@@ -0,0 +1,182 @@
+//! Provides detection of how Volta itself was installed
+//!
+//! When Volta's binaries are owned by a package manager like Homebrew, replacing them directly
+//! would leave the package manager out of sync with the files on disk. `volta setup` records
+//! where Volta was installed from, so that `volta upgrade --self` can hand off to the tool that
+//! owns the installation.
+
+use std::fmt;
+use std::fs::write;
+use std::path::{Path, PathBuf};
+use std::process::ExitStatus;
+
+use crate::command::create_command;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::read_file;
+use crate::layout::{volta_home, volta_install};
+use dunce::canonicalize;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+/// The source that Volta's binaries were installed from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum InstallSource {
+    /// The official installer script, or the Windows installer
+    Installer,
+    Homebrew,
+    Scoop,
+    Chocolatey,
+    Winget,
+    /// A package from the operating system's package manager, e.g. apt or Nix
+    System,
+}
+
+/// The provenance recorded by `volta setup`, which only applies to the installation it describes
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InstallRecord {
+    source: InstallSource,
+    install_dir: PathBuf,
+}
+
+impl InstallSource {
+    /// Determines how Volta was installed, preferring the record written by `volta setup`
+    ///
+    /// A record for a different install directory is ignored, since it was written by another
+    /// installation that shares the Volta directory.
+    pub fn current() -> Fallible<Self> {
+        let install_dir = install_dir()?;
+        let file = volta_home()?.install_source_file();
+
+        let recorded = read_file(file)
+            .ok()
+            .flatten()
+            .and_then(|contents| serde_json::from_str::<InstallRecord>(&contents).ok())
+            .filter(|record| record.install_dir == install_dir);
+
+        match recorded {
+            Some(record) => Ok(record.source),
+            None => {
+                debug!("No install source recorded for {}", install_dir.display());
+                Ok(detect(&install_dir))
+            }
+        }
+    }
+
+    /// Detects how Volta was installed and records it in the Volta directory
+    pub fn record() -> Fallible<Self> {
+        let install_dir = install_dir()?;
+        let source = detect(&install_dir);
+        let file = volta_home()?.install_source_file();
+        let record = InstallRecord {
+            source,
+            install_dir,
+        };
+        // Serializing an enum and a path can't fail
+        let json = serde_json::to_string_pretty(&record).expect("Record is serializable");
+
+        write(file, json).with_context(|| ErrorKind::WriteInstallSourceError {
+            file: file.to_owned(),
+        })?;
+        Ok(source)
+    }
+
+    /// Returns the command that upgrades Volta, if it is owned by a known package manager
+    pub fn upgrade_command(self) -> Option<(&'static str, &'static [&'static str])> {
+        match self {
+            InstallSource::Homebrew => Some(("brew", &["upgrade", "volta"])),
+            InstallSource::Scoop => Some(("scoop", &["update", "volta"])),
+            InstallSource::Chocolatey => Some(("choco", &["upgrade", "volta"])),
+            InstallSource::Winget => Some(("winget", &["upgrade", "Volta.Volta"])),
+            InstallSource::Installer | InstallSource::System => None,
+        }
+    }
+
+    /// Runs the package manager's upgrade command, returning its exit status
+    pub fn delegate_upgrade(self) -> Fallible<Option<ExitStatus>> {
+        let (exe, args) = match self.upgrade_command() {
+            Some(command) => command,
+            None => return Ok(None),
+        };
+
+        create_command(exe)
+            .args(args)
+            .status()
+            .map(Some)
+            .with_context(|| ErrorKind::ExecCommandError {
+                command: exe.into(),
+            })
+    }
+}
+
+impl fmt::Display for InstallSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            InstallSource::Installer => "the Volta installer",
+            InstallSource::Homebrew => "Homebrew",
+            InstallSource::Scoop => "Scoop",
+            InstallSource::Chocolatey => "Chocolatey",
+            InstallSource::Winget => "winget",
+            InstallSource::System => "your system's package manager",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Returns the directory of Volta's binaries, with any symlinks (like Homebrew's) resolved
+fn install_dir() -> Fallible<PathBuf> {
+    let root = volta_install()?.root();
+    Ok(canonicalize(root).unwrap_or_else(|_| root.to_owned()))
+}
+
+/// Identifies the package manager that owns an install directory from its location
+fn detect(install_dir: &Path) -> InstallSource {
+    let path = install_dir
+        .to_string_lossy()
+        .replace('\\', "/")
+        .to_lowercase();
+
+    if path.contains("/cellar/") || path.contains("/homebrew/") || path.contains("/linuxbrew/") {
+        InstallSource::Homebrew
+    } else if path.contains("/scoop/apps/") {
+        InstallSource::Scoop
+    } else if path.contains("/chocolatey/") {
+        InstallSource::Chocolatey
+    } else if path.contains("/winget/packages/") {
+        InstallSource::Winget
+    } else if path.starts_with("/nix/store/")
+        || (path.starts_with("/usr/") && !path.starts_with("/usr/local/"))
+    {
+        InstallSource::System
+    } else {
+        InstallSource::Installer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect, InstallSource};
+    use std::path::Path;
+
+    #[test]
+    fn detects_package_managers() {
+        assert_eq!(
+            detect(Path::new("/opt/homebrew/Cellar/volta/1.1.1/bin")),
+            InstallSource::Homebrew
+        );
+        assert_eq!(
+            detect(Path::new("C:\\Users\\me\\scoop\\apps\\volta\\current")),
+            InstallSource::Scoop
+        );
+        assert_eq!(detect(Path::new("/usr/bin")), InstallSource::System);
+        assert_eq!(
+            detect(Path::new("/usr/local/bin")),
+            InstallSource::Installer
+        );
+        assert_eq!(
+            detect(Path::new("/home/me/.volta/bin")),
+            InstallSource::Installer
+        );
+    }
+}
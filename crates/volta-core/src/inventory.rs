@@ -1,51 +1,582 @@
 //! Provides types for working with Volta's _inventory_, the local repository
 //! of available tool versions.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ffi::OsStr;
-use std::path::Path;
+use std::fmt;
+use std::fs::{self, metadata, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use crate::checksums::find_download;
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::read_dir_eager;
+use crate::fs::{
+    create_staging_file, file_sha256, long_path, read_dir_eager, read_file, remove_dir_if_exists,
+    remove_file_if_exists,
+};
 use crate::layout::volta_home;
-use crate::tool::PackageConfig;
-use crate::version::parse_version;
+use crate::session::Session;
+use crate::sync::VoltaLock;
+use crate::tool::verify::{self, Published};
+use crate::tool::{Node, Npm, PackageConfig, Spec, Yarn};
+use crate::version::{parse_version, VersionSpec};
+use chrono::{DateTime, Utc};
+use fs_utils::ensure_containing_dir_exists;
+use lazy_static::lazy_static;
 use log::debug;
 use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use walkdir::WalkDir;
 
+/// The name of the marker file in each complete tool image
+const IMAGE_MARKER: &str = ".volta-image.json";
+
+#[derive(Serialize, Deserialize)]
+struct ImageMarker {
+    sha256: String,
+    files: u64,
+    /// The SHA-256 digest of the path and contents of every file and symlink in the image
+    ///
+    /// Older versions of Volta only recorded the number of files.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    contents: Option<String>,
+    /// The architecture of the binaries in the image, for tools that have native binaries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arch: Option<String>,
+}
+
+/// Usage of a version is recorded at most once in this interval, so that running a tool doesn't
+/// write to the disk every time
+const USAGE_RESOLUTION: Duration = Duration::from_secs(60 * 60);
+
+/// Image directories modified this close to a scan might have changed during it, without the
+/// modification time showing it, so their versions aren't recorded
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// The tools that are unpacked into images in the inventory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageTool {
+    Node,
+    Npm,
+    Yarn,
+}
+
+impl ImageTool {
+    pub(crate) const ALL: [ImageTool; 3] = [ImageTool::Node, ImageTool::Npm, ImageTool::Yarn];
+
+    pub(crate) fn versions(self) -> Fallible<BTreeSet<Version>> {
+        match self {
+            ImageTool::Node => node_versions(),
+            ImageTool::Npm => npm_versions(),
+            ImageTool::Yarn => yarn_versions(),
+        }
+    }
+
+    pub(crate) fn available(self, version: &Version) -> Fallible<bool> {
+        match self {
+            ImageTool::Node => node_available(version),
+            ImageTool::Npm => npm_available(version),
+            ImageTool::Yarn => yarn_available(version),
+        }
+    }
+
+    pub(crate) fn record<F>(self, version: &Version, install: F) -> Fallible<()>
+    where
+        F: FnOnce() -> Fallible<()>,
+    {
+        match self {
+            ImageTool::Node => record_node_image(version, install),
+            ImageTool::Npm => record_npm_image(version, install),
+            ImageTool::Yarn => record_yarn_image(version, install),
+        }
+    }
+
+    pub(crate) fn image_dir(self, version: &Version) -> Fallible<PathBuf> {
+        let home = volta_home()?;
+        let version = version.to_string();
+        Ok(match self {
+            ImageTool::Node => home.node_image_dir(&version),
+            ImageTool::Npm => home.npm_image_dir(&version),
+            ImageTool::Yarn => home.yarn_image_dir(&version),
+        })
+    }
+
+    /// The archive that a version was unpacked from, which is kept in the inventory
+    pub(crate) fn archive_file(self, version: &Version) -> Fallible<PathBuf> {
+        let home = volta_home()?;
+        Ok(match self {
+            ImageTool::Node => home
+                .node_inventory_dir()
+                .join(Node::archive_filename(version)),
+            ImageTool::Npm => home
+                .npm_inventory_dir()
+                .join(Npm::archive_filename(&version.to_string())),
+            ImageTool::Yarn => home
+                .yarn_inventory_dir()
+                .join(Yarn::archive_filename(&version.to_string())),
+        })
+    }
+
+    /// The files that Volta keeps in the inventory for a version, which may not all exist
+    pub(crate) fn inventory_files(self, version: &Version) -> Fallible<Vec<PathBuf>> {
+        let mut files = vec![self.archive_file(version)?];
+        if self == ImageTool::Node {
+            files.push(volta_home()?.node_npm_version_file(&version.to_string()));
+        }
+        Ok(files)
+    }
+
+    fn spec(self, version: &Version) -> Spec {
+        let version = VersionSpec::Exact(version.clone());
+        match self {
+            ImageTool::Node => Spec::Node(version),
+            ImageTool::Npm => Spec::Npm(version),
+            ImageTool::Yarn => Spec::Yarn(version),
+        }
+    }
+}
+
+impl fmt::Display for ImageTool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ImageTool::Node => "node",
+            ImageTool::Npm => "npm",
+            ImageTool::Yarn => "yarn",
+        })
+    }
+}
+
 /// Checks if a given Node version image is available on the local machine
+#[cfg(not(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+)))]
 pub fn node_available(version: &Version) -> Fallible<bool> {
-    volta_home().map(|home| {
-        home.node_image_root_dir()
-            .join(version.to_string())
-            .exists()
-    })
+    volta_home().map(|home| image_complete(&home.node_image_dir(&version.to_string())))
+}
+
+/// Checks if a given Node version image is available on the local machine
+///
+/// On these platforms, an image can also hold the x64 binaries, e.g. if it was installed by an
+/// x64 build of Volta running under emulation. Those images are replaced with the native binaries
+/// once they exist for that version, so only images of the expected architecture are available.
+#[cfg(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+))]
+pub fn node_available(version: &Version) -> Fallible<bool> {
+    let image = volta_home()?.node_image_dir(&version.to_string());
+    if !image_complete(&image) {
+        return Ok(false);
+    }
+
+    let expected = crate::tool::Node::distro_arch(version);
+    match image_arch(&image) {
+        Some(arch) if arch != expected => {
+            debug!(
+                "Node {} image has {} binaries, rather than {}",
+                version, arch, expected
+            );
+            Ok(false)
+        }
+        _ => Ok(true),
+    }
 }
 
 /// Collects a set of all Node versions fetched on the local machine
 pub fn node_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.node_image_root_dir()))
+    volta_home().and_then(|home| recorded_versions("node", home.node_image_root_dir()))
+}
+
+/// Runs `install` to add the image for a Node version, recording the version in the manifest
+pub(crate) fn record_node_image<F>(version: &Version, install: F) -> Fallible<()>
+where
+    F: FnOnce() -> Fallible<()>,
+{
+    let home = volta_home()?;
+    record_image("node", home.node_image_root_dir(), version, install)
 }
 
 /// Checks if a given npm version image is available on the local machine
 pub fn npm_available(version: &Version) -> Fallible<bool> {
-    volta_home().map(|home| home.npm_image_dir(&version.to_string()).exists())
+    volta_home().map(|home| image_complete(&home.npm_image_dir(&version.to_string())))
 }
 
 /// Collects a set of all npm versions fetched on the local machine
 pub fn npm_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.npm_image_root_dir()))
+    volta_home().and_then(|home| recorded_versions("npm", home.npm_image_root_dir()))
+}
+
+/// Runs `install` to add the image for an npm version, recording the version in the manifest
+pub(crate) fn record_npm_image<F>(version: &Version, install: F) -> Fallible<()>
+where
+    F: FnOnce() -> Fallible<()>,
+{
+    let home = volta_home()?;
+    record_image("npm", home.npm_image_root_dir(), version, install)
 }
 
 /// Checks if a given Yarn version image is available on the local machine
 pub fn yarn_available(version: &Version) -> Fallible<bool> {
-    volta_home().map(|home| home.yarn_image_dir(&version.to_string()).exists())
+    volta_home().map(|home| image_complete(&home.yarn_image_dir(&version.to_string())))
 }
 
 /// Collects a set of all Yarn versions fetched on the local machine
 pub fn yarn_versions() -> Fallible<BTreeSet<Version>> {
-    volta_home().and_then(|home| read_versions(home.yarn_image_root_dir()))
+    volta_home().and_then(|home| recorded_versions("yarn", home.yarn_image_root_dir()))
+}
+
+/// Runs `install` to add the image for a Yarn version, recording the version in the manifest
+pub(crate) fn record_yarn_image<F>(version: &Version, install: F) -> Fallible<()>
+where
+    F: FnOnce() -> Fallible<()>,
+{
+    let home = volta_home()?;
+    record_image("yarn", home.yarn_image_root_dir(), version, install)
+}
+
+/// Checks that a tool image still contains all of the files it was unpacked with, unchanged
+///
+/// This reads the whole image, so unlike the `*_available` checks, it also catches images that
+/// were damaged after they were installed.
+pub fn image_intact(image: &Path) -> bool {
+    image_problem(image).is_none()
+}
+
+/// Finds how an image differs from what was unpacked, if it does
+fn image_problem(image: &Path) -> Option<ImageProblem> {
+    let marker = match read_file(image.join(IMAGE_MARKER)) {
+        Ok(Some(contents)) => contents,
+        _ => return Some(ImageProblem::Incomplete),
+    };
+    let marker = match serde_json::from_str::<ImageMarker>(&marker) {
+        Ok(marker) => marker,
+        Err(_) => return Some(ImageProblem::Incomplete),
+    };
+
+    // Counting is much cheaper than hashing, and catches most damage on its own
+    if count_files(image) != marker.files {
+        return Some(ImageProblem::Incomplete);
+    }
+    match marker.contents {
+        Some(expected) => match contents_sha256(image) {
+            Ok(actual) if actual == expected => None,
+            _ => Some(ImageProblem::Modified),
+        },
+        None => None,
+    }
+}
+
+/// Writes the marker into a fully unpacked tool image, recording the checksum of the archive it
+/// was unpacked from, the number and digest of the files in it, and the architecture of its
+/// binaries (if any)
+///
+/// The marker has to be written before the image is moved into place, so that an image is only
+/// ever considered available once it is complete.
+pub(crate) fn mark_image_complete(
+    image: &Path,
+    sha256: String,
+    arch: Option<&str>,
+) -> io::Result<()> {
+    let marker = ImageMarker {
+        sha256,
+        files: count_files(image),
+        contents: Some(contents_sha256(image)?),
+        arch: arch.map(String::from),
+    };
+    let contents = serde_json::to_string(&marker)?;
+    fs::write(image.join(IMAGE_MARKER), contents)
+}
+
+/// Checks that an image can be used, without reading all of it
+///
+/// The marker is only written once an image is fully unpacked. Volta versions that didn't write
+/// markers also only moved images into place once they were unpacked, so an image without a
+/// marker is treated as complete as long as it isn't empty. `volta verify` and explicit fetches
+/// still check those images fully, with `image_intact`.
+fn image_complete(image: &Path) -> bool {
+    if image.join(IMAGE_MARKER).is_file() {
+        return true;
+    }
+
+    match fs::read_dir(image) {
+        Ok(mut entries) => entries.next().is_some(),
+        Err(_) => false,
+    }
+}
+
+/// Returns the architecture recorded in the marker of an image
+///
+/// Images installed by older versions of Volta don't record their architecture.
+#[cfg(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+))]
+fn image_arch(image: &Path) -> Option<String> {
+    let marker = read_file(image.join(IMAGE_MARKER)).ok()??;
+    serde_json::from_str::<ImageMarker>(&marker).ok()?.arch
+}
+
+/// Returns the SHA-256 digest of the archive that an image was unpacked from, and when the image
+/// was installed, as recorded by its marker
+///
+/// Images that were installed before markers were written have neither.
+pub fn image_provenance(image: &Path) -> (Option<String>, Option<DateTime<Utc>>) {
+    let marker_file = image.join(IMAGE_MARKER);
+    let sha256 = read_file(&marker_file)
+        .ok()
+        .flatten()
+        .and_then(|marker| serde_json::from_str::<ImageMarker>(&marker).ok())
+        .map(|marker| marker.sha256);
+    let installed = metadata(&marker_file)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .map(DateTime::from);
+
+    (sha256, installed)
+}
+
+/// Returns when a package tool was installed, from the time its config was written
+pub fn package_installed(name: &str) -> Option<DateTime<Utc>> {
+    let config_file = volta_home().ok()?.default_package_config_file(name);
+    let modified = metadata(config_file).and_then(|metadata| metadata.modified());
+    modified.ok().map(DateTime::from)
+}
+
+/// When a version of Node, npm, or Yarn was installed, and when it was last run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ImageUsage {
+    /// Unknown for images that were installed before markers were written
+    pub installed: Option<DateTime<Utc>>,
+    /// Unknown for images that haven't been run since Volta started recording their use
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Returns when a version of `tool` (`node`, `npm`, or `yarn`) was installed and last used
+pub fn image_usage(tool: &str, version: &Version) -> ImageUsage {
+    let home = match volta_home() {
+        Ok(home) => home,
+        Err(_) => return ImageUsage::default(),
+    };
+    let version = version.to_string();
+    let image = match tool {
+        "node" => home.node_image_dir(&version),
+        "npm" => home.npm_image_dir(&version),
+        "yarn" => home.yarn_image_dir(&version),
+        _ => return ImageUsage::default(),
+    };
+
+    ImageUsage {
+        installed: image_provenance(&image).1,
+        last_used: read_last_used(&home.image_usage_file(tool, &version)),
+    }
+}
+
+/// Records that a version of `tool` is being run
+///
+/// Usage is only used for reporting and cleanup, so failing to record it isn't an error.
+pub fn record_usage(tool: &str, version: &Version) {
+    let file = match volta_home() {
+        Ok(home) => home.image_usage_file(tool, &version.to_string()),
+        Err(_) => return,
+    };
+    let now = Utc::now();
+    if let Some(last_used) = read_last_used(&file) {
+        let elapsed = now.signed_duration_since(last_used).to_std();
+        if matches!(elapsed, Ok(elapsed) if elapsed < USAGE_RESOLUTION) {
+            return;
+        }
+    }
+
+    let result =
+        ensure_containing_dir_exists(&file).and_then(|()| fs::write(&file, now.to_rfc3339()));
+    if let Err(error) = result {
+        debug!("Could not record usage of {} {}: {}", tool, version, error);
+    }
+}
+
+/// Removes the image of a version of Node, npm, or Yarn, along with the files kept for it in the
+/// inventory
+///
+/// The marker is removed first, so that if the removal is interrupted, the rest of the image is
+/// treated as incomplete instead of being run.
+pub(crate) fn remove_image(tool: ImageTool, version: &Version) -> Fallible<()> {
+    remove_image_files(tool, version)?;
+    let usage_file = volta_home()?.image_usage_file(&tool.to_string(), &version.to_string());
+    remove_file_if_exists(usage_file)
+}
+
+fn remove_image_files(tool: ImageTool, version: &Version) -> Fallible<()> {
+    let image = tool.image_dir(version)?;
+    remove_file_if_exists(image.join(IMAGE_MARKER))?;
+    remove_dir_if_exists(&image)?;
+
+    for file in tool.inventory_files(version)? {
+        remove_file_if_exists(file)?;
+    }
+    Ok(())
+}
+
+/// An installed version of Node, npm, or Yarn that no longer matches what was installed
+pub struct DamagedImage {
+    pub tool: ImageTool,
+    pub version: Version,
+    pub problem: ImageProblem,
+}
+
+/// How an installed image was found to be damaged
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImageProblem {
+    /// The image is missing its marker or some of the files it was unpacked with, e.g. because
+    /// unpacking was interrupted or files were deleted afterwards
+    Incomplete,
+    /// Some of the files in the image were changed after it was unpacked
+    Modified,
+    /// The archive kept in the inventory no longer has the digest recorded when it was unpacked
+    ArchiveChanged,
+    /// The digest recorded when the image was unpacked isn't the one its server publishes
+    PublishedMismatch,
+}
+
+/// Checks every installed version of Node, npm, and Yarn for damage
+///
+/// The contents of each image are checked against the digest recorded when it was unpacked, and
+/// the archive it was unpacked from against its own digest, when it is still in the inventory.
+/// Images installed by older versions of Volta only recorded their number of files, so changes
+/// to their contents are only found through the archive. With `published`, the recorded digest of each Node version is also checked against the
+/// `SHASUMS256.txt` of the server it was downloaded from, which requires a network connection.
+pub fn verify_images(published: bool) -> Fallible<Vec<DamagedImage>> {
+    let mut damaged = Vec::new();
+    for &tool in &ImageTool::ALL {
+        for version in tool.versions()? {
+            if let Some(problem) = verify_image(tool, &version, published)? {
+                damaged.push(DamagedImage {
+                    tool,
+                    version,
+                    problem,
+                });
+            }
+        }
+    }
+    Ok(damaged)
+}
+
+fn verify_image(
+    tool: ImageTool,
+    version: &Version,
+    published: bool,
+) -> Fallible<Option<ImageProblem>> {
+    let image = tool.image_dir(version)?;
+    if let Some(problem) = image_problem(&image) {
+        return Ok(Some(problem));
+    }
+    // Images installed before markers recorded digests can only be checked for completeness
+    let sha256 = match image_provenance(&image).0 {
+        Some(sha256) => sha256,
+        None => return Ok(None),
+    };
+
+    let archive = tool.archive_file(version)?;
+    if archive.is_file() {
+        // An archive that can't be read is as unusable as one that changed
+        let changed =
+            file_sha256(&archive).map_or(true, |actual| !actual.eq_ignore_ascii_case(&sha256));
+        if changed {
+            return Ok(Some(ImageProblem::ArchiveChanged));
+        }
+    }
+
+    if published && tool == ImageTool::Node {
+        if let Some((url, _)) = find_download(&sha256)? {
+            let listed = verify::shasums(&url, &Node::archive_filename(version));
+            if let Some(Published::Sha256(listed)) = listed {
+                if !listed.eq_ignore_ascii_case(&sha256) {
+                    return Ok(Some(ImageProblem::PublishedMismatch));
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+/// Replaces a damaged image by removing it, along with the archive it was unpacked from, and
+/// fetching the same version again
+///
+/// The archive is removed as well since it may be what was damaged, so repairing an image always
+/// downloads it again.
+pub fn repair_image(image: &DamagedImage, session: &mut Session) -> Fallible<()> {
+    {
+        let _lock = VoltaLock::acquire();
+        remove_image_files(image.tool, &image.version)?;
+    }
+    image
+        .tool
+        .spec(&image.version)
+        .resolve(session)?
+        .fetch(session)
+}
+
+/// The time is stored in the file, rather than using its modification time, which some file
+/// systems don't update reliably
+fn read_last_used(file: &Path) -> Option<DateTime<Utc>> {
+    let contents = read_file(file).ok()??;
+    DateTime::parse_from_rfc3339(contents.trim())
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+/// Returns the SHA-256 digest of the path and contents of every file in an image, and the path
+/// and target of every symlink, leaving out the marker
+///
+/// Paths always use `/` as the separator, so that the digest doesn't depend on the OS.
+fn contents_sha256(image: &Path) -> io::Result<String> {
+    let root = long_path(image);
+    let mut entries = BTreeMap::new();
+    for entry in WalkDir::new(&root).follow_links(false).min_depth(1) {
+        let entry = entry?;
+        if entry.file_name() == IMAGE_MARKER {
+            continue;
+        }
+        let contents = if entry.path_is_symlink() {
+            fs::read_link(entry.path())?.to_string_lossy().into_owned()
+        } else if entry.file_type().is_file() {
+            file_sha256(entry.path())?
+        } else {
+            continue;
+        };
+        let name = entry
+            .path()
+            .strip_prefix(&root)
+            .expect("Entries are inside the image")
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        entries.insert(name, contents);
+    }
+
+    let mut hasher = Sha256::new();
+    for (name, contents) in entries {
+        hasher.update(name.as_bytes());
+        hasher.update(b"\0");
+        hasher.update(contents.as_bytes());
+        hasher.update(b"\0");
+    }
+    Ok(hex::encode(hasher.finalize()))
+}
+
+fn count_files(image: &Path) -> u64 {
+    let files = WalkDir::new(long_path(image))
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_file() && entry.file_name() != IMAGE_MARKER)
+        .count();
+    files as u64
 }
 
 /// Collects a set of all Package Configs on the local machine
@@ -81,6 +612,141 @@ pub fn package_configs() -> Fallible<BTreeSet<PackageConfig>> {
         .collect::<Fallible<BTreeSet<PackageConfig>>>()
 }
 
+lazy_static! {
+    /// Serializes changes to the installed versions manifest between the threads of this process,
+    /// which share the Volta lock when they download tools in parallel
+    static ref MANIFEST_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// The versions in each image directory, so that they can be listed without reading the
+/// directories. Each entry is only used while the modification time of its directory matches,
+/// so images that are added or removed behind Volta's back are picked up by the next scan.
+#[derive(Default, Serialize, Deserialize)]
+struct InstalledVersions {
+    #[serde(flatten)]
+    tools: BTreeMap<String, ImageDirEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ImageDirEntry {
+    modified: Duration,
+    versions: BTreeSet<String>,
+}
+
+/// Returns the versions of a tool from the manifest, scanning its image directory if the
+/// recorded versions are out of date
+fn recorded_versions(tool: &str, dir: &Path) -> Fallible<BTreeSet<Version>> {
+    let modified = match dir_modified(dir) {
+        Some(modified) => modified,
+        None => return read_versions(dir),
+    };
+
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut manifest = read_manifest().unwrap_or_default();
+    if let Some(entry) = manifest.tools.get(tool) {
+        if entry.modified == modified {
+            return Ok(entry
+                .versions
+                .iter()
+                .filter_map(|version| parse_version(version).ok())
+                .collect());
+        }
+    }
+
+    let versions = read_versions(dir)?;
+
+    // A directory changed this recently could change again without its modification time
+    // showing it, so it is scanned again next time instead
+    let settled = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(false, |now| modified + RACY_WINDOW < now);
+    if settled {
+        manifest.tools.insert(
+            tool.into(),
+            ImageDirEntry {
+                modified,
+                versions: versions.iter().map(Version::to_string).collect(),
+            },
+        );
+        write_manifest(&manifest);
+    }
+
+    Ok(versions)
+}
+
+/// Runs `install`, which adds the image of a new version to the image directory of a tool,
+/// and then adds that version to the manifest
+///
+/// Images are only installed while holding the Volta lock, so if the recorded versions were up
+/// to date before the install, the new version is the only change. The manifest is only updated
+/// while holding the lock as well, and only by one thread at a time, so that tools that are
+/// installed in parallel don't overwrite each other's changes.
+fn record_image<F>(tool: &str, dir: &Path, version: &Version, install: F) -> Fallible<()>
+where
+    F: FnOnce() -> Fallible<()>,
+{
+    let _lock = VoltaLock::acquire();
+    let before = dir_modified(dir);
+    install()?;
+
+    let _guard = MANIFEST_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+    let mut manifest = match read_manifest() {
+        Some(manifest) => manifest,
+        None => return Ok(()),
+    };
+    match (manifest.tools.get_mut(tool), before, dir_modified(dir)) {
+        (Some(entry), Some(before), Some(after)) if entry.modified == before => {
+            entry.versions.insert(version.to_string());
+            entry.modified = after;
+        }
+        _ => {
+            manifest.tools.remove(tool);
+        }
+    }
+    write_manifest(&manifest);
+
+    Ok(())
+}
+
+fn dir_modified(dir: &Path) -> Option<Duration> {
+    let modified = metadata(dir).and_then(|meta| meta.modified()).ok()?;
+    modified.duration_since(UNIX_EPOCH).ok()
+}
+
+fn read_manifest() -> Option<InstalledVersions> {
+    let contents = read_file(volta_home().ok()?.installed_versions_file()).ok()??;
+    serde_json::from_str(&contents).ok()
+}
+
+/// Writes the manifest, which is only an optimization, so failures are only logged
+fn write_manifest(manifest: &InstalledVersions) {
+    let result = volta_home().and_then(|home| {
+        let file = home.installed_versions_file();
+        let write_error = || ErrorKind::WriteInstalledVersionsError {
+            file: file.to_owned(),
+        };
+
+        let contents = serde_json::to_string(manifest).with_context(write_error)?;
+        let staging = create_staging_file()?;
+        let mut staged: &File = staging.as_file();
+        staged
+            .write_all(contents.as_bytes())
+            .with_context(write_error)?;
+        ensure_containing_dir_exists(&file).with_context(|| ErrorKind::ContainingDirError {
+            path: file.to_owned(),
+        })?;
+        staging.persist(file).with_context(write_error)?;
+        Ok(())
+    });
+
+    if let Err(error) = result {
+        debug!(
+            "Could not write the installed versions manifest: {:?}",
+            error
+        );
+    }
+}
+
 /// Reads the contents of a directory and returns the set of all versions found
 /// in the directory's listing by parsing the directory names as semantic versions
 fn read_versions(dir: &Path) -> Fallible<BTreeSet<Version>> {
@@ -0,0 +1,536 @@
+//! Provides portable archives of the inventory, for provisioning machines that can't download
+//! tools themselves, e.g. air-gapped build machines.
+//!
+//! An archive is a gzipped tarball. It starts with a `manifest.json` that lists the images in it,
+//! the SHA-256 digest of each of their files, and the target of each of their symlinks, followed by the files of each image under
+//! `images/<tool>/<version>/`, and the distros they were unpacked from under `inventory/<tool>/`.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::fs::{read_link, File};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{
+    create_staging_dir, file_sha256, long_path, read_file, remove_dir_if_exists, rename,
+};
+use crate::inventory::{image_intact, ImageTool};
+use crate::signal::CleanupGuard;
+use crate::sync::VoltaLock;
+use crate::tool::{node_distro_arch, Node, Spec, NODE_DISTRO_OS};
+use crate::version::{satisfies, version_serde, VersionSpec, VersionTag};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use fs_utils::ensure_containing_dir_exists;
+use log::debug;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+use walkdir::WalkDir;
+
+/// The version of the archive format, which changes only when existing fields change meaning
+const FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    version: u32,
+    /// The platform that the Node binaries in the archive were built for
+    ///
+    /// Node images can record their own architecture, e.g. for versions that run under emulation.
+    os: String,
+    arch: String,
+    images: Vec<ManifestImage>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestImage {
+    tool: ImageTool,
+    #[serde(with = "version_serde")]
+    version: Version,
+    /// The SHA-256 digest of each file in the image, by its path within the image
+    ///
+    /// Symlinks and directories are in the archive, but aren't listed here.
+    files: BTreeMap<String, String>,
+    /// The target of each symlink in the image, by its path within the image
+    #[serde(default)]
+    symlinks: BTreeMap<String, String>,
+    /// The architecture of the binaries in a Node image
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    arch: Option<String>,
+    /// The SHA-256 digest of each file kept in the inventory for the image, by its name
+    #[serde(default)]
+    inventory: BTreeMap<String, String>,
+}
+
+/// An image of a tool version, as stored in an inventory archive
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ArchivedImage {
+    pub tool: ImageTool,
+    pub version: Version,
+}
+
+impl fmt::Display for ArchivedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}@{}", self.tool, self.version)
+    }
+}
+
+/// Writes the fetched images that match any of the specs to an inventory archive, along with the
+/// distros they were unpacked from, and returns the images that were written
+///
+/// With no specs, every fetched Node, npm, and Yarn image is exported.
+pub fn export(file: &Path, specs: &[Spec]) -> Fallible<Vec<ArchivedImage>> {
+    let images = select_images(specs)?;
+    let mut manifest = Manifest {
+        version: FORMAT_VERSION,
+        os: NODE_DISTRO_OS.into(),
+        arch: node_distro_arch().into(),
+        images: Vec::with_capacity(images.len()),
+    };
+    for image in &images {
+        manifest.images.push(describe_image(image)?);
+    }
+
+    let write_error = || ErrorKind::WriteInventoryArchiveError {
+        file: file.to_owned(),
+    };
+    // The archive is written next to its destination, so that it can be moved into place without
+    // copying it, and a failed export doesn't leave a partial archive behind
+    let dir = match file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let staging = NamedTempFile::new_in(dir).with_context(write_error)?;
+    let _cleanup = CleanupGuard::new(staging.path());
+    let mut builder = tar::Builder::new(GzEncoder::new(staging, Compression::default()));
+    builder.follow_symlinks(false);
+
+    append_manifest(&mut builder, &manifest).with_context(write_error)?;
+    for image in &images {
+        let dir = image.tool.image_dir(&image.version)?;
+        let inventory = image.tool.inventory_files(&image.version)?;
+        append_image(&mut builder, image, &dir, &inventory).with_context(write_error)?;
+    }
+
+    let staging = builder
+        .into_inner()
+        .and_then(GzEncoder::finish)
+        .with_context(write_error)?;
+    staging.as_file().sync_all().with_context(write_error)?;
+    staging.persist(file).with_context(write_error)?;
+
+    Ok(images)
+}
+
+/// Finds the fetched images that match any of the specs, or every fetched image without any
+fn select_images(specs: &[Spec]) -> Fallible<Vec<ArchivedImage>> {
+    let mut images = BTreeSet::new();
+
+    if specs.is_empty() {
+        for &tool in &ImageTool::ALL {
+            images.extend(
+                tool.versions()?
+                    .into_iter()
+                    .map(|version| ArchivedImage { tool, version }),
+            );
+        }
+    }
+
+    for spec in specs {
+        let (tool, requested) = match spec {
+            Spec::Node(requested) => (ImageTool::Node, requested),
+            Spec::Npm(requested) => (ImageTool::Npm, requested),
+            Spec::Yarn(requested) => (ImageTool::Yarn, requested),
+            Spec::Package(..) | Spec::PackageSource(..) => {
+                return Err(ErrorKind::InvalidInventorySelection {
+                    tool: spec.to_string(),
+                }
+                .into());
+            }
+        };
+
+        let mut matched = false;
+        for version in tool.versions()? {
+            let matches = match requested {
+                VersionSpec::None | VersionSpec::Tag(VersionTag::Installed) => true,
+                VersionSpec::Exact(exact) => *exact == version,
+                VersionSpec::Semver(requirement) => satisfies(requirement, &version, false),
+                // Other tags change over time, so they can't be matched against fetched versions
+                VersionSpec::Tag(_) => {
+                    return Err(ErrorKind::InvalidInventorySelection {
+                        tool: spec.to_string(),
+                    }
+                    .into());
+                }
+            };
+            if matches {
+                matched = true;
+                images.insert(ArchivedImage { tool, version });
+            }
+        }
+
+        if !matched {
+            return Err(ErrorKind::NoInstalledVersion {
+                tool: spec.to_string(),
+            }
+            .into());
+        }
+    }
+
+    Ok(images.into_iter().collect())
+}
+
+fn describe_image(image: &ArchivedImage) -> Fallible<ManifestImage> {
+    let dir = image.tool.image_dir(&image.version)?;
+    // Exporting a damaged image would only move the damage to another machine
+    if !image_intact(&dir) {
+        return Err(ErrorKind::InventoryImageDamaged {
+            tool: image.tool.to_string(),
+            version: image.version.to_string(),
+        }
+        .into());
+    }
+
+    let read_error = || ErrorKind::ReadInventoryImageError {
+        tool: image.tool.to_string(),
+        version: image.version.to_string(),
+    };
+    let mut files = BTreeMap::new();
+    let mut symlinks = BTreeMap::new();
+    for entry in image_entries(&dir).with_context(read_error)? {
+        if entry.is_file {
+            let sha256 = file_sha256(&entry.path).with_context(read_error)?;
+            files.insert(entry.name, sha256);
+        } else if entry.is_symlink {
+            let target = link_target(&entry.path).with_context(read_error)?;
+            symlinks.insert(entry.name, target);
+        }
+    }
+
+    let mut inventory = BTreeMap::new();
+    for path in image.tool.inventory_files(&image.version)? {
+        if let Some(name) = file_name(&path) {
+            if path.is_file() {
+                inventory.insert(name, file_sha256(&path).with_context(read_error)?);
+            }
+        }
+    }
+
+    Ok(ManifestImage {
+        tool: image.tool,
+        version: image.version.clone(),
+        files,
+        symlinks,
+        arch: match image.tool {
+            ImageTool::Node => Some(Node::distro_arch(&image.version).into()),
+            _ => None,
+        },
+        inventory,
+    })
+}
+
+fn append_manifest<W: Write>(builder: &mut tar::Builder<W>, manifest: &Manifest) -> io::Result<()> {
+    // Serializing strings and maps of strings can't fail
+    let json = serde_json::to_vec_pretty(manifest).expect("Manifest is serializable");
+    let mut header = tar::Header::new_gnu();
+    header.set_size(json.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, MANIFEST_FILE, json.as_slice())
+}
+
+fn append_image<W: Write>(
+    builder: &mut tar::Builder<W>,
+    image: &ArchivedImage,
+    dir: &Path,
+    inventory: &[PathBuf],
+) -> io::Result<()> {
+    debug!("Adding {} to the inventory archive", image);
+    let prefix = archived_image_dir(image.tool, &image.version);
+    for entry in image_entries(dir)? {
+        builder.append_path_with_name(&entry.path, format!("{}/{}", prefix, entry.name))?;
+    }
+
+    for path in inventory {
+        if let Some(name) = file_name(path) {
+            if path.is_file() {
+                builder.append_path_with_name(path, archived_inventory_file(image.tool, &name))?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reads the images in an inventory archive into the inventory, and returns the ones that weren't
+/// already fetched
+///
+/// Every file is checked against the manifest before any image is added, so a damaged archive
+/// doesn't change the inventory at all.
+pub fn import(file: &Path) -> Fallible<Vec<ArchivedImage>> {
+    let staging = create_staging_dir()?;
+    let _cleanup = CleanupGuard::new(staging.path());
+    let manifest = unpack(file, staging.path())?;
+
+    // npm and Yarn are plain JavaScript, but the Node binaries only run on the platform they were
+    // exported from
+    for image in &manifest.images {
+        if image.tool != ImageTool::Node {
+            continue;
+        }
+        let archived = image.arch.as_deref().unwrap_or(&manifest.arch);
+        let expected = Node::distro_arch(&image.version);
+        if manifest.os != NODE_DISTRO_OS || archived != expected {
+            return Err(ErrorKind::InventoryArchivePlatformMismatch {
+                file: file.to_owned(),
+                archive: format!("{}-{}", manifest.os, archived),
+                current: format!("{}-{}", NODE_DISTRO_OS, expected),
+            }
+            .into());
+        }
+    }
+
+    for image in &manifest.images {
+        verify_image(file, staging.path(), image)?;
+    }
+
+    let _lock = VoltaLock::acquire();
+    let mut imported = Vec::new();
+    for image in &manifest.images {
+        let archived = ArchivedImage {
+            tool: image.tool,
+            version: image.version.clone(),
+        };
+        if image.tool.available(&image.version)? {
+            debug!("{} has already been fetched, skipping it", archived);
+            continue;
+        }
+        install_image(staging.path(), image)?;
+        imported.push(archived);
+    }
+
+    Ok(imported)
+}
+
+/// Unpacks an inventory archive into a directory, and reads its manifest
+fn unpack(file: &Path, dest: &Path) -> Fallible<Manifest> {
+    let read_error = || ErrorKind::ReadInventoryArchiveError {
+        file: file.to_owned(),
+    };
+    let archive = File::open(file).with_context(read_error)?;
+    // Unpacking keeps every entry inside the destination, even if its path tries to leave it
+    tar::Archive::new(GzDecoder::new(archive))
+        .unpack(dest)
+        .with_context(read_error)?;
+
+    let parse_error = || ErrorKind::ParseInventoryArchiveError {
+        file: file.to_owned(),
+    };
+    let contents = read_file(dest.join(MANIFEST_FILE))
+        .with_context(read_error)?
+        .ok_or_else(parse_error)?;
+    serde_json::from_str(&contents)
+        .ok()
+        .filter(|manifest: &Manifest| manifest.version <= FORMAT_VERSION)
+        .ok_or_else(|| parse_error().into())
+}
+
+/// Checks that the unpacked files and symlinks of an image are exactly the ones listed in the
+/// manifest, and that every symlink points to somewhere inside the image
+fn verify_image(file: &Path, staging: &Path, image: &ManifestImage) -> Fallible<()> {
+    let mismatch = |entry: String| ErrorKind::InventoryChecksumMismatch {
+        file: file.to_owned(),
+        entry,
+    };
+    let prefix = archived_image_dir(image.tool, &image.version);
+    let dir = staging.join(&prefix);
+
+    let entries = image_entries(&dir).with_context(|| mismatch(prefix.clone()))?;
+    if let Some(unlisted) = entries.iter().find(|entry| {
+        (entry.is_file && !image.files.contains_key(&entry.name))
+            || (entry.is_symlink && !image.symlinks.contains_key(&entry.name))
+    }) {
+        return Err(mismatch(format!("{}/{}", prefix, unlisted.name)).into());
+    }
+
+    for (name, target) in &image.symlinks {
+        let entry = format!("{}/{}", prefix, name);
+        if !link_inside_image(name, target) {
+            return Err(mismatch(entry).into());
+        }
+        match link_target(&staging.join(&entry)) {
+            Ok(actual) if actual == *target => {}
+            _ => return Err(mismatch(entry).into()),
+        }
+    }
+
+    let listed = image
+        .files
+        .iter()
+        .map(|(name, sha256)| (format!("{}/{}", prefix, name), sha256))
+        .chain(
+            image
+                .inventory
+                .iter()
+                .map(|(name, sha256)| (archived_inventory_file(image.tool, name), sha256)),
+        );
+    for (entry, sha256) in listed {
+        match file_sha256(&staging.join(&entry)) {
+            Ok(actual) if actual == *sha256 => {}
+            _ => return Err(mismatch(entry).into()),
+        }
+    }
+    Ok(())
+}
+
+/// Moves an unpacked image into place, along with the files kept in the inventory for it
+fn install_image(staging: &Path, image: &ManifestImage) -> Fallible<()> {
+    let tool = image.tool.to_string();
+    let version = image.version.to_string();
+    let dest = image.tool.image_dir(&image.version)?;
+
+    // An image without a marker is incomplete, and has to be replaced
+    remove_dir_if_exists(&dest)?;
+    ensure_containing_dir_exists(&dest)
+        .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
+
+    let source = staging.join(archived_image_dir(image.tool, &image.version));
+    image.tool.record(&image.version, || {
+        rename(&source, &dest).with_context(|| ErrorKind::SetupToolImageError {
+            tool: tool.clone(),
+            version: version.clone(),
+            dir: dest.clone(),
+        })
+    })?;
+
+    // Only the files that Volta would have kept for the version are copied, so that a manifest
+    // can't write anywhere else
+    for path in image.tool.inventory_files(&image.version)? {
+        let name = match file_name(&path) {
+            Some(name) if image.inventory.contains_key(&name) && !path.exists() => name,
+            _ => continue,
+        };
+        let source = staging.join(archived_inventory_file(image.tool, &name));
+        ensure_containing_dir_exists(&path)
+            .and_then(|()| rename(&source, &path))
+            .with_context(|| ErrorKind::PersistInventoryError { tool: tool.clone() })?;
+    }
+
+    Ok(())
+}
+
+/// The directory of an image within an archive
+fn archived_image_dir(tool: ImageTool, version: &Version) -> String {
+    format!("images/{}/{}", tool, version)
+}
+
+/// The path within an archive of a file kept in the inventory
+fn archived_inventory_file(tool: ImageTool, name: &str) -> String {
+    format!("inventory/{}/{}", tool, name)
+}
+
+struct ImageEntry {
+    /// The path of the entry within the image, which always uses `/` as the separator, so that
+    /// the archive can be imported on any OS
+    name: String,
+    path: PathBuf,
+    /// Whether the entry is a regular file, rather than a directory or a symlink
+    is_file: bool,
+    is_symlink: bool,
+}
+
+/// Lists everything in an image directory, without following symlinks
+fn image_entries(dir: &Path) -> io::Result<Vec<ImageEntry>> {
+    let root = long_path(dir);
+    let mut entries = Vec::new();
+    for entry in WalkDir::new(&root).follow_links(false).min_depth(1) {
+        let entry = entry?;
+        let relative = entry
+            .path()
+            .strip_prefix(&root)
+            .expect("Entries are inside the image");
+        let name = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        entries.push(ImageEntry {
+            name,
+            is_file: entry.file_type().is_file(),
+            is_symlink: entry.path_is_symlink(),
+            path: entry.into_path(),
+        });
+    }
+    Ok(entries)
+}
+
+/// Reads the target of a symlink, using `/` as the separator like the names of image entries
+fn link_target(path: &Path) -> io::Result<String> {
+    let target = read_link(path)?;
+    let parts = target
+        .components()
+        .map(|component| match component {
+            Component::RootDir => String::new(),
+            _ => component.as_os_str().to_string_lossy().into_owned(),
+        })
+        .collect::<Vec<_>>();
+    // A root on its own has to keep its separator, so that it still reads as absolute
+    Ok(match parts.as_slice() {
+        [root] if root.is_empty() => "/".into(),
+        _ => parts.join("/"),
+    })
+}
+
+/// Determines whether a symlink target is relative, and stays inside the image when it's
+/// resolved from the directory of the symlink
+fn link_inside_image(name: &str, target: &str) -> bool {
+    if target.starts_with('/') || target.starts_with('\\') || Path::new(target).has_root() {
+        return false;
+    }
+
+    let mut dir = name.split('/').collect::<Vec<_>>();
+    dir.pop();
+    for part in target.split(|c| c == '/' || c == '\\') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                if dir.pop().is_none() {
+                    return false;
+                }
+            }
+            // Windows drive prefixes, e.g. `C:`, make a target absolute
+            _ if part.contains(':') => return false,
+            _ => dir.push(part),
+        }
+    }
+    true
+}
+
+fn file_name(path: &Path) -> Option<String> {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::link_inside_image;
+
+    #[test]
+    fn test_link_inside_image() {
+        assert!(link_inside_image(
+            "bin/npm",
+            "../lib/node_modules/npm/bin/npm-cli.js"
+        ));
+        assert!(link_inside_image("bin/node", "./node-bin"));
+        assert!(link_inside_image("lib/a/b", "../../bin/../lib"));
+
+        assert!(!link_inside_image("bin/npm", "../../outside"));
+        assert!(!link_inside_image("npm", "../npm"));
+        assert!(!link_inside_image("bin/npm", "/usr/bin/npm"));
+        assert!(!link_inside_image("bin/npm", "\\Windows\\npm.exe"));
+        assert!(!link_inside_image("bin/npm", "C:/Windows/npm.exe"));
+    }
+}
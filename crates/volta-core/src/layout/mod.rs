@@ -1,6 +1,7 @@
 use std::env;
 use std::path::PathBuf;
 
+use crate::environment;
 use crate::error::{Context, ErrorKind, Fallible};
 use cfg_if::cfg_if;
 use double_checked_cell::DoubleCheckedCell;
@@ -23,20 +24,51 @@ lazy_static! {
     static ref VOLTA_INSTALL: DoubleCheckedCell<VoltaInstall> = DoubleCheckedCell::new();
 }
 
+/// Sets the Volta directory, instead of finding it from `VOLTA_HOME` or the user's home directory
+///
+/// This is meant for programs that embed Volta, and has to be called before anything uses the
+/// Volta directory, since it can't change once it has been found. Returns whether the directory
+/// was set.
+pub fn set_volta_home(home_dir: PathBuf) -> bool {
+    let mut set = false;
+    VOLTA_HOME.get_or_init(|| {
+        set = true;
+        VoltaHome::new(home_dir)
+    });
+    set
+}
+
+/// Sets the directory of the Volta binaries, instead of finding it from `VOLTA_INSTALL_DIR` or
+/// the running executable
+///
+/// As with `set_volta_home`, this only works before the directory is first used, and returns
+/// whether it was set. It is only needed to create shims.
+pub fn set_volta_install(install_dir: PathBuf) -> bool {
+    let mut set = false;
+    VOLTA_INSTALL.get_or_init(|| {
+        set = true;
+        VoltaInstall::new(install_dir)
+    });
+    set
+}
+
 pub fn volta_home<'a>() -> Fallible<&'a VoltaHome> {
     VOLTA_HOME.get_or_try_init(|| {
-        let home_dir = match env::var_os("VOLTA_HOME") {
+        let home_dir = match environment::var_os("VOLTA_HOME") {
             Some(home) => PathBuf::from(home),
             None => default_home_dir()?,
         };
 
+        #[cfg(target_os = "linux")]
+        let home_dir = wsl_home_dir(home_dir)?;
+
         Ok(VoltaHome::new(home_dir))
     })
 }
 
 pub fn volta_install<'a>() -> Fallible<&'a VoltaInstall> {
     VOLTA_INSTALL.get_or_try_init(|| {
-        let install_dir = match env::var_os("VOLTA_INSTALL_DIR") {
+        let install_dir = match environment::var_os("VOLTA_INSTALL_DIR") {
             Some(install) => PathBuf::from(install),
             None => default_install_dir()?,
         };
@@ -45,6 +77,27 @@ pub fn volta_install<'a>() -> Fallible<&'a VoltaInstall> {
     })
 }
 
+/// Checks that the Volta home can be used from inside WSL
+///
+/// A `VOLTA_HOME` shared from Windows may still be a Windows path, which is translated to where
+/// WSL mounts it. Sharing the home of the Windows installation isn't allowed, since it would mix
+/// Linux images in with the Windows ones.
+#[cfg(target_os = "linux")]
+fn wsl_home_dir(home_dir: PathBuf) -> Fallible<PathBuf> {
+    use crate::wsl::{is_windows_volta_home, is_wsl, translate_windows_path};
+
+    if !is_wsl() {
+        return Ok(home_dir);
+    }
+
+    let home_dir = translate_windows_path(home_dir.as_os_str()).unwrap_or(home_dir);
+    if is_windows_volta_home(&home_dir) {
+        return Err(ErrorKind::WslWindowsVoltaHome { home: home_dir }.into());
+    }
+
+    Ok(home_dir)
+}
+
 /// Determine the binary install directory from the currently running executable
 ///
 /// The volta-shim and volta binaries will be installed in the same location, so we can use the
@@ -1,22 +1,99 @@
 //! The main implementation crate for the core of Volta.
+//!
+//! Besides the Volta binaries, this crate can be used by other programs that need to know which
+//! tools Volta would run, e.g. editors or build tools. Nothing in the library exits the process or
+//! installs signal handlers, except for `Session::exit`, `error::ExitCode::exit`, and
+//! `signal::setup_signal_handler` (whose handler exits on Ctrl+C), which are only meant for the
+//! binaries.
+//!
+//! The main types are:
+//!
+//! - [`session::Session`], which holds the settings, hooks, project, and toolchain in use
+//! - [`platform::Platform`], which resolves the Node, npm, and Yarn versions for a project
+//! - [`version::VersionSpec`], which parses requested versions like `16`, `^4.2`, or `lts`
+//! - the functions in [`inventory`], which list the versions that are installed
+//!
+//! Only those modules, along with [`layout`], [`http`], [`progress`], [`project`], and
+//! [`settings`], are meant to be used by other programs, and they only change in backward-compatible
+//! ways. The other public modules are internals that the Volta binaries share with this crate, such
+//! as `evict` and `permissions`. They are hidden from the documentation and can change at any time.
+//!
+//! The Volta directory is found from `VOLTA_HOME` (or the user's home directory) the first time
+//! it is needed, unless it was set first with [`layout::set_volta_home`].
+//!
+//! Not everything is held by a `Session`. The Volta directory, the HTTP client, the progress sink,
+//! the environment given to `SessionBuilder::env`, the pinned certificates, and the locks on the
+//! Volta directory and on project manifests are shared by the whole process. So every session in a
+//! process uses the same Volta directory, and replacing the client or the sink changes it for all
+//! of them.
+//!
+//! ```no_run
+//! use std::path::PathBuf;
+//!
+//! use volta_core::error::Fallible;
+//! use volta_core::platform::Platform;
+//! use volta_core::session::Session;
+//!
+//! /// Finds the Node version that Volta would run in a directory, if any
+//! fn node_version(dir: PathBuf) -> Fallible<Option<String>> {
+//!     let mut session = Session::init();
+//!     session.use_project_dir(dir)?;
+//!
+//!     let platform = Platform::current(&mut session)?;
+//!     Ok(platform.map(|platform| platform.node.value.to_string()))
+//! }
+//! ```
 
+mod cert_pins;
+#[doc(hidden)]
+pub mod checksums;
 mod command;
+#[doc(hidden)]
+pub mod conflicts;
+mod download;
+mod environment;
 pub mod error;
+#[doc(hidden)]
 pub mod event;
+#[doc(hidden)]
+pub mod evict;
+#[doc(hidden)]
 pub mod fs;
+#[doc(hidden)]
+pub mod github;
 mod hook;
+pub mod http;
+#[doc(hidden)]
+pub mod install_source;
 pub mod inventory;
+#[doc(hidden)]
+pub mod inventory_archive;
 pub mod layout;
+#[doc(hidden)]
 pub mod log;
+#[doc(hidden)]
 pub mod monitor;
+#[doc(hidden)]
+pub mod permissions;
 pub mod platform;
+pub mod progress;
 pub mod project;
+#[doc(hidden)]
 pub mod run;
 pub mod session;
+pub mod settings;
+#[doc(hidden)]
 pub mod shim;
+#[doc(hidden)]
 pub mod signal;
+#[doc(hidden)]
 pub mod style;
+#[doc(hidden)]
 pub mod sync;
+#[doc(hidden)]
 pub mod tool;
+#[doc(hidden)]
 pub mod toolchain;
 pub mod version;
+#[cfg(target_os = "linux")]
+mod wsl;
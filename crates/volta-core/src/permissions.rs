@@ -0,0 +1,67 @@
+//! Provides a check that other users can't change the tools that Volta runs
+//!
+//! Every `node`, `npm`, or `yarn` command runs through the shims in the Volta directory, so if
+//! another user can write to that directory, they can run anything as whoever uses those shims.
+//! On shared machines, that is an easy way to gain someone else's (or root's) privileges.
+
+use crate::environment;
+use crate::error::Fallible;
+use crate::layout::volta_home;
+use log::warn;
+
+/// Set to `warn` to only warn about unsafe permissions, instead of refusing to run
+const UNSAFE_PERMISSIONS_ENV_VAR: &str = "VOLTA_UNSAFE_PERMISSIONS";
+
+/// Ensures that the Volta directory and its shims can only be changed by the current user (or
+/// root)
+///
+/// Directories that don't exist yet are skipped, since Volta creates them with safe permissions.
+pub fn ensure_safe_permissions() -> Fallible<()> {
+    let home = volta_home()?;
+
+    for dir in [home.root(), home.shim_dir()] {
+        if let Err(error) = check_dir(dir) {
+            match environment::var(UNSAFE_PERMISSIONS_ENV_VAR) {
+                Ok(policy) if policy == "warn" => warn!("{}", error),
+                _ => return Err(error),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(unix)]
+fn check_dir(dir: &std::path::Path) -> Fallible<()> {
+    use crate::error::ErrorKind;
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = match dir.metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => return Ok(()),
+    };
+
+    if metadata.mode() & 0o002 != 0 {
+        return Err(ErrorKind::VoltaDirWorldWritable {
+            dir: dir.to_owned(),
+        }
+        .into());
+    }
+
+    // Safe because `geteuid` has no preconditions and can't fail
+    let current_user = unsafe { libc::geteuid() };
+    if metadata.uid() != current_user && metadata.uid() != 0 {
+        return Err(ErrorKind::VoltaDirOwnedByOtherUser {
+            dir: dir.to_owned(),
+        }
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Windows protects directories with ACLs, which are inherited from the user's profile by default
+#[cfg(windows)]
+fn check_dir(_dir: &std::path::Path) -> Fallible<()> {
+    Ok(())
+}
@@ -1,7 +1,8 @@
+use std::env;
 use std::ffi::OsString;
 use std::path::PathBuf;
 
-use super::{build_path_error, Sourced};
+use super::{build_path_error, Sourced, System};
 use crate::error::{Context, Fallible};
 use crate::layout::volta_home;
 use crate::tool::load_default_npm_version;
@@ -51,6 +52,19 @@ impl Image {
             .with_context(build_path_error)
     }
 
+    /// Produces a `PATH` that finds the toolchain executables in the installation directories
+    /// for the given versions, followed by the system `PATH` without the Volta shims, so that
+    /// the toolchain can be used without Volta being involved at all.
+    pub fn path_without_shims(&self) -> Fallible<OsString> {
+        let system_path = System::path()?;
+        let paths = self
+            .bins()?
+            .into_iter()
+            .chain(env::split_paths(&system_path).collect::<Vec<_>>());
+
+        env::join_paths(paths).with_context(build_path_error)
+    }
+
     /// Determines the sourced version of npm that will be available, resolving the version bundled with Node, if needed
     pub fn resolve_npm(&self) -> Fallible<Sourced<Version>> {
         match &self.npm {
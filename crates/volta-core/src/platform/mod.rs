@@ -1,11 +1,18 @@
 use std::fmt;
 
+use crate::download::{self, Task};
 use crate::error::{ErrorKind, Fallible};
+use crate::inventory::record_usage;
+use crate::project::resolve_unlocked_pins;
 use crate::session::Session;
-use crate::tool::{Node, Npm, Yarn};
+use crate::tool::{Distro, Node, Npm, Yarn};
+use crate::version::{option_version_serde, version_serde};
+use log::debug;
 use semver::Version;
+use serde::{Deserialize, Serialize};
 
 mod image;
+mod shell;
 mod system;
 // Note: The tests get their own module because we need them to run as a single unit to prevent
 // clobbering environment variable changes
@@ -13,6 +20,7 @@ mod system;
 mod tests;
 
 pub use image::Image;
+pub use shell::{ShellPlatform, SHELL_PLATFORM_VAR};
 pub use system::System;
 
 /// The source with which a version is associated
@@ -30,6 +38,9 @@ pub enum Source {
 
     /// Represents a version from the command line (via `volta run`)
     CommandLine,
+
+    /// Represents a version selected for the current shell session (via `volta use`)
+    Shell,
 }
 
 impl fmt::Display for Source {
@@ -39,6 +50,7 @@ impl fmt::Display for Source {
             Source::Project => write!(f, "project"),
             Source::Binary => write!(f, "binary"),
             Source::CommandLine => write!(f, "command-line"),
+            Source::Shell => write!(f, "shell session"),
         }
     }
 }
@@ -76,6 +88,13 @@ impl<T> Sourced<T> {
             source: Source::CommandLine,
         }
     }
+
+    pub fn with_shell(value: T) -> Self {
+        Sourced {
+            value,
+            source: Source::Shell,
+        }
+    }
 }
 
 impl<T> Sourced<T> {
@@ -155,12 +174,25 @@ impl<T> Default for InheritOption<T> {
     }
 }
 
-#[derive(Clone, PartialOrd, Ord, PartialEq, Eq)]
-#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Debug, PartialOrd, Ord, PartialEq, Eq, Serialize, Deserialize)]
 /// Represents the specification of a single Platform, regardless of the source
+///
+/// This is serialized in the same shape as the `"volta"` key of a `package.json`, e.g.
+/// `{ "node": "16.14.2", "yarn": "1.22.18" }`, with the tools that aren't pinned left out.
 pub struct PlatformSpec {
+    #[serde(with = "version_serde")]
     pub node: Version,
+    #[serde(
+        default,
+        with = "option_version_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub npm: Option<Version>,
+    #[serde(
+        default,
+        with = "option_version_serde",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub yarn: Option<Version>,
 }
 
@@ -242,10 +274,41 @@ impl Platform {
     /// - If it exists and has a Yarn version, then we use the project platform
     /// - If it exists but doesn't have a Yarn version, then we merge the two,
     ///   pulling Yarn from the user default platform, if available
-    /// - If there is no Project platform, then we use the user Default Platform
+    /// - If there is no Project platform, then we use the user Default Platform, with the package
+    ///   manager from the project's `packageManager` field, if any
+    ///
+    /// Any floating project pins that aren't recorded in `volta.lock` are resolved first
+    ///
+    /// In strict mode, nothing is taken from the user default platform while inside a project:
+    /// a project without pins is an error, and Yarn is only available if the project pins it
+    ///
+    /// Versions selected with `volta use` for the current shell session take precedence over all
+    /// of the above
     pub fn current(session: &mut Session) -> Fallible<Option<Self>> {
+        let platform = Platform::pinned_or_default(session)?;
+        Ok(match session.shell_platform() {
+            Some(shell) => shell.merge(platform),
+            None => platform,
+        })
+    }
+
+    fn pinned_or_default(session: &mut Session) -> Fallible<Option<Self>> {
+        resolve_unlocked_pins(session)?;
+
+        let strict = session.strict();
+        if let Some(project) = session.project_mut()? {
+            project.warn_engine_conflicts_once();
+
+            if strict && project.platform().is_none() {
+                return Err(ErrorKind::NoPinnedPlatform {
+                    manifest: project.manifest_file().to_owned(),
+                }
+                .into());
+            }
+        }
+
         if let Some(mut platform) = session.project_platform()?.map(PlatformSpec::as_project) {
-            if platform.yarn.is_none() {
+            if platform.yarn.is_none() && !session.strict() {
                 platform.yarn = session
                     .default_platform()?
                     .and_then(|default_platform| default_platform.yarn.clone())
@@ -254,22 +317,69 @@ impl Platform {
 
             Ok(Some(platform))
         } else {
-            Ok(session.default_platform()?.map(PlatformSpec::as_default))
+            let mut platform = session.default_platform()?.map(PlatformSpec::as_default);
+            if let (Some(platform), Some(project)) = (platform.as_mut(), session.project()?) {
+                project.apply_package_manager(platform);
+            }
+
+            Ok(platform)
         }
     }
 
     /// Check out a `Platform` into a fully-realized `Image`
     ///
     /// This will ensure that all necessary tools are fetched and available for execution
-    pub fn checkout(self, session: &mut Session) -> Fallible<Image> {
-        Node::new(self.node.value.clone()).ensure_fetched(session)?;
+    ///
+    /// A pinned npm that is the same version as the one bundled with Node is resolved to the
+    /// bundled copy, instead of being fetched separately. The bundled version is only known once
+    /// Node has been fetched, so if Node is missing, npm waits for it instead of being fetched
+    /// alongside it.
+    pub fn checkout(mut self, session: &mut Session) -> Fallible<Image> {
+        let node = Node::new(self.node.value.clone());
+        let node_available = node.available()?;
+        if node_available {
+            self.skip_bundled_npm();
+        }
+        let yarn = self
+            .yarn
+            .as_ref()
+            .map(|Sourced { value, .. }| Yarn::new(value.clone()));
+
+        if !session.fetch_missing() {
+            ensure_available(&node)?;
+            if let Some(npm) = &self.npm_distro() {
+                ensure_available(npm)?;
+            }
+            if let Some(yarn) = &yarn {
+                ensure_available(yarn)?;
+            }
+        }
+
+        let parallel_npm = if node_available {
+            self.npm_distro()
+        } else {
+            None
+        };
+        self.fetch_in_parallel(&node, parallel_npm.as_ref(), yarn.as_ref(), session)?;
+
+        node.ensure_fetched(session)?;
+
+        self.skip_bundled_npm();
+        if let Some(npm) = &self.npm_distro() {
+            npm.ensure_fetched(session)?;
+        }
 
-        if let Some(Sourced { value: version, .. }) = &self.npm {
-            Npm::new(version.clone()).ensure_fetched(session)?;
+        if let Some(yarn) = &yarn {
+            yarn.ensure_fetched(session)?;
         }
 
-        if let Some(Sourced { value: version, .. }) = &self.yarn {
-            Yarn::new(version.clone()).ensure_fetched(session)?;
+        // Only recorded once the tools are available, so that a failed fetch isn't counted as a use
+        record_usage("node", &self.node.value);
+        if let Some(npm) = &self.npm {
+            record_usage("npm", &npm.value);
+        }
+        if let Some(yarn) = &self.yarn {
+            record_usage("yarn", &yarn.value);
         }
 
         Ok(Image {
@@ -280,6 +390,84 @@ impl Platform {
     }
 }
 
+impl Platform {
+    fn npm_distro(&self) -> Option<Npm> {
+        self.npm
+            .as_ref()
+            .map(|Sourced { value, .. }| Npm::new(value.clone()))
+    }
+
+    /// Drops a pinned npm that is the same version as the one bundled with Node, so that the
+    /// image uses the bundled copy
+    fn skip_bundled_npm(&mut self) {
+        if let Some(npm) = &self.npm {
+            if Npm::new(npm.value.clone()).is_bundled_with(&self.node.value) {
+                debug!(
+                    "npm@{} is bundled with node@{}, using the bundled copy",
+                    npm.value, self.node.value
+                );
+                self.npm = None;
+            }
+        }
+    }
+
+    /// Downloads the tools of the platform at the same time, if more than one of them is missing
+    fn fetch_in_parallel(
+        &self,
+        node: &Node,
+        npm: Option<&Npm>,
+        yarn: Option<&Yarn>,
+        session: &mut Session,
+    ) -> Fallible<()> {
+        let mut missing = Vec::new();
+        // Node builds are left to the sequential fetch, which shows their output
+        let fetch_node = !node.available()? && !Node::builds_from_source();
+        if fetch_node {
+            missing.push(node.to_string());
+        }
+        if let Some(npm) = npm {
+            if !npm.available()? {
+                missing.push(npm.to_string());
+            }
+        }
+        if let Some(yarn) = yarn {
+            if !yarn.available()? {
+                missing.push(yarn.to_string());
+            }
+        }
+
+        if missing.len() < 2 {
+            return Ok(());
+        }
+
+        let hooks = session.hooks()?;
+        let mut downloads: Vec<Task> = Vec::new();
+        if fetch_node {
+            downloads.push(Box::new(move || node.ensure_fetched_with(hooks.node())));
+        }
+        if let Some(npm) = npm {
+            downloads.push(Box::new(move || npm.ensure_fetched_with(hooks.npm())));
+        }
+        if let Some(yarn) = yarn {
+            downloads.push(Box::new(move || yarn.ensure_fetched_with(hooks.yarn())));
+        }
+
+        download::run(format!("Fetching {}", missing.join(", ")), downloads)
+    }
+}
+
+/// Ensures that a tool has been fetched, for sessions that must not fetch missing tools
+fn ensure_available<D: Distro>(distro: &D) -> Fallible<()> {
+    if distro.available()? {
+        Ok(())
+    } else {
+        Err(ErrorKind::AutoDownloadDisabled {
+            tool: distro.to_string(),
+        }
+        .into())
+    }
+}
+
 fn build_path_error() -> ErrorKind {
     ErrorKind::BuildPathError
 }
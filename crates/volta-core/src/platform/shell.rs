@@ -0,0 +1,125 @@
+use std::fmt;
+
+use super::{Platform, Sourced};
+use crate::error::Fallible;
+use crate::session::Session;
+use crate::tool::{Distro, Node, Npm, Yarn};
+use crate::version::parse_version;
+use log::debug;
+use semver::Version;
+
+/// The environment variable that `volta use` sets to select versions for one shell session
+pub const SHELL_PLATFORM_VAR: &str = "VOLTA_USE";
+
+/// The versions selected with `volta use` for the current shell session
+///
+/// The shims find them in the `VOLTA_USE` environment variable, as exact versions like
+/// `node@18.17.1,yarn@1.22.19`, so that they don't have to resolve anything. Each version
+/// overrides the one from the project or the default platform, and anything that isn't selected
+/// comes from there as usual.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShellPlatform {
+    pub node: Option<Version>,
+    pub npm: Option<Version>,
+    pub yarn: Option<Version>,
+}
+
+impl ShellPlatform {
+    /// Parses the value of `VOLTA_USE`, ignoring it if it wasn't written by `volta use`
+    pub fn parse(token: &str) -> Option<Self> {
+        let mut platform = ShellPlatform::default();
+        for entry in token.split(',').filter(|entry| !entry.is_empty()) {
+            let parsed = entry
+                .split_once('@')
+                .and_then(|(tool, version)| Some((tool, parse_version(version).ok()?)));
+            match parsed {
+                Some(("node", version)) => platform.node = Some(version),
+                Some(("npm", version)) => platform.npm = Some(version),
+                Some(("yarn", version)) => platform.yarn = Some(version),
+                _ => {
+                    debug!("Ignoring invalid {} '{}'", SHELL_PLATFORM_VAR, token);
+                    return None;
+                }
+            }
+        }
+        Some(platform)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.node.is_none() && self.npm.is_none() && self.yarn.is_none()
+    }
+
+    /// Fetches the selected versions, so that the shims don't have to
+    pub fn fetch(&self, session: &mut Session) -> Fallible<()> {
+        if let Some(node) = &self.node {
+            Node::new(node.clone()).ensure_fetched(session)?;
+        }
+        if let Some(npm) = &self.npm {
+            Npm::new(npm.clone()).ensure_fetched(session)?;
+        }
+        if let Some(yarn) = &self.yarn {
+            Yarn::new(yarn.clone()).ensure_fetched(session)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the selected versions on top of the platform from the project or the default
+    pub(super) fn merge(&self, base: Option<Platform>) -> Option<Platform> {
+        let (node, npm, yarn) = match base {
+            Some(base) => (Some(base.node), base.npm, base.yarn),
+            None => (None, None, None),
+        };
+
+        let node = self.node.clone().map(Sourced::with_shell).or(node)?;
+        Some(Platform {
+            node,
+            npm: self.npm.clone().map(Sourced::with_shell).or(npm),
+            yarn: self.yarn.clone().map(Sourced::with_shell).or(yarn),
+        })
+    }
+}
+
+/// Formats the value of `VOLTA_USE`
+impl fmt::Display for ShellPlatform {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let versions = [
+            ("node", &self.node),
+            ("npm", &self.npm),
+            ("yarn", &self.yarn),
+        ];
+        let entries: Vec<String> = versions
+            .iter()
+            .filter_map(|(tool, version)| {
+                version
+                    .as_ref()
+                    .map(|version| format!("{}@{}", tool, version))
+            })
+            .collect();
+        f.write_str(&entries.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_through_the_token() {
+        let platform = ShellPlatform {
+            node: Some(Version::new(18, 17, 1)),
+            npm: None,
+            yarn: Some(Version::new(1, 22, 19)),
+        };
+
+        let token = platform.to_string();
+        assert_eq!(token, "node@18.17.1,yarn@1.22.19");
+        assert_eq!(ShellPlatform::parse(&token), Some(platform));
+    }
+
+    #[test]
+    fn ignores_invalid_tokens() {
+        assert_eq!(ShellPlatform::parse("node@18"), None);
+        assert_eq!(ShellPlatform::parse("typescript@5.0.0"), None);
+        assert_eq!(ShellPlatform::parse(""), Some(ShellPlatform::default()));
+    }
+}
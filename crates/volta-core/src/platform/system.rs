@@ -20,6 +20,20 @@ impl System {
             new_path = new_path.remove(remove_path);
         }
 
+        // Inside WSL, the Windows PATH is appended to the Linux one, but a Windows installation
+        // of Node can't be used in place of a Linux one, so it is never the system Node
+        #[cfg(target_os = "linux")]
+        if crate::wsl::is_wsl() {
+            let windows_node_dirs: Vec<_> = std::env::var_os("PATH")
+                .iter()
+                .flat_map(std::env::split_paths)
+                .filter(|dir| crate::wsl::is_windows_node_dir(dir))
+                .collect();
+            for dir in windows_node_dirs {
+                new_path = new_path.remove(dir);
+            }
+        }
+
         new_path.join().with_context(build_path_error)
     }
 }
@@ -558,3 +558,16 @@ mod cli_platform {
         }
     }
 }
+
+#[test]
+fn platform_spec_serializes_like_project_pins() {
+    let spec = PlatformSpec {
+        node: Version::parse("16.14.2").unwrap(),
+        npm: None,
+        yarn: Some(Version::parse("1.22.18").unwrap()),
+    };
+
+    let json = serde_json::to_string(&spec).unwrap();
+    assert_eq!(json, r#"{"node":"16.14.2","yarn":"1.22.18"}"#);
+    assert_eq!(serde_json::from_str::<PlatformSpec>(&json).unwrap(), spec);
+}
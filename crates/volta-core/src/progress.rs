@@ -0,0 +1,213 @@
+//! Provides the sinks that progress is reported to
+//!
+//! Downloads, installs, and waits for the Volta directory report their progress through a
+//! `Progress`, which passes each update on to the sink in use. By default that is a progress bar
+//! in the terminal, but `VOLTA_PROGRESS` can choose plain lines (`plain`), JSON lines for other
+//! programs to read (`json`), or nothing at all (`none`). Programs that embed Volta can provide a
+//! sink of their own with `SessionBuilder::progress_sink`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock};
+
+use crate::environment;
+use crate::style::{action_str, terminal_bar, terminal_spinner};
+use archive::Origin;
+use indicatif::ProgressBar;
+use lazy_static::lazy_static;
+use serde_json::json;
+
+const PROGRESS_ENV_VAR: &str = "VOLTA_PROGRESS";
+
+lazy_static! {
+    static ref SINK: RwLock<Arc<dyn ProgressSink>> = RwLock::new(default_sink());
+}
+
+/// Receives the progress of the tasks that Volta runs
+pub trait ProgressSink: Send + Sync {
+    /// Starts a task with a known length, like unpacking a download
+    ///
+    /// The `origin` tells whether the archive is being downloaded or was already on disk, and
+    /// `details` names what is unpacked, e.g. `node@16.14.2`.
+    fn bar(&self, origin: Origin, details: &str, len: u64) -> Box<dyn ProgressHandle>;
+
+    /// Starts a task with no known length, like fetching a registry index
+    fn spinner(&self, message: &str) -> Box<dyn ProgressHandle>;
+}
+
+/// Receives the updates of a single task
+pub trait ProgressHandle: Send {
+    fn inc(&self, delta: u64);
+
+    fn finish(&self);
+}
+
+/// The progress of a single task, which is reported to the sink that was in use when it started
+pub struct Progress {
+    handle: Box<dyn ProgressHandle>,
+}
+
+impl Progress {
+    /// Creates a progress that isn't reported anywhere
+    pub(crate) fn hidden() -> Self {
+        Progress {
+            handle: Box::new(Silent),
+        }
+    }
+
+    pub(crate) fn bar(origin: Origin, details: &str, len: u64) -> Self {
+        Progress {
+            handle: sink().bar(origin, details, len),
+        }
+    }
+
+    pub(crate) fn spinner(message: &str) -> Self {
+        Progress {
+            handle: sink().spinner(message),
+        }
+    }
+
+    pub fn inc(&self, delta: u64) {
+        self.handle.inc(delta);
+    }
+
+    pub fn finish_and_clear(&self) {
+        self.handle.finish();
+    }
+}
+
+/// Replaces the sink that progress is reported to, for every session in the process
+pub fn set_sink(sink: Arc<dyn ProgressSink>) {
+    *SINK.write().unwrap_or_else(PoisonError::into_inner) = sink;
+}
+
+fn sink() -> Arc<dyn ProgressSink> {
+    SINK.read().unwrap_or_else(PoisonError::into_inner).clone()
+}
+
+fn default_sink() -> Arc<dyn ProgressSink> {
+    match environment::var(PROGRESS_ENV_VAR).as_deref() {
+        Ok("plain") => Arc::new(PlainSink),
+        Ok("json") => Arc::new(JsonSink),
+        Ok("none") => Arc::new(SilentSink),
+        _ => Arc::new(TerminalSink),
+    }
+}
+
+/// Draws progress bars and spinners in the terminal
+pub struct TerminalSink;
+
+impl ProgressSink for TerminalSink {
+    fn bar(&self, origin: Origin, details: &str, len: u64) -> Box<dyn ProgressHandle> {
+        Box::new(terminal_bar(origin, details, len))
+    }
+
+    fn spinner(&self, message: &str) -> Box<dyn ProgressHandle> {
+        Box::new(terminal_spinner(message.to_string()))
+    }
+}
+
+impl ProgressHandle for ProgressBar {
+    fn inc(&self, delta: u64) {
+        ProgressBar::inc(self, delta);
+    }
+
+    fn finish(&self) {
+        self.finish_and_clear();
+    }
+}
+
+/// Writes a line to stderr when each task starts, for logs and terminals that can't redraw
+pub struct PlainSink;
+
+impl ProgressSink for PlainSink {
+    fn bar(&self, origin: Origin, details: &str, _len: u64) -> Box<dyn ProgressHandle> {
+        eprintln!("{} {}", action_str(origin), details);
+        Box::new(Silent)
+    }
+
+    fn spinner(&self, message: &str) -> Box<dyn ProgressHandle> {
+        eprintln!("{}", message);
+        Box::new(Silent)
+    }
+}
+
+/// Writes each update to stderr as a line of JSON, for other programs to follow along
+///
+/// Tasks with a known length are reported each time another percent is done, rather than for
+/// every chunk that is read.
+pub struct JsonSink;
+
+impl ProgressSink for JsonSink {
+    fn bar(&self, origin: Origin, details: &str, len: u64) -> Box<dyn ProgressHandle> {
+        Box::new(JsonTask::start(
+            format!("{} {}", action_str(origin), details),
+            Some(len),
+        ))
+    }
+
+    fn spinner(&self, message: &str) -> Box<dyn ProgressHandle> {
+        Box::new(JsonTask::start(message.to_string(), None))
+    }
+}
+
+struct JsonTask {
+    task: String,
+    total: Option<u64>,
+    done: AtomicU64,
+    percent: AtomicU64,
+}
+
+impl JsonTask {
+    fn start(task: String, total: Option<u64>) -> Self {
+        eprintln!(
+            "{}",
+            json!({ "event": "start", "task": task, "total": total })
+        );
+        JsonTask {
+            task,
+            total,
+            done: AtomicU64::new(0),
+            percent: AtomicU64::new(0),
+        }
+    }
+}
+
+impl ProgressHandle for JsonTask {
+    fn inc(&self, delta: u64) {
+        let done = self.done.fetch_add(delta, Ordering::SeqCst) + delta;
+        if let Some(total) = self.total.filter(|total| *total > 0) {
+            let percent = (done * 100 / total).min(100);
+            if self.percent.swap(percent, Ordering::SeqCst) != percent {
+                eprintln!(
+                    "{}",
+                    json!({ "event": "progress", "task": self.task, "done": done, "total": total })
+                );
+            }
+        }
+    }
+
+    fn finish(&self) {
+        eprintln!("{}", json!({ "event": "finish", "task": self.task }));
+    }
+}
+
+/// Doesn't report progress at all
+pub struct SilentSink;
+
+impl ProgressSink for SilentSink {
+    fn bar(&self, _origin: Origin, _details: &str, _len: u64) -> Box<dyn ProgressHandle> {
+        Box::new(Silent)
+    }
+
+    fn spinner(&self, _message: &str) -> Box<dyn ProgressHandle> {
+        Box::new(Silent)
+    }
+}
+
+struct Silent;
+
+impl ProgressHandle for Silent {
+    fn inc(&self, _delta: u64) {}
+
+    fn finish(&self) {}
+}
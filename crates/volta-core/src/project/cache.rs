@@ -0,0 +1,313 @@
+//! Provides a cache of loaded projects, so that shims don't need to re-read and parse every
+//! manifest in a project's `extends` chain each time they run.
+//!
+//! Each entry records the size and modification time of every file that went into it, including
+//! files that were checked for but didn't exist, and is only used while none of them change. For a
+//! project without pins of its own, that includes every file that was checked while looking for
+//! its workspace root.
+
+use std::collections::BTreeMap;
+use std::fs::{create_dir_all, read_dir, File};
+use std::io::{self, BufReader, Write};
+use std::iter::once;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::engines::Engines;
+use super::lock::lockfile_path;
+use super::serial::pin_file_path;
+use super::tolerant;
+use super::workspace::workspace_root_sources;
+use super::{LockedPlatform, ManagerPin};
+use crate::fs::{cache_key, FileStamp};
+use crate::layout::volta_home;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use tempfile::NamedTempFile;
+
+/// The version of the cached format, which has to change whenever `CachedProject` does, since
+/// entries written in an older format can't be told apart from current ones
+const CACHE_FORMAT: u32 = 2;
+
+/// The parts of a Project that are read from its manifests
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub(super) struct CachedProject {
+    pub workspace_manifests: Vec<PathBuf>,
+    pub platform: Option<LockedPlatform>,
+    pub engines: Engines,
+    pub pin_file: Option<PathBuf>,
+    pub has_own_pins: bool,
+    pub package_manager: Option<ManagerPin>,
+    pub tools: BTreeMap<String, String>,
+    /// The key of the `engines` ranges and pins that conflicts were last shown for
+    pub warned_engines: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    sources: Vec<FileStamp>,
+    project: CachedProject,
+}
+
+/// Returns the path to the cache file for the project with the given manifest
+///
+/// Whether manifests are read tolerantly is part of the key, since it changes what is read from
+/// the same files.
+pub(super) fn cache_file(manifest_file: &Path) -> Option<PathBuf> {
+    let key = cache_key(&(
+        CACHE_FORMAT,
+        tolerant::enabled(),
+        manifest_file.to_string_lossy(),
+    ));
+
+    let home = volta_home().ok()?;
+    Some(home.project_cache_dir().join(key))
+}
+
+/// Reads the cached project from the given cache file, if it is still up to date
+pub(super) fn load(cache_file: &Path) -> Option<CachedProject> {
+    let file = File::open(cache_file).ok()?;
+    let entry: Entry = match bincode::deserialize_from(BufReader::new(file)) {
+        Ok(entry) => entry,
+        Err(error) => {
+            debug!(
+                "Ignoring invalid project cache {}: {}",
+                cache_file.display(),
+                error
+            );
+            return None;
+        }
+    };
+
+    let up_to_date = entry.sources.iter().all(FileStamp::is_current);
+
+    if up_to_date {
+        Some(entry.project)
+    } else {
+        None
+    }
+}
+
+/// Reads every cached project that still exists, whether or not its entry is up to date
+///
+/// An out-of-date entry still shows what the project used the last time Volta ran in it.
+pub(super) fn load_all() -> Vec<CachedProject> {
+    let dir = match volta_home() {
+        Ok(home) => home.project_cache_dir().to_owned(),
+        Err(_) => return Vec::new(),
+    };
+    let entries = match read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    entries
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let file = File::open(entry.path()).ok()?;
+            let entry: Entry = bincode::deserialize_from(BufReader::new(file)).ok()?;
+            // Once a project is deleted, its manifest (the first source) no longer exists
+            if entry.sources.first().map_or(false, FileStamp::exists) {
+                Some(entry.project)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Writes a loaded project to the given cache file
+///
+/// `loaded` is the time at which loading started. Failures are only logged, since the cache is
+/// purely an optimization.
+pub(super) fn store(
+    cache_file: &Path,
+    manifest_file: &Path,
+    project: CachedProject,
+    loaded: SystemTime,
+) {
+    if let Err(error) = try_store(cache_file, manifest_file, project, loaded) {
+        debug!(
+            "Unable to write project cache {}: {}",
+            cache_file.display(),
+            error
+        );
+    }
+}
+
+fn try_store(
+    cache_file: &Path,
+    manifest_file: &Path,
+    project: CachedProject,
+    loaded: SystemTime,
+) -> io::Result<()> {
+    let sources = source_files(manifest_file, &project)
+        .iter()
+        .map(|path| FileStamp::current(path))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    if sources.iter().any(|stamp| stamp.is_racy(loaded)) {
+        return Ok(());
+    }
+
+    write_entry(cache_file, &Entry { sources, project })
+}
+
+/// Records in the given cache file that conflicts with `engines` were shown for the given key
+///
+/// The entry keeps the stamps it was written with, so it is only updated while it is still up to
+/// date. Failures are only logged, which at worst shows the warning again.
+pub(super) fn record_engines_warning(cache_file: &Path, key: String) {
+    let result = read_entry(cache_file).and_then(|mut entry| {
+        if !entry.sources.iter().all(FileStamp::is_current) {
+            return Ok(());
+        }
+        entry.project.warned_engines = Some(key);
+        write_entry(cache_file, &entry)
+    });
+
+    if let Err(error) = result {
+        debug!(
+            "Unable to record engines warning in {}: {}",
+            cache_file.display(),
+            error
+        );
+    }
+}
+
+fn read_entry(cache_file: &Path) -> io::Result<Entry> {
+    let file = File::open(cache_file)?;
+    bincode::deserialize_from(BufReader::new(file))
+        .map_err(|error| io::Error::new(io::ErrorKind::Other, error))
+}
+
+fn write_entry(cache_file: &Path, entry: &Entry) -> io::Result<()> {
+    let dir = cache_file
+        .parent()
+        .expect("File paths always have a parent");
+    create_dir_all(dir)?;
+
+    let contents =
+        bincode::serialize(entry).map_err(|error| io::Error::new(io::ErrorKind::Other, error))?;
+    let mut staging = NamedTempFile::new_in(dir)?;
+    staging.write_all(&contents)?;
+    staging.persist(cache_file).map_err(|error| error.error)?;
+
+    Ok(())
+}
+
+/// Returns every file that is read (or checked for) when loading a project, starting with its
+/// manifest
+fn source_files(manifest_file: &Path, project: &CachedProject) -> Vec<PathBuf> {
+    let workspace_manifests = project.workspace_manifests.iter().map(PathBuf::as_path);
+    let mut sources = Vec::new();
+    for manifest in once(manifest_file).chain(workspace_manifests) {
+        sources.push(manifest.to_owned());
+        sources.push(pin_file_path(manifest));
+    }
+    sources.push(lockfile_path(manifest_file));
+
+    // Only a project without pins of its own looks for its workspace root
+    if !project.has_own_pins {
+        let dir = manifest_file
+            .parent()
+            .expect("File paths always have a parent");
+        sources.extend(workspace_root_sources(dir));
+    }
+
+    sources
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::write;
+    use std::time::Duration;
+
+    fn cached() -> CachedProject {
+        CachedProject {
+            workspace_manifests: Vec::new(),
+            platform: None,
+            engines: Engines::default(),
+            pin_file: None,
+            has_own_pins: false,
+            package_manager: None,
+            tools: BTreeMap::new(),
+            warned_engines: None,
+        }
+    }
+
+    #[test]
+    fn invalidated_by_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        let cache = dir.path().join("cache").join("project");
+        write(&manifest, "{}").unwrap();
+
+        // Pretend the project was loaded well after the manifest was written
+        let loaded = SystemTime::now() + Duration::from_secs(10);
+        store(&cache, &manifest, cached(), loaded);
+        assert_eq!(load(&cache), Some(cached()));
+
+        // Creating a `volta.json` changes the pins, so the entry is out of date
+        write(dir.path().join("volta.json"), "{}").unwrap();
+        assert_eq!(load(&cache), None);
+    }
+
+    #[test]
+    fn invalidated_by_new_workspace_roots() {
+        let dir = tempfile::tempdir().unwrap();
+        let member_dir = dir.path().join("packages").join("member");
+        let manifest = member_dir.join("package.json");
+        let cache = dir.path().join("cache").join("project");
+        create_dir_all(&member_dir).unwrap();
+        write(&manifest, "{}").unwrap();
+        write(dir.path().join("package.json"), "{}").unwrap();
+
+        let loaded = SystemTime::now() + Duration::from_secs(10);
+        store(&cache, &manifest, cached(), loaded);
+        assert_eq!(load(&cache), Some(cached()));
+
+        // Defining a workspace in an ancestor makes it the root whose pins the member uses
+        write(
+            dir.path().join("pnpm-workspace.yaml"),
+            "packages:\n  - 'packages/*'\n",
+        )
+        .unwrap();
+        assert_eq!(load(&cache), None);
+    }
+
+    #[test]
+    fn records_engines_warning() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        let cache = dir.path().join("cache").join("project");
+        write(&manifest, "{}").unwrap();
+
+        let loaded = SystemTime::now() + Duration::from_secs(10);
+        store(&cache, &manifest, cached(), loaded);
+        record_engines_warning(&cache, "key".into());
+
+        let warned = CachedProject {
+            warned_engines: Some("key".into()),
+            ..cached()
+        };
+        assert_eq!(load(&cache), Some(warned));
+
+        // The entry keeps its stamps, so it still goes out of date when the pins change
+        write(dir.path().join("volta.json"), "{}").unwrap();
+        assert_eq!(load(&cache), None);
+    }
+
+    #[test]
+    fn skips_racy_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let manifest = dir.path().join("package.json");
+        let cache = dir.path().join("project");
+        write(&manifest, "{}").unwrap();
+
+        store(&cache, &manifest, cached(), SystemTime::now());
+        assert!(!cache.exists());
+    }
+}
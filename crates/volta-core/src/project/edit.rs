@@ -0,0 +1,400 @@
+//! Provides format-preserving edits of the pins in a package manifest or `volta.json`.
+//!
+//! Rather than re-serializing the whole manifest, which loses the user's indentation, key order,
+//! and line endings, these functions locate the relevant members in the original text and splice
+//! in only the changed values.
+
+use std::ops::Range;
+
+use serde_json::Value;
+
+/// A single `"key": value` member of a JSON object, as byte offsets into the source text
+struct Member {
+    key: String,
+    start: usize,
+    value: Range<usize>,
+}
+
+/// The members of a JSON object, along with the byte offsets of its braces
+struct Object {
+    open: usize,
+    close: usize,
+    members: Vec<Member>,
+}
+
+/// Sets (or removes, if `value` is `None`) a key in the `volta` hash of the manifest text
+///
+/// Returns `None` if the text isn't structured the way we expect, in which case the caller should
+/// fall back to re-serializing the manifest.
+pub(super) fn set_volta_key(
+    contents: &str,
+    key: &str,
+    value: Option<&str>,
+    indent: &str,
+) -> Option<String> {
+    let newline = detect_newline(contents);
+    let root = parse_object(contents, skip_whitespace(contents, 0))?;
+
+    let volta = match root.members.iter().find(|member| member.key == "volta") {
+        Some(member) => member,
+        None => {
+            // Without a `volta` hash, there's nothing to remove
+            let value = value?;
+            let base = line_indent(contents, root.open);
+            let hash = format!(
+                "{{{nl}{base}{ind}{ind}{key}: {value}{nl}{base}{ind}}}",
+                nl = newline,
+                base = base,
+                ind = indent,
+                key = quote(key),
+                value = quote(value)
+            );
+            return Some(insert_member(
+                contents, &root, "volta", &hash, indent, newline,
+            ));
+        }
+    };
+
+    if contents.as_bytes().get(volta.value.start) != Some(&b'{') {
+        return None;
+    }
+    let hash = parse_object(contents, volta.value.start)?;
+
+    // Removing the last pin removes the whole `volta` hash, rather than leaving it empty
+    if value.is_none() && hash.members.len() == 1 && hash.members[0].key == key {
+        let index = root
+            .members
+            .iter()
+            .position(|member| member.key == "volta")?;
+        return Some(remove_member(contents, &root, index));
+    }
+
+    Some(set_member(contents, &hash, key, value, indent, newline))
+}
+
+/// Sets (or removes, if `value` is `None`) a top-level key in the text of a `volta.json` pin file
+///
+/// Returns `None` if the text isn't structured the way we expect
+pub(super) fn set_root_key(
+    contents: &str,
+    key: &str,
+    value: Option<&str>,
+    indent: &str,
+) -> Option<String> {
+    let root = parse_object(contents, skip_whitespace(contents, 0))?;
+    Some(set_member(
+        contents,
+        &root,
+        key,
+        value,
+        indent,
+        detect_newline(contents),
+    ))
+}
+
+/// Removes the entire `volta` hash from the manifest text
+///
+/// Returns `None` if the text isn't structured the way we expect
+pub(super) fn remove_volta_hash(contents: &str) -> Option<String> {
+    let root = parse_object(contents, skip_whitespace(contents, 0))?;
+
+    match root.members.iter().position(|member| member.key == "volta") {
+        Some(index) => Some(remove_member(contents, &root, index)),
+        None => Some(contents.to_string()),
+    }
+}
+
+/// Sets or removes a member of an object, keeping its position if it already exists
+fn set_member(
+    contents: &str,
+    object: &Object,
+    key: &str,
+    value: Option<&str>,
+    indent: &str,
+    newline: &str,
+) -> String {
+    match (
+        object.members.iter().position(|member| member.key == key),
+        value,
+    ) {
+        (Some(index), Some(value)) => {
+            let member = &object.members[index];
+            splice(contents, member.value.clone(), &quote(value))
+        }
+        (Some(index), None) => remove_member(contents, object, index),
+        (None, Some(value)) => insert_member(contents, object, key, &quote(value), indent, newline),
+        (None, None) => contents.to_string(),
+    }
+}
+
+fn detect_newline(contents: &str) -> &'static str {
+    if contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    }
+}
+
+fn quote(value: &str) -> String {
+    Value::String(value.into()).to_string()
+}
+
+fn splice(contents: &str, range: Range<usize>, replacement: &str) -> String {
+    let mut output = String::with_capacity(contents.len() + replacement.len());
+    output.push_str(&contents[..range.start]);
+    output.push_str(replacement);
+    output.push_str(&contents[range.end..]);
+    output
+}
+
+/// Adds a new member at the end of an object, matching the indentation of the existing members
+fn insert_member(
+    contents: &str,
+    object: &Object,
+    key: &str,
+    value: &str,
+    indent: &str,
+    newline: &str,
+) -> String {
+    match object.members.last() {
+        Some(last) => {
+            let member_indent = line_indent(contents, last.start);
+            let text = format!(",{}{}{}: {}", newline, member_indent, quote(key), value);
+            splice(contents, last.value.end..last.value.end, &text)
+        }
+        None => {
+            let base = line_indent(contents, object.open);
+            let text = format!(
+                "{nl}{base}{ind}{key}: {value}{nl}{base}",
+                nl = newline,
+                base = base,
+                ind = indent,
+                key = quote(key),
+                value = value
+            );
+            splice(contents, object.open + 1..object.close, &text)
+        }
+    }
+}
+
+/// Removes a member from an object, along with the separator that joined it to its neighbor
+fn remove_member(contents: &str, object: &Object, index: usize) -> String {
+    let member = &object.members[index];
+    let range = if index > 0 {
+        object.members[index - 1].value.end..member.value.end
+    } else if let Some(next) = object.members.get(1) {
+        member.start..next.start
+    } else {
+        object.open + 1..object.close
+    };
+
+    splice(contents, range, "")
+}
+
+/// Returns the leading whitespace of the line containing the given offset
+fn line_indent(contents: &str, offset: usize) -> &str {
+    let line_start = contents[..offset].rfind('\n').map_or(0, |index| index + 1);
+    let line = &contents[line_start..];
+    let end = line
+        .find(|c: char| c != ' ' && c != '\t')
+        .unwrap_or(line.len());
+    &line[..end]
+}
+
+fn skip_whitespace(contents: &str, mut offset: usize) -> usize {
+    let bytes = contents.as_bytes();
+    while offset < bytes.len() && bytes[offset].is_ascii_whitespace() {
+        offset += 1;
+    }
+    offset
+}
+
+/// Parses the members of the object whose opening brace is at `open`
+fn parse_object(contents: &str, open: usize) -> Option<Object> {
+    let bytes = contents.as_bytes();
+    if bytes.get(open) != Some(&b'{') {
+        return None;
+    }
+
+    let mut members = Vec::new();
+    let mut offset = skip_whitespace(contents, open + 1);
+
+    if bytes.get(offset) == Some(&b'}') {
+        return Some(Object {
+            open,
+            close: offset,
+            members,
+        });
+    }
+
+    loop {
+        let start = offset;
+        let key_end = skip_string(bytes, start)?;
+        let key = serde_json::from_str(&contents[start..key_end]).ok()?;
+
+        offset = skip_whitespace(contents, key_end);
+        if bytes.get(offset) != Some(&b':') {
+            return None;
+        }
+
+        let value_start = skip_whitespace(contents, offset + 1);
+        let value_end = skip_value(bytes, value_start)?;
+        members.push(Member {
+            key,
+            start,
+            value: value_start..value_end,
+        });
+
+        offset = skip_whitespace(contents, value_end);
+        match bytes.get(offset) {
+            Some(b',') => offset = skip_whitespace(contents, offset + 1),
+            Some(b'}') => {
+                return Some(Object {
+                    open,
+                    close: offset,
+                    members,
+                })
+            }
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the offset just past the string literal starting at `start`
+fn skip_string(bytes: &[u8], start: usize) -> Option<usize> {
+    if bytes.get(start) != Some(&b'"') {
+        return None;
+    }
+
+    let mut offset = start + 1;
+    while offset < bytes.len() {
+        match bytes[offset] {
+            b'\\' => offset += 2,
+            b'"' => return Some(offset + 1),
+            _ => offset += 1,
+        }
+    }
+    None
+}
+
+/// Returns the offset just past the JSON value starting at `start`
+fn skip_value(bytes: &[u8], start: usize) -> Option<usize> {
+    match bytes.get(start)? {
+        b'"' => skip_string(bytes, start),
+        b'{' | b'[' => {
+            let mut depth = 0;
+            let mut offset = start;
+            while offset < bytes.len() {
+                match bytes[offset] {
+                    b'"' => {
+                        offset = skip_string(bytes, offset)?;
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(offset + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                offset += 1;
+            }
+            None
+        }
+        _ => {
+            let end = bytes[start..]
+                .iter()
+                .position(|b| b.is_ascii_whitespace() || matches!(b, b',' | b'}' | b']'))
+                .map_or(bytes.len(), |len| start + len);
+            Some(end)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{remove_volta_hash, set_root_key, set_volta_key};
+
+    #[test]
+    fn replaces_existing_value() {
+        let contents = "{\n    \"name\": \"x\",\n    \"volta\": {\"node\": \"12.0.0\", \"yarn\": \"1.2.0\"}\n}\n";
+        let expected = "{\n    \"name\": \"x\",\n    \"volta\": {\"node\": \"14.1.0\", \"yarn\": \"1.2.0\"}\n}\n";
+        assert_eq!(
+            set_volta_key(contents, "node", Some("14.1.0"), "    ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn inserts_into_existing_hash() {
+        let contents = "{\n\t\"volta\": {\n\t\t\"node\": \"12.0.0\"\n\t},\n\t\"name\": \"x\"\n}";
+        let expected =
+            "{\n\t\"volta\": {\n\t\t\"node\": \"12.0.0\",\n\t\t\"yarn\": \"1.22.4\"\n\t},\n\t\"name\": \"x\"\n}";
+        assert_eq!(
+            set_volta_key(contents, "yarn", Some("1.22.4"), "\t").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn creates_hash() {
+        let contents = "{\r\n  \"name\": \"x\",\r\n  \"files\": [\"a\", \"b\"]\r\n}\r\n";
+        let expected = "{\r\n  \"name\": \"x\",\r\n  \"files\": [\"a\", \"b\"],\r\n  \"volta\": {\r\n    \"node\": \"14.1.0\"\r\n  }\r\n}\r\n";
+        assert_eq!(
+            set_volta_key(contents, "node", Some("14.1.0"), "  ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn removes_value() {
+        let contents =
+            "{\n  \"volta\": {\n    \"node\": \"12.0.0\",\n    \"npm\": \"6.0.0\"\n  }\n}\n";
+        let expected = "{\n  \"volta\": {\n    \"node\": \"12.0.0\"\n  }\n}\n";
+        assert_eq!(
+            set_volta_key(contents, "npm", None, "  ").unwrap(),
+            expected
+        );
+
+        let contents =
+            "{\n  \"volta\": {\n    \"npm\": \"6.0.0\",\n    \"node\": \"12.0.0\"\n  }\n}\n";
+        assert_eq!(
+            set_volta_key(contents, "npm", None, "  ").unwrap(),
+            expected
+        );
+    }
+
+    #[test]
+    fn removes_last_value() {
+        let contents = "{\n  \"name\": \"x\",\n  \"volta\": {\n    \"node\": \"12.0.0\"\n  }\n}\n";
+        assert_eq!(
+            set_volta_key(contents, "node", None, "  ").unwrap(),
+            "{\n  \"name\": \"x\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn removes_hash() {
+        let contents = "{\n  \"name\": \"x\",\n  \"volta\": {\n    \"node\": \"12.0.0\"\n  }\n}\n";
+        assert_eq!(
+            remove_volta_hash(contents).unwrap(),
+            "{\n  \"name\": \"x\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn sets_root_key() {
+        let contents = "{\n  \"node\": \"12.0.0\"\n}\n";
+        assert_eq!(
+            set_root_key(contents, "yarn", Some("1.22.4"), "  ").unwrap(),
+            "{\n  \"node\": \"12.0.0\",\n  \"yarn\": \"1.22.4\"\n}\n"
+        );
+        assert_eq!(
+            set_root_key("{}\n", "node", Some("14.1.0"), "  ").unwrap(),
+            "{\n  \"node\": \"14.1.0\"\n}\n"
+        );
+        assert_eq!(set_root_key(contents, "node", None, "  ").unwrap(), "{}\n");
+    }
+}
@@ -0,0 +1,90 @@
+//! Provides the `Engines` type, which checks a project's pinned platform against the
+//! `engines` ranges in its manifest.
+
+use crate::fs::cache_key;
+use crate::platform::PlatformSpec;
+use crate::version::{parse_requirements, satisfies};
+use log::debug;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// The `engines` ranges for each tool that Volta can pin
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub(super) struct Engines {
+    pub node: Option<String>,
+    pub npm: Option<String>,
+    pub yarn: Option<String>,
+}
+
+/// A pinned tool version that doesn't satisfy the range in the manifest's `engines`
+pub struct EngineConflict {
+    pub tool: &'static str,
+    pub version: Version,
+    pub range: String,
+}
+
+impl Engines {
+    /// Returns the pinned versions in the platform that fall outside of their `engines` range
+    ///
+    /// Note: A pin that uses the npm bundled with Node isn't checked, since we don't know the
+    /// bundled version without fetching Node.
+    pub fn conflicts(&self, platform: &PlatformSpec) -> Vec<EngineConflict> {
+        self.conflicts_with(
+            &platform.node,
+            platform.npm.as_ref(),
+            platform.yarn.as_ref(),
+        )
+    }
+
+    /// Returns the given tool versions that fall outside of their `engines` range
+    pub fn conflicts_with(
+        &self,
+        node: &Version,
+        npm: Option<&Version>,
+        yarn: Option<&Version>,
+    ) -> Vec<EngineConflict> {
+        let versions = [
+            ("node", &self.node, Some(node)),
+            ("npm", &self.npm, npm),
+            ("yarn", &self.yarn, yarn),
+        ];
+
+        versions
+            .iter()
+            .filter_map(|(tool, range, version)| match (range, version) {
+                (Some(range), Some(version)) => conflict(*tool, range, version),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Computes a key that identifies this combination of `engines` and pinned platform
+    pub fn cache_key(&self, platform: &PlatformSpec) -> String {
+        cache_key(&(
+            self,
+            platform.node.to_string(),
+            platform.npm.as_ref().map(Version::to_string),
+            platform.yarn.as_ref().map(Version::to_string),
+        ))
+    }
+}
+
+fn conflict(tool: &'static str, range: &str, version: &Version) -> Option<EngineConflict> {
+    match parse_requirements(range) {
+        // Prereleases are compared as their release, so that e.g. a Node RC isn't reported
+        Ok(requirement) if !satisfies(&requirement, version, true) => Some(EngineConflict {
+            tool,
+            version: version.clone(),
+            range: range.into(),
+        }),
+        Ok(_) => None,
+        Err(_) => {
+            debug!(
+                "Ignoring unparseable engines range for {}: '{}'",
+                tool, range
+            );
+            None
+        }
+    }
+}
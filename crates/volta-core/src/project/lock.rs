@@ -0,0 +1,106 @@
+//! Provides the `Lockfile` type, which records the exact versions that a project's
+//! floating pins (version ranges or tags) were resolved to.
+
+use std::fs::{read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use super::serial::ManifestKey;
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::version::version_serde;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+/// Returns the path to the lockfile that sits alongside the given project manifest
+pub(super) fn lockfile_path(manifest_file: &Path) -> PathBuf {
+    manifest_file
+        .parent()
+        .expect("File paths always have a parent")
+        .join("volta.lock")
+}
+
+/// The contents of a project's `volta.lock`
+#[derive(Default, Deserialize, Serialize)]
+pub(super) struct Lockfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    node: Option<LockEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    npm: Option<LockEntry>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    yarn: Option<LockEntry>,
+}
+
+/// A single floating pin along with the exact version it was resolved to
+#[derive(Deserialize, Serialize)]
+struct LockEntry {
+    spec: String,
+    #[serde(with = "version_serde")]
+    version: Version,
+}
+
+impl Lockfile {
+    /// Reads the lockfile at the given path, returning `None` if it doesn't exist
+    pub fn from_file(file: &Path) -> Fallible<Option<Self>> {
+        if !file.exists() {
+            return Ok(None);
+        }
+
+        let contents = read_to_string(file).with_context(|| ErrorKind::ReadLockfileError {
+            file: file.to_owned(),
+        })?;
+
+        serde_json::from_str(&contents)
+            .with_context(|| ErrorKind::ParseLockfileError {
+                file: file.to_owned(),
+            })
+            .map(Some)
+    }
+
+    /// Writes the lockfile to the given path, replacing any existing contents
+    pub fn write(&self, file: &Path) -> Fallible<()> {
+        let mut contents =
+            serde_json::to_string_pretty(self).with_context(|| ErrorKind::WriteLockfileError {
+                file: file.to_owned(),
+            })?;
+        contents.push('\n');
+
+        write(file, contents).with_context(|| ErrorKind::WriteLockfileError {
+            file: file.to_owned(),
+        })
+    }
+
+    /// Returns the locked version for a tool, if it was locked for the same floating spec
+    ///
+    /// A lock entry for a different spec is stale (the pin was edited after locking), so it is ignored
+    pub fn locked(&self, key: ManifestKey, spec: &str) -> Option<&Version> {
+        self.entry(key)
+            .as_ref()
+            .filter(|entry| entry.spec == spec)
+            .map(|entry| &entry.version)
+    }
+
+    /// Records the version that a floating spec for a tool resolved to
+    pub fn insert(&mut self, key: ManifestKey, spec: String, version: Version) {
+        *self.entry_mut(key) = Some(LockEntry { spec, version });
+    }
+
+    /// Removes the lock entry for a tool, returning `true` if there was one
+    pub fn remove(&mut self, key: ManifestKey) -> bool {
+        self.entry_mut(key).take().is_some()
+    }
+
+    fn entry(&self, key: ManifestKey) -> &Option<LockEntry> {
+        match key {
+            ManifestKey::Node => &self.node,
+            ManifestKey::Npm => &self.npm,
+            ManifestKey::Yarn => &self.yarn,
+        }
+    }
+
+    fn entry_mut(&mut self, key: ManifestKey) -> &mut Option<LockEntry> {
+        match key {
+            ManifestKey::Node => &mut self.node,
+            ManifestKey::Npm => &mut self.npm,
+            ManifestKey::Yarn => &mut self.yarn,
+        }
+    }
+}
@@ -1,49 +1,122 @@
 //! Provides the `Project` type, which represents a Node project tree in
 //! the filesystem.
 
-use std::convert::{TryFrom, TryInto};
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::env;
 use std::ffi::OsStr;
+use std::fmt;
 use std::iter::once;
 use std::path::{Path, PathBuf};
+use std::time::SystemTime;
 
 use lazycell::LazyCell;
 use semver::Version;
 
+use crate::environment;
 use crate::error::{Context, ErrorKind, Fallible, VoltaError};
+use crate::fs::read_file;
 use crate::layout::volta_home;
-use crate::platform::PlatformSpec;
-use crate::tool::BinConfig;
+use crate::platform::{Platform, PlatformSpec, Sourced};
+use crate::session::Session;
+use crate::style::tool_version;
+use crate::tool::{node, npm, yarn, BinConfig};
+use crate::version::{option_version_serde, version_serde, VersionSpec};
 use chain_map::ChainMap;
+use dunce::canonicalize;
 use indexmap::IndexSet;
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
 
+mod cache;
+mod edit;
+mod engines;
+mod lock;
 mod serial;
 #[cfg(test)]
 mod tests;
+mod tolerant;
+mod workspace;
 
-use serial::{update_manifest, Manifest, ManifestKey};
+use cache::CachedProject;
+pub use engines::EngineConflict;
+use engines::Engines;
+use lock::{lockfile_path, Lockfile};
+use serial::{
+    clear_pin_file, pin_file_path, remove_volta_hash, update_manifest, update_pin_file, Manifest,
+    ManifestKey,
+};
+pub use workspace::MemberConflict;
+
+/// The environment variable that npm reads its `engine-strict` setting from
+const ENGINE_STRICT_ENV_VAR: &str = "npm_config_engine_strict";
 
 /// A lazily loaded Project
 pub struct LazyProject {
     project: LazyCell<Option<Project>>,
+    /// The directory to look for the project in, if not the current directory of the process
+    dir: Option<PathBuf>,
 }
 
 impl LazyProject {
     pub fn init() -> Self {
         LazyProject {
             project: LazyCell::new(),
+            dir: None,
+        }
+    }
+
+    /// Constructs a `LazyProject` that looks for the project in a directory, rather than in the
+    /// current directory of the process
+    pub fn init_in(dir: PathBuf) -> Self {
+        LazyProject {
+            project: LazyCell::new(),
+            dir: Some(dir),
         }
     }
 
     pub fn get(&self) -> Fallible<Option<&Project>> {
-        let project = self.project.try_borrow_with(Project::for_current_dir)?;
+        let dir = self.dir.as_deref();
+        let project = self
+            .project
+            .try_borrow_with(|| Project::for_current_dir(dir))?;
         Ok(project.as_ref())
     }
 
     pub fn get_mut(&mut self) -> Fallible<Option<&mut Project>> {
-        let project = self.project.try_borrow_mut_with(Project::for_current_dir)?;
+        let dir = self.dir.as_deref();
+        let project = self
+            .project
+            .try_borrow_mut_with(|| Project::for_current_dir(dir))?;
         Ok(project.as_mut())
     }
+
+    /// Replaces the current project with the root of the workspace that contains it
+    pub fn use_workspace_root(&mut self) -> Fallible<()> {
+        let (current, root) = match self.get()? {
+            Some(project) => (
+                project.manifest_file.clone(),
+                workspace::find_workspace_root(project.root_dir()),
+            ),
+            None => return Err(ErrorKind::NotInPackage.into()),
+        };
+
+        match root {
+            Some(root) if root == current => Ok(()),
+            Some(root) => {
+                debug!("Using workspace root: {}", root.display());
+                self.project.replace(Some(Project::from_file(root)?));
+                Ok(())
+            }
+            None => Err(ErrorKind::NotInWorkspace.into()),
+        }
+    }
+
+    /// Replaces the current project with the project that contains the given directory, if any
+    pub fn use_dir(&mut self, dir: PathBuf) -> Fallible<()> {
+        self.project.replace(Project::for_dir(dir)?);
+        Ok(())
+    }
 }
 
 /// A Node project workspace in the filesystem
@@ -51,37 +124,146 @@ impl LazyProject {
 pub struct Project {
     manifest_file: PathBuf,
     workspace_manifests: IndexSet<PathBuf>,
-    dependencies: ChainMap<String, String>,
+    dependencies: LazyCell<ChainMap<String, String>>,
     platform: Option<PlatformSpec>,
+    pending: Option<LockedPlatform>,
+    engines: Engines,
+    staged: Option<StagedPins>,
+    pin_file: Option<PathBuf>,
+    has_own_pins: bool,
+    package_manager: Option<ManagerPin>,
+    tools: BTreeMap<String, String>,
+    warned_engines: Option<String>,
+}
+
+/// Pins that have been applied to a Project but not yet written to disk
+#[derive(Default)]
+#[cfg_attr(test, derive(Debug))]
+struct StagedPins {
+    manifest: Vec<(ManifestKey, Option<String>)>,
+    locks: Vec<(ManifestKey, String, Version)>,
 }
 
 impl Project {
-    /// Creates an optional Project instance from the current directory
-    fn for_current_dir() -> Fallible<Option<Self>> {
-        let current_dir = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
-        Self::for_dir(current_dir)
+    /// Creates an optional Project instance from the current directory, which is that of the
+    /// process unless one is given
+    ///
+    /// Since this runs on every shim invocation, the project is loaded from the project cache
+    /// when possible
+    fn for_current_dir(current_dir: Option<&Path>) -> Fallible<Option<Self>> {
+        let current_dir = match current_dir {
+            Some(dir) => dir.to_owned(),
+            None => env::current_dir().with_context(|| ErrorKind::CurrentDirError)?,
+        };
+        find_closest_manifest(current_dir)
+            .map(Self::from_file_cached)
+            .transpose()
     }
 
     /// Creates an optional Project instance from the specified directory
     ///
     /// Will search ancestors to find a `package.json` and use that as the root of the project
     fn for_dir(base_dir: PathBuf) -> Fallible<Option<Self>> {
-        match find_closest_root(base_dir) {
-            Some(mut project) => {
-                project.push("package.json");
-                Self::from_file(project).map(Some)
-            }
-            None => Ok(None),
+        find_closest_manifest(base_dir)
+            .map(Self::from_file)
+            .transpose()
+    }
+
+    /// Creates a Project instance from the given manifest file, using the project cache if it is
+    /// up to date and updating it otherwise
+    fn from_file_cached(manifest_file: PathBuf) -> Fallible<Self> {
+        let cache_file = match cache::cache_file(&manifest_file) {
+            Some(cache_file) => cache_file,
+            None => return Self::from_file(manifest_file),
+        };
+
+        if let Some(cached) = cache::load(&cache_file) {
+            debug!("Using cached project: {}", manifest_file.display());
+            return Self::from_cached(manifest_file, cached);
+        }
+
+        let loaded = SystemTime::now();
+        let project = Self::from_file(manifest_file)?;
+        cache::store(
+            &cache_file,
+            &project.manifest_file,
+            project.to_cached(),
+            loaded,
+        );
+        Ok(project)
+    }
+
+    fn from_cached(manifest_file: PathBuf, cached: CachedProject) -> Fallible<Self> {
+        let (platform, pending) = split_platform(cached.platform)?;
+
+        Ok(Project {
+            manifest_file,
+            workspace_manifests: cached.workspace_manifests.into_iter().collect(),
+            dependencies: LazyCell::new(),
+            platform,
+            pending,
+            engines: cached.engines,
+            staged: None,
+            pin_file: cached.pin_file,
+            has_own_pins: cached.has_own_pins,
+            package_manager: cached.package_manager,
+            tools: cached.tools,
+            warned_engines: cached.warned_engines,
+        })
+    }
+
+    fn to_cached(&self) -> CachedProject {
+        let platform = match (&self.platform, &self.pending) {
+            (Some(platform), _) => Some(LockedPlatform {
+                node: Some(platform.node.clone()),
+                npm: platform.npm.clone(),
+                yarn: platform.yarn.clone(),
+                unlocked: Vec::new(),
+            }),
+            (None, Some(pending)) => Some(pending.clone()),
+            (None, None) => None,
+        };
+
+        CachedProject {
+            workspace_manifests: self.workspace_manifests.iter().cloned().collect(),
+            platform,
+            engines: self.engines.clone(),
+            pin_file: self.pin_file.clone(),
+            has_own_pins: self.has_own_pins,
+            package_manager: self.package_manager.clone(),
+            tools: self.tools.clone(),
+            warned_engines: self.warned_engines.clone(),
         }
     }
 
     /// Creates a Project instance from the given package manifest file (`package.json`)
     fn from_file(manifest_file: PathBuf) -> Fallible<Self> {
         let manifest = Manifest::from_file(&manifest_file)?;
-        let mut dependencies: ChainMap<String, String> = manifest.dependency_maps.collect();
         let mut workspace_manifests = IndexSet::new();
         let mut platform = manifest.platform;
         let mut extends = manifest.extends;
+        let mut package_manager = manifest.package_manager;
+        let mut tools = manifest.tools;
+        let engines = manifest.engines;
+        let pin_file = manifest.pin_file;
+        let has_own_pins = platform.is_some() || extends.is_some();
+
+        // A workspace member without pins of its own uses the pins from the workspace root, even
+        // if there are other (unpinned) projects in between
+        if !has_own_pins {
+            let dir = manifest_file
+                .parent()
+                .expect("File paths always have a parent");
+            if let Some(root) =
+                workspace::find_workspace_root(dir).filter(|root| *root != manifest_file)
+            {
+                debug!("Using pins from workspace root: {}", root.display());
+                extends = Some(
+                    canonicalize(&root)
+                        .with_context(|| ErrorKind::ExtensionPathError { path: root })?,
+                );
+            }
+        }
 
         // Iterate the `volta.extends` chain, parsing each file in turn
         while let Some(path) = extends {
@@ -99,7 +281,10 @@ impl Project {
 
             let manifest = Manifest::from_file(&path)?;
             workspace_manifests.insert(path);
-            dependencies.extend(manifest.dependency_maps);
+            package_manager = package_manager.or(manifest.package_manager);
+            for (name, version) in manifest.tools {
+                tools.entry(name).or_insert(version);
+            }
 
             platform = match (platform, manifest.platform) {
                 (Some(base), Some(ext)) => Some(base.merge(ext)),
@@ -110,13 +295,31 @@ impl Project {
             extends = manifest.extends;
         }
 
-        let platform = platform.map(TryInto::try_into).transpose()?;
+        // Floating pins use the versions recorded in `volta.lock`. If any of them aren't locked,
+        // the platform stays pending until they are resolved by `resolve_unlocked_pins`
+        let lockfile = Lockfile::from_file(&lockfile_path(&manifest_file))?;
+        let locked = platform.map(|partial| {
+            let mut locked = partial.lock(lockfile.as_ref());
+            if let Some(pin) = &package_manager {
+                locked.fill_package_manager(pin);
+            }
+            locked
+        });
+        let (platform, pending) = split_platform(locked)?;
 
         Ok(Project {
             manifest_file,
             workspace_manifests,
-            dependencies,
+            dependencies: LazyCell::new(),
             platform,
+            pending,
+            engines,
+            staged: None,
+            pin_file,
+            has_own_pins,
+            package_manager,
+            tools,
+            warned_engines: None,
         })
     }
 
@@ -125,22 +328,240 @@ impl Project {
         &self.manifest_file
     }
 
+    /// Returns the directory that contains the project manifest
+    pub fn root_dir(&self) -> &Path {
+        self.manifest_file
+            .parent()
+            .expect("File paths always have a parent")
+    }
+
+    /// Returns the file that this project's pins are written to
+    ///
+    /// This is the sibling `volta.json` if there is one, and the manifest file otherwise
+    pub fn pin_file(&self) -> &Path {
+        self.pin_file.as_deref().unwrap_or(&self.manifest_file)
+    }
+
+    /// Writes any new pins to a `volta.json` next to the manifest, rather than to the manifest
+    /// itself, creating the file if it doesn't exist yet
+    ///
+    /// Projects that already have pins in their manifest keep using it, so that the pins aren't
+    /// split between the two files. So do projects with a `volta.json` in the manifest format,
+    /// so that it isn't overwritten.
+    pub fn use_pin_file(&mut self) {
+        let pin_file = pin_file_path(&self.manifest_file);
+        if self.pin_file.is_none() && !self.has_own_pins && !pin_file.exists() {
+            self.pin_file = Some(pin_file);
+        }
+    }
+
     /// Returns an iterator of paths to all of the workspace roots
     pub fn workspace_roots(&self) -> impl Iterator<Item = &Path> {
         // Invariant: self.manifest_file and self.extensions will only contain paths to files that we successfully loaded
-        once(&self.manifest_file)
-            .chain(self.workspace_manifests.iter())
+        self.manifest_files()
             .map(|file| file.parent().expect("File paths always have a parent"))
     }
 
+    /// Returns an iterator of the project manifest, followed by the manifests that it extends
+    fn manifest_files(&self) -> impl Iterator<Item = &PathBuf> {
+        once(&self.manifest_file).chain(self.workspace_manifests.iter())
+    }
+
+    /// Returns the members of this project's workspace whose pins disagree with this project
+    ///
+    /// A member conflicts if it pins a tool differently, or if it doesn't inherit this project's
+    /// pins through `extends` (and so won't follow future changes to them)
+    pub fn workspace_conflicts(&self) -> Fallible<Vec<MemberConflict>> {
+        let root = Manifest::from_file(&self.manifest_file)?;
+        let root_pins = match root.platform {
+            Some(pins) => pins,
+            None => return Ok(Vec::new()),
+        };
+        let root_file =
+            canonicalize(&self.manifest_file).with_context(|| ErrorKind::PackageReadError {
+                file: self.manifest_file.clone(),
+            })?;
+
+        let mut conflicts = Vec::new();
+        for member_file in workspace::member_manifests(self.root_dir(), &root.workspaces) {
+            let member = Project::from_file(member_file.clone())?;
+            let extends_root = member.workspace_manifests.contains(&root_file);
+
+            let member_pins = Manifest::from_file(&member_file)?.platform;
+            let mismatched: Vec<_> = [ManifestKey::Node, ManifestKey::Npm, ManifestKey::Yarn]
+                .iter()
+                .filter_map(|key| {
+                    let expected = root_pins.get(*key)?.to_string();
+                    let pinned = member_pins.as_ref()?.get(*key)?.to_string();
+                    if pinned != expected {
+                        Some((key.to_string(), pinned, expected))
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+
+            if !mismatched.is_empty() || !extends_root {
+                conflicts.push(MemberConflict {
+                    manifest: member_file,
+                    mismatched,
+                    extends_root,
+                });
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    /// Removes a workspace member's conflicting pins and points its `extends` at this project,
+    /// so that the member inherits the workspace pins
+    pub fn clean_workspace_member(&self, conflict: &MemberConflict) -> Fallible<()> {
+        let member = Project::from_file(conflict.manifest.clone())?;
+
+        let mut updates: Vec<_> = conflict
+            .mismatched
+            .iter()
+            .map(|(tool, _, _)| (tool.as_str(), None))
+            .collect();
+        if !conflict.extends_root {
+            updates.push((
+                "extends",
+                workspace::relative_root(&self.manifest_file, &conflict.manifest),
+            ));
+        }
+
+        member.update_pins(&updates)
+    }
+
     /// Returns a reference to the Project's `PlatformSpec`, if available
+    ///
+    /// Note: If the project has floating pins without an entry in `volta.lock`, the platform is
+    /// not available until they are resolved with `resolve_unlocked_pins`
     pub fn platform(&self) -> Option<&PlatformSpec> {
         self.platform.as_ref()
     }
 
+    /// Uses the package manager from the `packageManager` field in a platform that doesn't come
+    /// from the project's pins, e.g. the user default platform
+    pub fn apply_package_manager(&self, platform: &mut Platform) {
+        if let Some(pin) = &self.package_manager {
+            let version = Some(Sourced::with_project(pin.version.clone()));
+            match pin.key {
+                ManifestKey::Npm => platform.npm = version,
+                ManifestKey::Yarn => platform.yarn = version,
+                ManifestKey::Node => {}
+            }
+        }
+    }
+
+    /// Returns the package tools pinned in `volta.tools`, mapping each package name to the
+    /// requested version
+    pub fn pinned_tools(&self) -> &BTreeMap<String, String> {
+        &self.tools
+    }
+
+    /// Returns the pinned tool versions that don't satisfy the `engines` ranges in the manifest
+    pub fn engine_conflicts(&self) -> Vec<EngineConflict> {
+        match &self.platform {
+            Some(platform) => self.engines.conflicts(platform),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns the versions in a resolved platform that don't satisfy the `engines` ranges in the
+    /// manifest, including versions that come from the user's default platform
+    pub fn platform_engine_conflicts(&self, platform: &Platform) -> Vec<EngineConflict> {
+        self.engines.conflicts_with(
+            &platform.node.value,
+            platform.npm.as_ref().map(|npm| &npm.value),
+            platform.yarn.as_ref().map(|yarn| &yarn.value),
+        )
+    }
+
+    /// Determines whether npm is configured with `engine-strict`, so that it refuses to install
+    /// when the `engines` ranges aren't satisfied
+    ///
+    /// The environment takes precedence over the project's `.npmrc`, as it does for npm. User and
+    /// global npm configuration isn't checked, since that applies to every project.
+    pub fn engine_strict(&self) -> bool {
+        match environment::var(ENGINE_STRICT_ENV_VAR) {
+            Ok(value) => value.trim() == "true",
+            Err(_) => read_engine_strict(&self.root_dir().join(".npmrc")),
+        }
+    }
+
+    /// Returns the Yarn release vendored with `yarnPath` in `.yarnrc.yml`, if any
+    ///
+    /// As with Yarn, the closest workspace root with a `yarnPath` wins, and the path is relative
+    /// to the directory of its `.yarnrc.yml`.
+    pub fn yarn_path(&self) -> Option<PathBuf> {
+        self.workspace_roots().find_map(|root| {
+            let release = root.join(read_yarn_path(&root.join(".yarnrc.yml"))?);
+            if release.is_file() {
+                Some(release)
+            } else {
+                debug!(
+                    "Ignoring yarnPath that doesn't exist: {}",
+                    release.display()
+                );
+                None
+            }
+        })
+    }
+
+    /// Shows a warning for each pinned tool version that conflicts with the manifest's `engines`
+    pub fn warn_engine_conflicts(&self) {
+        for conflict in self.engine_conflicts() {
+            warn!(
+                "the pinned {} does not satisfy the engines range '{}' in {}",
+                tool_version(conflict.tool, &conflict.version),
+                conflict.range,
+                self.manifest_file.display()
+            );
+        }
+    }
+
+    /// Shows engine conflict warnings, but only the first time for a given set of pins
+    ///
+    /// Shims run this on every invocation, so the warning is recorded in the project cache entry
+    /// to avoid repeating it until the pins or `engines` ranges change.
+    pub fn warn_engine_conflicts_once(&mut self) {
+        let platform = match &self.platform {
+            Some(platform) => platform,
+            None => return,
+        };
+
+        if self.engines.conflicts(platform).is_empty() {
+            return;
+        }
+
+        let key = self.engines.cache_key(platform);
+        if self.warned_engines.as_ref() == Some(&key) {
+            return;
+        }
+
+        self.warn_engine_conflicts();
+        if let Some(cache_file) = cache::cache_file(&self.manifest_file) {
+            cache::record_engines_warning(&cache_file, key.clone());
+        }
+        self.warned_engines = Some(key);
+    }
+
     /// Returns true if the project dependency map contains the specified dependency
-    pub fn has_direct_dependency(&self, dependency: &str) -> bool {
-        self.dependencies.contains_key(dependency)
+    pub fn has_direct_dependency(&self, dependency: &str) -> Fallible<bool> {
+        // Dependencies are only needed by some commands, so they are read on first use
+        let dependencies = self.dependencies.try_borrow_with(|| {
+            let mut dependencies: ChainMap<String, String> =
+                Manifest::from_file(&self.manifest_file)?
+                    .dependency_maps
+                    .collect();
+            for file in &self.workspace_manifests {
+                dependencies.extend(Manifest::from_file(file)?.dependency_maps);
+            }
+            Ok::<_, VoltaError>(dependencies)
+        })?;
+
+        Ok(dependencies.contains_key(dependency))
     }
 
     /// Returns true if the input binary name is a direct dependency of the input project
@@ -150,7 +571,7 @@ impl Project {
 
             return match BinConfig::from_file_if_exists(config_path)? {
                 None => Ok(false),
-                Some(config) => Ok(self.has_direct_dependency(&config.package)),
+                Some(config) => self.has_direct_dependency(&config.package),
             };
         }
         Ok(false)
@@ -183,7 +604,7 @@ impl Project {
 
     /// Pins the Node version in this project's manifest file
     pub fn pin_node(&mut self, version: Version) -> Fallible<()> {
-        update_manifest(&self.manifest_file, ManifestKey::Node, Some(&version))?;
+        self.write_pin(ManifestKey::Node, Some(version.to_string()))?;
 
         if let Some(platform) = self.platform.as_mut() {
             platform.node = version;
@@ -200,10 +621,12 @@ impl Project {
 
     /// Pins the npm version in this project's manifest file
     pub fn pin_npm(&mut self, version: Option<Version>) -> Fallible<()> {
-        if let Some(platform) = self.platform.as_mut() {
-            update_manifest(&self.manifest_file, ManifestKey::Npm, version.as_ref())?;
+        if self.platform.is_some() {
+            self.write_pin(ManifestKey::Npm, version.as_ref().map(Version::to_string))?;
 
-            platform.npm = version;
+            if let Some(platform) = self.platform.as_mut() {
+                platform.npm = version;
+            }
 
             Ok(())
         } else {
@@ -213,10 +636,12 @@ impl Project {
 
     /// Pins the Yarn version in this project's manifest file
     pub fn pin_yarn(&mut self, version: Option<Version>) -> Fallible<()> {
-        if let Some(platform) = self.platform.as_mut() {
-            update_manifest(&self.manifest_file, ManifestKey::Yarn, version.as_ref())?;
+        if self.platform.is_some() {
+            self.write_pin(ManifestKey::Yarn, version.as_ref().map(Version::to_string))?;
 
-            platform.yarn = version;
+            if let Some(platform) = self.platform.as_mut() {
+                platform.yarn = version;
+            }
 
             Ok(())
         } else {
@@ -226,6 +651,322 @@ impl Project {
             .into())
         }
     }
+
+    /// Removes the Node pin from this project's manifest file
+    ///
+    /// Since npm and Yarn can't be pinned without Node, this removes their pins as well
+    pub fn unpin_node(&mut self) -> Fallible<()> {
+        self.unpin(&[ManifestKey::Node, ManifestKey::Npm, ManifestKey::Yarn])
+    }
+
+    /// Removes the npm pin from this project's manifest file
+    pub fn unpin_npm(&mut self) -> Fallible<()> {
+        self.unpin(&[ManifestKey::Npm])
+    }
+
+    /// Removes the Yarn pin from this project's manifest file
+    pub fn unpin_yarn(&mut self) -> Fallible<()> {
+        self.unpin(&[ManifestKey::Yarn])
+    }
+
+    /// Removes the entire `volta` hash from this project's manifest file, including `extends`
+    ///
+    /// If the project has a `volta.json`, its pins are removed as well
+    pub fn unpin_all(&mut self) -> Fallible<()> {
+        remove_volta_hash(&self.manifest_file)?;
+        if let Some(pin_file) = &self.pin_file {
+            clear_pin_file(pin_file)?;
+        }
+        remove_locks(
+            &self.manifest_file,
+            &[ManifestKey::Node, ManifestKey::Npm, ManifestKey::Yarn],
+        )?;
+        self.reload()
+    }
+
+    fn unpin(&mut self, keys: &[ManifestKey]) -> Fallible<()> {
+        let updates: Vec<_> = keys.iter().map(|key| (*key, None)).collect();
+        self.update_pins(&updates)?;
+        remove_locks(&self.manifest_file, keys)?;
+        self.reload()
+    }
+
+    /// Re-reads the project from its manifest, so that any pins inherited through `extends`
+    /// take effect after a pin is removed
+    fn reload(&mut self) -> Fallible<()> {
+        *self = Project::from_file(self.manifest_file.clone())?;
+        Ok(())
+    }
+
+    /// Pins Node in this project's manifest file to a version range or tag
+    ///
+    /// The currently pinned Node version is recorded as the resolved version in `volta.lock`
+    pub fn lock_node(&mut self, spec: String) -> Fallible<()> {
+        match self.platform.as_ref() {
+            Some(platform) => {
+                let version = platform.node.clone();
+                self.lock_pin(ManifestKey::Node, spec, version)
+            }
+            None => Err(ErrorKind::NoPinnedNodeVersion {
+                tool: "node".into(),
+            }
+            .into()),
+        }
+    }
+
+    /// Pins npm in this project's manifest file to a version range or tag
+    ///
+    /// The currently pinned npm version is recorded as the resolved version in `volta.lock`
+    pub fn lock_npm(&mut self, spec: String) -> Fallible<()> {
+        match self.platform.as_ref().map(|platform| platform.npm.clone()) {
+            Some(Some(version)) => self.lock_pin(ManifestKey::Npm, spec, version),
+            // The pin resolved to the bundled npm, so there is no version to lock
+            Some(None) => Ok(()),
+            None => Err(ErrorKind::NoPinnedNodeVersion { tool: "npm".into() }.into()),
+        }
+    }
+
+    /// Pins Yarn in this project's manifest file to a version range or tag
+    ///
+    /// The currently pinned Yarn version is recorded as the resolved version in `volta.lock`
+    pub fn lock_yarn(&mut self, spec: String) -> Fallible<()> {
+        match self
+            .platform
+            .as_ref()
+            .and_then(|platform| platform.yarn.clone())
+        {
+            Some(version) => self.lock_pin(ManifestKey::Yarn, spec, version),
+            None => Err(ErrorKind::NoPinnedNodeVersion {
+                tool: "Yarn".into(),
+            }
+            .into()),
+        }
+    }
+
+    fn lock_pin(&mut self, key: ManifestKey, spec: String, version: Version) -> Fallible<()> {
+        self.write_pin(key, Some(spec.clone()))?;
+
+        match self.staged.as_mut() {
+            Some(staged) => {
+                staged.locks.retain(|(staged_key, _, _)| *staged_key != key);
+                staged.locks.push((key, spec, version));
+            }
+            None => write_locks(&self.manifest_file, vec![(key, spec, version)])?,
+        }
+
+        Ok(())
+    }
+
+    /// Defers writing pins to disk until `commit_pins` is called
+    ///
+    /// This allows pinning multiple tools with a single write of the manifest, so that a failure
+    /// while pinning one tool doesn't leave the others half-pinned.
+    pub fn stage_pins(&mut self) {
+        if self.staged.is_none() {
+            self.staged = Some(StagedPins::default());
+        }
+    }
+
+    /// Whether pins are being staged, rather than written as soon as they change
+    pub fn pins_staged(&self) -> bool {
+        self.staged.is_some()
+    }
+
+    /// Drops the staged pins without writing them
+    pub fn discard_pins(&mut self) {
+        self.staged = None;
+    }
+
+    /// Writes all of the staged pins to the manifest (and `volta.lock`, if needed) at once
+    pub fn commit_pins(&mut self) -> Fallible<()> {
+        let staged = match self.staged.take() {
+            Some(staged) => staged,
+            None => return Ok(()),
+        };
+
+        if !staged.manifest.is_empty() {
+            self.update_pins(&staged.manifest)?;
+        }
+        if !staged.locks.is_empty() {
+            write_locks(&self.manifest_file, staged.locks)?;
+        }
+        Ok(())
+    }
+
+    fn write_pin(&mut self, key: ManifestKey, value: Option<String>) -> Fallible<()> {
+        match self.staged.as_mut() {
+            Some(staged) => {
+                // Only the last value staged for a tool is written
+                staged.manifest.retain(|(staged_key, _)| *staged_key != key);
+                staged.manifest.push((key, value));
+                Ok(())
+            }
+            None => self.update_pins(&[(key, value)]),
+        }
+    }
+
+    fn update_pins<K: fmt::Display>(&self, updates: &[(K, Option<String>)]) -> Fallible<()> {
+        match &self.pin_file {
+            Some(pin_file) => update_pin_file(pin_file, updates),
+            None => update_manifest(&self.manifest_file, updates),
+        }
+    }
+
+    /// Completes the pending platform with the versions that its unlocked pins resolved to
+    ///
+    /// If the project already has a `volta.lock`, the new versions are recorded there as well
+    fn apply_resolved(
+        &mut self,
+        resolved: Vec<(ManifestKey, String, Option<Version>)>,
+    ) -> Fallible<()> {
+        let mut pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+
+        let path = lockfile_path(&self.manifest_file);
+        let mut lockfile = Lockfile::from_file(&path)?;
+
+        for (key, spec, version) in resolved {
+            if let (Some(lockfile), Some(version)) = (lockfile.as_mut(), &version) {
+                lockfile.insert(key, spec, version.clone());
+            }
+
+            match key {
+                ManifestKey::Node => pending.node = version,
+                ManifestKey::Npm => pending.npm = version,
+                ManifestKey::Yarn => pending.yarn = version,
+            }
+        }
+
+        if let Some(lockfile) = lockfile {
+            lockfile.write(&path)?;
+        }
+
+        pending.unlocked.clear();
+        self.platform = Some(PlatformSpec::try_from(pending)?);
+        Ok(())
+    }
+}
+
+/// Resolves any floating pins in the current project that have no up-to-date entry in `volta.lock`
+///
+/// When running frozen, an unlocked pin is an error instead, so that every machine is guaranteed
+/// to use exactly the versions recorded in the lockfile.
+pub fn resolve_unlocked_pins(session: &mut Session) -> Fallible<()> {
+    let unlocked = match session
+        .project()?
+        .and_then(|project| project.pending.as_ref())
+    {
+        Some(pending) => pending.unlocked.clone(),
+        None => return Ok(()),
+    };
+
+    if session.frozen() {
+        if let Some((key, spec)) = unlocked.into_iter().next() {
+            return Err(ErrorKind::LockfileOutOfDate {
+                tool: key.to_string(),
+                spec,
+            }
+            .into());
+        }
+        return Ok(());
+    }
+
+    let mut resolved = Vec::with_capacity(unlocked.len());
+    for (key, spec) in unlocked {
+        let matching = spec.parse()?;
+        let version = match key {
+            ManifestKey::Node => Some(node::resolve(matching, session)?),
+            ManifestKey::Npm => npm::resolve(matching, session)?,
+            ManifestKey::Yarn => Some(yarn::resolve(matching, session)?),
+        };
+        debug!("Resolved floating project pin {}@{}", key, spec);
+        resolved.push((key, spec, version));
+    }
+
+    match session.project_mut()? {
+        Some(project) => project.apply_resolved(resolved),
+        None => Ok(()),
+    }
+}
+
+/// Records resolved versions for floating pins in the `volta.lock` next to the manifest
+fn write_locks(manifest_file: &Path, locks: Vec<(ManifestKey, String, Version)>) -> Fallible<()> {
+    let path = lockfile_path(manifest_file);
+    let mut lockfile = Lockfile::from_file(&path)?.unwrap_or_default();
+    for (key, spec, version) in locks {
+        lockfile.insert(key, spec, version);
+    }
+    lockfile.write(&path)
+}
+
+/// Removes lock entries for tools that are no longer pinned, if the project has a `volta.lock`
+fn remove_locks(manifest_file: &Path, keys: &[ManifestKey]) -> Fallible<()> {
+    let path = lockfile_path(manifest_file);
+    if let Some(mut lockfile) = Lockfile::from_file(&path)? {
+        let mut changed = false;
+        for key in keys {
+            changed |= lockfile.remove(*key);
+        }
+        if changed {
+            lockfile.write(&path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Separates a project's pins into its platform, if all of the pins are locked, or a pending
+/// platform that still needs to be resolved by `resolve_unlocked_pins`
+fn split_platform(
+    locked: Option<LockedPlatform>,
+) -> Fallible<(Option<PlatformSpec>, Option<LockedPlatform>)> {
+    match locked {
+        Some(locked) if locked.unlocked.is_empty() => {
+            Ok((Some(PlatformSpec::try_from(locked)?), None))
+        }
+        Some(locked) => Ok((None, Some(locked))),
+        None => Ok((None, None)),
+    }
+}
+
+/// Reads the `engine-strict` setting from an `.npmrc` file, which is `false` if it isn't set
+fn read_engine_strict(npmrc: &Path) -> bool {
+    let contents = match read_file(npmrc) {
+        Ok(Some(contents)) => contents,
+        _ => return false,
+    };
+
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(';') && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| key.trim() == "engine-strict")
+        .map(|(_, value)| value.trim().trim_matches('"') == "true")
+        .last()
+        .unwrap_or(false)
+}
+
+/// Reads the top-level `yarnPath` setting from a `.yarnrc.yml` file
+///
+/// Only simple `key: value` lines are understood, which is how Yarn itself writes the setting.
+fn read_yarn_path(yarnrc: &Path) -> Option<String> {
+    let contents = read_file(yarnrc).ok()??;
+
+    contents.lines().find_map(|line| {
+        let value = line.strip_prefix("yarnPath:")?.trim();
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        Some(value.to_string()).filter(|value| !value.is_empty())
+    })
+}
+
+/// Finds the manifest of the closest project root to the given directory
+fn find_closest_manifest(base_dir: PathBuf) -> Option<PathBuf> {
+    find_closest_root(base_dir).map(|mut root| {
+        root.push("package.json");
+        root
+    })
 }
 
 fn is_node_root(dir: &Path) -> bool {
@@ -255,10 +996,27 @@ pub(crate) fn find_closest_root(mut dir: PathBuf) -> Option<PathBuf> {
     Some(dir)
 }
 
+/// A tool version as written in a project manifest
+enum Pin {
+    /// An exact version, which is used as-is
+    Exact(Version),
+    /// A version range or tag, which must be resolved to an exact version before use
+    Floating(String),
+}
+
+impl fmt::Display for Pin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Pin::Exact(version) => version.fmt(f),
+            Pin::Floating(spec) => f.write_str(spec),
+        }
+    }
+}
+
 struct PartialPlatform {
-    node: Option<Version>,
-    npm: Option<Version>,
-    yarn: Option<Version>,
+    node: Option<Pin>,
+    npm: Option<Pin>,
+    yarn: Option<Pin>,
 }
 
 impl PartialPlatform {
@@ -269,18 +1027,141 @@ impl PartialPlatform {
             yarn: self.yarn.or(other.yarn),
         }
     }
+
+    fn get(&self, key: ManifestKey) -> Option<&Pin> {
+        match key {
+            ManifestKey::Node => self.node.as_ref(),
+            ManifestKey::Npm => self.npm.as_ref(),
+            ManifestKey::Yarn => self.yarn.as_ref(),
+        }
+    }
+}
+
+impl PartialPlatform {
+    /// Replaces floating pins with their versions from the lockfile, if available
+    ///
+    /// Any floating pins without an up-to-date lock entry are collected in `unlocked`
+    fn lock(self, lockfile: Option<&Lockfile>) -> LockedPlatform {
+        let mut unlocked = Vec::new();
+        let mut lock_pin = |key: ManifestKey, pin: Option<Pin>| match pin {
+            Some(Pin::Exact(version)) => Some(version),
+            Some(Pin::Floating(spec)) => {
+                match lockfile.and_then(|lockfile| lockfile.locked(key, &spec)) {
+                    Some(version) => Some(version.clone()),
+                    None => {
+                        unlocked.push((key, spec));
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let node = lock_pin(ManifestKey::Node, self.node);
+        let npm = lock_pin(ManifestKey::Npm, self.npm);
+        let yarn = lock_pin(ManifestKey::Yarn, self.yarn);
+
+        LockedPlatform {
+            node,
+            npm,
+            yarn,
+            unlocked,
+        }
+    }
+}
+
+/// A package manager version from the Corepack `packageManager` field
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct ManagerPin {
+    key: ManifestKey,
+    #[serde(with = "version_serde")]
+    version: Version,
+}
+
+/// A version of Node, npm, or Yarn that a project is pinned to
+pub(crate) struct KnownPin {
+    /// The name of the tool: `node`, `npm`, or `yarn`
+    pub tool: String,
+    pub version: VersionSpec,
+}
+
+/// Returns the pins of every project that Volta has run in, as recorded in the project cache
+///
+/// Floating pins that haven't been locked are returned as the ranges they were pinned with, since
+/// the version they resolve to can change.
+pub(crate) fn known_pins() -> Vec<KnownPin> {
+    let mut pins = Vec::new();
+    for platform in cache::load_all()
+        .into_iter()
+        .filter_map(|project| project.platform)
+    {
+        let locked = vec![
+            ("node", platform.node),
+            ("npm", platform.npm),
+            ("yarn", platform.yarn),
+        ];
+        for (tool, version) in locked {
+            if let Some(version) = version {
+                pins.push(KnownPin {
+                    tool: tool.into(),
+                    version: VersionSpec::Exact(version),
+                });
+            }
+        }
+        for (key, spec) in platform.unlocked {
+            if let Ok(version) = spec.parse() {
+                pins.push(KnownPin {
+                    tool: key.to_string(),
+                    version,
+                });
+            }
+        }
+    }
+    pins
+}
+
+/// A project platform with floating pins replaced by exact versions, where known
+#[derive(Clone, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct LockedPlatform {
+    #[serde(with = "option_version_serde")]
+    node: Option<Version>,
+    #[serde(with = "option_version_serde")]
+    npm: Option<Version>,
+    #[serde(with = "option_version_serde")]
+    yarn: Option<Version>,
+    unlocked: Vec<(ManifestKey, String)>,
+}
+
+impl LockedPlatform {
+    /// Uses the version from `packageManager` for a tool that isn't pinned in the `volta` hash
+    fn fill_package_manager(&mut self, pin: &ManagerPin) {
+        if self.unlocked.iter().any(|(key, _)| *key == pin.key) {
+            return;
+        }
+
+        let version = match pin.key {
+            ManifestKey::Npm => &mut self.npm,
+            ManifestKey::Yarn => &mut self.yarn,
+            ManifestKey::Node => return,
+        };
+        if version.is_none() {
+            *version = Some(pin.version.clone());
+        }
+    }
 }
 
-impl TryFrom<PartialPlatform> for PlatformSpec {
+impl TryFrom<LockedPlatform> for PlatformSpec {
     type Error = VoltaError;
 
-    fn try_from(partial: PartialPlatform) -> Fallible<PlatformSpec> {
-        let node = partial.node.ok_or(ErrorKind::NoProjectNodeInManifest)?;
+    fn try_from(locked: LockedPlatform) -> Fallible<PlatformSpec> {
+        let node = locked.node.ok_or(ErrorKind::NoProjectNodeInManifest)?;
 
         Ok(PlatformSpec {
             node,
-            npm: partial.npm,
-            yarn: partial.yarn,
+            npm: locked.npm,
+            yarn: locked.yarn,
         })
     }
 }
@@ -1,16 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
-use std::fs::{read_to_string, File};
+use std::fs::read_to_string;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
-use super::PartialPlatform;
-use crate::error::{Context, ErrorKind, Fallible};
-use crate::version::parse_version;
+use super::edit::{self, set_root_key, set_volta_key};
+use super::engines::Engines;
+use super::tolerant;
+use super::{ManagerPin, PartialPlatform, Pin};
+use crate::error::{Context, ErrorKind, Fallible, VoltaError};
+use crate::version::{parse_version, VersionSpec, VersionTag};
 use dunce::canonicalize;
-use semver::Version;
+use log::debug;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
+use tempfile::NamedTempFile;
 
 pub type DependencyMapIterator = std::iter::Chain<
     std::option::IntoIter<HashMap<String, String>>,
@@ -21,6 +26,12 @@ pub(super) struct Manifest {
     pub dependency_maps: DependencyMapIterator,
     pub platform: Option<PartialPlatform>,
     pub extends: Option<PathBuf>,
+    /// The sibling `volta.json` that the pins were read from, if there is one
+    pub pin_file: Option<PathBuf>,
+    pub engines: Engines,
+    pub workspaces: Vec<String>,
+    pub package_manager: Option<ManagerPin>,
+    pub tools: BTreeMap<String, String>,
 }
 
 impl Manifest {
@@ -32,9 +43,32 @@ impl Manifest {
             .into_iter()
             .chain(raw.dev_dependencies.into_iter());
 
-        let (platform, extends) = match raw.volta {
+        // Pins in a sibling `volta.json` take precedence over the `volta` key in the manifest
+        let pin_file = pin_file_path(file);
+        let pins = if pin_file.is_file() {
+            RawPinFile::from_file(&pin_file)?.into_pins()
+        } else {
+            None
+        };
+        let has_pin_file = pins.is_some();
+        let toolchain = match pins {
+            Some(pins) => {
+                if raw.volta.is_some() {
+                    debug!(
+                        "Ignoring the 'volta' key in {} in favor of {}",
+                        file.display(),
+                        pin_file.display()
+                    );
+                }
+                Some(pins).filter(|spec| !spec.is_empty())
+            }
+            None => raw.volta,
+        };
+
+        let (platform, extends, tools) = match toolchain {
             Some(toolchain) => {
-                let (partial, extends) = toolchain.parse_split()?;
+                let toolchain_file = if has_pin_file { &pin_file } else { file };
+                let (partial, extends, tools) = toolchain.parse_split(toolchain_file)?;
 
                 let next = extends
                     .map(|path| {
@@ -47,19 +81,26 @@ impl Manifest {
                             .with_context(|| ErrorKind::ExtensionPathError { path })
                     })
                     .transpose()?;
-                (Some(partial), next)
+                (Some(partial), next, tools)
             }
-            None => (None, None),
+            None => (None, None, BTreeMap::new()),
         };
 
         Ok(Manifest {
             dependency_maps,
             platform,
             extends,
+            pin_file: Some(pin_file).filter(|_| has_pin_file),
+            engines: parse_engines(raw.engines),
+            workspaces: parse_workspaces(raw.workspaces),
+            package_manager: parse_package_manager(raw.package_manager),
+            tools,
         })
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug))]
 pub(super) enum ManifestKey {
     Node,
     Npm,
@@ -76,16 +117,138 @@ impl fmt::Display for ManifestKey {
     }
 }
 
-/// Updates the `volta` hash in the specified manifest with the given key and value
+/// Returns the path to the `volta.json` pin file that sits alongside the given project manifest
+pub(super) fn pin_file_path(manifest_file: &Path) -> PathBuf {
+    manifest_file
+        .parent()
+        .expect("File paths always have a parent")
+        .join("volta.json")
+}
+
+/// Updates the `volta` hash in the specified manifest with the given keys and values
 ///
 /// Will create the `volta` hash if it isn't already present
 ///
-/// If the value is `None`, will remove the key from the hash
-pub(super) fn update_manifest(
+/// If a value is `None`, will remove that key from the hash
+///
+/// All of the updates are applied in a single write, which replaces the manifest atomically
+pub(super) fn update_manifest<K: fmt::Display>(
     file: &Path,
-    key: ManifestKey,
-    value: Option<&Version>,
+    updates: &[(K, Option<String>)],
 ) -> Fallible<()> {
+    edit_manifest(
+        file,
+        |contents, indent| {
+            updates
+                .iter()
+                .try_fold(contents.to_string(), |text, (key, value)| {
+                    set_volta_key(&text, &key.to_string(), value.as_deref(), indent)
+                })
+        },
+        |root| {
+            for (key, value) in updates {
+                let key = key.to_string();
+
+                match (value, root.get_mut("volta").and_then(|v| v.as_object_mut())) {
+                    (Some(v), Some(hash)) => {
+                        hash.insert(key, Value::String(v.clone()));
+                    }
+                    (None, Some(hash)) => {
+                        hash.remove(&key);
+                        if hash.is_empty() {
+                            root.remove("volta");
+                        }
+                    }
+                    (Some(v), None) => {
+                        let mut map = Map::new();
+                        map.insert(key, Value::String(v.clone()));
+                        root.insert("volta".into(), Value::Object(map));
+                    }
+                    (None, None) => {}
+                }
+            }
+        },
+    )
+}
+
+/// Updates the pins in the specified `volta.json` pin file, creating it if necessary
+///
+/// If a value is `None`, will remove that key from the file
+pub(super) fn update_pin_file<K: fmt::Display>(
+    file: &Path,
+    updates: &[(K, Option<String>)],
+) -> Fallible<()> {
+    if !file.exists() {
+        let pins = updates
+            .iter()
+            .filter_map(|(key, value)| Some((key.to_string(), Value::String(value.clone()?))))
+            .collect();
+        return write_pin_file(file, &pins);
+    }
+
+    edit_manifest(
+        file,
+        |contents, indent| {
+            // A pin file that starts out empty has no indentation to match
+            let indent = if indent.is_empty() { "  " } else { indent };
+            updates
+                .iter()
+                .try_fold(contents.to_string(), |text, (key, value)| {
+                    set_root_key(&text, &key.to_string(), value.as_deref(), indent)
+                })
+        },
+        |root| {
+            for (key, value) in updates {
+                match value {
+                    Some(v) => {
+                        root.insert(key.to_string(), Value::String(v.clone()));
+                    }
+                    None => {
+                        root.remove(&key.to_string());
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Removes all of the pins (including `extends`) from the specified `volta.json` pin file
+///
+/// The file itself is kept, so that future pins are still written there
+pub(super) fn clear_pin_file(file: &Path) -> Fallible<()> {
+    write_pin_file(file, &Map::new())
+}
+
+fn write_pin_file(file: &Path, pins: &Map<String, Value>) -> Fallible<()> {
+    let mut contents =
+        serde_json::to_string_pretty(pins).with_context(|| ErrorKind::PackageWriteError {
+            file: file.to_owned(),
+        })?;
+    contents.push('\n');
+
+    write_manifest(file, contents.as_bytes())
+}
+
+/// Removes the entire `volta` hash from the specified manifest
+pub(super) fn remove_volta_hash(file: &Path) -> Fallible<()> {
+    edit_manifest(
+        file,
+        |contents, _| edit::remove_volta_hash(contents),
+        |root| {
+            root.remove("volta");
+        },
+    )
+}
+
+/// Applies an edit to the specified manifest
+///
+/// The edit is first attempted directly on the text, so that the rest of the file keeps its
+/// formatting. If that isn't possible, the parsed manifest is edited and re-serialized instead.
+fn edit_manifest<T, V>(file: &Path, edit_text: T, edit_value: V) -> Fallible<()>
+where
+    T: FnOnce(&str, &str) -> Option<String>,
+    V: FnOnce(&mut Map<String, Value>),
+{
     let contents = read_to_string(&file).with_context(|| ErrorKind::PackageReadError {
         file: file.to_owned(),
     })?;
@@ -95,35 +258,22 @@ pub(super) fn update_manifest(
             file: file.to_owned(),
         })?;
 
+    let indent = detect_indent::detect_indent(&contents);
+
+    if let Some(edited) = edit_text(&contents, indent.indent()) {
+        return write_manifest(file, edited.as_bytes());
+    }
+
     let root = manifest
         .as_object_mut()
         .ok_or_else(|| ErrorKind::PackageParseError {
             file: file.to_owned(),
         })?;
+    edit_value(root);
 
-    let key = key.to_string();
-
-    match (value, root.get_mut("volta").and_then(|v| v.as_object_mut())) {
-        (Some(v), Some(hash)) => {
-            hash.insert(key, Value::String(v.to_string()));
-        }
-        (None, Some(hash)) => {
-            hash.remove(&key);
-        }
-        (Some(v), None) => {
-            let mut map = Map::new();
-            map.insert(key, Value::String(v.to_string()));
-            root.insert("volta".into(), Value::Object(map));
-        }
-        (None, None) => {}
-    }
-
-    let indent = detect_indent::detect_indent(&contents);
-    let mut output = File::create(&file).with_context(|| ErrorKind::PackageWriteError {
-        file: file.to_owned(),
-    })?;
+    let mut output = Vec::new();
     let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.indent().as_bytes());
-    let mut ser = serde_json::Serializer::with_formatter(&output, formatter);
+    let mut ser = serde_json::Serializer::with_formatter(&mut output, formatter);
     manifest
         .serialize(&mut ser)
         .with_context(|| ErrorKind::PackageWriteError {
@@ -131,11 +281,24 @@ pub(super) fn update_manifest(
         })?;
 
     if contents.ends_with('\n') {
-        writeln!(output).with_context(|| ErrorKind::PackageWriteError {
-            file: file.to_owned(),
-        })?;
+        output.push(b'\n');
     }
 
+    write_manifest(file, &output)
+}
+
+/// Writes the manifest contents to a temporary file and then moves it into place, so that a
+/// failure part-way through never leaves behind a truncated manifest
+fn write_manifest(file: &Path, contents: &[u8]) -> Fallible<()> {
+    let write_error = || ErrorKind::PackageWriteError {
+        file: file.to_owned(),
+    };
+    let dir = file.parent().expect("File paths always have a parent");
+
+    let mut staging = NamedTempFile::new_in(dir).with_context(write_error)?;
+    staging.write_all(contents).with_context(write_error)?;
+    staging.persist(file).with_context(write_error)?;
+
     Ok(())
 }
 
@@ -147,20 +310,122 @@ struct RawManifest {
     dev_dependencies: Option<HashMap<String, String>>,
 
     volta: Option<ToolchainSpec>,
+
+    // Note: `engines` is only advisory, so it is parsed leniently rather than failing on
+    // unexpected values (e.g. the legacy array format)
+    engines: Option<Value>,
+
+    workspaces: Option<Value>,
+
+    #[serde(rename = "packageManager")]
+    package_manager: Option<Value>,
 }
 
 impl RawManifest {
     fn from_file(package: &Path) -> Fallible<Self> {
-        let file = File::open(package).with_context(|| ErrorKind::PackageReadError {
+        let contents = read_to_string(package).with_context(|| ErrorKind::PackageReadError {
             file: package.to_owned(),
         })?;
 
-        serde_json::de::from_reader(file).with_context(|| ErrorKind::PackageParseError {
-            file: package.to_owned(),
-        })
+        parse_json(package, &contents)
     }
 }
 
+/// Parses the contents of a manifest or pin file, falling back to the tolerant parser for files
+/// with cosmetic quirks if it has been enabled
+fn parse_json<T: DeserializeOwned>(file: &Path, contents: &str) -> Fallible<T> {
+    match serde_json::from_str(contents) {
+        Ok(parsed) => Ok(parsed),
+        Err(error) if tolerant::enabled() => {
+            debug!(
+                "Reading {} with the tolerant parser: {}",
+                file.display(),
+                error
+            );
+            serde_json::from_str(&tolerant::sanitize(contents)).with_context(|| {
+                ErrorKind::PackageParseError {
+                    file: file.to_owned(),
+                }
+            })
+        }
+        Err(error) => Err(error).with_context(|| ErrorKind::PackageParseError {
+            file: file.to_owned(),
+        }),
+    }
+}
+
+/// Extracts the `engines` ranges for the tools that Volta manages, ignoring anything else
+fn parse_engines(engines: Option<Value>) -> Engines {
+    let range = |tool: &str| {
+        engines
+            .as_ref()
+            .and_then(|engines| engines.get(tool))
+            .and_then(Value::as_str)
+            .map(String::from)
+    };
+
+    Engines {
+        node: range("node"),
+        npm: range("npm"),
+        yarn: range("yarn"),
+    }
+}
+
+/// Parses the Corepack `packageManager` field (e.g. `yarn@3.6.4+sha224.abc`) as a pin
+///
+/// Corepack requires an exact version, so anything else is ignored, as are package managers that
+/// Volta doesn't manage
+fn parse_package_manager(value: Option<Value>) -> Option<ManagerPin> {
+    let value = match value? {
+        Value::String(value) => value,
+        _ => return None,
+    };
+    let (name, version) = value.split_once('@')?;
+    // Strip the optional hash of the package manager archive
+    let version = version.split('+').next()?;
+
+    let key = match name {
+        "npm" => ManifestKey::Npm,
+        "yarn" => ManifestKey::Yarn,
+        other => {
+            debug!("Ignoring unsupported packageManager '{}'", other);
+            return None;
+        }
+    };
+
+    match parse_version(version) {
+        Ok(version) => Some(ManagerPin { key, version }),
+        Err(_) => {
+            debug!(
+                "Ignoring packageManager without an exact version: '{}'",
+                value
+            );
+            None
+        }
+    }
+}
+
+/// Extracts the member globs from `workspaces`, which is either an array of globs or an object
+/// with the globs in `packages` (the format used by Yarn's `nohoist`)
+fn parse_workspaces(workspaces: Option<Value>) -> Vec<String> {
+    let globs = match workspaces {
+        Some(Value::Array(globs)) => globs,
+        Some(Value::Object(mut map)) => match map.remove("packages") {
+            Some(Value::Array(globs)) => globs,
+            _ => Vec::new(),
+        },
+        _ => Vec::new(),
+    };
+
+    globs
+        .into_iter()
+        .filter_map(|glob| match glob {
+            Value::String(glob) => Some(glob),
+            _ => None,
+        })
+        .collect()
+}
+
 #[derive(Default, Deserialize, Serialize)]
 struct ToolchainSpec {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -171,17 +436,93 @@ struct ToolchainSpec {
     yarn: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     extends: Option<PathBuf>,
+    /// Package tools pinned by the project, mapping the package name to a version
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    tools: BTreeMap<String, String>,
+}
+
+/// The contents of a `volta.json` next to a project manifest
+///
+/// A `volta.json` with a `volta` key of its own is in the manifest format (e.g. the target of an
+/// `extends`), rather than a pin file.
+#[derive(Deserialize)]
+struct RawPinFile {
+    volta: Option<serde::de::IgnoredAny>,
+    #[serde(flatten)]
+    pins: ToolchainSpec,
+}
+
+impl RawPinFile {
+    fn from_file(file: &Path) -> Fallible<Self> {
+        let contents = read_to_string(file).with_context(|| ErrorKind::PackageReadError {
+            file: file.to_owned(),
+        })?;
+
+        parse_json(file, &contents)
+    }
+
+    /// Returns the pins in the file, or `None` if it isn't a pin file
+    fn into_pins(self) -> Option<ToolchainSpec> {
+        match self.volta {
+            Some(_) => None,
+            None => Some(self.pins),
+        }
+    }
 }
 
 impl ToolchainSpec {
-    /// Moves the tool versions into a `PartialPlatform` and returns that along with the `extends` value
-    fn parse_split(self) -> Fallible<(PartialPlatform, Option<PathBuf>)> {
-        let node = self.node.map(parse_version).transpose()?;
-        let npm = self.npm.map(parse_version).transpose()?;
-        let yarn = self.yarn.map(parse_version).transpose()?;
+    fn is_empty(&self) -> bool {
+        self.node.is_none()
+            && self.npm.is_none()
+            && self.yarn.is_none()
+            && self.extends.is_none()
+            && self.tools.is_empty()
+    }
+
+    /// Moves the tool versions into a `PartialPlatform` and returns that along with the `extends`
+    /// value and the pinned package tools
+    ///
+    /// `file` is the file that the pins were read from, for reporting an invalid pin.
+    fn parse_split(
+        self,
+        file: &Path,
+    ) -> Fallible<(PartialPlatform, Option<PathBuf>, BTreeMap<String, String>)> {
+        let parse =
+            |key, value: Option<String>| value.map(|value| parse_pin(key, value, file)).transpose();
+        let node = parse(ManifestKey::Node, self.node)?;
+        let npm = parse(ManifestKey::Npm, self.npm)?;
+        let yarn = parse(ManifestKey::Yarn, self.yarn)?;
 
         let platform = PartialPlatform { node, npm, yarn };
 
-        Ok((platform, self.extends))
+        Ok((platform, self.extends, self.tools))
+    }
+}
+
+/// Parses a pinned tool version
+///
+/// A valid semver range, or the `latest` tag (and `lts` for Node), is a floating pin that is
+/// locked to a version in `volta.lock`. Anything else is an error, rather than a pin that could
+/// never be resolved.
+fn parse_pin(key: ManifestKey, value: String, file: &Path) -> Fallible<Pin> {
+    if let Ok(version) = parse_version(&value) {
+        return Ok(Pin::Exact(version));
+    }
+
+    let floating = match value.parse::<VersionSpec>() {
+        Ok(VersionSpec::Semver(_)) | Ok(VersionSpec::Tag(VersionTag::Latest)) => true,
+        Ok(VersionSpec::Tag(VersionTag::Lts)) => key == ManifestKey::Node,
+        _ => false,
+    };
+
+    if floating {
+        Ok(Pin::Floating(value))
+    } else {
+        Err(VoltaError::from_source(
+            format!("Invalid {} version: '{}'", key, value),
+            ErrorKind::PackageParseError {
+                file: file.to_owned(),
+            },
+        ))
     }
 }
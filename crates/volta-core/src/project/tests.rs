@@ -94,19 +94,171 @@ mod project {
         assert_eq!(platform.yarn, Some("1.22.4".parse().unwrap()));
     }
 
+    #[test]
+    fn platform_locked() {
+        let project_path = fixture_path(&["locked"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = test_project.platform().unwrap();
+
+        // The range `^12.14` is locked to this version in `locked/volta.lock`
+        assert_eq!(platform.node, "12.16.1".parse().unwrap());
+        assert_eq!(platform.npm, None);
+        assert_eq!(platform.yarn, Some("1.22.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn platform_pin_file() {
+        let project_path = fixture_path(&["pin-file"]);
+        let test_project = Project::for_dir(project_path.clone()).unwrap().unwrap();
+        let platform = test_project.platform().unwrap();
+
+        // The pins in `volta.json` replace the `volta` hash in `package.json` entirely
+        assert_eq!(platform.node, "12.16.1".parse().unwrap());
+        assert_eq!(platform.npm, Some("6.13.4".parse().unwrap()));
+        assert_eq!(platform.yarn, None);
+        assert_eq!(test_project.pin_file(), project_path.join("volta.json"));
+    }
+
+    #[test]
+    fn platform_package_manager() {
+        let project_path = fixture_path(&["package-manager"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = test_project.platform().unwrap();
+
+        // Yarn isn't pinned in the `volta` hash, so it comes from `packageManager`
+        assert_eq!(platform.node, "12.16.1".parse().unwrap());
+        assert_eq!(platform.yarn, Some("1.22.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn pinned_tools() {
+        let project_path = fixture_path(&["pinned-tools"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let tools = test_project.pinned_tools();
+
+        assert_eq!(tools.len(), 2);
+        assert_eq!(tools["typescript"], "5.4.x");
+        assert_eq!(tools["@vue/cli"], "5.0.8");
+        // The tools don't affect the platform
+        assert_eq!(
+            test_project.platform().unwrap().node,
+            "12.16.1".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn platform_unlocked() {
+        let project_path = fixture_path(&["unlocked"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        // The lock entry is for a different range, so the platform is pending resolution
+        assert!(test_project.platform().is_none());
+        let pending = test_project.pending.as_ref().unwrap();
+        assert_eq!(
+            pending.unlocked,
+            vec![(ManifestKey::Node, "^14".to_string())]
+        );
+        assert_eq!(pending.yarn, Some("1.22.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn platform_invalid_pin() {
+        let project_path = fixture_path(&["invalid-pin"]);
+        let error = Project::for_dir(project_path.clone()).unwrap_err();
+
+        match error.kind() {
+            ErrorKind::PackageParseError { file } => {
+                assert_eq!(file, &project_path.join("package.json"))
+            }
+            kind => panic!("Unexpected error: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn engine_conflicts() {
+        let project_path = fixture_path(&["engines"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let conflicts = test_project.engine_conflicts();
+
+        // Yarn satisfies its range, and engines for other programs are ignored
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].tool, "node");
+        assert_eq!(conflicts[0].version, "12.16.1".parse().unwrap());
+        assert_eq!(conflicts[0].range, ">=14");
+    }
+
+    #[test]
+    fn engine_conflicts_none() {
+        let project_path = fixture_path(&["basic"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        assert!(test_project.engine_conflicts().is_empty());
+    }
+
+    #[test]
+    fn platform_engine_conflicts() {
+        let project_path = fixture_path(&["engines"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = Platform {
+            node: Sourced::with_default("14.17.0".parse().unwrap()),
+            npm: None,
+            yarn: Some(Sourced::with_default("2.4.0".parse().unwrap())),
+        };
+        let conflicts = test_project.platform_engine_conflicts(&platform);
+
+        // The versions from the default platform are checked, not the pins
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].tool, "yarn");
+        assert_eq!(conflicts[0].range, "^1.22.0");
+    }
+
+    #[test]
+    fn yarn_path() {
+        let project_path = fixture_path(&["yarn-path"]);
+        let test_project = Project::for_dir(project_path.clone()).unwrap().unwrap();
+
+        assert_eq!(
+            test_project.yarn_path(),
+            Some(project_path.join(".yarn/releases/yarn-3.2.0.cjs"))
+        );
+    }
+
+    #[test]
+    fn yarn_path_none() {
+        let project_path = fixture_path(&["basic"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+
+        assert_eq!(test_project.yarn_path(), None);
+    }
+
+    #[test]
+    fn engine_strict() {
+        let strict = Project::for_dir(fixture_path(&["engines"]))
+            .unwrap()
+            .unwrap();
+        let basic = Project::for_dir(fixture_path(&["basic"])).unwrap().unwrap();
+
+        assert!(strict.engine_strict());
+        assert!(!basic.engine_strict());
+    }
+
     #[test]
     fn direct_dependencies_single() {
         let project_path = fixture_path(&["basic"]);
         let test_project = Project::for_dir(project_path).unwrap().unwrap();
 
         // eslint, rsvp, bin-1, and bin-2 are direct dependencies
-        assert!(test_project.has_direct_dependency("eslint"));
-        assert!(test_project.has_direct_dependency("rsvp"));
-        assert!(test_project.has_direct_dependency("@namespace/some-dep"));
-        assert!(test_project.has_direct_dependency("@namespaced/something-else"));
+        assert!(test_project.has_direct_dependency("eslint").unwrap());
+        assert!(test_project.has_direct_dependency("rsvp").unwrap());
+        assert!(test_project
+            .has_direct_dependency("@namespace/some-dep")
+            .unwrap());
+        assert!(test_project
+            .has_direct_dependency("@namespaced/something-else")
+            .unwrap());
 
         // typescript is not a direct dependency
-        assert!(!test_project.has_direct_dependency("typescript"));
+        assert!(!test_project.has_direct_dependency("typescript").unwrap());
     }
 
     #[test]
@@ -115,17 +267,17 @@ mod project {
         let test_project = Project::for_dir(project_path).unwrap().unwrap();
 
         // express and typescript are direct dependencies of the innermost project
-        assert!(test_project.has_direct_dependency("express"));
-        assert!(test_project.has_direct_dependency("typescript"));
+        assert!(test_project.has_direct_dependency("express").unwrap());
+        assert!(test_project.has_direct_dependency("typescript").unwrap());
         // rsvp and glob are direct dependencies of the middle project
-        assert!(test_project.has_direct_dependency("rsvp"));
-        assert!(test_project.has_direct_dependency("glob"));
+        assert!(test_project.has_direct_dependency("rsvp").unwrap());
+        assert!(test_project.has_direct_dependency("glob").unwrap());
         // lodash and eslint are direct dependencies of the top-level workspace
-        assert!(test_project.has_direct_dependency("lodash"));
-        assert!(test_project.has_direct_dependency("eslint"));
+        assert!(test_project.has_direct_dependency("lodash").unwrap());
+        assert!(test_project.has_direct_dependency("eslint").unwrap());
 
         // react is not a direct dependency of any project
-        assert!(!test_project.has_direct_dependency("react"));
+        assert!(!test_project.has_direct_dependency("react").unwrap());
     }
 
     #[test]
@@ -242,3 +394,63 @@ mod plug_n_play {
         assert!(test_project.is_yarn_pnp());
     }
 }
+
+mod workspaces {
+    use super::*;
+
+    #[test]
+    fn finds_workspace_root() {
+        let member_dir = fixture_path(&["workspace", "packages", "a"]);
+        assert_eq!(
+            workspace::find_workspace_root(&member_dir),
+            Some(fixture_path(&["workspace", "package.json"]))
+        );
+
+        let project_dir = fixture_path(&["basic"]);
+        assert_eq!(workspace::find_workspace_root(&project_dir), None);
+    }
+
+    #[test]
+    fn inherits_pnpm_workspace_pins() {
+        let project_path = fixture_path(&["pnpm", "packages", "app"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = test_project.platform().unwrap();
+
+        // `packages/app` has no pins, so it uses those of the root with `pnpm-workspace.yaml`
+        assert_eq!(platform.node, "12.16.1".parse().unwrap());
+        assert_eq!(platform.yarn, Some("1.22.4".parse().unwrap()));
+    }
+
+    #[test]
+    fn inherits_lerna_pins() {
+        let project_path = fixture_path(&["lerna", "modules", "lib"]);
+        let test_project = Project::for_dir(project_path).unwrap().unwrap();
+        let platform = test_project.platform().unwrap();
+
+        assert_eq!(platform.node, "10.0.0".parse().unwrap());
+    }
+
+    #[test]
+    fn reports_conflicting_members() {
+        let root = Project::for_dir(fixture_path(&["workspace"]))
+            .unwrap()
+            .unwrap();
+        let conflicts = root.workspace_conflicts().unwrap();
+
+        // `packages/a` extends the root, so only `packages/b` conflicts
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(
+            conflicts[0].manifest,
+            fixture_path(&["workspace", "packages", "b", "package.json"])
+        );
+        assert_eq!(
+            conflicts[0].mismatched,
+            vec![(
+                "node".to_string(),
+                "10.0.0".to_string(),
+                "12.16.1".to_string()
+            )]
+        );
+        assert!(!conflicts[0].extends_root);
+    }
+}
@@ -0,0 +1,116 @@
+//! Provides an opt-in tolerant reading mode for project manifests, which accepts the quirks that
+//! some tools leave behind: a byte order mark, comments, and trailing commas.
+//!
+//! This is only used when reading pins. Manifests are still parsed strictly when Volta writes to
+//! them, so that it never rewrites a file it doesn't fully understand.
+
+use crate::environment;
+
+/// Whether tolerant parsing has been enabled with `VOLTA_TOLERANT_MANIFESTS`
+pub(super) fn enabled() -> bool {
+    environment::var_os("VOLTA_TOLERANT_MANIFESTS").is_some()
+}
+
+/// Converts manifest text with quirks into strict JSON
+pub(super) fn sanitize(contents: &str) -> String {
+    let contents = contents.trim_start_matches('\u{feff}');
+    remove_trailing_commas(&remove_comments(contents))
+}
+
+/// Removes `//` and `/* */` comments that appear outside of strings
+fn remove_comments(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut chars = contents.chars().peekable();
+    let mut in_string = false;
+
+    while let Some(c) = chars.next() {
+        if in_string {
+            output.push(c);
+            match c {
+                '\\' => output.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                output.push(c);
+            }
+            ('/', Some('/')) => {
+                // Keep the newline, so that the line structure is unchanged
+                while let Some(&next) = chars.peek() {
+                    if next == '\n' {
+                        break;
+                    }
+                    chars.next();
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut previous = None;
+                for next in chars.by_ref() {
+                    if previous == Some('*') && next == '/' {
+                        break;
+                    }
+                    previous = Some(next);
+                }
+            }
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// Removes commas that are followed only by whitespace before a closing brace or bracket
+fn remove_trailing_commas(contents: &str) -> String {
+    let mut output = String::with_capacity(contents.len());
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, c) in contents.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if c == '"' {
+            in_string = true;
+        } else if c == ',' {
+            let next = contents[index + 1..].trim_start().chars().next();
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+
+        output.push(c);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize;
+    use serde_json::{json, Value};
+
+    #[test]
+    fn accepts_quirks() {
+        let contents = "\u{feff}{\n  // the project name\n  \"name\": \"a // b\",\n  /* pins */\n  \"volta\": {\n    \"node\": \"12.16.1\",\n  },\n  \"files\": [\"x,\", \"y\",],\n}\n";
+        let parsed: Value = serde_json::from_str(&sanitize(contents)).unwrap();
+
+        assert_eq!(
+            parsed,
+            json!({
+                "name": "a // b",
+                "volta": { "node": "12.16.1" },
+                "files": ["x,", "y"]
+            })
+        );
+    }
+}
@@ -0,0 +1,297 @@
+//! Provides support for monorepos that list their member packages with globs at the root, either
+//! in the `workspaces` of `package.json` (npm and Yarn), `pnpm-workspace.yaml`, or `lerna.json`.
+
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+
+use super::serial::Manifest;
+use log::debug;
+use regex::Regex;
+use serde_json::Value;
+use walkdir::WalkDir;
+
+/// A workspace member whose pins don't agree with the workspace root
+pub struct MemberConflict {
+    /// The member's manifest file
+    pub manifest: PathBuf,
+    /// The tools that the member pins differently, as `(tool, member pin, root pin)`
+    pub mismatched: Vec<(String, String, String)>,
+    /// Whether the member inherits the root's pins through `extends`
+    pub extends_root: bool,
+}
+
+/// Finds the manifest of the workspace root that contains the project in `project_dir`
+///
+/// A project that defines a workspace itself is its own root. Otherwise, the closest ancestor
+/// that defines a workspace is the root, as long as the project is one of its members.
+pub(super) fn find_workspace_root(project_dir: &Path) -> Option<PathBuf> {
+    for dir in project_dir.ancestors() {
+        let manifest_file = dir.join("package.json");
+        if !manifest_file.is_file() {
+            continue;
+        }
+
+        let patterns = workspace_patterns(dir);
+        if patterns.is_empty() {
+            continue;
+        }
+
+        let is_member = project_dir
+            .strip_prefix(dir)
+            .map(|relative| Patterns::new(&patterns).matches(relative))
+            .unwrap_or(false);
+
+        if dir == project_dir || is_member {
+            return Some(manifest_file);
+        }
+    }
+
+    None
+}
+
+/// Returns every file that `find_workspace_root` reads or checks for, so that a result that was
+/// cached can be thrown out when any of them change
+pub(super) fn workspace_root_sources(project_dir: &Path) -> Vec<PathBuf> {
+    let root = find_workspace_root(project_dir);
+    let mut sources = Vec::new();
+
+    for dir in project_dir.ancestors() {
+        let manifest_file = dir.join("package.json");
+        let is_root = root.as_ref() == Some(&manifest_file);
+        sources.push(manifest_file);
+        sources.push(dir.join("pnpm-workspace.yaml"));
+        sources.push(dir.join("lerna.json"));
+
+        if is_root {
+            break;
+        }
+    }
+
+    sources
+}
+
+/// Returns the member globs from all of the workspace definitions in a directory
+pub(super) fn workspace_patterns(dir: &Path) -> Vec<String> {
+    let mut patterns = Vec::new();
+
+    let manifest_file = dir.join("package.json");
+    if manifest_file.is_file() {
+        match Manifest::from_file(&manifest_file) {
+            Ok(manifest) => patterns.extend(manifest.workspaces),
+            Err(_) => debug!(
+                "Skipping unreadable manifest while looking for the workspace root: {}",
+                manifest_file.display()
+            ),
+        }
+    }
+
+    if let Ok(contents) = read_to_string(dir.join("pnpm-workspace.yaml")) {
+        patterns.extend(parse_pnpm_packages(&contents));
+    }
+
+    if let Ok(contents) = read_to_string(dir.join("lerna.json")) {
+        patterns.extend(parse_lerna_packages(&contents));
+    }
+
+    patterns
+}
+
+/// Reads the `packages` list from `pnpm-workspace.yaml`
+///
+/// Only the simple list forms that pnpm documents are supported, rather than all of YAML
+fn parse_pnpm_packages(contents: &str) -> Vec<String> {
+    let unquote = |item: &str| {
+        item.trim()
+            .trim_matches(|c| c == '\'' || c == '"')
+            .to_string()
+    };
+
+    let mut lines = contents.lines();
+    while let Some(line) = lines.next() {
+        let rest = match line.strip_prefix("packages:") {
+            Some(rest) => rest.trim(),
+            None => continue,
+        };
+
+        // Flow style, e.g. `packages: ['packages/*', 'apps/*']`
+        if let Some(flow) = rest.strip_prefix('[') {
+            return flow
+                .trim_end_matches(']')
+                .split(',')
+                .map(unquote)
+                .filter(|item| !item.is_empty())
+                .collect();
+        }
+
+        // Block style, with one `- item` per line
+        return lines
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map_while(|line| line.strip_prefix('-'))
+            .map(unquote)
+            .collect();
+    }
+
+    Vec::new()
+}
+
+/// Reads the `packages` list from `lerna.json`, which defaults to `packages/*`
+fn parse_lerna_packages(contents: &str) -> Vec<String> {
+    let config: Value = match serde_json::from_str(contents) {
+        Ok(config) => config,
+        Err(_) => return Vec::new(),
+    };
+
+    match config.get("packages").and_then(Value::as_array) {
+        Some(globs) => globs
+            .iter()
+            .filter_map(Value::as_str)
+            .map(String::from)
+            .collect(),
+        // With `useWorkspaces`, the members come from the `workspaces` in `package.json`
+        None if config.get("useWorkspaces").and_then(Value::as_bool) == Some(true) => Vec::new(),
+        None => vec!["packages/*".into()],
+    }
+}
+
+/// A compiled set of workspace globs
+///
+/// Globs starting with `!` exclude any directories that they match
+struct Patterns {
+    includes: Vec<Regex>,
+    excludes: Vec<Regex>,
+}
+
+impl Patterns {
+    fn new(patterns: &[String]) -> Self {
+        let mut includes = Vec::new();
+        let mut excludes = Vec::new();
+
+        for pattern in patterns {
+            match pattern.strip_prefix('!') {
+                Some(excluded) => excludes.extend(glob_to_regex(excluded)),
+                None => includes.extend(glob_to_regex(pattern)),
+            }
+        }
+
+        Patterns { includes, excludes }
+    }
+
+    /// Determines whether a directory, relative to the workspace root, is a member
+    fn matches(&self, relative: &Path) -> bool {
+        let relative = relative
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        self.includes.iter().any(|regex| regex.is_match(&relative))
+            && !self.excludes.iter().any(|regex| regex.is_match(&relative))
+    }
+}
+
+/// Returns the manifests of all of the member packages matched by a root's workspace globs
+pub(super) fn member_manifests(root_dir: &Path, patterns: &[String]) -> Vec<PathBuf> {
+    let patterns = Patterns::new(patterns);
+
+    WalkDir::new(root_dir)
+        .min_depth(1)
+        .sort_by(|a, b| a.file_name().cmp(b.file_name()))
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || (entry.file_type().is_dir()
+                    && entry.file_name() != "node_modules"
+                    && !entry.file_name().to_string_lossy().starts_with('.'))
+        })
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(root_dir).ok()?;
+            if patterns.matches(relative) {
+                Some(entry.path().join("package.json")).filter(|file| file.is_file())
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Converts a workspace glob (like `packages/*` or `apps/**`) into an anchored regex
+fn glob_to_regex(pattern: &str) -> Option<Regex> {
+    let pattern = pattern.trim_start_matches("./").trim_end_matches('/');
+    let mut regex = String::from("^");
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            other => regex.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    regex.push('$');
+
+    Regex::new(&regex).ok()
+}
+
+/// Returns the path to the root manifest, relative to the directory of a member manifest
+///
+/// Members are always found below the root, so this only needs to walk back up the tree
+pub(super) fn relative_root(root_file: &Path, member_file: &Path) -> Option<String> {
+    let root_dir = root_file.parent()?;
+    let member_dir = member_file.parent()?;
+    let depth = member_dir.strip_prefix(root_dir).ok()?.components().count();
+
+    Some(format!("{}package.json", "../".repeat(depth)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glob_to_regex, parse_lerna_packages, parse_pnpm_packages};
+
+    #[test]
+    fn matches_globs() {
+        let single = glob_to_regex("packages/*").unwrap();
+        assert!(single.is_match("packages/a"));
+        assert!(!single.is_match("packages/a/b"));
+        assert!(!single.is_match("other/a"));
+
+        let recursive = glob_to_regex("./apps/**").unwrap();
+        assert!(recursive.is_match("apps/a/b"));
+
+        let exact = glob_to_regex("tools/cli/").unwrap();
+        assert!(exact.is_match("tools/cli"));
+        assert!(!exact.is_match("tools/clix"));
+    }
+
+    #[test]
+    fn parses_pnpm_packages() {
+        let block = "packages:\n  # all packages\n  - 'packages/*'\n  - \"!**/test/**\"\ncatalog:\n  react: ^18\n";
+        assert_eq!(
+            parse_pnpm_packages(block),
+            vec!["packages/*", "!**/test/**"]
+        );
+
+        let flow = "packages: ['apps/*', \"libs/*\"]\n";
+        assert_eq!(parse_pnpm_packages(flow), vec!["apps/*", "libs/*"]);
+
+        assert!(parse_pnpm_packages("catalog:\n  react: ^18\n").is_empty());
+    }
+
+    #[test]
+    fn parses_lerna_packages() {
+        assert_eq!(
+            parse_lerna_packages(r#"{ "packages": ["modules/*"] }"#),
+            vec!["modules/*"]
+        );
+        assert_eq!(
+            parse_lerna_packages(r#"{ "version": "1.0.0" }"#),
+            vec!["packages/*"]
+        );
+        assert!(parse_lerna_packages(r#"{ "useWorkspaces": true }"#).is_empty());
+    }
+}
@@ -8,12 +8,13 @@ use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
 use crate::platform::{Platform, Sourced, System};
 use crate::session::Session;
-use crate::tool::package::BinConfig;
+use crate::tool::package::{find_pinned_bin, BinConfig};
 use log::debug;
 
 /// Determine the correct command to run for a 3rd-party binary
 ///
-/// Will detect if we should delegate to the project-local version or use the default version
+/// Will detect if we should delegate to the project-local version, a package tool pinned by the
+/// project, or the default version
 pub(super) fn command(exe: &OsStr, args: &[OsString], session: &mut Session) -> Fallible<Executor> {
     let bin = exe.to_string_lossy().to_string();
     // First try to use the project toolchain
@@ -57,6 +58,24 @@ pub(super) fn command(exe: &OsStr, args: &[OsString], session: &mut Session) ->
         }
     }
 
+    // Next, try the package tools pinned by the project
+    if let Some(path_to_bin) = find_pinned_bin(&bin, session)? {
+        debug!(
+            "Found {} in project tools at '{}'",
+            bin,
+            path_to_bin.display()
+        );
+
+        let platform = Platform::current(session)?;
+        return Ok(ToolCommand::new(
+            path_to_bin,
+            args,
+            platform,
+            ToolKind::ProjectLocalBinary(bin),
+        )
+        .into());
+    }
+
     // Try to use the default toolchain
     if let Some(default_tool) = DefaultBinary::from_name(exe, session)? {
         debug!(
@@ -6,6 +6,7 @@ use std::os::unix::process::ExitStatusExt;
 use std::os::windows::process::ExitStatusExt;
 use std::process::{Command, ExitStatus};
 
+use super::nvm::NvmCommand;
 use super::RECURSION_ENV_VAR;
 use crate::command::create_command;
 use crate::error::{Context, ErrorKind, Fallible};
@@ -26,6 +27,7 @@ pub enum Executor {
     PackageUpgrade(Box<PackageUpgradeCommand>),
     InternalInstall(Box<InternalInstallCommand>),
     Uninstall(Box<UninstallCommand>),
+    Nvm(Box<NvmCommand>),
     Multiple(Vec<Executor>),
 }
 
@@ -44,6 +46,8 @@ impl Executor {
             Executor::InternalInstall(_) => {}
             // Uninstalls use Volta's logic and don't rely on environment variables
             Executor::Uninstall(_) => {}
+            // nvm commands only change Volta's state and don't rely on environment variables
+            Executor::Nvm(_) => {}
             Executor::Multiple(executors) => {
                 for exe in executors {
                     exe.envs(envs);
@@ -62,6 +66,8 @@ impl Executor {
             Executor::InternalInstall(_) => {}
             // Uninstall use Volta's logic and don't rely on the Node platform
             Executor::Uninstall(_) => {}
+            // nvm commands choose their own Node version
+            Executor::Nvm(_) => {}
             Executor::Multiple(executors) => {
                 for exe in executors {
                     exe.cli_platform(cli.clone());
@@ -78,6 +84,7 @@ impl Executor {
             Executor::PackageUpgrade(cmd) => cmd.execute(session),
             Executor::InternalInstall(cmd) => cmd.execute(session),
             Executor::Uninstall(cmd) => cmd.execute(),
+            Executor::Nvm(cmd) => cmd.execute(session),
             Executor::Multiple(executors) => {
                 info!(
                     "{} Volta is processing each package separately",
@@ -126,6 +133,7 @@ pub enum ToolKind {
     Yarn,
     ProjectLocalBinary(String),
     DefaultBinary(String),
+    Command(String),
     Bypass(String),
 }
 
@@ -186,6 +194,9 @@ impl ToolCommand {
             ToolKind::ProjectLocalBinary(bin) => {
                 super::binary::local_execution_context(bin, self.platform, session)?
             }
+            ToolKind::Command(command) => {
+                super::command_execution_context(command, self.platform, session)?
+            }
             ToolKind::Bypass(command) => (System::path()?, ErrorKind::BypassError { command }),
         };
 
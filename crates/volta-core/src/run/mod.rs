@@ -5,8 +5,9 @@ use std::path::Path;
 use std::process::ExitStatus;
 
 use crate::error::{ErrorKind, Fallible};
-use crate::platform::{CliPlatform, Image, Sourced};
+use crate::platform::{CliPlatform, Image, Platform, Sourced};
 use crate::session::Session;
+use crate::tool::package::EphemeralInstall;
 use log::debug;
 use semver::Version;
 
@@ -15,6 +16,7 @@ mod executor;
 mod node;
 mod npm;
 mod npx;
+mod nvm;
 mod parser;
 mod yarn;
 
@@ -38,6 +40,11 @@ pub fn execute_shim(session: &mut Session) -> Fallible<ExitStatus> {
     let exe = get_tool_name(&mut native_args)?;
     let args: Vec<_> = native_args.collect();
 
+    session.set_no_prompt();
+    if !session.settings()?.auto_download() {
+        session.set_no_fetch_missing();
+    }
+
     get_executor(&exe, &args, session)?.execute(session)
 }
 
@@ -64,6 +71,54 @@ where
     runner.execute(session)
 }
 
+/// Execute any command with the tools of the current platform on the `PATH`
+///
+/// Unlike the shims, the command doesn't need to be a Volta-managed tool, so that build scripts
+/// which run Node indirectly (e.g. through `make`) get the same toolchain as the project.
+pub fn execute_command(
+    exe: &OsStr,
+    args: &[OsString],
+    session: &mut Session,
+) -> Fallible<ExitStatus> {
+    env::remove_var(RECURSION_ENV_VAR);
+
+    let platform = Platform::current(session)?.ok_or(ErrorKind::NoPlatform)?;
+    executor::ToolCommand::new(
+        exe,
+        args,
+        Some(platform),
+        executor::ToolKind::Command(exe.to_string_lossy().to_string()),
+    )
+    .execute(session)
+}
+
+/// Execute an executable from a package that is installed only for the duration of the command
+///
+/// The package is installed with the current platform (the project platform if there is one,
+/// otherwise the default) into a temporary directory, which is removed after the command exits.
+pub fn execute_ephemeral(
+    package: &str,
+    bin: Option<&str>,
+    args: &[OsString],
+    session: &mut Session,
+) -> Fallible<ExitStatus> {
+    env::remove_var(RECURSION_ENV_VAR);
+
+    let platform = Platform::current(session)?.ok_or(ErrorKind::NoPlatform)?;
+    let image = platform.clone().checkout(session)?;
+    let install = EphemeralInstall::new(package.into(), &image)?;
+    let bin_path = install.bin_path(bin)?;
+    debug!("Running ephemeral executable '{}'", bin_path.display());
+
+    executor::ToolCommand::new(
+        bin_path,
+        args,
+        Some(platform),
+        executor::ToolKind::DefaultBinary(package.into()),
+    )
+    .execute(session)
+}
+
 /// Get the appropriate Tool command, based on the requested executable and arguments
 fn get_executor(
     exe: &OsStr,
@@ -85,6 +140,7 @@ fn get_executor(
             Some("npm") => npm::command(args, session),
             Some("npx") => npx::command(args, session),
             Some("yarn") => yarn::command(args, session),
+            Some("nvm") => nvm::command(args, session),
             _ => binary::command(exe, args, session),
         }
     }
@@ -113,6 +169,19 @@ fn tool_name_from_file_name(file_name: &OsStr) -> OsString {
     }
 }
 
+/// Determine the execution context (PATH and failure error message) for an arbitrary command
+fn command_execution_context(
+    command: String,
+    platform: Option<Platform>,
+    session: &mut Session,
+) -> Fallible<(OsString, ErrorKind)> {
+    let image = platform.ok_or(ErrorKind::NoPlatform)?.checkout(session)?;
+    let path = image.path()?;
+    debug_active_image(&image);
+
+    Ok((path, ErrorKind::ExecCommandError { command }))
+}
+
 /// Write a debug message that there is no platform available
 #[inline]
 fn debug_no_platform() {
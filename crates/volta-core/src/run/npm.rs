@@ -55,7 +55,11 @@ pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Exec
                 _ => {}
             }
 
-            Platform::current(session)?
+            let platform = Platform::current(session)?;
+            if let Some(platform) = &platform {
+                check_engine_strict(platform, session)?;
+            }
+            platform
         }
     };
 
@@ -83,6 +87,33 @@ pub(super) fn execution_context(
     }
 }
 
+/// Refuses to run npm when the project enables `engine-strict` and the platform doesn't satisfy its
+/// `engines` ranges
+///
+/// npm would reject the install itself, but only after starting, and without saying that the
+/// version it objects to was chosen by Volta.
+fn check_engine_strict(platform: &Platform, session: &mut Session) -> Fallible<()> {
+    let project = match session.project()? {
+        Some(project) if project.engine_strict() => project,
+        _ => return Ok(()),
+    };
+
+    match project
+        .platform_engine_conflicts(platform)
+        .into_iter()
+        .next()
+    {
+        Some(conflict) => Err(ErrorKind::EngineStrictConflict {
+            tool: conflict.tool.into(),
+            version: conflict.version.to_string(),
+            range: conflict.range,
+            manifest: project.manifest_file().to_owned(),
+        }
+        .into()),
+        None => Ok(()),
+    }
+}
+
 /// Determine the name of the current project, if possible
 fn current_project_name(session: &mut Session) -> Option<String> {
     let project = session.project().ok()??;
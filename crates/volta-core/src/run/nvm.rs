@@ -0,0 +1,136 @@
+//! Provides an `nvm` command that supports the common subset of nvm on top of Volta
+//!
+//! This keeps scripts and instructions written for nvm working while a team moves to Volta. Since
+//! a shim can't change the environment of the shell that runs it, `nvm use` sets the default
+//! Node version (as `volta install` does) rather than the version for the current shell only.
+
+use std::env;
+use std::ffi::OsString;
+#[cfg(unix)]
+use std::os::unix::process::ExitStatusExt;
+#[cfg(windows)]
+use std::os::windows::process::ExitStatusExt;
+use std::process::ExitStatus;
+
+use super::executor::{Executor, InternalInstallCommand};
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::read_file;
+use crate::inventory::{node_available, node_versions};
+use crate::platform::Platform;
+use crate::session::{ActivityKind, Session};
+use crate::style::success_prefix;
+use crate::tool::{node, Spec};
+use crate::version::{VersionSpec, VersionTag};
+use log::info;
+use semver::Version;
+
+/// Build an `Executor` for one of the supported nvm commands
+pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Executor> {
+    session.add_event_start(ActivityKind::Nvm);
+
+    let args: Vec<String> = args
+        .iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => ("", &[][..]),
+    };
+    // Options like `--silent` only change nvm's own output, so `--lts` is the only one that matters
+    let lts = rest.iter().any(|arg| arg == "--lts");
+    let version = rest.iter().find(|arg| !arg.starts_with('-'));
+
+    match command {
+        "install" | "i" => {
+            let spec = version_spec(version, lts)?;
+            Ok(InternalInstallCommand::new(Spec::Node(spec)).into())
+        }
+        "use" => Ok(NvmCommand::Use(version_spec(version, lts)?).into()),
+        "current" => Ok(NvmCommand::Current.into()),
+        other => Err(ErrorKind::NvmCommandUnsupported {
+            command: other.into(),
+        }
+        .into()),
+    }
+}
+
+/// An nvm command that is implemented by Volta directly, rather than by running a tool
+pub enum NvmCommand {
+    /// Makes an installed version the default, without fetching anything
+    Use(VersionSpec),
+    /// Prints the active version
+    Current,
+}
+
+impl NvmCommand {
+    pub(super) fn execute(self, session: &mut Session) -> Fallible<ExitStatus> {
+        match self {
+            NvmCommand::Use(spec) => {
+                let requested = spec.to_string();
+                let version = find_installed(spec, session)?
+                    .ok_or(ErrorKind::NvmVersionNotInstalled { version: requested })?;
+
+                session.toolchain_mut()?.set_active_node(&version)?;
+                info!("{} Now using node v{}", success_prefix(), version);
+            }
+            NvmCommand::Current => match Platform::current(session)? {
+                Some(platform) => println!("v{}", platform.node.value),
+                None => println!("none"),
+            },
+        }
+
+        Ok(ExitStatus::from_raw(0))
+    }
+}
+
+impl From<NvmCommand> for Executor {
+    fn from(cmd: NvmCommand) -> Self {
+        Executor::Nvm(Box::new(cmd))
+    }
+}
+
+/// Finds the newest installed version that matches, as `nvm use` only uses installed versions
+fn find_installed(spec: VersionSpec, session: &mut Session) -> Fallible<Option<Version>> {
+    match spec {
+        VersionSpec::Semver(requirement) => Ok(node_versions()?
+            .into_iter()
+            .rev()
+            .find(|version| requirement.matches(version))),
+        spec => {
+            let version = node::resolve(spec, session)?;
+            Ok(Some(version).filter(|version| node_available(version).unwrap_or(false)))
+        }
+    }
+}
+
+/// Converts an nvm version (like `v16`, `node`, or `lts/*`) to a Volta version spec
+///
+/// As with nvm, the version comes from the closest `.nvmrc` when none is given.
+fn version_spec(version: Option<&String>, lts: bool) -> Fallible<VersionSpec> {
+    if lts {
+        return Ok(VersionSpec::Tag(VersionTag::Lts));
+    }
+
+    let version = match version {
+        Some(version) => version.clone(),
+        None => read_nvmrc()?,
+    };
+
+    match version.strip_prefix('v').unwrap_or(&version) {
+        "node" | "stable" => Ok(VersionSpec::Tag(VersionTag::Latest)),
+        "lts/*" => Ok(VersionSpec::Tag(VersionTag::Lts)),
+        version => version.parse(),
+    }
+}
+
+/// Reads the version from the `.nvmrc` in the current directory or its closest ancestor
+fn read_nvmrc() -> Fallible<String> {
+    let current_dir = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
+
+    current_dir
+        .ancestors()
+        .find_map(|dir| read_file(dir.join(".nvmrc")).ok().flatten())
+        .map(|contents| contents.trim().to_string())
+        .filter(|version| !version.is_empty())
+        .ok_or_else(|| ErrorKind::NoNvmrcFile.into())
+}
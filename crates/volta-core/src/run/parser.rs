@@ -225,7 +225,7 @@ impl<'a> InstallArgs<'a> {
             // URL or path to a tarball). If parsing into a `Spec` fails, we assume that it's a
             // 3rd-party Tool and attempt to install anyway.
             match Spec::try_from_str(&tool.to_string_lossy()) {
-                Ok(Spec::Package(_, _)) | Err(_) => {
+                Ok(Spec::Package(_, _)) | Ok(Spec::PackageSource(_)) | Err(_) => {
                     let platform = platform_spec.as_default();
                     // The args for an individual install command are the common args combined
                     // with the name of the tool.
@@ -294,16 +294,16 @@ impl<'a> UpgradeArgs<'a> {
                         PackageUpgradeCommand::new(args, package, platform, self.manager)?.into(),
                     );
                 }
-                Ok(internal) => {
-                    executors.push(UninstallCommand::new(internal).into());
-                }
-                Err(_) => {
+                Ok(Spec::PackageSource(_)) | Err(_) => {
                     return Err(ErrorKind::UpgradePackageNotFound {
                         package: tool.to_string_lossy().to_string(),
                         manager: self.manager,
                     }
                     .into())
                 }
+                Ok(internal) => {
+                    executors.push(UninstallCommand::new(internal).into());
+                }
             }
         }
 
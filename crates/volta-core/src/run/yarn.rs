@@ -1,5 +1,6 @@
 use std::env;
 use std::ffi::OsString;
+use std::iter::once;
 
 use super::executor::{Executor, ToolCommand, ToolKind};
 use super::parser::CommandArg;
@@ -7,6 +8,7 @@ use super::{debug_active_image, debug_no_platform, RECURSION_ENV_VAR};
 use crate::error::{ErrorKind, Fallible};
 use crate::platform::{Platform, Source, System};
 use crate::session::{ActivityKind, Session};
+use log::debug;
 
 /// Build an `Executor` for Yarn
 ///
@@ -15,7 +17,8 @@ use crate::session::{ActivityKind, Session};
 /// directory.
 ///
 /// If the command is _not_ a global add / remove or we don't have a default platform, then
-/// we will allow Yarn to execute the command as usual.
+/// we will allow Yarn to execute the command as usual. When the project vendors a Yarn release
+/// with `yarnPath`, that release is run with the platform's Node instead of Volta's Yarn.
 pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Executor> {
     session.add_event_start(ActivityKind::Yarn);
     // Don't re-evaluate the context or global install interception if this is a recursive call
@@ -33,6 +36,20 @@ pub(super) fn command(args: &[OsString], session: &mut Session) -> Fallible<Exec
         }
     };
 
+    if let Some(release) = session.project()?.and_then(|project| project.yarn_path()) {
+        debug!(
+            "Using the Yarn release from yarnPath: {}",
+            release.display()
+        );
+        // The vendored release replaces the Yarn in the platform, so it doesn't need to be fetched
+        let platform = platform.map(|platform| Platform {
+            yarn: None,
+            ..platform
+        });
+        let args = once(release.into_os_string()).chain(args.iter().cloned());
+        return Ok(ToolCommand::new("node", args, platform, ToolKind::Node).into());
+    }
+
     Ok(ToolCommand::new("yarn", args, platform, ToolKind::Yarn).into())
 }
 
@@ -66,6 +83,7 @@ fn validate_platform_yarn(platform: &Platform) -> Fallible<()> {
             Source::Project => Err(ErrorKind::NoProjectYarn.into()),
             Source::Default | Source::Binary => Err(ErrorKind::NoDefaultYarn.into()),
             Source::CommandLine => Err(ErrorKind::NoCommandLineYarn.into()),
+            Source::Shell => Err(ErrorKind::NoShellYarn.into()),
         },
     }
 }
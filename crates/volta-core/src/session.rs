@@ -2,14 +2,23 @@
 //! execution of a Volta tool, including their current directory, Volta
 //! hook configuration, and the state of the local inventory.
 
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
 use std::fmt::{self, Display, Formatter};
+use std::path::PathBuf;
 use std::process::exit;
+use std::sync::Arc;
 
-use crate::error::{ExitCode, Fallible, VoltaError};
-use crate::event::EventLog;
+use crate::environment;
+use crate::error::{ErrorKind, ExitCode, Fallible, VoltaError};
+use crate::event::{EventLog, EventSubscriber};
 use crate::hook::{HookConfig, LazyHookConfig};
-use crate::platform::PlatformSpec;
+use crate::http::{set_client, HttpClient};
+use crate::layout::{set_volta_home, volta_home};
+use crate::platform::{PlatformSpec, ShellPlatform, SHELL_PLATFORM_VAR};
+use crate::progress::{self, ProgressSink};
 use crate::project::{LazyProject, Project};
+use crate::settings::{LazySettings, Settings};
 use crate::toolchain::{LazyToolchain, Toolchain};
 use log::debug;
 
@@ -18,10 +27,12 @@ pub enum ActivityKind {
     Fetch,
     Install,
     Uninstall,
+    Upgrade,
     List,
     Current,
     Default,
     Pin,
+    Unpin,
     Node,
     Npm,
     Npx,
@@ -36,6 +47,26 @@ pub enum ActivityKind {
     Which,
     Setup,
     Run,
+    Exec,
+    ExecCommand,
+    RefreshIndex,
+    EvictUnused,
+    Complete,
+    Env,
+    Use,
+    Activate,
+    Deactivate,
+    Inspect,
+    Import,
+    Inventory,
+    ExportSettings,
+    ImportSettings,
+    Doctor,
+    Verify,
+    Nvm,
+    Migrate,
+    Audit,
+    Sbom,
     Args,
 }
 
@@ -45,10 +76,12 @@ impl Display for ActivityKind {
             ActivityKind::Fetch => "fetch",
             ActivityKind::Install => "install",
             ActivityKind::Uninstall => "uninstall",
+            ActivityKind::Upgrade => "upgrade",
             ActivityKind::List => "list",
             ActivityKind::Current => "current",
             ActivityKind::Default => "default",
             ActivityKind::Pin => "pin",
+            ActivityKind::Unpin => "unpin",
             ActivityKind::Node => "node",
             ActivityKind::Npm => "npm",
             ActivityKind::Npx => "npx",
@@ -63,6 +96,26 @@ impl Display for ActivityKind {
             ActivityKind::Completions => "completions",
             ActivityKind::Which => "which",
             ActivityKind::Run => "run",
+            ActivityKind::Exec => "exec",
+            ActivityKind::ExecCommand => "exec-command",
+            ActivityKind::RefreshIndex => "refresh-index",
+            ActivityKind::EvictUnused => "evict-unused",
+            ActivityKind::Complete => "complete",
+            ActivityKind::Env => "env",
+            ActivityKind::Use => "use",
+            ActivityKind::Activate => "activate",
+            ActivityKind::Deactivate => "deactivate",
+            ActivityKind::Inspect => "inspect",
+            ActivityKind::Import => "import",
+            ActivityKind::Inventory => "inventory",
+            ActivityKind::ExportSettings => "export-settings",
+            ActivityKind::ImportSettings => "import-settings",
+            ActivityKind::Doctor => "doctor",
+            ActivityKind::Verify => "verify",
+            ActivityKind::Nvm => "nvm",
+            ActivityKind::Migrate => "migrate",
+            ActivityKind::Audit => "audit",
+            ActivityKind::Sbom => "sbom",
             ActivityKind::Args => "args",
         };
         f.write_str(s)
@@ -76,22 +129,51 @@ impl Display for ActivityKind {
 /// - the current directory
 /// - the Node project tree that contains the current directory (if any)
 /// - the Volta hook configuration
+/// - the Volta settings
 /// - the inventory of locally-fetched Volta tools
 pub struct Session {
     hooks: LazyHookConfig,
+    settings: LazySettings,
     toolchain: LazyToolchain,
     project: LazyProject,
     event_log: EventLog,
+    include_prerelease: bool,
+    replace_bins: bool,
+    frozen: bool,
+    strict: bool,
+    fetch_missing: bool,
+    prompt: bool,
+    shell_platform: Option<ShellPlatform>,
 }
 
 impl Session {
-    /// Constructs a new `Session`.
+    /// Constructs a new `Session` for the current directory and environment of the process.
     pub fn init() -> Session {
+        Session::new(LazyProject::init(), &environment::var_os)
+    }
+
+    /// Starts building a `Session` with an explicit working directory, Volta directory, or
+    /// environment, e.g. for tests or programs that embed Volta
+    pub fn builder() -> SessionBuilder {
+        SessionBuilder::default()
+    }
+
+    fn new(project: LazyProject, var_os: &dyn Fn(&str) -> Option<OsString>) -> Session {
         Session {
             hooks: LazyHookConfig::init(),
+            settings: LazySettings::init(),
             toolchain: LazyToolchain::init(),
-            project: LazyProject::init(),
+            project,
             event_log: EventLog::init(),
+            include_prerelease: false,
+            replace_bins: false,
+            frozen: var_os("VOLTA_FROZEN").is_some(),
+            strict: var_os("VOLTA_STRICT").is_some(),
+            fetch_missing: true,
+            prompt: true,
+            shell_platform: var_os(SHELL_PLATFORM_VAR)
+                .and_then(|token| ShellPlatform::parse(&token.to_string_lossy()))
+                .filter(|platform| !platform.is_empty()),
         }
     }
 
@@ -105,11 +187,26 @@ impl Session {
         self.project.get_mut()
     }
 
+    /// Switches the current Node project to the root of the workspace that contains it
+    pub fn use_workspace_root(&mut self) -> Fallible<()> {
+        self.project.use_workspace_root()
+    }
+
+    /// Switches the current Node project to the one that contains the given directory, if any
+    pub fn use_project_dir(&mut self, dir: PathBuf) -> Fallible<()> {
+        self.project.use_dir(dir)
+    }
+
     /// Returns the user's default platform, if any
     pub fn default_platform(&self) -> Fallible<Option<&PlatformSpec>> {
         self.toolchain.get().map(Toolchain::platform)
     }
 
+    /// Returns the versions selected with `volta use` for the current shell session, if any
+    pub fn shell_platform(&self) -> Option<&ShellPlatform> {
+        self.shell_platform.as_ref()
+    }
+
     /// Returns the current project's pinned platform image, if any.
     pub fn project_platform(&self) -> Fallible<Option<&PlatformSpec>> {
         if let Some(project) = self.project()? {
@@ -130,7 +227,79 @@ impl Session {
 
     /// Produces a reference to the hook configuration
     pub fn hooks(&self) -> Fallible<&HookConfig> {
-        self.hooks.get(self.project()?)
+        self.hooks.get(self.project()?, self.prompt)
+    }
+
+    /// Produces a reference to the settings
+    pub fn settings(&self) -> Fallible<&Settings> {
+        self.settings.get(self.project()?)
+    }
+
+    /// Whether version ranges should be allowed to match prerelease versions
+    pub fn include_prerelease(&self) -> bool {
+        self.include_prerelease
+    }
+
+    /// Opts in to matching prerelease versions when resolving version ranges
+    pub fn set_include_prerelease(&mut self, include: bool) {
+        self.include_prerelease = include;
+    }
+
+    /// Whether installed packages may take over executables that another package provides
+    pub fn replace_bins(&self) -> bool {
+        self.replace_bins
+    }
+
+    /// Allows installed packages to take over executables that another package provides
+    pub fn set_replace_bins(&mut self, replace: bool) {
+        self.replace_bins = replace;
+    }
+
+    /// Whether floating project pins must be resolved from `volta.lock`, rather than re-resolved
+    pub fn frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Requires floating project pins to be resolved from `volta.lock`, e.g. for CI
+    pub fn set_frozen(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Whether a project without pins must be an error, rather than using the default platform
+    pub fn strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Requires projects to pin their platform, e.g. so that CI never runs an unpinned toolchain
+    pub fn set_strict(&mut self) {
+        self.strict = true;
+    }
+
+    /// Whether checking out a platform may fetch the tools that are missing
+    pub fn fetch_missing(&self) -> bool {
+        self.fetch_missing
+    }
+
+    /// Makes checking out a platform fail if any of its tools are missing, rather than fetching
+    /// them, e.g. for shims when automatic downloads are disabled
+    pub fn set_no_fetch_missing(&mut self) {
+        self.fetch_missing = false;
+    }
+
+    /// Whether the user may be asked questions, e.g. to allow the commands of project hooks
+    pub fn prompt(&self) -> bool {
+        self.prompt
+    }
+
+    /// Makes anything that would ask the user a question fail instead, e.g. for shims, since the
+    /// terminal belongs to the tool being run
+    pub fn set_no_prompt(&mut self) {
+        self.prompt = false;
+    }
+
+    /// Adds a subscriber that receives the events of this session as they are recorded
+    pub fn subscribe(&mut self, subscriber: Box<dyn EventSubscriber>) {
+        self.event_log.subscribe(subscriber)
     }
 
     pub fn add_event_start(&mut self, activity_kind: ActivityKind) {
@@ -151,11 +320,12 @@ impl Session {
             project,
             hooks,
             mut event_log,
+            prompt,
             ..
         } = self;
         let plugin_res = project
             .get()
-            .and_then(|p| hooks.get(p))
+            .and_then(|p| hooks.get(p, prompt))
             .map(|hooks| hooks.events().and_then(|e| e.publish.as_ref()));
         match plugin_res {
             Ok(plugin) => {
@@ -168,17 +338,110 @@ impl Session {
         }
     }
 
+    /// Publishes the event log and exits the process
+    ///
+    /// This is only meant for the Volta binaries. Programs that embed Volta should drop the
+    /// session instead, which never exits the process.
     pub fn exit(self, code: ExitCode) -> ! {
         self.publish_to_event_log();
         code.exit();
     }
 
+    /// Publishes the event log and exits the process with the exit code of a tool, like `exit`
     pub fn exit_tool(self, code: i32) -> ! {
         self.publish_to_event_log();
         exit(code);
     }
 }
 
+/// Builds a `Session`, using the current directory and environment of the process for anything
+/// that isn't given
+#[derive(Default)]
+pub struct SessionBuilder {
+    current_dir: Option<PathBuf>,
+    volta_home: Option<PathBuf>,
+    env: Option<HashMap<OsString, OsString>>,
+    http_client: Option<Arc<dyn HttpClient>>,
+    progress_sink: Option<Arc<dyn ProgressSink>>,
+}
+
+impl SessionBuilder {
+    /// Sets the directory that the project is found from
+    pub fn current_dir(mut self, dir: PathBuf) -> Self {
+        self.current_dir = Some(dir);
+        self
+    }
+
+    /// Sets the Volta directory, which is shared by every session in the process, so it has to be
+    /// set before anything uses it (see `layout::set_volta_home`)
+    pub fn volta_home(mut self, home: PathBuf) -> Self {
+        self.volta_home = Some(home);
+        self
+    }
+
+    /// Replaces the environment that Volta reads its options from (e.g. `VOLTA_FROZEN` or
+    /// `VOLTA_HOME`) with a snapshot, so that the variables of the process are ignored
+    ///
+    /// Apart from the options of the session itself, the snapshot is shared by every session in
+    /// the process, like the HTTP client.
+    pub fn env<I, K, V>(mut self, vars: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        let vars = vars
+            .into_iter()
+            .map(|(key, value)| (key.as_ref().to_owned(), value.as_ref().to_owned()))
+            .collect();
+        self.env = Some(vars);
+        self
+    }
+
+    /// Sets the client that requests are sent with, which replaces the client of every session in
+    /// the process (see `http::set_client`)
+    pub fn http_client(mut self, client: Arc<dyn HttpClient>) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Sets the sink that progress is reported to, which is also shared by every session in the
+    /// process (see `progress::set_sink`)
+    pub fn progress_sink(mut self, sink: Arc<dyn ProgressSink>) -> Self {
+        self.progress_sink = Some(sink);
+        self
+    }
+
+    pub fn build(self) -> Fallible<Session> {
+        if let Some(vars) = &self.env {
+            environment::set_vars(vars.clone());
+        }
+        if let Some(client) = self.http_client {
+            set_client(client);
+        }
+        if let Some(sink) = self.progress_sink {
+            progress::set_sink(sink);
+        }
+
+        if let Some(home) = self.volta_home {
+            if !set_volta_home(home.clone()) && volta_home()?.root() != home {
+                return Err(ErrorKind::VoltaHomeAlreadySet { home }.into());
+            }
+        }
+
+        let project = match self.current_dir {
+            Some(dir) => LazyProject::init_in(dir),
+            None => LazyProject::init(),
+        };
+
+        let session = match &self.env {
+            Some(vars) => Session::new(project, &|name| vars.get(OsStr::new(name)).cloned()),
+            None => Session::new(project, &environment::var_os),
+        };
+        Ok(session)
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
 
@@ -211,4 +474,30 @@ pub mod tests {
             .expect("Couldn't create Project");
         assert!(unpinned_platform.is_none());
     }
+
+    #[test]
+    fn test_builder_uses_given_dir_and_env() {
+        let session = Session::builder()
+            .current_dir(fixture_path("basic"))
+            .env(vec![("VOLTA_STRICT", "1")])
+            .build()
+            .expect("Could not build session");
+        assert!(session
+            .project_platform()
+            .expect("Couldn't create Project")
+            .is_some());
+        assert!(session.strict());
+        assert!(!session.frozen());
+
+        let session = Session::builder()
+            .current_dir(fixture_path("no_toolchain"))
+            .env(Vec::<(String, String)>::new())
+            .build()
+            .expect("Could not build session");
+        assert!(session
+            .project_platform()
+            .expect("Couldn't create Project")
+            .is_none());
+        assert!(!session.strict());
+    }
 }
@@ -0,0 +1,599 @@
+//! Provides types for working with Volta settings, the user- and project-level
+//! policy configuration that controls how tool versions are resolved.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::iter::once;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::cert_pins::Pin;
+use crate::error::{Context, ErrorKind, Fallible, VoltaError};
+use crate::layout::volta_home;
+use crate::project::Project;
+use crate::style::{note_prefix, tool_version};
+use lazycell::LazyCell;
+use log::{debug, info};
+use semver::{Version, VersionReq};
+
+pub(crate) mod serial;
+
+/// Lazily loaded Volta settings
+pub struct LazySettings {
+    settings: LazyCell<Settings>,
+}
+
+impl LazySettings {
+    /// Constructs a new `LazySettings`
+    pub fn init() -> LazySettings {
+        LazySettings {
+            settings: LazyCell::new(),
+        }
+    }
+
+    /// Forces the loading of the settings from both project-local and user-default settings files
+    pub fn get(&self, project: Option<&Project>) -> Fallible<&Settings> {
+        self.settings.try_borrow_with(|| Settings::current(project))
+    }
+}
+
+/// Volta settings
+pub struct Settings {
+    node_blocklist: Blocklist,
+    npm_blocklist: Blocklist,
+    yarn_blocklist: Blocklist,
+    resolution: Option<ResolutionPreference>,
+    pin_location: Option<PinLocation>,
+    index_refresh_hours: Option<u64>,
+    node_build_command: Option<String>,
+    certificate_pins: HashMap<String, Vec<Pin>>,
+    checksum_policy: Option<ChecksumPolicy>,
+    auto_download: Option<bool>,
+    evict_unused_after_days: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            node_blocklist: Blocklist::empty("node"),
+            npm_blocklist: Blocklist::empty("npm"),
+            yarn_blocklist: Blocklist::empty("yarn"),
+            resolution: None,
+            pin_location: None,
+            index_refresh_hours: None,
+            node_build_command: None,
+            certificate_pins: HashMap::new(),
+            checksum_policy: None,
+            auto_download: None,
+            evict_unused_after_days: None,
+        }
+    }
+}
+
+impl Settings {
+    /// The known-bad Node versions that resolution should skip
+    pub fn node_blocklist(&self) -> &Blocklist {
+        &self.node_blocklist
+    }
+
+    /// The known-bad npm versions that resolution should skip
+    pub fn npm_blocklist(&self) -> &Blocklist {
+        &self.npm_blocklist
+    }
+
+    /// The known-bad Yarn versions that resolution should skip
+    pub fn yarn_blocklist(&self) -> &Blocklist {
+        &self.yarn_blocklist
+    }
+
+    /// How version ranges should be resolved, defaulting to the newest matching version
+    pub fn resolution(&self) -> ResolutionPreference {
+        self.resolution.unwrap_or_default()
+    }
+
+    /// Where new project pins should be written, defaulting to `package.json`
+    pub fn pin_location(&self) -> PinLocation {
+        self.pin_location.unwrap_or_default()
+    }
+
+    /// How old the cached Node index can get before it is refreshed in the background
+    ///
+    /// Background refreshes are opt-in, so this is `None` unless it has been configured
+    pub fn index_refresh(&self) -> Option<Duration> {
+        self.index_refresh_hours
+            .map(|hours| Duration::from_secs(hours * 60 * 60))
+    }
+
+    /// The shell command that builds Node from source, on platforms without pre-built binaries
+    ///
+    /// The command is run in the unpacked source directory, and installs into the directory in
+    /// `VOLTA_NODE_PREFIX`. Building from source is opt-in, so this is `None` unless configured.
+    pub fn node_build_command(&self) -> Option<&str> {
+        self.node_build_command.as_deref()
+    }
+
+    /// The pinned certificate digests for each host, which downloads from that host must match
+    pub(crate) fn certificate_pins(&self) -> &HashMap<String, Vec<Pin>> {
+        &self.certificate_pins
+    }
+
+    /// What to do when a download doesn't match the checksum recorded the first time its URL was
+    /// downloaded, defaulting to refusing to use it
+    pub fn checksum_policy(&self) -> ChecksumPolicy {
+        self.checksum_policy.unwrap_or_default()
+    }
+
+    /// Whether shims may download the tools that a project pins but that haven't been fetched,
+    /// which they do unless it has been disabled
+    pub fn auto_download(&self) -> bool {
+        self.auto_download.unwrap_or(true)
+    }
+
+    /// How long a Node, npm, or Yarn version can go unused before `volta evict-unused` removes it
+    ///
+    /// Removing versions is opt-in, so this is `None` unless it has been configured
+    pub fn evict_unused_after(&self) -> Option<Duration> {
+        self.evict_unused_after_days
+            .map(|days| Duration::from_secs(days * 24 * 60 * 60))
+    }
+
+    /// Returns the user settings alone, for policies that a project must not be able to change
+    pub(crate) fn user() -> Fallible<Self> {
+        let default_settings_file = volta_home()?.default_settings_file();
+        Self::from_paths(once(default_settings_file))
+    }
+
+    /// Returns the current settings, which are a merge between the user settings and the
+    /// project settings (if any).
+    fn current(project: Option<&Project>) -> Fallible<Self> {
+        // As with hooks, project settings are sorted in descending precedence, and all of them
+        // take precedence over the user-level settings
+        let paths = project
+            .into_iter()
+            .flat_map(Project::workspace_roots)
+            .map(|root| {
+                let mut path = root.join(".volta");
+                path.push("settings.json");
+                path
+            });
+
+        Ok(Self::from_paths(paths)?.over(Self::user()?))
+    }
+
+    /// Applies these project settings over the user settings
+    ///
+    /// Building Node from source runs an arbitrary shell command, so unlike the other settings,
+    /// the build command can only come from the user settings.
+    fn over(self, user: Self) -> Self {
+        let project = Settings {
+            node_build_command: None,
+            ..self
+        };
+        project.merge(user)
+    }
+
+    /// Returns the merged settings loaded from an iterator of potential settings files
+    ///
+    /// `paths` should be sorted in order of descending precedence.
+    fn from_paths<P, I>(paths: I) -> Fallible<Self>
+    where
+        P: AsRef<Path>,
+        I: IntoIterator<Item = P>,
+    {
+        paths.into_iter().try_fold(Self::default(), |acc, file| {
+            match Self::from_file(file.as_ref())? {
+                Some(settings) => {
+                    debug!("Loaded settings file: {}", file.as_ref().display());
+                    Ok(acc.merge(settings))
+                }
+                None => Ok(acc),
+            }
+        })
+    }
+
+    fn from_file(file_path: &Path) -> Fallible<Option<Self>> {
+        if !file_path.is_file() {
+            return Ok(None);
+        }
+
+        let file = File::open(file_path).with_context(|| ErrorKind::ReadSettingsError {
+            file: file_path.to_path_buf(),
+        })?;
+
+        let raw: serial::RawSettings =
+            serde_json::de::from_reader(file).with_context(|| ErrorKind::ParseSettingsError {
+                file: file_path.to_path_buf(),
+            })?;
+
+        raw.into_settings().map(Some)
+    }
+
+    /// Merges these Settings with another, giving precedence to the current instance
+    fn merge(self, other: Self) -> Self {
+        Self {
+            node_blocklist: self.node_blocklist.merge(other.node_blocklist),
+            npm_blocklist: self.npm_blocklist.merge(other.npm_blocklist),
+            yarn_blocklist: self.yarn_blocklist.merge(other.yarn_blocklist),
+            resolution: self.resolution.or(other.resolution),
+            pin_location: self.pin_location.or(other.pin_location),
+            index_refresh_hours: self.index_refresh_hours.or(other.index_refresh_hours),
+            node_build_command: self.node_build_command.or(other.node_build_command),
+            certificate_pins: merge_pins(self.certificate_pins, other.certificate_pins),
+            checksum_policy: self.checksum_policy.or(other.checksum_policy),
+            auto_download: merge_auto_download(self.auto_download, other.auto_download),
+            evict_unused_after_days: self
+                .evict_unused_after_days
+                .or(other.evict_unused_after_days),
+        }
+    }
+}
+
+/// Which version to choose when resolving a version range
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum ResolutionPreference {
+    /// Always use the newest published version that matches the range
+    Newest,
+    /// Use the newest matching version that is already installed, only downloading if none is
+    InstalledFirst,
+}
+
+impl Default for ResolutionPreference {
+    fn default() -> Self {
+        ResolutionPreference::Newest
+    }
+}
+
+impl FromStr for ResolutionPreference {
+    type Err = VoltaError;
+
+    fn from_str(s: &str) -> Fallible<Self> {
+        match s {
+            "newest" => Ok(ResolutionPreference::Newest),
+            "installed-first" => Ok(ResolutionPreference::InstalledFirst),
+            other => Err(ErrorKind::InvalidResolutionPreference {
+                preference: other.into(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// Which file new project pins are written to
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum PinLocation {
+    /// The `volta` key in `package.json`
+    PackageJson,
+    /// A separate `volta.json` file next to `package.json`
+    VoltaJson,
+}
+
+impl Default for PinLocation {
+    fn default() -> Self {
+        PinLocation::PackageJson
+    }
+}
+
+impl FromStr for PinLocation {
+    type Err = VoltaError;
+
+    fn from_str(s: &str) -> Fallible<Self> {
+        match s {
+            "package.json" => Ok(PinLocation::PackageJson),
+            "volta.json" => Ok(PinLocation::VoltaJson),
+            other => Err(ErrorKind::InvalidPinLocation {
+                location: other.into(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// What to do with a download that doesn't match the checksum recorded for its URL
+#[derive(Clone, Copy, PartialEq)]
+#[cfg_attr(test, derive(Debug))]
+pub enum ChecksumPolicy {
+    /// Fail the download, leaving the tool uninstalled
+    Refuse,
+    /// Use the download anyway, after showing a warning
+    Warn,
+}
+
+impl Default for ChecksumPolicy {
+    fn default() -> Self {
+        ChecksumPolicy::Refuse
+    }
+}
+
+impl FromStr for ChecksumPolicy {
+    type Err = VoltaError;
+
+    fn from_str(s: &str) -> Fallible<Self> {
+        match s {
+            "refuse" => Ok(ChecksumPolicy::Refuse),
+            "warn" => Ok(ChecksumPolicy::Warn),
+            other => Err(ErrorKind::InvalidChecksumPolicy {
+                policy: other.into(),
+            }
+            .into()),
+        }
+    }
+}
+
+/// A set of known-bad versions of a single tool
+pub struct Blocklist {
+    tool: &'static str,
+    entries: Vec<BlockedVersions>,
+}
+
+/// A range of blocklisted versions, along with an optional explanation
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct BlockedVersions {
+    pub versions: VersionReq,
+    pub reason: Option<String>,
+}
+
+impl Blocklist {
+    fn empty(tool: &'static str) -> Self {
+        Blocklist {
+            tool,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Returns the blocklist entry that matches the given version, if any
+    pub fn find(&self, version: &Version) -> Option<&BlockedVersions> {
+        self.entries
+            .iter()
+            .find(|entry| entry.versions.matches(version))
+    }
+
+    /// Determines whether a version that would otherwise be selected should be skipped
+    ///
+    /// Shows a note explaining the skip, so that users aren't surprised when resolution
+    /// chooses an older version than they expected.
+    pub fn skips(&self, version: &Version) -> bool {
+        match self.find(version) {
+            Some(entry) => {
+                info!(
+                    "{} skipping {} because it is blocklisted{}",
+                    note_prefix(),
+                    tool_version(self.tool, version),
+                    entry.explanation()
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a summary of the blocked ranges, which changes whenever the blocklist does
+    pub fn fingerprint(&self) -> String {
+        self.entries
+            .iter()
+            .map(|entry| entry.versions.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Ensures that an explicitly requested version is not blocklisted
+    pub fn ensure_allowed(&self, version: &Version) -> Fallible<()> {
+        match self.find(version) {
+            Some(entry) => Err(ErrorKind::VersionBlocklisted {
+                tool: self.tool.into(),
+                version: version.to_string(),
+                reason: entry.reason.clone(),
+            }
+            .into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Combines the entries of two blocklists, since a version blocked anywhere should be skipped
+    fn merge(mut self, other: Self) -> Self {
+        self.entries.extend(other.entries);
+        self
+    }
+}
+
+/// Combines the pins of two settings files, since a certificate pinned anywhere is trusted
+fn merge_pins(
+    mut pins: HashMap<String, Vec<Pin>>,
+    other: HashMap<String, Vec<Pin>>,
+) -> HashMap<String, Vec<Pin>> {
+    for (host, host_pins) in other {
+        pins.entry(host).or_default().extend(host_pins);
+    }
+    pins
+}
+
+/// Combines the automatic download settings, so that a project can't allow the downloads that
+/// the user settings disabled, e.g. on a metered connection
+fn merge_auto_download(auto_download: Option<bool>, other: Option<bool>) -> Option<bool> {
+    match (auto_download, other) {
+        (Some(false), _) | (_, Some(false)) => Some(false),
+        (auto_download, other) => auto_download.or(other),
+    }
+}
+
+impl BlockedVersions {
+    fn explanation(&self) -> String {
+        match &self.reason {
+            Some(reason) => format!(": {}", reason),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::version::{parse_requirements, parse_version};
+    use std::path::PathBuf;
+
+    fn fixture_path(fixture_dir: &str) -> PathBuf {
+        let mut cargo_manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+        cargo_manifest_dir.push("fixtures");
+        cargo_manifest_dir.push(fixture_dir);
+        cargo_manifest_dir
+    }
+
+    #[test]
+    fn test_from_file_blocklist() {
+        let settings_file = fixture_path("settings").join("blocklist.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+
+        assert_eq!(
+            settings.node_blocklist().entries,
+            vec![BlockedVersions {
+                versions: parse_requirements("18.0.0").unwrap(),
+                reason: Some("fs.watch regression".into()),
+            }]
+        );
+        assert_eq!(
+            settings.yarn_blocklist().entries,
+            vec![BlockedVersions {
+                versions: parse_requirements(">=1.22.0 <1.22.5").unwrap(),
+                reason: None,
+            }]
+        );
+        assert!(settings.npm_blocklist().entries.is_empty());
+    }
+
+    #[test]
+    fn test_resolution_preference() {
+        let settings_file = fixture_path("settings").join("resolution.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(settings.resolution(), ResolutionPreference::InstalledFirst);
+
+        // Project settings take precedence, and the default is to use the newest version
+        let merged = Settings::default().merge(settings);
+        assert_eq!(merged.resolution(), ResolutionPreference::InstalledFirst);
+        assert_eq!(
+            Settings::default().resolution(),
+            ResolutionPreference::Newest
+        );
+    }
+
+    #[test]
+    fn test_pin_location() {
+        let settings_file = fixture_path("settings").join("pin-location.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(settings.pin_location(), PinLocation::VoltaJson);
+        assert_eq!(Settings::default().pin_location(), PinLocation::PackageJson);
+    }
+
+    #[test]
+    fn test_index_refresh() {
+        let settings_file = fixture_path("settings").join("index-refresh.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(
+            settings.index_refresh(),
+            Some(Duration::from_secs(12 * 60 * 60))
+        );
+        assert_eq!(Settings::default().index_refresh(), None);
+    }
+
+    #[test]
+    fn test_node_build_command() {
+        let settings_file = fixture_path("settings").join("node-build.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(
+            settings.node_build_command(),
+            Some("./configure --prefix=\"$VOLTA_NODE_PREFIX\" && make -j4 install")
+        );
+        assert_eq!(Settings::default().node_build_command(), None);
+    }
+
+    #[test]
+    fn test_project_node_build_command_is_ignored() {
+        let settings_file = fixture_path("settings").join("node-build.json");
+        let project = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(project.over(Settings::default()).node_build_command(), None);
+
+        let user = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(
+            Settings::default().over(user).node_build_command(),
+            Some("./configure --prefix=\"$VOLTA_NODE_PREFIX\" && make -j4 install")
+        );
+    }
+
+    #[test]
+    fn test_certificate_pins() {
+        let settings_file = fixture_path("settings").join("certificate-pins.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        let pins = settings.certificate_pins();
+
+        assert_eq!(pins["nodejs.org"].len(), 2);
+        assert_eq!(
+            pins["repo.yarnpkg.com"],
+            vec![Pin::PublicKey(vec![0x01; 32])]
+        );
+        assert!(Settings::default().certificate_pins().is_empty());
+    }
+
+    #[test]
+    fn test_checksum_policy() {
+        let settings_file = fixture_path("settings").join("checksum-mismatch.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(settings.checksum_policy(), ChecksumPolicy::Warn);
+        assert_eq!(
+            Settings::default().checksum_policy(),
+            ChecksumPolicy::Refuse
+        );
+    }
+
+    #[test]
+    fn test_auto_download() {
+        let settings_file = fixture_path("settings").join("auto-download.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert!(!settings.auto_download());
+        assert!(Settings::default().auto_download());
+
+        // Downloads disabled by the user settings stay disabled, whatever the project settings say
+        let project = Settings {
+            auto_download: Some(true),
+            ..Settings::default()
+        };
+        assert!(!project.merge(settings).auto_download());
+    }
+
+    #[test]
+    fn test_evict_unused() {
+        let settings_file = fixture_path("settings").join("evict-unused.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        assert_eq!(
+            settings.evict_unused_after(),
+            Some(Duration::from_secs(30 * 24 * 60 * 60))
+        );
+        assert_eq!(Settings::default().evict_unused_after(), None);
+    }
+
+    #[test]
+    fn test_blocklist_skips() {
+        let settings_file = fixture_path("settings").join("blocklist.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+        let blocklist = settings.node_blocklist();
+
+        assert!(blocklist.skips(&parse_version("18.0.0").unwrap()));
+        assert!(!blocklist.skips(&parse_version("18.1.0").unwrap()));
+        assert!(blocklist
+            .ensure_allowed(&parse_version("18.0.0").unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_blocklist_fingerprint() {
+        let settings_file = fixture_path("settings").join("blocklist.json");
+        let settings = Settings::from_file(&settings_file).unwrap().unwrap();
+
+        // Resolutions are cached by the fingerprint, so it must change along with the blocklist
+        assert_ne!(
+            settings.node_blocklist().fingerprint(),
+            settings.npm_blocklist().fingerprint()
+        );
+        assert_eq!(settings.npm_blocklist().fingerprint(), "");
+    }
+}
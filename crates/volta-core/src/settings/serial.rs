@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+
+use super::{BlockedVersions, Blocklist, Settings};
+use crate::error::Fallible;
+use crate::version::parse_requirements;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct RawSettings {
+    #[serde(default)]
+    pub blocklist: RawBlocklists,
+    pub resolution: Option<String>,
+    #[serde(rename = "pinLocation")]
+    pub pin_location: Option<String>,
+    /// The number of hours after which the cached Node index is refreshed in the background
+    #[serde(rename = "indexRefreshHours")]
+    pub index_refresh_hours: Option<u64>,
+    /// The command that builds Node from source, on platforms without pre-built binaries
+    #[serde(rename = "nodeBuildCommand")]
+    pub node_build_command: Option<String>,
+    /// The SHA-256 digests of the certificates (or public keys) to accept for each host
+    #[serde(default, rename = "certificatePins")]
+    pub certificate_pins: HashMap<String, Vec<String>>,
+    /// Whether to "refuse" or "warn" about downloads that don't match their recorded checksum
+    #[serde(rename = "checksumMismatch")]
+    pub checksum_mismatch: Option<String>,
+    /// Whether shims may download the pinned tools that haven't been fetched
+    #[serde(rename = "autoDownload")]
+    pub auto_download: Option<bool>,
+    /// The number of days after which an unused Node, npm, or Yarn version can be removed
+    #[serde(rename = "evictUnusedAfterDays")]
+    pub evict_unused_after_days: Option<u64>,
+}
+
+#[derive(Default, Serialize, Deserialize)]
+pub struct RawBlocklists {
+    #[serde(default)]
+    pub node: Vec<RawBlockedVersions>,
+    #[serde(default)]
+    pub npm: Vec<RawBlockedVersions>,
+    #[serde(default)]
+    pub yarn: Vec<RawBlockedVersions>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct RawBlockedVersions {
+    pub versions: String,
+    pub reason: Option<String>,
+}
+
+impl RawSettings {
+    pub fn into_settings(self) -> Fallible<Settings> {
+        let RawBlocklists { node, npm, yarn } = self.blocklist;
+
+        Ok(Settings {
+            node_blocklist: into_blocklist("node", node)?,
+            npm_blocklist: into_blocklist("npm", npm)?,
+            yarn_blocklist: into_blocklist("yarn", yarn)?,
+            resolution: self.resolution.map(|raw| raw.parse()).transpose()?,
+            pin_location: self.pin_location.map(|raw| raw.parse()).transpose()?,
+            index_refresh_hours: self.index_refresh_hours,
+            node_build_command: self.node_build_command,
+            certificate_pins: self
+                .certificate_pins
+                .into_iter()
+                .map(|(host, pins)| {
+                    let pins = pins
+                        .iter()
+                        .map(|pin| pin.parse())
+                        .collect::<Fallible<Vec<_>>>()?;
+                    Ok((host, pins))
+                })
+                .collect::<Fallible<_>>()?,
+            checksum_policy: self.checksum_mismatch.map(|raw| raw.parse()).transpose()?,
+            auto_download: self.auto_download,
+            evict_unused_after_days: self.evict_unused_after_days,
+        })
+    }
+}
+
+fn into_blocklist(tool: &'static str, raw: Vec<RawBlockedVersions>) -> Fallible<Blocklist> {
+    let entries = raw
+        .into_iter()
+        .map(|RawBlockedVersions { versions, reason }| {
+            Ok(BlockedVersions {
+                versions: parse_requirements(versions)?,
+                reason,
+            })
+        })
+        .collect::<Fallible<Vec<_>>>()?;
+
+    Ok(Blocklist { tool, entries })
+}
@@ -2,14 +2,18 @@
 
 use std::collections::HashSet;
 use std::fs::{self, DirEntry, Metadata};
-use std::io;
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
 
 use crate::error::{Context, ErrorKind, Fallible, VoltaError};
-use crate::fs::{read_dir_eager, symlink_file};
+use crate::fs::{
+    create_staging_file, file_sha256, long_path, persist_staging_file, read_dir_eager, read_file,
+    symlink_file,
+};
 use crate::layout::{volta_home, volta_install};
 use crate::sync::VoltaLock;
 use log::debug;
+use serde::{Deserialize, Serialize};
 
 pub fn regenerate_shims_for_dir(dir: &Path) -> Fallible<()> {
     // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
@@ -20,7 +24,8 @@ pub fn regenerate_shims_for_dir(dir: &Path) -> Fallible<()> {
         create(shim_name)?;
     }
 
-    Ok(())
+    // The shims now all link to the current executable, so that is the one to expect from now on
+    record_executable()
 }
 
 fn get_shim_list_deduped(dir: &Path) -> Fallible<HashSet<String>> {
@@ -69,10 +74,17 @@ pub fn create(shim_name: &str) -> Fallible<ShimResult> {
     let executable = volta_install()?.shim_executable();
     let shim = volta_home()?.shim_file(shim_name);
 
+    // The first shims record the executable, so that later changes to it can be found
+    if !volta_home()?.shim_record_file().exists() {
+        if let Err(error) = record_executable() {
+            debug!("Could not record the shim executable: {}", error);
+        }
+    }
+
     #[cfg(windows)]
     windows::create_git_bash_script(shim_name)?;
 
-    match symlink_file(executable, shim) {
+    match symlink_file(executable, long_path(&shim)) {
         Ok(_) => Ok(ShimResult::Created),
         Err(err) => {
             if err.kind() == io::ErrorKind::AlreadyExists {
@@ -95,7 +107,7 @@ pub fn delete(shim_name: &str) -> Fallible<ShimResult> {
     #[cfg(windows)]
     windows::delete_git_bash_script(shim_name)?;
 
-    match fs::remove_file(shim) {
+    match fs::remove_file(long_path(&shim)) {
         Ok(_) => Ok(ShimResult::Deleted),
         Err(err) => {
             if err.kind() == io::ErrorKind::NotFound {
@@ -112,6 +124,97 @@ pub fn delete(shim_name: &str) -> Fallible<ShimResult> {
     }
 }
 
+/// A shim that no longer runs the shim executable it was generated for
+pub enum ShimProblem {
+    /// The shim was replaced with something other than a link to the shim executable
+    Replaced(PathBuf),
+    /// The shim links to a different executable, e.g. after Volta was moved or partly upgraded
+    Stale(PathBuf),
+    /// The shim executable itself changed since the shims were generated
+    ExecutableChanged(PathBuf),
+}
+
+/// The shim executable that the shims were last generated for, with its digest at the time
+#[derive(Serialize, Deserialize)]
+struct ShimRecord {
+    executable: PathBuf,
+    sha256: String,
+}
+
+/// Checks that every shim still links to the shim executable, and that the executable hasn't
+/// changed since the shims were generated
+///
+/// Changes to the executable can only be found once it has been recorded, which happens when
+/// shims are created or regenerated.
+pub fn verify() -> Fallible<Vec<ShimProblem>> {
+    let executable = volta_install()?.shim_executable();
+    let shim_dir = volta_home()?.shim_dir();
+    let entries = read_dir_eager(shim_dir).with_context(|| ErrorKind::ReadDirError {
+        dir: shim_dir.to_owned(),
+    })?;
+
+    let mut problems = Vec::new();
+    for (entry, metadata) in entries {
+        let path = entry.path();
+        // The Git Bash scripts next to the shims on Windows are files, not links
+        if cfg!(windows) && path.extension().map_or(true, |ext| ext != "exe") {
+            continue;
+        }
+
+        if !metadata.file_type().is_symlink() {
+            problems.push(ShimProblem::Replaced(path));
+        } else if fs::read_link(&path).map_or(true, |target| target != executable) {
+            problems.push(ShimProblem::Stale(path));
+        }
+    }
+
+    if let Some(record) = load_record()? {
+        // An executable that can't be read can't be confirmed to be the one that was recorded
+        let changed = file_sha256(executable).map_or(true, |sha256| sha256 != record.sha256);
+        if record.executable == executable && changed {
+            problems.push(ShimProblem::ExecutableChanged(executable.to_owned()));
+        }
+    }
+
+    Ok(problems)
+}
+
+fn load_record() -> Fallible<Option<ShimRecord>> {
+    let file = volta_home()?.shim_record_file();
+    let read_error = || ErrorKind::ReadShimRecordError {
+        file: file.to_owned(),
+    };
+
+    match read_file(file).with_context(read_error)? {
+        Some(contents) => serde_json::from_str(&contents)
+            .with_context(read_error)
+            .map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Records the digest of the current shim executable
+fn record_executable() -> Fallible<()> {
+    let executable = volta_install()?.shim_executable();
+    let file = volta_home()?.shim_record_file();
+    let write_error = || ErrorKind::WriteShimRecordError {
+        file: file.to_owned(),
+    };
+
+    let record = ShimRecord {
+        executable: executable.to_owned(),
+        sha256: file_sha256(executable).with_context(write_error)?,
+    };
+    // Serializing a path and a string can't fail
+    let json = serde_json::to_string_pretty(&record).expect("Shim record is serializable");
+
+    let mut staging = create_staging_file()?;
+    staging
+        .write_all(json.as_bytes())
+        .with_context(write_error)?;
+    persist_staging_file(staging, file).with_context(write_error)
+}
+
 /// These methods are a (hacky) workaround for an issue with Git Bash on Windows
 /// When executing the shim symlink, Git Bash resolves the symlink first and then calls shim.exe directly
 /// This results in the shim being unable to determine which tool is being executed
@@ -1,23 +1,156 @@
+//! Provides the handling of Ctrl+C, so that an interrupted fetch doesn't leave partial files behind
+//!
+//! When the process is interrupted during a task that can be cancelled, like a download, the task
+//! is only told to stop: downloads fail at their next read, so the task unwinds and removes its
+//! staging files, and the session publishes its events before exiting with status 130. At any
+//! other time, or when Ctrl+C is pressed a second time, the process exits right away, after
+//! removing the paths that are registered with a `CleanupGuard`.
+
+use std::fs::{remove_dir_all, remove_file};
+use std::path::{Path, PathBuf};
 use std::process::exit;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
 
+use crate::error::{ErrorKind, Fallible};
+use lazy_static::lazy_static;
 use log::debug;
 
 static SHIM_HAS_CONTROL: AtomicBool = AtomicBool::new(false);
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+/// The number of running tasks that stop on their own when the process is interrupted
+static CANCELLABLE_TASKS: AtomicUsize = AtomicUsize::new(0);
 const INTERRUPTED_EXIT_CODE: i32 = 130;
 
+lazy_static! {
+    static ref CLEANUP_PATHS: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+}
+
 pub fn pass_control_to_shim() {
     SHIM_HAS_CONTROL.store(true, Ordering::SeqCst);
 }
 
+/// Handles interruptions as described in the module docs, unless a shim has handed control to a
+/// tool, which handles them itself
+///
+/// This is only meant for the Volta binaries, since it replaces any handler a program already has.
 pub fn setup_signal_handler() {
     let result = ctrlc::set_handler(|| {
-        if !SHIM_HAS_CONTROL.load(Ordering::SeqCst) {
-            exit(INTERRUPTED_EXIT_CODE);
+        if SHIM_HAS_CONTROL.load(Ordering::SeqCst) {
+            return;
+        }
+
+        let first = !INTERRUPTED.swap(true, Ordering::SeqCst);
+        if first && CANCELLABLE_TASKS.load(Ordering::SeqCst) > 0 {
+            return;
         }
+
+        // The paths stay locked until the process exits, so no guard can be dropped meanwhile
+        let paths = cleanup_paths();
+        for path in paths.iter() {
+            remove_partial(path);
+        }
+        exit(INTERRUPTED_EXIT_CODE);
     });
 
     if result.is_err() {
         debug!("Unable to set Ctrl+C handler, SIGINT will not be handled correctly");
     }
 }
+
+/// Whether the process has been interrupted
+pub fn interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Runs a task that stops when the process is interrupted, instead of the process exiting
+///
+/// If the task fails after an interruption, it fails with `ErrorKind::Interrupted`, since it was
+/// most likely cancelled.
+pub(crate) fn cancellable<T, F>(task: F) -> Fallible<T>
+where
+    F: FnOnce() -> Fallible<T>,
+{
+    let result = {
+        let _running = CancellableTask::start();
+        task()
+    };
+
+    match result {
+        Err(_) if interrupted() => Err(ErrorKind::Interrupted.into()),
+        result => result,
+    }
+}
+
+struct CancellableTask;
+
+impl CancellableTask {
+    fn start() -> Self {
+        CANCELLABLE_TASKS.fetch_add(1, Ordering::SeqCst);
+        CancellableTask
+    }
+}
+
+impl Drop for CancellableTask {
+    fn drop(&mut self) {
+        CANCELLABLE_TASKS.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Removes a file or directory if the process exits because it was interrupted, while the guard
+/// is alive
+///
+/// This is meant for staging files, which are otherwise removed when they are dropped, and
+/// wouldn't be if the process exits in the middle of a task.
+pub(crate) struct CleanupGuard {
+    path: PathBuf,
+}
+
+impl CleanupGuard {
+    pub(crate) fn new(path: &Path) -> Self {
+        let path = path.to_owned();
+        cleanup_paths().push(path.clone());
+        CleanupGuard { path }
+    }
+}
+
+impl Drop for CleanupGuard {
+    fn drop(&mut self) {
+        let mut paths = cleanup_paths();
+        if let Some(index) = paths.iter().position(|path| *path == self.path) {
+            paths.swap_remove(index);
+        }
+    }
+}
+
+fn cleanup_paths() -> MutexGuard<'static, Vec<PathBuf>> {
+    CLEANUP_PATHS.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn remove_partial(path: &Path) {
+    let result = if path.is_dir() {
+        remove_dir_all(path)
+    } else {
+        remove_file(path)
+    };
+
+    if let Err(error) = result {
+        debug!("Could not remove {}: {}", path.display(), error);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cleanup_paths, CleanupGuard};
+    use std::path::Path;
+
+    #[test]
+    fn cleanup_guards_unregister_when_dropped() {
+        let path = Path::new("/volta/tmp/partial-download");
+        let guard = CleanupGuard::new(path);
+        assert!(cleanup_paths().iter().any(|registered| registered == path));
+
+        drop(guard);
+        assert!(!cleanup_paths().iter().any(|registered| registered == path));
+    }
+}
@@ -1,7 +1,9 @@
 //! The view layer of Volta, with utilities for styling command-line output.
 use std::borrow::Cow;
 use std::error::Error;
+use std::sync::atomic::{AtomicBool, Ordering};
 
+use crate::progress::Progress;
 use archive::Origin;
 use cfg_if::cfg_if;
 use console::{style, StyledObject};
@@ -10,6 +12,19 @@ use indicatif::{ProgressBar, ProgressStyle};
 pub const MAX_WIDTH: usize = 100;
 const MAX_PROGRESS_WIDTH: usize = 40;
 
+/// Whether downloads are running in parallel, in which case their progress indicators would draw
+/// over each other, so they are hidden in favor of a single spinner for all of them
+static PARALLEL_DOWNLOADS: AtomicBool = AtomicBool::new(false);
+
+/// Hides (or shows again) the progress indicators of individual downloads
+pub(crate) fn set_parallel_downloads(parallel: bool) {
+    PARALLEL_DOWNLOADS.store(parallel, Ordering::SeqCst);
+}
+
+fn hide_progress() -> bool {
+    PARALLEL_DOWNLOADS.load(Ordering::SeqCst)
+}
+
 /// Generate the styled prefix for a success message
 pub fn success_prefix() -> StyledObject<&'static str> {
     style("success:").green().bold()
@@ -31,7 +46,7 @@ pub(crate) fn format_error_cause(inner: &dyn Error) -> String {
 }
 
 /// Determines the string to display based on the Origin of the operation.
-fn action_str(origin: Origin) -> &'static str {
+pub(crate) fn action_str(origin: Origin) -> &'static str {
     match origin {
         Origin::Local => "Unpacking",
         Origin::Remote => "Fetching",
@@ -51,11 +66,33 @@ pub fn text_width() -> Option<usize> {
     term_size::dimensions().map(|(w, _)| w.min(MAX_WIDTH))
 }
 
-/// Constructs a command-line progress bar based on the specified Origin enum
+/// Starts reporting the progress of a task based on the specified Origin enum
 /// (e.g., `Origin::Remote`), details string (e.g., `"v1.23.4"`), and logical
 /// length (i.e., the number of logical progress steps in the process being
 /// visualized by the progress bar).
-pub fn progress_bar(origin: Origin, details: &str, len: u64) -> ProgressBar {
+pub fn progress_bar(origin: Origin, details: &str, len: u64) -> Progress {
+    if hide_progress() {
+        return Progress::hidden();
+    }
+    Progress::bar(origin, details, len)
+}
+
+/// Starts reporting the progress of a task with the specified "message" string,
+/// for tasks whose length isn't known.
+pub fn progress_spinner<S>(message: S) -> Progress
+where
+    S: Into<Cow<'static, str>>,
+{
+    if hide_progress() {
+        return Progress::hidden();
+    }
+    let message: Cow<'static, str> = message.into();
+    Progress::spinner(&message)
+}
+
+/// Constructs a command-line progress bar, which `progress::TerminalSink` draws
+/// the progress of a task with.
+pub(crate) fn terminal_bar(origin: Origin, details: &str, len: u64) -> ProgressBar {
     let action = action_str(origin);
     let action_width = action.len() + 2; // plus 2 spaces to look nice
     let msg_width = action_width + 1 + details.len();
@@ -92,10 +129,7 @@ cfg_if! {
     if #[cfg(windows)] {
         /// Constructs a command-line progress spinner with the specified "message"
         /// string. The spinner is ticked by default every 100ms.
-        pub fn progress_spinner<S>(message: S) -> ProgressBar
-        where
-            S: Into<Cow<'static, str>>,
-        {
+        pub(crate) fn terminal_spinner(message: String) -> ProgressBar {
             let spinner = ProgressBar::new_spinner();
             // Windows CMD prompt doesn't support Unicode characters, so use a simplified spinner
             let style = ProgressStyle::default_spinner().tick_chars(r#"-\|/-"#);
@@ -109,10 +143,7 @@ cfg_if! {
     } else {
         /// Constructs a command-line progress spinner with the specified "message"
         /// string. The spinner is ticked by default every 50ms.
-        pub fn progress_spinner<S>(message: S) -> ProgressBar
-        where
-            S: Into<Cow<'static, str>>,
-        {
+        pub(crate) fn terminal_spinner(message: String) -> ProgressBar {
             // ⠋ Fetching public registry: https://nodejs.org/dist/index.json
             let spinner = ProgressBar::new_spinner();
 
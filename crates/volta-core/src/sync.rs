@@ -20,16 +20,26 @@
 //! This allows multiple code paths to request a lock and not worry about
 //! potential deadlocks, while still preventing multiple processes from making
 //! concurrent changes.
+//!
+//! A process that finds the directory locked waits for the other process to
+//! finish, for up to `VOLTA_LOCK_TIMEOUT` seconds (10 minutes by default). The
+//! process holding the lock writes its ID into the lock file, so that waiting
+//! processes can say which process they are waiting for.
 
 use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::marker::PhantomData;
 use std::ops::Drop;
+use std::process;
 use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
 
+use crate::environment;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::layout::volta_home;
 use crate::style::progress_spinner;
-use fs2::FileExt;
+use fs2::{lock_contended_error, FileExt};
 use lazy_static::lazy_static;
 use log::debug;
 
@@ -50,6 +60,14 @@ struct LockState {
 
 const LOCK_FILE: &str = "volta.lock";
 
+const LOCK_TIMEOUT_ENV_VAR: &str = "VOLTA_LOCK_TIMEOUT";
+
+/// Long enough for another process to download and unpack a large tool over a slow connection
+const DEFAULT_LOCK_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// How often a waiting process checks whether the lock was released
+const LOCK_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// An RAII implementation of a process lock on the Volta directory. A given Volta process can have
 /// multiple active locks, but only one process can have any locks at a time.
 ///
@@ -78,21 +96,17 @@ impl VoltaLock {
                 debug!("Acquiring lock on Volta directory: {}", path.display());
 
                 let file = OpenOptions::new()
+                    .read(true)
                     .write(true)
                     .create(true)
                     .open(path)
                     .with_context(|| ErrorKind::LockAcquireError)?;
-                // First we try to lock the file without blocking. If that fails, then we show a spinner
-                // and block until the lock completes.
-                if file.try_lock_exclusive().is_err() {
-                    let spinner = progress_spinner("Waiting for file lock on Volta directory");
-                    // Note: Blocks until the file can be locked
-                    let lock_result = file
-                        .lock_exclusive()
-                        .with_context(|| ErrorKind::LockAcquireError);
-                    spinner.finish_and_clear();
-                    lock_result?;
+                // First we try to lock the file without blocking. If another process holds the
+                // lock, then we show a spinner and wait for it to be released.
+                if !try_lock(&file).with_context(|| ErrorKind::LockAcquireError)? {
+                    wait_for_lock(&file)?;
                 }
+                record_holder(&file);
 
                 *state = Some(LockState { file, count: 1 });
             }
@@ -102,6 +116,97 @@ impl VoltaLock {
             _private: PhantomData,
         })
     }
+
+    /// Acquires the lock for changes that are still worth making without it, e.g. on file
+    /// systems that don't support locking
+    ///
+    /// Only running out of time while another process holds the lock is an error, since going
+    /// ahead then would risk corrupting what that process is writing.
+    pub fn acquire_if_supported() -> Fallible<Option<Self>> {
+        match VoltaLock::acquire() {
+            Ok(lock) => Ok(Some(lock)),
+            Err(error) if matches!(error.kind(), ErrorKind::LockTimeout { .. }) => Err(error),
+            Err(_) => {
+                debug!("Unable to acquire lock on Volta directory!");
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// Tries to lock the file without blocking, returning `false` if another process holds the lock
+fn try_lock(file: &File) -> io::Result<bool> {
+    match file.try_lock_exclusive() {
+        Ok(()) => Ok(true),
+        Err(error) if error.raw_os_error() == lock_contended_error().raw_os_error() => Ok(false),
+        Err(error) => Err(error),
+    }
+}
+
+/// Waits for the process holding the lock to release it, up to the lock timeout
+fn wait_for_lock(file: &File) -> Fallible<()> {
+    let timeout = lock_timeout();
+    let message = match read_holder(file) {
+        Some(pid) => format!("Waiting for another Volta process (pid {}) to finish", pid),
+        None => "Waiting for another Volta process to finish".into(),
+    };
+    debug!("{}", message);
+    let spinner = progress_spinner(message);
+
+    let start = Instant::now();
+    let result = loop {
+        match try_lock(file) {
+            Ok(true) => break Ok(()),
+            Ok(false) if start.elapsed() >= timeout => {
+                break Err(ErrorKind::LockTimeout {
+                    seconds: timeout.as_secs(),
+                }
+                .into())
+            }
+            Ok(false) => thread::sleep(LOCK_POLL_INTERVAL),
+            Err(error) => break Err(error).with_context(|| ErrorKind::LockAcquireError),
+        }
+    };
+    spinner.finish_and_clear();
+    result
+}
+
+fn lock_timeout() -> Duration {
+    match environment::var(LOCK_TIMEOUT_ENV_VAR) {
+        Ok(seconds) => match seconds.trim().parse() {
+            Ok(seconds) => Duration::from_secs(seconds),
+            Err(_) => {
+                debug!(
+                    "Ignoring invalid {} '{}', expected a number of seconds",
+                    LOCK_TIMEOUT_ENV_VAR, seconds
+                );
+                DEFAULT_LOCK_TIMEOUT
+            }
+        },
+        Err(_) => DEFAULT_LOCK_TIMEOUT,
+    }
+}
+
+/// Writes the ID of this process into the lock file, for other processes to report
+///
+/// The ID is only informational, so failing to write it isn't an error.
+fn record_holder(mut file: &File) {
+    let result = file
+        .set_len(0)
+        .and_then(|()| file.seek(SeekFrom::Start(0)))
+        .and_then(|_| write!(file, "{}", process::id()));
+    if let Err(error) = result {
+        debug!("Could not record the process holding the lock: {}", error);
+    }
+}
+
+/// Reads the ID of the process holding the lock, which some platforms don't allow while the
+/// file is locked
+fn read_holder(mut file: &File) -> Option<u32> {
+    let mut contents = String::new();
+    file.seek(SeekFrom::Start(0)).ok()?;
+    file.read_to_string(&mut contents).ok()?;
+    contents.trim().parse().ok()
 }
 
 impl Drop for VoltaLock {
@@ -127,3 +232,36 @@ impl Drop for VoltaLock {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn try_lock_reports_contention() {
+        let lock_file = NamedTempFile::new().unwrap();
+        let holder = File::open(lock_file.path()).unwrap();
+        let waiter = File::open(lock_file.path()).unwrap();
+
+        assert!(try_lock(&holder).unwrap());
+        assert!(!try_lock(&waiter).unwrap());
+
+        holder.unlock().unwrap();
+        assert!(try_lock(&waiter).unwrap());
+    }
+
+    #[test]
+    fn holder_is_readable() {
+        let lock_file = NamedTempFile::new().unwrap();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(lock_file.path())
+            .unwrap();
+
+        assert_eq!(read_holder(&file), None);
+        record_holder(&file);
+        assert_eq!(read_holder(&file), Some(process::id()));
+    }
+}
@@ -0,0 +1,55 @@
+use std::fmt::Display;
+use std::path::PathBuf;
+
+use super::{check_fetched, debug_already_fetched, remove_damaged_image, FetchStatus};
+use crate::error::Fallible;
+use crate::hook::HookConfig;
+use crate::session::Session;
+use crate::signal::cancellable;
+
+/// A runtime that is downloaded as a distro and unpacked into an image of its own, like Node,
+/// npm, or Yarn
+///
+/// Each distro only describes where its versions come from and where they go. Checking the
+/// inventory, locking the Volta directory while fetching, and replacing damaged images are shared,
+/// so that adding a distro doesn't mean repeating them.
+pub(crate) trait Distro: Display {
+    /// The hooks that can change where the distro is downloaded from
+    type Hooks: Sync;
+
+    /// Picks the hooks for this distro out of the hook configuration
+    fn hooks(config: &HookConfig) -> Option<&Self::Hooks>;
+
+    /// The directory that this version is unpacked into
+    fn image_dir(&self) -> Fallible<PathBuf>;
+
+    /// Whether this version is already in the inventory
+    fn available(&self) -> Fallible<bool>;
+
+    /// Downloads and unpacks this version, without checking whether it is already available
+    fn fetch_with(&self, hooks: Option<&Self::Hooks>) -> Fallible<()>;
+
+    /// Fetches this version if necessary, using the provided hooks rather than the session, so
+    /// that it can be called from another thread
+    fn ensure_fetched_with(&self, hooks: Option<&Self::Hooks>) -> Fallible<()> {
+        match check_fetched(|| self.available())? {
+            FetchStatus::AlreadyFetched => {
+                debug_already_fetched(self);
+                Ok(())
+            }
+            FetchStatus::FetchNeeded(_lock) => cancellable(|| self.fetch_with(hooks)),
+        }
+    }
+
+    /// Fetches this version if necessary, using the hooks of the session
+    fn ensure_fetched(&self, session: &mut Session) -> Fallible<()> {
+        self.ensure_fetched_with(Self::hooks(session.hooks()?))
+    }
+
+    /// Fetches this version for an explicit `volta fetch`, first removing an image that is
+    /// missing files so that it is downloaded again
+    fn refetch(&self, session: &mut Session) -> Fallible<()> {
+        remove_damaged_image(&self.image_dir()?)?;
+        self.ensure_fetched(session)
+    }
+}
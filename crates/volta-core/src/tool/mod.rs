@@ -1,24 +1,33 @@
 use std::fmt::{self, Display};
+use std::path::Path;
 
 use crate::error::{ErrorKind, Fallible};
+use crate::fs::remove_dir_if_exists;
+use crate::inventory::image_intact;
+use crate::project::Project;
 use crate::session::Session;
 use crate::style::{note_prefix, success_prefix, tool_version};
 use crate::sync::VoltaLock;
 use crate::version::VersionSpec;
 use log::{debug, info};
 
+mod distro;
 pub mod node;
 pub mod npm;
+mod npmrc;
 pub mod package;
 mod registry;
 mod serial;
+pub(crate) mod verify;
 pub mod yarn;
 
+pub(crate) use distro::Distro;
 pub use node::{
-    load_default_npm_version, Node, NODE_DISTRO_ARCH, NODE_DISTRO_EXTENSION, NODE_DISTRO_OS,
+    is_musl, load_default_npm_version, node_distro_arch, Node, NODE_DISTRO_ARCH,
+    NODE_DISTRO_EXTENSION, NODE_DISTRO_OS,
 };
 pub use npm::{BundledNpm, Npm};
-pub use package::{BinConfig, Package, PackageConfig, PackageManifest};
+pub use package::{BinConfig, Package, PackageConfig, PackageManifest, SourcePackage};
 pub use registry::PackageDetails;
 pub use yarn::Yarn;
 
@@ -27,6 +36,20 @@ fn debug_already_fetched<T: Display + Sized>(tool: T) {
     debug!("{} has already been fetched, skipping download", tool);
 }
 
+/// Removes an installed image that is missing files or has changed ones, so that an explicit
+/// fetch replaces it
+///
+/// Checking every file is too slow to do each time a tool runs, so this is only done when the
+/// user asks for a tool to be fetched.
+fn remove_damaged_image(image: &Path) -> Fallible<()> {
+    if image.exists() && !image_intact(image) {
+        let _lock = VoltaLock::acquire();
+        debug!("Removing damaged image at '{}'", image.display());
+        remove_dir_if_exists(image)?;
+    }
+    Ok(())
+}
+
 #[inline]
 fn info_installed<T: Display + Sized>(tool: T) {
     info!("{} installed and set {} as default", success_prefix(), tool);
@@ -69,6 +92,8 @@ pub enum Spec {
     Npm(VersionSpec),
     Yarn(VersionSpec),
     Package(String, VersionSpec),
+    /// A package identified by its location, such as a git URL or a path to a tarball
+    PackageSource(String),
 }
 
 impl Spec {
@@ -92,9 +117,81 @@ impl Spec {
                 let package = Package::new(name, version)?;
                 Ok(Box::new(package))
             }
+            Spec::PackageSource(source) => Ok(Box::new(SourcePackage::new(source)?)),
         }
     }
 
+    /// Pin a tool in the local project, keeping a version range or tag as the pinned value
+    ///
+    /// The exact version that the range or tag resolves to is recorded in the project's
+    /// `volta.lock`, so that every machine uses that version until the tool is pinned again.
+    /// Exact versions are pinned as usual.
+    pub fn pin_locked(self, session: &mut Session) -> Fallible<()> {
+        let (spec, lock): (String, fn(&mut Project, String) -> Fallible<()>) = match &self {
+            Spec::Node(version) if version.is_floating() => {
+                (version.to_string(), Project::lock_node)
+            }
+            Spec::Npm(version) if version.is_floating() => (version.to_string(), Project::lock_npm),
+            Spec::Yarn(version) if version.is_floating() => {
+                (version.to_string(), Project::lock_yarn)
+            }
+            _ => return self.resolve(session)?.pin(session),
+        };
+
+        // Stage the pin, unless the caller already is, so that the manifest is only written once,
+        // with the range or tag rather than the version it resolved to
+        let project = locked_project(session)?;
+        let staged_here = !project.pins_staged();
+        project.stage_pins();
+
+        let result = self
+            .resolve(session)
+            .and_then(|tool| tool.pin(session))
+            .and_then(|_| lock(locked_project(session)?, spec));
+
+        if !staged_here {
+            return result;
+        }
+
+        let project = locked_project(session)?;
+        match result {
+            Ok(()) => project.commit_pins(),
+            Err(error) => {
+                project.discard_pins();
+                Err(error)
+            }
+        }
+    }
+
+    /// Unpin a tool from the local project, so that the user default is used instead
+    ///
+    /// This is implemented on Spec, instead of Resolved, because the version is irrelevant when
+    /// removing a pin.
+    pub fn unpin(self, session: &mut Session) -> Fallible<()> {
+        let project = session.project_mut()?.ok_or(ErrorKind::NotInPackage)?;
+
+        let tool = match self {
+            Spec::Node(_) => {
+                project.unpin_node()?;
+                "node"
+            }
+            Spec::Npm(_) => {
+                project.unpin_npm()?;
+                "npm"
+            }
+            Spec::Yarn(_) => {
+                project.unpin_yarn()?;
+                "yarn"
+            }
+            Spec::Package(package, _) | Spec::PackageSource(package) => {
+                return Err(ErrorKind::CannotUnpinPackage { package }.into())
+            }
+        };
+
+        info!("{} unpinned {} from package.json", success_prefix(), tool);
+        Ok(())
+    }
+
     /// Uninstall a tool, removing it from the local inventory
     ///
     /// This is implemented on Spec, instead of Resolved, because there is currently no need to
@@ -113,7 +210,7 @@ impl Spec {
                 feature: "Uninstalling yarn".into(),
             }
             .into()),
-            Spec::Package(name, _) => package::uninstall(&name),
+            Spec::Package(name, _) | Spec::PackageSource(name) => package::uninstall(&name),
         }
     }
 
@@ -123,7 +220,7 @@ impl Spec {
             Spec::Node(_) => "Node",
             Spec::Npm(_) => "npm",
             Spec::Yarn(_) => "Yarn",
-            Spec::Package(name, _) => name,
+            Spec::Package(name, _) | Spec::PackageSource(name) => name,
         }
     }
 }
@@ -135,6 +232,7 @@ impl Display for Spec {
             Spec::Npm(ref version) => tool_version("npm", version),
             Spec::Yarn(ref version) => tool_version("yarn", version),
             Spec::Package(ref name, ref version) => tool_version(name, version),
+            Spec::PackageSource(ref source) => source.clone(),
         };
         f.write_str(&s)
     }
@@ -155,19 +253,15 @@ enum FetchStatus {
 /// - If `already_fetched` indicates that a fetch is needed, we acquire an exclusive lock on the Volta directory
 /// - Then, we check _again_, to confirm that no other process completed the fetch while we waited for the lock
 ///
-/// Note: If acquiring the lock fails, we proceed anyway, since the fetch is still necessary.
+/// Note: If acquiring the lock fails, we proceed anyway, since the fetch is still necessary. The
+/// exception is timing out while another process holds the lock, since that process may be
+/// writing the same files.
 fn check_fetched<F>(already_fetched: F) -> Fallible<FetchStatus>
 where
     F: Fn() -> Fallible<bool>,
 {
     if !already_fetched()? {
-        let lock = match VoltaLock::acquire() {
-            Ok(l) => Some(l),
-            Err(_) => {
-                debug!("Unable to acquire lock on Volta directory!");
-                None
-            }
-        };
+        let lock = VoltaLock::acquire_if_supported()?;
 
         if !already_fetched()? {
             Ok(FetchStatus::FetchNeeded(lock))
@@ -192,3 +286,10 @@ fn registry_fetch_error(
     let from_url = from_url.as_ref().to_string();
     || ErrorKind::RegistryFetchError { tool, from_url }
 }
+
+/// Returns the current project after a successful pin, for recording the pinned version in `volta.lock`
+fn locked_project(session: &mut Session) -> Fallible<&mut Project> {
+    session
+        .project_mut()?
+        .ok_or_else(|| ErrorKind::NotInPackage.into())
+}
@@ -0,0 +1,62 @@
+//! Provides support for building Node from source, on platforms that Node doesn't publish
+//! pre-built binaries for.
+//!
+//! The build itself is left to a command from the settings, since the right toolchain and flags
+//! differ between systems. It installs into a prefix that becomes the image directory, so the
+//! result is used like any other Node image.
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::style::{note_prefix, tool_version};
+use log::{debug, info};
+use semver::Version;
+
+/// Runs the build command in the unpacked `source` directory, installing into `prefix`
+pub(super) fn build(
+    version: &Version,
+    source: &Path,
+    prefix: &Path,
+    command: &str,
+) -> Fallible<()> {
+    let build_error = || ErrorKind::NodeBuildFailed {
+        version: version.to_string(),
+        command: command.into(),
+    };
+
+    info!(
+        "{} building {} from source, which can take a while",
+        note_prefix(),
+        tool_version("node", version)
+    );
+
+    let mut shell = shell_command(command);
+    shell
+        .current_dir(source)
+        .env("VOLTA_NODE_PREFIX", prefix)
+        .env("VOLTA_NODE_VERSION", version.to_string());
+
+    debug!("Building node with command: {:?}", shell);
+    let status = shell.status().with_context(build_error)?;
+
+    if status.success() {
+        Ok(())
+    } else {
+        Err(build_error().into())
+    }
+}
+
+#[cfg(unix)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd.exe");
+    shell.arg("/C").arg(command);
+    shell
+}
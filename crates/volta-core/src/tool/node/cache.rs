@@ -0,0 +1,113 @@
+//! Provides a cache of resolved Node versions, so that resolving a version requirement doesn't
+//! need to parse the full Node index (several megabytes of JSON) every time.
+//!
+//! Each result is recorded against a snapshot of the cached index: its URL and expiry date. A
+//! refresh of the index changes the expiry, which discards all of the previous results.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{create_staging_file, read_file};
+use crate::layout::volta_home;
+use crate::settings::Blocklist;
+use crate::version::version_serde;
+use hyperx::header::HttpDate;
+use log::debug;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Serialize, Deserialize)]
+struct ResolutionCache {
+    snapshot: String,
+    resolved: HashMap<String, Resolved>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Resolved(#[serde(with = "version_serde")] Version);
+
+/// Builds the key for a resolution, which includes everything that affects its result
+pub(super) fn query(matching: &str, include_prerelease: bool, blocklist: &Blocklist) -> String {
+    format!(
+        "{} prerelease={} blocklist={}",
+        matching,
+        include_prerelease,
+        blocklist.fingerprint()
+    )
+}
+
+/// Returns the snapshot of the cached index for the given URL, if there is an unexpired one
+pub(super) fn index_snapshot(url: &str) -> Option<String> {
+    let home = volta_home().ok()?;
+    let expiry = read_file(home.node_index_expiry_file()).ok()??;
+    let expiry_date = HttpDate::from_str(&expiry).ok()?;
+    if HttpDate::from(SystemTime::now()) >= expiry_date {
+        return None;
+    }
+
+    // The cached index starts with the URL that it was fetched from
+    let index = File::open(home.node_index_file()).ok()?;
+    let mut cached_url = String::new();
+    BufReader::new(index).read_line(&mut cached_url).ok()?;
+    if cached_url.trim_end() != url {
+        return None;
+    }
+
+    Some(format!("{} {}", url, expiry))
+}
+
+/// Looks up a previous resolution against the given index snapshot
+pub(super) fn load(snapshot: &str, query: &str) -> Option<Version> {
+    let cache = read_cache()?;
+    if cache.snapshot != snapshot {
+        return None;
+    }
+
+    cache.resolved.get(query).map(|Resolved(version)| {
+        debug!("Found cached resolution of node '{}': {}", query, version);
+        version.clone()
+    })
+}
+
+/// Records a resolution against the given index snapshot
+///
+/// The cache is only an optimization, so failing to write it isn't an error
+pub(super) fn store(snapshot: &str, query: &str, version: &Version) {
+    let mut cache = read_cache()
+        .filter(|cache| cache.snapshot == snapshot)
+        .unwrap_or_else(|| ResolutionCache {
+            snapshot: snapshot.into(),
+            resolved: HashMap::new(),
+        });
+    cache
+        .resolved
+        .insert(query.into(), Resolved(version.clone()));
+
+    if let Err(error) = write_cache(&cache) {
+        debug!("Could not write the node resolution cache: {:?}", error);
+    }
+}
+
+fn read_cache() -> Option<ResolutionCache> {
+    let contents = read_file(volta_home().ok()?.node_resolution_cache_file()).ok()??;
+    serde_json::from_str(&contents).ok()
+}
+
+fn write_cache(cache: &ResolutionCache) -> Fallible<()> {
+    let cache_file = volta_home()?.node_resolution_cache_file();
+    let write_error = || ErrorKind::WriteNodeIndexCacheError {
+        file: cache_file.to_owned(),
+    };
+
+    let contents = serde_json::to_string(cache).with_context(write_error)?;
+    let staging = create_staging_file()?;
+    let mut file: &File = staging.as_file();
+    file.write_all(contents.as_bytes())
+        .with_context(write_error)?;
+    staging.persist(cache_file).with_context(write_error)?;
+
+    Ok(())
+}
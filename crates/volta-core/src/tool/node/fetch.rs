@@ -1,15 +1,24 @@
 //! Provides fetcher for Node distributions
 
-use std::fs::{read_to_string, write, File};
+use std::fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, remove_file, write, File};
+use std::io;
 use std::path::{Path, PathBuf};
 
-use super::NodeVersion;
+use super::{build, NodeVersion};
+use crate::cert_pins;
+use crate::checksums;
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::{create_staging_dir, create_staging_file, rename};
-use crate::hook::ToolHooks;
+use crate::fs::{
+    create_staging_dir, create_staging_file, link_duplicate_files, long_path, persist_staging_file,
+    remove_dir_if_exists, rename,
+};
+use crate::hook::NodeHooks;
+use crate::http;
+use crate::inventory::{mark_image_complete, node_versions, record_node_image};
 use crate::layout::volta_home;
+use crate::signal::CleanupGuard;
 use crate::style::{progress_bar, tool_version};
-use crate::tool::{self, download_tool_error, Node};
+use crate::tool::{self, download_tool_error, verify, Node};
 use crate::version::{parse_version, VersionSpec};
 use archive::{self, Archive};
 use cfg_if::cfg_if;
@@ -17,6 +26,7 @@ use fs_utils::ensure_containing_dir_exists;
 use log::debug;
 use semver::Version;
 use serde::Deserialize;
+use tempfile::{Builder, NamedTempFile};
 
 cfg_if! {
     if #[cfg(feature = "mock-network")] {
@@ -29,13 +39,22 @@ cfg_if! {
         }
     } else {
         fn public_node_server_root() -> String {
-            "https://nodejs.org/dist".to_string()
+            if super::is_musl() {
+                "https://unofficial-builds.nodejs.org/download/release".to_string()
+            } else {
+                "https://nodejs.org/dist".to_string()
+            }
         }
     }
 }
 
 fn npm_manifest_path(version: &Version) -> PathBuf {
-    let mut manifest = PathBuf::from(Node::archive_basename(version));
+    PathBuf::from(Node::archive_basename(version)).join(bundled_npm_manifest())
+}
+
+/// The path of the bundled npm's `package.json`, relative to the root of a Node installation
+pub(super) fn bundled_npm_manifest() -> PathBuf {
+    let mut manifest = PathBuf::new();
 
     #[cfg(unix)]
     manifest.push("lib");
@@ -47,11 +66,29 @@ fn npm_manifest_path(version: &Version) -> PathBuf {
     manifest
 }
 
-pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Node>>) -> Fallible<NodeVersion> {
+pub fn fetch(
+    version: &Version,
+    hooks: Option<&NodeHooks>,
+    build_command: Option<&str>,
+) -> Fallible<NodeVersion> {
+    // Without pre-built binaries, the distro is the source, which is only useful if it can be built
+    let build_command = match (Node::builds_from_source(), build_command) {
+        (true, Some(command)) => Some(command),
+        (true, None) => {
+            return Err(ErrorKind::NodeBuildNotConfigured {
+                version: version.to_string(),
+            }
+            .into())
+        }
+        (false, _) => None,
+    };
+
     let home = volta_home()?;
     let node_dir = home.node_inventory_dir();
     let cache_file = node_dir.join(Node::archive_filename(version));
 
+    // Removes a partial download if the process exits before it is moved into the cache
+    let mut _download_cleanup = None;
     let (archive, staging) = match load_cached_distro(&cache_file) {
         Some(archive) => {
             debug!(
@@ -63,13 +100,16 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Node>>) -> Fallible<Nod
         }
         None => {
             let staging = create_staging_file()?;
+            _download_cleanup = Some(CleanupGuard::new(staging.path()));
             let remote_url = determine_remote_url(version, hooks)?;
             let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
+            let published = verify::shasums(&remote_url, &Node::archive_filename(version));
+            let archive = checksums::guard(&remote_url, archive, staging.path(), published)?;
             (archive, Some(staging))
         }
     };
 
-    let node_version = unpack_archive(archive, version)?;
+    let node_version = unpack_archive(archive, version, build_command)?;
 
     if let Some(staging_file) = staging {
         ensure_containing_dir_exists(&cache_file).with_context(|| {
@@ -77,20 +117,137 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Node>>) -> Fallible<Nod
                 path: cache_file.clone(),
             }
         })?;
-        staging_file
-            .persist(cache_file)
-            .with_context(|| ErrorKind::PersistInventoryError {
+        persist_staging_file(staging_file, &cache_file).with_context(|| {
+            ErrorKind::PersistInventoryError {
                 tool: "Node".into(),
-            })?;
+            }
+        })?;
     }
 
     Ok(node_version)
 }
 
+/// Downloads a Node distro and unpacks it into an arbitrary prefix (e.g. `/usr/local`), merging
+/// its directories with those that are already there
+///
+/// Nothing is recorded in the Volta home: the archive isn't cached and no image is created, so
+/// that this can be used on its own, e.g. when building container images. The download is still
+/// verified against the published checksums.
+pub fn fetch_to_prefix(
+    version: &Version,
+    hooks: Option<&NodeHooks>,
+    build_command: Option<&str>,
+    prefix: &Path,
+) -> Fallible<()> {
+    let build_command = match (Node::builds_from_source(), build_command) {
+        (true, Some(command)) => Some(command),
+        (true, None) => {
+            return Err(ErrorKind::NodeBuildNotConfigured {
+                version: version.to_string(),
+            }
+            .into())
+        }
+        (false, _) => None,
+    };
+
+    create_dir_all(prefix).with_context(|| ErrorKind::CreateDirError {
+        dir: prefix.to_owned(),
+    })?;
+    // Staging in the prefix itself means that the files can be moved into place, rather than
+    // copied, and that nothing is written anywhere else
+    let temp_error = || ErrorKind::CreateTempDirError {
+        in_dir: prefix.to_owned(),
+    };
+    let staging = Builder::new()
+        .prefix(".volta-")
+        .tempdir_in(prefix)
+        .with_context(temp_error)?;
+    let _cleanup = CleanupGuard::new(staging.path());
+    let download = NamedTempFile::new_in(staging.path()).with_context(temp_error)?;
+
+    let remote_url = determine_remote_url(version, hooks)?;
+    let archive = fetch_remote_distro(version, &remote_url, download.path())?;
+    let published = verify::shasums(&remote_url, &Node::archive_filename(version));
+    let archive = checksums::guard(&remote_url, archive, download.path(), published)?;
+
+    let progress = progress_bar(
+        archive.origin(),
+        &tool_version("node", &version),
+        archive
+            .uncompressed_size()
+            .unwrap_or_else(|| archive.compressed_size()),
+    );
+    archive
+        .unpack(&long_path(staging.path()), &mut |_, read| {
+            progress.inc(read as u64);
+        })
+        .with_context(|| ErrorKind::UnpackArchiveError {
+            tool: "Node".into(),
+            version: version.to_string(),
+        })?;
+    progress.finish_and_clear();
+
+    let distro = staging.path().join(Node::archive_basename(version));
+    match build_command {
+        Some(command) => build::build(version, &distro, prefix, command),
+        None => merge_into_prefix(&distro, prefix, true).with_context(|| {
+            ErrorKind::SetupToolImageError {
+                tool: "Node".into(),
+                version: version.to_string(),
+                dir: prefix.to_owned(),
+            }
+        }),
+    }
+}
+
+/// Moves the contents of a directory into another one, merging any directories that exist in
+/// both and replacing any files
+///
+/// At the top level of a Unix distro, only the directories (`bin`, `lib`, etc.) are moved, since
+/// the documents there (`README.md`, `LICENSE`, etc.) don't belong in a prefix. Windows distros
+/// have the executables at the top level, so everything is moved there.
+fn merge_into_prefix(source: &Path, dest: &Path, top_level: bool) -> io::Result<()> {
+    for entry in read_dir(source)? {
+        let entry = entry?;
+        let target = dest.join(entry.file_name());
+        let is_dir = entry.file_type()?.is_dir();
+
+        if top_level && !is_dir && cfg!(unix) {
+            continue;
+        }
+
+        if is_dir && target.is_dir() {
+            merge_into_prefix(&entry.path(), &target, false)?;
+        } else {
+            if target.is_dir() {
+                remove_dir_all(&target)?;
+            } else if target.symlink_metadata().is_ok() {
+                remove_file(&target)?;
+            }
+            rename(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Unpack the node archive into the image directory so that it is ready for use
-fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<NodeVersion> {
+///
+/// If there is a build command, the archive is the source distro, which is unpacked separately
+/// and built into the image directory.
+fn unpack_archive(
+    archive: Box<dyn Archive>,
+    version: &Version,
+    build_command: Option<&str>,
+) -> Fallible<NodeVersion> {
     let temp = create_staging_dir()?;
-    debug!("Unpacking node into '{}'", temp.path().display());
+    let _cleanup = CleanupGuard::new(temp.path());
+    let source = build_command.map(|_| create_staging_dir()).transpose()?;
+    let _source_cleanup = source
+        .as_ref()
+        .map(|source| CleanupGuard::new(source.path()));
+    let unpack_dir = source.as_ref().unwrap_or(&temp).path();
+    debug!("Unpacking node into '{}'", unpack_dir.display());
 
     let progress = progress_bar(
         archive.origin(),
@@ -101,8 +258,8 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<Node
     );
     let version_string = version.to_string();
 
-    archive
-        .unpack(temp.path(), &mut |_, read| {
+    let digest = archive
+        .unpack(&long_path(unpack_dir), &mut |_, read| {
             progress.inc(read as u64);
         })
         .with_context(|| ErrorKind::UnpackArchiveError {
@@ -110,21 +267,48 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<Node
             version: version_string.clone(),
         })?;
 
+    if let Some(command) = build_command {
+        progress.finish_and_clear();
+        build::build(
+            version,
+            &unpack_dir.join(Node::archive_basename(version)),
+            &temp.path().join(Node::archive_basename(version)),
+            command,
+        )?;
+    }
+
     // Save the npm version number in the npm version file for this distro
     let npm_package_json = temp.path().join(npm_manifest_path(version));
     let npm = Manifest::version(&npm_package_json)?;
     save_default_npm_version(version, &npm)?;
 
+    link_to_installed_image(&temp.path().join(Node::archive_basename(version)), version)?;
+
     let dest = volta_home()?.node_image_dir(&version_string);
+    mark_image_complete(
+        &temp.path().join(Node::archive_basename(version)),
+        digest,
+        Some(Node::distro_arch(version)),
+    )
+    .with_context(|| ErrorKind::SetupToolImageError {
+        tool: "Node".into(),
+        version: version_string.clone(),
+        dir: dest.clone(),
+    })?;
+
+    // An image without a marker is incomplete, and has to be replaced
+    remove_dir_if_exists(&dest)?;
     ensure_containing_dir_exists(&dest)
         .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
 
-    rename(temp.path().join(Node::archive_basename(version)), &dest).with_context(|| {
-        ErrorKind::SetupToolImageError {
-            tool: "Node".into(),
-            version: version_string,
-            dir: dest.clone(),
-        }
+    record_node_image(version, || {
+        rename(temp.path().join(Node::archive_basename(version)), &dest).with_context(|| {
+            ErrorKind::SetupToolImageError {
+                tool: "Node".into(),
+                version: version_string,
+                dir: dest.clone(),
+            }
+        })
     })?;
 
     progress.finish_and_clear();
@@ -139,6 +323,31 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<Node
     })
 }
 
+/// Links the files of a new Node image that are identical to those of an installed image
+///
+/// The installed version closest to the new one (preferring the same major version) is the most
+/// likely to share files, such as the bundled npm and the C headers.
+pub(super) fn link_to_installed_image(image: &Path, version: &Version) -> Fallible<()> {
+    let installed = node_versions()?;
+    let closest = installed
+        .iter()
+        .rev()
+        .find(|installed| installed.major == version.major)
+        .or_else(|| installed.iter().next_back());
+
+    if let Some(closest) = closest {
+        let reference = volta_home()?.node_image_dir(&closest.to_string());
+        let linked = link_duplicate_files(image, &reference);
+        debug!(
+            "Linked {} files shared with {}",
+            linked,
+            tool_version("node", closest)
+        );
+    }
+
+    Ok(())
+}
+
 /// Return the archive if it is valid. It may have been corrupted or interrupted in the middle of
 /// downloading.
 // ISSUE(#134) - verify checksum
@@ -152,10 +361,10 @@ fn load_cached_distro(file: &Path) -> Option<Box<dyn Archive>> {
 }
 
 /// Determine the remote URL to download from, using the hooks if available
-fn determine_remote_url(version: &Version, hooks: Option<&ToolHooks<Node>>) -> Fallible<String> {
+fn determine_remote_url(version: &Version, hooks: Option<&NodeHooks>) -> Fallible<String> {
     let distro_file_name = Node::archive_filename(version);
     match hooks {
-        Some(&ToolHooks {
+        Some(&NodeHooks {
             distro: Some(ref hook),
             ..
         }) => {
@@ -178,7 +387,8 @@ fn fetch_remote_distro(
     staging_path: &Path,
 ) -> Fallible<Box<dyn Archive>> {
     debug!("Downloading {} from {}", tool_version("node", version), url);
-    archive::fetch_native(url, staging_path).with_context(download_tool_error(
+    cert_pins::verify(url)?;
+    archive::fetch_native(&*http::client(), url, staging_path).with_context(download_tool_error(
         tool::Spec::Node(VersionSpec::Exact(version.clone())),
         url,
     ))
@@ -186,13 +396,13 @@ fn fetch_remote_distro(
 
 /// The portion of npm's `package.json` file that we care about
 #[derive(Deserialize)]
-struct Manifest {
+pub(super) struct Manifest {
     version: String,
 }
 
 impl Manifest {
     /// Parse the version out of a package.json file
-    fn version(path: &Path) -> Fallible<Version> {
+    pub(super) fn version(path: &Path) -> Fallible<Version> {
         let file = File::open(path).with_context(|| ErrorKind::ReadNpmManifestError)?;
         let manifest: Manifest =
             serde_json::de::from_reader(file).with_context(|| ErrorKind::ParseNpmManifestError)?;
@@ -211,7 +421,7 @@ pub fn load_default_npm_version(node: &Version) -> Fallible<Version> {
 }
 
 /// Save the default npm version to the filesystem for a given version of Node
-fn save_default_npm_version(node: &Version, npm: &Version) -> Fallible<()> {
+pub(super) fn save_default_npm_version(node: &Version, npm: &Version) -> Fallible<()> {
     let npm_version_file_path = volta_home()?.node_npm_version_file(&node.to_string());
     write(&npm_version_file_path, npm.to_string().as_bytes()).with_context(|| {
         ErrorKind::WriteDefaultNpmError {
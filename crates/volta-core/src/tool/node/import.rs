@@ -0,0 +1,122 @@
+//! Provides support for importing Node versions installed by other version managers
+//!
+//! Managers like nvm unpack the same distros that Volta downloads, so an installed version can
+//! be turned into a Volta image without downloading it again. Only the files of the distro are
+//! imported: packages that were installed globally with the other manager's npm are left behind.
+
+use std::path::{Component, Path};
+
+use super::{fetch, NodeVersion};
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{copy_tree, create_staging_dir, remove_dir_if_exists, rename};
+use crate::inventory::{mark_image_complete, node_available, record_node_image};
+use crate::layout::volta_home;
+use crate::style::tool_version;
+use fs_utils::ensure_containing_dir_exists;
+use log::debug;
+use semver::Version;
+
+/// The packages that are part of the Node distro
+const BUNDLED_PACKAGES: &[&str] = &["npm", "corepack"];
+
+/// The executables that are part of the Node distro, in its `bin` directory
+#[cfg(unix)]
+const BUNDLED_BINS: &[&str] = &["node", "npm", "npx", "corepack"];
+
+/// Imports the Node version installed in `source` into the inventory
+///
+/// With `link`, the files are hard linked rather than copied where possible. Returns `None` if
+/// the version is already installed, in which case nothing is imported.
+pub fn import_installation(
+    version: &Version,
+    source: &Path,
+    link: bool,
+) -> Fallible<Option<NodeVersion>> {
+    if node_available(version)? {
+        debug!(
+            "{} is already installed, not importing it",
+            tool_version("node", version)
+        );
+        return Ok(None);
+    }
+
+    let version_string = version.to_string();
+    let dest = volta_home()?.node_image_dir(&version_string);
+    let import_error = || ErrorKind::ImportNodeError {
+        version: version_string.clone(),
+        dir: source.to_owned(),
+    };
+
+    let temp = create_staging_dir()?;
+    let image = temp.path().join("node");
+    debug!("Importing node from '{}'", source.display());
+    copy_tree(source, &image, link, is_distro_file).with_context(import_error)?;
+
+    let npm = fetch::Manifest::version(&image.join(fetch::bundled_npm_manifest()))?;
+    fetch::save_default_npm_version(version, &npm)?;
+    fetch::link_to_installed_image(&image, version)?;
+
+    // There is no archive to record the checksum of, and the architecture of the binaries is
+    // whatever the other manager chose, so neither is recorded
+    mark_image_complete(&image, String::new(), None).with_context(import_error)?;
+
+    remove_dir_if_exists(&dest)?;
+    ensure_containing_dir_exists(&dest)
+        .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
+
+    record_node_image(version, || {
+        rename(&image, &dest).with_context(|| ErrorKind::SetupToolImageError {
+            tool: "Node".into(),
+            version: version_string.clone(),
+            dir: dest.clone(),
+        })
+    })?;
+    debug!("Imported node into '{}'", dest.display());
+
+    Ok(Some(NodeVersion {
+        runtime: version.clone(),
+        npm,
+    }))
+}
+
+/// Determines whether a path (relative to the root of an installation) is part of the distro,
+/// rather than a globally installed package or one of its executables
+fn is_distro_file(relative: &Path) -> bool {
+    let components: Vec<_> = relative
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect();
+    let components: Vec<&str> = components.iter().map(|name| &**name).collect();
+
+    match components.as_slice() {
+        #[cfg(unix)]
+        ["lib", "node_modules", package, ..] => BUNDLED_PACKAGES.contains(package),
+        #[cfg(unix)]
+        ["bin", bin] => BUNDLED_BINS.contains(bin),
+        #[cfg(windows)]
+        ["node_modules", package, ..] => BUNDLED_PACKAGES.contains(package),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_distro_file;
+    use std::path::Path;
+
+    #[test]
+    #[cfg(unix)]
+    fn skips_global_packages() {
+        assert!(is_distro_file(Path::new("")));
+        assert!(is_distro_file(Path::new("bin/node")));
+        assert!(is_distro_file(Path::new(
+            "lib/node_modules/npm/package.json"
+        )));
+        assert!(is_distro_file(Path::new("include/node/node.h")));
+        assert!(!is_distro_file(Path::new("bin/tsc")));
+        assert!(!is_distro_file(Path::new("lib/node_modules/typescript")));
+    }
+}
@@ -0,0 +1,108 @@
+//! Provides a binary copy of the parsed Node index, so that resolutions which miss the
+//! resolution cache don't need to parse the JSON index either.
+//!
+//! The copy is tagged with the `ETag` of the index it was parsed from. When the index is
+//! downloaded again without changing, the copy is kept, and when the server doesn't provide an
+//! `ETag`, no copy is made and the JSON index is used directly.
+
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+use super::metadata::{parse_entries, NodeEntry};
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{create_staging_file, read_file, remove_file_if_exists};
+use crate::layout::volta_home;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+struct ParsedIndex {
+    url: String,
+    etag: String,
+    entries: Vec<NodeEntry>,
+}
+
+/// Loads the parsed entries of the cached index, if the binary copy matches it
+///
+/// A missing, stale, or corrupt copy is ignored, so the caller falls back to the JSON index
+pub(super) fn load(url: &str) -> Option<Vec<NodeEntry>> {
+    let home = volta_home().ok()?;
+    let etag = read_file(home.node_index_etag_file()).ok()??;
+    let parsed = read_parsed()?;
+
+    if parsed.url == url && parsed.etag == etag {
+        debug!("Found parsed copy of Node version index");
+        Some(parsed.entries)
+    } else {
+        None
+    }
+}
+
+/// Brings the binary copy up to date with a newly downloaded index
+///
+/// The index is only parsed again if its `ETag` changed. A copy that can't be made is skipped,
+/// but the recorded `ETag` always matches the downloaded index, so that an outdated copy is never
+/// mistaken for the current one.
+pub(super) fn update(url: &str, etag: Option<&str>, json: &str) -> Fallible<()> {
+    let home = volta_home()?;
+    let etag_file = home.node_index_etag_file();
+    remove_file_if_exists(&etag_file)?;
+
+    let etag = match etag {
+        Some(etag) => etag,
+        None => return Ok(()),
+    };
+
+    let unchanged = read_parsed().map_or(false, |parsed| parsed.url == url && parsed.etag == etag);
+    if !unchanged {
+        let entries = match parse_entries(json) {
+            Ok(entries) => entries,
+            Err(error) => {
+                debug!(
+                    "Could not parse the Node index for its binary copy: {}",
+                    error
+                );
+                return Ok(());
+            }
+        };
+        let parsed = ParsedIndex {
+            url: url.into(),
+            etag: etag.into(),
+            entries,
+        };
+        let binary_file = home.node_index_binary_file();
+        let contents =
+            bincode::serialize(&parsed).with_context(|| ErrorKind::WriteNodeIndexCacheError {
+                file: binary_file.to_owned(),
+            })?;
+        write_staged(&contents, binary_file)?;
+    }
+
+    write_staged(etag.as_bytes(), etag_file)
+}
+
+fn read_parsed() -> Option<ParsedIndex> {
+    let binary_file = volta_home().ok()?.node_index_binary_file();
+    let file = File::open(binary_file).ok()?;
+    match bincode::deserialize_from(BufReader::new(file)) {
+        Ok(parsed) => Some(parsed),
+        Err(error) => {
+            debug!("Ignoring invalid parsed copy of Node index: {}", error);
+            None
+        }
+    }
+}
+
+fn write_staged(contents: &[u8], file: &Path) -> Fallible<()> {
+    let write_error = || ErrorKind::WriteNodeIndexCacheError {
+        file: file.to_owned(),
+    };
+
+    let staging = create_staging_file()?;
+    let mut staged: &File = staging.as_file();
+    staged.write_all(contents).with_context(write_error)?;
+    staging.persist(file).with_context(write_error)?;
+
+    Ok(())
+}
@@ -1,27 +1,37 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::io::Read;
+use std::iter::once;
 
-use super::NODE_DISTRO_IDENTIFIER;
-#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+use super::node_distro_identifier;
+#[cfg(any(
+    all(target_os = "macos", target_arch = "aarch64"),
+    all(target_os = "windows", target_arch = "aarch64")
+))]
 use super::NODE_DISTRO_IDENTIFIER_FALLBACK;
 use crate::version::{option_version_serde, version_serde};
 use semver::Version;
-use serde::{Deserialize, Deserializer};
+use serde::de::{SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
-/// The index of the public Node server.
-pub struct NodeIndex {
-    pub(super) entries: Vec<NodeEntry>,
-}
-
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct NodeEntry {
+    #[serde(with = "version_serde")]
     pub version: Version,
     pub lts: bool,
 }
 
-#[derive(Deserialize)]
-pub struct RawNodeIndex(Vec<RawNodeEntry>);
+/// A release of Node listed in the index, with the details that are shown to users
+#[derive(Clone, Debug)]
+pub struct NodeRelease {
+    pub version: Version,
+    /// The codename of the LTS line, if the release is part of one
+    pub lts: Option<String>,
+    /// The date the release was published, as `YYYY-MM-DD`
+    pub date: Option<String>,
+}
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct RawNodeEntry {
     #[serde(with = "version_serde")]
     version: Version,
@@ -29,52 +39,284 @@ pub struct RawNodeEntry {
     #[serde(with = "option_version_serde")]
     npm: Option<Version>,
     files: HashSet<String>,
-    #[serde(deserialize_with = "lts_version_serde")]
-    lts: bool,
+    #[serde(deserialize_with = "lts_name_serde")]
+    lts: Option<String>,
+    #[serde(default)]
+    date: Option<String>,
 }
 
-impl From<RawNodeIndex> for NodeIndex {
-    fn from(raw: RawNodeIndex) -> NodeIndex {
-        let entries = raw
-            .0
-            .into_iter()
-            .filter_map(|entry| {
-                #[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
-                if entry.npm.is_some() && entry.files.contains(NODE_DISTRO_IDENTIFIER) {
-                    Some(NodeEntry {
-                        version: entry.version,
-                        lts: entry.lts,
-                    })
-                } else {
-                    None
-                }
+/// A Node index in the format of the npm Registry, for mirrors that serve Node as a package
+///
+/// Each version can list the `files` and `lts` fields of the nodejs.org index, and the release
+/// date is taken from the `time` that the registry records for it.
+#[derive(Deserialize)]
+struct RawRegistryIndex {
+    versions: HashMap<String, RawRegistryVersion>,
+    #[serde(default)]
+    time: HashMap<String, String>,
+}
 
-                #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
-                if entry.npm.is_some()
-                    && (entry.files.contains(NODE_DISTRO_IDENTIFIER)
-                        || entry.files.contains(NODE_DISTRO_IDENTIFIER_FALLBACK))
-                {
-                    Some(NodeEntry {
-                        version: entry.version,
-                        lts: entry.lts,
-                    })
-                } else {
-                    None
-                }
+#[derive(Deserialize)]
+struct RawRegistryVersion {
+    #[serde(with = "version_serde")]
+    version: Version,
+    #[serde(default)]
+    files: Option<HashSet<String>>,
+    #[serde(default, deserialize_with = "lts_name_serde")]
+    lts: Option<String>,
+}
+
+impl RawNodeEntry {
+    /// Converts the raw entry, if it has a distro that can be installed on the current platform
+    fn into_entry(self) -> Option<NodeEntry> {
+        if self.installable() {
+            Some(NodeEntry {
+                version: self.version,
+                lts: self.lts.is_some(),
             })
-            .collect();
+        } else {
+            None
+        }
+    }
+
+    /// Converts the raw entry into a release, if it can be installed on the current platform
+    fn into_release(self) -> Option<NodeRelease> {
+        if self.installable() {
+            Some(NodeRelease {
+                version: self.version,
+                lts: self.lts,
+                date: self.date,
+            })
+        } else {
+            None
+        }
+    }
+
+    fn installable(&self) -> bool {
+        #[cfg(not(any(
+            all(target_os = "macos", target_arch = "aarch64"),
+            all(target_os = "windows", target_arch = "aarch64")
+        )))]
+        let available = self.files.contains(node_distro_identifier());
+
+        // Versions without native binaries can use the ones for the emulated architecture
+        #[cfg(any(
+            all(target_os = "macos", target_arch = "aarch64"),
+            all(target_os = "windows", target_arch = "aarch64")
+        ))]
+        let available = self.files.contains(node_distro_identifier())
+            || self.files.contains(NODE_DISTRO_IDENTIFIER_FALLBACK);
+
+        self.bundles_npm() && available
+    }
+
+    /// Whether the release comes with npm, which a registry-format index doesn't list, so every
+    /// release since npm was first bundled (in 0.6.3) is assumed to include it
+    fn bundles_npm(&self) -> bool {
+        self.npm.is_some() || self.version >= Version::new(0, 6, 3)
+    }
+}
+
+/// Converts a Node index in the format of the npm Registry into the format of the nodejs.org
+/// index, newest first
+///
+/// A registry doesn't have to list the distros of each version, so versions without `files` are
+/// assumed to have one for the current platform.
+pub fn convert_registry_index(json: &str) -> serde_json::Result<String> {
+    let RawRegistryIndex { versions, mut time } = serde_json::de::from_str(json)?;
+
+    let mut entries: Vec<RawNodeEntry> = versions
+        .into_iter()
+        .map(|(key, raw)| RawNodeEntry {
+            version: raw.version,
+            npm: None,
+            files: raw
+                .files
+                .unwrap_or_else(|| once(node_distro_identifier().to_string()).collect()),
+            lts: raw.lts,
+            // Registry times are full timestamps, like `2023-07-18T15:41:04.512Z`
+            date: time
+                .remove(&key)
+                .and_then(|published| published.get(..10).map(String::from)),
+        })
+        .collect();
+    entries.sort_by(|a, b| b.version.cmp(&a.version));
+
+    serde_json::to_string(&entries)
+}
+
+/// Parses every entry of a serialized Node index that can be installed on the current platform
+pub fn parse_entries(json: &str) -> serde_json::Result<Vec<NodeEntry>> {
+    let raw: Vec<RawNodeEntry> = serde_json::de::from_str(json)?;
+    Ok(raw
+        .into_iter()
+        .filter_map(RawNodeEntry::into_entry)
+        .collect())
+}
+
+/// Parses every release in a serialized Node index that can be installed on the current platform,
+/// newest first
+pub fn parse_releases(json: &str) -> serde_json::Result<Vec<NodeRelease>> {
+    let raw: Vec<RawNodeEntry> = serde_json::de::from_str(json)?;
+    Ok(raw
+        .into_iter()
+        .filter_map(RawNodeEntry::into_release)
+        .collect())
+}
 
-        NodeIndex { entries }
+/// Finds the first entry in a serialized Node index that matches the predicate
+///
+/// The index lists versions from newest to oldest, so the first match is the newest one. Entries
+/// are deserialized one at a time, and reading stops as soon as one matches, so most of the index
+/// (which is several megabytes) is never parsed or held in memory.
+pub fn find_entry<R, P>(reader: R, predicate: P) -> serde_json::Result<Option<NodeEntry>>
+where
+    R: Read,
+    P: Fn(&NodeEntry) -> bool,
+{
+    let mut found = None;
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = deserializer.deserialize_seq(FindEntry {
+        predicate,
+        found: &mut found,
+    });
+
+    // Stopping partway through the list makes the deserializer report an error, since it expects
+    // the closing bracket, so that error is only relevant if nothing was found
+    match found {
+        Some(entry) => Ok(Some(entry)),
+        None => result.map(|_| None),
+    }
+}
+
+struct FindEntry<'a, P> {
+    predicate: P,
+    found: &'a mut Option<NodeEntry>,
+}
+
+impl<'de, 'a, P> Visitor<'de> for FindEntry<'a, P>
+where
+    P: Fn(&NodeEntry) -> bool,
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of Node versions")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        while let Some(raw) = seq.next_element::<RawNodeEntry>()? {
+            if let Some(entry) = raw.into_entry() {
+                if (self.predicate)(&entry) {
+                    *self.found = Some(entry);
+                    break;
+                }
+            }
+        }
+
+        Ok(())
     }
 }
 
 #[allow(clippy::unnecessary_wraps)] // Needs to match the API expected by Serde
-fn lts_version_serde<'de, D>(deserializer: D) -> Result<bool, D::Error>
+fn lts_name_serde<'de, D>(deserializer: D) -> Result<Option<String>, D::Error>
 where
     D: Deserializer<'de>,
 {
+    // The index lists `false` for releases that aren't part of an LTS line
     match String::deserialize(deserializer) {
-        Ok(_) => Ok(true),
-        Err(_) => Ok(false),
+        Ok(name) => Ok(Some(name)),
+        Err(_) => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::node_distro_identifier;
+    use super::{convert_registry_index, find_entry, parse_releases};
+    use semver::Version;
+
+    fn entry(version: &str, lts: &str) -> String {
+        format!(
+            r#"{{"version":"v{}","npm":"8.0.0","files":["{}"],"lts":{}}}"#,
+            version,
+            node_distro_identifier(),
+            lts
+        )
+    }
+
+    #[test]
+    fn finds_newest_matching_entry() {
+        let index = format!(
+            "[{},{},{}]",
+            entry("17.0.0", "false"),
+            entry("16.13.0", r#""Gallium""#),
+            entry("14.18.1", r#""Fermium""#)
+        );
+
+        let found = find_entry(index.as_bytes(), |entry| entry.lts).unwrap();
+        assert_eq!(found.unwrap().version, Version::new(16, 13, 0));
+
+        let missing = find_entry(index.as_bytes(), |entry| entry.version.major == 12).unwrap();
+        assert!(missing.is_none());
+    }
+
+    #[test]
+    fn parses_release_details() {
+        let index = format!(
+            "[{},{}]",
+            entry("17.0.0", "false"),
+            entry("16.13.0", r#""Gallium""#).replace(r#""lts""#, r#""date":"2021-10-26","lts""#)
+        );
+
+        let releases = parse_releases(&index).unwrap();
+        assert_eq!(releases[0].version, Version::new(17, 0, 0));
+        assert_eq!(releases[0].lts, None);
+        assert_eq!(releases[0].date, None);
+        assert_eq!(releases[1].lts.as_deref(), Some("Gallium"));
+        assert_eq!(releases[1].date.as_deref(), Some("2021-10-26"));
+    }
+
+    #[test]
+    fn stops_reading_after_a_match() {
+        // Anything after the match is never parsed, so it doesn't need to be valid
+        let index = format!("[{},{{not json", entry("17.0.0", "false"));
+
+        let found = find_entry(index.as_bytes(), |_| true).unwrap();
+        assert_eq!(found.unwrap().version, Version::new(17, 0, 0));
+
+        assert!(find_entry(index.as_bytes(), |_| false).is_err());
+    }
+
+    #[test]
+    fn converts_registry_index() {
+        let registry = r#"{
+            "name": "node",
+            "versions": {
+                "16.13.0": { "version": "16.13.0", "lts": "Gallium" },
+                "17.0.0": { "version": "17.0.0", "lts": false },
+                "18.0.0": { "version": "18.0.0", "files": ["src"] }
+            },
+            "time": {
+                "16.13.0": "2021-10-26T18:32:04.512Z"
+            }
+        }"#;
+
+        let index = convert_registry_index(registry).unwrap();
+        let releases = parse_releases(&index).unwrap();
+
+        // 18.0.0 lists its distros, and none are for this platform
+        assert_eq!(releases.len(), 2);
+        assert_eq!(releases[0].version, Version::new(17, 0, 0));
+        assert_eq!(releases[0].lts, None);
+        assert_eq!(releases[1].version, Version::new(16, 13, 0));
+        assert_eq!(releases[1].lts.as_deref(), Some("Gallium"));
+        assert_eq!(releases[1].date.as_deref(), Some("2021-10-26"));
+
+        let found = find_entry(index.as_bytes(), |entry| entry.lts).unwrap();
+        assert_eq!(found.unwrap().version, Version::new(16, 13, 0));
     }
 }
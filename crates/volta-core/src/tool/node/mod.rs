@@ -1,24 +1,41 @@
 use std::fmt::{self, Display};
+use std::path::{Path, PathBuf};
 
 use super::{
     check_fetched, debug_already_fetched, info_fetched, info_installed, info_pinned,
-    info_project_version, FetchStatus, Tool,
+    info_project_version, remove_damaged_image, Distro, FetchStatus, Tool,
 };
 use crate::error::{ErrorKind, Fallible};
+use crate::hook::{HookConfig, NodeHooks};
 use crate::inventory::node_available;
+use crate::layout::volta_home;
 use crate::session::Session;
-use crate::style::{note_prefix, tool_version};
+use crate::signal::cancellable;
+use crate::style::{note_prefix, success_prefix, tool_version};
 use crate::sync::VoltaLock;
 use cfg_if::cfg_if;
+#[cfg(all(
+    target_os = "linux",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+use lazy_static::lazy_static;
 use log::info;
 use semver::Version;
 
+mod build;
+mod cache;
 mod fetch;
+mod import;
+mod index_cache;
 mod metadata;
+mod refresh;
 mod resolve;
 
 pub use fetch::load_default_npm_version;
-pub use resolve::resolve;
+pub use import::import_installation;
+pub use metadata::NodeRelease;
+pub use refresh::refresh_index_in_background;
+pub use resolve::{available_releases, cached_lts_lines, refresh_index, resolve};
 
 cfg_if! {
     if #[cfg(all(target_os = "windows", target_arch = "x86"))] {
@@ -39,6 +56,24 @@ cfg_if! {
         pub const NODE_DISTRO_EXTENSION: &str = "zip";
         /// The file identifier in the Node index `files` array
         pub const NODE_DISTRO_IDENTIFIER: &str = "win-x64-zip";
+    } else if #[cfg(all(target_os = "windows", target_arch = "aarch64"))] {
+        /// The OS component of a Node distro filename
+        pub const NODE_DISTRO_OS: &str = "win";
+        /// The architecture component of a Node distro filename
+        pub const NODE_DISTRO_ARCH: &str = "arm64";
+        /// The extension for Node distro files
+        pub const NODE_DISTRO_EXTENSION: &str = "zip";
+        /// The file identifier in the Node index `files` array
+        pub const NODE_DISTRO_IDENTIFIER: &str = "win-arm64-zip";
+
+        // NOTE: Node support for pre-built Windows on ARM binaries was added in version 19.9
+        // For versions prior to that, we need to fall back on the x64 binaries, which Windows
+        // runs under emulation
+
+        /// The fallback architecture component of a Node distro filename
+        pub const NODE_DISTRO_ARCH_FALLBACK: &str = "x64";
+        /// The fallback file identifier in the Node index `files` array
+        pub const NODE_DISTRO_IDENTIFIER_FALLBACK: &str = "win-x64-zip";
     } else if #[cfg(all(target_os = "macos", target_arch = "x86_64"))] {
         /// The OS component of a Node distro filename
         pub const NODE_DISTRO_OS: &str = "darwin";
@@ -92,11 +127,133 @@ cfg_if! {
         pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
         /// The file identifier in the Node index `files` array
         pub const NODE_DISTRO_IDENTIFIER: &str = "linux-armv7l";
+    } else if #[cfg(all(
+        target_os = "linux",
+        target_arch = "powerpc64",
+        target_endian = "little"
+    ))] {
+        /// The OS component of a Node distro filename
+        pub const NODE_DISTRO_OS: &str = "linux";
+        /// The architecture component of a Node distro filename
+        pub const NODE_DISTRO_ARCH: &str = "ppc64le";
+        /// The extension for Node distro files
+        pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
+        /// The file identifier in the Node index `files` array
+        pub const NODE_DISTRO_IDENTIFIER: &str = "linux-ppc64le";
+    } else if #[cfg(all(target_os = "linux", target_arch = "s390x"))] {
+        /// The OS component of a Node distro filename
+        pub const NODE_DISTRO_OS: &str = "linux";
+        /// The architecture component of a Node distro filename
+        pub const NODE_DISTRO_ARCH: &str = "s390x";
+        /// The extension for Node distro files
+        pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
+        /// The file identifier in the Node index `files` array
+        pub const NODE_DISTRO_IDENTIFIER: &str = "linux-s390x";
     } else {
-        compile_error!("Unsuppored operating system + architecture combination");
+        // NOTE: Node doesn't publish pre-built binaries for this platform, so it can only be
+        // installed by building the source distro, which is opt-in
+
+        /// The OS component of a Node distro filename
+        pub const NODE_DISTRO_OS: &str = std::env::consts::OS;
+        /// The architecture component of a Node distro filename
+        pub const NODE_DISTRO_ARCH: &str = std::env::consts::ARCH;
+        /// The extension for Node distro files
+        pub const NODE_DISTRO_EXTENSION: &str = "tar.gz";
+        /// The file identifier in the Node index `files` array
+        pub const NODE_DISTRO_IDENTIFIER: &str = NODE_SOURCE_IDENTIFIER;
     }
 }
 
+/// The file identifier for the source distro in the Node index `files` array
+const NODE_SOURCE_IDENTIFIER: &str = "src";
+
+cfg_if! {
+    if #[cfg(all(target_os = "linux", target_arch = "x86_64"))] {
+        /// The architecture in the file names of the musl builds of Node
+        ///
+        /// These aren't published on nodejs.org, but by the unofficial-builds project
+        pub const NODE_DISTRO_ARCH_MUSL: &str = "x64-musl";
+        /// The identifier for the musl builds in the unofficial-builds index
+        pub const NODE_DISTRO_IDENTIFIER_MUSL: &str = "linux-x64-musl";
+    } else if #[cfg(all(target_os = "linux", target_arch = "aarch64"))] {
+        /// The architecture in the file names of the musl builds of Node
+        ///
+        /// These aren't published on nodejs.org, but by the unofficial-builds project
+        pub const NODE_DISTRO_ARCH_MUSL: &str = "arm64-musl";
+        /// The identifier for the musl builds in the unofficial-builds index
+        pub const NODE_DISTRO_IDENTIFIER_MUSL: &str = "linux-arm64-musl";
+    }
+}
+
+cfg_if! {
+    if #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))] {
+        lazy_static! {
+            // The dynamic loader of musl is named for the architecture, which uses the same names
+            // as Rust, e.g. `ld-musl-aarch64.so.1`
+            static ref MUSL: bool =
+                Path::new(&format!("/lib/ld-musl-{}.so.1", std::env::consts::ARCH)).exists();
+        }
+
+        /// Determines whether the system uses musl as its C library (e.g. Alpine Linux)
+        ///
+        /// The official Linux builds of Node are linked against glibc, so they can't be loaded
+        /// on these systems, which need the musl builds instead.
+        pub fn is_musl() -> bool {
+            *MUSL
+        }
+    } else {
+        /// Determines whether the system uses musl as its C library (e.g. Alpine Linux)
+        pub fn is_musl() -> bool {
+            false
+        }
+    }
+}
+
+/// The architecture in the file names of the Node distros for the current system
+pub fn node_distro_arch() -> &'static str {
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    if is_musl() {
+        return NODE_DISTRO_ARCH_MUSL;
+    }
+
+    NODE_DISTRO_ARCH
+}
+
+/// The identifier in the Node index for the distros for the current system
+pub fn node_distro_identifier() -> &'static str {
+    #[cfg(all(
+        target_os = "linux",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    if is_musl() {
+        return NODE_DISTRO_IDENTIFIER_MUSL;
+    }
+
+    NODE_DISTRO_IDENTIFIER
+}
+
+/// Determines whether Node publishes pre-built binaries of the given version for this platform
+///
+/// Older versions use the x64 binaries instead, which run under Rosetta 2
+#[cfg(all(target_os = "macos", target_arch = "aarch64"))]
+fn has_native_distro(version: &Version) -> bool {
+    version.major >= 16
+}
+
+/// Determines whether Node publishes pre-built binaries of the given version for this platform
+///
+/// Older versions use the x64 binaries instead, which Windows runs under emulation
+#[cfg(all(target_os = "windows", target_arch = "aarch64"))]
+fn has_native_distro(version: &Version) -> bool {
+    (version.major, version.minor) >= (19, 9)
+}
+
 /// A full Node version including not just the version of Node itself
 /// but also the specific version of npm installed globally with that
 /// Node installation.
@@ -129,24 +286,45 @@ impl Node {
         Node { version }
     }
 
-    #[cfg(not(all(target_os = "macos", target_arch = "aarch64")))]
-    pub fn archive_basename(version: &Version) -> String {
-        format!("node-v{}-{}-{}", version, NODE_DISTRO_OS, NODE_DISTRO_ARCH)
+    /// The architecture of the binaries that are installed for the given version of Node
+    #[cfg(not(any(
+        all(target_os = "macos", target_arch = "aarch64"),
+        all(target_os = "windows", target_arch = "aarch64")
+    )))]
+    pub fn distro_arch(_version: &Version) -> &'static str {
+        node_distro_arch()
+    }
+
+    /// The architecture of the binaries that are installed for the given version of Node
+    ///
+    /// Versions without native binaries use the x64 ones instead, which run under emulation.
+    #[cfg(any(
+        all(target_os = "macos", target_arch = "aarch64"),
+        all(target_os = "windows", target_arch = "aarch64")
+    ))]
+    pub fn distro_arch(version: &Version) -> &'static str {
+        if has_native_distro(version) {
+            NODE_DISTRO_ARCH
+        } else {
+            NODE_DISTRO_ARCH_FALLBACK
+        }
+    }
+
+    /// Determines whether Node has to be built from source on this platform
+    pub fn builds_from_source() -> bool {
+        NODE_DISTRO_IDENTIFIER == NODE_SOURCE_IDENTIFIER
     }
 
-    #[cfg(all(target_os = "macos", target_arch = "aarch64"))]
     pub fn archive_basename(version: &Version) -> String {
-        // Note: Node began shipping pre-built binaries for Apple Silicon with Major version 16
-        // Prior to that, we need to fall back on the x64 binaries
+        if Node::builds_from_source() {
+            return format!("node-v{}", version);
+        }
+
         format!(
             "node-v{}-{}-{}",
             version,
             NODE_DISTRO_OS,
-            if version.major >= 16 {
-                NODE_DISTRO_ARCH
-            } else {
-                NODE_DISTRO_ARCH_FALLBACK
-            }
+            Node::distro_arch(version)
         )
     }
 
@@ -158,7 +336,11 @@ impl Node {
         )
     }
 
-    pub(crate) fn ensure_fetched(&self, session: &mut Session) -> Fallible<NodeVersion> {
+    /// Fetches this version if necessary, like `Distro::ensure_fetched`, returning the version of
+    /// npm bundled with it
+    ///
+    /// Unlike the other distros, Node can be built from source when the settings ask for it.
+    pub(crate) fn ensure_fetched_version(&self, session: &mut Session) -> Fallible<NodeVersion> {
         match check_fetched(|| node_available(&self.version))? {
             FetchStatus::AlreadyFetched => {
                 debug_already_fetched(self);
@@ -169,14 +351,69 @@ impl Node {
                     npm,
                 })
             }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.node()),
+            FetchStatus::FetchNeeded(_lock) => cancellable(|| {
+                fetch::fetch(
+                    &self.version,
+                    session.hooks()?.node(),
+                    session.settings()?.node_build_command(),
+                )
+            }),
         }
     }
 }
 
+impl Distro for Node {
+    type Hooks = NodeHooks;
+
+    fn hooks(config: &HookConfig) -> Option<&NodeHooks> {
+        config.node()
+    }
+
+    fn image_dir(&self) -> Fallible<PathBuf> {
+        Ok(volta_home()?.node_image_dir(&self.version.to_string()))
+    }
+
+    fn available(&self) -> Fallible<bool> {
+        node_available(&self.version)
+    }
+
+    /// Building from source isn't supported here, since this is used to fetch from another
+    /// thread, where the build output would be interleaved with the other downloads
+    fn fetch_with(&self, hooks: Option<&NodeHooks>) -> Fallible<()> {
+        fetch::fetch(&self.version, hooks, None).map(drop)
+    }
+
+    fn ensure_fetched(&self, session: &mut Session) -> Fallible<()> {
+        self.ensure_fetched_version(session).map(drop)
+    }
+}
+
+impl Node {
+    /// Installs this version into an arbitrary prefix, without any of Volta's own state
+    pub fn install_to_prefix(&self, prefix: &Path, session: &mut Session) -> Fallible<()> {
+        cancellable(|| {
+            fetch::fetch_to_prefix(
+                &self.version,
+                session.hooks()?.node(),
+                session.settings()?.node_build_command(),
+                prefix,
+            )
+        })?;
+
+        info!(
+            "{} installed {} into {}",
+            success_prefix(),
+            tool_version("node", &self.version),
+            prefix.display()
+        );
+        Ok(())
+    }
+}
+
 impl Tool for Node {
     fn fetch(self: Box<Self>, session: &mut Session) -> Fallible<()> {
-        let node_version = self.ensure_fetched(session)?;
+        remove_damaged_image(&self.image_dir()?)?;
+        let node_version = self.ensure_fetched_version(session)?;
 
         info_fetched(node_version);
         Ok(())
@@ -184,7 +421,7 @@ impl Tool for Node {
     fn install(self: Box<Self>, session: &mut Session) -> Fallible<()> {
         // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
         let _lock = VoltaLock::acquire();
-        let node_version = self.ensure_fetched(session)?;
+        let node_version = self.ensure_fetched_version(session)?;
 
         let default_toolchain = session.toolchain_mut()?;
         default_toolchain.set_active_node(&self.version)?;
@@ -215,7 +452,7 @@ impl Tool for Node {
     }
     fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
         if session.project()?.is_some() {
-            let node_version = self.ensure_fetched(session)?;
+            let node_version = self.ensure_fetched_version(session)?;
 
             // Note: We know this will succeed, since we checked above
             let project = session.project_mut()?.unwrap();
@@ -260,7 +497,11 @@ mod tests {
     fn test_node_archive_basename() {
         assert_eq!(
             Node::archive_basename(&Version::new(1, 2, 3)),
-            format!("node-v1.2.3-{}-{}", NODE_DISTRO_OS, NODE_DISTRO_ARCH)
+            format!(
+                "node-v1.2.3-{}-{}",
+                NODE_DISTRO_OS,
+                Node::distro_arch(&Version::new(1, 2, 3))
+            )
         );
     }
 
@@ -270,8 +511,68 @@ mod tests {
             Node::archive_filename(&Version::new(1, 2, 3)),
             format!(
                 "node-v1.2.3-{}-{}.{}",
-                NODE_DISTRO_OS, NODE_DISTRO_ARCH, NODE_DISTRO_EXTENSION
+                NODE_DISTRO_OS,
+                Node::distro_arch(&Version::new(1, 2, 3)),
+                NODE_DISTRO_EXTENSION
             )
         );
     }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    fn test_node_archive_filename_linux_arm64() {
+        assert_eq!(
+            Node::archive_filename(&Version::new(16, 13, 0)),
+            "node-v16.13.0-linux-arm64.tar.gz"
+        );
+        assert_eq!(NODE_DISTRO_IDENTIFIER, "linux-arm64");
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "arm"))]
+    fn test_node_archive_filename_linux_armv7() {
+        assert_eq!(
+            Node::archive_filename(&Version::new(16, 13, 0)),
+            "node-v16.13.0-linux-armv7l.tar.gz"
+        );
+        assert_eq!(NODE_DISTRO_IDENTIFIER, "linux-armv7l");
+    }
+
+    #[test]
+    #[cfg(all(
+        target_os = "linux",
+        target_arch = "powerpc64",
+        target_endian = "little"
+    ))]
+    fn test_node_archive_filename_linux_ppc64le() {
+        assert_eq!(
+            Node::archive_filename(&Version::new(16, 13, 0)),
+            "node-v16.13.0-linux-ppc64le.tar.gz"
+        );
+        assert_eq!(NODE_DISTRO_IDENTIFIER, "linux-ppc64le");
+    }
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_arch = "s390x"))]
+    fn test_node_archive_filename_linux_s390x() {
+        assert_eq!(
+            Node::archive_filename(&Version::new(16, 13, 0)),
+            "node-v16.13.0-linux-s390x.tar.gz"
+        );
+        assert_eq!(NODE_DISTRO_IDENTIFIER, "linux-s390x");
+    }
+
+    #[test]
+    #[cfg(all(target_os = "windows", target_arch = "aarch64"))]
+    fn test_node_archive_filename_windows_arm64() {
+        assert_eq!(
+            Node::archive_filename(&Version::new(20, 1, 0)),
+            "node-v20.1.0-win-arm64.zip"
+        );
+        // Versions without native binaries fall back to x64, which runs under emulation
+        assert_eq!(
+            Node::archive_filename(&Version::new(18, 16, 0)),
+            "node-v18.16.0-win-x64.zip"
+        );
+    }
 }
@@ -0,0 +1,67 @@
+//! Provides the opt-in background refresh of the cached Node index, so that commands which need
+//! to resolve a Node version rarely have to wait for the index to download.
+
+use std::fs::{metadata, File};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::SystemTime;
+
+use crate::error::Fallible;
+use crate::layout::{volta_home, volta_install};
+use crate::session::Session;
+use log::debug;
+
+/// Starts a refresh of the cached Node index in a separate process, if background refreshes are
+/// enabled with the `indexRefreshHours` setting and the index is older than that
+///
+/// The refresh is only an optimization, so any failure is ignored
+pub fn refresh_index_in_background(session: &mut Session) {
+    if let Err(error) = start_refresh(session) {
+        debug!(
+            "Could not start a background refresh of the Node index: {:?}",
+            error
+        );
+    }
+}
+
+fn start_refresh(session: &mut Session) -> Fallible<()> {
+    let window = match session.settings()?.index_refresh() {
+        Some(window) => window,
+        None => return Ok(()),
+    };
+
+    let home = volta_home()?;
+    let refresh_file = home.node_index_refresh_file();
+    // The marker records the last attempt, so a refresh that is running (or failing) isn't
+    // started again by every command
+    let last_update = [home.node_index_file(), refresh_file]
+        .iter()
+        .filter_map(|file| modified(file))
+        .max();
+    let stale = match last_update {
+        Some(time) => time.elapsed().unwrap_or_default() > window,
+        None => true,
+    };
+
+    if stale {
+        debug!("Refreshing the Node index in the background");
+        // Failing to write the marker isn't a reason to skip the refresh
+        let _ = File::create(refresh_file);
+
+        let spawned = Command::new(volta_install()?.main_executable())
+            .arg("refresh-index")
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        if let Err(error) = spawned {
+            debug!("Could not spawn the refresh process: {}", error);
+        }
+    }
+
+    Ok(())
+}
+
+fn modified(file: &Path) -> Option<SystemTime> {
+    metadata(file).and_then(|meta| meta.modified()).ok()
+}
@@ -1,27 +1,33 @@
 //! Provides resolution of Node requirements into specific versions, using the NodeJS index
 
 use std::fs::File;
-use std::io::Write;
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::str::FromStr;
 use std::time::{Duration, SystemTime};
 
 use super::super::registry_fetch_error;
-use super::metadata::{NodeEntry, NodeIndex, RawNodeIndex};
+use super::metadata::{
+    convert_registry_index, find_entry, parse_entries, parse_releases, NodeEntry, NodeRelease,
+};
+use super::{cache, index_cache};
+use crate::cert_pins;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::{create_staging_file, read_file};
-use crate::hook::ToolHooks;
+use crate::hook::{NodeHooks, NodeIndexFormat};
+use crate::http::{self, HttpResponse};
+use crate::inventory::node_versions;
 use crate::layout::volta_home;
 use crate::session::Session;
+use crate::settings::{Blocklist, ResolutionPreference};
 use crate::style::progress_spinner;
-use crate::tool::Node;
-use crate::version::{VersionSpec, VersionTag};
-use attohttpc::header::HeaderMap;
-use attohttpc::Response;
+use crate::version::{satisfies, VersionSpec, VersionTag};
+use attohttpc::header::{HeaderMap, ETAG};
 use cfg_if::cfg_if;
 use fs_utils::ensure_containing_dir_exists;
 use hyperx::header::{CacheControl, CacheDirective, Expires, HttpDate, TypedHeaders};
 use log::debug;
 use semver::{Version, VersionReq};
+use serde::de::IgnoredAny;
 
 // ISSUE (#86): Move public repository URLs to config file
 cfg_if! {
@@ -36,19 +42,46 @@ cfg_if! {
         }
     } else {
         /// Returns the URL of the index of available Node versions on the public Node server.
+        ///
+        /// On musl-based systems, this is the index of the unofficial builds, since nodejs.org
+        /// doesn't publish musl builds.
         fn public_node_version_index() -> String {
-            "https://nodejs.org/dist/index.json".to_string()
+            if super::is_musl() {
+                "https://unofficial-builds.nodejs.org/download/release/index.json".to_string()
+            } else {
+                "https://nodejs.org/dist/index.json".to_string()
+            }
         }
     }
 }
 
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Version> {
+    let include_prerelease = session.include_prerelease();
+    let settings = session.settings()?;
+    let blocklist = settings.node_blocklist();
+    let prefer_installed = settings.resolution() == ResolutionPreference::InstalledFirst;
     let hooks = session.hooks()?.node();
     match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
-        VersionSpec::Exact(version) => Ok(version),
-        VersionSpec::None | VersionSpec::Tag(VersionTag::Lts) => resolve_lts(hooks),
-        VersionSpec::Tag(VersionTag::Latest) => resolve_latest(hooks),
+        VersionSpec::Semver(requirement) => {
+            if prefer_installed {
+                if let Some(version) =
+                    find_installed_semver(&requirement, include_prerelease, blocklist)?
+                {
+                    return Ok(version);
+                }
+            }
+            resolve_semver(requirement, hooks, include_prerelease, blocklist)
+        }
+        VersionSpec::Exact(version) => {
+            blocklist.ensure_allowed(&version)?;
+            Ok(version)
+        }
+        VersionSpec::None | VersionSpec::Tag(VersionTag::Lts) => resolve_lts(hooks, blocklist),
+        VersionSpec::Tag(VersionTag::Latest) => {
+            resolve_latest(hooks, include_prerelease, blocklist)
+        }
+        VersionSpec::Tag(VersionTag::Installed) => resolve_installed(include_prerelease, blocklist),
+        VersionSpec::Tag(VersionTag::Current) => resolve_current(session),
         // Node doesn't have "tagged" versions (apart from 'latest' and 'lts'), so custom tags will always be an error
         VersionSpec::Tag(VersionTag::Custom(tag)) => {
             Err(ErrorKind::NodeVersionNotFound { matching: tag }.into())
@@ -56,12 +89,100 @@ pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Version
     }
 }
 
-fn resolve_latest(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
+/// Returns the URL of the Node index and its format, from the `node.index` hook if there is one
+fn index_source(hooks: Option<&NodeHooks>) -> Fallible<(String, NodeIndexFormat)> {
+    match hooks {
+        Some(&NodeHooks {
+            index: Some(ref hook),
+            ..
+        }) => {
+            debug!("Using node.index hook to determine node index URL");
+            // Like the Yarn index, a registry-format index is the URL of the package itself
+            let url = match hook.format {
+                NodeIndexFormat::Dist => hook.resolve("index.json")?,
+                NodeIndexFormat::Npm => hook.resolve("")?,
+            };
+            Ok((url, hook.format))
+        }
+        _ => Ok((public_node_version_index(), NodeIndexFormat::Dist)),
+    }
+}
+
+/// Downloads the Node index again, replacing the cached copy even if it hasn't expired yet
+pub fn refresh_index(session: &mut Session) -> Fallible<()> {
+    let (url, format) = index_source(session.hooks()?.node())?;
+
+    fetch_node_index(&url, format)?;
+    Ok(())
+}
+
+/// Lists the releases in the Node index that can be installed on this platform, newest first
+///
+/// The cached index is used while it is valid, so this only needs the network when it's missing
+/// or has expired.
+pub fn available_releases(session: &Session) -> Fallible<Vec<NodeRelease>> {
+    let (url, format) = index_source(session.hooks()?.node())?;
+
+    match read_cached_opt(&url)? {
+        Some(mut cached) => {
+            debug!("Found valid cache of Node version index");
+            let index_file = volta_home()?.node_index_file();
+            let mut json = String::new();
+            cached.read_to_string(&mut json).with_context(|| {
+                ErrorKind::ReadNodeIndexCacheError {
+                    file: index_file.to_owned(),
+                }
+            })?;
+            parse_releases(&json).with_context(|| ErrorKind::ParseNodeIndexCacheError)
+        }
+        None => {
+            let json = fetch_node_index(&url, format)?;
+            parse_releases(&json).with_context(|| ErrorKind::ParseNodeIndexError {
+                from_url: url.clone(),
+            })
+        }
+    }
+}
+
+/// Lists the major versions of the LTS lines in the cached Node index, newest first
+///
+/// Only the cache is read, even if it has expired, so that this is fast enough for shell
+/// completions. Without a cached index, no lines are listed.
+pub fn cached_lts_lines() -> Vec<u64> {
+    let contents = match volta_home()
+        .ok()
+        .and_then(|home| read_file(home.node_index_file()).ok())
+    {
+        Some(Some(contents)) => contents,
+        _ => return Vec::new(),
+    };
+    let (url, json) = match contents.split_once('\n') {
+        Some(parts) => parts,
+        None => return Vec::new(),
+    };
+
+    let entries = index_cache::load(url)
+        .or_else(|| parse_entries(json).ok())
+        .unwrap_or_default();
+    let mut lines = Vec::new();
+    for NodeEntry { version, lts } in entries {
+        if lts && !lines.contains(&version.major) {
+            lines.push(version.major);
+        }
+    }
+    lines
+}
+
+fn resolve_latest(
+    hooks: Option<&NodeHooks>,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     // NOTE: This assumes the registry always produces a list in sorted order
     //       from newest to oldest. This should be specified as a requirement
     //       when we document the plugin API.
     let url = match hooks {
-        Some(&ToolHooks {
+        Some(&NodeHooks {
             latest: Some(ref hook),
             ..
         }) => {
@@ -70,7 +191,16 @@ fn resolve_latest(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
         }
         _ => public_node_version_index(),
     };
-    let version_opt = match_node_version(&url, |_| true)?;
+    // A custom index may list prereleases, which should never be picked as 'latest' implicitly
+    let query = cache::query("latest", include_prerelease, blocklist);
+    let version_opt = match_node_version(
+        &url,
+        NodeIndexFormat::Dist,
+        &query,
+        |NodeEntry { version, .. }| {
+            (include_prerelease || !version.is_prerelease()) && !blocklist.skips(version)
+        },
+    )?;
 
     match version_opt {
         Some(version) => {
@@ -84,18 +214,17 @@ fn resolve_latest(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
     }
 }
 
-fn resolve_lts(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
-    let url = match hooks {
-        Some(&ToolHooks {
-            index: Some(ref hook),
-            ..
-        }) => {
-            debug!("Using node.index hook to determine node index URL");
-            hook.resolve("index.json")?
-        }
-        _ => public_node_version_index(),
-    };
-    let version_opt = match_node_version(&url, |&NodeEntry { lts, .. }| lts)?;
+fn resolve_lts(hooks: Option<&NodeHooks>, blocklist: &Blocklist) -> Fallible<Version> {
+    let (url, format) = index_source(hooks)?;
+    let query = cache::query("lts", false, blocklist);
+    let version_opt = match_node_version(
+        &url,
+        format,
+        &query,
+        |NodeEntry { version, lts, .. }| {
+            *lts && !version.is_prerelease() && !blocklist.skips(version)
+        },
+    )?;
 
     match version_opt {
         Some(version) => {
@@ -109,19 +238,77 @@ fn resolve_lts(hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
     }
 }
 
-fn resolve_semver(matching: VersionReq, hooks: Option<&ToolHooks<Node>>) -> Fallible<Version> {
-    let url = match hooks {
-        Some(&ToolHooks {
-            index: Some(ref hook),
-            ..
-        }) => {
-            debug!("Using node.index hook to determine node index URL");
-            hook.resolve("index.json")?
+/// Resolves the 'installed' alias to the newest Node version in the local inventory
+///
+/// This never touches the network, so it can be used to pin on air-gapped machines
+fn resolve_installed(include_prerelease: bool, blocklist: &Blocklist) -> Fallible<Version> {
+    let version_opt = node_versions()?.into_iter().rev().find(|version| {
+        (include_prerelease || !version.is_prerelease()) && !blocklist.skips(version)
+    });
+
+    match version_opt {
+        Some(version) => {
+            debug!("Found newest installed node version ({})", version);
+            Ok(version)
         }
-        _ => public_node_version_index(),
+        None => Err(ErrorKind::NoInstalledVersion {
+            tool: "Node".into(),
+        }
+        .into()),
+    }
+}
+
+/// Finds the newest installed node version that satisfies a requirement, if any
+///
+/// Used by the 'installed-first' resolution preference to avoid downloading a new version
+fn find_installed_semver(
+    matching: &VersionReq,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Option<Version>> {
+    let version_opt = node_versions()?.into_iter().rev().find(|version| {
+        satisfies(matching, version, include_prerelease) && !blocklist.skips(version)
+    });
+
+    if let Some(version) = &version_opt {
+        debug!(
+            "Found installed node@{} matching requirement '{}'",
+            version, matching
+        );
+    }
+    Ok(version_opt)
+}
+
+/// Resolves the 'current' alias to the Node version active in the project or default platform
+fn resolve_current(session: &Session) -> Fallible<Version> {
+    let platform = match session.project_platform()? {
+        Some(platform) => Some(platform),
+        None => session.default_platform()?,
     };
-    let version_opt =
-        match_node_version(&url, |NodeEntry { version, .. }| matching.matches(version))?;
+
+    match platform {
+        Some(platform) => {
+            debug!("Found current node version ({})", platform.node);
+            Ok(platform.node.clone())
+        }
+        None => Err(ErrorKind::NoCurrentVersion {
+            tool: "Node".into(),
+        }
+        .into()),
+    }
+}
+
+fn resolve_semver(
+    matching: VersionReq,
+    hooks: Option<&NodeHooks>,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
+    let (url, format) = index_source(hooks)?;
+    let query = cache::query(&matching.to_string(), include_prerelease, blocklist);
+    let version_opt = match_node_version(&url, format, &query, |NodeEntry { version, .. }| {
+        satisfies(&matching, version, include_prerelease) && !blocklist.skips(version)
+    })?;
 
     match version_opt {
         Some(version) => {
@@ -138,19 +325,56 @@ fn resolve_semver(matching: VersionReq, hooks: Option<&ToolHooks<Node>>) -> Fall
     }
 }
 
+/// Finds the newest version in the index that matches the predicate
+///
+/// Matches are cached (by the `query` that describes the predicate) for as long as the cached
+/// index is unchanged, so that repeated resolutions don't need to parse the index again
 fn match_node_version(
     url: &str,
+    format: NodeIndexFormat,
+    query: &str,
     predicate: impl Fn(&NodeEntry) -> bool,
 ) -> Fallible<Option<Version>> {
-    let index: NodeIndex = resolve_node_versions(url)?.into();
-    let mut entries = index.entries.into_iter();
-    Ok(entries
-        .find(predicate)
-        .map(|NodeEntry { version, .. }| version))
+    let snapshot = cache::index_snapshot(url);
+    if let Some(version) = snapshot.as_ref().and_then(|snap| cache::load(snap, query)) {
+        return Ok(Some(version));
+    }
+
+    // The parsed copy of the index is only trusted while the index itself is still valid
+    let parsed = snapshot.and_then(|_| index_cache::load(url));
+    let entry_opt = match parsed {
+        Some(entries) => entries.into_iter().find(predicate),
+        None => match read_cached_opt(url)? {
+            Some(cached) => {
+                debug!("Found valid cache of Node version index");
+                find_entry(cached, predicate)
+                    .with_context(|| ErrorKind::ParseNodeIndexCacheError)?
+            }
+            None => {
+                debug!("Node index cache was not found or was invalid");
+                let index = fetch_node_index(url, format)?;
+                find_entry(index.as_bytes(), predicate).with_context(|| {
+                    ErrorKind::ParseNodeIndexError {
+                        from_url: url.to_string(),
+                    }
+                })?
+            }
+        },
+    };
+    let version_opt = entry_opt.map(|NodeEntry { version, .. }| version);
+
+    if let (Some(version), Some(snapshot)) = (&version_opt, cache::index_snapshot(url)) {
+        cache::store(&snapshot, query, version);
+    }
+
+    Ok(version_opt)
 }
 
-/// Reads a public index from the Node cache, if it exists and hasn't expired.
-fn read_cached_opt(url: &str) -> Fallible<Option<RawNodeIndex>> {
+/// Opens the public index in the Node cache, if it exists and hasn't expired.
+///
+/// The returned reader is positioned at the start of the JSON, after the URL that the index was
+/// fetched from.
+fn read_cached_opt(url: &str) -> Fallible<Option<BufReader<File>>> {
     let expiry_file = volta_home()?.node_index_expiry_file();
     let expiry = read_file(&expiry_file).with_context(|| ErrorKind::ReadNodeIndexExpiryError {
         file: expiry_file.to_owned(),
@@ -163,16 +387,19 @@ fn read_cached_opt(url: &str) -> Fallible<Option<RawNodeIndex>> {
 
         if current_date < expiry_date {
             let index_file = volta_home()?.node_index_file();
-            let cached =
-                read_file(&index_file).with_context(|| ErrorKind::ReadNodeIndexCacheError {
-                    file: index_file.to_owned(),
-                })?;
+            let read_error = || ErrorKind::ReadNodeIndexCacheError {
+                file: index_file.to_owned(),
+            };
+            let mut cached = match File::open(&index_file) {
+                Ok(file) => BufReader::new(file),
+                Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+                Err(error) => return Err(error).with_context(read_error),
+            };
 
-            if let Some(content) = cached {
-                if let Some(json) = content.strip_prefix(url) {
-                    return serde_json::de::from_str(json)
-                        .with_context(|| ErrorKind::ParseNodeIndexCacheError);
-                }
+            let mut cached_url = String::new();
+            cached.read_line(&mut cached_url).with_context(read_error)?;
+            if cached_url.trim_end() == url {
+                return Ok(Some(cached));
             }
         }
     }
@@ -194,84 +421,85 @@ fn max_age(headers: &HeaderMap) -> u32 {
     4 * 60 * 60
 }
 
-fn resolve_node_versions(url: &str) -> Fallible<RawNodeIndex> {
-    match read_cached_opt(url)? {
-        Some(serial) => {
-            debug!("Found valid cache of Node version index");
-            Ok(serial)
-        }
-        None => {
-            debug!("Node index cache was not found or was invalid");
-            let spinner = progress_spinner(format!("Fetching public registry: {}", url));
+/// Downloads the Node index from the given URL and writes it to the cache, returning its JSON
+///
+/// A registry-format index is converted to the format of the nodejs.org index first, so the
+/// cached index is always in that format.
+fn fetch_node_index(url: &str, format: NodeIndexFormat) -> Fallible<String> {
+    cert_pins::verify(url)?;
+    let spinner = progress_spinner(format!("Fetching public registry: {}", url));
 
-            let (_, headers, response) = attohttpc::get(url)
-                .send()
-                .and_then(Response::error_for_status)
-                .with_context(registry_fetch_error("Node", url))?
-                .split();
+    let HttpResponse {
+        headers, mut body, ..
+    } = http::get(url, &[]).with_context(registry_fetch_error("Node", url))?;
+    let etag = headers
+        .get(ETAG)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_owned);
 
-            let expires = if let Ok(expires_header) = headers.decode::<Expires>() {
-                expires_header.to_string()
-            } else {
-                let expiry_date = SystemTime::now() + Duration::from_secs(max_age(&headers).into());
-                HttpDate::from(expiry_date).to_string()
-            };
+    let expires = if let Ok(expires_header) = headers.decode::<Expires>() {
+        expires_header.to_string()
+    } else {
+        let expiry_date = SystemTime::now() + Duration::from_secs(max_age(&headers).into());
+        HttpDate::from(expiry_date).to_string()
+    };
 
-            let response_text = response
-                .text()
-                .with_context(registry_fetch_error("Node", url))?;
+    let mut response_text = String::new();
+    body.read_to_string(&mut response_text)
+        .with_context(registry_fetch_error("Node", url))?;
 
-            let index: RawNodeIndex =
-                serde_json::de::from_str(&response_text).with_context(|| {
-                    ErrorKind::ParseNodeIndexError {
-                        from_url: url.to_string(),
-                    }
-                })?;
+    let parse_error = || ErrorKind::ParseNodeIndexError {
+        from_url: url.to_string(),
+    };
+    if format == NodeIndexFormat::Npm {
+        response_text = convert_registry_index(&response_text).with_context(parse_error)?;
+    }
 
-            let cached = create_staging_file()?;
+    // Only well-formed JSON is cached. The entries themselves are checked as they are searched
+    serde_json::de::from_str::<IgnoredAny>(&response_text).with_context(parse_error)?;
 
-            let mut cached_file: &File = cached.as_file();
-            writeln!(cached_file, "{}", url)
-                .and_then(|_| cached_file.write(response_text.as_bytes()))
-                .with_context(|| ErrorKind::WriteNodeIndexCacheError {
-                    file: cached.path().to_path_buf(),
-                })?;
+    index_cache::update(url, etag.as_deref(), &response_text)?;
 
-            let index_cache_file = volta_home()?.node_index_file();
-            ensure_containing_dir_exists(&index_cache_file).with_context(|| {
-                ErrorKind::ContainingDirError {
-                    path: index_cache_file.to_owned(),
-                }
-            })?;
-            cached.persist(&index_cache_file).with_context(|| {
-                ErrorKind::WriteNodeIndexCacheError {
-                    file: index_cache_file.to_owned(),
-                }
-            })?;
+    let cached = create_staging_file()?;
 
-            let expiry = create_staging_file()?;
-            let mut expiry_file: &File = expiry.as_file();
+    let mut cached_file: &File = cached.as_file();
+    writeln!(cached_file, "{}", url)
+        .and_then(|_| cached_file.write(response_text.as_bytes()))
+        .with_context(|| ErrorKind::WriteNodeIndexCacheError {
+            file: cached.path().to_path_buf(),
+        })?;
 
-            write!(expiry_file, "{}", expires).with_context(|| {
-                ErrorKind::WriteNodeIndexExpiryError {
-                    file: expiry.path().to_path_buf(),
-                }
-            })?;
+    let index_cache_file = volta_home()?.node_index_file();
+    ensure_containing_dir_exists(&index_cache_file).with_context(|| {
+        ErrorKind::ContainingDirError {
+            path: index_cache_file.to_owned(),
+        }
+    })?;
+    cached
+        .persist(&index_cache_file)
+        .with_context(|| ErrorKind::WriteNodeIndexCacheError {
+            file: index_cache_file.to_owned(),
+        })?;
 
-            let index_expiry_file = volta_home()?.node_index_expiry_file();
-            ensure_containing_dir_exists(&index_expiry_file).with_context(|| {
-                ErrorKind::ContainingDirError {
-                    path: index_expiry_file.to_owned(),
-                }
-            })?;
-            expiry.persist(&index_expiry_file).with_context(|| {
-                ErrorKind::WriteNodeIndexExpiryError {
-                    file: index_expiry_file.to_owned(),
-                }
-            })?;
+    let expiry = create_staging_file()?;
+    let mut expiry_file: &File = expiry.as_file();
+
+    write!(expiry_file, "{}", expires).with_context(|| ErrorKind::WriteNodeIndexExpiryError {
+        file: expiry.path().to_path_buf(),
+    })?;
 
-            spinner.finish_and_clear();
-            Ok(index)
+    let index_expiry_file = volta_home()?.node_index_expiry_file();
+    ensure_containing_dir_exists(&index_expiry_file).with_context(|| {
+        ErrorKind::ContainingDirError {
+            path: index_expiry_file.to_owned(),
         }
-    }
+    })?;
+    expiry
+        .persist(&index_expiry_file)
+        .with_context(|| ErrorKind::WriteNodeIndexExpiryError {
+            file: index_expiry_file.to_owned(),
+        })?;
+
+    spinner.finish_and_clear();
+    Ok(response_text)
 }
@@ -5,10 +5,18 @@ use std::path::Path;
 
 use super::super::download_tool_error;
 use super::super::registry::public_registry_package;
+use crate::cert_pins;
+use crate::checksums;
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::{create_staging_dir, create_staging_file, rename, set_executable};
+use crate::fs::{
+    create_staging_dir, create_staging_file, long_path, persist_staging_file, remove_dir_if_exists,
+    rename, set_executable,
+};
 use crate::hook::ToolHooks;
+use crate::http;
+use crate::inventory::{mark_image_complete, record_npm_image};
 use crate::layout::volta_home;
+use crate::signal::CleanupGuard;
 use crate::style::{progress_bar, tool_version};
 use crate::tool::{self, Npm};
 use crate::version::VersionSpec;
@@ -21,6 +29,8 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Npm>>) -> Fallible<()>
     let npm_dir = volta_home()?.npm_inventory_dir();
     let cache_file = npm_dir.join(Npm::archive_filename(&version.to_string()));
 
+    // Removes a partial download if the process exits before it is moved into the cache
+    let mut _download_cleanup = None;
     let (archive, staging) = match load_cached_distro(&cache_file) {
         Some(archive) => {
             debug!(
@@ -32,8 +42,10 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Npm>>) -> Fallible<()>
         }
         None => {
             let staging = create_staging_file()?;
+            _download_cleanup = Some(CleanupGuard::new(staging.path()));
             let remote_url = determine_remote_url(version, hooks)?;
             let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
+            let archive = checksums::guard(&remote_url, archive, staging.path(), None)?;
             (archive, Some(staging))
         }
     };
@@ -46,8 +58,7 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Npm>>) -> Fallible<()>
                 path: cache_file.clone(),
             }
         })?;
-        staging_file
-            .persist(cache_file)
+        persist_staging_file(staging_file, &cache_file)
             .with_context(|| ErrorKind::PersistInventoryError { tool: "npm".into() })?;
     }
 
@@ -57,6 +68,7 @@ pub fn fetch(version: &Version, hooks: Option<&ToolHooks<Npm>>) -> Fallible<()>
 /// Unpack the npm archive into the image directory so that it is ready for use
 fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()> {
     let temp = create_staging_dir()?;
+    let _cleanup = CleanupGuard::new(temp.path());
     debug!("Unpacking npm into '{}'", temp.path().display());
 
     let progress = progress_bar(
@@ -68,8 +80,8 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     );
     let version_string = version.to_string();
 
-    archive
-        .unpack(temp.path(), &mut |_, read| {
+    let digest = archive
+        .unpack(&long_path(temp.path()), &mut |_, read| {
             progress.inc(read as u64);
         })
         .with_context(|| ErrorKind::UnpackArchiveError {
@@ -88,13 +100,25 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     }
 
     let dest = volta_home()?.npm_image_dir(&version_string);
+    mark_image_complete(&temp.path().join("package"), digest, None).with_context(|| {
+        ErrorKind::SetupToolImageError {
+            tool: "npm".into(),
+            version: version_string.clone(),
+            dir: dest.clone(),
+        }
+    })?;
+
+    // An image without a marker is incomplete, and has to be replaced
+    remove_dir_if_exists(&dest)?;
     ensure_containing_dir_exists(&dest)
         .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
 
-    rename(temp.path().join("package"), &dest).with_context(|| ErrorKind::SetupToolImageError {
-        tool: "npm".into(),
-        version: version_string.clone(),
-        dir: dest.clone(),
+    record_npm_image(version, || {
+        rename(temp.path().join("package"), &dest).with_context(|| ErrorKind::SetupToolImageError {
+            tool: "npm".into(),
+            version: version_string.clone(),
+            dir: dest.clone(),
+        })
     })?;
 
     progress.finish_and_clear();
@@ -140,7 +164,8 @@ fn fetch_remote_distro(
     staging_path: &Path,
 ) -> Fallible<Box<dyn Archive>> {
     debug!("Downloading {} from {}", tool_version("npm", version), url);
-    Tarball::fetch(url, staging_path).with_context(download_tool_error(
+    cert_pins::verify(url)?;
+    Tarball::fetch(&*http::client(), url, staging_path).with_context(download_tool_error(
         tool::Spec::Npm(VersionSpec::Exact(version.clone())),
         url,
     ))
@@ -1,12 +1,12 @@
 use std::fmt::{self, Display};
+use std::path::PathBuf;
 
 use super::node::load_default_npm_version;
-use super::{
-    check_fetched, debug_already_fetched, info_fetched, info_installed, info_pinned,
-    info_project_version, FetchStatus, Tool,
-};
+use super::{info_fetched, info_installed, info_pinned, info_project_version, Distro, Tool};
 use crate::error::{Context, ErrorKind, Fallible};
+use crate::hook::{HookConfig, ToolHooks};
 use crate::inventory::npm_available;
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::{success_prefix, tool_version};
 use crate::sync::VoltaLock;
@@ -28,6 +28,14 @@ impl Npm {
         Npm { version }
     }
 
+    /// Checks whether this is the version of npm bundled with a version of Node, so that the
+    /// bundled copy can be used instead of fetching a separate one
+    ///
+    /// The bundled version is only known once that version of Node has been fetched.
+    pub(crate) fn is_bundled_with(&self, node: &Version) -> bool {
+        load_default_npm_version(node).map_or(false, |bundled| bundled == self.version)
+    }
+
     pub fn archive_basename(version: &str) -> String {
         format!("npm-{}", version)
     }
@@ -35,21 +43,31 @@ impl Npm {
     pub fn archive_filename(version: &str) -> String {
         format!("{}.tgz", Npm::archive_basename(version))
     }
+}
 
-    pub(crate) fn ensure_fetched(&self, session: &mut Session) -> Fallible<()> {
-        match check_fetched(|| npm_available(&self.version))? {
-            FetchStatus::AlreadyFetched => {
-                debug_already_fetched(self);
-                Ok(())
-            }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.npm()),
-        }
+impl Distro for Npm {
+    type Hooks = ToolHooks<Npm>;
+
+    fn hooks(config: &HookConfig) -> Option<&ToolHooks<Npm>> {
+        config.npm()
+    }
+
+    fn image_dir(&self) -> Fallible<PathBuf> {
+        Ok(volta_home()?.npm_image_dir(&self.version.to_string()))
+    }
+
+    fn available(&self) -> Fallible<bool> {
+        npm_available(&self.version)
+    }
+
+    fn fetch_with(&self, hooks: Option<&ToolHooks<Npm>>) -> Fallible<()> {
+        fetch::fetch(&self.version, hooks)
     }
 }
 
 impl Tool for Npm {
     fn fetch(self: Box<Self>, session: &mut Session) -> Fallible<()> {
-        self.ensure_fetched(session)?;
+        self.refetch(session)?;
 
         info_fetched(self);
         Ok(())
@@ -73,8 +91,14 @@ impl Tool for Npm {
         Ok(())
     }
     fn pin(self: Box<Self>, session: &mut Session) -> Fallible<()> {
-        if session.project()?.is_some() {
-            self.ensure_fetched(session)?;
+        if let Some(project) = session.project()? {
+            // The pin is kept as written, but the project will use the copy bundled with Node
+            let bundled = project
+                .platform()
+                .map_or(false, |platform| self.is_bundled_with(&platform.node));
+            if !bundled {
+                self.ensure_fetched(session)?;
+            }
 
             // Note: We know this will succeed, since we checked above
             let project = session.project_mut()?.unwrap();
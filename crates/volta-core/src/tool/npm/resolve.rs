@@ -5,22 +5,44 @@ use super::super::registry::{
 };
 use crate::error::{ErrorKind, Fallible};
 use crate::hook::ToolHooks;
+use crate::inventory::npm_versions;
 use crate::session::Session;
+use crate::settings::{Blocklist, ResolutionPreference};
 use crate::tool::Npm;
-use crate::version::{VersionSpec, VersionTag};
+use crate::version::{satisfies, VersionSpec, VersionTag};
 use log::debug;
 use semver::{Version, VersionReq};
 
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Option<Version>> {
+    let include_prerelease = session.include_prerelease();
+    let settings = session.settings()?;
+    let blocklist = settings.npm_blocklist();
+    let prefer_installed = settings.resolution() == ResolutionPreference::InstalledFirst;
     let hooks = session.hooks()?.npm();
     match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks).map(Some),
-        VersionSpec::Exact(version) => Ok(Some(version)),
+        VersionSpec::Semver(requirement) => {
+            if prefer_installed {
+                if let Some(version) =
+                    find_installed_semver(&requirement, include_prerelease, blocklist)?
+                {
+                    return Ok(Some(version));
+                }
+            }
+            resolve_semver(requirement, hooks, include_prerelease, blocklist).map(Some)
+        }
+        VersionSpec::Exact(version) => {
+            blocklist.ensure_allowed(&version)?;
+            Ok(Some(version))
+        }
         VersionSpec::None | VersionSpec::Tag(VersionTag::Latest) => {
-            resolve_tag("latest", hooks).map(Some)
+            resolve_tag("latest", hooks, blocklist).map(Some)
         }
         VersionSpec::Tag(VersionTag::Custom(tag)) if tag == "bundled" => Ok(None),
-        VersionSpec::Tag(tag) => resolve_tag(&tag.to_string(), hooks).map(Some),
+        VersionSpec::Tag(VersionTag::Installed) => {
+            resolve_installed(include_prerelease, blocklist).map(Some)
+        }
+        VersionSpec::Tag(VersionTag::Current) => resolve_current(session),
+        VersionSpec::Tag(tag) => resolve_tag(&tag.to_string(), hooks, blocklist).map(Some),
     }
 }
 
@@ -39,12 +61,18 @@ fn fetch_npm_index(hooks: Option<&ToolHooks<Npm>>) -> Fallible<(String, PackageI
     fetch_npm_registry(url, "npm")
 }
 
-fn resolve_tag(tag: &str, hooks: Option<&ToolHooks<Npm>>) -> Fallible<Version> {
+fn resolve_tag(
+    tag: &str,
+    hooks: Option<&ToolHooks<Npm>>,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     let (url, mut index) = fetch_npm_index(hooks)?;
 
     match index.tags.remove(tag) {
         Some(version) => {
             debug!("Found npm@{} matching tag '{}' from {}", version, tag, url);
+            // A tag only ever points at a single version, so there is nothing to fall back to
+            blocklist.ensure_allowed(&version)?;
             Ok(version)
         }
         None => Err(ErrorKind::NpmVersionNotFound {
@@ -54,13 +82,69 @@ fn resolve_tag(tag: &str, hooks: Option<&ToolHooks<Npm>>) -> Fallible<Version> {
     }
 }
 
-fn resolve_semver(matching: VersionReq, hooks: Option<&ToolHooks<Npm>>) -> Fallible<Version> {
+/// Resolves the 'installed' alias to the newest npm version in the local inventory
+fn resolve_installed(include_prerelease: bool, blocklist: &Blocklist) -> Fallible<Version> {
+    let version_opt = npm_versions()?.into_iter().rev().find(|version| {
+        (include_prerelease || !version.is_prerelease()) && !blocklist.skips(version)
+    });
+
+    match version_opt {
+        Some(version) => {
+            debug!("Found newest installed npm version ({})", version);
+            Ok(version)
+        }
+        None => Err(ErrorKind::NoInstalledVersion { tool: "npm".into() }.into()),
+    }
+}
+
+/// Finds the newest installed npm version that satisfies a requirement, if any
+fn find_installed_semver(
+    matching: &VersionReq,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Option<Version>> {
+    let version_opt = npm_versions()?.into_iter().rev().find(|version| {
+        satisfies(matching, version, include_prerelease) && !blocklist.skips(version)
+    });
+
+    if let Some(version) = &version_opt {
+        debug!(
+            "Found installed npm@{} matching requirement '{}'",
+            version, matching
+        );
+    }
+    Ok(version_opt)
+}
+
+/// Resolves the 'current' alias to the npm version active in the project or default platform
+///
+/// If the current platform uses the npm bundled with Node, this will resolve to `None`
+fn resolve_current(session: &Session) -> Fallible<Option<Version>> {
+    let platform = match session.project_platform()? {
+        Some(platform) => Some(platform),
+        None => session.default_platform()?,
+    };
+
+    match platform {
+        Some(platform) => Ok(platform.npm.clone()),
+        None => Err(ErrorKind::NoCurrentVersion { tool: "npm".into() }.into()),
+    }
+}
+
+fn resolve_semver(
+    matching: VersionReq,
+    hooks: Option<&ToolHooks<Npm>>,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     let (url, index) = fetch_npm_index(hooks)?;
 
     let details_opt = index
         .entries
         .into_iter()
-        .find(|PackageDetails { version, .. }| matching.matches(version));
+        .find(|PackageDetails { version, .. }| {
+            satisfies(&matching, version, include_prerelease) && !blocklist.skips(version)
+        });
 
     match details_opt {
         Some(details) => {
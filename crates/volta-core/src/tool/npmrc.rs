@@ -0,0 +1,128 @@
+//! Provides support for the scoped registry settings in npm's `.npmrc` files, so that Volta
+//! downloads scoped packages from the same registry as npm does, with the same credentials.
+//!
+//! Global package installs are run with npm itself, which reads these settings directly. This is
+//! only needed for the packages that Volta fetches on its own.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::read_to_string;
+use std::path::PathBuf;
+
+#[derive(Default)]
+pub(super) struct Npmrc {
+    /// The registry URLs, by scope (e.g. `@corp`)
+    registries: HashMap<String, String>,
+    /// The auth tokens, by the location of the registry they belong to (e.g. `//npm.corp.com/`)
+    tokens: HashMap<String, String>,
+}
+
+impl Npmrc {
+    /// Loads the user `.npmrc`, followed by the one in the current directory, which takes
+    /// precedence
+    pub(super) fn load() -> Self {
+        let user_config = env::var_os("NPM_CONFIG_USERCONFIG")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".npmrc")));
+        let project_config = env::current_dir().ok().map(|dir| dir.join(".npmrc"));
+
+        let mut npmrc = Npmrc::default();
+        for file in user_config.into_iter().chain(project_config) {
+            if let Ok(contents) = read_to_string(&file) {
+                npmrc.parse(&contents);
+            }
+        }
+        npmrc
+    }
+
+    fn parse(&mut self, contents: &str) {
+        for line in contents.lines().map(str::trim) {
+            if line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            let (key, value) = match line.split_once('=') {
+                Some((key, value)) => (key.trim(), expand_env(value.trim().trim_matches('"'))),
+                None => continue,
+            };
+
+            if let Some(scope) = key.strip_suffix(":registry") {
+                if scope.starts_with('@') {
+                    self.registries.insert(scope.into(), value);
+                }
+            } else if let Some(location) = key.strip_suffix(":_authToken") {
+                let location = format!("{}/", location.trim_end_matches('/'));
+                self.tokens.insert(location, value);
+            }
+        }
+    }
+
+    /// Returns the registry configured for the scope of a package, like `@corp/tool`
+    pub(super) fn scope_registry(&self, package: &str) -> Option<&str> {
+        let (scope, _) = package.split_once('/')?;
+        self.registries.get(scope).map(String::as_str)
+    }
+
+    /// Returns the `Authorization` header for a request to the given URL, if there is a token for
+    /// its registry
+    pub(super) fn authorization(&self, url: &str) -> Option<String> {
+        let (_, location) = url.split_once("://")?;
+        let location = format!("//{}", location);
+
+        self.tokens
+            .iter()
+            .filter(|(registry, _)| location.starts_with(registry.as_str()))
+            .max_by_key(|(registry, _)| registry.len())
+            .map(|(_, token)| format!("Bearer {}", token))
+    }
+}
+
+/// Replaces `${NAME}` references with the values of those environment variables, as npm does
+fn expand_env(value: &str) -> String {
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value;
+
+    while let Some(start) = rest.find("${") {
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+
+        expanded.push_str(&rest[..start]);
+        expanded.push_str(&env::var(&rest[start + 2..end]).unwrap_or_default());
+        rest = &rest[end + 1..];
+    }
+
+    expanded.push_str(rest);
+    expanded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Npmrc;
+
+    #[test]
+    fn reads_scoped_registries_and_tokens() {
+        let mut npmrc = Npmrc::default();
+        npmrc.parse(
+            "# company registry\n@corp:registry=https://npm.corp.com/\n//npm.corp.com/:_authToken=\"secret\"\nregistry=https://registry.npmjs.org/\n",
+        );
+
+        assert_eq!(
+            npmrc.scope_registry("@corp/tool"),
+            Some("https://npm.corp.com/")
+        );
+        assert_eq!(npmrc.scope_registry("@other/tool"), None);
+        assert_eq!(npmrc.scope_registry("tool"), None);
+
+        assert_eq!(
+            npmrc.authorization("https://npm.corp.com/@corp/tool"),
+            Some("Bearer secret".into())
+        );
+        // Tokens are only sent to the host they belong to
+        assert_eq!(
+            npmrc.authorization("https://npm.corp.com.example/@corp/tool"),
+            None
+        );
+    }
+}
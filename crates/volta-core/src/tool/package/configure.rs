@@ -2,10 +2,12 @@ use std::path::PathBuf;
 
 use super::manager::PackageManager;
 use super::metadata::{BinConfig, PackageConfig, PackageManifest};
+use super::uninstall::remove_config_and_shim;
 use crate::error::{ErrorKind, Fallible};
 use crate::layout::volta_home;
 use crate::platform::{Image, PlatformSpec};
 use crate::shim;
+use log::info;
 
 /// Read the manifest for the package being installed
 pub(super) fn parse_manifest(
@@ -19,14 +21,54 @@ pub(super) fn parse_manifest(
     PackageManifest::for_dir(package_name, &package_dir)
 }
 
+/// How the bins of a package being installed relate to the bins of other packages
+#[derive(Default)]
+pub(super) struct BinClaims {
+    /// Bins that this package gave to another package, which are left alone
+    yielded: Vec<String>,
+    /// Bins being taken over from other packages, as `(bin, package)`
+    taken: Vec<(String, String)>,
+}
+
 /// Generate configuration files and shims for the package and each of its bins
+///
+/// Any bins that were provided by a previous install of the package, but not by this one, have
+/// their shims and configuration removed
 pub(super) fn write_config_and_shims(
     name: &str,
     manifest: &PackageManifest,
     image: &Image,
     manager: PackageManager,
+    spec: Option<String>,
+    source: Option<String>,
+    claims: BinClaims,
 ) -> Fallible<()> {
-    validate_bins(name, manifest)?;
+    let home = volta_home()?;
+    let previous = PackageConfig::from_file_if_exists(home.default_package_config_file(name))?;
+    if let Some(previous) = previous {
+        for bin_name in previous
+            .bins
+            .iter()
+            .filter(|bin| !manifest.bin.contains(bin))
+        {
+            remove_config_and_shim(bin_name, name)?;
+        }
+    }
+
+    // Record the bins that were taken over in the configs of the packages that gave them up, so
+    // that reinstalling those packages later leaves the bins alone
+    for (bin_name, owner) in &claims.taken {
+        let config_file = home.default_package_config_file(owner);
+        if let Some(mut config) = PackageConfig::from_file_if_exists(&config_file)? {
+            config.bins.retain(|bin| bin != bin_name);
+            config.yielded_bins.push(bin_name.clone());
+            config.write()?;
+        }
+        info!(
+            "Executable '{}' is now provided by '{}' instead of '{}'",
+            bin_name, name, owner
+        );
+    }
 
     let platform = PlatformSpec {
         node: image.node.value.clone(),
@@ -34,8 +76,15 @@ pub(super) fn write_config_and_shims(
         yarn: image.yarn.clone().map(|s| s.value),
     };
 
+    let bins: Vec<String> = manifest
+        .bin
+        .iter()
+        .filter(|bin| !claims.yielded.contains(bin))
+        .cloned()
+        .collect();
+
     // Generate the shims and bin configs for each bin provided by the package
-    for bin_name in &manifest.bin {
+    for bin_name in &bins {
         shim::create(bin_name)?;
 
         BinConfig {
@@ -53,8 +102,11 @@ pub(super) fn write_config_and_shims(
         name: name.into(),
         version: manifest.version.clone(),
         platform,
-        bins: manifest.bin.clone(),
+        bins,
         manager,
+        spec,
+        source,
+        yielded_bins: claims.yielded,
     }
     .write()?;
 
@@ -63,15 +115,52 @@ pub(super) fn write_config_and_shims(
 
 /// Validate that we aren't attempting to install a bin that is already installed by
 /// another package.
-fn validate_bins(package_name: &str, manifest: &PackageManifest) -> Fallible<()> {
+///
+/// With `replace`, such bins are taken over from the other package instead. Otherwise, bins that
+/// this package previously gave up are skipped, so that reinstalling it is deterministic.
+///
+/// This needs to happen before the package image is persisted, so that a conflict doesn't
+/// replace an existing install of the package.
+pub(super) fn validate_bins(
+    package_name: &str,
+    manifest: &PackageManifest,
+    replace: bool,
+) -> Fallible<BinClaims> {
     let home = volta_home()?;
+    let previously_yielded =
+        PackageConfig::from_file_if_exists(home.default_package_config_file(package_name))
+            .ok()
+            .flatten()
+            .map(|config| config.yielded_bins)
+            .unwrap_or_default();
+
+    let mut claims = BinClaims::default();
     for bin_name in &manifest.bin {
         // Check for name conflicts with already-installed bins
         // Some packages may install bins with the same name
         if let Ok(config) = BinConfig::from_file(home.default_tool_bin_config(bin_name)) {
+            // On a case-insensitive file system, this can be the config of a bin whose name only
+            // differs by case, which would be overwritten along with its shim
+            if config.name != *bin_name {
+                return Err(ErrorKind::BinaryNameCaseConflict {
+                    bin_name: bin_name.clone(),
+                    existing_bin: config.name,
+                    existing_package: config.package,
+                }
+                .into());
+            }
+
             // The file exists, so there is a bin with this name
             // That is okay iff it came from the package that is currently being installed
-            if package_name != config.package {
+            if package_name == config.package {
+                continue;
+            }
+
+            if replace {
+                claims.taken.push((bin_name.clone(), config.package));
+            } else if previously_yielded.contains(bin_name) {
+                claims.yielded.push(bin_name.clone());
+            } else {
                 return Err(ErrorKind::BinaryAlreadyInstalled {
                     bin_name: bin_name.into(),
                     existing_package: config.package,
@@ -82,5 +171,5 @@ fn validate_bins(package_name: &str, manifest: &PackageManifest) -> Fallible<()>
         }
     }
 
-    Ok(())
+    Ok(claims)
 }
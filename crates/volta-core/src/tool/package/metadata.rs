@@ -28,6 +28,19 @@ pub struct PackageConfig {
     pub bins: Vec<String>,
     /// The package manager that was used to install this package
     pub manager: PackageManager,
+    /// The version requested when the package was installed with `volta install`, or `latest` if
+    /// none was
+    ///
+    /// Packages installed with `npm install --global` or by older versions of Volta don't have
+    /// one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spec: Option<String>,
+    /// The git URL or tarball that the package was installed from, if not the registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// The binaries of this package that were given to another package with `--force`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub yielded_bins: Vec<String>,
 }
 
 impl PackageConfig {
@@ -11,7 +11,8 @@ use crate::platform::{Image, PlatformSpec};
 use crate::session::Session;
 use crate::style::{success_prefix, tool_version};
 use crate::sync::VoltaLock;
-use crate::version::VersionSpec;
+use crate::version::{VersionSpec, VersionTag};
+use dunce::canonicalize;
 use fs_utils::ensure_containing_dir_exists;
 use log::info;
 use tempfile::{tempdir_in, TempDir};
@@ -20,17 +21,22 @@ mod configure;
 mod install;
 mod manager;
 mod metadata;
+mod pinned;
 mod uninstall;
+mod upgrade;
 
 pub use manager::PackageManager;
 pub use metadata::{BinConfig, PackageConfig, PackageManifest};
+pub use pinned::find_pinned_bin;
 pub use uninstall::uninstall;
+pub use upgrade::upgrade;
 
 /// The Tool implementation for installing 3rd-party global packages
 pub struct Package {
     name: String,
     version: VersionSpec,
     staging: TempDir,
+    replace_bins: bool,
 }
 
 impl Package {
@@ -41,6 +47,7 @@ impl Package {
             name,
             version,
             staging,
+            replace_bins: false,
         })
     }
 
@@ -56,13 +63,41 @@ impl Package {
         let manager = PackageManager::Npm;
         let manifest =
             configure::parse_manifest(&self.name, self.staging.path().to_owned(), manager)?;
+        let claims = configure::validate_bins(&self.name, &manifest, self.replace_bins)?;
 
         persist_install(&self.name, &self.version, self.staging.path())?;
         link_package_to_shared_dir(&self.name, manager)?;
-        configure::write_config_and_shims(&self.name, &manifest, image, manager)?;
+
+        // A package installed without a version follows the latest one when it is upgraded
+        let spec = match self.version {
+            VersionSpec::None => VersionTag::Latest.to_string(),
+            version => version.to_string(),
+        };
+        configure::write_config_and_shims(
+            &self.name,
+            &manifest,
+            image,
+            manager,
+            Some(spec),
+            None,
+            claims,
+        )?;
 
         Ok(manifest)
     }
+
+    /// Installs the package with the user default platform
+    fn install_with_default(mut self, session: &mut Session) -> Fallible<PackageManifest> {
+        self.replace_bins = session.replace_bins();
+        let default_image = session
+            .default_platform()?
+            .map(PlatformSpec::as_default)
+            .ok_or(ErrorKind::NoPlatform)?
+            .checkout(session)?;
+
+        self.run_install(&default_image)?;
+        self.complete_install(&default_image)
+    }
 }
 
 impl Tool for Package {
@@ -76,46 +111,132 @@ impl Tool for Package {
     fn install(self: Box<Self>, session: &mut Session) -> Fallible<()> {
         let _lock = VoltaLock::acquire();
 
+        let manifest = self.install_with_default(session)?;
+        info_package_installed(manifest);
+
+        Ok(())
+    }
+
+    fn pin(self: Box<Self>, _session: &mut Session) -> Fallible<()> {
+        Err(ErrorKind::CannotPinPackage { package: self.name }.into())
+    }
+}
+
+impl Display for Package {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.version {
+            VersionSpec::None => f.write_str(&self.name),
+            _ => f.write_str(&tool_version(&self.name, &self.version)),
+        }
+    }
+}
+
+/// The Tool implementation for installing packages from a location instead of the registry, such
+/// as a git URL or a local tarball
+///
+/// The name of the package isn't known until it has been installed, so it is read from the
+/// staging directory afterwards. The location is recorded so that the package can be upgraded.
+pub struct SourcePackage {
+    source: String,
+    staging: TempDir,
+}
+
+impl SourcePackage {
+    pub fn new(source: String) -> Fallible<Self> {
+        // Local paths are made absolute, so that upgrades work from any directory
+        let source = match canonicalize(&source) {
+            Ok(path) => path.to_string_lossy().into_owned(),
+            Err(_) => source,
+        };
+        let staging = setup_staging_directory(PackageManager::Npm, NeedsScope::No)?;
+
+        Ok(SourcePackage { source, staging })
+    }
+
+    /// Installs the package with the user default platform
+    fn install_with_default(self, session: &mut Session) -> Fallible<PackageManifest> {
         let default_image = session
             .default_platform()?
             .map(PlatformSpec::as_default)
             .ok_or(ErrorKind::NoPlatform)?
             .checkout(session)?;
 
-        self.run_install(&default_image)?;
-        let manifest = self.complete_install(&default_image)?;
+        install::run_global_install(
+            self.source.clone(),
+            self.staging.path().to_owned(),
+            &default_image,
+        )?;
 
-        let bins = manifest.bin.join(", ");
+        let manager = PackageManager::Npm;
+        let name = manager
+            .get_installed_package(self.staging.path().to_owned())
+            .ok_or(ErrorKind::InstalledPackageNameError)?;
+        let manifest = configure::parse_manifest(&name, self.staging.path().to_owned(), manager)?;
+        let claims = configure::validate_bins(&name, &manifest, session.replace_bins())?;
 
-        if bins.is_empty() {
-            info!(
-                "{} installed {}",
-                success_prefix(),
-                tool_version(manifest.name, manifest.version)
-            );
-        } else {
-            info!(
-                "{} installed {} with executables: {}",
-                success_prefix(),
-                tool_version(manifest.name, manifest.version),
-                bins
-            );
+        persist_install(&name, &manifest.version, self.staging.path())?;
+        link_package_to_shared_dir(&name, manager)?;
+        configure::write_config_and_shims(
+            &name,
+            &manifest,
+            &default_image,
+            manager,
+            None,
+            Some(self.source),
+            claims,
+        )?;
+
+        Ok(manifest)
+    }
+}
+
+impl Tool for SourcePackage {
+    fn fetch(self: Box<Self>, _session: &mut Session) -> Fallible<()> {
+        Err(ErrorKind::CannotFetchPackage {
+            package: self.source,
         }
+        .into())
+    }
+
+    fn install(self: Box<Self>, session: &mut Session) -> Fallible<()> {
+        let _lock = VoltaLock::acquire();
+
+        let manifest = self.install_with_default(session)?;
+        info_package_installed(manifest);
 
         Ok(())
     }
 
     fn pin(self: Box<Self>, _session: &mut Session) -> Fallible<()> {
-        Err(ErrorKind::CannotPinPackage { package: self.name }.into())
+        Err(ErrorKind::CannotPinPackage {
+            package: self.source,
+        }
+        .into())
     }
 }
 
-impl Display for Package {
+impl Display for SourcePackage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.version {
-            VersionSpec::None => f.write_str(&self.name),
-            _ => f.write_str(&tool_version(&self.name, &self.version)),
-        }
+        f.write_str(&self.source)
+    }
+}
+
+fn info_package_installed(manifest: PackageManifest) {
+    let bins = manifest.bin.join(", ");
+
+    if bins.is_empty() {
+        info!(
+            "{} installed {}",
+            success_prefix(),
+            tool_version(manifest.name, manifest.version)
+        );
+    } else {
+        info!(
+            "{} installed {} with executables: {}",
+            success_prefix(),
+            tool_version(manifest.name, manifest.version),
+            bins
+        );
     }
 }
 
@@ -169,10 +290,11 @@ impl DirectInstall {
             .or_else(|| manager.get_installed_package(staging.path().to_owned()))
             .ok_or(ErrorKind::InstalledPackageNameError)?;
         let manifest = configure::parse_manifest(&name, staging.path().to_owned(), manager)?;
+        let claims = configure::validate_bins(&name, &manifest, false)?;
 
         persist_install(&name, &manifest.version, staging.path())?;
         link_package_to_shared_dir(&name, manager)?;
-        configure::write_config_and_shims(&name, &manifest, image, manager)
+        configure::write_config_and_shims(&name, &manifest, image, manager, None, None, claims)
     }
 }
 
@@ -225,9 +347,85 @@ impl InPlaceUpgrade {
 
     pub fn complete_upgrade(self, image: &Image) -> Fallible<()> {
         let manifest = configure::parse_manifest(&self.package, self.directory, self.manager)?;
+        let claims = configure::validate_bins(&self.package, &manifest, false)?;
+
+        // Keep the version or location that was requested when the package was first installed
+        let (spec, source) = match PackageConfig::from_file_if_exists(
+            volta_home()?.default_package_config_file(&self.package),
+        )? {
+            Some(config) => (config.spec, config.source),
+            None => (None, None),
+        };
 
         link_package_to_shared_dir(&self.package, self.manager)?;
-        configure::write_config_and_shims(&self.package, &manifest, image, self.manager)
+        configure::write_config_and_shims(
+            &self.package,
+            &manifest,
+            image,
+            self.manager,
+            spec,
+            source,
+            claims,
+        )
+    }
+}
+
+/// Helper struct for one-off package executions through `volta x`
+///
+/// The package is installed into a temporary directory, without writing any configuration or
+/// shims, and the install is removed when the struct is dropped.
+pub struct EphemeralInstall {
+    package: String,
+    staging: TempDir,
+    manifest: PackageManifest,
+}
+
+impl EphemeralInstall {
+    /// Installs the package (which may include a version, or be any location supported by `npm
+    /// install`) with the provided platform
+    pub fn new(package: String, platform_image: &Image) -> Fallible<Self> {
+        let manager = PackageManager::Npm;
+        let staging = setup_staging_directory(manager, NeedsScope::No)?;
+        install::run_global_install(package.clone(), staging.path().to_owned(), platform_image)?;
+
+        let name = manager
+            .get_installed_package(staging.path().to_owned())
+            .ok_or(ErrorKind::InstalledPackageNameError)?;
+        let manifest = configure::parse_manifest(&name, staging.path().to_owned(), manager)?;
+
+        Ok(EphemeralInstall {
+            package,
+            staging,
+            manifest,
+        })
+    }
+
+    /// Determines the path to the executable to run
+    ///
+    /// If no executable is requested, the package's only executable is used, or the one with the
+    /// same name as the package (without the scope), like `npx` does.
+    pub fn bin_path(&self, bin: Option<&str>) -> Fallible<PathBuf> {
+        let bins = &self.manifest.bin;
+        let unscoped = self.manifest.name.rsplit('/').next().unwrap_or_default();
+
+        let found = match bin {
+            Some(bin) => bins.iter().find(|name| *name == bin),
+            None if bins.len() == 1 => bins.first(),
+            None => bins.iter().find(|name| *name == unscoped),
+        };
+
+        match found {
+            Some(name) => {
+                let mut path = PackageManager::Npm.binary_dir(self.staging.path().to_owned());
+                path.push(name);
+                Ok(path)
+            }
+            None => Err(ErrorKind::ExecutableNotFound {
+                package: self.package.clone(),
+                bins: bins.clone(),
+            }
+            .into()),
+        }
     }
 }
 
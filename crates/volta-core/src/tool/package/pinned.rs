@@ -0,0 +1,147 @@
+//! Provides support for the package tools that a project pins in `volta.tools`
+//!
+//! Pinned tools are installed separately from the user's default packages, with one image for
+//! each version, so that different projects can use different versions of the same tool. They
+//! are installed on demand, the first time one of their binaries is needed.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use super::{configure, Package, PackageManager};
+use crate::error::{Context, ErrorKind, Fallible};
+use crate::fs::{link_duplicate_files, read_dir_eager, remove_dir_if_exists, rename};
+use crate::layout::volta_home;
+use crate::platform::{Image, Platform};
+use crate::session::Session;
+use crate::shim;
+use crate::style::{success_prefix, tool_version};
+use crate::sync::VoltaLock;
+use crate::version::VersionSpec;
+use fs_utils::ensure_containing_dir_exists;
+use log::{debug, info};
+use semver::Version;
+
+/// Finds the path to the named binary among the package tools pinned by the current project
+///
+/// Any pinned tool that doesn't have a matching version installed yet is installed with the
+/// current platform before checking its binaries.
+pub fn find_pinned_bin(bin: &str, session: &mut Session) -> Fallible<Option<PathBuf>> {
+    let tools: BTreeMap<String, String> = match session.project()? {
+        Some(project) => project.pinned_tools().clone(),
+        None => return Ok(None),
+    };
+
+    let mut missing = Vec::new();
+    for (name, version) in tools {
+        let version: VersionSpec = version.parse()?;
+        match installed_version(&name, &version)? {
+            Some(installed) => {
+                let image_dir = volta_home()?.project_tool_image_dir(&name, &installed);
+                if let Some(path) = find_bin(&name, image_dir, bin)? {
+                    return Ok(Some(path));
+                }
+            }
+            None => missing.push((name, version)),
+        }
+    }
+
+    if missing.is_empty() {
+        return Ok(None);
+    }
+
+    let image = Platform::current(session)?
+        .ok_or(ErrorKind::NoPlatform)?
+        .checkout(session)?;
+    let _lock = VoltaLock::acquire();
+
+    for (name, version) in missing {
+        let image_dir = install(name.clone(), version, &image)?;
+        if let Some(path) = find_bin(&name, image_dir, bin)? {
+            return Ok(Some(path));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Determines the newest installed version of a pinned tool that satisfies the requested version
+///
+/// Tags can't be matched without contacting the registry, so any installed version is used
+fn installed_version(name: &str, requested: &VersionSpec) -> Fallible<Option<String>> {
+    let tool_dir = volta_home()?.project_tool_image_root_dir().join(name);
+    let entries = match read_dir_eager(&tool_dir) {
+        Ok(entries) => entries,
+        Err(_) => return Ok(None),
+    };
+
+    let newest = entries
+        .filter(|(_, metadata)| metadata.is_dir())
+        .filter_map(|(entry, _)| Version::parse(&entry.file_name().to_string_lossy()).ok())
+        .filter(|version| match requested {
+            VersionSpec::Exact(exact) => version == exact,
+            VersionSpec::Semver(req) => req.matches(version),
+            VersionSpec::None | VersionSpec::Tag(_) => true,
+        })
+        .max();
+
+    Ok(newest.map(|version| version.to_string()))
+}
+
+/// Returns the path to the binary in the image of a pinned tool, if the tool provides it
+fn find_bin(name: &str, image_dir: PathBuf, bin: &str) -> Fallible<Option<PathBuf>> {
+    let manager = PackageManager::Npm;
+    let manifest = configure::parse_manifest(name, image_dir.clone(), manager)?;
+
+    if manifest.bin.iter().any(|name| name == bin) {
+        let mut path = manager.binary_dir(image_dir);
+        path.push(bin);
+        Ok(Some(path))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Installs a pinned tool into its own image directory, returning that directory
+///
+/// Shims are created for the tool's binaries so that they are available on the PATH, however
+/// no default binary configuration is written, so outside of the project they aren't handled
+/// by Volta unless they are also installed as a default package.
+fn install(name: String, version: VersionSpec, image: &Image) -> Fallible<PathBuf> {
+    let package = Package::new(name, version)?;
+    package.run_install(image)?;
+
+    let manager = PackageManager::Npm;
+    let manifest =
+        configure::parse_manifest(&package.name, package.staging.path().to_owned(), manager)?;
+    let image_dir =
+        volta_home()?.project_tool_image_dir(&package.name, &manifest.version.to_string());
+
+    // Versions of a tool usually share most of their dependencies, so those files are linked
+    if let Some(installed) = installed_version(&package.name, &VersionSpec::None)? {
+        let reference = volta_home()?.project_tool_image_dir(&package.name, &installed);
+        link_duplicate_files(package.staging.path(), &reference);
+    }
+
+    remove_dir_if_exists(&image_dir)?;
+    ensure_containing_dir_exists(&image_dir).with_context(|| ErrorKind::ContainingDirError {
+        path: image_dir.clone(),
+    })?;
+    rename(package.staging.path(), &image_dir).with_context(|| ErrorKind::SetupToolImageError {
+        tool: package.name.clone(),
+        version: manifest.version.to_string(),
+        dir: image_dir.clone(),
+    })?;
+    debug!("Installed project tool into '{}'", image_dir.display());
+
+    for bin in &manifest.bin {
+        shim::create(bin)?;
+    }
+
+    info!(
+        "{} installed project tool {}",
+        success_prefix(),
+        tool_version(&manifest.name, &manifest.version)
+    );
+
+    Ok(image_dir)
+}
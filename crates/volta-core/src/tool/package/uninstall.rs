@@ -7,7 +7,7 @@ use crate::layout::volta_home;
 use crate::shim;
 use crate::style::success_prefix;
 use crate::sync::VoltaLock;
-use log::{info, warn};
+use log::{debug, info, warn};
 
 /// Uninstalls the specified package.
 ///
@@ -63,9 +63,22 @@ pub fn uninstall(name: &str) -> Fallible<()> {
 }
 
 /// Remove a shim and its associated configuration file
-fn remove_config_and_shim(bin_name: &str, pkg_name: &str) -> Fallible<()> {
-    shim::delete(bin_name)?;
+///
+/// If the binary has since been claimed by a different package, it is left in place, so that
+/// uninstalling one package never breaks the executables of another.
+pub(super) fn remove_config_and_shim(bin_name: &str, pkg_name: &str) -> Fallible<()> {
     let config_file = volta_home()?.default_tool_bin_config(bin_name);
+    if let Ok(Some(config)) = BinConfig::from_file_if_exists(&config_file) {
+        if config.package != pkg_name {
+            debug!(
+                "Keeping executable '{}', which is now provided by '{}'",
+                bin_name, config.package
+            );
+            return Ok(());
+        }
+    }
+
+    shim::delete(bin_name)?;
     remove_file_if_exists(config_file)?;
     info!(
         "Removed executable '{}' installed by '{}'",
@@ -0,0 +1,55 @@
+use super::metadata::PackageConfig;
+use super::{Package, SourcePackage};
+use crate::error::{ErrorKind, Fallible};
+use crate::layout::volta_home;
+use crate::session::Session;
+use crate::style::{success_prefix, tool_version};
+use crate::sync::VoltaLock;
+use crate::version::VersionSpec;
+use log::info;
+
+/// Upgrades the specified package.
+///
+/// The package is resolved again using the version or location that was requested when it was
+/// installed, and reinstalled with the current user default platform. Packages that don't record
+/// what was requested, e.g. because they were installed by an older version of Volta, are
+/// reinstalled at the version they already have.
+pub fn upgrade(name: &str, session: &mut Session) -> Fallible<()> {
+    // Acquire a lock on the Volta directory, if possible, to prevent concurrent changes
+    let _lock = VoltaLock::acquire();
+
+    let config =
+        PackageConfig::from_file_if_exists(volta_home()?.default_package_config_file(name))?
+            .ok_or_else(|| ErrorKind::PackageNotInstalled {
+                package: name.into(),
+            })?;
+
+    let manifest = match &config.source {
+        Some(source) => SourcePackage::new(source.clone())?.install_with_default(session)?,
+        None => {
+            let version = match &config.spec {
+                Some(spec) => spec.parse()?,
+                None => VersionSpec::Exact(config.version.clone()),
+            };
+            Package::new(name.into(), version)?.install_with_default(session)?
+        }
+    };
+
+    if manifest.version == config.version {
+        info!(
+            "{} reinstalled {} with the default platform",
+            success_prefix(),
+            tool_version(manifest.name, manifest.version)
+        );
+    } else {
+        info!(
+            "{} upgraded {} from {} to {}",
+            success_prefix(),
+            manifest.name,
+            config.version,
+            manifest.version
+        );
+    }
+
+    Ok(())
+}
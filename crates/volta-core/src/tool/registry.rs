@@ -1,13 +1,15 @@
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
+use super::npmrc::Npmrc;
 use super::registry_fetch_error;
+use crate::cert_pins;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::fs::read_dir_eager;
+use crate::http;
 use crate::style::progress_spinner;
 use crate::version::{hashmap_version_serde, version_serde};
-use attohttpc::header::ACCEPT;
-use attohttpc::Response;
+use attohttpc::header::{ACCEPT, AUTHORIZATION};
 use cfg_if::cfg_if;
 use semver::Version;
 use serde::Deserialize;
@@ -34,15 +36,31 @@ cfg_if! {
     }
 }
 
+/// Returns the URL of the index for a package, using the registry configured for its scope in
+/// `.npmrc`, if there is one
+pub fn registry_index(package: &str) -> String {
+    match Npmrc::load().scope_registry(package) {
+        Some(registry) => format!("{}/{}", registry.trim_end_matches('/'), package),
+        None => public_registry_index(package),
+    }
+}
+
+/// Returns the `Authorization` header to send with a request to a registry, if `.npmrc` has a
+/// token for it
+pub fn registry_authorization(url: &str) -> Option<String> {
+    Npmrc::load().authorization(url)
+}
+
 // fetch a registry that returns info in Npm format
 pub fn fetch_npm_registry(url: String, name: &str) -> Fallible<(String, PackageIndex)> {
+    cert_pins::verify(&url)?;
     let spinner = progress_spinner(format!("Fetching npm registry: {}", url));
-    let metadata: RawPackageMetadata = attohttpc::get(&url)
-        .header(ACCEPT, NPM_ABBREVIATED_ACCEPT_HEADER)
-        .send()
-        .and_then(Response::error_for_status)
-        .and_then(Response::json)
-        .with_context(registry_fetch_error(name, &url))?;
+    let mut headers = vec![(ACCEPT, NPM_ABBREVIATED_ACCEPT_HEADER.to_string())];
+    if let Some(authorization) = registry_authorization(&url) {
+        headers.push((AUTHORIZATION, authorization));
+    }
+    let metadata: RawPackageMetadata =
+        http::get_json(&url, &headers).with_context(registry_fetch_error(name, &url))?;
 
     spinner.finish_and_clear();
     Ok((url, metadata.into()))
@@ -59,11 +77,11 @@ pub fn public_registry_package(package: &str, version: &str) -> String {
 
 // need package and filename for namespaced tools like @yarnpkg/cli-dist, which is located at
 //   https://registry.npmjs.org/@yarnpkg/cli-dist/-/cli-dist-1.2.3.tgz
-pub fn scoped_public_registry_package(scope: &str, package: &str, version: &str) -> String {
+// unless `.npmrc` configures a different registry for the scope
+pub fn scoped_registry_package(scope: &str, package: &str, version: &str) -> String {
     format!(
-        "{}/{}/-/{}-{}.tgz",
-        public_registry_index(scope),
-        package,
+        "{}/-/{}-{}.tgz",
+        registry_index(&format!("{}/{}", scope, package)),
         package,
         version
     )
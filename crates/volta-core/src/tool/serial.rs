@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use std::path::Path;
 
 use super::Spec;
 use crate::error::{ErrorKind, Fallible};
@@ -26,7 +27,13 @@ impl Spec {
     }
 
     /// Try to parse a tool and version from a string like `<tool>[@<version>].
+    ///
+    /// Git URLs and paths to tarballs are parsed as the location of a package.
     pub fn try_from_str(tool_spec: &str) -> Fallible<Self> {
+        if is_package_source(tool_spec) {
+            return Ok(Spec::PackageSource(tool_spec.into()));
+        }
+
         let captures =
             TOOL_SPEC_PATTERN
                 .captures(tool_spec)
@@ -145,14 +152,29 @@ impl Spec {
             (Spec::Yarn(_), Spec::Yarn(_)) => Ordering::Equal,
             (Spec::Yarn(_), _) => Ordering::Less,
             (_, Spec::Yarn(_)) => Ordering::Greater,
-            (Spec::Package(_, _), Spec::Package(_, _)) => Ordering::Equal,
+            // Packages keep their order, whether they are named or given by location
+            _ => Ordering::Equal,
         }
     }
 }
 
+/// Determine if a given string is the location of a package, rather than a name.
+///
+/// This covers the forms that npm accepts for git repositories and tarballs: URLs, `github:`
+/// shorthands, and local paths.
+fn is_package_source(value: &str) -> bool {
+    const PREFIXES: [&str; 6] = ["git+", "github:", "file:", "./", "../", ".\\"];
+
+    value.contains("://")
+        || PREFIXES.iter().any(|prefix| value.starts_with(prefix))
+        || Path::new(value).is_absolute()
+        || value.ends_with(".tgz")
+        || value.ends_with(".tar.gz")
+}
+
 /// Determine if a given string is "version-like".
 ///
-/// This means it is either 'latest', 'lts', a Version, or a Version Range.
+/// This means it is either 'latest', 'lts', 'installed', 'current', a Version, or a Version Range.
 fn is_version_like(value: &str) -> bool {
     matches!(
         value.parse(),
@@ -160,6 +182,8 @@ fn is_version_like(value: &str) -> bool {
             | Ok(VersionSpec::Semver(_))
             | Ok(VersionSpec::Tag(VersionTag::Latest))
             | Ok(VersionSpec::Tag(VersionTag::Lts))
+            | Ok(VersionSpec::Tag(VersionTag::Installed))
+            | Ok(VersionSpec::Tag(VersionTag::Current))
     )
 }
 
@@ -223,6 +247,19 @@ mod tests {
             );
         }
 
+        #[test]
+        fn parses_local_aliases() {
+            assert_eq!(
+                Spec::try_from_str("node@installed").expect("succeeds"),
+                Spec::Node(VersionSpec::Tag(VersionTag::Installed))
+            );
+
+            assert_eq!(
+                Spec::try_from_str("yarn@current").expect("succeeds"),
+                Spec::Yarn(VersionSpec::Tag(VersionTag::Current))
+            );
+        }
+
         #[test]
         fn parses_bare_yarn() {
             assert_eq!(
@@ -256,6 +293,22 @@ mod tests {
             );
         }
 
+        #[test]
+        fn parses_package_sources() {
+            for source in &[
+                "https://github.com/org/cli#v2",
+                "git+ssh://git@github.com/org/cli.git",
+                "github:org/cli",
+                "./cli-2.0.0.tgz",
+                "../vendor/cli",
+            ] {
+                assert_eq!(
+                    Spec::try_from_str(source).expect("succeeds"),
+                    Spec::PackageSource(source.to_string())
+                );
+            }
+        }
+
         #[test]
         fn parses_bare_packages() {
             let package = "ember-cli";
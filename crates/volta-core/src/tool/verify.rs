@@ -0,0 +1,238 @@
+//! Provides verification of downloaded tools against the digests that their servers publish
+//!
+//! Each tool finds what its server publishes in its own way, e.g. the `SHASUMS256.txt` next to
+//! each Node distro, or the `dist` details of a package in the npm registry. Whatever is found is
+//! passed to `checksums::guard`, which checks the download as it is unpacked, so that a tool is
+//! never installed from an archive that doesn't match.
+
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::registry::registry_authorization;
+use crate::cert_pins;
+use crate::http;
+use archive::{Archive, ArchiveError, Origin};
+use attohttpc::header::AUTHORIZATION;
+use log::debug;
+use semver::Version;
+use serde::Deserialize;
+use sha1::Sha1;
+use sha2::{Digest, Sha256, Sha512};
+
+/// A digest of a download, as published by the server it comes from
+pub(crate) enum Published {
+    /// A hex-encoded SHA-256 digest, which is checked while the archive streams in
+    Sha256(String),
+    /// A hex-encoded SHA-1 digest, from the `shasum` of packages published before `integrity`
+    Sha1(String),
+    /// A Subresource Integrity string (e.g. `sha512-<base64>`), from the npm registry
+    Integrity(String),
+}
+
+#[derive(Deserialize)]
+struct RawVersionDetails {
+    dist: RawDist,
+}
+
+#[derive(Deserialize)]
+struct RawDist {
+    shasum: Option<String>,
+    integrity: Option<String>,
+}
+
+/// Looks up the SHA-256 digest of a file in the `SHASUMS256.txt` file next to it
+///
+/// Servers provided by hooks might not publish checksums, so if the file is missing or doesn't
+/// list the distro, the download isn't verified.
+pub(crate) fn shasums(url: &str, file_name: &str) -> Option<Published> {
+    let (base, _) = url.rsplit_once('/')?;
+    let shasums_url = format!("{}/SHASUMS256.txt", base);
+
+    let shasums = match fetch_text(&shasums_url, None) {
+        Ok(shasums) => shasums,
+        Err(error) => {
+            debug!("Could not fetch checksums from {}: {}", shasums_url, error);
+            return None;
+        }
+    };
+
+    let digest = shasums.lines().find_map(|line| {
+        let (digest, name) = line.split_once(char::is_whitespace)?;
+        (name.trim() == file_name).then(|| digest.to_string())
+    });
+    if digest.is_none() {
+        debug!("{} does not list a checksum for {}", shasums_url, file_name);
+    }
+    digest.map(Published::Sha256)
+}
+
+/// Looks up the digest of a version of a package in the npm registry, preferring its `integrity`
+///
+/// As with `SHASUMS256.txt`, registries that don't publish the details of each version (e.g.
+/// mirrors set up with hooks) leave the download unverified.
+pub(crate) fn registry_dist(index_url: &str, version: &Version) -> Option<Published> {
+    let details_url = format!("{}/{}", index_url.trim_end_matches('/'), version);
+
+    let authorization = registry_authorization(&details_url);
+    let details = match fetch_text(&details_url, authorization)
+        .and_then(|text| serde_json::from_str::<RawVersionDetails>(&text).map_err(io::Error::from))
+    {
+        Ok(details) => details,
+        Err(error) => {
+            debug!(
+                "Could not fetch package details from {}: {}",
+                details_url, error
+            );
+            return None;
+        }
+    };
+
+    match details.dist {
+        RawDist {
+            integrity: Some(integrity),
+            ..
+        } => Some(Published::Integrity(integrity)),
+        RawDist {
+            shasum: Some(shasum),
+            ..
+        } => Some(Published::Sha1(shasum)),
+        _ => {
+            debug!("{} does not list a digest", details_url);
+            None
+        }
+    }
+}
+
+/// Wraps an archive so that, once it has been unpacked, the downloaded file is checked against a
+/// digest that can't be checked while it streams in
+pub(crate) fn check_download(
+    archive: Box<dyn Archive>,
+    download: &Path,
+    published: Published,
+) -> Box<dyn Archive> {
+    Box::new(CheckedArchive {
+        inner: archive,
+        download: download.to_owned(),
+        published,
+    })
+}
+
+struct CheckedArchive {
+    inner: Box<dyn Archive>,
+    download: PathBuf,
+    published: Published,
+}
+
+impl Archive for CheckedArchive {
+    fn compressed_size(&self) -> u64 {
+        self.inner.compressed_size()
+    }
+
+    fn uncompressed_size(&self) -> Option<u64> {
+        self.inner.uncompressed_size()
+    }
+
+    fn unpack(
+        self: Box<Self>,
+        dest: &Path,
+        progress: &mut dyn FnMut(&(), usize),
+    ) -> Result<String, ArchiveError> {
+        let CheckedArchive {
+            inner,
+            download,
+            published,
+        } = *self;
+        // The whole download has been written to its file once it is unpacked
+        let digest = inner.unpack(dest, progress)?;
+
+        let (expected, actual) = match published {
+            Published::Sha256(expected) => (
+                expected.to_lowercase(),
+                hex::encode(file_digest::<Sha256>(&download)?),
+            ),
+            Published::Sha1(expected) => (
+                expected.to_lowercase(),
+                hex::encode(file_digest::<Sha1>(&download)?),
+            ),
+            Published::Integrity(integrity) => match strongest_integrity(&integrity) {
+                Some((algorithm, expected)) => {
+                    let hash: fn(&Path) -> io::Result<Vec<u8>> = match algorithm {
+                        "sha512" => file_digest::<Sha512>,
+                        "sha256" => file_digest::<Sha256>,
+                        _ => file_digest::<Sha1>,
+                    };
+                    (
+                        format!("{}-{}", algorithm, expected),
+                        format!("{}-{}", algorithm, base64::encode(hash(&download)?)),
+                    )
+                }
+                None => {
+                    debug!("Unsupported integrity '{}', skipping check", integrity);
+                    return Ok(digest);
+                }
+            },
+        };
+
+        if expected == actual {
+            Ok(digest)
+        } else {
+            Err(ArchiveError::ChecksumMismatch { expected, actual })
+        }
+    }
+
+    fn origin(&self) -> Origin {
+        self.inner.origin()
+    }
+
+    fn expect_sha256(&mut self, digest: String) {
+        self.inner.expect_sha256(digest);
+    }
+}
+
+/// Finds the entry of an integrity string with the strongest supported algorithm, returning the
+/// algorithm and its (base64-encoded) digest
+fn strongest_integrity(integrity: &str) -> Option<(&str, &str)> {
+    integrity
+        .split_whitespace()
+        // Entries may have options after the digest, which don't affect the check
+        .filter_map(|entry| entry.split('?').next()?.split_once('-'))
+        .filter(|(algorithm, _)| matches!(*algorithm, "sha512" | "sha256" | "sha1"))
+        .max_by_key(|(algorithm, _)| match *algorithm {
+            "sha512" => 3,
+            "sha256" => 2,
+            _ => 1,
+        })
+}
+
+fn file_digest<D: Digest + io::Write>(file: &Path) -> io::Result<Vec<u8>> {
+    let mut hasher = D::new();
+    io::copy(&mut File::open(file)?, &mut hasher)?;
+    Ok(hasher.finalize().to_vec())
+}
+
+fn fetch_text(url: &str, authorization: Option<String>) -> io::Result<String> {
+    cert_pins::verify(url)
+        .map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()))?;
+
+    let headers: Vec<_> = authorization
+        .map(|authorization| (AUTHORIZATION, authorization))
+        .into_iter()
+        .collect();
+    http::get_text(url, &headers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::strongest_integrity;
+
+    #[test]
+    fn prefers_strongest_integrity() {
+        assert_eq!(
+            strongest_integrity("sha1-abc= sha512-def==?opt sha256-ghi="),
+            Some(("sha512", "def=="))
+        );
+        assert_eq!(strongest_integrity("md5-abc="), None);
+        assert_eq!(strongest_integrity(""), None);
+    }
+}
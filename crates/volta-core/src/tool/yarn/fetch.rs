@@ -6,12 +6,22 @@ use std::path::Path;
 
 use super::super::download_tool_error;
 use super::super::registry::{
-    find_unpack_dir, public_registry_package, scoped_public_registry_package,
+    find_unpack_dir, public_registry_index, public_registry_package, registry_authorization,
+    registry_index, scoped_registry_package,
 };
+use super::super::verify::{self, Published};
+use crate::cert_pins;
+use crate::checksums;
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::{create_staging_dir, create_staging_file, rename, set_executable};
+use crate::fs::{
+    create_staging_dir, create_staging_file, long_path, persist_staging_file, remove_dir_if_exists,
+    rename, set_executable,
+};
 use crate::hook::YarnHooks;
+use crate::http;
+use crate::inventory::{mark_image_complete, record_yarn_image};
 use crate::layout::volta_home;
+use crate::signal::CleanupGuard;
 use crate::style::{progress_bar, tool_version};
 use crate::tool::{self, Yarn};
 use crate::version::VersionSpec;
@@ -24,6 +34,8 @@ pub fn fetch(version: &Version, hooks: Option<&YarnHooks>) -> Fallible<()> {
     let yarn_dir = volta_home()?.yarn_inventory_dir();
     let cache_file = yarn_dir.join(Yarn::archive_filename(&version.to_string()));
 
+    // Removes a partial download if the process exits before it is moved into the cache
+    let mut _download_cleanup = None;
     let (archive, staging) = match load_cached_distro(&cache_file) {
         Some(archive) => {
             debug!(
@@ -35,8 +47,11 @@ pub fn fetch(version: &Version, hooks: Option<&YarnHooks>) -> Fallible<()> {
         }
         None => {
             let staging = create_staging_file()?;
+            _download_cleanup = Some(CleanupGuard::new(staging.path()));
             let remote_url = determine_remote_url(version, hooks)?;
             let archive = fetch_remote_distro(version, &remote_url, staging.path())?;
+            let published = published_digest(version, hooks);
+            let archive = checksums::guard(&remote_url, archive, staging.path(), published)?;
             (archive, Some(staging))
         }
     };
@@ -49,11 +64,11 @@ pub fn fetch(version: &Version, hooks: Option<&YarnHooks>) -> Fallible<()> {
                 path: cache_file.clone(),
             }
         })?;
-        staging_file
-            .persist(cache_file)
-            .with_context(|| ErrorKind::PersistInventoryError {
+        persist_staging_file(staging_file, &cache_file).with_context(|| {
+            ErrorKind::PersistInventoryError {
                 tool: "Yarn".into(),
-            })?;
+            }
+        })?;
     }
 
     Ok(())
@@ -62,6 +77,7 @@ pub fn fetch(version: &Version, hooks: Option<&YarnHooks>) -> Fallible<()> {
 /// Unpack the yarn archive into the image directory so that it is ready for use
 fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()> {
     let temp = create_staging_dir()?;
+    let _cleanup = CleanupGuard::new(temp.path());
     debug!("Unpacking yarn into '{}'", temp.path().display());
 
     let progress = progress_bar(
@@ -73,8 +89,8 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     );
     let version_string = version.to_string();
 
-    archive
-        .unpack(temp.path(), &mut |_, read| {
+    let digest = archive
+        .unpack(&long_path(temp.path()), &mut |_, read| {
             progress.inc(read as u64);
         })
         .with_context(|| ErrorKind::UnpackArchiveError {
@@ -87,13 +103,25 @@ fn unpack_archive(archive: Box<dyn Archive>, version: &Version) -> Fallible<()>
     ensure_bin_is_executable(&unpack_dir, "yarn")?;
 
     let dest = volta_home()?.yarn_image_dir(&version_string);
+    mark_image_complete(&unpack_dir, digest, None).with_context(|| {
+        ErrorKind::SetupToolImageError {
+            tool: "Yarn".into(),
+            version: version_string.clone(),
+            dir: dest.clone(),
+        }
+    })?;
+
+    // An image without a marker is incomplete, and has to be replaced
+    remove_dir_if_exists(&dest)?;
     ensure_containing_dir_exists(&dest)
         .with_context(|| ErrorKind::ContainingDirError { path: dest.clone() })?;
 
-    rename(unpack_dir, &dest).with_context(|| ErrorKind::SetupToolImageError {
-        tool: "Yarn".into(),
-        version: version_string.clone(),
-        dir: dest.clone(),
+    record_yarn_image(version, || {
+        rename(unpack_dir, &dest).with_context(|| ErrorKind::SetupToolImageError {
+            tool: "Yarn".into(),
+            version: version_string.clone(),
+            dir: dest.clone(),
+        })
     })?;
 
     progress.finish_and_clear();
@@ -130,7 +158,7 @@ fn determine_remote_url(version: &Version, hooks: Option<&YarnHooks>) -> Fallibl
         }
         _ => {
             if env::var_os("VOLTA_FEATURE_YARN_3").is_some() && version.major >= 2 {
-                Ok(scoped_public_registry_package(
+                Ok(scoped_registry_package(
                     "@yarnpkg",
                     "cli-dist",
                     &version_str,
@@ -142,6 +170,26 @@ fn determine_remote_url(version: &Version, hooks: Option<&YarnHooks>) -> Fallibl
     }
 }
 
+/// Looks up the digest that the registry publishes for a Yarn version
+///
+/// Servers provided by hooks have no standard way to publish digests, so those downloads aren't
+/// verified.
+fn published_digest(version: &Version, hooks: Option<&YarnHooks>) -> Option<Published> {
+    if let Some(&YarnHooks {
+        distro: Some(_), ..
+    }) = hooks
+    {
+        return None;
+    }
+
+    let index_url = if env::var_os("VOLTA_FEATURE_YARN_3").is_some() && version.major >= 2 {
+        registry_index("@yarnpkg/cli-dist")
+    } else {
+        public_registry_index("yarn")
+    };
+    verify::registry_dist(&index_url, version)
+}
+
 /// Fetch the distro archive from the internet
 fn fetch_remote_distro(
     version: &Version,
@@ -149,7 +197,15 @@ fn fetch_remote_distro(
     staging_path: &Path,
 ) -> Fallible<Box<dyn Archive>> {
     debug!("Downloading {} from {}", tool_version("yarn", version), url);
-    Tarball::fetch(url, staging_path).with_context(download_tool_error(
+    cert_pins::verify(url)?;
+    let authorization = registry_authorization(url);
+    Tarball::fetch_authorized(
+        &*http::client(),
+        url,
+        authorization.as_deref(),
+        staging_path,
+    )
+    .with_context(download_tool_error(
         tool::Spec::Yarn(VersionSpec::Exact(version.clone())),
         url,
     ))
@@ -1,11 +1,11 @@
 use std::fmt::{self, Display};
+use std::path::PathBuf;
 
-use super::{
-    check_fetched, debug_already_fetched, info_fetched, info_installed, info_pinned,
-    info_project_version, FetchStatus, Tool,
-};
+use super::{info_fetched, info_installed, info_pinned, info_project_version, Distro, Tool};
 use crate::error::{ErrorKind, Fallible};
+use crate::hook::{HookConfig, YarnHooks};
 use crate::inventory::yarn_available;
+use crate::layout::volta_home;
 use crate::session::Session;
 use crate::style::tool_version;
 use crate::sync::VoltaLock;
@@ -34,21 +34,31 @@ impl Yarn {
     pub fn archive_filename(version: &str) -> String {
         format!("{}.tar.gz", Yarn::archive_basename(version))
     }
+}
 
-    pub(crate) fn ensure_fetched(&self, session: &mut Session) -> Fallible<()> {
-        match check_fetched(|| yarn_available(&self.version))? {
-            FetchStatus::AlreadyFetched => {
-                debug_already_fetched(self);
-                Ok(())
-            }
-            FetchStatus::FetchNeeded(_lock) => fetch::fetch(&self.version, session.hooks()?.yarn()),
-        }
+impl Distro for Yarn {
+    type Hooks = YarnHooks;
+
+    fn hooks(config: &HookConfig) -> Option<&YarnHooks> {
+        config.yarn()
+    }
+
+    fn image_dir(&self) -> Fallible<PathBuf> {
+        Ok(volta_home()?.yarn_image_dir(&self.version.to_string()))
+    }
+
+    fn available(&self) -> Fallible<bool> {
+        yarn_available(&self.version)
+    }
+
+    fn fetch_with(&self, hooks: Option<&YarnHooks>) -> Fallible<()> {
+        fetch::fetch(&self.version, hooks)
     }
 }
 
 impl Tool for Yarn {
     fn fetch(self: Box<Self>, session: &mut Session) -> Fallible<()> {
-        self.ensure_fetched(session)?;
+        self.refetch(session)?;
 
         info_fetched(self);
         Ok(())
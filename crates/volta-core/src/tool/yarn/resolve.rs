@@ -2,38 +2,61 @@
 
 use std::env;
 
-use super::super::registry::{
-    fetch_npm_registry, public_registry_index, PackageDetails, PackageIndex,
-};
+use super::super::registry::{fetch_npm_registry, registry_index, PackageDetails, PackageIndex};
 use super::super::registry_fetch_error;
 use super::metadata::{RawYarnIndex, YarnIndex};
+use crate::cert_pins;
 use crate::error::{Context, ErrorKind, Fallible};
 use crate::hook::{RegistryFormat, YarnHooks};
+use crate::http;
+use crate::inventory::yarn_versions;
 use crate::session::Session;
+use crate::settings::{Blocklist, ResolutionPreference};
 use crate::style::progress_spinner;
-use crate::version::{parse_version, VersionSpec, VersionTag};
-use attohttpc::Response;
+use crate::version::{parse_version, satisfies, VersionSpec, VersionTag};
 use log::debug;
 use semver::{Version, VersionReq};
 
 pub fn resolve(matching: VersionSpec, session: &mut Session) -> Fallible<Version> {
+    let include_prerelease = session.include_prerelease();
+    let settings = session.settings()?;
+    let blocklist = settings.yarn_blocklist();
+    let prefer_installed = settings.resolution() == ResolutionPreference::InstalledFirst;
     let hooks = session.hooks()?.yarn();
     match matching {
-        VersionSpec::Semver(requirement) => resolve_semver(requirement, hooks),
-        VersionSpec::Exact(version) => Ok(version),
-        VersionSpec::None => resolve_tag(VersionTag::Latest, hooks),
-        VersionSpec::Tag(tag) => resolve_tag(tag, hooks),
+        VersionSpec::Semver(requirement) => {
+            if prefer_installed {
+                if let Some(version) =
+                    find_installed_semver(&requirement, include_prerelease, blocklist)?
+                {
+                    return Ok(version);
+                }
+            }
+            resolve_semver(requirement, hooks, include_prerelease, blocklist)
+        }
+        VersionSpec::Exact(version) => {
+            blocklist.ensure_allowed(&version)?;
+            Ok(version)
+        }
+        VersionSpec::None => resolve_tag(VersionTag::Latest, hooks, blocklist),
+        VersionSpec::Tag(VersionTag::Installed) => resolve_installed(include_prerelease, blocklist),
+        VersionSpec::Tag(VersionTag::Current) => resolve_current(session),
+        VersionSpec::Tag(tag) => resolve_tag(tag, hooks, blocklist),
     }
 }
 
-fn resolve_tag(tag: VersionTag, hooks: Option<&YarnHooks>) -> Fallible<Version> {
+fn resolve_tag(
+    tag: VersionTag,
+    hooks: Option<&YarnHooks>,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     // This triage is complicated because we need to maintain the legacy behavior of hooks
     // First, if the tag is 'latest' and we have a 'latest' hook, we use the old behavior
     // Next, if the tag is 'latest' and we _do not_ have a 'latest' hook, we use the new behavior
     // Next, if the tag is _not_ 'latest' and we have an 'index' hook, we show an error since
     //     the previous behavior did not support generic tags
     // Finally, we don't have any relevant hooks, so we can use the new behavior
-    match (tag, hooks) {
+    let version = match (tag, hooks) {
         (
             VersionTag::Latest,
             Some(&YarnHooks {
@@ -51,10 +74,82 @@ fn resolve_tag(tag: VersionTag, hooks: Option<&YarnHooks>) -> Fallible<Version>
         }
         .into()),
         (tag, _) => resolve_custom_tag(tag.to_string()),
+    }?;
+
+    // A tag only ever points at a single version, so there is nothing to fall back to
+    blocklist.ensure_allowed(&version)?;
+    Ok(version)
+}
+
+/// Resolves the 'installed' alias to the newest Yarn version in the local inventory
+fn resolve_installed(include_prerelease: bool, blocklist: &Blocklist) -> Fallible<Version> {
+    let version_opt = yarn_versions()?.into_iter().rev().find(|version| {
+        (include_prerelease || !version.is_prerelease()) && !blocklist.skips(version)
+    });
+
+    match version_opt {
+        Some(version) => {
+            debug!("Found newest installed yarn version ({})", version);
+            Ok(version)
+        }
+        None => Err(ErrorKind::NoInstalledVersion {
+            tool: "Yarn".into(),
+        }
+        .into()),
     }
 }
 
-fn resolve_semver(matching: VersionReq, hooks: Option<&YarnHooks>) -> Fallible<Version> {
+/// Finds the newest installed yarn version that satisfies a requirement, if any
+fn find_installed_semver(
+    matching: &VersionReq,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Option<Version>> {
+    let version_opt = yarn_versions()?.into_iter().rev().find(|version| {
+        satisfies(matching, version, include_prerelease) && !blocklist.skips(version)
+    });
+
+    if let Some(version) = &version_opt {
+        debug!(
+            "Found installed yarn@{} matching requirement '{}'",
+            version, matching
+        );
+    }
+    Ok(version_opt)
+}
+
+/// Resolves the 'current' alias to the Yarn version active in the current context
+///
+/// As with running Yarn, a project without a pinned Yarn falls back to the default Yarn
+fn resolve_current(session: &Session) -> Fallible<Version> {
+    let project_yarn = session
+        .project_platform()?
+        .and_then(|platform| platform.yarn.as_ref());
+    let yarn = match project_yarn {
+        Some(yarn) => Some(yarn),
+        None => session
+            .default_platform()?
+            .and_then(|platform| platform.yarn.as_ref()),
+    };
+
+    match yarn {
+        Some(version) => {
+            debug!("Found current yarn version ({})", version);
+            Ok(version.clone())
+        }
+        None => Err(ErrorKind::NoCurrentVersion {
+            tool: "Yarn".into(),
+        }
+        .into()),
+    }
+}
+
+fn resolve_semver(
+    matching: VersionReq,
+    hooks: Option<&YarnHooks>,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     // For semver, the triage is less complicated: The previous behavior _always_ used
     // the 'index' hook, so we can check for that to decide which behavior to use.
     //
@@ -67,16 +162,23 @@ fn resolve_semver(matching: VersionReq, hooks: Option<&YarnHooks>) -> Fallible<V
     {
         debug!("Using yarn.index hook to determine yarn index URL");
         match hook.format {
-            RegistryFormat::Github => resolve_semver_legacy(matching, hook.resolve("releases")?),
-            RegistryFormat::Npm => resolve_semver_npm(matching, hook.resolve("")?),
+            RegistryFormat::Github => resolve_semver_legacy(
+                matching,
+                hook.resolve("releases")?,
+                include_prerelease,
+                blocklist,
+            ),
+            RegistryFormat::Npm => {
+                resolve_semver_npm(matching, hook.resolve("")?, include_prerelease, blocklist)
+            }
         }
     } else {
-        resolve_semver_from_registry(matching)
+        resolve_semver_from_registry(matching, include_prerelease, blocklist)
     }
 }
 
 fn fetch_yarn_index(package: &str) -> Fallible<(String, PackageIndex)> {
-    let url = public_registry_index(package);
+    let url = registry_index(package);
     fetch_npm_registry(url, "Yarn")
 }
 
@@ -109,11 +211,9 @@ fn resolve_custom_tag(tag: String) -> Fallible<Version> {
 }
 
 fn resolve_latest_legacy(url: String) -> Fallible<Version> {
-    let response_text = attohttpc::get(&url)
-        .send()
-        .and_then(Response::error_for_status)
-        .and_then(Response::text)
-        .with_context(|| ErrorKind::YarnLatestFetchError {
+    cert_pins::verify(&url)?;
+    let response_text =
+        http::get_text(&url, &[]).with_context(|| ErrorKind::YarnLatestFetchError {
             from_url: url.clone(),
         })?;
 
@@ -121,20 +221,28 @@ fn resolve_latest_legacy(url: String) -> Fallible<Version> {
     parse_version(response_text)
 }
 
-fn resolve_semver_from_registry(matching: VersionReq) -> Fallible<Version> {
+fn resolve_semver_from_registry(
+    matching: VersionReq,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     if env::var_os("VOLTA_FEATURE_YARN_3").is_some() {
         // first try yarn2+, which uses "@yarnpkg/cli-dist" instead of "yarn"
         let (url, index) = fetch_yarn_index("@yarnpkg/cli-dist")?;
         let matching_entries: Vec<PackageDetails> = index
             .entries
             .into_iter()
-            .filter(|PackageDetails { version, .. }| matching.matches(version))
+            .filter(|PackageDetails { version, .. }| {
+                satisfies(&matching, version, include_prerelease)
+            })
             .collect();
 
         if !matching_entries.is_empty() {
             let details_opt = matching_entries
                 .iter()
-                .find(|PackageDetails { version, .. }| version.major >= 3);
+                .find(|PackageDetails { version, .. }| {
+                    version.major >= 3 && !blocklist.skips(version)
+                });
 
             match details_opt {
                 Some(details) => {
@@ -160,7 +268,9 @@ fn resolve_semver_from_registry(matching: VersionReq) -> Fallible<Version> {
     let details_opt = index
         .entries
         .into_iter()
-        .find(|PackageDetails { version, .. }| matching.matches(version));
+        .find(|PackageDetails { version, .. }| {
+            satisfies(&matching, version, include_prerelease) && !blocklist.skips(version)
+        });
 
     match details_opt {
         Some(details) => {
@@ -178,17 +288,23 @@ fn resolve_semver_from_registry(matching: VersionReq) -> Fallible<Version> {
     }
 }
 
-fn resolve_semver_legacy(matching: VersionReq, url: String) -> Fallible<Version> {
+fn resolve_semver_legacy(
+    matching: VersionReq,
+    url: String,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
+    cert_pins::verify(&url)?;
     let spinner = progress_spinner(format!("Fetching registry: {}", url));
-    let releases: RawYarnIndex = attohttpc::get(&url)
-        .send()
-        .and_then(Response::error_for_status)
-        .and_then(Response::json)
-        .with_context(registry_fetch_error("Yarn", &url))?;
+    let releases: RawYarnIndex =
+        http::get_json(&url, &[]).with_context(registry_fetch_error("Yarn", &url))?;
     let index = YarnIndex::from(releases);
     let releases = index.entries;
     spinner.finish_and_clear();
-    let version_opt = releases.into_iter().rev().find(|v| matching.matches(v));
+    let version_opt = releases
+        .into_iter()
+        .rev()
+        .find(|v| satisfies(&matching, v, include_prerelease) && !blocklist.skips(v));
 
     match version_opt {
         Some(version) => {
@@ -205,13 +321,20 @@ fn resolve_semver_legacy(matching: VersionReq, url: String) -> Fallible<Version>
     }
 }
 
-fn resolve_semver_npm(matching: VersionReq, url: String) -> Fallible<Version> {
+fn resolve_semver_npm(
+    matching: VersionReq,
+    url: String,
+    include_prerelease: bool,
+    blocklist: &Blocklist,
+) -> Fallible<Version> {
     let (url, index) = fetch_npm_registry(url, "Yarn")?;
 
     let details_opt = index
         .entries
         .into_iter()
-        .find(|PackageDetails { version, .. }| matching.matches(version));
+        .find(|PackageDetails { version, .. }| {
+            satisfies(&matching, version, include_prerelease) && !blocklist.skips(version)
+        });
 
     match details_opt {
         Some(details) => {
@@ -1,10 +1,11 @@
 use std::convert::TryFrom;
-use std::fs::write;
+use std::io::Write;
 
 use crate::error::{Context, ErrorKind, Fallible};
-use crate::fs::touch;
+use crate::fs::{create_staging_file, persist_staging_file, touch};
 use crate::layout::volta_home;
 use crate::platform::PlatformSpec;
+use crate::sync::VoltaLock;
 use lazycell::LazyCell;
 use log::debug;
 use readext::ReadExt;
@@ -119,17 +120,25 @@ impl Toolchain {
         Ok(())
     }
 
+    /// Writes the default platform file
+    ///
+    /// The file is replaced in one step while holding the lock on the Volta directory, so that
+    /// concurrent installs can't leave it half-written.
     pub fn save(&self) -> Fallible<()> {
+        let _lock = VoltaLock::acquire_if_supported()?;
         let path = volta_home()?.default_platform_file();
-        let result = match &self.platform {
-            Some(platform) => {
-                let src = serial::Platform::of(platform).into_json()?;
-                write(&path, src)
-            }
-            None => write(&path, "{}"),
+        let src = match &self.platform {
+            Some(platform) => serial::Platform::of(platform).into_json()?,
+            None => "{}".into(),
         };
-        result.with_context(|| ErrorKind::WritePlatformError {
+        let write_error = || ErrorKind::WritePlatformError {
             file: path.to_owned(),
-        })
+        };
+
+        let mut staging = create_staging_file()?;
+        staging
+            .write_all(src.as_bytes())
+            .with_context(write_error)?;
+        persist_staging_file(staging, path).with_context(write_error)
     }
 }
@@ -3,11 +3,16 @@ use std::str::FromStr;
 
 use crate::error::{Context, ErrorKind, Fallible, VoltaError};
 use semver::{Version, VersionReq};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 mod serial;
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+/// A requested version of a tool, like `16`, `^4.2`, `lts`, or `16.14.2`
+///
+/// Specs are parsed with `FromStr` and written back with `Display`. They are serialized as the
+/// same strings, except that `VersionSpec::None` is serialized as `null`.
+#[derive(Clone, Debug, PartialEq)]
 pub enum VersionSpec {
     /// No version specified (default)
     None,
@@ -22,8 +27,7 @@ pub enum VersionSpec {
     Tag(VersionTag),
 }
 
-#[derive(Debug)]
-#[cfg_attr(test, derive(PartialEq))]
+#[derive(Clone, Debug, PartialEq)]
 pub enum VersionTag {
     /// The 'latest' tag, a special case that exists for all packages
     Latest,
@@ -31,10 +35,33 @@ pub enum VersionTag {
     /// The 'lts' tag, a special case for Node
     Lts,
 
+    /// The 'installed' alias, the newest version already fetched into the local inventory
+    Installed,
+
+    /// The 'current' alias, the version active in the current project or default platform
+    Current,
+
     /// An arbitrary tag version
     Custom(String),
 }
 
+impl VersionSpec {
+    /// Whether this spec can resolve to different versions over time, i.e. a range or a tag
+    ///
+    /// Note: The 'installed' and 'current' aliases depend on the local machine, so they aren't
+    /// considered floating
+    pub fn is_floating(&self) -> bool {
+        match self {
+            VersionSpec::Semver(_) => true,
+            VersionSpec::Tag(VersionTag::Installed) | VersionSpec::Tag(VersionTag::Current) => {
+                false
+            }
+            VersionSpec::Tag(_) => true,
+            VersionSpec::None | VersionSpec::Exact(_) => false,
+        }
+    }
+}
+
 impl fmt::Display for VersionSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -51,6 +78,8 @@ impl fmt::Display for VersionTag {
         match self {
             VersionTag::Latest => write!(f, "latest"),
             VersionTag::Lts => write!(f, "lts"),
+            VersionTag::Installed => write!(f, "installed"),
+            VersionTag::Current => write!(f, "current"),
             VersionTag::Custom(s) => s.fmt(f),
         }
     }
@@ -84,12 +113,60 @@ impl FromStr for VersionTag {
             Ok(VersionTag::Latest)
         } else if s == "lts" {
             Ok(VersionTag::Lts)
+        } else if s == "installed" {
+            Ok(VersionTag::Installed)
+        } else if s == "current" {
+            Ok(VersionTag::Current)
         } else {
             Ok(VersionTag::Custom(s.into()))
         }
     }
 }
 
+impl Serialize for VersionSpec {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self {
+            VersionSpec::None => s.serialize_none(),
+            spec => s.collect_str(spec),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionSpec {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(spec) => spec.parse().map_err(D::Error::custom),
+            None => Ok(VersionSpec::None),
+        }
+    }
+}
+
+impl Serialize for VersionTag {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        s.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for VersionTag {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
 pub fn parse_requirements(s: impl AsRef<str>) -> Fallible<VersionReq> {
     let s = s.as_ref();
     serial::parse_requirements(s)
@@ -102,6 +179,26 @@ pub fn parse_version(s: impl AsRef<str>) -> Fallible<Version> {
         .with_context(|| ErrorKind::VersionParseError { version: s.into() })
 }
 
+/// Determines whether a version satisfies a requirement
+///
+/// Prerelease versions follow npm semantics: they only satisfy a range that explicitly names a
+/// prerelease on the same `major.minor.patch` tuple (e.g. `>=20.0.0-rc.1`). If the user has
+/// opted in with `include_prerelease`, a prerelease is instead compared as if it were the
+/// release it precedes, so `node@20 --include-prerelease` can match `20.0.0-rc.2`.
+pub fn satisfies(requirement: &VersionReq, version: &Version, include_prerelease: bool) -> bool {
+    if requirement.matches(version) {
+        return true;
+    }
+
+    include_prerelease
+        && version.is_prerelease()
+        && requirement.matches(&Version {
+            pre: Vec::new(),
+            build: Vec::new(),
+            ..version.clone()
+        })
+}
+
 // remove the leading 'v' from the version string, if present
 fn trim_version(s: &str) -> &str {
     let s = s.trim();
@@ -202,3 +299,72 @@ pub mod hashmap_version_serde {
         Ok(m.into_iter().map(|(k, Wrapper(v))| (k, v)).collect())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn req(s: &str) -> VersionReq {
+        parse_requirements(s).unwrap()
+    }
+
+    fn version(s: &str) -> Version {
+        parse_version(s).unwrap()
+    }
+
+    #[test]
+    fn satisfies_excludes_prereleases_from_ranges() {
+        assert!(satisfies(&req("^20"), &version("20.1.0"), false));
+        assert!(!satisfies(&req("^20"), &version("20.0.0-rc.2"), false));
+        assert!(!satisfies(
+            &req(">=19"),
+            &version("21.0.0-nightly.1"),
+            false
+        ));
+    }
+
+    #[test]
+    fn satisfies_allows_explicit_prereleases() {
+        assert!(satisfies(
+            &req(">=20.0.0-rc.1"),
+            &version("20.0.0-rc.2"),
+            false
+        ));
+    }
+
+    #[test]
+    fn partial_versions_are_ranges() {
+        assert_eq!(
+            "18".parse::<VersionSpec>().unwrap(),
+            VersionSpec::Semver(req("18.x"))
+        );
+        assert_eq!(
+            "18.19".parse::<VersionSpec>().unwrap(),
+            VersionSpec::Semver(req("18.19.x"))
+        );
+        assert!(satisfies(&req("18"), &version("18.19.1"), false));
+        assert!(!satisfies(&req("18.19"), &version("18.20.0"), false));
+    }
+
+    #[test]
+    fn satisfies_includes_prereleases_when_requested() {
+        assert!(satisfies(&req("^20"), &version("20.0.0-rc.2"), true));
+        assert!(!satisfies(&req("^18"), &version("20.0.0-rc.2"), true));
+    }
+
+    #[test]
+    fn version_specs_round_trip_through_serde() {
+        for spec in &["16.14.2", "^4.2", "lts", "latest", "beta"] {
+            let parsed: VersionSpec = spec.parse().unwrap();
+            let json = serde_json::to_string(&parsed).unwrap();
+            assert_eq!(json, format!("\"{}\"", parsed));
+            assert_eq!(serde_json::from_str::<VersionSpec>(&json).unwrap(), parsed);
+        }
+
+        assert_eq!(serde_json::to_string(&VersionSpec::None).unwrap(), "null");
+        assert_eq!(
+            serde_json::from_str::<VersionSpec>("null").unwrap(),
+            VersionSpec::None
+        );
+    }
+}
@@ -14,7 +14,28 @@ use semver::{Compat, ReqParseError, VersionReq};
 pub fn parse_requirements(src: &str) -> Result<VersionReq, ReqParseError> {
     let src = src.trim().trim_start_matches('v');
 
-    VersionReq::parse_compat(src, Compat::Node)
+    match expand_partial_version(src) {
+        Some(range) => VersionReq::parse_compat(&range, Compat::Node),
+        None => VersionReq::parse_compat(src, Compat::Node),
+    }
+}
+
+/// Expands a partial version like `18` or `18.19` into the equivalent X-range (`18.x`, `18.19.x`)
+///
+/// This matches how nvm treats partial versions, so that `node@18` means the newest 18.x release,
+/// rather than being interpreted as an exact version.
+fn expand_partial_version(src: &str) -> Option<String> {
+    let parts: Vec<&str> = src.split('.').collect();
+    let is_partial = parts.len() <= 2
+        && parts
+            .iter()
+            .all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit()));
+
+    if is_partial {
+        Some(format!("{}.x", src))
+    } else {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -31,7 +52,7 @@ pub mod tests {
         );
         assert_eq!(
             parse_requirements("v1.5").unwrap(),
-            VersionReq::parse_compat("=1.5", Compat::Node).unwrap()
+            VersionReq::parse_compat("1.5.x", Compat::Node).unwrap()
         );
         assert_eq!(
             parse_requirements("=1.2.3").unwrap(),
@@ -50,4 +71,20 @@ pub mod tests {
             VersionReq::parse_compat("8.11 - 8.17 || 10.* || >= 12", Compat::Node).unwrap()
         );
     }
+
+    #[test]
+    fn test_parse_partial_versions() {
+        assert_eq!(
+            parse_requirements("18").unwrap(),
+            VersionReq::parse_compat("18.x", Compat::Node).unwrap()
+        );
+        assert_eq!(
+            parse_requirements("v18.19").unwrap(),
+            VersionReq::parse_compat("18.19.x", Compat::Node).unwrap()
+        );
+        assert_eq!(
+            parse_requirements("18.19.0").unwrap(),
+            VersionReq::parse_compat("=18.19.0", Compat::Node).unwrap()
+        );
+    }
 }
@@ -0,0 +1,99 @@
+//! Provides support for running inside the Windows Subsystem for Linux (WSL)
+//!
+//! WSL puts the Windows `PATH` after the Linux one and mounts the Windows drives under `/mnt`,
+//! so a Windows installation of Node (or of Volta itself) is visible to the Linux side. Windows
+//! and Linux images can't be used interchangeably, so they have to be kept apart.
+
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Component, Path, PathBuf};
+
+use lazy_static::lazy_static;
+
+/// The directory where WSL mounts the Windows drives, unless configured otherwise
+const MOUNT_ROOT: &str = "/mnt";
+
+lazy_static! {
+    static ref WSL: bool = env::var_os("WSL_DISTRO_NAME").is_some()
+        || Path::new("/proc/sys/fs/binfmt_misc/WSLInterop").exists();
+}
+
+/// Determines whether Volta is running inside WSL
+pub(crate) fn is_wsl() -> bool {
+    *WSL
+}
+
+/// Translates a Windows path (e.g. `C:\Users\me`) into the path of the same location in the WSL
+/// mount of that drive (e.g. `/mnt/c/Users/me`)
+///
+/// Variables shared from Windows through `WSLENV` without the `/p` flag keep their Windows
+/// paths, which would otherwise be treated as relative paths. Returns `None` for other paths.
+pub(crate) fn translate_windows_path(path: &OsStr) -> Option<PathBuf> {
+    let path = path.to_str()?;
+    let mut chars = path.chars();
+    let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+    if chars.next() != Some(':') || !matches!(chars.next(), Some('\\') | Some('/')) {
+        return None;
+    }
+
+    let mut translated = PathBuf::from(MOUNT_ROOT);
+    translated.push(drive.to_ascii_lowercase().to_string());
+    translated.extend(
+        path[3..]
+            .split(|c| c == '\\' || c == '/')
+            .filter(|s| !s.is_empty()),
+    );
+    Some(translated)
+}
+
+/// Determines whether a path is on one of the Windows drives mounted by WSL
+fn is_windows_mount(path: &Path) -> bool {
+    let mut components = path
+        .strip_prefix(MOUNT_ROOT)
+        .into_iter()
+        .flat_map(Path::components);
+    match components.next() {
+        Some(Component::Normal(drive)) => {
+            let drive = drive.to_string_lossy();
+            drive.len() == 1 && drive.chars().all(|c| c.is_ascii_alphabetic())
+        }
+        _ => false,
+    }
+}
+
+/// Determines whether a directory contains a Windows installation of Node
+pub(crate) fn is_windows_node_dir(dir: &Path) -> bool {
+    is_windows_mount(dir) && dir.join("node.exe").is_file()
+}
+
+/// Determines whether a Volta home directory belongs to the Windows installation of Volta,
+/// which keeps `.exe` shims in its `bin` directory
+pub(crate) fn is_windows_volta_home(home: &Path) -> bool {
+    is_windows_mount(home) && home.join("bin").join("node.exe").exists()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_windows_paths() {
+        assert_eq!(
+            translate_windows_path(OsStr::new("C:\\Users\\me\\AppData\\Local\\Volta")),
+            Some(PathBuf::from("/mnt/c/Users/me/AppData/Local/Volta"))
+        );
+        assert_eq!(
+            translate_windows_path(OsStr::new("d:/volta")),
+            Some(PathBuf::from("/mnt/d/volta"))
+        );
+        assert_eq!(translate_windows_path(OsStr::new("/home/me/.volta")), None);
+        assert_eq!(translate_windows_path(OsStr::new("volta")), None);
+    }
+
+    #[test]
+    fn detects_windows_mounts() {
+        assert!(is_windows_mount(Path::new("/mnt/c/Program Files/nodejs")));
+        assert!(!is_windows_mount(Path::new("/mnt/wsl/shared")));
+        assert!(!is_windows_mount(Path::new("/usr/local/bin")));
+    }
+}
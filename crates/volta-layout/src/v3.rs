@@ -11,7 +11,14 @@ layout! {
             "node": node_cache_dir {
                 "index.json": node_index_file;
                 "index.json.expires": node_index_expiry_file;
+                "resolved.json": node_resolution_cache_file;
+                "index.json.refresh": node_index_refresh_file;
+                "index.json.etag": node_index_etag_file;
+                "index.bin": node_index_binary_file;
             }
+            "projects": project_cache_dir {}
+            "hooks": hooks_cache_dir {}
+            "hook-output": hook_output_cache_dir {}
         }
         "bin": shim_dir {}
         "log": log_dir {}
@@ -20,12 +27,15 @@ layout! {
                 "node": node_inventory_dir {}
                 "npm": npm_inventory_dir {}
                 "yarn": yarn_inventory_dir {}
+                "usage": usage_dir {}
+                "installed.json": installed_versions_file;
             }
             "image": image_dir {
                 "node": node_image_root_dir {}
                 "npm": npm_image_root_dir {}
                 "yarn": yarn_image_root_dir {}
                 "packages": package_image_root_dir {}
+                "project-tools": project_tool_image_root_dir {}
             }
             "shared": shared_lib_root {}
             "user": default_toolchain_dir {
@@ -36,6 +46,11 @@ layout! {
         }
         "tmp": tmp_dir {}
         "hooks.json": default_hooks_file;
+        "settings.json": default_settings_file;
+        "install.json": install_source_file;
+        "checksums.json": checksums_file;
+        "hook-approvals.json": hook_approvals_file;
+        "shims.json": shim_record_file;
         "layout.v3": layout_file;
     }
 }
@@ -65,6 +80,10 @@ impl VoltaHome {
         path_buf!(self.package_image_root_dir.clone(), name)
     }
 
+    pub fn project_tool_image_dir(&self, name: &str, version: &str) -> PathBuf {
+        path_buf!(self.project_tool_image_root_dir.clone(), name, version)
+    }
+
     pub fn default_package_config_file(&self, package_name: &str) -> PathBuf {
         path_buf!(
             self.default_package_dir.clone(),
@@ -83,6 +102,10 @@ impl VoltaHome {
         )
     }
 
+    pub fn image_usage_file(&self, tool: &str, version: &str) -> PathBuf {
+        path_buf!(self.usage_dir.clone(), format!("{}-{}", tool, version))
+    }
+
     pub fn shim_file(&self, toolname: &str) -> PathBuf {
         path_buf!(self.shim_dir.clone(), executable(toolname))
     }
@@ -23,7 +23,8 @@ use v2::V2;
 use v3::V3;
 
 use log::{debug, info};
-use volta_core::error::Fallible;
+use volta_core::error::{ErrorKind, Fallible};
+use volta_core::fs::read_dir_eager;
 use volta_core::layout::volta_home;
 #[cfg(unix)]
 use volta_core::layout::volta_install;
@@ -81,6 +82,9 @@ macro_rules! detect_tagged {
 
 detect_tagged!((v3, V3, V3), (v2, V2, V2), (v1, V1, V1));
 
+/// The layout version that migrations end at, which must match the newest `detect_tagged!` entry
+const CURRENT_LAYOUT: u32 = 3;
+
 impl MigrationState {
     fn current() -> Fallible<Self> {
         // First look for a tagged version (V1+). If that can't be found, then go through the triage
@@ -136,6 +140,19 @@ impl MigrationState {
     }
 }
 
+/// Migrates the Volta directory to the current layout, unless it is already there
+///
+/// This is run at the start of every command and shim, so that a directory left behind by an
+/// older version of Volta is upgraded in place the first time the new binary runs, without
+/// depending on a separate `volta-migrate` executable having been installed alongside it.
+pub fn ensure_current_layout() -> Fallible<()> {
+    if volta_home()?.layout_file().exists() {
+        return Ok(());
+    }
+
+    run_migration()
+}
+
 pub fn run_migration() -> Fallible<()> {
     // Acquire an exclusive lock on the Volta directory, to ensure that no other migrations are running.
     // If this fails, however, we still need to run the migration
@@ -152,6 +169,12 @@ pub fn run_migration() -> Fallible<()> {
 }
 
 fn detect_and_migrate() -> Fallible<()> {
+    // A directory written by a newer version of Volta has no marker that we recognize, so it would
+    // otherwise be treated as a legacy directory and "migrated" into a layout it has moved past
+    if let Some(version) = newer_layout(volta_home()?.root()) {
+        return Err(ErrorKind::NewerLayoutError { version }.into());
+    }
+
     info!("Updating your Volta directory. This may take a few moments...");
     let mut state = MigrationState::current()?;
 
@@ -174,3 +197,19 @@ fn detect_and_migrate() -> Fallible<()> {
 
     Ok(())
 }
+
+/// Finds the newest layout marker in the Volta directory that is newer than the current layout
+fn newer_layout(home: &Path) -> Option<u32> {
+    read_dir_eager(home)
+        .ok()?
+        .filter_map(|(entry, _)| {
+            entry
+                .file_name()
+                .to_str()?
+                .strip_prefix("layout.v")?
+                .parse::<u32>()
+                .ok()
+        })
+        .filter(|version| *version > CURRENT_LAYOUT)
+        .max()
+}
@@ -92,7 +92,8 @@ impl TryFrom<V2> for V3 {
 
 fn migrate_packages(old_home: &v2::VoltaHome) -> Fallible<()> {
     let packages = get_installed_packages(old_home);
-    let mut session = Session::init();
+    // Uses the environment that the Volta binary built its session with
+    let mut session = Session::builder().build()?;
 
     for package in packages {
         migrate_single_package(package, &mut session)?;
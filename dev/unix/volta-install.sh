@@ -128,6 +128,18 @@ parse_os_info() {
 
   case "$uname_str" in
     Linux)
+      # the ARM builds are only made against OpenSSL 3, which current ARM distros ship by default
+      case "$(uname -m)" in
+        aarch64)
+          echo "linux-arm"
+          return 0
+          ;;
+        arm|armv7*)
+          echo "linux-armv7"
+          return 0
+          ;;
+      esac
+
       parsed_version="$(parse_openssl_version "$openssl_version")"
       exit_code="$?"
       if [ "$exit_code" != 0 ]; then
@@ -399,9 +411,14 @@ check_architecture() {
           return 0
         fi
         ;;
+      aarch64|arm|armv7*)
+        if [ "$(uname -s)" = "Linux" ]; then
+          return 0
+        fi
+        ;;
     esac
 
-    error "Sorry! Volta currently only provides pre-built binaries for x86_64 architectures."
+    error "Sorry! Volta currently only provides pre-built binaries for x86_64, 64-bit ARM, and ARMv7 architectures."
     return 1
   fi
 }
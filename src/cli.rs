@@ -3,6 +3,7 @@ use structopt::StructOpt;
 use crate::command::{self, Command};
 use volta_core::error::{ExitCode, Fallible};
 use volta_core::session::Session;
+use volta_core::tool::node;
 
 #[derive(StructOpt)]
 #[structopt(
@@ -36,6 +37,20 @@ pub(crate) struct Volta {
     )]
     pub(crate) quiet: bool,
 
+    #[structopt(
+        long = "frozen",
+        help = "Requires floating project pins to use the versions in volta.lock",
+        global = true
+    )]
+    pub(crate) frozen: bool,
+
+    #[structopt(
+        long = "require-pin",
+        help = "Fails instead of using the default platform in a project without pins",
+        global = true
+    )]
+    pub(crate) require_pin: bool,
+
     #[structopt(
         short = "v",
         long = "version",
@@ -46,6 +61,13 @@ pub(crate) struct Volta {
 
 impl Volta {
     pub(crate) fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        if self.frozen {
+            session.set_frozen();
+        }
+        if self.require_pin {
+            session.set_strict();
+        }
+
         if self.version {
             // suffix indicator for dev build
             if cfg!(debug_assertions) {
@@ -55,6 +77,17 @@ impl Volta {
             }
             Ok(ExitCode::Success)
         } else if let Some(command) = self.command {
+            if !matches!(
+                command,
+                Subcommand::RefreshIndex(_)
+                    | Subcommand::Complete(_)
+                    | Subcommand::Current(_)
+                    | Subcommand::Inspect(_)
+                    | Subcommand::Audit(_)
+                    | Subcommand::Sbom(_)
+            ) {
+                node::refresh_index_in_background(session);
+            }
             command.run(session)
         } else {
             Volta::from_iter(["volta", "help"].iter()).run(session)
@@ -64,7 +97,7 @@ impl Volta {
 
 #[derive(StructOpt)]
 pub(crate) enum Subcommand {
-    /// Fetches a tool to the local machine
+    /// Fetches tools to the local machine, without installing them
     #[structopt(name = "fetch", author = "", version = "")]
     Fetch(command::Fetch),
 
@@ -76,14 +109,144 @@ pub(crate) enum Subcommand {
     #[structopt(name = "uninstall", author = "", version = "")]
     Uninstall(command::Uninstall),
 
+    /// Upgrades installed packages to the latest version that matches their install, or Volta itself
+    #[structopt(name = "upgrade", author = "", version = "")]
+    Upgrade(command::Upgrade),
+
     /// Pins your project's runtime or package manager
     #[structopt(name = "pin", author = "", version = "")]
     Pin(command::Pin),
 
+    /// Removes pinned tools from your project
+    #[structopt(name = "unpin", author = "", version = "")]
+    Unpin(command::Unpin),
+
     /// Displays the current toolchain
     #[structopt(name = "list", alias = "ls", author = "", version = "")]
     List(command::List),
 
+    /// Displays the active versions of Node and the package managers
+    #[structopt(name = "current", author = "", version = "")]
+    Current(command::Current),
+
+    /// Imports the Node versions installed by another version manager
+    #[structopt(
+        name = "import",
+        author = "",
+        version = "",
+        long_about = "Imports the Node versions installed by another version manager
+
+Each installed version is added to Volta without downloading it again, and the
+manager's default version becomes your default. Packages installed globally with
+the other manager are not imported; use `volta install` to add them to Volta.
+
+With `--link`, the files are hard linked instead of copied where possible, so the
+versions don't take up additional disk space.
+
+The directories of the managers are found from `NVM_DIR` (nvm), `NODENV_ROOT`
+(nodenv), and `N_PREFIX` (n), or their default locations if those aren't set."
+    )]
+    Import(command::Import),
+
+    /// Moves fetched tools between machines, without downloading them
+    #[structopt(
+        name = "inventory",
+        author = "",
+        version = "",
+        raw(setting = "structopt::clap::AppSettings::SubcommandRequiredElseHelp")
+    )]
+    Inventory(command::Inventory),
+
+    /// Writes your hooks, settings, and installed tools to a bundle for another machine
+    #[structopt(
+        name = "export-settings",
+        author = "",
+        version = "",
+        long_about = "Writes your hooks, settings, and installed tools to a bundle for another machine
+
+The bundle is a JSON document with the contents of your `hooks.json` and
+`settings.json`, and the versions of your default toolchain and installed
+packages (but not their files). Use `volta import-settings` to restore it."
+    )]
+    ExportSettings(command::ExportSettings),
+
+    /// Restores the hooks, settings, and tools from a bundle made by `export-settings`
+    #[structopt(
+        name = "import-settings",
+        author = "",
+        version = "",
+        long_about = "Restores the hooks, settings, and tools from a bundle made by `export-settings`
+
+Your `hooks.json` and `settings.json` are replaced with the ones in the bundle,
+and then each tool in it is installed, so that the default toolchain and
+packages match the machine that the bundle was exported from."
+    )]
+    ImportSettings(command::ImportSettings),
+
+    /// Moves you to Volta from another version manager, step by step
+    #[structopt(
+        name = "migrate",
+        author = "",
+        version = "",
+        long_about = "Moves you to Volta from another version manager, step by step
+
+This imports the Node versions installed by nvm, nodenv, or n, pins Node in the
+projects in the current directory (and its subdirectories) that choose a version
+with an `.nvmrc` file or `engines`, and sets up your shell to use Volta. Each step
+is confirmed before it is made, unless `--yes` is set."
+    )]
+    Migrate(command::Migrate),
+
+    /// Describes the toolchain of a directory as JSON, for editors and other tools
+    #[structopt(
+        name = "inspect",
+        author = "",
+        version = "",
+        long_about = "Describes the toolchain of a directory as JSON, for editors and other tools
+
+The document includes the resolved Node, npm, and Yarn versions with where they
+come from and the directories of their binaries, the project root and its pinned
+tools, and the paths of the Volta shims. Nothing is fetched, so tools that aren't
+installed yet are marked with `\"installed\": false`.
+
+The `version` field of the document is increased whenever existing fields change."
+    )]
+    Inspect(command::Inspect),
+
+    /// Reports where every installed tool came from, as JSON
+    #[structopt(
+        name = "audit",
+        author = "",
+        version = "",
+        long_about = "Reports where every installed tool came from, as JSON
+
+For each installed version of Node, npm, and Yarn, the report includes the URL it
+was downloaded from, the SHA-256 checksum of the download, how that download was
+verified, and when it was installed. Package tools are listed with their source,
+package manager, Node version, and install date.
+
+The source and verification are only known for downloads recorded since Volta
+began tracking checksums, so they are null for older installations.
+
+The `version` field of the report is increased whenever existing fields change."
+    )]
+    Audit(command::Audit),
+
+    /// Generates a software bill of materials for the installed tools
+    #[structopt(
+        name = "sbom",
+        author = "",
+        version = "",
+        long_about = "Generates a software bill of materials for the installed tools
+
+The document lists every installed version of Node, npm, and Yarn, and every
+package tool, with its package URL. Where Volta recorded the download, the URL
+it came from and its SHA-256 checksum are included.
+
+Use `--format` to choose between CycloneDX (the default) and SPDX JSON."
+    )]
+    Sbom(command::Sbom),
+
     /// Generates Volta completions
     #[structopt(
         name = "completions",
@@ -106,18 +269,124 @@ otherwise, they will be written to `stdout`.
     #[structopt(name = "which", author = "", version = "")]
     Which(command::Which),
 
+    /// Selects tool versions for the current shell session only
     #[structopt(
         name = "use",
         author = "",
         version = "",
-        template = "{usage}",
-        raw(
-            usage = "crate::command::r#use::USAGE",
-            setting = "structopt::clap::AppSettings::Hidden"
-        )
+        long_about = "Selects tool versions for the current shell session only
+
+Like `nvm use`, this switches versions without changing your default toolchain
+or the pins of any project. The selected versions take precedence over both,
+until the shell exits or `volta use --clear` is run.
+
+A command can't change the environment of the shell that runs it, so this
+prints a statement for your shell to run instead, e.g.
+
+    eval \"$(volta use node@18)\"
+
+By default, the statement is written for your current shell, i.e. the value of
+`SHELL`. If you set the `--shell` option, it will be written for that shell
+instead."
     )]
     Use(command::Use),
 
+    /// Removes Volta's shims from the PATH of the current shell
+    #[structopt(
+        name = "deactivate",
+        author = "",
+        version = "",
+        long_about = "Removes Volta's shims from the PATH of the current shell
+
+This makes the shell find tools as if Volta wasn't installed, e.g. to try the
+system's Node, without editing your profile. Other shells are unaffected, and
+`volta activate` adds the shims back.
+
+A command can't change the environment of the shell that runs it, so this
+prints a statement for your shell to run instead, e.g.
+
+    eval \"$(volta deactivate)\""
+    )]
+    Deactivate(command::Deactivate),
+
+    /// Adds Volta's shims back to the PATH of the current shell
+    #[structopt(
+        name = "activate",
+        author = "",
+        version = "",
+        long_about = "Adds Volta's shims back to the PATH of the current shell
+
+This undoes `volta deactivate`, putting the shims at the front of the PATH. If
+they are already on the PATH, it is left as it is.
+
+A command can't change the environment of the shell that runs it, so this
+prints a statement for your shell to run instead, e.g.
+
+    eval \"$(volta activate)\""
+    )]
+    Activate(command::Activate),
+
+    /// Prints the environment that uses the current toolchain without Volta's shims
+    #[structopt(
+        name = "env",
+        author = "",
+        version = "",
+        long_about = "Prints the environment that uses the current toolchain without Volta's shims
+
+The output sets `PATH` to find the Node, npm, and Yarn versions of the current
+project (or your default toolchain) directly, along with `VOLTA_HOME`, so that it
+can be evaluated by direnv, scripts, or Makefiles, e.g. `eval \"$(volta env)\"`.
+
+By default, the statements are written for your current shell, i.e. the value of
+`SHELL`. If you set the `--shell` option, they will be written for that shell
+instead."
+    )]
+    Env(command::Env),
+
+    /// Checks your environment for problems that keep Volta from working
+    #[structopt(
+        name = "doctor",
+        author = "",
+        version = "",
+        long_about = "Checks your environment for problems that keep Volta from working
+
+This reports if Volta's shims are missing from your PATH, and any directories
+earlier on your PATH that provide Node or a package manager, such as those of
+nvm, n, asdf, or Corepack, along with how to fix each one."
+    )]
+    Doctor(command::Doctor),
+
+    /// Checks installed versions of Node, npm, and Yarn for damage
+    #[structopt(
+        name = "verify",
+        author = "",
+        version = "",
+        long_about = "Checks installed versions of Node, npm, and Yarn for damage
+
+Each version is checked for files that went missing since it was unpacked, and
+against the checksum of the archive it was unpacked from. With --published, the
+checksums of Node versions are also compared to the ones published by the server
+they were downloaded from.
+
+With --repair, damaged versions are removed and fetched again."
+    )]
+    Verify(command::Verify),
+
+    /// Lists or removes the versions of Node, npm, and Yarn that haven't been used in a while
+    #[structopt(
+        name = "evict-unused",
+        author = "",
+        version = "",
+        long_about = "Lists or removes the versions of Node, npm, and Yarn that haven't been used in a while
+
+A version is unused once it hasn't been run for longer than the
+`evictUnusedAfterDays` setting allows. Versions used by the default toolchain, an
+installed package, or a project that Volta has run in are kept.
+
+The unused versions are only listed, unless --yes is given."
+    )]
+    EvictUnused(command::EvictUnused),
+
     /// Enables Volta for the current user / shell
     #[structopt(name = "setup", author = "", version = "")]
     Setup(command::Setup),
@@ -127,6 +396,45 @@ otherwise, they will be written to `stdout`.
     #[structopt(raw(setting = "structopt::clap::AppSettings::AllowLeadingHyphen"))]
     #[structopt(raw(setting = "structopt::clap::AppSettings::TrailingVarArg"))]
     Run(command::Run),
+
+    /// Runs an executable from a package without installing it
+    #[structopt(name = "x", author = "", version = "")]
+    #[structopt(raw(setting = "structopt::clap::AppSettings::AllowLeadingHyphen"))]
+    #[structopt(raw(setting = "structopt::clap::AppSettings::TrailingVarArg"))]
+    Exec(command::Exec),
+
+    /// Runs any command with the current toolchain on the PATH
+    #[structopt(
+        name = "exec",
+        author = "",
+        version = "",
+        long_about = "Runs any command with the current toolchain on the PATH
+
+The command doesn't need to be a tool managed by Volta, so build tools that run
+Node indirectly (e.g. `make` or `gradle`) get the versions of the current
+project, e.g. `volta exec -- make build`."
+    )]
+    #[structopt(raw(setting = "structopt::clap::AppSettings::AllowLeadingHyphen"))]
+    #[structopt(raw(setting = "structopt::clap::AppSettings::TrailingVarArg"))]
+    ExecCommand(command::ExecCommand),
+
+    #[structopt(
+        name = "refresh-index",
+        author = "",
+        version = "",
+        raw(setting = "structopt::clap::AppSettings::Hidden")
+    )]
+    RefreshIndex(command::RefreshIndex),
+
+    #[structopt(
+        name = "__complete",
+        author = "",
+        version = "",
+        raw(setting = "structopt::clap::AppSettings::Hidden"),
+        raw(setting = "structopt::clap::AppSettings::AllowLeadingHyphen"),
+        raw(setting = "structopt::clap::AppSettings::TrailingVarArg")
+    )]
+    Complete(command::Complete),
 }
 
 impl Subcommand {
@@ -135,13 +443,34 @@ impl Subcommand {
             Subcommand::Fetch(fetch) => fetch.run(session),
             Subcommand::Install(install) => install.run(session),
             Subcommand::Uninstall(uninstall) => uninstall.run(session),
+            Subcommand::Upgrade(upgrade) => upgrade.run(session),
             Subcommand::Pin(pin) => pin.run(session),
+            Subcommand::Unpin(unpin) => unpin.run(session),
             Subcommand::List(list) => list.run(session),
+            Subcommand::Current(current) => current.run(session),
+            Subcommand::Inspect(inspect) => inspect.run(session),
+            Subcommand::Audit(audit) => audit.run(session),
+            Subcommand::Sbom(sbom) => sbom.run(session),
+            Subcommand::Import(import) => import.run(session),
+            Subcommand::Inventory(inventory) => inventory.run(session),
+            Subcommand::ExportSettings(export) => export.run(session),
+            Subcommand::ImportSettings(import) => import.run(session),
+            Subcommand::Migrate(migrate) => migrate.run(session),
             Subcommand::Completions(completions) => completions.run(session),
             Subcommand::Which(which) => which.run(session),
             Subcommand::Use(r#use) => r#use.run(session),
+            Subcommand::Deactivate(deactivate) => deactivate.run(session),
+            Subcommand::Activate(activate) => activate.run(session),
+            Subcommand::Env(env) => env.run(session),
+            Subcommand::Doctor(doctor) => doctor.run(session),
+            Subcommand::Verify(verify) => verify.run(session),
             Subcommand::Setup(setup) => setup.run(session),
             Subcommand::Run(run) => run.run(session),
+            Subcommand::Exec(exec) => exec.run(session),
+            Subcommand::ExecCommand(exec) => exec.run(session),
+            Subcommand::RefreshIndex(refresh) => refresh.run(session),
+            Subcommand::EvictUnused(evict) => evict.run(session),
+            Subcommand::Complete(complete) => complete.run(session),
         }
     }
 }
@@ -0,0 +1,56 @@
+use structopt::StructOpt;
+
+use volta_core::conflicts::{path_with_shims, path_without_shims};
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::env::{warn_if_not_evaluated, Shell, SHELLS};
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct Activate {
+    /// Shell to write the statements for (defaults to the value of `SHELL`)
+    #[structopt(
+        long = "shell",
+        raw(possible_values = "SHELLS"),
+        case_insensitive = true
+    )]
+    shell: Option<String>,
+}
+
+impl Command for Activate {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Activate);
+
+        let shell = Shell::from_option(self.shell.as_deref());
+        println!("{}", shell.export_path(&path_with_shims()?));
+        warn_if_not_evaluated("activate");
+
+        session.add_event_end(ActivityKind::Activate, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+#[derive(StructOpt)]
+pub(crate) struct Deactivate {
+    /// Shell to write the statements for (defaults to the value of `SHELL`)
+    #[structopt(
+        long = "shell",
+        raw(possible_values = "SHELLS"),
+        case_insensitive = true
+    )]
+    shell: Option<String>,
+}
+
+impl Command for Deactivate {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Deactivate);
+
+        let shell = Shell::from_option(self.shell.as_deref());
+        println!("{}", shell.export_path(&path_without_shims()?));
+        warn_if_not_evaluated("deactivate");
+
+        session.add_event_end(ActivityKind::Deactivate, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
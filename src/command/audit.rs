@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use structopt::StructOpt;
+
+use volta_core::checksums::{find_download, Verification};
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::inventory::{
+    image_provenance, node_versions, npm_versions, package_configs, package_installed,
+    yarn_versions,
+};
+use volta_core::layout::volta_home;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::tool::package::PackageManager;
+
+use crate::command::Command;
+
+/// The version of the report format, which changes only when existing fields change meaning or
+/// are removed (new fields may be added at any time)
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(StructOpt)]
+pub(crate) struct Audit {}
+
+impl Command for Audit {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Audit);
+
+        let report = Report::build()?;
+        // Serializing plain strings and lists can't fail
+        let json = serde_json::to_string_pretty(&report).expect("Report is serializable");
+        println!("{}", json);
+
+        session.add_event_end(ActivityKind::Audit, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Describes where every installed tool came from
+#[derive(Serialize)]
+pub(crate) struct Report {
+    version: u32,
+    pub(crate) runtimes: Vec<RuntimeInfo>,
+    pub(crate) packages: Vec<PackageInfo>,
+}
+
+/// An installed version of Node, npm, or Yarn
+///
+/// The source and verification are only known for downloads that were recorded when they were
+/// first fetched, so they are `None` for tools installed by older versions of Volta, or imported
+/// from other version managers.
+#[derive(Serialize)]
+pub(crate) struct RuntimeInfo {
+    pub(crate) tool: &'static str,
+    pub(crate) version: String,
+    image: PathBuf,
+    pub(crate) source: Option<String>,
+    pub(crate) sha256: Option<String>,
+    verification: Option<Verification>,
+    installed: Option<String>,
+}
+
+/// A package tool installed with `volta install`
+///
+/// The package manager verifies the integrity of packages from the registry, so no checksum is
+/// recorded for them.
+#[derive(Serialize)]
+pub(crate) struct PackageInfo {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    /// The git URL or tarball that the package was installed from, or `None` for the registry
+    pub(crate) source: Option<String>,
+    manager: PackageManager,
+    node: String,
+    image: PathBuf,
+    installed: Option<String>,
+}
+
+impl Report {
+    pub(crate) fn build() -> Fallible<Self> {
+        let home = volta_home()?;
+        let mut runtimes = Vec::new();
+
+        for version in node_versions()? {
+            let image = home.node_image_dir(&version.to_string());
+            runtimes.push(RuntimeInfo::new("node", version.to_string(), image)?);
+        }
+        for version in npm_versions()? {
+            let image = home.npm_image_dir(&version.to_string());
+            runtimes.push(RuntimeInfo::new("npm", version.to_string(), image)?);
+        }
+        for version in yarn_versions()? {
+            let image = home.yarn_image_dir(&version.to_string());
+            runtimes.push(RuntimeInfo::new("yarn", version.to_string(), image)?);
+        }
+
+        let packages = package_configs()?
+            .into_iter()
+            .map(|config| PackageInfo {
+                image: home.package_image_dir(&config.name),
+                installed: package_installed(&config.name).map(|date| date.to_rfc3339()),
+                version: config.version.to_string(),
+                node: config.platform.node.to_string(),
+                source: config.source,
+                manager: config.manager,
+                name: config.name,
+            })
+            .collect();
+
+        Ok(Report {
+            version: FORMAT_VERSION,
+            runtimes,
+            packages,
+        })
+    }
+}
+
+impl RuntimeInfo {
+    fn new(tool: &'static str, version: String, image: PathBuf) -> Fallible<Self> {
+        let (sha256, installed) = image_provenance(&image);
+        let download = match &sha256 {
+            Some(sha256) => find_download(sha256)?,
+            None => None,
+        };
+        let (source, verification) = match download {
+            Some((url, download)) => (Some(url), Some(download.verification)),
+            None => (None, None),
+        };
+
+        Ok(RuntimeInfo {
+            tool,
+            version,
+            image,
+            source,
+            sha256,
+            verification,
+            installed: installed.map(|date| date.to_rfc3339()),
+        })
+    }
+}
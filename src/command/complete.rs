@@ -0,0 +1,149 @@
+use std::collections::BTreeSet;
+
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::inventory::{node_versions, npm_versions, package_configs, yarn_versions};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::tool::node;
+
+use crate::command::Command;
+
+/// The tools that Volta manages directly, rather than as packages
+const TOOLS: &[&str] = &["node", "npm", "yarn"];
+
+/// Suggests values for the word being completed on a `volta` command line
+///
+/// This is called by the shell completion scripts, with the words after `volta` up to and
+/// including the (possibly empty) word being completed. The suggestions are printed one per line,
+/// and when there are none, the scripts fall back to the static completions for commands and
+/// flags. Only local state is read, so that completion never waits on the network.
+#[derive(StructOpt)]
+pub(crate) struct Complete {
+    /// The words of the command line, ending with the word being completed
+    words: Vec<String>,
+}
+
+impl Command for Complete {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Complete);
+
+        for suggestion in suggestions(&self.words) {
+            println!("{}", suggestion);
+        }
+
+        session.add_event_end(ActivityKind::Complete, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+fn suggestions(words: &[String]) -> Vec<String> {
+    let (current, previous) = match words.split_last() {
+        Some((current, previous)) => (current.as_str(), previous),
+        None => return Vec::new(),
+    };
+    let subcommand = match previous.iter().find(|word| !word.starts_with('-')) {
+        Some(subcommand) => subcommand.as_str(),
+        None => return Vec::new(),
+    };
+    if current.starts_with('-') {
+        return Vec::new();
+    }
+
+    let candidates = match previous.last().map(String::as_str) {
+        Some("--node") => tool_versions("node"),
+        Some("--npm") => tool_versions("npm"),
+        Some("--yarn") => tool_versions("yarn"),
+        _ => match subcommand {
+            "fetch" | "install" | "pin" => tool_specs(current),
+            "uninstall" | "upgrade" => package_names(),
+            "which" => binary_names(),
+            _ => Vec::new(),
+        },
+    };
+
+    candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(current))
+        .collect()
+}
+
+/// Suggests tools, or versions once a tool is followed by `@`
+fn tool_specs(current: &str) -> Vec<String> {
+    // Scoped packages start with `@`, so that one is never the version separator
+    match current.rfind('@').filter(|&index| index > 0) {
+        Some(index) => {
+            let tool = &current[..index];
+            let versions = match tool {
+                name if TOOLS.contains(&name) => tool_versions(name),
+                name => package_version(name),
+            };
+            versions
+                .into_iter()
+                .map(|version| format!("{}@{}", tool, version))
+                .collect()
+        }
+        None => TOOLS
+            .iter()
+            .map(|tool| tool.to_string())
+            .chain(package_names())
+            .collect(),
+    }
+}
+
+/// Suggests the installed versions of a tool, along with the tags and, for Node, the LTS lines
+/// in the cached index
+fn tool_versions(tool: &str) -> Vec<String> {
+    let installed = match tool {
+        "node" => node_versions(),
+        "npm" => npm_versions(),
+        _ => yarn_versions(),
+    }
+    .unwrap_or_default();
+
+    let mut versions: Vec<String> = installed
+        .into_iter()
+        .rev()
+        .map(|version| version.to_string())
+        .collect();
+    if tool == "node" {
+        versions.push("lts".into());
+        versions.extend(node::cached_lts_lines().iter().map(u64::to_string));
+    }
+    versions.push("latest".into());
+    versions
+}
+
+/// Suggests the installed version of a package, along with the `latest` tag
+fn package_version(name: &str) -> Vec<String> {
+    let mut versions: Vec<String> = package_configs()
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|config| config.name == name)
+        .map(|config| config.version.to_string())
+        .collect();
+    versions.push("latest".into());
+    versions
+}
+
+fn package_names() -> Vec<String> {
+    package_configs()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|config| config.name)
+        .collect()
+}
+
+fn binary_names() -> Vec<String> {
+    let bins: BTreeSet<String> = package_configs()
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|config| config.bins)
+        .collect();
+
+    ["node", "npm", "npx", "yarn"]
+        .iter()
+        .map(|tool| tool.to_string())
+        .chain(bins)
+        .collect()
+}
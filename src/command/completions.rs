@@ -1,3 +1,4 @@
+use std::io::{self, Write};
 use std::path::PathBuf;
 
 use log::info;
@@ -11,16 +12,19 @@ use volta_core::{
 
 use crate::command::Command;
 
+/// The shells that completions can be generated for: those supported by clap, and Nushell
+const SHELLS: &[&str] = &["bash", "fish", "zsh", "powershell", "elvish", "nushell"];
+
 #[derive(Debug, StructOpt)]
 pub(crate) struct Completions {
     /// Shell to generate completions for
     #[structopt(
         takes_value = true,
         index = 1,
-        raw(possible_values = "&Shell::variants()"),
+        raw(possible_values = "SHELLS"),
         case_insensitive = true
     )]
-    shell: Shell,
+    shell: String,
 
     /// File to write generated completions to
     #[structopt(short = "o", long = "output")]
@@ -35,7 +39,6 @@ impl Command for Completions {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::Completions);
 
-        let mut app = crate::cli::Volta::clap();
         match self.out_file {
             Some(path) => {
                 if path.is_file() && !self.force {
@@ -65,7 +68,11 @@ impl Command for Completions {
                     }
                 })?;
 
-                app.gen_completions_to("volta", self.shell, &mut file);
+                write_completions(&self.shell, &mut file).with_context(|| {
+                    ErrorKind::CompletionsOutFileError {
+                        path: path.to_path_buf(),
+                    }
+                })?;
 
                 info!(
                     "{} installed completions to {}",
@@ -73,10 +80,74 @@ impl Command for Completions {
                     path.display()
                 );
             }
-            None => app.gen_completions_to("volta", self.shell, &mut std::io::stdout()),
+            None => {
+                // Output that can't be written (e.g. to a closed pipe) has nowhere else to go
+                let _ = write_completions(&self.shell, &mut io::stdout());
+            }
         };
 
         session.add_event_end(ActivityKind::Completions, ExitCode::Success);
         Ok(ExitCode::Success)
     }
 }
+
+fn write_completions(shell: &str, out: &mut dyn Write) -> io::Result<()> {
+    // Every other value was already validated to be one of clap's shells
+    match shell.parse::<Shell>() {
+        Ok(shell) => generate(shell, out),
+        Err(_) => out.write_all(NUSHELL_COMPLETIONS.as_bytes()),
+    }
+}
+
+/// Writes the completions for the given shell, including the dynamic ones
+pub(crate) fn generate(shell: Shell, mut out: &mut dyn Write) -> io::Result<()> {
+    crate::cli::Volta::clap().gen_completions_to("volta", shell, &mut out);
+    out.write_all(dynamic_completions(shell).as_bytes())
+}
+
+/// Completions for Nushell, which clap can't generate, so they are entirely dynamic
+///
+/// They are installed as an external completer that only handles `volta`, and defers to any
+/// completer that was configured before it for other commands.
+pub(crate) const NUSHELL_COMPLETIONS: &str = r#"let volta_previous_completer = $env.config.completions.external.completer?
+
+$env.config.completions.external.enable = true
+$env.config.completions.external.completer = {|spans|
+    if ($spans | first) == "volta" {
+        let suggestions = (^volta __complete ...($spans | skip 1) | lines)
+        if ($suggestions | is-empty) { null } else { $suggestions }
+    } else if $volta_previous_completer != null {
+        do $volta_previous_completer $spans
+    }
+}
+"#;
+
+/// Returns the script that asks `volta __complete` for versions and package names, for the
+/// shells that can call it
+///
+/// Anything it doesn't suggest is left to the static completions generated above.
+fn dynamic_completions(shell: Shell) -> &'static str {
+    match shell {
+        Shell::Bash => {
+            r#"
+_volta_dynamic() {
+    local suggestions
+    suggestions=$(volta __complete "${COMP_WORDS[@]:1:COMP_CWORD}" 2>/dev/null)
+    if [[ -n "$suggestions" ]]; then
+        COMPREPLY=( $(compgen -W "$suggestions" -- "${COMP_WORDS[COMP_CWORD]}") )
+    else
+        _volta "$@"
+    fi
+}
+
+complete -F _volta_dynamic -o bashdefault -o default volta
+"#
+        }
+        Shell::Fish => {
+            r#"
+complete -c volta -n "__fish_seen_subcommand_from fetch install pin uninstall upgrade which run" -f -a "(volta __complete (commandline -opc)[2..-1] (commandline -ct) 2>/dev/null)"
+"#
+        }
+        _ => "",
+    }
+}
@@ -0,0 +1,64 @@
+use semver::Version;
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::platform::{Platform, Sourced};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::note_prefix;
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct Current {
+    /// Prints the active versions on one line, for shell prompts and scripts
+    #[structopt(long = "porcelain")]
+    porcelain: bool,
+}
+
+impl Command for Current {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Current);
+
+        // Only the pins are read, nothing is fetched, so that this is fast enough for prompts
+        let platform = Platform::current(session)?;
+        let tools = match &platform {
+            Some(platform) => active_tools(platform),
+            None => Vec::new(),
+        };
+
+        if self.porcelain {
+            // Without an active platform nothing is printed, not even an empty line
+            if !tools.is_empty() {
+                let line: Vec<String> = tools
+                    .iter()
+                    .map(|(tool, version)| format!("{}@{}:{}", tool, version.value, version.source))
+                    .collect();
+                println!("{}", line.join(" "));
+            }
+        } else if tools.is_empty() {
+            println!(
+                "{} No Node version is active. Use `volta install node` to set a default.",
+                note_prefix()
+            );
+        } else {
+            for (tool, version) in tools {
+                println!("{}: v{} ({})", tool, version.value, version.source);
+            }
+        }
+
+        session.add_event_end(ActivityKind::Current, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Lists the tools of the platform, with a custom npm only if there is one
+fn active_tools(platform: &Platform) -> Vec<(&'static str, Sourced<&Version>)> {
+    let mut tools = vec![("node", platform.node.as_ref())];
+    if let Some(npm) = &platform.npm {
+        tools.push(("npm", npm.as_ref()));
+    }
+    if let Some(yarn) = &platform.yarn {
+        tools.push(("yarn", yarn.as_ref()));
+    }
+    tools
+}
@@ -0,0 +1,90 @@
+use log::{info, warn};
+use structopt::StructOpt;
+
+use volta_core::conflicts::{path_conflicts, shims_on_path, Conflict};
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::layout::volta_home;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::shim::{self, ShimProblem};
+use volta_core::style::success_prefix;
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct Doctor {}
+
+impl Command for Doctor {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Doctor);
+
+        let mut problems = 0;
+
+        if !shims_on_path()? {
+            warn!(
+                "Volta's shims ({}) are not on your PATH.
+Run `volta setup` to add them, and then open a new terminal.",
+                volta_home()?.shim_dir().display()
+            );
+            problems += 1;
+        }
+
+        let conflicts = path_conflicts()?;
+        report_conflicts(&conflicts);
+        problems += conflicts.len();
+
+        let shim_problems = shim::verify()?;
+        report_shim_problems(&shim_problems);
+        problems += shim_problems.len();
+
+        let exit_code = if problems == 0 {
+            info!("{} No problems found.", success_prefix());
+            ExitCode::Success
+        } else {
+            ExitCode::EnvironmentError
+        };
+
+        session.add_event_end(ActivityKind::Doctor, exit_code);
+        Ok(exit_code)
+    }
+}
+
+/// Warns about each directory on the PATH that takes precedence over Volta's shims
+pub(crate) fn report_conflicts(conflicts: &[Conflict]) {
+    for conflict in conflicts {
+        warn!(
+            "{} from {} in {} will be used instead of Volta's shims, so the versions that Volta chooses are ignored.
+{}",
+            conflict.tools.join(", "),
+            conflict.manager,
+            conflict.dir.display(),
+            conflict.fix()
+        );
+    }
+}
+
+/// Warns about each shim that no longer runs the shim executable it was generated for
+fn report_shim_problems(problems: &[ShimProblem]) {
+    const REGENERATE: &str = "Run `volta setup` to regenerate the shims.";
+
+    for problem in problems {
+        match problem {
+            ShimProblem::Replaced(shim) => warn!(
+                "The shim {} is not a link to Volta's shim executable, so it may have been replaced.
+{}",
+                shim.display(),
+                REGENERATE
+            ),
+            ShimProblem::Stale(shim) => warn!(
+                "The shim {} links to a different executable, e.g. from an older or moved install of Volta.
+{}",
+                shim.display(),
+                REGENERATE
+            ),
+            ShimProblem::ExecutableChanged(executable) => warn!(
+                "The shim executable {} has changed since the shims were generated.
+If you didn't upgrade Volta, reinstall it. Otherwise, run `volta setup` to regenerate the shims.",
+                executable.display()
+            ),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use std::env;
+use std::ffi::OsStr;
+use std::path::Path;
+
+use atty::Stream;
+use log::warn;
+use structopt::StructOpt;
+
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::layout::volta_home;
+use volta_core::platform::Platform;
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::Command;
+
+/// The shells that `volta env` and `volta use` can write statements for
+pub(crate) const SHELLS: &[&str] = &["bash", "zsh", "sh", "fish", "powershell", "nu"];
+
+#[derive(StructOpt)]
+pub(crate) struct Env {
+    /// Shell to write the statements for (defaults to the value of `SHELL`)
+    #[structopt(
+        long = "shell",
+        raw(possible_values = "SHELLS"),
+        case_insensitive = true
+    )]
+    shell: Option<String>,
+}
+
+impl Command for Env {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Env);
+
+        let shell = Shell::from_option(self.shell.as_deref());
+
+        let image = Platform::current(session)?
+            .ok_or(ErrorKind::NoPlatform)?
+            .checkout(session)?;
+        let path = image.path_without_shims()?;
+        let home = volta_home()?;
+
+        println!("{}", shell.export("VOLTA_HOME", home.root().as_os_str()));
+        println!("{}", shell.export_path(&path));
+
+        session.add_event_end(ActivityKind::Env, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+pub(crate) enum Shell {
+    Posix,
+    Fish,
+    PowerShell,
+    Nushell,
+}
+
+impl Shell {
+    /// Picks the shell named by a `--shell` option, or detects it if the option wasn't given
+    pub(crate) fn from_option(name: Option<&str>) -> Self {
+        match name {
+            Some(name) => Shell::from_name(&name.to_lowercase()).unwrap_or(Shell::Posix),
+            None => Shell::detect(),
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "bash" | "zsh" | "sh" => Some(Shell::Posix),
+            "fish" => Some(Shell::Fish),
+            "powershell" | "pwsh" => Some(Shell::PowerShell),
+            "nu" => Some(Shell::Nushell),
+            _ => None,
+        }
+    }
+
+    /// Determines the shell from the `SHELL` environment variable, falling back to POSIX syntax
+    /// (or PowerShell on Windows, where `SHELL` is usually not set)
+    fn detect() -> Self {
+        let shell = env::var_os("SHELL").unwrap_or_default();
+        let name = Path::new(&shell)
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_lowercase();
+        Shell::from_name(&name).unwrap_or(if cfg!(windows) {
+            Shell::PowerShell
+        } else {
+            Shell::Posix
+        })
+    }
+
+    /// Formats a statement that sets and exports an environment variable
+    pub(crate) fn export(&self, name: &str, value: &OsStr) -> String {
+        let value = value.to_string_lossy();
+        match self {
+            Shell::Posix => format!("export {}={}", name, posix_quote(&value)),
+            Shell::Fish => format!("set -gx {} {}", name, fish_quote(&value)),
+            Shell::PowerShell => format!("$env:{} = '{}'", name, value.replace('\'', "''")),
+            Shell::Nushell => format!("$env.{} = {}", name, nu_quote(&value)),
+        }
+    }
+
+    /// Formats a statement that removes an environment variable
+    pub(crate) fn unset(&self, name: &str) -> String {
+        match self {
+            Shell::Posix => format!("unset {}", name),
+            Shell::Fish => format!("set -e {}", name),
+            Shell::PowerShell => format!("Remove-Item Env:{} -ErrorAction SilentlyContinue", name),
+            Shell::Nushell => format!("hide-env --ignore-errors {}", name),
+        }
+    }
+
+    /// Formats a statement that sets and exports `PATH`
+    ///
+    /// Fish and Nushell treat `PATH` as a list, so each directory is a separate element there
+    pub(crate) fn export_path(&self, path: &OsStr) -> String {
+        match self {
+            Shell::Fish => {
+                let dirs: Vec<String> = env::split_paths(path)
+                    .map(|dir| fish_quote(&dir.to_string_lossy()))
+                    .collect();
+                format!("set -gx PATH {}", dirs.join(" "))
+            }
+            Shell::Nushell => {
+                let dirs: Vec<String> = env::split_paths(path)
+                    .map(|dir| nu_quote(&dir.to_string_lossy()))
+                    .collect();
+                format!("$env.PATH = [{}]", dirs.join(", "))
+            }
+            _ => self.export("PATH", path),
+        }
+    }
+}
+
+/// Reminds the user that the statements printed by a command only take effect once their shell
+/// runs them, if they ran the command directly in a terminal
+pub(crate) fn warn_if_not_evaluated(command: &str) {
+    if atty::is(Stream::Stdout) {
+        warn!(
+            "`volta {command}` only prints statements, which your shell has to run.
+Use `eval \"$(volta {command})\"`, or `volta {command} | Invoke-Expression` in PowerShell.",
+            command = command
+        );
+    }
+}
+
+fn posix_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+fn fish_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', r"\\").replace('\'', r"\'"))
+}
+
+fn nu_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', r"\\").replace('"', r#"\""#))
+}
@@ -0,0 +1,52 @@
+use log::info;
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::evict;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{note_prefix, success_prefix};
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct EvictUnused {
+    /// Removes the versions, rather than only listing them
+    #[structopt(long = "yes", short = "y")]
+    yes: bool,
+}
+
+impl Command for EvictUnused {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::EvictUnused);
+
+        let unused = evict::unused_versions(session)?;
+        if unused.is_empty() {
+            info!("{} No versions are unused.", note_prefix());
+        } else if self.yes {
+            evict::evict(&unused)?;
+            for version in &unused {
+                info!(
+                    "{} removed unused {}@{}",
+                    success_prefix(),
+                    version.tool,
+                    version.version
+                );
+            }
+        } else {
+            for version in &unused {
+                println!(
+                    "{}@{} (unused for {} days)",
+                    version.tool, version.version, version.idle_days
+                );
+            }
+            info!(
+                "{} Projects that Volta hasn't run in recently may still need these versions. \
+                 Run `volta evict-unused --yes` to remove them.",
+                note_prefix()
+            );
+        }
+
+        session.add_event_end(ActivityKind::EvictUnused, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
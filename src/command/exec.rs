@@ -0,0 +1,47 @@
+use std::ffi::OsString;
+
+use crate::command::Command;
+use crate::common::{Error, IntoResult};
+use structopt::StructOpt;
+use volta_core::error::{report_error, ExitCode, Fallible};
+use volta_core::run::execute_ephemeral;
+use volta_core::session::{ActivityKind, Session};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct Exec {
+    /// The executable to run, if the package provides more than one
+    #[structopt(long = "bin", value_name = "name")]
+    bin: Option<String>,
+
+    /// The package to run, with an optional version (e.g. `create-react-app` or `cowsay@1.4`)
+    package: String,
+
+    #[structopt(parse(from_os_str))]
+    /// Arguments to pass to the executable
+    args: Vec<OsString>,
+}
+
+impl Command for Exec {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Exec);
+
+        match execute_ephemeral(&self.package, self.bin.as_deref(), &self.args, session)
+            .into_result()
+        {
+            Ok(()) => {
+                session.add_event_end(ActivityKind::Exec, ExitCode::Success);
+                Ok(ExitCode::Success)
+            }
+            Err(Error::Tool(code)) => {
+                session.add_event_tool_end(ActivityKind::Exec, code);
+                Ok(ExitCode::ExecutionFailure)
+            }
+            Err(Error::Volta(err)) => {
+                report_error(env!("CARGO_PKG_VERSION"), &err);
+                session.add_event_error(ActivityKind::Exec, &err);
+                session.add_event_end(ActivityKind::Exec, err.exit_code());
+                Ok(err.exit_code())
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use std::ffi::OsString;
+
+use crate::command::Command;
+use crate::common::{Error, IntoResult};
+use structopt::StructOpt;
+use volta_core::error::{report_error, ExitCode, Fallible};
+use volta_core::run::execute_command;
+use volta_core::session::{ActivityKind, Session};
+
+#[derive(Debug, StructOpt)]
+pub(crate) struct ExecCommand {
+    #[structopt(parse(from_os_str))]
+    /// The command to run
+    command: OsString,
+
+    #[structopt(parse(from_os_str))]
+    /// Arguments to pass to the command
+    args: Vec<OsString>,
+}
+
+impl Command for ExecCommand {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::ExecCommand);
+
+        match execute_command(&self.command, &self.args, session).into_result() {
+            Ok(()) => {
+                session.add_event_end(ActivityKind::ExecCommand, ExitCode::Success);
+                Ok(ExitCode::Success)
+            }
+            Err(Error::Tool(code)) => {
+                session.add_event_tool_end(ActivityKind::ExecCommand, code);
+                Ok(ExitCode::ExecutionFailure)
+            }
+            Err(Error::Volta(err)) => {
+                report_error(env!("CARGO_PKG_VERSION"), &err);
+                session.add_event_error(ActivityKind::ExecCommand, &err);
+                session.add_event_end(ActivityKind::ExecCommand, err.exit_code());
+                Ok(err.exit_code())
+            }
+        }
+    }
+}
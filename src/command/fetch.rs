@@ -1,6 +1,6 @@
 use structopt::StructOpt;
 
-use volta_core::error::{ExitCode, Fallible};
+use volta_core::error::{report_error, ExitCode, Fallible};
 use volta_core::session::{ActivityKind, Session};
 use volta_core::tool;
 
@@ -9,19 +9,36 @@ use crate::command::Command;
 #[derive(StructOpt)]
 pub(crate) struct Fetch {
     /// Tools to fetch, like `node`, `yarn@latest` or `your-package@^14.4.3`.
+    ///
+    /// Fetched tools are only downloaded and unpacked: the default toolchain and any pins are left
+    /// as they are, so this can be used to prepare for working offline.
     #[structopt(name = "tool[@version]", required = true, min_values = 1)]
     tools: Vec<String>,
+
+    /// Allows version ranges to match prerelease versions, like `node@20 --include-prerelease`
+    #[structopt(long = "include-prerelease")]
+    include_prerelease: bool,
 }
 
 impl Command for Fetch {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::Fetch);
+        session.set_include_prerelease(self.include_prerelease);
 
+        // A failure is reported without stopping, so that one unavailable version doesn't keep
+        // the others from being fetched
+        let mut exit_code = ExitCode::Success;
         for tool in tool::Spec::from_strings(&self.tools, "fetch")? {
-            tool.resolve(session)?.fetch(session)?;
+            if let Err(err) = tool.resolve(session).and_then(|tool| tool.fetch(session)) {
+                report_error(env!("CARGO_PKG_VERSION"), &err);
+                session.add_event_error(ActivityKind::Fetch, &err);
+                if let ExitCode::Success = exit_code {
+                    exit_code = err.exit_code();
+                }
+            }
         }
 
-        session.add_event_end(ActivityKind::Fetch, ExitCode::Success);
-        Ok(ExitCode::Success)
+        session.add_event_end(ActivityKind::Fetch, exit_code);
+        Ok(exit_code)
     }
 }
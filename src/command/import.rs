@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs::read_to_string;
+use std::path::{Path, PathBuf};
+use std::process::Command as ProcessCommand;
+
+use log::info;
+use semver::Version;
+use structopt::StructOpt;
+
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::fs::read_dir_eager;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{note_prefix, success_prefix, tool_version};
+use volta_core::sync::VoltaLock;
+use volta_core::tool::node;
+
+use crate::command::Command;
+
+/// The version managers that Node versions can be imported from
+const MANAGERS: &[&str] = &["nvm", "nodenv", "n"];
+
+/// How many aliases can point to other aliases before the default alias is given up on
+const MAX_ALIAS_DEPTH: usize = 8;
+
+#[derive(StructOpt)]
+pub(crate) struct Import {
+    /// The version manager to import from
+    #[structopt(raw(possible_values = "MANAGERS"), case_insensitive = true)]
+    manager: String,
+
+    /// Hard links the files of each version instead of copying them, where possible
+    #[structopt(long = "link")]
+    link: bool,
+}
+
+impl Command for Import {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Import);
+
+        let manager = Manager::from_name(&self.manager.to_lowercase())
+            .expect("Only the names of managers are accepted");
+        let root = manager.root()?;
+        if !root.is_dir() {
+            return Err(ErrorKind::ImportSourceNotFound {
+                manager: manager.name().into(),
+                dir: root,
+            }
+            .into());
+        }
+
+        let installations = manager.installations(&root);
+        if installations.is_empty() {
+            info!(
+                "{} No Node versions were found in {}",
+                note_prefix(),
+                root.display()
+            );
+            session.add_event_end(ActivityKind::Import, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
+        import_versions(&manager, &root, &installations, self.link, session)?;
+
+        session.add_event_end(ActivityKind::Import, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Imports the installed Node versions of a manager, making its default version the default
+pub(crate) fn import_versions(
+    manager: &Manager,
+    root: &Path,
+    installations: &BTreeMap<Version, PathBuf>,
+    link: bool,
+    session: &mut Session,
+) -> Fallible<()> {
+    let _lock = VoltaLock::acquire();
+    for (version, dir) in installations {
+        match node::import_installation(version, dir, link)? {
+            Some(imported) => info!(
+                "{} imported {} (with {})",
+                success_prefix(),
+                tool_version("node", &imported.runtime),
+                tool_version("npm", &imported.npm)
+            ),
+            None => info!(
+                "{} {} is already installed",
+                note_prefix(),
+                tool_version("node", version)
+            ),
+        }
+    }
+
+    if let Some(default) = manager.default_version(root, installations) {
+        session.toolchain_mut()?.set_active_node(&default)?;
+        info!(
+            "{} set {} as your default (it was the default in {})",
+            success_prefix(),
+            tool_version("node", &default),
+            manager.name()
+        );
+    }
+
+    Ok(())
+}
+
+pub(crate) enum Manager {
+    Nvm,
+    Nodenv,
+    N,
+}
+
+impl Manager {
+    /// Every manager that can be imported from, in the order they are checked for
+    pub(crate) const ALL: [Manager; 3] = [Manager::Nvm, Manager::Nodenv, Manager::N];
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nvm" => Some(Manager::Nvm),
+            "nodenv" => Some(Manager::Nodenv),
+            "n" => Some(Manager::N),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            Manager::Nvm => "nvm",
+            Manager::Nodenv => "nodenv",
+            Manager::N => "n",
+        }
+    }
+
+    /// Determines the directory that the manager keeps its versions in
+    pub(crate) fn root(&self) -> Fallible<PathBuf> {
+        match self {
+            Manager::Nvm => env_dir("NVM_DIR", ".nvm"),
+            Manager::Nodenv => env_dir("NODENV_ROOT", ".nodenv"),
+            // n keeps its cache of versions inside the prefix that it installs into
+            Manager::N => Ok(env::var_os("N_PREFIX")
+                .filter(|dir| !dir.is_empty())
+                .map_or_else(|| PathBuf::from("/usr/local"), PathBuf::from)),
+        }
+    }
+
+    /// Lists the installed Node versions, with the directory of each one
+    pub(crate) fn installations(&self, root: &Path) -> BTreeMap<Version, PathBuf> {
+        match self {
+            Manager::Nvm => version_dirs(&root.join("versions").join("node")),
+            Manager::Nodenv => version_dirs(&root.join("versions")),
+            Manager::N => version_dirs(&root.join("n").join("versions").join("node")),
+        }
+    }
+
+    /// Determines the manager's default version, if it is one of the installed versions
+    fn default_version(
+        &self,
+        root: &Path,
+        installations: &BTreeMap<Version, PathBuf>,
+    ) -> Option<Version> {
+        match self {
+            Manager::Nvm => {
+                // Aliases are files that contain a version or the name of another alias, e.g.
+                // `default` contains `lts/*`, which contains `lts/hydrogen`
+                let mut alias = String::from("default");
+                for _ in 0..MAX_ALIAS_DEPTH {
+                    if let Some(version) = find_installed(installations, &alias) {
+                        return Some(version);
+                    }
+                    alias = read_to_string(root.join("alias").join(&alias))
+                        .ok()?
+                        .trim()
+                        .to_string();
+                }
+                None
+            }
+            // The global version is in the `version` file, unless it is `system`
+            Manager::Nodenv => {
+                let version = read_to_string(root.join("version")).ok()?;
+                find_installed(installations, version.trim())
+            }
+            // n doesn't record the active version, it is whichever one was copied into the prefix
+            Manager::N => {
+                let output = ProcessCommand::new(root.join("bin").join("node"))
+                    .arg("--version")
+                    .output()
+                    .ok()?;
+                let version = String::from_utf8_lossy(&output.stdout);
+                find_installed(installations, version.trim())
+            }
+        }
+    }
+}
+
+/// Returns the directory from the given environment variable, or from the home directory
+fn env_dir(var: &str, home_relative: &str) -> Fallible<PathBuf> {
+    match env::var_os(var).filter(|dir| !dir.is_empty()) {
+        Some(dir) => Ok(PathBuf::from(dir)),
+        None => dirs::home_dir()
+            .map(|home| home.join(home_relative))
+            .ok_or_else(|| ErrorKind::NoHomeEnvironmentVar.into()),
+    }
+}
+
+/// Lists the subdirectories of a directory that are named after versions (with or without a `v`)
+fn version_dirs(dir: &Path) -> BTreeMap<Version, PathBuf> {
+    let entries = match read_dir_eager(dir) {
+        Ok(entries) => entries,
+        Err(_) => return BTreeMap::new(),
+    };
+
+    entries
+        .filter(|(_, metadata)| metadata.is_dir())
+        .filter_map(|(entry, _)| {
+            let name = entry.file_name();
+            let version = Version::parse(name.to_string_lossy().trim_start_matches('v')).ok()?;
+            Some((version, entry.path()))
+        })
+        .collect()
+}
+
+/// Finds the newest installed version that matches a version or version prefix (like `16` or
+/// `v16.3`), where `node` and `stable` match the newest version of all
+fn find_installed(installations: &BTreeMap<Version, PathBuf>, name: &str) -> Option<Version> {
+    let name = name.trim_start_matches('v');
+    let prefix = format!("{}.", name);
+
+    installations
+        .keys()
+        .rev()
+        .find(|version| {
+            let version = version.to_string();
+            matches!(name, "node" | "stable") || version == name || version.starts_with(&prefix)
+        })
+        .cloned()
+}
@@ -0,0 +1,173 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::path::PathBuf;
+
+use semver::Version;
+use serde::Serialize;
+use structopt::StructOpt;
+
+use volta_core::error::{Context, ErrorKind, ExitCode, Fallible};
+use volta_core::inventory::{node_available, npm_available, yarn_available};
+use volta_core::layout::volta_home;
+use volta_core::platform::{Platform, Sourced};
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::Command;
+
+/// The version of the document format, which changes only when existing fields change meaning
+/// or are removed (new fields may be added at any time)
+const FORMAT_VERSION: u32 = 1;
+
+#[derive(StructOpt)]
+pub(crate) struct Inspect {
+    /// The directory to inspect (defaults to the current directory)
+    #[structopt(parse(from_os_str))]
+    dir: Option<PathBuf>,
+}
+
+impl Command for Inspect {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Inspect);
+
+        let dir = match self.dir {
+            Some(dir) => {
+                if !dir.is_dir() {
+                    return Err(ErrorKind::DirectoryNotFound { dir }.into());
+                }
+                session.use_project_dir(dir.clone())?;
+                dir
+            }
+            None => env::current_dir().with_context(|| ErrorKind::CurrentDirError)?,
+        };
+
+        let document = Document::build(dir, session)?;
+        // Serializing plain strings and maps can't fail
+        let json = serde_json::to_string_pretty(&document).expect("Document is serializable");
+        println!("{}", json);
+
+        session.add_event_end(ActivityKind::Inspect, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Describes the toolchain that Volta uses in a directory
+///
+/// Nothing is fetched, so the binaries of tools that aren't installed yet may not exist.
+#[derive(Serialize)]
+struct Document {
+    version: u32,
+    directory: PathBuf,
+    project: Option<ProjectInfo>,
+    platform: Option<PlatformInfo>,
+    shims: ShimInfo,
+}
+
+#[derive(Serialize)]
+struct ProjectInfo {
+    root: PathBuf,
+    manifest: PathBuf,
+    /// The package tools pinned in `volta.tools`, by name
+    tools: BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct PlatformInfo {
+    node: ToolInfo,
+    /// `None` when the npm bundled with Node is used
+    npm: Option<ToolInfo>,
+    yarn: Option<ToolInfo>,
+}
+
+#[derive(Serialize)]
+struct ToolInfo {
+    version: String,
+    source: String,
+    installed: bool,
+    bin_dir: PathBuf,
+}
+
+#[derive(Serialize)]
+struct ShimInfo {
+    dir: PathBuf,
+    node: PathBuf,
+    npm: PathBuf,
+    yarn: PathBuf,
+}
+
+impl Document {
+    fn build(directory: PathBuf, session: &mut Session) -> Fallible<Self> {
+        let platform = match Platform::current(session)? {
+            Some(platform) => Some(PlatformInfo::new(&platform)?),
+            None => None,
+        };
+
+        let project = session.project()?.map(|project| ProjectInfo {
+            root: project.root_dir().to_owned(),
+            manifest: project.manifest_file().to_owned(),
+            tools: project.pinned_tools().clone(),
+        });
+
+        let home = volta_home()?;
+        let shims = ShimInfo {
+            dir: home.shim_dir().to_owned(),
+            node: home.shim_file("node"),
+            npm: home.shim_file("npm"),
+            yarn: home.shim_file("yarn"),
+        };
+
+        Ok(Document {
+            version: FORMAT_VERSION,
+            directory,
+            project,
+            platform,
+            shims,
+        })
+    }
+}
+
+impl PlatformInfo {
+    fn new(platform: &Platform) -> Fallible<Self> {
+        let home = volta_home()?;
+
+        let node = ToolInfo::new(platform.node.as_ref(), node_available, |version| {
+            home.node_image_bin_dir(version)
+        })?;
+        let npm = platform
+            .npm
+            .as_ref()
+            .map(|npm| {
+                ToolInfo::new(npm.as_ref(), npm_available, |version| {
+                    home.npm_image_bin_dir(version)
+                })
+            })
+            .transpose()?;
+        let yarn = platform
+            .yarn
+            .as_ref()
+            .map(|yarn| {
+                ToolInfo::new(yarn.as_ref(), yarn_available, |version| {
+                    home.yarn_image_bin_dir(version)
+                })
+            })
+            .transpose()?;
+
+        Ok(PlatformInfo { node, npm, yarn })
+    }
+}
+
+impl ToolInfo {
+    fn new<A, B>(version: Sourced<&Version>, available: A, bin_dir: B) -> Fallible<Self>
+    where
+        A: FnOnce(&Version) -> Fallible<bool>,
+        B: FnOnce(&str) -> PathBuf,
+    {
+        let version_str = version.value.to_string();
+
+        Ok(ToolInfo {
+            installed: available(version.value)?,
+            bin_dir: bin_dir(&version_str),
+            source: version.source.to_string(),
+            version: version_str,
+        })
+    }
+}
@@ -1,8 +1,11 @@
+use std::path::PathBuf;
+
 use structopt::StructOpt;
 
-use volta_core::error::{ExitCode, Fallible};
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::github;
 use volta_core::session::{ActivityKind, Session};
-use volta_core::tool::Spec;
+use volta_core::tool::{node, Node, Spec};
 
 use crate::command::Command;
 
@@ -11,14 +14,57 @@ pub(crate) struct Install {
     /// Tools to install, like `node`, `yarn@latest` or `your-package@^14.4.3`.
     #[structopt(name = "tool[@version]", required = true, min_values = 1)]
     tools: Vec<String>,
+
+    /// Allows version ranges to match prerelease versions, like `node@20 --include-prerelease`
+    #[structopt(long = "include-prerelease")]
+    include_prerelease: bool,
+
+    /// Lets packages take over executables that are provided by another installed package
+    #[structopt(long = "force")]
+    force: bool,
+
+    /// Unpacks Node into a prefix (like `/usr/local`) instead, without shims or Volta state
+    #[structopt(long = "dest", value_name = "prefix", parse(from_os_str))]
+    dest: Option<PathBuf>,
 }
 
 impl Command for Install {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::Install);
+        session.set_include_prerelease(self.include_prerelease);
+        session.set_replace_bins(self.force);
+
+        let tools = Spec::from_strings(&self.tools, "install")?;
+
+        match &self.dest {
+            Some(dest) => {
+                // Everything is checked first, so that nothing is installed if any can't be
+                let mut versions = Vec::new();
+                for tool in tools {
+                    match tool {
+                        Spec::Node(version) => versions.push(version),
+                        tool => {
+                            return Err(ErrorKind::PrefixInstallUnsupported {
+                                tool: tool.name().into(),
+                            }
+                            .into())
+                        }
+                    }
+                }
+
+                for version in versions {
+                    Node::new(node::resolve(version, session)?).install_to_prefix(dest, session)?;
+                }
+            }
+            None => {
+                for tool in tools {
+                    tool.resolve(session)?.install(session)?;
+                }
 
-        for tool in Spec::from_strings(&self.tools, "install")? {
-            tool.resolve(session)?.install(session)?;
+                if github::is_github_actions() {
+                    github::prepare_job(session)?;
+                }
+            }
         }
 
         session.add_event_end(ActivityKind::Install, ExitCode::Success);
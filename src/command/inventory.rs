@@ -0,0 +1,118 @@
+use std::path::PathBuf;
+
+use log::info;
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::inventory_archive;
+use volta_core::layout::volta_home;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::shim::regenerate_shims_for_dir;
+use volta_core::style::success_prefix;
+use volta_core::tool::Spec;
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) enum Inventory {
+    /// Writes fetched tools to an archive, for importing on another machine
+    #[structopt(
+        name = "export",
+        author = "",
+        version = "",
+        long_about = "Writes fetched tools to an archive, for importing on another machine
+
+The archive holds the unpacked files of each tool and the distro it was fetched
+from, with a manifest of their checksums. Without any tools, every fetched
+version of Node, npm, and Yarn is exported.
+
+The Node binaries only run on machines with the same OS and architecture. Use
+`volta inventory import` to add the tools on the other machine."
+    )]
+    Export(ExportInventory),
+
+    /// Adds the tools in an archive written by `volta inventory export`
+    #[structopt(
+        name = "import",
+        author = "",
+        version = "",
+        long_about = "Adds the tools in an archive written by `volta inventory export`
+
+Every file is checked against the checksums in the archive before anything is
+added, so a damaged archive leaves the inventory unchanged. Tools that were
+already fetched are skipped.
+
+The tools are only fetched: use `volta install` to make one your default."
+    )]
+    Import(ImportInventory),
+}
+
+impl Command for Inventory {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        match self {
+            Inventory::Export(export) => export.run(session),
+            Inventory::Import(import) => import.run(session),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+pub(crate) struct ExportInventory {
+    /// File to write the archive to
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+
+    /// Tools to export, like `node`, `node@18`, or `yarn@1.22.19`
+    #[structopt(name = "tool[@version]")]
+    tools: Vec<String>,
+}
+
+impl Command for ExportInventory {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Inventory);
+
+        let specs = Spec::from_strings(&self.tools, "inventory export")?;
+        let images = inventory_archive::export(&self.file, &specs)?;
+        for image in &images {
+            info!("{} exported {}", success_prefix(), image);
+        }
+        info!(
+            "{} wrote inventory archive to {}",
+            success_prefix(),
+            self.file.display()
+        );
+
+        session.add_event_end(ActivityKind::Inventory, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+#[derive(StructOpt)]
+pub(crate) struct ImportInventory {
+    /// The archive written by `volta inventory export`
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+}
+
+impl Command for ImportInventory {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Inventory);
+
+        let images = inventory_archive::import(&self.file)?;
+        // The shims for Node and the package managers may not exist yet on a new machine
+        regenerate_shims_for_dir(volta_home()?.shim_dir())?;
+
+        for image in &images {
+            info!("{} imported {}", success_prefix(), image);
+        }
+        if images.is_empty() {
+            info!(
+                "All of the tools in {} have already been fetched",
+                self.file.display()
+            );
+        }
+
+        session.add_event_end(ActivityKind::Inventory, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
@@ -2,7 +2,10 @@
 
 use std::collections::BTreeMap;
 
-use super::{Node, Package, PackageManager, PackageManagerKind, Toolchain};
+use super::{
+    format_installed_with, format_usage_date, Node, Package, PackageManager, PackageManagerKind,
+    RemoteNode, ToolUsage, Toolchain, UNUSED_DAYS,
+};
 use lazy_static::lazy_static;
 use textwrap::{fill, Options};
 use volta_core::style::{text_width, tool_version, MAX_WIDTH};
@@ -23,6 +26,8 @@ pub(super) fn format(toolchain: &Toolchain) -> Option<String> {
     // indentation
     Some(match toolchain {
         Toolchain::Node(runtimes) => display_node(runtimes),
+        Toolchain::Remote(runtimes) => display_remote(runtimes),
+        Toolchain::Usage(tools) => display_usage(tools),
         Toolchain::Active {
             runtime,
             package_managers,
@@ -120,6 +125,55 @@ fn display_node(runtimes: &[Node]) -> String {
     }
 }
 
+/// Format the Node versions that are available to fetch, for `volta list node --remote`
+fn display_remote(runtimes: &[RemoteNode]) -> String {
+    if runtimes.is_empty() {
+        "⚡️ No Node versions available to fetch match the request.".to_string()
+    } else {
+        let versions = wrap(
+            runtimes
+                .iter()
+                .map(format_remote_runtime)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        );
+        format!("⚡️ Node versions available to fetch:\n\n{}", versions)
+    }
+}
+
+/// Format the fetched tools with when they were installed and last used, for `volta list --usage`
+fn display_usage(tools: &[ToolUsage]) -> String {
+    if tools.is_empty() {
+        return "⚡️ No Node, npm, or Yarn versions installed.".to_string();
+    }
+
+    let versions = wrap(
+        tools
+            .iter()
+            .map(format_tool_usage)
+            .collect::<Vec<_>>()
+            .join("\n"),
+    );
+    let mut output = format!(
+        "⚡️ When your tools were installed and last used:\n\n{}",
+        versions
+    );
+
+    let unused = tools.iter().filter(|tool| tool.unused).count();
+    if unused > 0 {
+        output.push_str(&format!(
+            "\n\n{} {} not been used in the last {} days, and could be removed to free up disk space.",
+            unused,
+            if unused == 1 { "version has" } else { "versions have" },
+            UNUSED_DAYS
+        ));
+        output.push_str(
+            "\nSet `evictUnusedAfterDays` in your Volta settings, then run `volta evict-unused` to see which versions can be removed.",
+        );
+    }
+    output
+}
+
 /// Format a set of `Toolchain::PackageManager`s for `volta list npm`
 fn display_npms(managers: &[PackageManager]) -> String {
     if managers.is_empty() {
@@ -252,6 +306,41 @@ fn format_runtime(runtime: &Node) -> String {
     format!("v{}{}", runtime.version, runtime.source)
 }
 
+/// format a single version of `Toolchain::Remote`.
+fn format_remote_runtime(runtime: &RemoteNode) -> String {
+    let mut details = Vec::new();
+    if let Some(date) = &runtime.date {
+        details.push(format!("released {}", date));
+    }
+    if let Some(lts) = &runtime.lts {
+        details.push(format!("LTS: {}", lts));
+    }
+    if runtime.installed {
+        details.push("installed".to_string());
+    }
+
+    if details.is_empty() {
+        format!("v{}", runtime.version)
+    } else {
+        format!("v{} ({})", runtime.version, details.join(", "))
+    }
+}
+
+/// format a single version of `Toolchain::Usage`.
+fn format_tool_usage(tool: &ToolUsage) -> String {
+    let mut line = format!(
+        "{}{}: installed {}, last used {}",
+        tool_version(tool.tool, &tool.version),
+        tool.source,
+        format_usage_date(tool.installed, "unknown"),
+        format_usage_date(tool.last_used, "never")
+    );
+    if tool.unused {
+        line.push_str(" (unused)");
+    }
+    line
+}
+
 /// format a list of `Toolchain::PackageManager`s in condensed form
 fn format_package_manager_list_condensed(package_managers: &[PackageManager]) -> String {
     wrap(
@@ -327,8 +416,8 @@ fn format_package(package: &Package) -> String {
         Package::Default {
             details,
             node,
+            package_manager,
             tools,
-            ..
         } => {
             let tools = match tools.len() {
                 0 => String::from(""),
@@ -340,9 +429,7 @@ fn format_package(package: &Package) -> String {
             let platform_detail = wrap(format!(
                 "runtime: {}\npackage manager: {}",
                 tool_version("node", &node),
-                // TODO: Should be updated when we support installing with custom package_managers,
-                // whether Yarn or non-built-in versions of npm
-                "npm@built-in"
+                format_installed_with(package_manager)
             ));
             let platform = wrap(format!("platform:\n{}", platform_detail));
             format!("{}@{}\n{}\n{}", details.name, version, binaries, platform)
@@ -777,6 +864,7 @@ See options for more detailed reports by running `volta list --help`.";
                         version: Version::from((3, 0, 1)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["create-react-app".to_string()],
                 },
                 Package::Default {
@@ -785,6 +873,7 @@ See options for more detailed reports by running `volta list --help`.";
                         version: Version::from((3, 4, 3)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
                 },
             ];
@@ -836,6 +925,7 @@ See options for more detailed reports by running `volta list --help`.";
                         version: Version::from((3, 4, 3)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
                 },
             ];
@@ -1163,6 +1253,7 @@ See `volta help install` for details and more options.";
                     version: Version::from((3, 10, 1)),
                 },
                 node: NODE_12.clone(),
+                package_manager: None,
                 tools: vec!["ember".to_string()],
             }];
 
@@ -1245,6 +1336,7 @@ See `volta help install` for details and more options.";
                         version: Version::from((3, 10, 1)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["ember".to_string()],
                 },
                 Package::Project {
@@ -1289,6 +1381,7 @@ See `volta help install` for details and more options.";
                     version: Version::from((3, 10, 1)),
                 },
                 node: NODE_12.clone(),
+                package_manager: None,
                 tools: vec!["ember".to_string()],
             }];
 
@@ -1371,6 +1464,7 @@ See `volta help install` for details and more options.";
                         version: Version::from((3, 10, 1)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["ember".to_string()],
                 },
                 Package::Project {
@@ -1606,6 +1700,7 @@ See `volta help install` for details and more options.";
                         version: Version::from((3, 4, 3)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["tsc".to_string(), "tsserver".to_string()],
                 },
                 Package::Project {
@@ -1624,6 +1719,7 @@ See `volta help install` for details and more options.";
                         version: Version::from((3, 8, 2)),
                     },
                     node: NODE_12.clone(),
+                    package_manager: None,
                     tools: vec!["ember".to_string()],
                 },
             ];
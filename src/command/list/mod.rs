@@ -4,15 +4,18 @@ mod toolchain;
 
 use std::{fmt, path::PathBuf, str::FromStr};
 
+use chrono::{DateTime, Utc};
 use semver::Version;
 use structopt::StructOpt;
 
 use crate::command::Command;
 use toolchain::Toolchain;
-use volta_core::error::{ExitCode, Fallible};
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
 use volta_core::inventory::package_configs;
 use volta_core::project::Project;
 use volta_core::session::{ActivityKind, Session};
+use volta_core::style::tool_version;
+use volta_core::tool::package::PackageManager as InstallManager;
 use volta_core::tool::PackageConfig;
 
 #[derive(Copy, Clone, PartialEq)]
@@ -89,6 +92,9 @@ enum Package {
         details: PackageDetails,
         /// The version of Node the package is installed against.
         node: Version,
+        /// The package manager the package was installed with, or `None` for the npm bundled
+        /// with Node.
+        package_manager: Option<(PackageManagerKind, Version)>,
         /// The names of the tools associated with the package.
         tools: Vec<String>,
     },
@@ -112,6 +118,18 @@ impl Package {
             Source::Default => Package::Default {
                 details,
                 node: config.platform.node.clone(),
+                package_manager: match config.manager {
+                    InstallManager::Npm => config
+                        .platform
+                        .npm
+                        .clone()
+                        .map(|version| (PackageManagerKind::Npm, version)),
+                    InstallManager::Yarn => config
+                        .platform
+                        .yarn
+                        .clone()
+                        .map(|version| (PackageManagerKind::Yarn, version)),
+                },
                 tools: config.bins.clone(),
             },
             Source::Project(path) => Package::Project {
@@ -124,23 +142,21 @@ impl Package {
     }
 
     fn from_inventory_and_project(project: Option<&Project>) -> Fallible<Vec<Package>> {
-        package_configs().map(|configs| {
-            configs
-                .iter()
-                .map(|config| {
-                    let source = Self::source(&config.name, project);
-                    Package::new(config, &source)
-                })
-                .collect()
-        })
+        package_configs()?
+            .iter()
+            .map(|config| {
+                let source = Self::source(&config.name, project)?;
+                Ok(Package::new(config, &source))
+            })
+            .collect()
     }
 
-    fn source(name: &str, project: Option<&Project>) -> Source {
+    fn source(name: &str, project: Option<&Project>) -> Fallible<Source> {
         match project {
-            Some(project) if project.has_direct_dependency(name) => {
-                Source::Project(project.manifest_file().to_owned())
+            Some(project) if project.has_direct_dependency(name)? => {
+                Ok(Source::Project(project.manifest_file().to_owned()))
             }
-            _ => Source::Default,
+            _ => Ok(Source::Default),
         }
     }
 }
@@ -151,6 +167,34 @@ struct Node {
     pub version: Version,
 }
 
+/// A Node version that is available to fetch, for `volta list node --remote`
+struct RemoteNode {
+    pub version: Version,
+    /// The codename of the LTS line the version is part of, if any.
+    pub lts: Option<String>,
+    /// The date the version was released, if the index lists it.
+    pub date: Option<String>,
+    /// Whether the version has already been fetched.
+    pub installed: bool,
+}
+
+/// A fetched version of Node, npm, or Yarn, with when it was installed and last used, for
+/// `volta list --usage`
+struct ToolUsage {
+    /// The name of the tool: `node`, `npm`, or `yarn`.
+    pub tool: &'static str,
+    pub version: Version,
+    pub source: Source,
+    pub installed: Option<DateTime<Utc>>,
+    pub last_used: Option<DateTime<Utc>>,
+    /// Whether the version is neither the default nor the project's, and hasn't been used in
+    /// `UNUSED_DAYS` days, which makes it a candidate for removal.
+    pub unused: bool,
+}
+
+/// Versions that haven't been used in this many days are suggested for removal
+const UNUSED_DAYS: i64 = 90;
+
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum PackageManagerKind {
     Npm,
@@ -170,6 +214,22 @@ impl fmt::Display for PackageManagerKind {
     }
 }
 
+/// Format the package manager that a default package was installed with
+fn format_installed_with(package_manager: &Option<(PackageManagerKind, Version)>) -> String {
+    match package_manager {
+        Some((kind, version)) => tool_version(kind, version),
+        None => String::from("npm@built-in"),
+    }
+}
+
+/// Format a time from `volta list --usage` as a date, or `missing` if it isn't known
+fn format_usage_date(time: Option<DateTime<Utc>>, missing: &str) -> String {
+    match time {
+        Some(time) => time.format("%Y-%m-%d").to_string(),
+        None => missing.to_string(),
+    }
+}
+
 #[derive(Clone)]
 struct PackageManager {
     kind: PackageManagerKind,
@@ -220,6 +280,25 @@ pub(crate) struct List {
     /// Show your default tool(s).
     #[structopt(long = "default", short = "d", conflicts_with = "current")]
     default: bool,
+
+    /// Show the Node versions that are available to fetch, rather than the installed ones.
+    ///
+    /// Use a version range to narrow the list, like `volta list node@18 --remote`.
+    #[structopt(
+        long = "remote",
+        raw(conflicts_with_all = r#"&["current", "default"]"#)
+    )]
+    remote: bool,
+
+    /// Show when each Node, npm, and Yarn version was installed and last used.
+    ///
+    /// Versions that you haven't used in a while are marked, so you can see which ones could be
+    /// removed.
+    #[structopt(
+        long = "usage",
+        raw(conflicts_with_all = r#"&["current", "default", "remote"]"#)
+    )]
+    usage: bool,
 }
 
 /// Which tool should we look up?
@@ -268,18 +347,51 @@ impl List {
     fn subcommand(&self) -> Option<Subcommand> {
         self.subcommand.as_ref().map(|s| s.as_str().into())
     }
+
+    fn print(&self, toolchain: &Toolchain) {
+        let format = match self.output_format() {
+            Format::Human => human::format,
+            Format::Plain => plain::format,
+        };
+
+        if let Some(string) = format(toolchain) {
+            println!("{}", string)
+        };
+    }
 }
 
 impl Command for List {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::List);
 
+        if self.remote {
+            let tool = self.subcommand.as_deref().unwrap_or("node");
+            let toolchain = Toolchain::remote(tool, session)?;
+            self.print(&toolchain);
+
+            session.add_event_end(ActivityKind::List, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
+
         let project = session.project()?;
         let default_platform = session.default_platform()?;
-        let format = match self.output_format() {
-            Format::Human => human::format,
-            Format::Plain => plain::format,
-        };
+
+        if self.usage {
+            let tools: &[&'static str] = match self.subcommand() {
+                None | Some(Subcommand::All) => &["node", "npm", "yarn"],
+                Some(Subcommand::Node) => &["node"],
+                Some(Subcommand::Npm) => &["npm"],
+                Some(Subcommand::Yarn) => &["yarn"],
+                Some(Subcommand::PackageOrTool { name }) => {
+                    return Err(ErrorKind::UsageListUnsupported { tool: name }.into());
+                }
+            };
+            let toolchain = Toolchain::usage(tools, project, default_platform)?;
+            self.print(&toolchain);
+
+            session.add_event_end(ActivityKind::List, ExitCode::Success);
+            return Ok(ExitCode::Success);
+        }
 
         let filter = match (self.current, self.default) {
             (true, false) => Filter::Current,
@@ -300,9 +412,7 @@ impl Command for List {
             }
         };
 
-        if let Some(string) = format(&toolchain) {
-            println!("{}", string)
-        };
+        self.print(&toolchain);
 
         session.add_event_end(ActivityKind::List, ExitCode::Success);
         Ok(ExitCode::Success)
@@ -4,11 +4,16 @@ use semver::Version;
 
 use volta_core::style::tool_version;
 
-use super::{Node, Package, PackageManager, Source, Toolchain};
+use super::{
+    format_installed_with, format_usage_date, Node, Package, PackageManager, RemoteNode, Source,
+    ToolUsage, Toolchain,
+};
 
 pub(super) fn format(toolchain: &Toolchain) -> Option<String> {
     let (runtimes, package_managers, packages) = match toolchain {
         Toolchain::Node(runtimes) => (describe_runtimes(runtimes), None, None),
+        Toolchain::Remote(runtimes) => (describe_remote_runtimes(runtimes), None, None),
+        Toolchain::Usage(tools) => (describe_usage(tools), None, None),
         Toolchain::PackageManagers { managers, .. } => {
             (None, describe_package_managers(managers), None)
         }
@@ -71,6 +76,34 @@ fn describe_runtimes(runtimes: &[Node]) -> Option<String> {
     }
 }
 
+fn describe_remote_runtimes(runtimes: &[RemoteNode]) -> Option<String> {
+    if runtimes.is_empty() {
+        None
+    } else {
+        Some(
+            runtimes
+                .iter()
+                .map(display_remote_node)
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    }
+}
+
+fn describe_usage(tools: &[ToolUsage]) -> Option<String> {
+    if tools.is_empty() {
+        None
+    } else {
+        Some(
+            tools
+                .iter()
+                .map(display_tool_usage)
+                .collect::<Vec<String>>()
+                .join("\n"),
+        )
+    }
+}
+
 fn describe_package_managers(package_managers: &[PackageManager]) -> Option<String> {
     if package_managers.is_empty() {
         None
@@ -111,6 +144,39 @@ fn display_node(source: &Source, version: &Version) -> String {
     format!("runtime {}{}", tool_version("node", version), source)
 }
 
+fn display_remote_node(runtime: &RemoteNode) -> String {
+    let mut line = format!("runtime {}", tool_version("node", &runtime.version));
+    if let Some(date) = &runtime.date {
+        line.push_str(&format!(" {}", date));
+    }
+    if let Some(lts) = &runtime.lts {
+        line.push_str(&format!(" (lts: {})", lts));
+    }
+    if runtime.installed {
+        line.push_str(" (installed)");
+    }
+    line
+}
+
+fn display_tool_usage(tool: &ToolUsage) -> String {
+    let kind = match tool.tool {
+        "node" => "runtime",
+        _ => "package-manager",
+    };
+    let mut line = format!(
+        "{} {}{} installed {} last-used {}",
+        kind,
+        tool_version(tool.tool, &tool.version),
+        tool.source,
+        format_usage_date(tool.installed, "unknown"),
+        format_usage_date(tool.last_used, "never")
+    );
+    if tool.unused {
+        line.push_str(" (unused)");
+    }
+    line
+}
+
 fn display_package_manager(package_manager: &PackageManager) -> String {
     format!(
         "package-manager {}{}",
@@ -132,8 +198,8 @@ fn display_package(package: &Package) -> String {
         Package::Default {
             details,
             node,
+            package_manager,
             tools,
-            ..
         } => {
             let tools = match tools.len() {
                 0 => String::from(" "),
@@ -145,9 +211,7 @@ fn display_package(package: &Package) -> String {
                 tool_version(&details.name, &details.version),
                 tools,
                 tool_version("node", &node),
-                // Should be updated when we support installing with custom package_managers,
-                // whether Yarn or non-built-in versions of npm
-                "npm@built-in",
+                format_installed_with(package_manager),
                 package_source(package)
             )
         }
@@ -175,12 +239,17 @@ fn display_package(package: &Package) -> String {
 
 fn display_tool(name: &str, host: &Package) -> Option<String> {
     match host {
-        Package::Default { details, node, .. } => Some(format!(
+        Package::Default {
+            details,
+            node,
+            package_manager,
+            ..
+        } => Some(format!(
             "tool {} / {} / {} {}{}",
             name,
             tool_version(&details.name, &details.version),
             tool_version("node", &node),
-            "npm@built-in",
+            format_installed_with(package_manager),
             package_source(host)
         )),
         Package::Project {
@@ -206,7 +275,7 @@ mod tests {
     use lazy_static::lazy_static;
     use semver::Version;
 
-    use crate::command::list::PackageDetails;
+    use crate::command::list::{PackageDetails, PackageManagerKind};
 
     lazy_static! {
         static ref NODE_VERSION: Version = Version::from((12, 4, 0));
@@ -248,6 +317,77 @@ mod tests {
         }
     }
 
+    mod remote {
+        use super::super::*;
+        use super::*;
+
+        #[test]
+        fn lts_installed() {
+            let runtime = RemoteNode {
+                version: NODE_VERSION.clone(),
+                lts: Some("Erbium".into()),
+                date: Some("2019-06-04".into()),
+                installed: true,
+            };
+            assert_eq!(
+                display_remote_node(&runtime).as_str(),
+                "runtime node@12.4.0 2019-06-04 (lts: Erbium) (installed)"
+            );
+        }
+
+        #[test]
+        fn without_details() {
+            let runtime = RemoteNode {
+                version: NODE_VERSION.clone(),
+                lts: None,
+                date: None,
+                installed: false,
+            };
+            assert_eq!(
+                display_remote_node(&runtime).as_str(),
+                "runtime node@12.4.0"
+            );
+        }
+    }
+
+    mod usage {
+        use super::super::*;
+        use super::*;
+        use chrono::{DateTime, Utc};
+
+        #[test]
+        fn default_version() {
+            let tool = ToolUsage {
+                tool: "node",
+                version: NODE_VERSION.clone(),
+                source: Source::Default,
+                installed: "2019-06-04T12:00:00Z".parse::<DateTime<Utc>>().ok(),
+                last_used: "2020-01-02T08:30:00Z".parse::<DateTime<Utc>>().ok(),
+                unused: false,
+            };
+            assert_eq!(
+                display_tool_usage(&tool).as_str(),
+                "runtime node@12.4.0 (default) installed 2019-06-04 last-used 2020-01-02"
+            );
+        }
+
+        #[test]
+        fn unused_version() {
+            let tool = ToolUsage {
+                tool: "yarn",
+                version: YARN_VERSION.clone(),
+                source: Source::None,
+                installed: None,
+                last_used: None,
+                unused: true,
+            };
+            assert_eq!(
+                display_tool_usage(&tool).as_str(),
+                "package-manager yarn@1.16.0 installed unknown last-used never (unused)"
+            );
+        }
+    }
+
     mod npm {
         use super::super::*;
         use super::*;
@@ -351,6 +491,7 @@ mod tests {
                         version: TYPESCRIPT_VERSION.clone(),
                     },
                     node: NODE_VERSION.clone(),
+                    package_manager: None,
                     tools: vec!["tsc".into(), "tsserver".into()]
                 }])
                 .expect("Should always return a `String` if given a non-empty set")
@@ -388,6 +529,7 @@ mod tests {
                             version: Version::from((3, 10, 0)),
                         },
                         node: NODE_VERSION.clone(),
+                        package_manager: None,
                         tools: vec!["ember".into()],
                     },
                     Package::Fetched(PackageDetails {
@@ -432,6 +574,7 @@ mod tests {
                             version: TYPESCRIPT_VERSION.clone(),
                         },
                         node: NODE_VERSION.clone(),
+                        package_manager: None,
                         tools: vec!["tsc".into(), "tsserver".into()],
                     }
                 )
@@ -441,6 +584,27 @@ mod tests {
             );
         }
 
+        #[test]
+        fn default_with_yarn() {
+            assert_eq!(
+                display_tool(
+                    "tsc",
+                    &Package::Default {
+                        details: PackageDetails {
+                            name: "typescript".into(),
+                            version: TYPESCRIPT_VERSION.clone(),
+                        },
+                        node: NODE_VERSION.clone(),
+                        package_manager: Some((PackageManagerKind::Yarn, YARN_VERSION.clone())),
+                        tools: vec!["tsc".into(), "tsserver".into()],
+                    }
+                )
+                .expect("should always return `Some` for `Default`")
+                .as_str(),
+                "tool tsc / typescript@3.4.1 / node@12.4.0 yarn@1.16.0 (default)"
+            );
+        }
+
         #[test]
         fn project() {
             assert_eq!(
@@ -521,6 +685,7 @@ mod tests {
                                 version: Version::from((3, 10, 2)),
                             },
                             node: NODE_VERSION.clone(),
+                            package_manager: None,
                             tools: vec!["ember".into()]
                         },
                         Package::Project {
@@ -534,6 +699,7 @@ mod tests {
                                 version: TYPESCRIPT_VERSION.clone(),
                             },
                             node: NODE_VERSION.clone(),
+                            package_manager: None,
                             tools: vec!["tsc".into(), "tsserver".into()]
                         }
                     ]
@@ -1,14 +1,22 @@
-use super::{Filter, Node, Package, PackageManager, Source};
+use super::{Filter, Node, Package, PackageManager, RemoteNode, Source, ToolUsage, UNUSED_DAYS};
 use crate::command::list::PackageManagerKind;
+use chrono::{Duration, Utc};
 use semver::Version;
-use volta_core::error::Fallible;
-use volta_core::inventory::{node_versions, npm_versions, package_configs, yarn_versions};
+use volta_core::error::{ErrorKind, Fallible};
+use volta_core::inventory::{
+    image_usage, node_versions, npm_versions, package_configs, yarn_versions,
+};
 use volta_core::platform::PlatformSpec;
 use volta_core::project::Project;
-use volta_core::tool::PackageConfig;
+use volta_core::session::Session;
+use volta_core::tool::node::available_releases;
+use volta_core::tool::{PackageConfig, Spec};
+use volta_core::version::{satisfies, VersionSpec, VersionTag};
 
 pub(super) enum Toolchain {
     Node(Vec<Node>),
+    Remote(Vec<RemoteNode>),
+    Usage(Vec<ToolUsage>),
     PackageManagers {
         kind: PackageManagerKind,
         managers: Vec<PackageManager>,
@@ -31,6 +39,7 @@ pub(super) enum Toolchain {
 }
 
 /// Lightweight rule for which item to get the `Source` for.
+#[derive(Clone, Copy)]
 enum Lookup {
     /// Look up the Node runtime
     Runtime,
@@ -207,6 +216,75 @@ impl Toolchain {
         Ok(Toolchain::Node(runtimes))
     }
 
+    /// The Node versions in the index that match `tool`, which is `node` or a Node spec like
+    /// `node@18`
+    pub(super) fn remote(tool: &str, session: &Session) -> Fallible<Toolchain> {
+        let matching = match Spec::try_from_str(tool)? {
+            Spec::Node(version) => version,
+            _ => {
+                return Err(ErrorKind::RemoteListUnsupported { tool: tool.into() }.into());
+            }
+        };
+
+        let installed = node_versions()?;
+        let runtimes = available_releases(session)?
+            .into_iter()
+            .filter(|release| match &matching {
+                VersionSpec::Semver(requirement) => {
+                    satisfies(requirement, &release.version, session.include_prerelease())
+                }
+                VersionSpec::Exact(version) => release.version == *version,
+                VersionSpec::Tag(VersionTag::Lts) => release.lts.is_some(),
+                _ => true,
+            })
+            .map(|release| RemoteNode {
+                installed: installed.contains(&release.version),
+                version: release.version,
+                lts: release.lts,
+                date: release.date,
+            })
+            .collect();
+
+        Ok(Toolchain::Remote(runtimes))
+    }
+
+    /// The fetched versions of the given tools (`node`, `npm`, or `yarn`), with when each was
+    /// installed and last used
+    pub(super) fn usage(
+        tools: &[&'static str],
+        project: Option<&Project>,
+        default_platform: Option<&PlatformSpec>,
+    ) -> Fallible<Toolchain> {
+        let now = Utc::now();
+        let mut usage = Vec::new();
+        for &tool in tools {
+            let (versions, lookup) = match tool {
+                "node" => (node_versions()?, Lookup::Runtime),
+                "npm" => (npm_versions()?, Lookup::Npm),
+                _ => (yarn_versions()?, Lookup::Yarn),
+            };
+            for version in versions {
+                let source = lookup.version_source(project, default_platform, &version);
+                let times = image_usage(tool, &version);
+                // Versions that were never run are measured from when they were installed
+                let unused = source == Source::None
+                    && times.last_used.or(times.installed).map_or(false, |time| {
+                        now.signed_duration_since(time) > Duration::days(UNUSED_DAYS)
+                    });
+                usage.push(ToolUsage {
+                    tool,
+                    version,
+                    source,
+                    installed: times.installed,
+                    last_used: times.last_used,
+                    unused,
+                });
+            }
+        }
+
+        Ok(Toolchain::Usage(usage))
+    }
+
     pub(super) fn npm(
         project: Option<&Project>,
         default_platform: Option<&PlatformSpec>,
@@ -285,11 +363,9 @@ impl Toolchain {
                 // Start with the package itself, since tools often match
                 // the package name and we prioritize packages.
                 if config.name == name {
-                    let source = Package::source(name, project);
-                    if source.allowed_with(filter) {
-                        Some(Ok((Kind::Package, config, source)))
-                    } else {
-                        None
+                    match Package::source(name, project) {
+                        Ok(source) if !source.allowed_with(filter) => None,
+                        source => Some(source.map(|source| (Kind::Package, config, source))),
                     }
 
                 // Then check if the passed name matches an installed package's
@@ -0,0 +1,178 @@
+use std::env;
+use std::fs::read_to_string;
+use std::io::{self, Write};
+use std::iter::once;
+use std::path::{Path, PathBuf};
+
+use atty::Stream;
+use log::info;
+use serde_json::Value;
+use structopt::StructOpt;
+
+use volta_core::error::{Context, ErrorKind, ExitCode, Fallible};
+use volta_core::fs::read_dir_eager;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::settings::PinLocation;
+use volta_core::style::{note_prefix, success_prefix};
+use volta_core::tool::Spec;
+use volta_core::version::{VersionSpec, VersionTag};
+
+use crate::command::import::{import_versions, Manager};
+use crate::command::{Command, Setup};
+
+#[derive(StructOpt)]
+pub(crate) struct Migrate {
+    /// Makes every change without asking first
+    #[structopt(long = "yes", short = "y")]
+    yes: bool,
+
+    /// Hard links the files of imported versions instead of copying them, where possible
+    #[structopt(long = "link")]
+    link: bool,
+}
+
+impl Command for Migrate {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Migrate);
+
+        if !self.yes && !atty::is(Stream::Stdin) {
+            return Err(ErrorKind::MigrateNotInteractive.into());
+        }
+
+        // 1. Import the versions installed by other managers
+        let mut found = false;
+        for manager in &Manager::ALL {
+            let root = manager.root()?;
+            let installations = manager.installations(&root);
+            if installations.is_empty() {
+                continue;
+            }
+
+            found = true;
+            let question = format!(
+                "Found {} Node version(s) installed by {} in {}. Import them?",
+                installations.len(),
+                manager.name(),
+                root.display()
+            );
+            if self.confirm(&question) {
+                import_versions(manager, &root, &installations, self.link, session)?;
+            }
+        }
+        if !found {
+            info!("{} No other version managers were found", note_prefix());
+        }
+
+        // 2. Pin the projects that choose their Node version in another way
+        let current_dir = env::current_dir().with_context(|| ErrorKind::CurrentDirError)?;
+        for (dir, version) in unpinned_projects(&current_dir) {
+            let question = format!("Pin node@{} in {}?", version, dir.display());
+            if self.confirm(&question) {
+                pin_node(dir, &version, session)?;
+            }
+        }
+
+        // 3. Point the shell at Volta's shims
+        if self.confirm("Set up your shell to use Volta?") {
+            Setup::default().run(session)?;
+        }
+
+        info!(
+            "{} Migration complete. Once your shell uses Volta, you can remove the other version managers.",
+            success_prefix()
+        );
+        session.add_event_end(ActivityKind::Migrate, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+impl Migrate {
+    /// Asks a yes or no question, where pressing enter accepts
+    fn confirm(&self, question: &str) -> bool {
+        if self.yes {
+            return true;
+        }
+
+        print!("{} [Y/n] ", question);
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+        match io::stdin().read_line(&mut answer) {
+            // Without an answer at all (e.g. stdin was closed), nothing is changed
+            Ok(0) | Err(_) => false,
+            Ok(_) => matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes"),
+        }
+    }
+}
+
+/// Pins a Node version in the project in a directory, the same way as `volta pin`
+fn pin_node(dir: PathBuf, version: &str, session: &mut Session) -> Fallible<()> {
+    session.use_project_dir(dir)?;
+    let pin_location = session.settings()?.pin_location();
+
+    if let Some(project) = session.project_mut()? {
+        if pin_location == PinLocation::VoltaJson {
+            project.use_pin_file();
+        }
+        project.stage_pins();
+    }
+
+    Spec::Node(node_version_spec(version)?)
+        .resolve(session)?
+        .pin(session)?;
+
+    if let Some(project) = session.project_mut()? {
+        project.commit_pins()?;
+    }
+    Ok(())
+}
+
+/// Finds the projects in a directory, and in its subdirectories, that don't pin Node with Volta
+/// but choose a version with an `.nvmrc` file or `engines`, along with that version
+fn unpinned_projects(dir: &Path) -> Vec<(PathBuf, String)> {
+    let subdirs = read_dir_eager(dir)
+        .map(|entries| {
+            entries
+                .filter(|(_, metadata)| metadata.is_dir())
+                .map(|(entry, _)| entry.path())
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    once(dir.to_owned())
+        .chain(subdirs)
+        .filter_map(|dir| {
+            let version = requested_node_version(&dir)?;
+            Some((dir, version))
+        })
+        .collect()
+}
+
+/// Reads the Node version a project asks for, preferring `.nvmrc` to the `engines` range
+fn requested_node_version(dir: &Path) -> Option<String> {
+    let manifest: Value =
+        serde_json::from_str(&read_to_string(dir.join("package.json")).ok()?).ok()?;
+    if manifest.pointer("/volta/node").is_some() || manifest.pointer("/volta/extends").is_some() {
+        return None;
+    }
+
+    read_to_string(dir.join(".nvmrc"))
+        .ok()
+        .map(|contents| contents.trim().to_string())
+        .filter(|version| !version.is_empty())
+        .or_else(|| {
+            manifest
+                .pointer("/engines/node")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+}
+
+/// Converts a version from an `.nvmrc` (like `v16` or `lts/*`) or `engines` to a version spec
+fn node_version_spec(version: &str) -> Fallible<VersionSpec> {
+    match version.strip_prefix('v').unwrap_or(version) {
+        "node" | "stable" => Ok(VersionSpec::Tag(VersionTag::Latest)),
+        "lts/*" => Ok(VersionSpec::Tag(VersionTag::Lts)),
+        version => version.parse(),
+    }
+}
@@ -1,24 +1,62 @@
+pub(crate) mod activate;
+pub(crate) mod audit;
+pub(crate) mod complete;
 pub(crate) mod completions;
+pub(crate) mod current;
+pub(crate) mod doctor;
+pub(crate) mod env;
+pub(crate) mod evict_unused;
+pub(crate) mod exec;
+pub(crate) mod exec_command;
 pub(crate) mod fetch;
+pub(crate) mod import;
+pub(crate) mod inspect;
 pub(crate) mod install;
+pub(crate) mod inventory;
 pub(crate) mod list;
+pub(crate) mod migrate;
 pub(crate) mod pin;
+pub(crate) mod refresh_index;
 pub(crate) mod run;
+pub(crate) mod sbom;
+pub(crate) mod settings_bundle;
 pub(crate) mod setup;
 pub(crate) mod uninstall;
+pub(crate) mod unpin;
+pub(crate) mod upgrade;
 pub(crate) mod r#use;
+pub(crate) mod verify;
 pub(crate) mod which;
 
 pub(crate) use self::which::Which;
+pub(crate) use activate::{Activate, Deactivate};
+pub(crate) use audit::Audit;
+pub(crate) use complete::Complete;
 pub(crate) use completions::Completions;
+pub(crate) use current::Current;
+pub(crate) use doctor::Doctor;
+pub(crate) use env::Env;
+pub(crate) use evict_unused::EvictUnused;
+pub(crate) use exec::Exec;
+pub(crate) use exec_command::ExecCommand;
 pub(crate) use fetch::Fetch;
+pub(crate) use import::Import;
+pub(crate) use inspect::Inspect;
 pub(crate) use install::Install;
+pub(crate) use inventory::Inventory;
 pub(crate) use list::List;
+pub(crate) use migrate::Migrate;
 pub(crate) use pin::Pin;
 pub(crate) use r#use::Use;
+pub(crate) use refresh_index::RefreshIndex;
 pub(crate) use run::Run;
+pub(crate) use sbom::Sbom;
+pub(crate) use settings_bundle::{ExportSettings, ImportSettings};
 pub(crate) use setup::Setup;
 pub(crate) use uninstall::Uninstall;
+pub(crate) use unpin::Unpin;
+pub(crate) use upgrade::Upgrade;
+pub(crate) use verify::Verify;
 
 use volta_core::error::{ExitCode, Fallible};
 use volta_core::session::Session;
@@ -1,7 +1,11 @@
+use log::{info, warn};
 use structopt::StructOpt;
 
 use volta_core::error::{ExitCode, Fallible};
+use volta_core::project::{resolve_unlocked_pins, Project};
 use volta_core::session::{ActivityKind, Session};
+use volta_core::settings::PinLocation;
+use volta_core::style::{note_prefix, success_prefix, tool_version};
 use volta_core::tool::Spec;
 
 use crate::command::Command;
@@ -11,17 +15,108 @@ pub(crate) struct Pin {
     /// Tools to pin, like `node@lts` or `yarn@^1.14`.
     #[structopt(name = "tool[@version]", required = true, min_values = 1)]
     tools: Vec<String>,
+
+    /// Allows version ranges to match prerelease versions, like `node@20 --include-prerelease`
+    #[structopt(long = "include-prerelease")]
+    include_prerelease: bool,
+
+    /// Keeps version ranges and tags in package.json, recording the resolved versions in volta.lock
+    #[structopt(long = "lock")]
+    lock: bool,
+
+    /// Pins the tools at the root of the workspace that contains this project
+    #[structopt(long = "workspace")]
+    workspace: bool,
+
+    /// Removes conflicting pins from workspace members, so that they inherit the root's pins
+    #[structopt(long = "clean", requires = "workspace")]
+    clean: bool,
 }
 
 impl Command for Pin {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::Pin);
+        session.set_include_prerelease(self.include_prerelease);
+
+        if self.workspace {
+            session.use_workspace_root()?;
+        }
+
+        // Make sure the project platform is complete before updating it
+        resolve_unlocked_pins(session)?;
+
+        let pin_location = session.settings()?.pin_location();
+
+        // Stage the pins, so that the manifest is only written once every tool has been fetched
+        if let Some(project) = session.project_mut()? {
+            if pin_location == PinLocation::VoltaJson {
+                project.use_pin_file();
+            }
+            project.stage_pins();
+        }
 
         for tool in Spec::from_strings(&self.tools, "pin")? {
-            tool.resolve(session)?.pin(session)?;
+            if self.lock {
+                tool.pin_locked(session)?;
+            } else {
+                tool.resolve(session)?.pin(session)?;
+            }
+        }
+
+        if let Some(project) = session.project_mut()? {
+            project.commit_pins()?;
+        }
+
+        if let Some(project) = session.project()? {
+            project.warn_engine_conflicts();
+
+            if self.workspace {
+                check_workspace_members(project, self.clean)?;
+            }
         }
 
         session.add_event_end(ActivityKind::Pin, ExitCode::Success);
         Ok(ExitCode::Success)
     }
 }
+
+/// Reports (or with `clean`, fixes) workspace members whose pins disagree with the root
+fn check_workspace_members(root: &Project, clean: bool) -> Fallible<()> {
+    let conflicts = root.workspace_conflicts()?;
+
+    for conflict in &conflicts {
+        if clean {
+            root.clean_workspace_member(conflict)?;
+            info!(
+                "{} updated {} to use the workspace pins",
+                success_prefix(),
+                conflict.manifest.display()
+            );
+            continue;
+        }
+
+        for (tool, pinned, expected) in &conflict.mismatched {
+            warn!(
+                "{} pins {}, but the workspace root pins {}",
+                conflict.manifest.display(),
+                tool_version(tool, pinned),
+                tool_version(tool, expected)
+            );
+        }
+        if !conflict.extends_root {
+            warn!(
+                "{} does not extend the workspace root, so it won't use the workspace pins",
+                conflict.manifest.display()
+            );
+        }
+    }
+
+    if !clean && !conflicts.is_empty() {
+        info!(
+            "{} run `volta pin --workspace --clean` to make workspace members use the root's pins",
+            note_prefix()
+        );
+    }
+
+    Ok(())
+}
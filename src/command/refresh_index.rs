@@ -0,0 +1,24 @@
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::tool::node;
+
+use crate::command::Command;
+
+/// Downloads the Node index again, replacing the cached copy
+///
+/// This is run in the background when the `indexRefreshHours` setting is enabled
+#[derive(StructOpt)]
+pub(crate) struct RefreshIndex {}
+
+impl Command for RefreshIndex {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::RefreshIndex);
+
+        node::refresh_index(session)?;
+
+        session.add_event_end(ActivityKind::RefreshIndex, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
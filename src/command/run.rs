@@ -33,6 +33,10 @@ pub(crate) struct Run {
     #[structopt(long = "no-yarn", conflicts_with = "yarn")]
     no_yarn: bool,
 
+    /// Allows version ranges to match prerelease versions
+    #[structopt(long = "include-prerelease")]
+    include_prerelease: bool,
+
     /// Set an environment variable (can be used multiple times)
     #[structopt(long = "env", value_name = "NAME=value", raw(number_of_values = "1"))]
     envs: Vec<String>,
@@ -49,6 +53,7 @@ pub(crate) struct Run {
 impl Command for Run {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::Run);
+        session.set_include_prerelease(self.include_prerelease);
 
         let envs = self.parse_envs();
         let platform = self.parse_platform(session)?;
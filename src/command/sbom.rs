@@ -0,0 +1,215 @@
+use std::str::FromStr;
+
+use chrono::{SecondsFormat, Utc};
+use serde_json::{json, Value};
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::session::{ActivityKind, Session};
+
+use crate::command::audit::{PackageInfo, Report, RuntimeInfo};
+use crate::command::Command;
+
+#[derive(Copy, Clone, PartialEq)]
+enum Format {
+    CycloneDx,
+    Spdx,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "cyclonedx" => Ok(Format::CycloneDx),
+            "spdx" => Ok(Format::Spdx),
+            _ => Err("No".into()),
+        }
+    }
+}
+
+#[derive(StructOpt)]
+pub(crate) struct Sbom {
+    /// The format of the document
+    ///
+    /// Defaults to `cyclonedx`.
+    #[structopt(long = "format", raw(possible_values = r#"&["cyclonedx", "spdx"]"#))]
+    format: Option<Format>,
+}
+
+impl Command for Sbom {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Sbom);
+
+        let components: Vec<Component> = {
+            let report = Report::build()?;
+            let runtimes = report.runtimes.iter().map(Component::runtime);
+            let packages = report.packages.iter().map(Component::package);
+            runtimes.chain(packages).collect()
+        };
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+
+        let document = match self.format.unwrap_or(Format::CycloneDx) {
+            Format::CycloneDx => cyclonedx(&components, &timestamp),
+            Format::Spdx => spdx(&components, &timestamp),
+        };
+        // Serializing a `Value` can't fail
+        let json = serde_json::to_string_pretty(&document).expect("Document is serializable");
+        println!("{}", json);
+
+        session.add_event_end(ActivityKind::Sbom, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// A tool in the document, which is described the same way in either format
+struct Component {
+    name: String,
+    version: String,
+    purl: String,
+    source: Option<String>,
+    sha256: Option<String>,
+    /// Runtimes are applications in their own right, while package tools are npm libraries
+    application: bool,
+}
+
+impl Component {
+    fn runtime(runtime: &RuntimeInfo) -> Self {
+        // npm and Yarn are published to the npm registry, while Node has no package ecosystem
+        let purl = match runtime.tool {
+            "node" => format!("pkg:generic/node@{}", runtime.version),
+            tool => format!("pkg:npm/{}@{}", tool, runtime.version),
+        };
+
+        Component {
+            name: runtime.tool.into(),
+            version: runtime.version.clone(),
+            purl,
+            source: runtime.source.clone(),
+            sha256: runtime.sha256.clone(),
+            application: true,
+        }
+    }
+
+    fn package(package: &PackageInfo) -> Self {
+        Component {
+            name: package.name.clone(),
+            version: package.version.clone(),
+            // The scope of a package is part of the purl namespace, with its `@` escaped
+            purl: format!(
+                "pkg:npm/{}@{}",
+                package.name.replacen('@', "%40", 1),
+                package.version
+            ),
+            source: package.source.clone(),
+            sha256: None,
+            application: false,
+        }
+    }
+}
+
+/// Builds a CycloneDX 1.4 document
+fn cyclonedx(components: &[Component], timestamp: &str) -> Value {
+    let components: Vec<Value> = components
+        .iter()
+        .map(|component| {
+            let mut value = json!({
+                "type": if component.application { "application" } else { "library" },
+                "name": component.name,
+                "version": component.version,
+                "purl": component.purl,
+            });
+            if let Some(sha256) = &component.sha256 {
+                value["hashes"] = json!([{ "alg": "SHA-256", "content": sha256 }]);
+            }
+            if let Some(source) = &component.source {
+                value["externalReferences"] = json!([{ "type": "distribution", "url": source }]);
+            }
+            value
+        })
+        .collect();
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.4",
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp,
+            "tools": [{ "vendor": "Volta", "name": "volta", "version": env!("CARGO_PKG_VERSION") }],
+        },
+        "components": components,
+    })
+}
+
+/// Builds an SPDX 2.3 document
+fn spdx(components: &[Component], timestamp: &str) -> Value {
+    let ids: Vec<String> = components
+        .iter()
+        .enumerate()
+        .map(|(index, component)| spdx_id(index, &component.name))
+        .collect();
+
+    let packages: Vec<Value> = components
+        .iter()
+        .zip(&ids)
+        .map(|(component, id)| {
+            let mut value = json!({
+                "SPDXID": id,
+                "name": component.name,
+                "versionInfo": component.version,
+                "downloadLocation": component.source.as_deref().unwrap_or("NOASSERTION"),
+                "filesAnalyzed": false,
+                "externalRefs": [{
+                    "referenceCategory": "PACKAGE-MANAGER",
+                    "referenceType": "purl",
+                    "referenceLocator": component.purl,
+                }],
+            });
+            if let Some(sha256) = &component.sha256 {
+                value["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": sha256 }]);
+            }
+            value
+        })
+        .collect();
+
+    let relationships: Vec<Value> = ids
+        .iter()
+        .map(|id| {
+            json!({
+                "spdxElementId": "SPDXRef-DOCUMENT",
+                "relationshipType": "DESCRIBES",
+                "relatedSpdxElement": id,
+            })
+        })
+        .collect();
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": "volta-toolchain",
+        "documentNamespace": format!("https://volta.sh/spdx/toolchain-{}", timestamp),
+        "creationInfo": {
+            "created": timestamp,
+            "creators": [format!("Tool: volta-{}", env!("CARGO_PKG_VERSION"))],
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
+
+/// Builds a unique SPDX identifier, which may only contain letters, numbers, `.`, and `-`
+fn spdx_id(index: usize, name: &str) -> String {
+    let name: String = name
+        .trim_start_matches('@')
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("SPDXRef-{}-{}", index, name)
+}
@@ -0,0 +1,199 @@
+use std::fs::{create_dir_all, read_to_string, write};
+use std::path::{Path, PathBuf};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use structopt::StructOpt;
+
+use volta_core::error::{Context, ErrorKind, ExitCode, Fallible};
+use volta_core::fs::read_file;
+use volta_core::inventory::package_configs;
+use volta_core::layout::volta_home;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::success_prefix;
+use volta_core::tool::Spec;
+
+use crate::command::Command;
+
+/// The version of the bundle format, which changes only when existing fields change meaning
+const FORMAT_VERSION: u32 = 1;
+
+/// The user's Volta configuration, as a single JSON document that can be restored on another
+/// machine
+///
+/// Tools are recorded as the specs to install them with, rather than as binaries, so that the
+/// bundle is small and works across platforms.
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    version: u32,
+    #[serde(default)]
+    hooks: Option<Value>,
+    #[serde(default)]
+    settings: Option<Value>,
+    /// The default platform, followed by the installed packages
+    #[serde(default)]
+    tools: Vec<String>,
+}
+
+#[derive(StructOpt)]
+pub(crate) struct ExportSettings {
+    /// File to write the bundle to (defaults to stdout)
+    #[structopt(parse(from_os_str))]
+    output: Option<PathBuf>,
+}
+
+impl Command for ExportSettings {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::ExportSettings);
+
+        let home = volta_home()?;
+        let hooks_file = home.default_hooks_file();
+        let hooks = read_json(
+            hooks_file,
+            ErrorKind::ReadHooksError {
+                file: hooks_file.to_owned(),
+            },
+            ErrorKind::ParseHooksError {
+                file: hooks_file.to_owned(),
+            },
+        )?;
+        let settings_file = home.default_settings_file();
+        let settings = read_json(
+            settings_file,
+            ErrorKind::ReadSettingsError {
+                file: settings_file.to_owned(),
+            },
+            ErrorKind::ParseSettingsError {
+                file: settings_file.to_owned(),
+            },
+        )?;
+
+        let mut tools = Vec::new();
+        if let Some(platform) = session.default_platform()? {
+            tools.push(format!("node@{}", platform.node));
+            if let Some(npm) = &platform.npm {
+                tools.push(format!("npm@{}", npm));
+            }
+            if let Some(yarn) = &platform.yarn {
+                tools.push(format!("yarn@{}", yarn));
+            }
+        }
+        // Packages installed from git or a tarball are installed from there again
+        tools.extend(
+            package_configs()?
+                .into_iter()
+                .map(|config| match config.source {
+                    Some(source) => source,
+                    None => format!("{}@{}", config.name, config.version),
+                }),
+        );
+
+        let bundle = SettingsBundle {
+            version: FORMAT_VERSION,
+            hooks,
+            settings,
+            tools,
+        };
+        // Serializing JSON values and strings can't fail
+        let json = serde_json::to_string_pretty(&bundle).expect("Bundle is serializable");
+
+        match &self.output {
+            Some(output) => {
+                write(output, format!("{}\n", json)).with_context(|| {
+                    ErrorKind::WriteSettingsBundleError {
+                        file: output.clone(),
+                    }
+                })?;
+                info!(
+                    "{} exported settings to {}",
+                    success_prefix(),
+                    output.display()
+                );
+            }
+            None => println!("{}", json),
+        }
+
+        session.add_event_end(ActivityKind::ExportSettings, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+#[derive(StructOpt)]
+pub(crate) struct ImportSettings {
+    /// The bundle written by `volta export-settings`
+    #[structopt(parse(from_os_str))]
+    file: PathBuf,
+
+    /// Only restores the hooks and settings, without installing any tools
+    #[structopt(long = "no-install")]
+    no_install: bool,
+}
+
+impl Command for ImportSettings {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::ImportSettings);
+
+        let contents =
+            read_to_string(&self.file).with_context(|| ErrorKind::ReadSettingsBundleError {
+                file: self.file.clone(),
+            })?;
+        let bundle: SettingsBundle = serde_json::from_str(&contents)
+            .ok()
+            .filter(|bundle: &SettingsBundle| bundle.version <= FORMAT_VERSION)
+            .ok_or_else(|| ErrorKind::ParseSettingsBundleError {
+                file: self.file.clone(),
+            })?;
+
+        // The configuration is written first, so that the tools are fetched with the hooks
+        let home = volta_home()?;
+        if let Some(hooks) = &bundle.hooks {
+            write_json(home.default_hooks_file(), hooks)?;
+        }
+        if let Some(settings) = &bundle.settings {
+            write_json(home.default_settings_file(), settings)?;
+        }
+        info!(
+            "{} imported settings from {}",
+            success_prefix(),
+            self.file.display()
+        );
+
+        if !self.no_install && !bundle.tools.is_empty() {
+            for tool in Spec::from_strings(&bundle.tools, "install")? {
+                tool.resolve(session)?.install(session)?;
+            }
+        }
+
+        session.add_event_end(ActivityKind::ImportSettings, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Reads a JSON configuration file, if it exists
+fn read_json(
+    file: &Path,
+    read_error: ErrorKind,
+    parse_error: ErrorKind,
+) -> Fallible<Option<Value>> {
+    let contents = match read_file(file).with_context(|| read_error)? {
+        Some(contents) => contents,
+        None => return Ok(None),
+    };
+    serde_json::from_str(&contents)
+        .map(Some)
+        .with_context(|| parse_error)
+}
+
+fn write_json(file: &Path, value: &Value) -> Fallible<()> {
+    let write_error = || ErrorKind::WriteSettingsBundleError {
+        file: file.to_owned(),
+    };
+    // Serializing a JSON value can't fail
+    let json = serde_json::to_string_pretty(value).expect("Value is serializable");
+
+    if let Some(dir) = file.parent() {
+        create_dir_all(dir).with_context(write_error)?;
+    }
+    write(file, format!("{}\n", json)).with_context(write_error)
+}
@@ -1,44 +1,130 @@
-use log::info;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+
+use log::{debug, info};
 use structopt::StructOpt;
+use volta_core::conflicts::path_conflicts;
 use volta_core::error::{ExitCode, Fallible};
+use volta_core::github;
+use volta_core::install_source::InstallSource;
 use volta_core::layout::volta_home;
 use volta_core::session::{ActivityKind, Session};
-use volta_core::shim::regenerate_shims_for_dir;
+use volta_core::shim::{self, regenerate_shims_for_dir};
 use volta_core::style::success_prefix;
 
+use crate::command::doctor::report_conflicts;
 use crate::command::Command;
 
-#[derive(StructOpt)]
-pub(crate) struct Setup {}
+#[derive(StructOpt, Default)]
+pub(crate) struct Setup {
+    /// Also installs an `nvm` command that supports `nvm install`, `nvm use`, and `nvm current`
+    #[structopt(long = "nvm")]
+    nvm: bool,
+}
 
 impl Command for Setup {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
         session.add_event_start(ActivityKind::Setup);
 
-        os::setup_environment()?;
-        regenerate_shims_for_dir(volta_home()?.shim_dir())?;
+        // Workflow steps don't read profile scripts, so the runner's files are used instead
+        if github::is_github_actions() {
+            regenerate_shims_for_dir(volta_home()?.shim_dir())?;
+            github::prepare_job(session)?;
+
+            info!(
+                "{} Setup complete. Volta is available in the following steps of this job.",
+                success_prefix()
+            );
+        } else {
+            os::setup_environment()?;
+            regenerate_shims_for_dir(volta_home()?.shim_dir())?;
+
+            info!(
+                "{} Setup complete. Open a new terminal to start using Volta!",
+                success_prefix()
+            );
+        }
 
-        info!(
-            "{} Setup complete. Open a new terminal to start using Volta!",
-            success_prefix()
-        );
+        if self.nvm {
+            shim::create("nvm")?;
+            info!(
+                "{} Installed the nvm compatibility command. Note that `nvm use` changes your \
+                 default Node version, not just the one for the current shell.",
+                success_prefix()
+            );
+        }
+
+        // Recorded so that `volta upgrade --self` knows whether a package manager owns Volta
+        let source = InstallSource::record()?;
+        debug!("Volta was installed by {}", source);
+
+        report_conflicts(&path_conflicts()?);
 
         session.add_event_end(ActivityKind::Setup, ExitCode::Success);
         Ok(ExitCode::Success)
     }
 }
 
+/// Reads a profile script without the lines that Volta added to it, so they can be written again
+fn read_profile_without_volta(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let reader = BufReader::new(file);
+
+    reader
+        .lines()
+        .filter(|line_result| match line_result {
+            Ok(line) if !line.contains("VOLTA") => true,
+            Ok(_) => false,
+            Err(_) => true,
+        })
+        .collect::<io::Result<Vec<String>>>()
+        .map(|lines| lines.join("\n"))
+        .ok()
+}
+
+/// Writes a PowerShell profile that loads the Volta completions, along with the environment if
+/// a Volta home is given (on Windows, the environment comes from the registry instead)
+fn write_profile_powershell(
+    path: &Path,
+    contents: String,
+    volta_home: Option<&str>,
+) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut file = File::create(path)?;
+    writeln!(file, "{}", contents)?;
+    if let Some(volta_home) = volta_home {
+        writeln!(file, "$env:VOLTA_HOME = \"{}\"", volta_home)?;
+        writeln!(
+            file,
+            "$env:PATH = \"$env:VOLTA_HOME/bin\" + [IO.Path]::PathSeparator + $env:PATH"
+        )?;
+    }
+    writeln!(
+        file,
+        "volta completions powershell | Out-String | Invoke-Expression # VOLTA completions"
+    )
+}
+
 #[cfg(unix)]
 mod os {
     use std::env;
-    use std::fs::File;
-    use std::io::{self, BufRead, BufReader, Write};
+    use std::fs::{self, File};
+    use std::io::{self, Write};
     use std::path::{Path, PathBuf};
 
     use log::{debug, warn};
+    use structopt::clap::Shell;
+    use volta_core::conflicts::path_conflicts;
     use volta_core::error::{ErrorKind, Fallible};
     use volta_core::layout::volta_home;
 
+    use super::{read_profile_without_volta, write_profile_powershell};
+    use crate::command::completions::{generate, NUSHELL_COMPLETIONS};
+
     pub fn setup_environment() -> Fallible<()> {
         let home = volta_home()?;
         let formatted_home = format_home(home.root());
@@ -49,7 +135,12 @@ mod os {
             None => None,
         };
 
-        if env::var_os("VOLTA_HOME").is_some() && home_in_path.is_some() {
+        // With another manager earlier on the PATH, the profiles are rewritten anyway, which moves
+        // Volta's lines after the ones that load the other manager
+        if env::var_os("VOLTA_HOME").is_some()
+            && home_in_path.is_some()
+            && path_conflicts()?.is_empty()
+        {
             debug!(
                 "Skipping dot-file modification as VOLTA_HOME is set, and included in the PATH."
             );
@@ -64,11 +155,20 @@ mod os {
 
             let write_profile = match profile.extension() {
                 Some(ext) if ext == "fish" => write_profile_fish,
+                Some(ext) if ext == "ps1" => write_profile_pwsh,
+                Some(ext) if ext == "nu" => write_profile_nu,
                 _ => write_profile_sh,
             };
 
             match write_profile(&profile, contents, &formatted_home) {
-                Ok(()) => true,
+                Ok(()) => {
+                    match profile.extension() {
+                        Some(ext) if ext == "fish" => write_fish_completions(&profile),
+                        Some(ext) if ext == "nu" => write_nushell_completions(&profile),
+                        _ => {}
+                    }
+                    true
+                }
                 Err(err) => {
                     warn!(
                         "Found profile script, but could not modify it: {}",
@@ -110,6 +210,8 @@ mod os {
         add_zsh_profile(&home_dir, &shell, &mut profiles);
         add_bash_profiles(&home_dir, &shell, &mut profiles);
         add_fish_profile(&home_dir, &shell, &mut profiles);
+        add_powershell_profile(&home_dir, &shell, &mut profiles);
+        add_nushell_profile(&shell, &mut profiles);
 
         Ok(profiles)
     }
@@ -173,20 +275,31 @@ If you run into problems running Volta, create {} and run `volta setup` again.",
         }
     }
 
-    fn read_profile_without_volta(path: &Path) -> Option<String> {
-        let file = File::open(path).ok()?;
-        let reader = BufReader::new(file);
-
-        reader
-            .lines()
-            .filter(|line_result| match line_result {
-                Ok(line) if !line.contains("VOLTA") => true,
-                Ok(_) => false,
-                Err(_) => true,
-            })
-            .collect::<io::Result<Vec<String>>>()
-            .map(|lines| lines.join("\n"))
-            .ok()
+    /// Add the PowerShell profile script, if necessary
+    fn add_powershell_profile(home_dir: &Path, shell: &str, profiles: &mut Vec<PathBuf>) {
+        let powershell_dir = home_dir.join(".config/powershell");
+
+        if shell.contains("pwsh") || powershell_dir.exists() {
+            profiles.push(powershell_dir.join("Microsoft.PowerShell_profile.ps1"));
+        }
+    }
+
+    /// Add the Nushell environment script, if necessary
+    ///
+    /// Nushell doesn't read any of the POSIX profiles, so it needs its own
+    fn add_nushell_profile(shell: &str, profiles: &mut Vec<PathBuf>) {
+        let nushell_dir = match dirs::config_dir() {
+            Some(config_dir) => config_dir.join("nushell"),
+            None => return,
+        };
+
+        if Path::new(shell)
+            .file_name()
+            .map_or(false, |name| name == "nu")
+            || nushell_dir.exists()
+        {
+            profiles.push(nushell_dir.join("env.nu"));
+        }
     }
 
     fn format_home(volta_home: &Path) -> String {
@@ -214,50 +327,201 @@ If you run into problems running Volta, create {} and run `volta setup` again.",
         let mut file = File::create(path)?;
         write!(
             file,
-            "{}\nset -gx VOLTA_HOME \"{}\"\nset -gx PATH \"$VOLTA_HOME/bin\" $PATH\n",
+            "{}\nset -gx VOLTA_HOME \"{}\"\ncontains -- \"$VOLTA_HOME/bin\" $fish_user_paths; or set -g fish_user_paths \"$VOLTA_HOME/bin\" $fish_user_paths\n",
             contents, volta_home,
         )
     }
+
+    fn write_profile_pwsh(path: &Path, contents: String, volta_home: &str) -> io::Result<()> {
+        // `$HOME` is also a PowerShell variable, so the formatted home works there as well
+        write_profile_powershell(path, contents, Some(volta_home))
+    }
+
+    fn write_profile_nu(path: &Path, contents: String, volta_home: &str) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // Nushell doesn't expand variables in paths, so `$HOME` is interpolated explicitly
+        let volta_home = match volta_home.strip_prefix("$HOME") {
+            Some(suffix) => format!("($env.HOME){}", suffix),
+            None => volta_home.to_string(),
+        };
+
+        let mut file = File::create(path)?;
+        write!(
+            file,
+            "{}\n$env.VOLTA_HOME = $\"{}\"\n$env.PATH = ($env.PATH | split row (char esep) | prepend $\"($env.VOLTA_HOME)/bin\")\n",
+            contents, volta_home,
+        )
+    }
+
+    /// Installs the completions next to the Nushell environment script, and loads them from the
+    /// Nushell config, since the completer has to be configured after the config is
+    fn write_nushell_completions(env_script: &Path) {
+        let dir = match env_script.parent() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let completions = dir.join("volta-completions.nu");
+        let config = dir.join("config.nu");
+
+        let result = fs::write(&completions, NUSHELL_COMPLETIONS).and_then(|_| {
+            let contents = read_profile_without_volta(&config).unwrap_or_default();
+            let mut file = File::create(&config)?;
+            write!(
+                file,
+                "{}\nsource \"{}\" # VOLTA completions\n",
+                contents,
+                completions.display()
+            )
+        });
+
+        match result {
+            Ok(()) => debug!("Installed Nushell completions to {}", completions.display()),
+            Err(err) => {
+                warn!(
+                    "Could not install Nushell completions to {}",
+                    completions.display()
+                );
+                debug!("Completions error: {}", err);
+            }
+        }
+    }
+
+    /// Installs the completions next to the fish config, in the directory that fish loads
+    /// completions from on demand
+    fn write_fish_completions(config: &Path) {
+        let completions = match config.parent() {
+            Some(dir) => dir.join("completions").join("volta.fish"),
+            None => return,
+        };
+
+        let result = fs::create_dir_all(completions.parent().unwrap_or(config))
+            .and_then(|_| File::create(&completions))
+            .and_then(|mut file| generate(Shell::Fish, &mut file));
+
+        match result {
+            Ok(()) => debug!("Installed fish completions to {}", completions.display()),
+            Err(err) => {
+                warn!(
+                    "Could not install fish completions to {}",
+                    completions.display()
+                );
+                debug!("Completions error: {}", err);
+            }
+        }
+    }
 }
 
 #[cfg(windows)]
 mod os {
-    use std::process::Command;
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::path::PathBuf;
 
-    use log::debug;
+    use log::{debug, warn};
     use volta_core::error::{Context, ErrorKind, Fallible};
     use volta_core::layout::volta_home;
-    use winreg::enums::HKEY_CURRENT_USER;
-    use winreg::RegKey;
+    use winapi::shared::minwindef::LPARAM;
+    use winapi::um::winuser::{
+        SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+    };
+    use winreg::enums::{RegType, HKEY_CURRENT_USER, KEY_READ, KEY_WRITE};
+    use winreg::{RegKey, RegValue};
+
+    use super::{read_profile_without_volta, write_profile_powershell};
 
     pub fn setup_environment() -> Fallible<()> {
         let shim_dir = volta_home()?.shim_dir().to_string_lossy().to_string();
         let hkcu = RegKey::predef(HKEY_CURRENT_USER);
         let env = hkcu
-            .open_subkey("Environment")
+            .open_subkey_with_flags("Environment", KEY_READ | KEY_WRITE)
             .with_context(|| ErrorKind::ReadUserPathError)?;
         let path: String = env
             .get_value("Path")
             .with_context(|| ErrorKind::ReadUserPathError)?;
 
         if !path.contains(&shim_dir) {
-            // Use `setx` command to edit the user Path environment variable
-            let mut command = Command::new("setx");
-            command.arg("Path");
-            command.arg(format!("{};{}", shim_dir, path));
-
-            debug!("Modifying User Path with command: {:?}", command);
-            let output = command
-                .output()
+            // The value is written directly, since `setx` truncates it to 1024 characters
+            let new_path = format!("{};{}", shim_dir, path);
+            debug!("Modifying User Path to: {}", new_path);
+            env.set_raw_value("Path", &expand_string_value(&new_path))
                 .with_context(|| ErrorKind::WriteUserPathError)?;
 
-            if !output.status.success() {
-                debug!("[setx stderr]\n{}", String::from_utf8_lossy(&output.stderr));
-                debug!("[setx stdout]\n{}", String::from_utf8_lossy(&output.stdout));
-                return Err(ErrorKind::WriteUserPathError.into());
+            broadcast_environment_change();
+        }
+
+        for profile in powershell_profiles() {
+            let contents = read_profile_without_volta(&profile).unwrap_or_default();
+            if let Err(err) = write_profile_powershell(&profile, contents, None) {
+                warn!(
+                    "Found PowerShell profile, but could not modify it: {}",
+                    profile.display()
+                );
+                debug!("Profile modification error: {}", err);
             }
         }
 
         Ok(())
     }
+
+    /// Encodes a string as a `REG_EXPAND_SZ` value, so that references to other variables in
+    /// the Path (e.g. `%USERPROFILE%`) keep working
+    fn expand_string_value(value: &str) -> RegValue {
+        let bytes = OsStr::new(value)
+            .encode_wide()
+            .chain(Some(0))
+            .flat_map(|unit| unit.to_le_bytes().to_vec())
+            .collect();
+
+        RegValue {
+            bytes,
+            vtype: RegType::REG_EXPAND_SZ,
+        }
+    }
+
+    /// Notifies running applications (notably Explorer) that the environment changed, so that
+    /// new terminals get the updated Path without signing out
+    fn broadcast_environment_change() {
+        let environment: Vec<u16> = OsStr::new("Environment")
+            .encode_wide()
+            .chain(Some(0))
+            .collect();
+        let mut result = 0;
+
+        // SAFETY: The message parameter is a valid, nul-terminated wide string that outlives the
+        // call, and the timeout keeps unresponsive windows from blocking setup
+        unsafe {
+            SendMessageTimeoutW(
+                HWND_BROADCAST,
+                WM_SETTINGCHANGE,
+                0,
+                environment.as_ptr() as LPARAM,
+                SMTO_ABORTIFHUNG,
+                5000,
+                &mut result,
+            );
+        }
+    }
+
+    /// Returns the profiles of Windows PowerShell, which is always installed, and of PowerShell
+    /// (Core), if it has been used
+    fn powershell_profiles() -> Vec<PathBuf> {
+        let documents = match dirs::document_dir() {
+            Some(documents) => documents,
+            None => return Vec::new(),
+        };
+
+        let mut profiles = vec![documents
+            .join("WindowsPowerShell")
+            .join("Microsoft.PowerShell_profile.ps1")];
+
+        let powershell_dir = documents.join("PowerShell");
+        if powershell_dir.exists() {
+            profiles.push(powershell_dir.join("Microsoft.PowerShell_profile.ps1"));
+        }
+
+        profiles
+    }
 }
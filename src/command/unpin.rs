@@ -0,0 +1,69 @@
+use log::info;
+use semver::Version;
+use structopt::StructOpt;
+
+use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::platform::{Platform, Sourced};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::{note_prefix, success_prefix, tool_version};
+use volta_core::tool::Spec;
+use volta_core::version::VersionSpec;
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct Unpin {
+    /// Tools to unpin, like `node` or `yarn`
+    #[structopt(name = "tool", required_unless = "all", min_values = 1)]
+    tools: Vec<String>,
+
+    /// Removes the whole `volta` key from package.json, including any `extends`
+    #[structopt(long = "all", conflicts_with = "tool")]
+    all: bool,
+}
+
+impl Command for Unpin {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Unpin);
+
+        if self.all {
+            let project = session.project_mut()?.ok_or(ErrorKind::NotInPackage)?;
+            project.unpin_all()?;
+            info!("{} removed all pins from package.json", success_prefix());
+        } else {
+            for tool in &self.tools {
+                Spec::from_str_and_version(tool, VersionSpec::default()).unpin(session)?;
+            }
+        }
+
+        report_current_platform(Platform::current(session)?);
+
+        session.add_event_end(ActivityKind::Unpin, ExitCode::Success);
+        Ok(ExitCode::Success)
+    }
+}
+
+/// Shows which tool versions the project will use now that the pins have been removed
+fn report_current_platform(platform: Option<Platform>) {
+    match platform {
+        Some(platform) => {
+            info!("{} this project will now use:", note_prefix());
+            report_tool("node", Some(&platform.node));
+            match &platform.npm {
+                Some(npm) => report_tool("npm", Some(npm)),
+                None => info!("    npm bundled with Node"),
+            }
+            report_tool("yarn", platform.yarn.as_ref());
+        }
+        None => info!(
+            "{} no Node version is available in this project. Use `volta install node` to set a default.",
+            note_prefix()
+        ),
+    }
+}
+
+fn report_tool(tool: &str, version: Option<&Sourced<Version>>) {
+    if let Some(Sourced { value, source }) = version {
+        info!("    {} ({})", tool_version(tool, value), source);
+    }
+}
@@ -0,0 +1,105 @@
+use log::info;
+use structopt::StructOpt;
+
+use volta_core::error::{report_error, ExitCode, Fallible};
+use volta_core::install_source::InstallSource;
+use volta_core::inventory::package_configs;
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::note_prefix;
+use volta_core::tool::package;
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct Upgrade {
+    /// The packages to upgrade, e.g. `typescript` or `ember-cli`
+    #[structopt(name = "package", raw(required_unless_one = r#"&["all", "self"]"#))]
+    packages: Vec<String>,
+
+    /// Upgrades every package installed with Volta
+    #[structopt(long = "all", conflicts_with = "package")]
+    all: bool,
+
+    /// Upgrades Volta itself, using the package manager that installed it
+    #[structopt(
+        name = "self",
+        long = "self",
+        raw(conflicts_with_all = r#"&["package", "all"]"#)
+    )]
+    upgrade_self: bool,
+}
+
+impl Command for Upgrade {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Upgrade);
+
+        if self.upgrade_self {
+            return upgrade_volta(session);
+        }
+
+        let packages = if self.all {
+            package_configs()?
+                .into_iter()
+                .map(|config| config.name)
+                .collect()
+        } else {
+            self.packages
+        };
+
+        // With `--all`, the remaining packages are still upgraded when one fails, and the failures
+        // are reported together once they are done
+        let mut failures = Vec::new();
+        for name in packages {
+            match package::upgrade(&name, session) {
+                Ok(()) => {}
+                Err(err) if self.all => failures.push(err),
+                Err(err) => return Err(err),
+            }
+        }
+
+        let exit_code = failures
+            .first()
+            .map_or(ExitCode::Success, |err| err.exit_code());
+        for err in &failures {
+            report_error(env!("CARGO_PKG_VERSION"), err);
+            session.add_event_error(ActivityKind::Upgrade, err);
+        }
+
+        session.add_event_end(ActivityKind::Upgrade, exit_code);
+        Ok(exit_code)
+    }
+}
+
+/// Hands off the upgrade to the package manager that owns Volta's files, or explains how to
+/// upgrade when there isn't one that can be run
+///
+/// Volta never replaces its own binaries, so that a package manager's record of the installed
+/// files stays accurate.
+fn upgrade_volta(session: &mut Session) -> Fallible<ExitCode> {
+    let source = InstallSource::current()?;
+
+    match source.delegate_upgrade()? {
+        Some(status) if status.success() => {}
+        Some(status) => {
+            session.add_event_tool_end(ActivityKind::Upgrade, status.code().unwrap_or(1));
+            return Ok(ExitCode::ExecutionFailure);
+        }
+        None if source == InstallSource::System => info!(
+            "{} Volta was installed by {}. Please use it to upgrade Volta.",
+            note_prefix(),
+            source
+        ),
+        None => info!("{} {}", note_prefix(), INSTALLER_INSTRUCTIONS),
+    }
+
+    session.add_event_end(ActivityKind::Upgrade, ExitCode::Success);
+    Ok(ExitCode::Success)
+}
+
+#[cfg(unix)]
+const INSTALLER_INSTRUCTIONS: &str =
+    "To upgrade Volta, run the latest installer: curl https://get.volta.sh | bash";
+
+#[cfg(windows)]
+const INSTALLER_INSTRUCTIONS: &str = "To upgrade Volta, download and run the latest installer \
+     from https://github.com/volta-cli/volta/releases";
@@ -1,38 +1,68 @@
+use std::ffi::OsStr;
+
 use structopt::StructOpt;
 
-use crate::command::Command;
 use volta_core::error::{ErrorKind, ExitCode, Fallible};
+use volta_core::platform::SHELL_PLATFORM_VAR;
 use volta_core::session::{ActivityKind, Session};
+use volta_core::tool::{node, npm, yarn, Spec};
 
-// NOTE: These use the same text as the `long_about` in crate::cli.
-//       It's hard to abstract since it's in an attribute string.
-
-pub(crate) const USAGE: &str = "The subcommand `use` is deprecated.
-
-    To install a tool in your toolchain, use `volta install`.
-    To pin your project's runtime or package manager, use `volta pin`.
-";
-
-const ADVICE: &str = "
-    To install a tool in your toolchain, use `volta install`.
-    To pin your project's runtime or package manager, use `volta pin`.
-";
+use crate::command::env::{warn_if_not_evaluated, Shell, SHELLS};
+use crate::command::Command;
 
 #[derive(StructOpt)]
 pub(crate) struct Use {
-    #[allow(dead_code)]
-    anything: Vec<String>, // Prevent StructOpt argument errors when invoking e.g. `volta use node`
+    /// Tools to use in this shell session, like `node@18` or `yarn@1.22`
+    #[structopt(name = "tool[@version]")]
+    tools: Vec<String>,
+
+    /// Goes back to the versions of the project or your default toolchain
+    #[structopt(long = "clear", conflicts_with = "tool[@version]")]
+    clear: bool,
+
+    /// Shell to write the statements for (defaults to the value of `SHELL`)
+    #[structopt(
+        long = "shell",
+        raw(possible_values = "SHELLS"),
+        case_insensitive = true
+    )]
+    shell: Option<String>,
 }
 
 impl Command for Use {
     fn run(self, session: &mut Session) -> Fallible<ExitCode> {
-        session.add_event_start(ActivityKind::Help);
-        let result = Err(ErrorKind::DeprecatedCommandError {
-            command: "use".to_string(),
-            advice: ADVICE.to_string(),
-        }
-        .into());
-        session.add_event_end(ActivityKind::Help, ExitCode::InvalidArguments);
-        result
+        session.add_event_start(ActivityKind::Use);
+
+        let shell = Shell::from_option(self.shell.as_deref());
+        let statement = if self.clear {
+            shell.unset(SHELL_PLATFORM_VAR)
+        } else {
+            if self.tools.is_empty() {
+                return Err(ErrorKind::UseWithoutTools.into());
+            }
+
+            // Tools selected earlier in the session stay selected, unless they are replaced
+            let mut platform = session.shell_platform().cloned().unwrap_or_default();
+            for spec in Spec::from_strings(&self.tools, "use")? {
+                match spec {
+                    Spec::Node(version) => platform.node = Some(node::resolve(version, session)?),
+                    // The bundled npm is selected by not overriding npm at all
+                    Spec::Npm(version) => platform.npm = npm::resolve(version, session)?,
+                    Spec::Yarn(version) => platform.yarn = Some(yarn::resolve(version, session)?),
+                    Spec::Package(name, _) | Spec::PackageSource(name) => {
+                        return Err(ErrorKind::UseUnsupportedTool { tool: name }.into());
+                    }
+                }
+            }
+            platform.fetch(session)?;
+
+            shell.export(SHELL_PLATFORM_VAR, OsStr::new(&platform.to_string()))
+        };
+
+        println!("{}", statement);
+        warn_if_not_evaluated("use <tools>");
+
+        session.add_event_end(ActivityKind::Use, ExitCode::Success);
+        Ok(ExitCode::Success)
     }
 }
@@ -0,0 +1,65 @@
+use log::{info, warn};
+use structopt::StructOpt;
+
+use volta_core::error::{ExitCode, Fallible};
+use volta_core::inventory::{repair_image, verify_images, DamagedImage, ImageProblem};
+use volta_core::session::{ActivityKind, Session};
+use volta_core::style::success_prefix;
+
+use crate::command::Command;
+
+#[derive(StructOpt)]
+pub(crate) struct Verify {
+    /// Fetches damaged versions again
+    #[structopt(long = "repair")]
+    repair: bool,
+
+    /// Also checks Node versions against the checksums published by the server they came from
+    #[structopt(long = "published")]
+    published: bool,
+}
+
+impl Command for Verify {
+    fn run(self, session: &mut Session) -> Fallible<ExitCode> {
+        session.add_event_start(ActivityKind::Verify);
+
+        let damaged = verify_images(self.published)?;
+        for image in &damaged {
+            report_damaged(image);
+        }
+
+        let exit_code = if damaged.is_empty() {
+            info!("{} All installed versions are intact.", success_prefix());
+            ExitCode::Success
+        } else if self.repair {
+            for image in &damaged {
+                repair_image(image, session)?;
+                info!(
+                    "{} repaired {}@{}",
+                    success_prefix(),
+                    image.tool,
+                    image.version
+                );
+            }
+            ExitCode::Success
+        } else {
+            warn!("Run `volta verify --repair` to fetch the damaged versions again.");
+            ExitCode::FileSystemError
+        };
+
+        session.add_event_end(ActivityKind::Verify, exit_code);
+        Ok(exit_code)
+    }
+}
+
+fn report_damaged(image: &DamagedImage) {
+    let reason = match image.problem {
+        ImageProblem::Incomplete => "is missing files",
+        ImageProblem::Modified => "has files that changed since it was installed",
+        ImageProblem::ArchiveChanged => "was unpacked from an archive that has since changed",
+        ImageProblem::PublishedMismatch => {
+            "doesn't match the checksum published by the server it was downloaded from"
+        }
+    };
+    warn!("{}@{} {}.", image.tool, image.version, reason);
+}
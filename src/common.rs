@@ -1,26 +1,35 @@
-use std::process::{Command, ExitStatus};
+use std::env;
+use std::process::ExitStatus;
 
-use volta_core::error::{Context, ErrorKind, VoltaError};
-use volta_core::layout::{volta_home, volta_install};
+use volta_core::error::{report_error, VoltaError};
+use volta_core::permissions::ensure_safe_permissions;
+use volta_core::session::Session;
+use volta_migrate::ensure_current_layout;
 
 pub enum Error {
     Volta(VoltaError),
     Tool(i32),
 }
 
-pub fn ensure_layout() -> Result<(), Error> {
-    let home = volta_home().map_err(Error::Volta)?;
-
-    if !home.layout_file().exists() {
-        let install = volta_install().map_err(Error::Volta)?;
-        Command::new(install.migrate_executable())
-            .env("VOLTA_LOGLEVEL", format!("{}", log::max_level()))
-            .status()
-            .with_context(|| ErrorKind::CouldNotStartMigration)
-            .into_result()?;
+/// Builds the session from a snapshot of the environment, so that everything Volta reads from the
+/// environment comes from the same snapshot
+pub fn init_session() -> Session {
+    match Session::builder().env(env::vars_os()).build() {
+        Ok(session) => session,
+        Err(err) => {
+            report_error(env!("CARGO_PKG_VERSION"), &err);
+            err.exit_code().exit();
+        }
     }
+}
+
+pub fn ensure_layout() -> Result<(), Error> {
+    // Nothing in the Volta directory can be trusted if other users are able to change it
+    ensure_safe_permissions().map_err(Error::Volta)?;
 
-    Ok(())
+    // Migrations run in-process, so that upgrading only the `volta` binary over an old Volta
+    // directory still works
+    ensure_current_layout().map_err(Error::Volta)
 }
 
 pub trait IntoResult<T> {
@@ -6,10 +6,11 @@ use structopt::StructOpt;
 
 use volta_core::error::report_error;
 use volta_core::log::{LogContext, LogVerbosity, Logger};
-use volta_core::session::{ActivityKind, Session};
+use volta_core::session::ActivityKind;
+use volta_core::signal::setup_signal_handler;
 
 mod common;
-use common::{ensure_layout, Error};
+use common::{ensure_layout, init_session, Error};
 
 /// The entry point for the `volta` CLI.
 pub fn main() {
@@ -23,8 +24,9 @@ pub fn main() {
         ),
     };
     Logger::init(LogContext::Volta, verbosity).expect("Only a single logger should be initialized");
+    setup_signal_handler();
 
-    let mut session = Session::init();
+    let mut session = init_session();
     session.add_event_start(ActivityKind::Volta);
 
     let result = ensure_layout().and_then(|()| volta.run(&mut session).map_err(Error::Volta));
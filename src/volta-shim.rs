@@ -1,10 +1,10 @@
 mod common;
 
-use common::{ensure_layout, Error, IntoResult};
+use common::{ensure_layout, init_session, Error, IntoResult};
 use volta_core::error::{report_error, ExitCode};
 use volta_core::log::{LogContext, LogVerbosity, Logger};
 use volta_core::run::execute_shim;
-use volta_core::session::{ActivityKind, Session};
+use volta_core::session::ActivityKind;
 use volta_core::signal::setup_signal_handler;
 
 pub fn main() {
@@ -12,7 +12,7 @@ pub fn main() {
         .expect("Only a single Logger should be initialized");
     setup_signal_handler();
 
-    let mut session = Session::init();
+    let mut session = init_session();
     session.add_event_start(ActivityKind::Tool);
 
     let result = ensure_layout().and_then(|()| execute_shim(&mut session).into_result());
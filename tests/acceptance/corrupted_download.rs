@@ -1,4 +1,4 @@
-use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Yarn1Fixture};
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox, Yarn1Fixture};
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
 use semver::Version;
@@ -111,3 +111,95 @@ fn install_valid_yarn_saves_to_inventory() {
 
     assert!(s.yarn_inventory_archive_exists("1.2.42"));
 }
+
+#[test]
+fn install_yarn_records_checksum() {
+    let s = sandbox()
+        .platform(r#"{ "node": { "runtime": "1.2.3", "npm": null }, "yarn": null }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("install yarn@1.2.42"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    let checksums = Sandbox::read_checksums();
+    assert!(checksums.contains("/yarn/-/yarn-1.2.42.tgz"));
+}
+
+#[test]
+fn install_yarn_with_changed_checksum_leaves_inventory_unchanged() {
+    let url = format!("{}/yarn/-/yarn-1.2.42.tgz", mockito::SERVER_URL);
+    let checksums = format!(
+        r#"{{ "{}": {{ "sha256": "{}", "verification": "first-use" }} }}"#,
+        url,
+        "0".repeat(64)
+    );
+    let s = sandbox()
+        .platform(r#"{ "node": { "runtime": "1.2.3", "npm": null }, "yarn": null }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .file(".volta/checksums.json", &checksums)
+        .build();
+
+    assert_that!(
+        s.volta("install yarn@1.2.42"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stderr_contains("[..]Could not unpack[..]")
+    );
+
+    assert!(!s.yarn_inventory_archive_exists("1.2.42"));
+}
+
+#[test]
+fn install_yarn_verifies_registry_integrity() {
+    let s = sandbox()
+        .platform(r#"{ "node": { "runtime": "1.2.3", "npm": null }, "yarn": null }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_1_version_details(
+            "1.2.42",
+            r#"{ "version": "1.2.42", "dist": { "integrity": "sha512-F0sPBsZOIl2BLybQCXk4qUyLPZm5Y6XqXuoM8ptqZmUGJURfscez+JfmJazc3Jd6G6VxIKUzHHIibug+mmTFBw==" } }"#,
+        )
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("install yarn@1.2.42"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert!(Sandbox::read_checksums().contains("\"verification\": \"published-checksum\""));
+}
+
+#[test]
+fn install_yarn_with_wrong_registry_integrity_leaves_inventory_unchanged() {
+    let s = sandbox()
+        .platform(r#"{ "node": { "runtime": "1.2.3", "npm": null }, "yarn": null }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .yarn_1_version_details(
+            "1.2.42",
+            r#"{ "version": "1.2.42", "dist": { "shasum": "0000000000000000000000000000000000000000" } }"#,
+        )
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("install yarn@1.2.42"),
+        execs()
+            .with_status(ExitCode::UnknownError as i32)
+            .with_stderr_contains("[..]Could not unpack[..]")
+    );
+
+    assert!(!s.yarn_inventory_archive_exists("1.2.42"));
+}
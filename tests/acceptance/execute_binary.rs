@@ -174,6 +174,13 @@ const PLATFORM_NODE_NPM: &str = r#"{
     }
 }"#;
 
+const PLATFORM_NODE_12: &str = r#"{
+    "node":{
+        "runtime":"12.16.1",
+        "npm":null
+    }
+}"#;
+
 #[test]
 fn default_binary_no_project() {
     // platform node is 11.10.1, npm is 6.7.0
@@ -240,6 +247,35 @@ fn default_binary_no_project_dep() {
     );
 }
 
+#[test]
+fn default_binary_uses_recorded_platform() {
+    // platform node is 12.16.1, npm is bundled
+    // package cowsay is 1.4.0, installed with node 11.10.1 and npm 6.7.0
+    // the default changed after the install, so it should still run with the recorded platform
+    let s = sandbox()
+        .platform(PLATFORM_NODE_12)
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .binary_config("cowthink", &bin_config("cowthink"))
+        .shim("cowsay")
+        .shim("cowthink")
+        .package_image("cowsay", "1.4.0", Some(cowsay_bin_info("1.4.0")))
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .setup_npm_binary("6.7.0", &npm_bin("6.7.0"))
+        .add_dir_to_path(PathBuf::from("/bin"))
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.exec_shim("cowsay", "foo"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay version 1.4.0")
+            .with_stderr_contains("[..]Node: 11.10.1 from binary configuration")
+            .with_stderr_contains("[..]npm: 6.7.0 from binary configuration")
+    );
+}
+
 #[test]
 fn project_local_binary() {
     // platform node is 11.10.1, npm is 6.7.0
@@ -0,0 +1,42 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::{matchers::execs, paths};
+use volta_core::error::ExitCode;
+
+#[test]
+fn setup_writes_path_for_later_steps() {
+    let s = sandbox()
+        .env("GITHUB_ACTIONS", "true")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let path_file = s.root().join("github_path");
+    let env_file = s.root().join("github_env");
+
+    assert_that!(
+        s.volta("setup")
+            .env("GITHUB_PATH", &path_file)
+            .env("GITHUB_ENV", &env_file),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]Volta is available in the following steps of this job.")
+    );
+
+    let shim_dir = paths::home().join(".volta").join("bin");
+    assert!(s
+        .read_project_file("github_path")
+        .contains(&*shim_dir.to_string_lossy()));
+    assert!(s.read_project_file("github_env").contains("VOLTA_HOME="));
+}
+
+#[test]
+fn errors_are_annotated() {
+    let s = sandbox().env("GITHUB_ACTIONS", "true").build();
+
+    assert_that!(
+        s.volta("env --shell bash"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stdout_contains("::error title=Volta::[..]Node is not available.%0A[..]")
+    );
+}
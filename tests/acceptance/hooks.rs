@@ -156,6 +156,22 @@ fn yarn_hooks_format_json(format: &str) -> String {
     )
 }
 
+fn node_hooks_format_json(format: &str) -> String {
+    format!(
+        r#"
+{{
+    "node": {{
+        "index": {{
+            "template": "{0}/node-registry/node",
+            "format": "{1}"
+        }}
+    }}
+}}"#,
+        mockito::server_url(),
+        format
+    )
+}
+
 #[test]
 fn redirects_download() {
     let s = sandbox()
@@ -425,3 +441,79 @@ fn yarn_semver_with_hook_uses_configured_format() {
             .with_stderr_contains("[..]Could not download yarn@3.12.99")
     );
 }
+
+#[test]
+fn node_semver_with_hook_uses_configured_format() {
+    let s = sandbox()
+        .default_hooks(&node_hooks_format_json("npm"))
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+    let _mock = mock("GET", "/node-registry/node")
+        .with_status(200)
+        .with_header("Content-Type", "application/json")
+        .with_body(
+            // Should be using the Npm format
+            r#"{
+    "name":"node",
+    "dist-tags": { "latest":"17.0.1" },
+    "versions": {
+        "14.18.1": { "version":"14.18.1", "lts":"Fermium" },
+        "16.13.0": { "version":"16.13.0", "lts":"Gallium" },
+        "16.4.2": { "version":"16.4.2" },
+        "17.0.1": { "version":"17.0.1" }
+    }
+}"#,
+        )
+        .create();
+
+    assert_that!(
+        s.volta("install node@16"),
+        execs()
+            .with_status(ExitCode::NetworkError as i32)
+            .with_stderr_contains("[..]Using node.index hook to determine node index URL")
+            .with_stderr_contains("[..]Found node@16.13.0 matching requirement[..]")
+            .with_stderr_contains("[..]Could not download node@16.13.0")
+    );
+}
+
+#[test]
+fn project_bin_hooks_need_approval() {
+    let local_hooks: PathBuf = [".volta", "hooks.json"].iter().collect();
+    let s = sandbox()
+        .package_json("{}")
+        .project_file(
+            &local_hooks.to_string_lossy(),
+            r#"{ "yarn": { "distro": { "bin": "./resolve-yarn" } } }"#,
+        )
+        .build();
+
+    // Without a terminal to ask for approval, the hooks are refused rather than run
+    assert_that!(
+        s.volta("install yarn@3.2.1"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]run commands that haven't been allowed.")
+    );
+}
+
+#[test]
+fn project_bin_hooks_allowed_by_env() {
+    let local_hooks: PathBuf = [".volta", "hooks.json"].iter().collect();
+    let s = sandbox()
+        .package_json("{}")
+        .project_file(
+            &local_hooks.to_string_lossy(),
+            r#"{ "node": { "index": { "bin": "./resolve-node-index" } } }"#,
+        )
+        .env("VOLTA_ALLOW_PROJECT_HOOKS", "1")
+        .build();
+
+    // The hook is run (and fails, since the command doesn't exist) without being reviewed
+    assert_that!(
+        s.volta("install node@16"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not determine path to hook command[..]")
+            .with_stderr_does_not_contain("[..]haven't been allowed[..]")
+    );
+}
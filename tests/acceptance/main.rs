@@ -9,15 +9,39 @@ cfg_if! {
         mod direct_install;
         mod direct_uninstall;
         mod execute_binary;
+        mod github_actions;
         mod hooks;
         mod merged_platform;
         mod migrations;
+        mod nvm_shim;
         mod run_shim_directly;
+        #[cfg(unix)]
+        mod unsafe_permissions;
         mod verbose_errors;
+        mod volta_activate;
+        mod volta_audit;
         mod volta_bypass;
+        mod volta_complete;
+        mod volta_current;
+        mod volta_doctor;
+        mod volta_env;
+        mod volta_evict_unused;
+        mod volta_exec;
+        mod volta_fetch;
+        mod volta_import;
+        mod volta_inspect;
         mod volta_install;
+        mod volta_inventory;
+        mod volta_migrate;
         mod volta_pin;
         mod volta_run;
+        mod volta_sbom;
+        mod volta_settings_bundle;
         mod volta_uninstall;
+        mod volta_unpin;
+        mod volta_upgrade;
+        mod volta_use;
+        mod volta_verify;
+        mod volta_x;
     }
 }
@@ -23,6 +23,14 @@ const PACKAGE_JSON_WITH_NPM: &str = r#"{
     }
 }"#;
 
+const PACKAGE_JSON_WITH_BUNDLED_NPM: &str = r#"{
+    "name": "with-bundled-npm",
+    "volta": {
+        "node": "10.99.1040",
+        "npm": "6.2.26"
+    }
+}"#;
+
 const PACKAGE_JSON_WITH_YARN: &str = r#"{
     "name": "with-yarn",
     "volta": {
@@ -200,6 +208,46 @@ fn uses_bundled_npm_in_project_without_npm() {
     );
 }
 
+#[test]
+fn uses_bundled_copy_of_pinned_npm() {
+    // There is no fixture for npm 6.2.26, so this fails if npm is fetched separately
+    let s = sandbox()
+        .platform(PLATFORM_WITH_NPM)
+        .package_json(PACKAGE_JSON_WITH_BUNDLED_NPM)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<NpmFixture>(&NPM_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.npm("--version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]npm@6.2.26 is bundled with node@10.99.1040[..]")
+            .with_stderr_contains("[..]npm: 6.2.26 from project configuration")
+    );
+}
+
+#[test]
+fn uses_shell_session_node_over_project() {
+    let s = sandbox()
+        .platform(PLATFORM_WITH_NPM)
+        .package_json(PACKAGE_JSON_WITH_NPM)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<NpmFixture>(&NPM_VERSION_FIXTURES)
+        .env("VOLTA_USE", "node@9.27.6")
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.npm("--version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Node: 9.27.6 from shell session configuration")
+            .with_stderr_contains("[..]npm: 4.5.6 from project configuration")
+    );
+}
+
 #[test]
 fn uses_default_npm_outside_project() {
     let s = sandbox()
@@ -293,6 +341,24 @@ fn uses_default_yarn_in_project_without_yarn() {
     );
 }
 
+#[test]
+fn uses_package_manager_yarn_in_unpinned_project() {
+    let s = sandbox()
+        .platform(PLATFORM_WITH_YARN)
+        .package_json(r#"{ "name": "corepack", "packageManager": "yarn@1.12.99" }"#)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.yarn("--version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]Yarn: 1.12.99 from project configuration")
+    );
+}
+
 #[test]
 fn uses_default_yarn_outside_project() {
     let s = sandbox()
@@ -338,3 +404,54 @@ fn throws_default_error_outside_project() {
             .with_stderr_contains("[..]Yarn is not available.")
     );
 }
+
+#[test]
+fn strict_does_not_use_default_yarn_in_project() {
+    let s = sandbox()
+        .platform(PLATFORM_WITH_YARN)
+        .package_json(PACKAGE_JSON_NODE_ONLY)
+        .env("VOLTA_STRICT", "1")
+        .build();
+
+    assert_that!(
+        s.yarn("--version"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]No Yarn version found in this project.")
+    );
+}
+
+#[test]
+fn strict_throws_error_in_unpinned_project() {
+    let s = sandbox()
+        .platform(PLATFORM_WITH_YARN)
+        .package_json(r#"{ "name": "unpinned" }"#)
+        .env("VOLTA_STRICT", "1")
+        .build();
+
+    assert_that!(
+        s.yarn("--version"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]No pinned Node version found in [..]")
+    );
+}
+
+#[test]
+fn fails_instead_of_fetching_when_auto_download_is_disabled() {
+    let s = sandbox()
+        .platform(PLATFORM_WITH_NPM)
+        .package_json(PACKAGE_JSON_NODE_ONLY)
+        .project_file(".volta/settings.json", r#"{ "autoDownload": false }"#)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.npm("--version"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains(
+                "[..]Could not use node@10.99.1040, because it hasn't been fetched[..]"
+            )
+    );
+}
@@ -2,6 +2,7 @@ use crate::support::sandbox::{sandbox, Sandbox};
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
 use test_support::matchers::execs;
+use volta_core::error::ExitCode;
 
 #[test]
 fn empty_volta_home_is_created() {
@@ -228,3 +229,21 @@ fn current_v3_volta_home_is_unchanged() {
     assert!(Sandbox::path_exists(".volta/tools/inventory/node"));
     assert!(Sandbox::path_exists(".volta/tools/inventory/yarn"));
 }
+
+#[test]
+fn newer_volta_home_is_not_migrated() {
+    let s = sandbox().layout_file("v4").build();
+
+    // the directory is from a newer version of Volta, so it can't be migrated or used
+    assert_that!(
+        s.volta("--version"),
+        execs()
+            .with_status(ExitCode::EnvironmentError as i32)
+            .with_stderr_contains("[..]upgraded by a newer version of Volta (layout v4)[..]")
+    );
+
+    // nothing should be changed
+    assert!(Sandbox::path_exists(".volta/layout.v4"));
+    assert!(!Sandbox::path_exists(".volta/layout.v3"));
+    assert!(!Sandbox::path_exists(".volta/tools/user"));
+}
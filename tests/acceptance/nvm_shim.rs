@@ -0,0 +1,69 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"14.17.0",
+        "npm":null
+    },
+    "yarn":null
+}"#;
+
+#[test]
+fn current_prints_active_version() {
+    let s = sandbox().platform(PLATFORM_NODE).shim("nvm").build();
+
+    assert_that!(
+        s.exec_shim("nvm", "current"),
+        execs().with_status(0).with_stdout("v14.17.0")
+    );
+}
+
+#[test]
+fn use_sets_installed_version_as_default() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("12.16.1", "6.13.4", "")
+        .shim("nvm")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.exec_shim("nvm", "use v12.16.1"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("[..]Now using node v12.16.1")
+    );
+    assert_that!(
+        s.exec_shim("nvm", "current"),
+        execs().with_status(0).with_stdout("v12.16.1")
+    );
+}
+
+#[test]
+fn use_requires_installed_version() {
+    let s = sandbox().platform(PLATFORM_NODE).shim("nvm").build();
+
+    assert_that!(
+        s.exec_shim("nvm", "use 12.16.1"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]No installed version of Node matches '12.16.1'.")
+    );
+}
+
+#[test]
+fn unsupported_command_is_an_error() {
+    let s = sandbox().shim("nvm").build();
+
+    assert_that!(
+        s.exec_shim("nvm", "ls-remote"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]'nvm ls-remote' is not supported by Volta.")
+    );
+}
@@ -361,6 +361,17 @@ impl SandboxBuilder {
         self
     }
 
+    /// Setup mock to return the registry details of a single Yarn@1 version (chainable)
+    pub fn yarn_1_version_details(mut self, version: &str, body: &str) -> Self {
+        let mock = mock("GET", &format!("/yarn/{}", version)[..])
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .create();
+        self.root.mocks.push(mock);
+        self
+    }
+
     /// Setup mock to return the available Yarn@2+ versions (chainable)
     pub fn yarn_berry_available_versions(mut self, body: &str) -> Self {
         let mock = mock("GET", "/@yarnpkg/cli-dist")
@@ -571,6 +582,22 @@ impl SandboxBuilder {
         self
     }
 
+    /// Write an executable npm binary into the image of a node version, like the npm bundled with
+    /// it, with the input contents (chainable)
+    pub fn setup_bundled_npm_binary(mut self, node_version: &str, contents: &str) -> Self {
+        cfg_if! {
+            if #[cfg(target_os = "windows")] {
+                let npm_file = "npm.cmd";
+            } else {
+                let npm_file = "npm";
+            }
+        }
+        let npm_bin_file = node_image_dir(node_version).join("bin").join(npm_file);
+        self.files
+            .push(FileBuilder::new(npm_bin_file, contents).make_executable());
+        self
+    }
+
     /// Write an executable yarn binary with the input contents (chainable)
     pub fn setup_yarn_binary(mut self, version: &str, contents: &str) -> Self {
         cfg_if! {
@@ -778,6 +805,7 @@ impl Sandbox {
             .env("PATH", &self.path)
             .env("VOLTA_POSTSCRIPT", volta_postscript())
             .env_remove("VOLTA_SHELL")
+            .env_remove("GITHUB_ACTIONS")
             .env_remove("MSYSTEM"); // assume cmd.exe everywhere on windows
 
         // overrides for env vars
@@ -833,6 +861,10 @@ impl Sandbox {
         read_file_to_string(package_file)
     }
 
+    pub fn read_project_file(&self, path: &str) -> String {
+        read_file_to_string(self.root().join(path))
+    }
+
     pub fn read_log_dir(&self) -> Option<fs::ReadDir> {
         fs::read_dir(volta_log_dir()).ok()
     }
@@ -849,6 +881,14 @@ impl Sandbox {
             .exists()
     }
 
+    pub fn node_inventory_archive(&self, version: &Version) -> PathBuf {
+        node_inventory_dir().join(Node::archive_filename(version))
+    }
+
+    pub fn node_image(&self, version: &Version) -> PathBuf {
+        node_image_dir(&version.to_string())
+    }
+
     pub fn yarn_inventory_archive_exists(&self, version: &str) -> bool {
         yarn_inventory_dir()
             .join(Yarn::archive_filename(version))
@@ -871,9 +911,15 @@ impl Sandbox {
         let package_img_dir = package_image_dir(name);
         package_img_dir.join("package.json").exists()
     }
+    pub fn default_platform_exists() -> bool {
+        default_platform_file().exists()
+    }
     pub fn read_default_platform() -> String {
         read_file_to_string(default_platform_file())
     }
+    pub fn read_checksums() -> String {
+        read_file_to_string(volta_home().join("checksums.json"))
+    }
 }
 
 impl Drop for Sandbox {
@@ -0,0 +1,46 @@
+use std::fs::{metadata, set_permissions};
+use std::os::unix::fs::PermissionsExt;
+
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use test_support::paths;
+
+use volta_core::error::ExitCode;
+
+fn make_shims_world_writable() {
+    let shim_dir = paths::home().join(".volta").join("bin");
+    let mut permissions = metadata(&shim_dir).unwrap().permissions();
+    permissions.set_mode(0o777);
+    set_permissions(&shim_dir, permissions).unwrap();
+}
+
+#[test]
+fn world_writable_shims_are_refused() {
+    let s = sandbox().build();
+    make_shims_world_writable();
+
+    assert_that!(
+        s.volta("--version"),
+        execs()
+            .with_status(ExitCode::EnvironmentError as i32)
+            .with_stderr_contains("[..]is writable by every user[..]")
+    );
+}
+
+#[test]
+fn world_writable_shims_can_be_allowed_with_warning() {
+    let s = sandbox()
+        .env("VOLTA_UNSAFE_PERMISSIONS", "warn")
+        .env("VOLTA_LOGLEVEL", "warn")
+        .build();
+    make_shims_world_writable();
+
+    assert_that!(
+        s.volta("--version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains("[..]is writable by every user[..]")
+    );
+}
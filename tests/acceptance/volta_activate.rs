@@ -0,0 +1,33 @@
+use std::path::PathBuf;
+
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+#[test]
+fn deactivate_removes_shims_from_path() {
+    let system_bin = PathBuf::from("/opt/system-node/bin");
+    let s = sandbox().add_dir_to_path(system_bin.clone()).build();
+
+    assert_that!(
+        s.volta("deactivate --shell bash"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(format!("export PATH='{}'", system_bin.display()))
+    );
+}
+
+#[test]
+fn activate_keeps_shims_on_path() {
+    let system_bin = PathBuf::from("/opt/system-node/bin");
+    let s = sandbox().add_dir_to_path(system_bin).build();
+
+    assert_that!(
+        s.volta("activate --shell fish"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("set -gx PATH '[..].volta[..]bin' '/opt/system-node/bin'")
+    );
+}
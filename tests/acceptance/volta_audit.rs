@@ -0,0 +1,70 @@
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Yarn1Fixture};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "10.99.1040",
+    compressed_size: 273,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+const YARN_1_VERSION_INFO: &str = r#"{
+    "name":"yarn",
+    "dist-tags": { "latest": "1.2.42" },
+    "versions": {
+        "1.2.42": { "version":"1.2.42", "dist": { "shasum":"", "tarball":"" }}
+    }
+}"#;
+
+const YARN_1_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "1.2.42",
+    compressed_size: 174,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+#[test]
+fn audit_without_tools() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("audit"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]\"runtimes\": [],")
+            .with_stdout_contains("[..]\"packages\": []")
+    );
+}
+
+#[test]
+fn audit_reports_download_source() {
+    let s = sandbox()
+        .platform(r#"{ "node": { "runtime": "1.2.3", "npm": null }, "yarn": null }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("install yarn@1.2.42"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    // The mock registry doesn't publish checksums, so the download was trusted on first use
+    assert_that!(
+        s.volta("audit"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]\"tool\": \"yarn\",")
+            .with_stdout_contains("[..]\"source\": \"[..]/yarn/-/yarn-1.2.42.tgz\",")
+            .with_stdout_contains("[..]\"verification\": \"first-use\",")
+    );
+}
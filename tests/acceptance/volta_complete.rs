@@ -0,0 +1,54 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+const PKG_CONFIG_COWSAY: &str = r#"{
+  "name": "cowsay",
+  "version": "1.4.0",
+  "platform": {
+    "node": "11.10.1",
+    "npm": "6.7.0",
+    "yarn": null
+  },
+  "bins": [
+    "cowsay",
+    "cowthink"
+  ],
+  "manager": "Npm"
+}"#;
+
+#[test]
+fn completes_installed_packages() {
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .build();
+
+    assert_that!(
+        s.volta("__complete uninstall co"),
+        execs().with_status(0).with_stdout("cowsay")
+    );
+    assert_that!(
+        s.volta("__complete install cowsay@"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("cowsay@1.4.0")
+            .with_stdout_contains("cowsay@latest")
+    );
+    assert_that!(
+        s.volta("__complete which cowt"),
+        execs().with_status(0).with_stdout("cowthink")
+    );
+}
+
+#[test]
+fn leaves_flags_to_static_completions() {
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_COWSAY)
+        .build();
+
+    assert_that!(
+        s.volta("__complete install --verb"),
+        execs().with_status(0).with_stdout("")
+    );
+}
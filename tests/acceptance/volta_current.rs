@@ -0,0 +1,56 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+const PLATFORM_NODE_YARN: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":null
+    },
+    "yarn":"1.22.4"
+}"#;
+
+const PACKAGE_JSON_NODE: &str = r#"{
+    "name": "text-package",
+    "volta": {
+        "node": "10.99.1040"
+    }
+}"#;
+
+#[test]
+fn porcelain_without_platform() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("current --porcelain"),
+        execs().with_status(0).with_stdout("")
+    );
+}
+
+#[test]
+fn porcelain_default_platform() {
+    let s = sandbox().platform(PLATFORM_NODE_YARN).build();
+
+    assert_that!(
+        s.volta("current --porcelain"),
+        execs()
+            .with_status(0)
+            .with_stdout("node@11.10.1:default yarn@1.22.4:default")
+    );
+}
+
+#[test]
+fn porcelain_project_platform() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_YARN)
+        .package_json(PACKAGE_JSON_NODE)
+        .build();
+
+    assert_that!(
+        s.volta("current --porcelain"),
+        execs()
+            .with_status(0)
+            .with_stdout("node@10.99.1040:project yarn@1.22.4:default")
+    );
+}
@@ -0,0 +1,66 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+#[cfg(unix)]
+use test_support::paths;
+use volta_core::error::ExitCode;
+
+#[test]
+fn reports_no_problems() {
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]No problems found.")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn reports_nvm_before_shims() {
+    let s = sandbox()
+        .file(".nvm/versions/node/v10.99.1040/bin/node", "")
+        .env("VOLTA_LOGLEVEL", "warn")
+        .build();
+    let nvm_bin = paths::home()
+        .join(".nvm")
+        .join("versions")
+        .join("node")
+        .join("v10.99.1040")
+        .join("bin");
+    let path = std::env::join_paths(&[nvm_bin, paths::home().join(".volta").join("bin")]).unwrap();
+
+    assert_that!(
+        s.volta("doctor").env("PATH", path),
+        execs()
+            .with_status(ExitCode::EnvironmentError as i32)
+            .with_stderr_contains(
+                "[..]node from nvm in [..] will be used instead of Volta's shims[..]"
+            )
+            .with_stderr_contains("Remove the lines that load nvm from your shell profile[..]")
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn reports_replaced_shims() {
+    // With the current layout in place, no migration regenerates the shims before they are checked
+    let s = sandbox()
+        .file(".volta/layout.v3", "")
+        .file(".volta/bin/node", "#!/bin/sh\n")
+        .env("VOLTA_LOGLEVEL", "warn")
+        .build();
+
+    assert_that!(
+        s.volta("doctor"),
+        execs()
+            .with_status(ExitCode::EnvironmentError as i32)
+            .with_stderr_contains(
+                "[..]/.volta/bin/node is not a link to Volta's shim executable[..]"
+            )
+            .with_stderr_contains("Run `volta setup` to regenerate the shims.")
+    );
+}
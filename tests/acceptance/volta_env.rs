@@ -0,0 +1,88 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":null
+    }
+}"#;
+
+#[test]
+fn prints_exports_for_default_platform() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("env --shell bash"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("export VOLTA_HOME='[..]'")
+            .with_stdout_contains("export PATH='[..]11.10.1[..]'")
+    );
+}
+
+#[test]
+fn fails_without_platform() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("env --shell bash"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Node is not available.")
+    );
+}
+
+#[test]
+fn prints_fish_statements() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("env --shell fish"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("set -gx VOLTA_HOME '[..]'")
+            .with_stdout_contains("set -gx PATH '[..]11.10.1[..]' [..]")
+    );
+}
+
+#[test]
+fn prints_powershell_statements() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("env --shell powershell"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("$env:VOLTA_HOME = '[..]'")
+            .with_stdout_contains("$env:PATH = '[..]11.10.1[..]'")
+    );
+}
+
+#[test]
+fn prints_nushell_statements() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("env --shell nu"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("$env.VOLTA_HOME = \"[..]\"")
+            .with_stdout_contains("$env.PATH = [\"[..]11.10.1[..]\", [..]]")
+    );
+}
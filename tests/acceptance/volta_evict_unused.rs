@@ -0,0 +1,73 @@
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "10.99.1040",
+    compressed_size: 273,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+#[test]
+fn evict_unused_without_setting() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("evict-unused --yes"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]No versions are unused.")
+    );
+    assert!(s.node_image(&Version::new(10, 99, 1040)).exists());
+}
+
+#[test]
+fn evict_unused_lists_before_removing() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .file(".volta/settings.json", r#"{ "evictUnusedAfterDays": 0 }"#)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let image = s.node_image(&Version::new(10, 99, 1040));
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    // Without --yes, the versions are only listed
+    assert_that!(
+        s.volta("evict-unused"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("node@10.99.1040 (unused for 0 days)")
+            .with_stdout_contains("[..]Run `volta evict-unused --yes` to remove them.")
+    );
+    assert!(image.exists());
+
+    assert_that!(
+        s.volta("evict-unused --yes"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]removed unused node@10.99.1040")
+    );
+    assert!(!image.exists());
+}
@@ -0,0 +1,99 @@
+use crate::support::sandbox::sandbox;
+use cfg_if::cfg_if;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":null
+    }
+}"#;
+
+fn node_bin(version: &str) -> String {
+    cfg_if! {
+            if #[cfg(target_os = "windows")] {
+                format!(
+                    r#"@echo off
+echo Node version {}
+echo node args: %*
+"#,
+    version
+                )
+            } else {
+                format!(
+                    r#"#!/bin/sh
+echo "Node version {}"
+echo "node args: $@"
+"#,
+    version
+                )
+            }
+        }
+}
+
+#[test]
+fn exec_without_platform() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("exec -- node --version"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Node is not available.")
+    );
+}
+
+#[test]
+fn exec_uses_platform_path() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .build();
+
+    assert_that!(
+        s.volta("exec -- node --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Node version 11.10.1")
+            .with_stdout_contains("node args: --version")
+    );
+}
+
+#[test]
+fn exec_uses_images_installed_without_markers() {
+    // The sandbox images have no `.volta-image.json`, like those installed by older versions of
+    // Volta, and there is no registry to fetch them from again
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .env("VOLTA_LOGLEVEL", "debug")
+        .build();
+
+    assert_that!(
+        s.volta("exec -- node --version"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("Node version 11.10.1")
+            .with_stderr_does_not_contain("[..]Downloading[..]")
+    );
+}
+
+// On Windows, commands are run through `cmd.exe`, which reports missing commands itself
+#[test]
+#[cfg(unix)]
+fn exec_missing_command() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE)
+        .setup_node_binary("11.10.1", "6.7.0", &node_bin("11.10.1"))
+        .build();
+
+    assert_that!(
+        s.volta("exec -- volta-missing-command"),
+        execs()
+            .with_status(ExitCode::ExecutionFailure as i32)
+            .with_stderr_contains("[..]Could not execute command 'volta-missing-command'")
+    );
+}
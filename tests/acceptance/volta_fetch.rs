@@ -0,0 +1,69 @@
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v9.27.6","npm":"5.6.17","lts": false,"files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]},
+{"version":"v0.0.1","npm":"0.0.2","lts": "Sure","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_VERSION_FIXTURES: [DistroMetadata; 3] = [
+    DistroMetadata {
+        version: "0.0.1",
+        compressed_size: 10,
+        uncompressed_size: Some(0x0028_0000),
+    },
+    DistroMetadata {
+        version: "9.27.6",
+        compressed_size: 272,
+        uncompressed_size: Some(0x0028_0000),
+    },
+    DistroMetadata {
+        version: "10.99.1040",
+        compressed_size: 273,
+        uncompressed_size: Some(0x0028_0000),
+    },
+];
+
+#[test]
+fn fetch_multiple_versions_leaves_default_unchanged() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@9.27.6 node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]fetched node@9.27.6[..]")
+            .with_stdout_contains("[..]fetched node@10.99.1040[..]")
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::new(9, 27, 6)));
+    assert!(s.node_inventory_archive_exists(&Version::new(10, 99, 1040)));
+    assert!(!Sandbox::default_platform_exists());
+}
+
+#[test]
+fn fetch_continues_after_a_failure() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@0.0.1 node@10.99.1040"),
+        execs().with_status(ExitCode::UnknownError as i32)
+    );
+
+    assert!(!s.node_inventory_archive_exists(&Version::new(0, 0, 1)));
+    assert!(s.node_inventory_archive_exists(&Version::new(10, 99, 1040)));
+}
@@ -0,0 +1,102 @@
+// The version managers that can be imported from only run on Unix
+#![cfg(unix)]
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::{matchers::execs, paths};
+use volta_core::error::ExitCode;
+
+const NPM_PACKAGE_JSON: &str = r#"{
+    "name": "npm",
+    "version": "6.2.26"
+}"#;
+
+const TYPESCRIPT_PACKAGE_JSON: &str = r#"{
+    "name": "typescript",
+    "version": "4.9.5"
+}"#;
+
+fn nvm_dir() -> String {
+    paths::home().join(".nvm").to_string_lossy().into_owned()
+}
+
+#[test]
+fn imports_versions_and_default_from_nvm() {
+    let s = sandbox()
+        .env("NVM_DIR", &nvm_dir())
+        .env("VOLTA_LOGLEVEL", "info")
+        .file(".nvm/versions/node/v10.99.1040/bin/node", "")
+        .file(".nvm/versions/node/v10.99.1040/bin/tsc", "")
+        .file(
+            ".nvm/versions/node/v10.99.1040/lib/node_modules/npm/package.json",
+            NPM_PACKAGE_JSON,
+        )
+        .file(
+            ".nvm/versions/node/v10.99.1040/lib/node_modules/typescript/package.json",
+            TYPESCRIPT_PACKAGE_JSON,
+        )
+        .file(".nvm/alias/default", "lts/*\n")
+        .file(".nvm/alias/lts/*", "v10\n")
+        .build();
+
+    assert_that!(
+        s.volta("import nvm"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]imported node@10.99.1040 (with npm@6.2.26)")
+            .with_stdout_contains("[..]set node@10.99.1040 as your default[..]")
+    );
+
+    assert!(Sandbox::path_exists(
+        ".volta/tools/image/node/10.99.1040/bin/node"
+    ));
+    assert!(!Sandbox::path_exists(
+        ".volta/tools/image/node/10.99.1040/bin/tsc"
+    ));
+    assert!(!Sandbox::path_exists(
+        ".volta/tools/image/node/10.99.1040/lib/node_modules/typescript"
+    ));
+    assert!(Sandbox::read_default_platform().contains("10.99.1040"));
+}
+
+#[test]
+fn imports_versions_and_global_version_from_nodenv() {
+    let nodenv_root = paths::home().join(".nodenv");
+    let s = sandbox()
+        .env("NODENV_ROOT", &nodenv_root.to_string_lossy())
+        .env("VOLTA_LOGLEVEL", "info")
+        .file(".nodenv/versions/8.9.10/bin/node", "")
+        .file(
+            ".nodenv/versions/8.9.10/lib/node_modules/npm/package.json",
+            NPM_PACKAGE_JSON,
+        )
+        .file(".nodenv/versions/10.99.1040/bin/node", "")
+        .file(
+            ".nodenv/versions/10.99.1040/lib/node_modules/npm/package.json",
+            NPM_PACKAGE_JSON,
+        )
+        .file(".nodenv/version", "8.9.10\n")
+        .build();
+
+    assert_that!(
+        s.volta("import nodenv"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]imported node@8.9.10 (with npm@6.2.26)")
+            .with_stdout_contains("[..]imported node@10.99.1040 (with npm@6.2.26)")
+            .with_stdout_contains("[..]set node@8.9.10 as your default[..]")
+    );
+}
+
+#[test]
+fn fails_without_nvm_dir() {
+    let s = sandbox().env("NVM_DIR", &nvm_dir()).build();
+
+    assert_that!(
+        s.volta("import nvm"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not find the nvm directory at [..]")
+    );
+}
@@ -0,0 +1,72 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE_YARN: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":null
+    },
+    "yarn":"1.22.4"
+}"#;
+
+const PACKAGE_JSON_NODE: &str = r#"{
+    "name": "text-package",
+    "volta": {
+        "node": "10.99.1040"
+    }
+}"#;
+
+#[test]
+fn describes_default_platform() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_YARN)
+        .setup_node_binary("11.10.1", "6.7.0", "")
+        .build();
+
+    assert_that!(
+        s.volta("inspect"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"  "version": 1,"#)
+            .with_stdout_contains(r#"  "project": null,"#)
+            .with_stdout_contains(r#"      "version": "11.10.1","#)
+            .with_stdout_contains(r#"      "source": "default","#)
+            .with_stdout_contains(r#"      "installed": true,"#)
+            .with_stdout_contains(r#"    "npm": null,"#)
+            .with_stdout_contains(r#"      "version": "1.22.4","#)
+            .with_stdout_contains(r#"      "installed": false,"#)
+    );
+}
+
+#[test]
+fn describes_project_in_directory() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_YARN)
+        .package_json(PACKAGE_JSON_NODE)
+        .build();
+
+    assert_that!(
+        s.volta(&format!("inspect {}", s.root().display())),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"    "root": "[..]","#)
+            .with_stdout_contains(r#"    "manifest": "[..]package.json","#)
+            .with_stdout_contains(r#"      "version": "10.99.1040","#)
+            .with_stdout_contains(r#"      "source": "project","#)
+    );
+}
+
+#[test]
+fn fails_for_missing_directory() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("inspect does-not-exist"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Directory not found: does-not-exist")
+    );
+}
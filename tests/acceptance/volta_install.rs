@@ -3,6 +3,7 @@ use crate::support::sandbox::{
 };
 use hamcrest2::assert_that;
 use hamcrest2::prelude::*;
+use semver::Version;
 use test_support::matchers::execs;
 
 use volta_core::error::ExitCode;
@@ -229,6 +230,25 @@ fn install_node_informs_newer_npm() {
     );
 }
 
+#[test]
+fn install_node_reports_json_progress() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_PROGRESS", "json")
+        .build();
+
+    assert_that!(
+        s.volta("install node@10.99.1040"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stderr_contains(
+                r#"{"event":"start","task":"Fetching node@10.99.1040","total":[..]}"#
+            )
+            .with_stderr_contains(r#"{"event":"finish","task":"Fetching node@10.99.1040"}"#)
+    );
+}
+
 #[test]
 fn install_node_with_npm_hides_bundled_version() {
     let s = sandbox()
@@ -333,3 +353,45 @@ fn install_yarn_3_without_node_errors() {
             )
     );
 }
+
+#[test]
+#[cfg(unix)]
+fn install_node_into_prefix() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let prefix = s.root().join("prefix");
+
+    assert_that!(
+        s.volta(&format!(
+            "install node@10.99.1040 --dest {}",
+            prefix.display()
+        )),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]installed node@10.99.1040 into [..]prefix")
+    );
+
+    assert!(prefix.join("bin").join("node").is_file());
+    assert!(prefix
+        .join("lib")
+        .join("node_modules")
+        .join("npm")
+        .join("package.json")
+        .is_file());
+    assert!(!s.node_inventory_archive_exists(&Version::parse("10.99.1040").unwrap()));
+}
+
+#[test]
+fn install_yarn_into_prefix_errors() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("install yarn@1.12.99 --dest prefix"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Cannot install Yarn into a prefix.")
+    );
+}
@@ -0,0 +1,104 @@
+use std::fs;
+
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "10.99.1040",
+    compressed_size: 273,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+#[test]
+fn export_writes_fetched_versions() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let archive = s.root().join("toolchain.tar.gz");
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta(&format!("inventory export {} node", archive.display())),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]exported node@10.99.1040[..]")
+    );
+
+    assert!(archive.is_file());
+}
+
+#[test]
+fn export_requires_a_fetched_version() {
+    let s = sandbox().build();
+    let archive = s.root().join("toolchain.tar.gz");
+
+    assert_that!(
+        s.volta(&format!("inventory export {} node@12", archive.display())),
+        execs()
+            .with_status(ExitCode::NoVersionMatch as i32)
+            .with_stderr_contains(
+                "[..]Could not find an installed version of node@>=12.0.0 <13.0.0."
+            )
+    );
+
+    assert!(!archive.exists());
+}
+
+#[test]
+fn import_restores_exported_versions() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let archive = s.root().join("toolchain.tar.gz");
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta(&format!("inventory export {}", archive.display())),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    s.remove_volta_home();
+    assert_that!(
+        s.volta(&format!("inventory import {}", archive.display())),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]imported node@10.99.1040[..]")
+    );
+
+    assert!(s.node_inventory_archive_exists(&Version::new(10, 99, 1040)));
+    assert!(Sandbox::path_exists(".volta/tools/image/node/10.99.1040"));
+}
+
+#[test]
+fn import_rejects_an_invalid_archive() {
+    let s = sandbox().build();
+    let archive = s.root().join("toolchain.tar.gz");
+    fs::write(&archive, "not an archive").unwrap();
+
+    assert_that!(
+        s.volta(&format!("inventory import {}", archive.display())),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains("[..]Could not read inventory archive[..]")
+    );
+}
@@ -0,0 +1,63 @@
+// The version managers that can be migrated from only run on Unix
+#![cfg(unix)]
+
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::{matchers::execs, paths};
+use volta_core::error::ExitCode;
+
+const NPM_PACKAGE_JSON: &str = r#"{
+    "name": "npm",
+    "version": "6.2.26"
+}"#;
+
+const PACKAGE_JSON: &str = r#"{
+    "name": "test-package"
+}"#;
+
+fn home_path(path: &str) -> String {
+    paths::home().join(path).to_string_lossy().into_owned()
+}
+
+#[test]
+fn imports_versions_and_pins_from_nvmrc() {
+    let s = sandbox()
+        .env("HOME", &home_path(""))
+        .env("NVM_DIR", &home_path(".nvm"))
+        .env("N_PREFIX", &home_path("n-prefix"))
+        .env("VOLTA_LOGLEVEL", "info")
+        .file(".nvm/versions/node/v10.99.1040/bin/node", "")
+        .file(
+            ".nvm/versions/node/v10.99.1040/lib/node_modules/npm/package.json",
+            NPM_PACKAGE_JSON,
+        )
+        .package_json(PACKAGE_JSON)
+        .project_file(".nvmrc", "v10.99.1040\n")
+        .build();
+
+    assert_that!(
+        s.volta("migrate --yes"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]imported node@10.99.1040 (with npm@6.2.26)")
+            .with_stdout_contains("[..]Migration complete[..]")
+    );
+
+    assert!(Sandbox::path_exists(
+        ".volta/tools/image/node/10.99.1040/bin/node"
+    ));
+    assert!(s.read_package_json().contains(r#""node": "10.99.1040""#));
+}
+
+#[test]
+fn requires_yes_without_terminal() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("migrate"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Use `volta migrate --yes`[..]")
+    );
+}
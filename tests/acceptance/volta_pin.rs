@@ -14,6 +14,11 @@ const PACKAGE_JSON_WITH_EMPTY_LINE: &str = r#"{
   "name": "test-package"
 }
 "#;
+const PACKAGE_JSON_WITH_CUSTOM_FORMAT: &str = r#"{
+    "volta": { "node": "8.9.10" },
+    "name": "test-package",
+    "files": ["index.js", "lib"]
+}"#;
 const PACKAGE_JSON_WITH_EXTENDS: &str = r#"{
   "name": "test-package",
   "volta": {
@@ -808,6 +813,120 @@ fn pin_node_and_yarn() {
     )
 }
 
+#[test]
+fn pin_multiple_tools_is_atomic() {
+    // Yarn can be resolved, but not downloaded, so nothing should be pinned
+    let s = sandbox()
+        .package_json(&package_json_with_pinned_node("1.2.3"))
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6 yarn@1.4"),
+        execs().with_status(ExitCode::NetworkError as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        package_json_with_pinned_node("1.2.3"),
+    )
+}
+
+#[test]
+fn pin_node_writes_to_pin_file() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file("volta.json", "{\n  \"node\": \"8.9.10\"\n}\n")
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_project_file("volta.json"),
+        "{\n  \"node\": \"6.19.62\"\n}\n"
+    );
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON);
+}
+
+#[test]
+fn pin_node_creates_pin_file_from_settings() {
+    let s = sandbox()
+        .package_json(BASIC_PACKAGE_JSON)
+        .project_file(".volta/settings.json", r#"{ "pinLocation": "volta.json" }"#)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_project_file("volta.json"),
+        "{\n  \"node\": \"6.19.62\"\n}\n"
+    );
+    assert_eq!(s.read_package_json(), BASIC_PACKAGE_JSON);
+}
+
+#[test]
+fn pin_workspace_cleans_members() {
+    let s = sandbox()
+        .package_json(
+            r#"{
+  "name": "test-package",
+  "workspaces": ["packages/*"]
+}"#,
+        )
+        .project_file(
+            "packages/b/package.json",
+            r#"{
+  "name": "member-b",
+  "volta": {
+    "node": "8.9.10"
+  }
+}"#,
+        )
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("pin --workspace --clean node@6"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]updated [..] to use the workspace pins")
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        r#"{
+  "name": "test-package",
+  "workspaces": ["packages/*"],
+  "volta": {
+    "node": "6.19.62"
+  }
+}"#
+    );
+    assert_eq!(
+        s.read_project_file("packages/b/package.json"),
+        r#"{
+  "name": "member-b",
+  "volta": {
+    "extends": "../../package.json"
+  }
+}"#
+    );
+}
+
 #[test]
 fn pin_node_does_not_remove_trailing_newline() {
     let s = sandbox()
@@ -842,3 +961,22 @@ fn pin_node_does_not_overwrite_extends() {
         .read_package_json()
         .contains(r#""extends": "./basic.json""#));
 }
+
+#[test]
+fn pin_node_preserves_formatting() {
+    let s = sandbox()
+        .package_json(PACKAGE_JSON_WITH_CUSTOM_FORMAT)
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("pin node@6"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        PACKAGE_JSON_WITH_CUSTOM_FORMAT.replace("8.9.10", "6.19.62")
+    );
+}
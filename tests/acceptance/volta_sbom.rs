@@ -0,0 +1,77 @@
+use crate::support::sandbox::{sandbox, DistroMetadata, Yarn1Fixture};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const YARN_1_VERSION_INFO: &str = r#"{
+    "name":"yarn",
+    "dist-tags": { "latest": "1.2.42" },
+    "versions": {
+        "1.2.42": { "version":"1.2.42", "dist": { "shasum":"", "tarball":"" }}
+    }
+}"#;
+
+const YARN_1_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "1.2.42",
+    compressed_size: 174,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+#[test]
+fn sbom_without_tools() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("sbom"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]\"bomFormat\": \"CycloneDX\",")
+            .with_stdout_contains("[..]\"components\": []")
+    );
+}
+
+#[test]
+fn sbom_lists_installed_runtimes() {
+    let s = sandbox()
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("fetch yarn@1.2.42"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_that!(
+        s.volta("sbom"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]\"purl\": \"pkg:npm/yarn@1.2.42\",")
+            .with_stdout_contains("[..]\"alg\": \"SHA-256\",")
+            .with_stdout_contains("[..]\"url\": \"[..]/yarn/-/yarn-1.2.42.tgz\"")
+    );
+}
+
+#[test]
+fn sbom_in_spdx_format() {
+    let s = sandbox()
+        .yarn_1_available_versions(YARN_1_VERSION_INFO)
+        .distro_mocks::<Yarn1Fixture>(&YARN_1_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("fetch yarn@1.2.42"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_that!(
+        s.volta("sbom --format spdx"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]\"spdxVersion\": \"SPDX-2.3\",")
+            .with_stdout_contains("[..]\"SPDXID\": \"SPDXRef-0-yarn\",")
+            .with_stdout_contains("[..]\"downloadLocation\": \"[..]/yarn/-/yarn-1.2.42.tgz\",")
+    );
+}
@@ -0,0 +1,79 @@
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE_YARN: &str = r#"{
+    "node":{
+        "runtime":"11.10.1",
+        "npm":null
+    },
+    "yarn":"1.22.4"
+}"#;
+
+const HOOKS: &str = r#"{
+    "node": {
+        "index": {
+            "template": "https://nodejs.example.com/index.json"
+        }
+    }
+}"#;
+
+const BUNDLE: &str = r#"{
+    "version": 1,
+    "hooks": {
+        "node": {
+            "index": {
+                "template": "https://nodejs.example.com/index.json"
+            }
+        }
+    },
+    "settings": null,
+    "tools": ["node@11.10.1"]
+}"#;
+
+#[test]
+fn exports_hooks_and_default_toolchain() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_YARN)
+        .default_hooks(HOOKS)
+        .build();
+
+    assert_that!(
+        s.volta("export-settings"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains(r#"  "version": 1,"#)
+            .with_stdout_contains(r#"        "template": "https://nodejs.example.com/index.json""#)
+            .with_stdout_contains(r#"    "node@11.10.1","#)
+            .with_stdout_contains(r#"    "yarn@1.22.4""#)
+    );
+}
+
+#[test]
+fn imports_hooks_without_installing() {
+    let s = sandbox().project_file("bundle.json", BUNDLE).build();
+
+    assert_that!(
+        s.volta("import-settings bundle.json --no-install"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert!(Sandbox::path_exists(".volta/hooks.json"));
+    assert!(!Sandbox::path_exists(".volta/tools/user/platform.json"));
+}
+
+#[test]
+fn rejects_unknown_bundle_versions() {
+    let s = sandbox()
+        .project_file("bundle.json", r#"{ "version": 99 }"#)
+        .build();
+
+    assert_that!(
+        s.volta("import-settings bundle.json"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Could not parse settings bundle")
+    );
+}
@@ -94,6 +94,36 @@ fn uninstall_package_basic() {
     assert!(!Sandbox::package_image_exists("cowsay"));
 }
 
+#[test]
+fn uninstall_package_keeps_bins_from_other_packages() {
+    // `cowthink` was taken over by another package, so uninstalling cowsay must not remove it
+    let other_config =
+        bin_config("cowthink").replace(r#""package": "cowsay""#, r#""package": "cowthink""#);
+    let s = sandbox()
+        .package_config("cowsay", PKG_CONFIG_BASIC)
+        .binary_config("cowsay", &bin_config("cowsay"))
+        .binary_config("cowthink", &other_config)
+        .shim("cowsay")
+        .shim("cowthink")
+        .package_image("cowsay", "1.4.0", None)
+        .env(VOLTA_LOGLEVEL, "info")
+        .build();
+
+    assert_that!(
+        s.volta("uninstall cowsay"),
+        execs()
+            .with_status(0)
+            .with_stdout_contains("Removed executable 'cowsay' installed by 'cowsay'")
+            .with_stdout_does_not_contain("Removed executable 'cowthink'[..]")
+            .with_stdout_contains("[..]package 'cowsay' uninstalled")
+    );
+
+    assert!(!Sandbox::bin_config_exists("cowsay"));
+    assert!(!Sandbox::shim_exists("cowsay"));
+    assert!(Sandbox::bin_config_exists("cowthink"));
+    assert!(Sandbox::shim_exists("cowthink"));
+}
+
 #[test]
 fn uninstall_package_no_bins() {
     // the package doesn't contain any executables, it should uninstall without error
@@ -0,0 +1,82 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const PACKAGE_JSON_WITH_PINS: &str = r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "10.99.1040",
+    "npm": "6.2.26",
+    "yarn": "1.4.159"
+  }
+}"#;
+
+#[test]
+fn unpin_yarn() {
+    let s = sandbox().package_json(PACKAGE_JSON_WITH_PINS).build();
+
+    assert_that!(
+        s.volta("unpin yarn"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        r#"{
+  "name": "test-package",
+  "volta": {
+    "node": "10.99.1040",
+    "npm": "6.2.26"
+  }
+}"#
+    );
+}
+
+#[test]
+fn unpin_node_removes_dependent_pins() {
+    let s = sandbox().package_json(PACKAGE_JSON_WITH_PINS).build();
+
+    assert_that!(
+        s.volta("unpin node"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        r#"{
+  "name": "test-package"
+}"#
+    );
+}
+
+#[test]
+fn unpin_all() {
+    let s = sandbox().package_json(PACKAGE_JSON_WITH_PINS).build();
+
+    assert_that!(
+        s.volta("unpin --all"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+
+    assert_eq!(
+        s.read_package_json(),
+        r#"{
+  "name": "test-package"
+}"#
+    );
+}
+
+#[test]
+fn unpin_package_is_error() {
+    let s = sandbox().package_json(PACKAGE_JSON_WITH_PINS).build();
+
+    assert_that!(
+        s.volta("unpin typescript"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Only node, npm, and yarn can be unpinned from a project")
+    );
+}
@@ -0,0 +1,138 @@
+use crate::support::sandbox::sandbox;
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+const PLATFORM_NODE_ONLY: &str = r#"{
+    "node": {
+        "runtime": "10.99.1040",
+        "npm": null
+    }
+}"#;
+
+fn package_config(name: &str, version: &str, spec: Option<&str>) -> String {
+    let spec = match spec {
+        Some(spec) => format!(r#","spec": "{}""#, spec),
+        None => String::new(),
+    };
+    format!(
+        r#"{{
+  "name": "{}",
+  "version": "{}",
+  "platform": {{
+    "node": "10.99.1040",
+    "npm": null,
+    "yarn": null
+  }},
+  "bins": [],
+  "manager": "Npm"{}
+}}"#,
+        name, version, spec
+    )
+}
+
+// A stand-in for `npm install --global` that always installs cowsay, at the version in the
+// COWSAY_VERSION environment variable
+cfg_if::cfg_if! {
+    if #[cfg(windows)] {
+        const NPM_EXECUTABLE: &str = r#"@echo off
+mkdir "%npm_config_prefix%\node_modules\cowsay"
+echo {"name": "cowsay", "version": "%COWSAY_VERSION%"} > "%npm_config_prefix%\node_modules\cowsay\package.json"
+"#;
+    } else if #[cfg(unix)] {
+        const NPM_EXECUTABLE: &str = r#"#!/bin/bash
+/bin/mkdir -p "$npm_config_prefix/lib/node_modules/cowsay"
+echo "{\"name\": \"cowsay\", \"version\": \"$COWSAY_VERSION\"}" > "$npm_config_prefix/lib/node_modules/cowsay/package.json"
+"#;
+    }
+}
+
+#[test]
+fn upgrade_nonexistent_pkg() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("upgrade cowsay"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Package 'cowsay' is not installed.")
+    );
+}
+
+#[test]
+fn upgrade_self_without_package_manager() {
+    let s = sandbox().env("VOLTA_LOGLEVEL", "info").build();
+
+    // Volta is never replaced directly, so the installer is suggested instead
+    assert_that!(
+        s.volta("upgrade --self"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]To upgrade Volta, [..] the latest installer[..]")
+    );
+}
+
+#[test]
+fn upgrade_to_newer_version() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_ONLY)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_bundled_npm_binary("10.99.1040", NPM_EXECUTABLE)
+        .package_config("cowsay", &package_config("cowsay", "1.4.0", Some("^1")))
+        .package_image("cowsay", "1.4.0", None)
+        .env("COWSAY_VERSION", "1.5.0")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("upgrade cowsay"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]upgraded cowsay from 1.4.0 to 1.5.0")
+    );
+}
+
+#[test]
+fn upgrade_without_spec_keeps_version() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_ONLY)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_bundled_npm_binary("10.99.1040", NPM_EXECUTABLE)
+        .package_config("cowsay", &package_config("cowsay", "1.4.0", None))
+        .package_image("cowsay", "1.4.0", None)
+        .env("COWSAY_VERSION", "1.4.0")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    // Packages that don't record what was requested are reinstalled at the same version
+    assert_that!(
+        s.volta("upgrade cowsay"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]reinstalled cowsay@1.4.0 with the default platform")
+    );
+}
+
+#[test]
+fn upgrade_all_continues_after_failure() {
+    let s = sandbox()
+        .platform(PLATFORM_NODE_ONLY)
+        .setup_node_binary("10.99.1040", "6.2.26", "")
+        .setup_bundled_npm_binary("10.99.1040", NPM_EXECUTABLE)
+        .package_config("bovine", &package_config("bovine", "2.0.0", Some("^2")))
+        .package_config("cowsay", &package_config("cowsay", "1.4.0", Some("^1")))
+        .package_image("cowsay", "1.4.0", None)
+        .env("COWSAY_VERSION", "1.5.0")
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    // Only cowsay can be installed, so upgrading bovine fails first
+    assert_that!(
+        s.volta("upgrade --all"),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stdout_contains("[..]upgraded cowsay from 1.4.0 to 1.5.0")
+            .with_stderr_contains("[..]Could not read package.json manifest for bovine")
+    );
+}
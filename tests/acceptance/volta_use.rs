@@ -0,0 +1,62 @@
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "10.99.1040",
+    compressed_size: 273,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+#[test]
+fn use_prints_statement_for_shell() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .build();
+
+    assert_that!(
+        s.volta("use node@10 --shell bash"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("export VOLTA_USE='node@10.99.1040'")
+    );
+    assert_that!(
+        s.volta("use node@10 --shell fish"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("set -gx VOLTA_USE 'node@10.99.1040'")
+    );
+}
+
+#[test]
+fn use_clear_unsets_the_session() {
+    let s = sandbox().env("VOLTA_USE", "node@10.99.1040").build();
+
+    assert_that!(
+        s.volta("use --clear --shell bash"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("unset VOLTA_USE")
+    );
+}
+
+#[test]
+fn use_rejects_packages() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("use typescript@5"),
+        execs()
+            .with_status(ExitCode::InvalidArguments as i32)
+            .with_stderr_contains("[..]Could not select typescript for this shell session.")
+    );
+}
@@ -0,0 +1,119 @@
+use std::fs;
+
+use crate::support::sandbox::{sandbox, DistroMetadata, NodeFixture};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use semver::Version;
+use test_support::matchers::execs;
+
+use volta_core::error::ExitCode;
+
+const NODE_VERSION_INFO: &str = r#"[
+{"version":"v10.99.1040","npm":"6.2.26","lts": "Dubnium","files":["linux-x64","osx-x64-tar","win-x64-zip","win-x86-zip", "linux-arm64"]}
+]
+"#;
+
+const NODE_VERSION_FIXTURES: [DistroMetadata; 1] = [DistroMetadata {
+    version: "10.99.1040",
+    compressed_size: 273,
+    uncompressed_size: Some(0x0028_0000),
+}];
+
+cfg_if::cfg_if! {
+    if #[cfg(windows)] {
+        const NPM_MANIFEST: &str = "node_modules/npm/package.json";
+    } else {
+        const NPM_MANIFEST: &str = "lib/node_modules/npm/package.json";
+    }
+}
+
+#[test]
+fn verify_passes_for_fetched_versions() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    assert_that!(
+        s.volta("verify"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]All installed versions are intact.")
+    );
+}
+
+#[test]
+fn verify_repairs_changed_archives() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let version = Version::parse("10.99.1040").unwrap();
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    fs::write(s.node_inventory_archive(&version), "damaged").unwrap();
+
+    assert_that!(
+        s.volta("verify"),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains(
+                "[..]node@10.99.1040 was unpacked from an archive that has since changed."
+            )
+    );
+    assert_that!(
+        s.volta("verify --repair"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]repaired node@10.99.1040")
+    );
+    assert_that!(
+        s.volta("verify"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+}
+
+#[test]
+fn verify_finds_changed_files() {
+    let s = sandbox()
+        .node_available_versions(NODE_VERSION_INFO)
+        .distro_mocks::<NodeFixture>(&NODE_VERSION_FIXTURES)
+        .env("VOLTA_LOGLEVEL", "info")
+        .build();
+    let version = Version::parse("10.99.1040").unwrap();
+
+    assert_that!(
+        s.volta("fetch node@10.99.1040"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+    // The image keeps the same number of files, so only the contents show the change
+    fs::write(s.node_image(&version).join(NPM_MANIFEST), "{}").unwrap();
+
+    assert_that!(
+        s.volta("verify"),
+        execs()
+            .with_status(ExitCode::FileSystemError as i32)
+            .with_stderr_contains(
+                "[..]node@10.99.1040 has files that changed since it was installed."
+            )
+    );
+    assert_that!(
+        s.volta("verify --repair"),
+        execs()
+            .with_status(ExitCode::Success as i32)
+            .with_stdout_contains("[..]repaired node@10.99.1040")
+    );
+    assert_that!(
+        s.volta("verify"),
+        execs().with_status(ExitCode::Success as i32)
+    );
+}
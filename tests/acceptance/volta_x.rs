@@ -0,0 +1,18 @@
+use crate::support::sandbox::{sandbox, Sandbox};
+use hamcrest2::assert_that;
+use hamcrest2::prelude::*;
+use test_support::matchers::execs;
+use volta_core::error::ExitCode;
+
+#[test]
+fn exec_without_platform() {
+    let s = sandbox().build();
+
+    assert_that!(
+        s.volta("x cowsay -- hello"),
+        execs()
+            .with_status(ExitCode::ConfigurationError as i32)
+            .with_stderr_contains("[..]Node is not available.")
+    );
+    assert!(!Sandbox::shim_exists("cowsay"));
+}
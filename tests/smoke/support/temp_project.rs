@@ -8,7 +8,7 @@ use envoy;
 use serde_json;
 
 use volta_core::fs::symlink_file;
-use volta_core::tool::{NODE_DISTRO_ARCH, NODE_DISTRO_OS};
+use volta_core::tool::{node_distro_arch, NODE_DISTRO_OS};
 
 use test_support::{self, ok_or_panic, paths, paths::PathExt, process::ProcessBuilder};
 
@@ -253,7 +253,9 @@ fn default_platform_file(root: PathBuf) -> PathBuf {
 pub fn node_distro_file_name(version: &str) -> String {
     format!(
         "node-v{}-{}-{}.tar.gz",
-        version, NODE_DISTRO_OS, NODE_DISTRO_ARCH
+        version,
+        NODE_DISTRO_OS,
+        node_distro_arch()
     )
 }
 fn npm_distro_file_name(version: &str) -> String {